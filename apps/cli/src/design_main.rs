@@ -4,7 +4,10 @@ use std::collections::BTreeMap;
 use std::fs::{self, File};
 use std::io::{BufRead, BufReader, BufWriter, Write};
 
-use agent_core::{HvPolicy, Phase1Config, SoftTraceParams, TraceRunConfig, run_phase1_matrix};
+use agent_core::{
+    HvPolicy, NormalizationConfig, Phase1Config, SoftTraceParams, TraceRunConfig, WarmupConfig,
+    run_phase1_matrix,
+};
 use analysis_tools::{CaseData, compute_correlation};
 use clap::{Parser, Subcommand};
 use design_reasoning::{Phase1Engine, ScsInputs};
@@ -1000,6 +1003,16 @@ fn run_search(depth: usize, beam: usize, seed: u64, hv_guided: bool) -> Result<(
         adaptive_alpha: false,
         hv_guided,
         raw_output_path: None,
+        normalization: NormalizationConfig::default(),
+        warmup: WarmupConfig::default(),
+        calibration: None,
+        convergence: None,
+        field_dimensions: agent_core::DEFAULT_FIELD_DIMENSIONS,
+        novelty: None,
+        repair: None,
+        candidate_pipeline: agent_core::CandidatePipelineConfig::default(),
+        diversity: None,
+        directions: agent_core::ObjectiveDirections::default(),
     };
     let rows = agent_core::generate_trace_baseline_off_soft(cfg, SoftTraceParams::default());
     let last = rows.last().cloned().unwrap_or_default();
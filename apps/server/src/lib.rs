@@ -0,0 +1,430 @@
+//! JSON-RPC 2.0 facade over `HybridVM` for remote design sessions.
+//!
+//! Each session owns a `DesignPipeline` whose VM is opened with
+//! `HybridVM::for_cli_storage(<base_dir>/<session>)`, so reopening a session
+//! name resumes the persisted semantic memory. The facade is transport
+//! agnostic: `DesignServer::handle` maps one request line to one response line.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use agent_core::pipeline::{DesignPipeline, PipelineCheckpoint, PipelineConfig, PipelineStage};
+use hybrid_vm::{ArtifactFormat, HybridVM, L1Id, SemanticError};
+use serde_json::{Value, json};
+
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+/// The `session` parameter does not name an open session.
+pub const SESSION_NOT_FOUND: i64 = -32000;
+/// The VM rejected the call (`SemanticError`).
+pub const SEMANTIC_ERROR: i64 = -32001;
+
+pub const METHODS: [&str; 8] = [
+    "session.open",
+    "session.close",
+    "session.list",
+    "analyze",
+    "draft",
+    "search",
+    "simulate",
+    "artifacts",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+}
+
+impl From<SemanticError> for RpcError {
+    fn from(err: SemanticError) -> Self {
+        Self::new(SEMANTIC_ERROR, err.to_string())
+    }
+}
+
+/// Upper bounds on per-request search effort; larger requests are
+/// rejected with `INVALID_PARAMS`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ServerLimits {
+    pub max_beam_width: usize,
+    pub max_depth: usize,
+}
+
+impl Default for ServerLimits {
+    fn default() -> Self {
+        Self {
+            max_beam_width: 16,
+            max_depth: 12,
+        }
+    }
+}
+
+struct Session {
+    dir: PathBuf,
+    pipeline: DesignPipeline,
+}
+
+/// Session registry plus method dispatch.
+pub struct DesignServer {
+    base_dir: PathBuf,
+    sessions: BTreeMap<String, Session>,
+    next_session: u64,
+    limits: ServerLimits,
+}
+
+impl DesignServer {
+    pub fn new(base_dir: impl AsRef<Path>) -> Self {
+        Self {
+            base_dir: base_dir.as_ref().to_path_buf(),
+            sessions: BTreeMap::new(),
+            next_session: 1,
+            limits: ServerLimits::default(),
+        }
+    }
+
+    pub fn with_limits(mut self, limits: ServerLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    pub fn limits(&self) -> ServerLimits {
+        self.limits
+    }
+
+    pub fn base_dir(&self) -> &Path {
+        &self.base_dir
+    }
+
+    pub fn session_ids(&self) -> Vec<String> {
+        self.sessions.keys().cloned().collect()
+    }
+
+    /// Handles one JSON-RPC request (or batch) and returns the serialized
+    /// response. Notifications (no `id`) yield `None`.
+    pub fn handle(&mut self, line: &str) -> Option<String> {
+        let parsed = match serde_json::from_str::<Value>(line) {
+            Ok(v) => v,
+            Err(err) => {
+                return Some(
+                    error_response(Value::Null, RpcError::new(PARSE_ERROR, err.to_string()))
+                        .to_string(),
+                );
+            }
+        };
+        match parsed {
+            Value::Array(batch) if !batch.is_empty() => {
+                let responses = batch
+                    .into_iter()
+                    .filter_map(|req| self.handle_value(req))
+                    .collect::<Vec<_>>();
+                (!responses.is_empty()).then(|| Value::Array(responses).to_string())
+            }
+            other => self.handle_value(other).map(|v| v.to_string()),
+        }
+    }
+
+    pub fn handle_value(&mut self, request: Value) -> Option<Value> {
+        let Some(obj) = request.as_object() else {
+            return Some(error_response(
+                Value::Null,
+                RpcError::new(INVALID_REQUEST, "request must be an object"),
+            ));
+        };
+        let id = obj.get("id").cloned();
+        let method = match (obj.get("jsonrpc"), obj.get("method")) {
+            (Some(Value::String(v)), Some(Value::String(m))) if v == "2.0" => m.clone(),
+            _ => {
+                return Some(error_response(
+                    id.unwrap_or(Value::Null),
+                    RpcError::new(INVALID_REQUEST, "expected jsonrpc 2.0 request"),
+                ));
+            }
+        };
+        let params = obj.get("params").cloned().unwrap_or(Value::Null);
+        let outcome = self.call(&method, &params);
+        let id = id?;
+        Some(match outcome {
+            Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+            Err(err) => error_response(id, err),
+        })
+    }
+
+    pub fn call(&mut self, method: &str, params: &Value) -> Result<Value, RpcError> {
+        match method {
+            "session.open" => self.open_session(params),
+            "session.close" => {
+                let name = session_param(params)?;
+                let session = self
+                    .sessions
+                    .remove(&name)
+                    .ok_or_else(|| session_not_found(&name))?;
+                Ok(json!({ "session": name, "dir": session.dir }))
+            }
+            "session.list" => Ok(json!({ "sessions": self.session_ids() })),
+            "analyze" => {
+                let text = str_param(params, "text")?;
+                let unit = self
+                    .session_mut(params)?
+                    .pipeline
+                    .vm_mut()
+                    .analyze_text(text)?;
+                Ok(json!({
+                    "concept_id": unit.id.0,
+                    "l1_refs": unit.l1_refs.iter().map(|id| format!("{:032x}", id.0)).collect::<Vec<_>>(),
+                    "abstraction": unit.a,
+                    "polarity": unit.polarity,
+                }))
+            }
+            "draft" => {
+                let drafts = self.session_mut(params)?.pipeline.vm().generate_drafts()?;
+                Ok(json!({
+                    "drafts": drafts
+                        .iter()
+                        .map(|d| json!({
+                            "draft_id": d.draft_id,
+                            "parent_l1": format!("{:032x}", d.parent_l1.0),
+                            "prompt": d.prompt,
+                            "stability_impact": d.stability_impact,
+                            "context_summary": d.context_summary,
+                            "added_units": d.added_units.len(),
+                        }))
+                        .collect::<Vec<_>>()
+                }))
+            }
+            "search" => self.search(params),
+            "simulate" => {
+                let target = l1_param(params, "l1")?;
+                let delta = match params.get("delta") {
+                    None | Some(Value::Null) => -1.0,
+                    Some(v) => v
+                        .as_f64()
+                        .ok_or_else(|| RpcError::invalid_params("delta must be a number"))?
+                        as f32,
+                };
+                let vm = self.session_mut(params)?.pipeline.vm();
+                let report = vm.simulate_perturbation(target, delta)?;
+                let blast = vm.evaluate_blast_radius(&report);
+                Ok(json!({
+                    "original_objectives": to_value(&report.original_objectives)?,
+                    "simulated_objectives": to_value(&report.simulated_objectives)?,
+                    "affected_concepts": report
+                        .affected_concepts
+                        .iter()
+                        .map(|c| json!({
+                            "concept_id": c.concept_id.0,
+                            "original_stability": c.original_stability,
+                            "simulated_stability": c.simulated_stability,
+                        }))
+                        .collect::<Vec<_>>(),
+                    "total_concepts": report.total_concepts,
+                    "blast_radius": {
+                        "coverage": blast.coverage,
+                        "intensity": blast.intensity,
+                        "structural_risk": blast.structural_risk,
+                        "total_score": blast.total_score,
+                    },
+                }))
+            }
+            "artifacts" => {
+                let formats = match params.get("format") {
+                    None | Some(Value::Null) => {
+                        vec![
+                            ArtifactFormat::Rust,
+                            ArtifactFormat::Sql,
+                            ArtifactFormat::Mermaid,
+                            ArtifactFormat::RustTests,
+                        ]
+                    }
+                    Some(v) => {
+                        vec![
+                            serde_json::from_value::<ArtifactFormat>(v.clone()).map_err(|_| {
+                                RpcError::invalid_params(
+                                    "format must be one of Rust, Sql, Mermaid, RustTests",
+                                )
+                            })?,
+                        ]
+                    }
+                };
+                let vm = self.session_mut(params)?.pipeline.vm();
+                let mut out = serde_json::Map::new();
+                for format in formats {
+                    out.insert(
+                        format!("{format:?}"),
+                        to_value(&vm.generate_artifacts(format)?)?,
+                    );
+                }
+                Ok(Value::Object(out))
+            }
+            other => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method: {other}"),
+            )),
+        }
+    }
+
+    fn open_session(&mut self, params: &Value) -> Result<Value, RpcError> {
+        let name = match params.get("session") {
+            None | Some(Value::Null) => loop {
+                let candidate = format!("session-{}", self.next_session);
+                self.next_session += 1;
+                if !self.sessions.contains_key(&candidate) {
+                    break candidate;
+                }
+            },
+            Some(Value::String(name)) => {
+                validate_session_name(name)?;
+                name.clone()
+            }
+            Some(_) => return Err(RpcError::invalid_params("session must be a string")),
+        };
+        if let Some(existing) = self.sessions.get(&name) {
+            return Ok(json!({ "session": name, "dir": existing.dir, "resumed": true }));
+        }
+        let dir = self.base_dir.join(&name);
+        let vm = HybridVM::for_cli_storage(&dir)
+            .map_err(|err| RpcError::new(INTERNAL_ERROR, format!("open storage: {err}")))?;
+        self.sessions.insert(
+            name.clone(),
+            Session {
+                dir: dir.clone(),
+                pipeline: DesignPipeline::new(vm),
+            },
+        );
+        Ok(json!({ "session": name, "dir": dir, "resumed": false }))
+    }
+
+    /// Rebuilds concepts from the session's analyzed text and runs beam
+    /// search over the seeded state, stopping before cards and artifacts.
+    fn search(&mut self, params: &Value) -> Result<Value, RpcError> {
+        let mut config = PipelineConfig {
+            stop_after: Some(PipelineStage::Search),
+            ..PipelineConfig::default()
+        };
+        let limits = self.limits;
+        config.search.beam_width = config.search.beam_width.min(limits.max_beam_width);
+        config.search.max_depth = config.search.max_depth.min(limits.max_depth);
+        if let Some(width) = bounded_usize_param(params, "beam_width", limits.max_beam_width)? {
+            config.search.beam_width = width;
+        }
+        if let Some(depth) = bounded_usize_param(params, "max_depth", limits.max_depth)? {
+            config.search.max_depth = depth;
+        }
+        let pipeline = &mut self.session_mut(params)?.pipeline;
+        *pipeline.config_mut() = config;
+        let report = pipeline
+            .resume_from(PipelineCheckpoint {
+                last_completed: Some(PipelineStage::Analyze),
+                ..PipelineCheckpoint::default()
+            })
+            .map_err(|err| RpcError::new(SEMANTIC_ERROR, err.to_string()))?;
+        Ok(json!({
+            "concepts": report.concepts.len(),
+            "pareto_front": to_value(&report.pareto_front)?,
+            "recommended": to_value(&report.recommended)?,
+            "depth_fronts": to_value(&report.depth_fronts)?,
+            "targets_met_at": report.targets_met_at,
+        }))
+    }
+
+    fn session_mut(&mut self, params: &Value) -> Result<&mut Session, RpcError> {
+        let name = session_param(params)?;
+        self.sessions
+            .get_mut(&name)
+            .ok_or_else(|| session_not_found(&name))
+    }
+}
+
+fn error_response(id: Value, err: RpcError) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "error": { "code": err.code, "message": err.message },
+    })
+}
+
+fn session_not_found(name: &str) -> RpcError {
+    RpcError::new(SESSION_NOT_FOUND, format!("unknown session: {name}"))
+}
+
+/// Session names become directory names, so only `[A-Za-z0-9_-]` is allowed.
+fn validate_session_name(name: &str) -> Result<(), RpcError> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    if valid {
+        Ok(())
+    } else {
+        Err(RpcError::invalid_params(
+            "session name must be 1-64 characters of [A-Za-z0-9_-]",
+        ))
+    }
+}
+
+fn session_param(params: &Value) -> Result<String, RpcError> {
+    str_param(params, "session").map(str::to_string)
+}
+
+fn str_param<'a>(params: &'a Value, key: &str) -> Result<&'a str, RpcError> {
+    params
+        .get(key)
+        .and_then(Value::as_str)
+        .ok_or_else(|| RpcError::invalid_params(format!("missing string param: {key}")))
+}
+
+fn usize_param(params: &Value, key: &str) -> Result<Option<usize>, RpcError> {
+    match params.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => v
+            .as_u64()
+            .and_then(|n| usize::try_from(n).ok())
+            .map(Some)
+            .ok_or_else(|| {
+                RpcError::invalid_params(format!("{key} must be a non-negative integer"))
+            }),
+    }
+}
+
+fn bounded_usize_param(params: &Value, key: &str, max: usize) -> Result<Option<usize>, RpcError> {
+    match usize_param(params, key)? {
+        Some(n) if n > max => Err(RpcError::invalid_params(format!(
+            "{key} must be at most {max}"
+        ))),
+        n => Ok(n),
+    }
+}
+
+/// Accepts an L1 id as a hex string (as returned by `analyze`) or an integer.
+fn l1_param(params: &Value, key: &str) -> Result<L1Id, RpcError> {
+    match params.get(key) {
+        Some(Value::String(hex)) => u128::from_str_radix(hex, 16)
+            .map(L1Id)
+            .map_err(|_| RpcError::invalid_params(format!("{key} must be a hex id"))),
+        Some(Value::Number(n)) => n
+            .as_u64()
+            .map(|n| L1Id(n as u128))
+            .ok_or_else(|| RpcError::invalid_params(format!("{key} must be a non-negative id"))),
+        _ => Err(RpcError::invalid_params(format!("missing param: {key}"))),
+    }
+}
+
+fn to_value<T: serde::Serialize>(value: &T) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|err| RpcError::new(INTERNAL_ERROR, err.to_string()))
+}
@@ -1,3 +1,39 @@
-fn main() {
-    println!("server app placeholder");
+use std::io::{self, BufRead, Write};
+
+use design_server::{DesignServer, ServerLimits};
+
+/// Line-delimited JSON-RPC over stdio. Session storage lives under the first
+/// argument, `DESIGN_SERVER_DIR`, or `./.design_server`. Search limits can be
+/// raised or lowered with `DESIGN_SERVER_MAX_BEAM` and
+/// `DESIGN_SERVER_MAX_DEPTH`.
+fn main() -> io::Result<()> {
+    let base_dir = std::env::args()
+        .nth(1)
+        .or_else(|| std::env::var("DESIGN_SERVER_DIR").ok())
+        .unwrap_or_else(|| ".design_server".to_string());
+    let defaults = ServerLimits::default();
+    let limit = |var: &str, default: usize| {
+        std::env::var(var)
+            .ok()
+            .and_then(|v| v.parse().ok())
+            .unwrap_or(default)
+    };
+    let mut server = DesignServer::new(base_dir).with_limits(ServerLimits {
+        max_beam_width: limit("DESIGN_SERVER_MAX_BEAM", defaults.max_beam_width),
+        max_depth: limit("DESIGN_SERVER_MAX_DEPTH", defaults.max_depth),
+    });
+
+    let stdin = io::stdin();
+    let mut stdout = io::stdout().lock();
+    for line in stdin.lock().lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(response) = server.handle(&line) {
+            writeln!(stdout, "{response}")?;
+            stdout.flush()?;
+        }
+    }
+    Ok(())
 }
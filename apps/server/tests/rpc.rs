@@ -0,0 +1,130 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use design_server::{
+    DesignServer, INVALID_PARAMS, METHOD_NOT_FOUND, PARSE_ERROR, SESSION_NOT_FOUND, ServerLimits,
+};
+use serde_json::{Value, json};
+
+fn temp_server(tag: &str) -> DesignServer {
+    DesignServer::new(std::env::temp_dir().join(format!(
+        "design_server_{tag}_{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos()
+    )))
+}
+
+fn rpc(server: &mut DesignServer, id: u64, method: &str, params: Value) -> Value {
+    let request = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+    let response = server.handle(&request.to_string()).expect("response");
+    serde_json::from_str(&response).expect("json response")
+}
+
+#[test]
+fn session_round_trip_covers_every_endpoint() {
+    let mut server = temp_server("endpoints");
+    let opened = rpc(
+        &mut server,
+        1,
+        "session.open",
+        json!({ "session": "alpha" }),
+    );
+    assert_eq!(opened["result"]["session"], "alpha");
+    assert!(server.base_dir().join("alpha").is_dir());
+
+    let analyzed = rpc(
+        &mut server,
+        2,
+        "analyze",
+        json!({ "session": "alpha", "text": "高速化を重視する。セキュリティを確保する。" }),
+    );
+    let l1 = analyzed["result"]["l1_refs"][0].clone();
+    assert!(l1.is_string(), "{analyzed}");
+
+    let drafts = rpc(&mut server, 3, "draft", json!({ "session": "alpha" }));
+    assert!(drafts["result"]["drafts"].is_array(), "{drafts}");
+
+    let searched = rpc(
+        &mut server,
+        4,
+        "search",
+        json!({ "session": "alpha", "beam_width": 2, "max_depth": 2 }),
+    );
+    assert!(
+        !searched["result"]["pareto_front"]
+            .as_array()
+            .expect("front")
+            .is_empty(),
+        "{searched}"
+    );
+
+    let simulated = rpc(
+        &mut server,
+        5,
+        "simulate",
+        json!({ "session": "alpha", "l1": l1 }),
+    );
+    assert!(
+        simulated["result"]["blast_radius"]["total_score"].is_number(),
+        "{simulated}"
+    );
+
+    let artifacts = rpc(
+        &mut server,
+        6,
+        "artifacts",
+        json!({ "session": "alpha", "format": "Mermaid" }),
+    );
+    assert!(artifacts["result"]["Mermaid"].is_array(), "{artifacts}");
+
+    let closed = rpc(
+        &mut server,
+        7,
+        "session.close",
+        json!({ "session": "alpha" }),
+    );
+    assert_eq!(closed["result"]["session"], "alpha");
+    assert!(server.session_ids().is_empty());
+}
+
+#[test]
+fn errors_use_json_rpc_codes() {
+    let mut server = temp_server("errors");
+    let parse: Value =
+        serde_json::from_str(&server.handle("{not json").expect("response")).expect("json");
+    assert_eq!(parse["error"]["code"], PARSE_ERROR);
+
+    let unknown = rpc(&mut server, 1, "nope", Value::Null);
+    assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+
+    let missing = rpc(&mut server, 2, "draft", json!({ "session": "ghost" }));
+    assert_eq!(missing["error"]["code"], SESSION_NOT_FOUND);
+
+    let bad_name = rpc(
+        &mut server,
+        3,
+        "session.open",
+        json!({ "session": "../escape" }),
+    );
+    assert_eq!(bad_name["error"]["code"], INVALID_PARAMS);
+
+    let notification = json!({ "jsonrpc": "2.0", "method": "session.list" });
+    assert!(server.handle(&notification.to_string()).is_none());
+}
+
+#[test]
+fn search_effort_above_the_limits_is_rejected() {
+    let mut server = temp_server("limits").with_limits(ServerLimits {
+        max_beam_width: 2,
+        max_depth: 2,
+    });
+    rpc(&mut server, 1, "session.open", json!({ "session": "beta" }));
+    for params in [
+        json!({ "session": "beta", "beam_width": 3 }),
+        json!({ "session": "beta", "max_depth": 1000000 }),
+    ] {
+        let rejected = rpc(&mut server, 2, "search", params);
+        assert_eq!(rejected["error"]["code"], INVALID_PARAMS, "{rejected}");
+    }
+}
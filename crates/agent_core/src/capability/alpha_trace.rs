@@ -0,0 +1,320 @@
+//! Per-depth decisions of the adaptive alpha controller, and an offline
+//! replay of them.
+//!
+//! Alpha, the weight of weak dimensions in normalized distances, goes
+//! through a base value, feedback, smoothing, a deadband, the safety valve
+//! and a final clamp, and is held outright below `min_effective_dim`. A
+//! trace run with `adaptive_alpha` keeps one `AlphaDecision` per depth
+//! recording which of those steps decided the outcome.
+//!
+//! `AlphaTrace::replay` feeds the recorded inputs through another
+//! `AlphaConfig`. The inputs are replayed as recorded, so it shows what the
+//! controller would have chosen, not how the search would have responded.
+
+use crate::GlobalRobustStats;
+
+/// Controller constants; the default is what trace runs use.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlphaConfig {
+    pub alpha_min: f64,
+    pub alpha_max: f64,
+    /// Weak-dimension share at which the base alpha starts rising from
+    /// `alpha_min`.
+    pub r0: f64,
+    /// Weak-dimension share at which the base alpha reaches `alpha_max`.
+    pub r1: f64,
+    /// Feedback gain on the relative nearest-neighbour distance error.
+    pub k: f64,
+    /// Weight of the feedback value against the previous alpha.
+    pub beta: f64,
+    /// Largest share of the distance weight weak dimensions may carry.
+    pub rho_max: f64,
+    /// Deadband half-width as a fraction of `d_target`.
+    pub deadband: f64,
+    /// Below this many effective dimensions alpha is held.
+    pub min_effective_dim: usize,
+}
+
+impl Default for AlphaConfig {
+    fn default() -> Self {
+        Self {
+            alpha_min: 0.01,
+            alpha_max: 0.20,
+            r0: 0.25,
+            r1: 0.75,
+            k: 0.05,
+            beta: 0.2,
+            rho_max: 0.35,
+            deadband: 0.1,
+            min_effective_dim: 3,
+        }
+    }
+}
+
+/// What the controller saw at one depth.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlphaInputs {
+    /// Alpha the depth ran with (`TraceRow::alpha_t`).
+    pub alpha: f64,
+    /// Active dimensions that are not weak.
+    pub strong_dims: usize,
+    pub weak_dims: usize,
+    /// Normalized mean nearest-neighbour distance on the front.
+    pub mean_nn_dist: f64,
+    pub pareto_size: usize,
+    pub d_target: f64,
+    pub effective_dim: usize,
+}
+
+impl AlphaInputs {
+    pub fn new(
+        alpha: f64,
+        stats: &GlobalRobustStats,
+        mean_nn_dist: f64,
+        pareto_size: usize,
+        d_target: f64,
+        effective_dim: usize,
+    ) -> Self {
+        let strong_dims = stats
+            .active_dims
+            .iter()
+            .zip(stats.weak_dims.iter())
+            .filter(|&(&a, &w)| a && !w)
+            .count();
+        Self {
+            alpha,
+            strong_dims,
+            weak_dims: stats.weak_dims.iter().filter(|&&w| w).count(),
+            mean_nn_dist,
+            pareto_size,
+            d_target,
+            effective_dim,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AlphaBranch {
+    /// Statistics still warming up; alpha held.
+    Warmup,
+    /// Fewer than `min_effective_dim` effective dimensions; alpha held.
+    LowDimension,
+    /// Distance inside the deadband; the previous alpha goes on to the
+    /// safety valve and clamp instead of the smoothed target.
+    Deadband,
+    /// The smoothed feedback target goes on to the safety valve and clamp.
+    Feedback,
+}
+
+impl AlphaBranch {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Warmup => "warmup",
+            Self::LowDimension => "low_dimension",
+            Self::Deadband => "deadband",
+            Self::Feedback => "feedback",
+        }
+    }
+
+    /// Alpha was carried over without running the controller.
+    pub fn holds(self) -> bool {
+        matches!(self, Self::Warmup | Self::LowDimension)
+    }
+}
+
+/// Intermediate values of a depth where the controller ran.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlphaSteps {
+    /// From the weak-dimension share alone.
+    pub base: f64,
+    /// `base` corrected by the distance error.
+    pub feedback: f64,
+    /// `feedback` smoothed against the previous alpha.
+    pub target: f64,
+    /// Safety-valve ceiling; `None` without weak dimensions.
+    pub safety_cap: Option<f64>,
+    /// The ceiling lowered alpha.
+    pub safety_valve: bool,
+    /// Before the final clamp into `alpha_min..=alpha_max`.
+    pub unclamped: f64,
+    pub clamped: bool,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlphaDecision {
+    pub depth: usize,
+    pub inputs: AlphaInputs,
+    pub branch: AlphaBranch,
+    /// `None` when the branch holds alpha.
+    pub steps: Option<AlphaSteps>,
+    /// Alpha the next depth runs with.
+    pub alpha: f64,
+}
+
+/// Runs the controller once. `depth` only labels the decision.
+pub fn decide_alpha(config: &AlphaConfig, depth: usize, inputs: AlphaInputs) -> AlphaDecision {
+    let held = |branch| AlphaDecision {
+        depth,
+        inputs,
+        branch,
+        steps: None,
+        alpha: inputs.alpha,
+    };
+    if inputs.effective_dim < config.min_effective_dim {
+        return held(AlphaBranch::LowDimension);
+    }
+
+    let s_count = inputs.strong_dims as f64;
+    let w_count = inputs.weak_dims as f64;
+    let e_count = s_count + w_count;
+
+    let r = w_count / e_count.max(1.0);
+    let ratio_factor = ((r - config.r0) / (config.r1 - config.r0)).clamp(0.0, 1.0);
+    let base = config.alpha_min + (config.alpha_max - config.alpha_min) * ratio_factor;
+
+    // Below the target distance alpha rises to spread the front; above it
+    // alpha may fall.
+    let error = ((inputs.d_target - inputs.mean_nn_dist) / inputs.d_target).clamp(-1.0, 1.0);
+    let feedback = base + config.k * error;
+    let target = (1.0 - config.beta) * inputs.alpha + config.beta * feedback;
+
+    // Inside the deadband the previous alpha is kept to prevent chattering.
+    let deadband = (inputs.mean_nn_dist - inputs.d_target).abs()
+        < config.deadband * inputs.d_target
+        && inputs.pareto_size > 1;
+    let (branch, mut next) = if deadband {
+        (AlphaBranch::Deadband, inputs.alpha)
+    } else {
+        (AlphaBranch::Feedback, target)
+    };
+
+    // alpha * W / (S + alpha * W) <= rho_max
+    // alpha <= (rho_max * S) / (W * (1 - rho_max))
+    let safety_cap =
+        (w_count > 0.0).then(|| (config.rho_max * s_count) / (w_count * (1.0 - config.rho_max)));
+    let safety_valve = safety_cap.is_some_and(|cap| cap < next);
+    if let Some(cap) = safety_cap {
+        next = next.min(cap);
+    }
+
+    let unclamped = next;
+    let alpha = unclamped.clamp(config.alpha_min, config.alpha_max);
+    AlphaDecision {
+        depth,
+        inputs,
+        branch,
+        steps: Some(AlphaSteps {
+            base,
+            feedback,
+            target,
+            safety_cap,
+            safety_valve,
+            unclamped,
+            clamped: alpha != unclamped,
+        }),
+        alpha,
+    }
+}
+
+/// Every decision of one run, in depth order.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AlphaTrace {
+    pub config: AlphaConfig,
+    pub initial_alpha: f64,
+    pub decisions: Vec<AlphaDecision>,
+}
+
+#[cfg(feature = "serde")]
+impl core_types::SchemaVersioned for AlphaTrace {
+    const KIND: &'static str = "alpha_trace";
+    const VERSION: u32 = 1;
+}
+
+impl AlphaTrace {
+    pub fn new(config: AlphaConfig, initial_alpha: f64) -> Self {
+        Self {
+            config,
+            initial_alpha,
+            decisions: Vec::new(),
+        }
+    }
+
+    /// Alpha the next depth runs with.
+    pub fn alpha(&self) -> f64 {
+        self.decisions
+            .last()
+            .map_or(self.initial_alpha, |decision| decision.alpha)
+    }
+
+    /// Decides `depth` from `inputs`, or holds alpha during the warm-up.
+    pub fn record(&mut self, depth: usize, inputs: AlphaInputs, warmup: bool) -> &AlphaDecision {
+        let decision = if warmup {
+            AlphaDecision {
+                depth,
+                inputs,
+                branch: AlphaBranch::Warmup,
+                steps: None,
+                alpha: inputs.alpha,
+            }
+        } else {
+            decide_alpha(&self.config, depth, inputs)
+        };
+        self.decisions.push(decision);
+        self.decisions.last().expect("just pushed")
+    }
+
+    /// The recorded run's inputs decided under `config`, starting from
+    /// `initial_alpha`. Warm-up depths stay warm-up depths; each depth's
+    /// input alpha is the replayed one.
+    pub fn replay(&self, config: AlphaConfig, initial_alpha: f64) -> Self {
+        let mut replayed = Self::new(config, initial_alpha);
+        for decision in &self.decisions {
+            let inputs = AlphaInputs {
+                alpha: replayed.alpha(),
+                ..decision.inputs
+            };
+            replayed.record(
+                decision.depth,
+                inputs,
+                decision.branch == AlphaBranch::Warmup,
+            );
+        }
+        replayed
+    }
+
+    pub fn branch_count(&self, branch: AlphaBranch) -> usize {
+        self.decisions.iter().filter(|d| d.branch == branch).count()
+    }
+
+    /// Depths where the safety valve lowered alpha.
+    pub fn safety_valve_depths(&self) -> Vec<usize> {
+        self.decisions
+            .iter()
+            .filter(|d| d.steps.is_some_and(|s| s.safety_valve))
+            .map(|d| d.depth)
+            .collect()
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn load(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let raw = std::fs::read_to_string(path)?;
+        let envelope: core_types::Versioned<Self> = serde_json::from_str(&raw)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        envelope
+            .into_checked()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(&core_types::Versioned::new(self.clone()))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, json)
+    }
+}
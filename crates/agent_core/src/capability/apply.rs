@@ -1,12 +1,46 @@
 use std::collections::BTreeMap;
 use std::sync::Arc;
 
-use hybrid_vm::{DesignRule, EffectVector, RuleCategory, RuleId, Transformation};
-use memory_space::{DesignNode, DesignState, StateId, StructuralGraph, Uuid, Value};
+use hybrid_vm::{
+    CONSTRAINT_ATTRIBUTE_PREFIX, DesignRule, EffectVector, Precondition, RuleCategory, RuleId,
+    Transformation,
+};
+use memory_space::{
+    DesignNode, DesignState, GraphViolation, StateId, StructuralGraph, Uuid, Value,
+};
 
 use crate::MacroOperator;
 
-pub fn apply_atomic(rule: &DesignRule, state: &DesignState) -> DesignState {
+/// Prefix of the marker attribute `apply_atomic` puts on a node added by a
+/// rule, followed by the rule id.
+pub const GENERATED_ATTRIBUTE_PREFIX: &str = "generated_by_";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ApplyError {
+    /// The transformed graph failed `StructuralGraph::validate`; no child
+    /// state was produced. Only checked in debug builds.
+    InvalidResult(Vec<GraphViolation>),
+}
+
+impl std::fmt::Display for ApplyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidResult(violations) => {
+                write!(f, "rule produced an invalid graph:")?;
+                for violation in violations {
+                    write!(f, " {violation};")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ApplyError {}
+
+/// Debug builds validate the resulting graph and reject the child on a
+/// violation; release builds skip the scan and always produce it.
+pub fn apply_atomic(rule: &DesignRule, state: &DesignState) -> Result<DesignState, ApplyError> {
     let graph = &state.graph;
     let next_graph = match rule.transformation {
         Transformation::AddNode => apply_add_node(graph, rule),
@@ -15,6 +49,8 @@ pub fn apply_atomic(rule: &DesignRule, state: &DesignState) -> DesignState {
         Transformation::AddConstraint => apply_add_constraint(graph, rule),
         Transformation::RewireDependency => apply_rewire_dependency(graph),
     };
+    #[cfg(debug_assertions)]
+    next_graph.validate().map_err(ApplyError::InvalidResult)?;
 
     let next_snapshot = append_rule_history(&state.profile_snapshot, rule.id);
     let next_id = deterministic_state_id(
@@ -25,17 +61,23 @@ pub fn apply_atomic(rule: &DesignRule, state: &DesignState) -> DesignState {
         next_graph.edges().len(),
     );
 
-    DesignState::new(next_id, Arc::new(next_graph), next_snapshot)
+    Ok(DesignState::new(
+        next_id,
+        Arc::new(next_graph),
+        next_snapshot,
+    ))
 }
 
-pub fn apply_macro(op: &MacroOperator, state: &DesignState) -> DesignState {
+/// Applies the macro's steps in order, stopping at the first step that
+/// would break a graph invariant.
+pub fn apply_macro(op: &MacroOperator, state: &DesignState) -> Result<DesignState, ApplyError> {
     let mut current = state.clone();
     for (idx, step) in op.steps.iter().take(op.max_activations).enumerate() {
         let rule = DesignRule {
             id: deterministic_uuid(op.id.as_u128(), idx as u128 + 1, 0xAA),
             category: RuleCategory::Refactor,
             priority: 0.5,
-            precondition: |_| true,
+            precondition: Precondition::Always,
             transformation: step.clone(),
             expected_effect: EffectVector {
                 delta_struct: 0.0,
@@ -44,9 +86,9 @@ pub fn apply_macro(op: &MacroOperator, state: &DesignState) -> DesignState {
                 delta_cost: 0.0,
             },
         };
-        current = apply_atomic(&rule, &current);
+        current = apply_atomic(&rule, &current)?;
     }
-    current
+    Ok(current)
 }
 
 fn apply_add_node(graph: &StructuralGraph, rule: &DesignRule) -> StructuralGraph {
@@ -55,7 +97,7 @@ fn apply_add_node(graph: &StructuralGraph, rule: &DesignRule) -> StructuralGraph
 
     let mut attrs = BTreeMap::new();
     attrs.insert(
-        format!("generated_by_{}", rule.id.as_u128()),
+        format!("{GENERATED_ATTRIBUTE_PREFIX}{}", rule.id.as_u128()),
         Value::Bool(true),
     );
     let node = DesignNode::new(node_id, "GeneratedNode", attrs);
@@ -83,21 +125,21 @@ fn apply_modify_attribute(graph: &StructuralGraph, rule: &DesignRule) -> Structu
 
 fn apply_add_constraint(graph: &StructuralGraph, rule: &DesignRule) -> StructuralGraph {
     let ids = sorted_node_ids(graph);
+    let Some(&anchor) = ids.first() else {
+        return graph.clone();
+    };
+    let mut nodes = graph.nodes().clone();
+    if let Some(node) = nodes.get_mut(&anchor) {
+        node.attributes.insert(
+            format!("{CONSTRAINT_ATTRIBUTE_PREFIX}{}", rule.id.as_u128()),
+            Value::Bool(true),
+        );
+    }
+    let next = StructuralGraph::new(nodes, graph.edges().clone());
     if ids.len() >= 2 {
-        let from = ids[0];
-        let to = ids[1];
-        if graph
-            .edges()
-            .iter()
-            .any(|edge| edge.0 == from && edge.1 == to)
-        {
-            graph.clone()
-        } else {
-            let _ = rule;
-            graph.with_edge_added(from, to)
-        }
+        next.with_edge_added(ids[0], ids[1])
     } else {
-        graph.clone()
+        next
     }
 }
 
@@ -116,7 +158,7 @@ fn sorted_node_ids(graph: &StructuralGraph) -> Vec<Uuid> {
     ids
 }
 
-fn append_rule_history(snapshot: &str, rule_id: RuleId) -> String {
+pub(crate) fn append_rule_history(snapshot: &str, rule_id: RuleId) -> String {
     let mut history = parse_rule_history(snapshot);
     history.push(rule_id);
     let serialized = history
@@ -127,7 +169,7 @@ fn append_rule_history(snapshot: &str, rule_id: RuleId) -> String {
     format!("history:{serialized}")
 }
 
-fn parse_rule_history(snapshot: &str) -> Vec<RuleId> {
+pub(crate) fn parse_rule_history(snapshot: &str) -> Vec<RuleId> {
     snapshot
         .strip_prefix("history:")
         .unwrap_or("")
@@ -155,7 +197,7 @@ fn deterministic_state_id(
     Uuid::from_u128(acc)
 }
 
-fn deterministic_uuid(a: u128, b: u128, salt: u128) -> Uuid {
+pub(crate) fn deterministic_uuid(a: u128, b: u128, salt: u128) -> Uuid {
     let mut acc = 0x9e3779b97f4a7c15u128;
     acc = fnv_mix_u128(acc, a);
     acc = fnv_mix_u128(acc, b);
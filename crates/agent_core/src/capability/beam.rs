@@ -1,8 +1,31 @@
+use std::collections::{BTreeMap, BTreeSet};
+
 use core_types::ObjectiveVector;
-use hybrid_vm::HybridVM;
-use memory_space::DesignState;
+use core_types::progress::{ProgressSink, ProgressTracker};
+use hybrid_vm::{HybridVM, ObjectiveAttribution, RuleId};
+use memory_space::{DesignState, StateId};
 
-use crate::{BeamSearch, DepthFront, SOFT_PARETO_TEMPERATURE, SearchMode, SearchResult};
+use crate::capability::budget::{BudgetHandle, BudgetStats};
+use crate::capability::crossover::{CrossoverStats, recombine};
+use crate::capability::dispatch::{DispatchStats, EvaluationDispatcher, EvaluationJob};
+use crate::capability::evaluation::{evaluate_child_with_policy, evaluate_with_policy};
+use crate::capability::invariants::{
+    InvariantViolation, check_constrained_order, check_norm_alpha, check_normalized,
+    check_soft_front_order, check_unique_ids,
+};
+use crate::capability::sanitize::{QuarantinedCandidate, quarantine};
+use crate::capability::search_tree::{SearchTree, SearchTreeNode};
+use crate::capability::selection::{epsilon_constraint_rank, lexicographic_rank, soft_front_rank};
+use crate::capability::state_archive::{StateArchive, StateOrigin};
+use crate::capability::suggestion::{
+    RuleSuggester, SuggestedRule, SuggestionConfig, admit_suggestions,
+};
+use crate::{
+    BeamSearch, DepthFront, DepthNormalizer, EpsilonConstraint, EvaluationPolicy,
+    GlobalRobustStats, LexicographicOrder, LexicographicTieStats, ManualChoice,
+    ObjectiveDirections, SOFT_PARETO_TEMPERATURE, SearchMode, SearchResult, lower_confidence_bound,
+    noise_epsilon,
+};
 
 impl<'a> BeamSearch<'a> {
     pub fn search(&self, initial_state: &DesignState) -> Vec<DesignState> {
@@ -11,56 +34,805 @@ impl<'a> BeamSearch<'a> {
     }
 
     pub fn search_with_mode(&self, initial_state: &DesignState, mode: SearchMode) -> SearchResult {
+        self.search_with(initial_state, mode, SearchHooks::default())
+    }
+
+    /// `search_with_mode` with candidates scored through `dispatcher`
+    /// instead of `self.evaluator`; the initial state still uses the latter.
+    pub fn search_dispatched(
+        &self,
+        initial_state: &DesignState,
+        mode: SearchMode,
+        dispatcher: &EvaluationDispatcher,
+    ) -> SearchResult {
+        let hooks = SearchHooks {
+            dispatcher: Some(dispatcher),
+            ..SearchHooks::default()
+        };
+        self.search_with(initial_state, mode, hooks)
+    }
+
+    /// `search_with_mode` drawing candidate evaluations from a shared pool;
+    /// stops early once the pool has nothing left for `budget`.
+    pub fn search_budgeted(
+        &self,
+        initial_state: &DesignState,
+        mode: SearchMode,
+        budget: BudgetHandle<'_>,
+    ) -> SearchResult {
+        let hooks = SearchHooks {
+            budget: Some(budget),
+            ..SearchHooks::default()
+        };
+        self.search_with(initial_state, mode, hooks)
+    }
+
+    /// `search_with_mode` with `suggester`'s rules vetted and added to each
+    /// state's rule-set children.
+    pub fn search_with_suggester(
+        &self,
+        initial_state: &DesignState,
+        mode: SearchMode,
+        suggester: &dyn RuleSuggester,
+        config: SuggestionConfig,
+    ) -> SearchResult {
+        let hooks = SearchHooks {
+            suggester: Some((suggester, config)),
+            ..SearchHooks::default()
+        };
+        self.search_with(initial_state, mode, hooks)
+    }
+
+    /// `search_with_mode` recording every accepted state, the initial one
+    /// included, into `archive`; returned in `SearchResult::archive`.
+    pub fn search_archived(
+        &self,
+        initial_state: &DesignState,
+        mode: SearchMode,
+        archive: StateArchive,
+    ) -> SearchResult {
+        let hooks = SearchHooks {
+            archive: Some(archive),
+            ..SearchHooks::default()
+        };
+        self.search_with(initial_state, mode, hooks)
+    }
+
+    /// `search_with_mode` reporting a `beam_search` phase to `progress`, one
+    /// item per depth out of `max_depth`. A search that stops early still
+    /// reports completion.
+    pub fn search_with_progress(
+        &self,
+        initial_state: &DesignState,
+        mode: SearchMode,
+        progress: &dyn ProgressSink,
+    ) -> SearchResult {
+        let hooks = SearchHooks {
+            progress: Some(progress),
+            ..SearchHooks::default()
+        };
+        self.search_with(initial_state, mode, hooks)
+    }
+
+    /// Runs the search to completion with any combination of `hooks`. A
+    /// zero beam width or depth returns the initial state unevaluated.
+    pub fn search_with(
+        &self,
+        initial_state: &DesignState,
+        mode: SearchMode,
+        hooks: SearchHooks<'_>,
+    ) -> SearchResult {
+        let mut tracker = hooks.progress.map(|progress| {
+            ProgressTracker::start(progress, "beam_search", self.config.max_depth as u64)
+        });
         if self.config.beam_width == 0 || self.config.max_depth == 0 {
+            let archive = hooks.archive.map(|mut archive| {
+                archive.record(initial_state, 0, StateOrigin::default());
+                archive
+            });
+            if let Some(tracker) = tracker {
+                tracker.finish();
+            }
             return SearchResult {
                 final_frontier: vec![initial_state.clone()],
                 depth_fronts: vec![DepthFront {
                     depth: 0,
                     state_ids: vec![initial_state.id],
                 }],
+                targets_met_at: None,
+                objective_variance: BTreeMap::new(),
+                search_tree: None,
+                crossover: CrossoverStats::default(),
+                manual_choices: Vec::new(),
+                dispatch: DispatchStats::default(),
+                attributions: BTreeMap::new(),
+                suggestions: Vec::new(),
+                budget: BudgetStats::default(),
+                archive,
+                quarantined: Vec::new(),
+                lexicographic_ties: Vec::new(),
+                invariant_violations: Vec::new(),
+            };
+        }
+
+        let mut run = self.start(initial_state);
+        if let Some(dispatcher) = hooks.dispatcher {
+            run = run.with_dispatcher(dispatcher);
+        }
+        if let Some(budget) = hooks.budget {
+            run = run.with_budget(budget);
+        }
+        if let Some((suggester, config)) = hooks.suggester {
+            run = run.with_suggester(suggester, config);
+        }
+        if let Some(archive) = hooks.archive {
+            run = run.with_archive(archive);
+        }
+        while run.step() {
+            if let Some(tracker) = tracker.as_mut() {
+                tracker.advance(1);
+            }
+        }
+        if let Some(tracker) = tracker {
+            tracker.finish();
+        }
+        run.finish(mode)
+    }
+
+    /// Begins an incremental search; drive it with `AnytimeSearch::step`.
+    pub fn start<'s>(&'s self, initial_state: &DesignState) -> AnytimeSearch<'s, 'a> {
+        let objective =
+            evaluate_with_policy(self.evaluator, initial_state, self.config.evaluation).objective;
+        let mut tree = self
+            .config
+            .record_tree
+            .then(|| SearchTree::new(self.config.max_tree_nodes));
+        if let Some(tree) = tree.as_mut() {
+            tree.record(SearchTreeNode {
+                state_id: initial_state.id,
+                parent: None,
+                rule_id: None,
+                depth: 0,
+                objective: objective.clone(),
+                kept: true,
+            });
+        }
+        let targets_met_at = self
+            .config
+            .targets
+            .filter(|t| t.is_met(&objective))
+            .map(|_| 0);
+        let mut attributions = BTreeMap::new();
+        if self.config.explain {
+            record_attribution(&mut attributions, self, initial_state, &objective);
+        }
+        // A non-finite initial state is still expanded, just never ranked.
+        let quarantined = quarantine(0, initial_state.id, None, &objective)
+            .into_iter()
+            .collect::<Vec<_>>();
+        let best = if quarantined.is_empty() {
+            vec![(initial_state.clone(), objective)]
+        } else {
+            Vec::new()
+        };
+        AnytimeSearch {
+            search: self,
+            depth: 0,
+            frontier: vec![initial_state.clone()],
+            best,
+            all_depths: Vec::new(),
+            targets_met_at,
+            objective_variance: BTreeMap::new(),
+            tree,
+            crossover: CrossoverStats::default(),
+            normalizer: DepthNormalizer::new(self.config.normalization, self.config.warmup),
+            exhausted: false,
+            manual_choices: Vec::new(),
+            dispatcher: None,
+            dispatch: DispatchStats::default(),
+            attributions,
+            suggester: None,
+            suggestions: Vec::new(),
+            budget: None,
+            budget_stats: BudgetStats::default(),
+            archive: None,
+            quarantined,
+            lexicographic_ties: Vec::new(),
+            invariant_violations: Vec::new(),
+        }
+    }
+}
+
+/// Optional collaborators for `BeamSearch::search_with`; the default runs
+/// a plain `search_with_mode`.
+#[derive(Default)]
+pub struct SearchHooks<'s> {
+    /// Scores candidates; see `BeamSearch::search_dispatched`.
+    pub dispatcher: Option<&'s EvaluationDispatcher>,
+    /// Shared evaluation pool; see `BeamSearch::search_budgeted`.
+    pub budget: Option<BudgetHandle<'s>>,
+    /// Extra rules per state; see `BeamSearch::search_with_suggester`.
+    pub suggester: Option<(&'s dyn RuleSuggester, SuggestionConfig)>,
+    /// Records accepted states; see `BeamSearch::search_archived`.
+    pub archive: Option<StateArchive>,
+    /// Receives one item per depth; see `BeamSearch::search_with_progress`.
+    pub progress: Option<&'s dyn ProgressSink>,
+}
+
+/// A beam search advanced one depth at a time. Between steps the caller can
+/// inspect `best_front()` and simply drop the run once satisfied.
+pub struct AnytimeSearch<'s, 'a> {
+    pub(super) search: &'s BeamSearch<'a>,
+    pub(super) depth: usize,
+    pub(super) frontier: Vec<DesignState>,
+    /// Non-dominated (or, under an epsilon constraint or lexicographic
+    /// order, best) states over every frontier seen so far.
+    best: Vec<(DesignState, ObjectiveVector)>,
+    all_depths: Vec<DepthFront>,
+    targets_met_at: Option<usize>,
+    objective_variance: BTreeMap<StateId, ObjectiveVector>,
+    pub(super) tree: Option<SearchTree>,
+    crossover: CrossoverStats,
+    normalizer: DepthNormalizer,
+    exhausted: bool,
+    pub(super) manual_choices: Vec<ManualChoice>,
+    dispatcher: Option<&'s EvaluationDispatcher>,
+    dispatch: DispatchStats,
+    pub(super) attributions: BTreeMap<StateId, ObjectiveAttribution>,
+    suggester: Option<(&'s dyn RuleSuggester, SuggestionConfig)>,
+    suggestions: Vec<SuggestedRule>,
+    budget: Option<BudgetHandle<'s>>,
+    budget_stats: BudgetStats,
+    archive: Option<StateArchive>,
+    quarantined: Vec<QuarantinedCandidate>,
+    lexicographic_ties: Vec<LexicographicTieStats>,
+    invariant_violations: Vec<InvariantViolation>,
+}
+
+/// A candidate awaiting evaluation in `AnytimeSearch::step`.
+struct Pending<'f> {
+    parent: &'f DesignState,
+    /// `None` for crossover offspring, which are evaluated from scratch.
+    rule_id: Option<RuleId>,
+    state: DesignState,
+}
+
+impl<'s> AnytimeSearch<'s, '_> {
+    /// Scores later depths through `dispatcher`, concurrently and with its
+    /// retry and timeout policy.
+    pub fn with_dispatcher(mut self, dispatcher: &'s EvaluationDispatcher) -> Self {
+        self.dispatcher = Some(dispatcher);
+        self
+    }
+
+    pub fn dispatch(&self) -> DispatchStats {
+        self.dispatch
+    }
+
+    /// Asks `suggester` for extra rules on every frontier state from the
+    /// next depth on; see `capability::suggestion`.
+    pub fn with_suggester(
+        mut self,
+        suggester: &'s dyn RuleSuggester,
+        config: SuggestionConfig,
+    ) -> Self {
+        self.suggester = Some((suggester, config));
+        self
+    }
+
+    /// Draws each depth's candidate evaluations from `budget`'s pool,
+    /// narrowing the beam as the pool runs low.
+    pub fn with_budget(mut self, budget: BudgetHandle<'s>) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    pub fn budget(&self) -> BudgetStats {
+        self.budget_stats
+    }
+
+    /// Records the current beam and every later one into `archive`.
+    pub fn with_archive(mut self, mut archive: StateArchive) -> Self {
+        for state in &self.frontier {
+            archive.record(state, self.depth, StateOrigin::default());
+        }
+        self.archive = Some(archive);
+        self
+    }
+
+    /// States accepted so far, when archiving.
+    pub fn archive(&self) -> Option<&StateArchive> {
+        self.archive.as_ref()
+    }
+
+    /// Candidates dropped so far for a NaN or infinite objective.
+    pub fn quarantined(&self) -> &[QuarantinedCandidate] {
+        &self.quarantined
+    }
+
+    /// Failed invariant checks so far; empty unless
+    /// `SearchConfig::validate_invariants` is set.
+    pub fn invariant_violations(&self) -> &[InvariantViolation] {
+        &self.invariant_violations
+    }
+
+    /// Every suggestion examined so far, admitted or not.
+    pub fn suggestions(&self) -> &[SuggestedRule] {
+        &self.suggestions
+    }
+
+    /// Depths expanded so far.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// The current beam.
+    pub fn frontier(&self) -> &[DesignState] {
+        &self.frontier
+    }
+
+    /// Best-so-far Pareto set with raw objectives, in discovery order. Under
+    /// `SearchConfig::epsilon_constraint` this is the single best state.
+    pub fn best_front(&self) -> &[(DesignState, ObjectiveVector)] {
+        &self.best
+    }
+
+    pub fn crossover(&self) -> CrossoverStats {
+        self.crossover
+    }
+
+    pub fn targets_met_at(&self) -> Option<usize> {
+        self.targets_met_at
+    }
+
+    /// Warm-up statistics behind `GlobalFrozen` axes, once frozen.
+    pub fn calibration(&self) -> Option<GlobalRobustStats> {
+        self.normalizer.estimator().export()
+    }
+
+    /// Reuses statistics from an earlier run instead of warming up; call
+    /// before the first `step`.
+    pub fn import_calibration(&mut self, stats: GlobalRobustStats) {
+        self.normalizer.estimator_mut().import(stats);
+    }
+
+    /// True once max depth, the targets, or a dead end has been reached.
+    pub fn is_finished(&self) -> bool {
+        let config = &self.search.config;
+        self.exhausted
+            || self.targets_met_at.is_some()
+            || config.beam_width == 0
+            || self.depth >= config.max_depth
+    }
+
+    /// Expands the current beam by one depth. Returns false without doing
+    /// anything when the search is already finished.
+    pub fn step(&mut self) -> bool {
+        if self.is_finished() {
+            return false;
+        }
+        let search = self.search;
+        let config = &search.config;
+        let repeated = matches!(config.evaluation, EvaluationPolicy::Repeated { .. });
+        let depth = self.depth + 1;
+        let beam_width = match &self.budget {
+            Some(budget) => budget.beam_width(config.beam_width),
+            None => config.beam_width,
+        };
+        if beam_width < config.beam_width {
+            self.budget_stats.degraded_depths += 1;
+        }
+
+        // Rule children first, then crossover offspring; tree records and
+        // candidates keep that order whichever way they are evaluated.
+        let mut pending: Vec<Pending<'_>> = Vec::new();
+        for state in self.frontier.iter().take(beam_width) {
+            for rule in HybridVM::applicable_rules(search.shm, state) {
+                if let Ok(child) = crate::apply_atomic(rule, state) {
+                    pending.push(Pending {
+                        parent: state,
+                        rule_id: Some(rule.id),
+                        state: child,
+                    });
+                }
+            }
+            if let Some((suggester, suggestion_config)) = self.suggester {
+                let admitted = admit_suggestions(
+                    search,
+                    suggester,
+                    suggestion_config,
+                    state,
+                    depth,
+                    &mut self.suggestions,
+                );
+                for (rule_id, child) in admitted {
+                    pending.push(Pending {
+                        parent: state,
+                        rule_id: Some(rule_id),
+                        state: child,
+                    });
+                }
+            }
+        }
+        let mut offspring = BTreeSet::new();
+        let pairs = self.frontier.iter().enumerate().flat_map(|(i, left)| {
+            self.frontier[i + 1..]
+                .iter()
+                .map(move |right| (left, right))
+        });
+        for (left, right) in pairs.take(config.crossover_pairs) {
+            let Some(child) = recombine(left, right) else {
+                continue;
             };
+            if !offspring.insert(child.id) {
+                continue;
+            }
+            pending.push(Pending {
+                parent: left,
+                rule_id: None,
+                state: child,
+            });
+        }
+        // Past the grant, crossover offspring are dropped first, then the
+        // children of the last frontier states.
+        if let Some(budget) = self.budget.as_mut() {
+            let granted = budget.draw(pending.len());
+            self.budget_stats.drawn += granted;
+            self.budget_stats.denied += pending.len() - granted;
+            pending.truncate(granted);
+            offspring.retain(|id| pending.iter().any(|p| p.state.id == *id));
         }
 
-        let mut frontier = vec![initial_state.clone()];
-        let mut all_depths = Vec::new();
-        for depth in 0..self.config.max_depth {
-            let mut candidates: Vec<(DesignState, ObjectiveVector)> = Vec::new();
-            for state in &frontier {
-                for rule in HybridVM::applicable_rules(self.shm, state) {
-                    let new_state = crate::apply_atomic(rule, state);
-                    let obj = self.evaluator.evaluate(&new_state);
-                    candidates.push((new_state, obj));
+        let evaluations = match self.dispatcher {
+            Some(dispatcher) => {
+                let jobs = pending
+                    .iter()
+                    .map(|p| EvaluationJob {
+                        parent: p.rule_id.map(|_| p.parent.clone()),
+                        state: p.state.clone(),
+                    })
+                    .collect();
+                let (evaluations, stats) = dispatcher.evaluate_batch(jobs, config.evaluation);
+                self.dispatch.absorb(stats);
+                evaluations
+            }
+            None => pending
+                .iter()
+                .map(|p| {
+                    Some(match p.rule_id {
+                        Some(_) => evaluate_child_with_policy(
+                            search.evaluator,
+                            p.parent,
+                            &p.state,
+                            config.evaluation,
+                        ),
+                        None => evaluate_with_policy(search.evaluator, &p.state, config.evaluation),
+                    })
+                })
+                .collect(),
+        };
+
+        let mut candidates: Vec<(DesignState, ObjectiveVector)> = Vec::new();
+        let mut origins = BTreeMap::new();
+        for (p, evaluation) in pending.into_iter().zip(evaluations) {
+            // Candidates the dispatcher gave up on are left out of this depth.
+            let Some(evaluation) = evaluation else {
+                continue;
+            };
+            if let Some(diagnostic) =
+                quarantine(depth, p.state.id, p.rule_id, &evaluation.objective)
+            {
+                self.quarantined.push(diagnostic);
+                continue;
+            }
+            if repeated {
+                self.objective_variance
+                    .insert(p.state.id, evaluation.variance);
+            }
+            if config.explain {
+                record_attribution(
+                    &mut self.attributions,
+                    search,
+                    &p.state,
+                    &evaluation.objective,
+                );
+            }
+            if let Some(tree) = self.tree.as_mut() {
+                tree.record(SearchTreeNode {
+                    state_id: p.state.id,
+                    parent: Some(p.parent.id),
+                    rule_id: p.rule_id,
+                    depth,
+                    objective: evaluation.objective.clone(),
+                    kept: false,
+                });
+            }
+            if self.archive.is_some() {
+                origins.insert(
+                    p.state.id,
+                    StateOrigin {
+                        parent: Some(p.parent.id),
+                        rule_id: p.rule_id,
+                    },
+                );
+            }
+            candidates.push((p.state, evaluation.objective));
+        }
+        self.crossover.generated += offspring.len();
+        if candidates.is_empty() {
+            self.exhausted = true;
+            return false;
+        }
+        let raw = candidates
+            .iter()
+            .map(|(state, obj)| (state.id, obj.clone()))
+            .collect::<BTreeMap<_, _>>();
+
+        let validate = config.validate_invariants;
+        let front_states = match (&config.epsilon_constraint, &config.lexicographic) {
+            (Some(constraint), _) => {
+                let ranked = epsilon_constraint_rank(candidates, constraint, config.directions);
+                if validate {
+                    self.invariant_violations.extend(check_constrained_order(
+                        depth,
+                        &ranked,
+                        constraint,
+                        config.directions,
+                    ));
                 }
+                ranked
+            }
+            // Tolerance bands make the lexicographic order intransitive, so
+            // there is no pairwise order to recheck.
+            (None, Some(order)) => {
+                let (ranked, tied) = lexicographic_rank(candidates, order, config.directions);
+                self.lexicographic_ties.push(LexicographicTieStats {
+                    depth,
+                    candidates: ranked.len(),
+                    tied,
+                });
+                ranked
             }
-            if candidates.is_empty() {
-                break;
+            (None, None) => {
+                // Noisy candidates rank by the low end of their confidence
+                // band, so a lucky sample mean does not buy a beam slot.
+                let samples = config.evaluation.samples();
+                let candidates = candidates
+                    .into_iter()
+                    .map(|(state, obj)| {
+                        let oriented = config.directions.orient(&obj);
+                        match self.objective_variance.get(&state.id) {
+                            Some(variance) => {
+                                let lower = lower_confidence_bound(&oriented, variance, samples);
+                                (state, lower)
+                            }
+                            None => (state, oriented),
+                        }
+                    })
+                    .collect();
+                let normalized = self.normalizer.normalize(candidates, config.norm_alpha);
+                if validate {
+                    self.invariant_violations.extend(
+                        check_norm_alpha(depth, config.norm_alpha)
+                            .into_iter()
+                            .chain(check_normalized(depth, &normalized)),
+                    );
+                }
+                let ranked = soft_front_rank(normalized, SOFT_PARETO_TEMPERATURE);
+                if validate {
+                    self.invariant_violations.extend(check_soft_front_order(
+                        depth,
+                        &ranked,
+                        SOFT_PARETO_TEMPERATURE,
+                    ));
+                }
+                ranked
             }
+        };
+        let frontier = front_states
+            .into_iter()
+            .take(beam_width)
+            .map(|(state, _)| state)
+            .collect();
+        self.advance(depth, frontier, &raw, &offspring, &origins);
+        true
+    }
 
-            let (normalized, _) = crate::normalize_by_depth(candidates, self.config.norm_alpha);
-            let front_states =
-                crate::capability::selection::soft_front_rank(normalized, SOFT_PARETO_TEMPERATURE);
-            frontier = front_states
-                .into_iter()
-                .take(self.config.beam_width)
-                .map(|(state, _)| state)
-                .collect();
+    /// Makes `frontier` the beam at `depth` and updates the tree, archives,
+    /// and target bookkeeping from the raw candidate objectives. `origins`
+    /// only matters with a `StateArchive`.
+    pub(super) fn advance(
+        &mut self,
+        depth: usize,
+        frontier: Vec<DesignState>,
+        raw: &BTreeMap<StateId, ObjectiveVector>,
+        offspring: &BTreeSet<StateId>,
+        origins: &BTreeMap<StateId, StateOrigin>,
+    ) {
+        let search = self.search;
+        let config = &search.config;
+        if config.validate_invariants {
+            self.invariant_violations
+                .extend(check_unique_ids(depth, &frontier));
+        }
+        self.frontier = frontier;
+        self.depth = depth;
 
-            all_depths.push(DepthFront {
-                depth: depth + 1,
-                state_ids: frontier.iter().map(|state| state.id).collect(),
+        let kept = self
+            .frontier
+            .iter()
+            .map(|state| state.id)
+            .collect::<Vec<_>>();
+        if let Some(tree) = self.tree.as_mut() {
+            tree.mark_kept(depth, &kept);
+        }
+        self.crossover.survived += kept.iter().filter(|id| offspring.contains(id)).count();
+        self.all_depths.push(DepthFront {
+            depth,
+            state_ids: kept,
+        });
+        let tolerance = config
+            .dominance_tolerance
+            .map(|[f_struct, f_field, f_risk, f_shape]| ObjectiveVector {
+                f_struct,
+                f_field,
+                f_risk,
+                f_shape,
             });
-            if frontier.is_empty() {
-                break;
+        for state in &self.frontier {
+            if let Some(obj) = raw.get(&state.id) {
+                match (&config.epsilon_constraint, &config.lexicographic) {
+                    (Some(constraint), _) => archive_constrained(
+                        &mut self.best,
+                        state,
+                        obj,
+                        constraint,
+                        config.directions,
+                    ),
+                    (None, Some(order)) => {
+                        archive_lexicographic(&mut self.best, state, obj, order, config.directions)
+                    }
+                    (None, None) => archive(
+                        &mut self.best,
+                        state,
+                        obj,
+                        tolerance.as_ref(),
+                        config.directions,
+                        (&self.objective_variance, config.evaluation.samples()),
+                    ),
+                }
+            }
+        }
+        if let Some(state_archive) = self.archive.as_mut() {
+            for state in &self.frontier {
+                let origin = origins.get(&state.id).copied().unwrap_or_default();
+                state_archive.record(state, depth, origin);
             }
         }
+        if let Some(targets) = config.targets
+            && self
+                .frontier
+                .iter()
+                .filter_map(|state| raw.get(&state.id))
+                .any(|obj| targets.is_met(obj))
+        {
+            self.targets_met_at = Some(depth);
+        }
+        if self.frontier.is_empty() {
+            self.exhausted = true;
+        }
+    }
 
+    /// Stops the run and packages what was found so far.
+    pub fn finish(self, mode: SearchMode) -> SearchResult {
         let depth_fronts = match mode {
-            SearchMode::Auto => all_depths.last().cloned().into_iter().collect(),
-            SearchMode::Manual => all_depths,
+            SearchMode::Auto => self.all_depths.last().cloned().into_iter().collect(),
+            SearchMode::Manual => self.all_depths,
         };
         SearchResult {
-            final_frontier: frontier,
+            final_frontier: self.frontier,
             depth_fronts,
+            targets_met_at: self.targets_met_at,
+            objective_variance: self.objective_variance,
+            search_tree: self.tree,
+            crossover: self.crossover,
+            manual_choices: self.manual_choices,
+            dispatch: self.dispatch,
+            attributions: self.attributions,
+            suggestions: self.suggestions,
+            budget: self.budget_stats,
+            archive: self.archive,
+            quarantined: self.quarantined,
+            lexicographic_ties: self.lexicographic_ties,
+            invariant_violations: self.invariant_violations,
+        }
+    }
+}
+
+/// Stores the evaluator's breakdown of `state`, topped up with an
+/// `unattributed` term wherever the ranked `objective` differs from it
+/// (memory recall, repeated sampling).
+pub(super) fn record_attribution(
+    attributions: &mut BTreeMap<StateId, ObjectiveAttribution>,
+    search: &BeamSearch<'_>,
+    state: &DesignState,
+    objective: &ObjectiveVector,
+) {
+    if let Some(attribution) = search.evaluator.explain(state) {
+        attributions.insert(state.id, attribution.reconciled(objective, "unattributed"));
+    }
+}
+
+/// Keeps the non-dominated states. Between two states with recorded sample
+/// variance, one only dominates the other by leading beyond the noise
+/// epsilon (widened by `tolerance`).
+fn archive(
+    best: &mut Vec<(DesignState, ObjectiveVector)>,
+    state: &DesignState,
+    obj: &ObjectiveVector,
+    tolerance: Option<&ObjectiveVector>,
+    directions: ObjectiveDirections,
+    (variance, samples): (&BTreeMap<StateId, ObjectiveVector>, usize),
+) {
+    let dominates = |a: (StateId, &ObjectiveVector), b: (StateId, &ObjectiveVector)| match (
+        variance.get(&a.0),
+        variance.get(&b.0),
+    ) {
+        (Some(var_a), Some(var_b)) => {
+            let mut eps = noise_epsilon(var_a, var_b, samples);
+            if let Some(t) = tolerance {
+                eps.f_struct += t.f_struct;
+                eps.f_field += t.f_field;
+                eps.f_risk += t.f_risk;
+                eps.f_shape += t.f_shape;
+            }
+            directions.epsilon_dominates(a.1, b.1, &eps)
         }
+        _ => directions.dominates_within(a.1, b.1, tolerance),
+    };
+    if best
+        .iter()
+        .any(|(s, o)| s.id == state.id || dominates((s.id, o), (state.id, obj)))
+    {
+        return;
+    }
+    best.retain(|(s, o)| !dominates((state.id, obj), (s.id, o)));
+    best.push((state.clone(), obj.clone()));
+}
+
+/// Keeps the single best state under `constraint`, preferring feasibility.
+fn archive_constrained(
+    best: &mut Vec<(DesignState, ObjectiveVector)>,
+    state: &DesignState,
+    obj: &ObjectiveVector,
+    constraint: &EpsilonConstraint,
+    directions: ObjectiveDirections,
+) {
+    let key = |o: &ObjectiveVector| constraint.key(o, directions);
+    let (violation, value) = key(obj);
+    let improves = best.first().is_none_or(|(_, current)| {
+        let (cur_violation, cur_value) = key(current);
+        violation < cur_violation || (violation == cur_violation && value > cur_value)
+    });
+    if improves {
+        best.clear();
+        best.push((state.clone(), obj.clone()));
+    }
+}
+
+/// Keeps the single state ranked first under `order`.
+fn archive_lexicographic(
+    best: &mut Vec<(DesignState, ObjectiveVector)>,
+    state: &DesignState,
+    obj: &ObjectiveVector,
+    order: &LexicographicOrder,
+    directions: ObjectiveDirections,
+) {
+    let improves = best
+        .first()
+        .is_none_or(|(_, current)| order.compare(obj, current, directions).is_gt());
+    if improves {
+        best.clear();
+        best.push((state.clone(), obj.clone()));
     }
 }
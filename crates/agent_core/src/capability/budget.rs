@@ -0,0 +1,199 @@
+//! Evaluation quota shared by searches running side by side, e.g. ensemble
+//! members or islands on one CI machine.
+//!
+//! A `BudgetPool` counts candidate evaluations against a global cap. Each
+//! search joins it for a `BudgetHandle` and draws units before scoring a
+//! depth; the pool's `BudgetPolicy` decides how much of the remainder one
+//! handle may take. Bookkeeping is lock-free, so handles can be driven from
+//! different threads. One unit is one scored candidate, whatever the
+//! `EvaluationPolicy` sample count.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Distinct `BudgetHandle` priorities; higher values are clamped.
+pub const PRIORITY_LEVELS: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BudgetPolicy {
+    /// Any handle may take everything that is left.
+    FirstCome,
+    /// Each live handle is entitled to its weight's share of the units not
+    /// spent by handles that already left, so an early finisher's unused
+    /// share goes to the rest.
+    FairShare,
+    /// Handles below the highest live priority may not draw the pool under
+    /// `reserve` units.
+    Priority { reserve: usize },
+}
+
+pub struct BudgetPool {
+    total: usize,
+    policy: BudgetPolicy,
+    /// Remaining share of `total` below which `BudgetHandle::beam_width`
+    /// starts shrinking beams.
+    degrade_below: f64,
+    used: AtomicUsize,
+    /// Units drawn by handles that have been dropped.
+    departed_used: AtomicUsize,
+    /// Weight summed over live handles.
+    active_weight: AtomicUsize,
+    /// Live handles per priority level.
+    active_levels: [AtomicUsize; PRIORITY_LEVELS],
+}
+
+impl BudgetPool {
+    pub fn new(total: usize, policy: BudgetPolicy) -> Self {
+        Self {
+            total,
+            policy,
+            degrade_below: 0.25,
+            used: AtomicUsize::new(0),
+            departed_used: AtomicUsize::new(0),
+            active_weight: AtomicUsize::new(0),
+            active_levels: Default::default(),
+        }
+    }
+
+    /// Sets the remaining share (0..=1) under which beams shrink in
+    /// proportion; 0 disables degradation. Defaults to 0.25.
+    pub fn with_degrade_below(mut self, fraction: f64) -> Self {
+        self.degrade_below = fraction.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Registers a search. `weight` (at least 1) matters under `FairShare`,
+    /// `priority` under `Priority`.
+    pub fn join(&self, weight: usize, priority: usize) -> BudgetHandle<'_> {
+        let weight = weight.max(1);
+        let priority = priority.min(PRIORITY_LEVELS - 1);
+        self.active_weight.fetch_add(weight, Ordering::AcqRel);
+        self.active_levels[priority].fetch_add(1, Ordering::AcqRel);
+        BudgetHandle {
+            pool: self,
+            weight,
+            priority,
+            drawn: 0,
+        }
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    pub fn used(&self) -> usize {
+        self.used.load(Ordering::Acquire).min(self.total)
+    }
+
+    pub fn remaining(&self) -> usize {
+        self.total - self.used()
+    }
+
+    /// `remaining / total`; 0 for an empty pool.
+    pub fn remaining_fraction(&self) -> f64 {
+        if self.total == 0 {
+            0.0
+        } else {
+            self.remaining() as f64 / self.total as f64
+        }
+    }
+}
+
+/// One search's membership in a `BudgetPool`. Dropping it leaves the pool
+/// and releases any fair share it did not use.
+pub struct BudgetHandle<'p> {
+    pool: &'p BudgetPool,
+    weight: usize,
+    priority: usize,
+    drawn: usize,
+}
+
+impl BudgetHandle<'_> {
+    /// Takes up to `wanted` units and returns how many were granted; 0 once
+    /// the policy leaves nothing for this handle.
+    pub fn draw(&mut self, wanted: usize) -> usize {
+        let pool = self.pool;
+        let mut used = pool.used.load(Ordering::Acquire);
+        loop {
+            let remaining = pool.total.saturating_sub(used);
+            let grant = wanted.min(self.allowance(remaining));
+            if grant == 0 {
+                return 0;
+            }
+            match pool.used.compare_exchange_weak(
+                used,
+                used + grant,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    self.drawn += grant;
+                    return grant;
+                }
+                Err(current) => used = current,
+            }
+        }
+    }
+
+    /// Units drawn through this handle.
+    pub fn drawn(&self) -> usize {
+        self.drawn
+    }
+
+    /// `configured` while the pool is above its degradation threshold, then
+    /// shrinking with the remaining share down to 1.
+    pub fn beam_width(&self, configured: usize) -> usize {
+        let pool = self.pool;
+        let fraction = pool.remaining_fraction();
+        if configured == 0 || pool.degrade_below <= 0.0 || fraction >= pool.degrade_below {
+            return configured;
+        }
+        ((configured as f64 * fraction / pool.degrade_below).ceil() as usize).clamp(1, configured)
+    }
+
+    fn allowance(&self, remaining: usize) -> usize {
+        let pool = self.pool;
+        match pool.policy {
+            BudgetPolicy::FirstCome => remaining,
+            BudgetPolicy::FairShare => {
+                let shared = pool
+                    .total
+                    .saturating_sub(pool.departed_used.load(Ordering::Acquire));
+                let active = pool.active_weight.load(Ordering::Acquire).max(self.weight);
+                let entitled = (shared as u128 * self.weight as u128 / active as u128) as usize;
+                remaining.min(entitled.saturating_sub(self.drawn))
+            }
+            BudgetPolicy::Priority { reserve } => {
+                let outranked = pool.active_levels[self.priority + 1..]
+                    .iter()
+                    .any(|level| level.load(Ordering::Acquire) > 0);
+                if outranked {
+                    remaining.saturating_sub(reserve)
+                } else {
+                    remaining
+                }
+            }
+        }
+    }
+}
+
+impl Drop for BudgetHandle<'_> {
+    fn drop(&mut self) {
+        let pool = self.pool;
+        pool.departed_used.fetch_add(self.drawn, Ordering::AcqRel);
+        pool.active_weight.fetch_sub(self.weight, Ordering::AcqRel);
+        pool.active_levels[self.priority].fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Reported in `SearchResult::budget`; zero without a `BudgetHandle`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BudgetStats {
+    /// Candidates scored on budget.
+    pub drawn: usize,
+    /// Candidates skipped because the pool refused them.
+    pub denied: usize,
+    /// Depths expanded with a beam narrower than configured.
+    pub degraded_depths: usize,
+}
@@ -0,0 +1,200 @@
+//! Effect calibration: applies every rule in isolation to a corpus of states
+//! and compares the measured objective deltas with its `expected_effect`.
+//! Deltas follow the objective conventions: risk = `1 - f_risk`,
+//! cost = `1 - f_shape`.
+
+use core_types::ObjectiveVector;
+use hybrid_vm::{DesignRule, EffectVector, Evaluator, RuleId, RulePack, Shm};
+use memory_space::DesignState;
+
+use super::apply::apply_atomic;
+use super::rule_sampling::SplitMix64;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CalibrationConfig {
+    pub corpus_size: usize,
+    /// Upper bound on random rule applications per sampled state.
+    pub walk_depth: usize,
+    pub seed: u64,
+}
+
+impl Default for CalibrationConfig {
+    fn default() -> Self {
+        Self {
+            corpus_size: 32,
+            walk_depth: 4,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleCalibration {
+    pub rule_id: RuleId,
+    pub expected: EffectVector,
+    /// Mean delta over the corpus states the rule applied to; all zero when
+    /// `samples == 0`.
+    pub measured: EffectVector,
+    pub samples: usize,
+}
+
+impl RuleCalibration {
+    /// Mean absolute difference between expected and measured, per dimension.
+    pub fn error(&self) -> f64 {
+        let e = effect_array(&self.expected);
+        let m = effect_array(&self.measured);
+        e.iter().zip(m).map(|(e, m)| (e - m).abs()).sum::<f64>() / 4.0
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CalibrationReport {
+    pub corpus_size: usize,
+    pub rules: Vec<RuleCalibration>,
+}
+
+impl CalibrationReport {
+    /// Average `error()` over rules that were exercised at least once.
+    pub fn mean_error(&self) -> f64 {
+        let measured = self
+            .rules
+            .iter()
+            .filter(|r| r.samples > 0)
+            .map(RuleCalibration::error)
+            .collect::<Vec<_>>();
+        if measured.is_empty() {
+            0.0
+        } else {
+            measured.iter().sum::<f64>() / measured.len() as f64
+        }
+    }
+
+    /// Rules that never matched a corpus state and so stay uncalibrated.
+    pub fn unexercised(&self) -> Vec<RuleId> {
+        self.rules
+            .iter()
+            .filter(|r| r.samples == 0)
+            .map(|r| r.rule_id)
+            .collect()
+    }
+
+    /// Copies `rules`, replacing `expected_effect` with the measured mean for
+    /// every exercised rule.
+    pub fn calibrated_rules(&self, rules: &[DesignRule]) -> Vec<DesignRule> {
+        rules
+            .iter()
+            .map(|rule| {
+                let mut rule = rule.clone();
+                if let Some(cal) = self
+                    .rules
+                    .iter()
+                    .find(|c| c.rule_id == rule.id && c.samples > 0)
+                {
+                    rule.expected_effect = cal.measured.clone();
+                }
+                rule
+            })
+            .collect()
+    }
+
+    pub fn calibrated_pack(&self, pack: &RulePack) -> RulePack {
+        RulePack::new(pack.name.clone(), self.calibrated_rules(&pack.rules))
+    }
+}
+
+/// Random rule walks of up to `walk_depth` steps from each seed state, drawn
+/// round-robin. Deterministic for a given `config.seed`.
+pub fn sample_corpus(
+    seeds: &[DesignState],
+    shm: &Shm,
+    config: CalibrationConfig,
+) -> Vec<DesignState> {
+    if seeds.is_empty() {
+        return Vec::new();
+    }
+    let mut rng = SplitMix64::from_seed(config.seed);
+    (0..config.corpus_size)
+        .map(|i| {
+            let mut state = seeds[i % seeds.len()].clone();
+            for _ in 0..rng.next_index(config.walk_depth + 1) {
+                let applicable = shm.applicable_rules(&state);
+                if applicable.is_empty() {
+                    break;
+                }
+                match apply_atomic(applicable[rng.next_index(applicable.len())], &state) {
+                    Ok(next) => state = next,
+                    Err(_) => break,
+                }
+            }
+            state
+        })
+        .collect()
+}
+
+/// Applies each rule once to every corpus state whose precondition holds and
+/// averages the observed deltas.
+pub fn calibrate_effects(
+    rules: &[DesignRule],
+    corpus: &[DesignState],
+    evaluator: &dyn Evaluator,
+) -> CalibrationReport {
+    let baselines = corpus
+        .iter()
+        .map(|state| evaluator.evaluate(state))
+        .collect::<Vec<_>>();
+
+    let rules = rules
+        .iter()
+        .map(|rule| {
+            let mut sum = [0.0; 4];
+            let mut samples = 0usize;
+            for (state, before) in corpus.iter().zip(&baselines) {
+                if !rule.precondition.evaluate(state) {
+                    continue;
+                }
+                let Ok(child) = apply_atomic(rule, state) else {
+                    continue;
+                };
+                let after = evaluator.evaluate_child(state, &child);
+                for (acc, d) in sum.iter_mut().zip(objective_delta(before, &after)) {
+                    *acc += d;
+                }
+                samples += 1;
+            }
+            let mean = if samples == 0 {
+                [0.0; 4]
+            } else {
+                sum.map(|v| v / samples as f64)
+            };
+            RuleCalibration {
+                rule_id: rule.id,
+                expected: rule.expected_effect.clone(),
+                measured: EffectVector {
+                    delta_struct: mean[0],
+                    delta_field: mean[1],
+                    delta_risk: mean[2],
+                    delta_cost: mean[3],
+                },
+                samples,
+            }
+        })
+        .collect();
+
+    CalibrationReport {
+        corpus_size: corpus.len(),
+        rules,
+    }
+}
+
+fn objective_delta(before: &ObjectiveVector, after: &ObjectiveVector) -> [f64; 4] {
+    [
+        after.f_struct - before.f_struct,
+        after.f_field - before.f_field,
+        before.f_risk - after.f_risk,
+        before.f_shape - after.f_shape,
+    ]
+}
+
+fn effect_array(e: &EffectVector) -> [f64; 4] {
+    [e.delta_struct, e.delta_field, e.delta_risk, e.delta_cost]
+}
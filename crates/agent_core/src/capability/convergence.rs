@@ -0,0 +1,106 @@
+//! Early stopping for trace runs once the search stops making progress.
+
+/// Each enabled criterion must hold for `window` consecutive depths; the
+/// first one that does ends the run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConvergenceConfig {
+    pub window: usize,
+    /// Hypervolume gain over the best so far that still counts as progress.
+    pub hv_epsilon: Option<f64>,
+    /// Rule-category entropy below this means selection has collapsed.
+    pub entropy_threshold: Option<f64>,
+    /// `|delta_lambda|` below this means the lambda controller has settled.
+    pub lambda_epsilon: Option<f64>,
+}
+
+impl Default for ConvergenceConfig {
+    fn default() -> Self {
+        Self {
+            window: 5,
+            hv_epsilon: Some(1e-6),
+            entropy_threshold: None,
+            lambda_epsilon: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConvergenceReason {
+    HypervolumeStagnant,
+    LowEntropy,
+    LambdaStable,
+}
+
+impl ConvergenceReason {
+    /// Label recorded in `TraceRow::convergence_reason`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::HypervolumeStagnant => "hv_stagnant",
+            Self::LowEntropy => "low_entropy",
+            Self::LambdaStable => "lambda_stable",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ConvergenceMonitor {
+    config: ConvergenceConfig,
+    best_hv: f64,
+    stagnant_depths: usize,
+    low_entropy_depths: usize,
+    stable_lambda_depths: usize,
+}
+
+impl ConvergenceMonitor {
+    pub fn new(config: ConvergenceConfig) -> Self {
+        Self {
+            config,
+            best_hv: f64::NEG_INFINITY,
+            stagnant_depths: 0,
+            low_entropy_depths: 0,
+            stable_lambda_depths: 0,
+        }
+    }
+
+    pub fn config(&self) -> ConvergenceConfig {
+        self.config
+    }
+
+    /// Records one depth; returns the reason to stop, if any.
+    pub fn observe(
+        &mut self,
+        hypervolume: f64,
+        entropy: f64,
+        delta_lambda: f64,
+    ) -> Option<ConvergenceReason> {
+        let window = self.config.window.max(1);
+        if let Some(eps) = self.config.hv_epsilon {
+            if hypervolume > self.best_hv + eps {
+                self.best_hv = hypervolume;
+                self.stagnant_depths = 0;
+            } else {
+                self.stagnant_depths += 1;
+            }
+        }
+        self.low_entropy_depths = match self.config.entropy_threshold {
+            Some(threshold) if entropy < threshold => self.low_entropy_depths + 1,
+            _ => 0,
+        };
+        self.stable_lambda_depths = match self.config.lambda_epsilon {
+            Some(eps) if delta_lambda.abs() < eps => self.stable_lambda_depths + 1,
+            _ => 0,
+        };
+
+        if self.stagnant_depths >= window {
+            Some(ConvergenceReason::HypervolumeStagnant)
+        } else if self.low_entropy_depths >= window {
+            Some(ConvergenceReason::LowEntropy)
+        } else if self.stable_lambda_depths >= window {
+            Some(ConvergenceReason::LambdaStable)
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,71 @@
+//! Recombination of two frontier states into one child graph.
+//!
+//! Conflict resolution is left-biased: when both parents hold a node with the
+//! same id, the child keeps the left parent's kind and its value for every
+//! attribute key present in both, adding only keys the left side lacks. All
+//! left edges are kept; right edges that would close a cycle are dropped.
+
+use std::sync::Arc;
+
+use memory_space::{DesignState, StructuralGraph};
+
+use super::apply::{append_rule_history, deterministic_uuid, parse_rule_history};
+
+/// Running totals reported in `SearchResult::crossover`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CrossoverStats {
+    /// Valid, novel children added to the candidate pool.
+    pub generated: usize,
+    /// Children that were kept in the next beam.
+    pub survived: usize,
+}
+
+impl CrossoverStats {
+    pub fn survival_rate(&self) -> f64 {
+        if self.generated == 0 {
+            0.0
+        } else {
+            self.survived as f64 / self.generated as f64
+        }
+    }
+}
+
+/// Merges `right` into `left`. Returns `None` when the child would equal a
+/// parent or fails `StructuralGraph::validate`.
+pub fn recombine(left: &DesignState, right: &DesignState) -> Option<DesignState> {
+    let mut nodes = left.graph.nodes().clone();
+    for (id, node) in right.graph.nodes() {
+        match nodes.get_mut(id) {
+            Some(existing) => {
+                for (key, value) in &node.attributes {
+                    existing
+                        .attributes
+                        .entry(key.clone())
+                        .or_insert_with(|| value.clone());
+                }
+            }
+            None => {
+                nodes.insert(*id, node.clone());
+            }
+        }
+    }
+    let mut graph = StructuralGraph::new(nodes, left.graph.edges().clone());
+    for (from, to) in right.graph.edges() {
+        graph = graph.with_edge_added(*from, *to);
+    }
+    if graph.validate().is_err() || graph == *left.graph || graph == *right.graph {
+        return None;
+    }
+
+    let mut history = parse_rule_history(&left.profile_snapshot);
+    let mut snapshot = left.profile_snapshot.clone();
+    for id in parse_rule_history(&right.profile_snapshot) {
+        if !history.contains(&id) {
+            history.push(id);
+            snapshot = append_rule_history(&snapshot, id);
+        }
+    }
+    let id = deterministic_uuid(left.id.as_u128(), right.id.as_u128(), 0xC0);
+    Some(DesignState::new(id, Arc::new(graph), snapshot))
+}
@@ -0,0 +1,559 @@
+//! Concurrent candidate evaluation for slow, fallible evaluators such as
+//! external simulators.
+//!
+//! `EvaluationDispatcher` keeps up to `DispatchConfig::concurrency` worker
+//! threads pulling jobs from one shared queue, so a slow candidate only
+//! holds up its own worker while idle workers take the remaining jobs.
+//! Failed or timed-out attempts are retried up to `max_retries` times.
+//! Threads cannot be cancelled: a timed-out call keeps running in the
+//! background and its eventual result is dropped. Its worker is not
+//! replaced; it rejoins the pool once the call returns. Calls still hung
+//! from earlier batches count against `concurrency`, so one dispatcher never
+//! runs more than `concurrency` threads. When every worker of a batch is
+//! hung, the jobs that have not finished fail.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::panic::{AssertUnwindSafe, catch_unwind};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use core_types::ObjectiveVector;
+use hybrid_vm::Evaluator;
+use memory_space::DesignState;
+
+use super::evaluation::{PolicyEvaluation, aggregate_samples};
+use crate::EvaluationPolicy;
+
+/// Evaluator that may fail and is safe to call from several threads at once.
+pub trait AsyncEvaluator: Send + Sync {
+    fn evaluate(&self, state: &DesignState) -> Result<ObjectiveVector, EvaluationError>;
+
+    /// See `Evaluator::evaluate_child`.
+    fn evaluate_child(
+        &self,
+        parent: &DesignState,
+        child: &DesignState,
+    ) -> Result<ObjectiveVector, EvaluationError> {
+        let _ = parent;
+        self.evaluate(child)
+    }
+}
+
+/// Runs an infallible `Evaluator` through the dispatcher.
+pub struct InfallibleEvaluator<E>(pub E);
+
+impl<E: Evaluator + Send + Sync> AsyncEvaluator for InfallibleEvaluator<E> {
+    fn evaluate(&self, state: &DesignState) -> Result<ObjectiveVector, EvaluationError> {
+        Ok(self.0.evaluate(state))
+    }
+
+    fn evaluate_child(
+        &self,
+        parent: &DesignState,
+        child: &DesignState,
+    ) -> Result<ObjectiveVector, EvaluationError> {
+        Ok(self.0.evaluate_child(parent, child))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvaluationError(pub String);
+
+impl std::fmt::Display for EvaluationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "evaluation failed: {}", self.0)
+    }
+}
+
+impl std::error::Error for EvaluationError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DispatchConfig {
+    /// Upper bound on attempts running at once (at least 1).
+    pub concurrency: usize,
+    /// Extra attempts after a failure or timeout.
+    pub max_retries: usize,
+    /// Per-attempt limit; `None` waits indefinitely.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for DispatchConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: 4,
+            max_retries: 1,
+            timeout: None,
+        }
+    }
+}
+
+/// Running totals reported in `SearchResult::dispatch`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DispatchStats {
+    /// Jobs that produced an objective.
+    pub evaluated: usize,
+    pub retried: usize,
+    pub timed_out: usize,
+    /// Jobs dropped after exhausting their retries.
+    pub failed: usize,
+}
+
+impl DispatchStats {
+    pub fn absorb(&mut self, other: DispatchStats) {
+        self.evaluated += other.evaluated;
+        self.retried += other.retried;
+        self.timed_out += other.timed_out;
+        self.failed += other.failed;
+    }
+}
+
+/// One candidate to score; `parent` routes it through `evaluate_child`.
+#[derive(Clone, Debug)]
+pub struct EvaluationJob {
+    pub parent: Option<DesignState>,
+    pub state: DesignState,
+}
+
+pub struct EvaluationDispatcher {
+    evaluator: Arc<dyn AsyncEvaluator>,
+    config: DispatchConfig,
+    /// Workers still inside a timed-out call, across batches.
+    hung: Arc<AtomicUsize>,
+}
+
+/// `(job index, attempt)`.
+type AttemptKey = (usize, usize);
+
+enum WorkerEvent {
+    Started(AttemptKey, Instant),
+    Finished(AttemptKey, Result<PolicyEvaluation, EvaluationError>),
+    /// A timed-out call returned and its worker takes jobs again.
+    Rejoined,
+}
+
+/// State shared between the coordinator and its workers for one batch.
+struct Batch {
+    jobs: Vec<EvaluationJob>,
+    policy: EvaluationPolicy,
+    queue: Mutex<Receiver<AttemptKey>>,
+    /// Attempts whose outcome is decided. Whoever inserts a key first, the
+    /// worker on completion or the coordinator on timeout, owns it. The
+    /// coordinator counts a worker as hung while holding this lock.
+    settled: Mutex<BTreeSet<AttemptKey>>,
+}
+
+impl EvaluationDispatcher {
+    pub fn new(evaluator: Arc<dyn AsyncEvaluator>, config: DispatchConfig) -> Self {
+        Self {
+            evaluator,
+            config,
+            hung: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn config(&self) -> DispatchConfig {
+        self.config
+    }
+
+    /// Workers currently stuck in a timed-out call.
+    pub fn hung_workers(&self) -> usize {
+        self.hung.load(Ordering::SeqCst)
+    }
+
+    /// Scores every job under `policy`. Results are aligned with `jobs`;
+    /// `None` marks a job that failed or timed out on every attempt, or that
+    /// never started because no worker was free.
+    pub fn evaluate_batch(
+        &self,
+        jobs: Vec<EvaluationJob>,
+        policy: EvaluationPolicy,
+    ) -> (Vec<Option<PolicyEvaluation>>, DispatchStats) {
+        let mut stats = DispatchStats::default();
+        let total = jobs.len();
+        let mut results = vec![None; total];
+        if total == 0 {
+            return (results, stats);
+        }
+        let workers = self
+            .config
+            .concurrency
+            .max(1)
+            .saturating_sub(self.hung_workers())
+            .min(total);
+        if workers == 0 {
+            stats.failed = total;
+            return (results, stats);
+        }
+
+        let (job_tx, job_rx) = mpsc::channel();
+        let (event_tx, event_rx) = mpsc::channel();
+        let batch = Arc::new(Batch {
+            jobs,
+            policy,
+            queue: Mutex::new(job_rx),
+            settled: Mutex::new(BTreeSet::new()),
+        });
+        for index in 0..total {
+            job_tx.send((index, 0)).expect("queue receiver alive");
+        }
+        for _ in 0..workers {
+            self.spawn_worker(&batch, &event_tx);
+        }
+
+        let mut running: BTreeMap<AttemptKey, Instant> = BTreeMap::new();
+        let mut done = 0;
+        let mut hung = 0;
+        let retry_or_fail = |(index, attempt): AttemptKey, stats: &mut DispatchStats| {
+            if attempt < self.config.max_retries {
+                stats.retried += 1;
+                job_tx
+                    .send((index, attempt + 1))
+                    .expect("queue receiver alive");
+                false
+            } else {
+                stats.failed += 1;
+                true
+            }
+        };
+        while done < total {
+            if hung == workers {
+                stats.failed += total - done;
+                break;
+            }
+            let next_deadline = self
+                .config
+                .timeout
+                .and_then(|limit| running.values().min().map(|start| *start + limit));
+            let event = match next_deadline {
+                Some(deadline) => {
+                    match event_rx.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                    {
+                        Ok(event) => Some(event),
+                        Err(RecvTimeoutError::Timeout) => None,
+                        Err(RecvTimeoutError::Disconnected) => break,
+                    }
+                }
+                None => match event_rx.recv() {
+                    Ok(event) => Some(event),
+                    Err(_) => break,
+                },
+            };
+            match event {
+                Some(WorkerEvent::Started(key, at)) => {
+                    running.insert(key, at);
+                }
+                Some(WorkerEvent::Rejoined) => {
+                    hung -= 1;
+                }
+                Some(WorkerEvent::Finished(key, result)) => {
+                    running.remove(&key);
+                    match result {
+                        Ok(evaluation) => {
+                            stats.evaluated += 1;
+                            results[key.0] = Some(evaluation);
+                            done += 1;
+                        }
+                        Err(_) => {
+                            if retry_or_fail(key, &mut stats) {
+                                done += 1;
+                            }
+                        }
+                    }
+                }
+                None => {
+                    let limit = self.config.timeout.unwrap_or_default();
+                    let now = Instant::now();
+                    let expired = running
+                        .iter()
+                        .filter(|(_, start)| now.duration_since(**start) >= limit)
+                        .map(|(key, _)| *key)
+                        .collect::<Vec<_>>();
+                    for key in expired {
+                        running.remove(&key);
+                        {
+                            let mut settled = batch.settled.lock().expect("settled lock");
+                            // Lost the race: the worker finished and its event is queued.
+                            if !settled.insert(key) {
+                                continue;
+                            }
+                            self.hung.fetch_add(1, Ordering::SeqCst);
+                        }
+                        hung += 1;
+                        stats.timed_out += 1;
+                        if retry_or_fail(key, &mut stats) {
+                            done += 1;
+                        }
+                    }
+                }
+            }
+        }
+        // Dropping the sender lets idle workers exit. A worker that rejoins
+        // after this finds the event receiver gone and exits as well, leaving
+        // any retries still queued after an early stop unrun.
+        drop(job_tx);
+        (results, stats)
+    }
+
+    fn spawn_worker(&self, batch: &Arc<Batch>, events: &Sender<WorkerEvent>) {
+        let evaluator = Arc::clone(&self.evaluator);
+        let batch = Arc::clone(batch);
+        let events = events.clone();
+        let hung = Arc::clone(&self.hung);
+        std::thread::spawn(move || {
+            loop {
+                let next = batch.queue.lock().expect("queue lock").recv();
+                let Ok(key) = next else {
+                    return;
+                };
+                if events
+                    .send(WorkerEvent::Started(key, Instant::now()))
+                    .is_err()
+                {
+                    return;
+                }
+                let job = &batch.jobs[key.0];
+                let result = catch_unwind(AssertUnwindSafe(|| {
+                    evaluate_job(evaluator.as_ref(), job, batch.policy)
+                }))
+                .unwrap_or_else(|_| Err(EvaluationError("evaluator panicked".to_string())));
+                if !batch.settled.lock().expect("settled lock").insert(key) {
+                    // Timed out meanwhile: drop the result and take jobs again.
+                    hung.fetch_sub(1, Ordering::SeqCst);
+                    if events.send(WorkerEvent::Rejoined).is_err() {
+                        return;
+                    }
+                    continue;
+                }
+                if events.send(WorkerEvent::Finished(key, result)).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+}
+
+fn evaluate_job(
+    evaluator: &dyn AsyncEvaluator,
+    job: &EvaluationJob,
+    policy: EvaluationPolicy,
+) -> Result<PolicyEvaluation, EvaluationError> {
+    let n = match policy {
+        EvaluationPolicy::Single => 1,
+        EvaluationPolicy::Repeated { n, .. } => n.max(1),
+    };
+    let mut samples = Vec::with_capacity(n);
+    for _ in 0..n {
+        samples.push(match &job.parent {
+            Some(parent) => evaluator.evaluate_child(parent, &job.state)?,
+            None => evaluator.evaluate(&job.state)?,
+        });
+    }
+    let mut samples = samples.into_iter();
+    Ok(aggregate_samples(policy, || {
+        samples.next().expect("one sample per draw")
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use core_types::ObjectiveVector;
+    use memory_space::{DesignNode, DesignState, StructuralGraph, Uuid};
+
+    use super::{
+        AsyncEvaluator, DispatchConfig, DispatchStats, EvaluationDispatcher, EvaluationError,
+        EvaluationJob,
+    };
+    use crate::EvaluationPolicy;
+
+    fn state(nodes: u128) -> DesignState {
+        let mut graph = StructuralGraph::default();
+        for i in 0..nodes {
+            graph = graph.with_node_added(DesignNode::new(
+                Uuid::from_u128(i + 1),
+                "N",
+                BTreeMap::new(),
+            ));
+        }
+        DesignState::new(Uuid::from_u128(100 + nodes), Arc::new(graph), "history:")
+    }
+
+    fn objective(value: f64) -> ObjectiveVector {
+        ObjectiveVector {
+            f_struct: value,
+            f_field: 0.5,
+            f_risk: 0.5,
+            f_shape: 0.5,
+        }
+    }
+
+    /// Sleeps on states with 2 nodes, fails the first call on 3-node states,
+    /// and always fails on 4-node states.
+    struct FlakySimulator {
+        calls: AtomicUsize,
+        flaky_failures: AtomicUsize,
+    }
+
+    impl AsyncEvaluator for FlakySimulator {
+        fn evaluate(&self, state: &DesignState) -> Result<ObjectiveVector, EvaluationError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            match state.graph.nodes().len() {
+                2 => std::thread::sleep(Duration::from_millis(400)),
+                3 if self.flaky_failures.fetch_add(1, Ordering::SeqCst) == 0 => {
+                    return Err(EvaluationError("simulator busy".to_string()));
+                }
+                4 => return Err(EvaluationError("simulator crashed".to_string())),
+                _ => {}
+            }
+            Ok(objective(state.graph.nodes().len() as f64))
+        }
+    }
+
+    #[test]
+    fn dispatcher_retries_failures_and_abandons_timeouts() {
+        let dispatcher = EvaluationDispatcher::new(
+            Arc::new(FlakySimulator {
+                calls: AtomicUsize::new(0),
+                flaky_failures: AtomicUsize::new(0),
+            }),
+            DispatchConfig {
+                concurrency: 2,
+                max_retries: 1,
+                timeout: Some(Duration::from_millis(100)),
+            },
+        );
+        let jobs = (1..=4)
+            .map(|n| EvaluationJob {
+                parent: None,
+                state: state(n),
+            })
+            .collect();
+
+        let (results, stats) = dispatcher.evaluate_batch(jobs, EvaluationPolicy::Single);
+
+        assert_eq!(results[0].as_ref().map(|e| e.objective.f_struct), Some(1.0));
+        assert!(results[1].is_none());
+        assert_eq!(results[2].as_ref().map(|e| e.objective.f_struct), Some(3.0));
+        assert!(results[3].is_none());
+        assert_eq!(
+            stats,
+            DispatchStats {
+                evaluated: 2,
+                retried: 3,
+                timed_out: 2,
+                failed: 2,
+            }
+        );
+    }
+
+    /// Every call waits until `parties` calls are in flight at once.
+    struct Rendezvous {
+        barrier: std::sync::Barrier,
+    }
+
+    impl AsyncEvaluator for Rendezvous {
+        fn evaluate(&self, state: &DesignState) -> Result<ObjectiveVector, EvaluationError> {
+            self.barrier.wait();
+            Ok(objective(state.graph.nodes().len() as f64))
+        }
+    }
+
+    #[test]
+    fn slow_jobs_run_concurrently_and_results_keep_job_order() {
+        let dispatcher = EvaluationDispatcher::new(
+            Arc::new(Rendezvous {
+                barrier: std::sync::Barrier::new(8),
+            }),
+            DispatchConfig {
+                concurrency: 8,
+                max_retries: 0,
+                timeout: Some(Duration::from_secs(5)),
+            },
+        );
+        let jobs = (1..=8)
+            .map(|n| EvaluationJob {
+                parent: None,
+                state: state(n),
+            })
+            .collect();
+
+        let (results, stats) = dispatcher.evaluate_batch(jobs, EvaluationPolicy::Single);
+
+        assert_eq!(
+            stats,
+            DispatchStats {
+                evaluated: 8,
+                ..DispatchStats::default()
+            }
+        );
+        let values = results
+            .iter()
+            .map(|r| r.as_ref().expect("evaluated").objective.f_struct)
+            .collect::<Vec<_>>();
+        assert_eq!(values, (1..=8).map(f64::from).collect::<Vec<_>>());
+    }
+
+    struct Stuck {
+        calls: AtomicUsize,
+    }
+
+    impl AsyncEvaluator for Stuck {
+        fn evaluate(&self, _: &DesignState) -> Result<ObjectiveVector, EvaluationError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_secs(2));
+            Ok(objective(0.0))
+        }
+    }
+
+    #[test]
+    fn hung_workers_count_against_concurrency() {
+        let stuck = Arc::new(Stuck {
+            calls: AtomicUsize::new(0),
+        });
+        let dispatcher = EvaluationDispatcher::new(
+            stuck.clone(),
+            DispatchConfig {
+                concurrency: 2,
+                max_retries: 0,
+                timeout: Some(Duration::from_millis(50)),
+            },
+        );
+        let jobs = || {
+            (1..=4)
+                .map(|n| EvaluationJob {
+                    parent: None,
+                    state: state(n),
+                })
+                .collect::<Vec<_>>()
+        };
+
+        let (results, stats) = dispatcher.evaluate_batch(jobs(), EvaluationPolicy::Single);
+        assert!(results.iter().all(Option::is_none));
+        assert_eq!(
+            stats,
+            DispatchStats {
+                timed_out: 2,
+                failed: 4,
+                ..DispatchStats::default()
+            }
+        );
+        assert_eq!(dispatcher.hung_workers(), 2);
+
+        let (_, stats) = dispatcher.evaluate_batch(jobs(), EvaluationPolicy::Single);
+        assert_eq!(
+            stats,
+            DispatchStats {
+                failed: 4,
+                ..DispatchStats::default()
+            }
+        );
+        assert_eq!(stuck.calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,141 @@
+//! Per-run control over how hard beam selection pushes for spread.
+//!
+//! Pressure is the share of the selection score given to distance from the
+//! states already kept: 1 is pure max-min spread, 0 keeps the best scalar
+//! scores. A schedule picks it per depth from the previous depth's trace
+//! row, and the chosen value and the reason for it are written back into
+//! the row so every intervention can be audited afterwards.
+
+use crate::TraceRow;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DiversitySchedule {
+    /// The same pressure at every depth.
+    Constant { pressure: f64 },
+    /// `initial * decay^(depth - 1)`, never below `floor`: explore early,
+    /// exploit late.
+    DepthDecay {
+        initial: f64,
+        decay: f64,
+        floor: f64,
+    },
+    /// `base` until the previous depth reports a collapse, then `boost` for
+    /// that depth and the `hold` depths after it.
+    CollapseBoost { base: f64, boost: f64, hold: usize },
+}
+
+impl Default for DiversitySchedule {
+    fn default() -> Self {
+        Self::CollapseBoost {
+            base: 0.5,
+            boost: 1.0,
+            hold: 2,
+        }
+    }
+}
+
+/// Why a depth ran at the pressure it did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DiversityTrigger {
+    Constant,
+    DepthDecay,
+    /// No collapse signal and no boost still held.
+    Base,
+    /// The previous depth reported this collapse signal.
+    Collapse(String),
+    /// A boost started by an earlier collapse is still held.
+    BoostHold,
+}
+
+impl DiversityTrigger {
+    /// Label recorded in `TraceRow::diversity_trigger`.
+    pub fn label(&self) -> String {
+        match self {
+            Self::Constant => "constant".to_string(),
+            Self::DepthDecay => "depth_decay".to_string(),
+            Self::Base => "base".to_string(),
+            Self::Collapse(signal) => format!("collapse:{signal}"),
+            Self::BoostHold => "boost_hold".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiversityPressure {
+    /// In `[0, 1]`.
+    pub pressure: f64,
+    /// `crate::diversity::epsilon_effect` of `pressure`.
+    pub epsilon_effect: f64,
+    pub trigger: DiversityTrigger,
+}
+
+/// The collapse signal `row` carries, if any: the collapse proxy first,
+/// then the stability analyzer's reasons.
+pub fn collapse_signal(row: &TraceRow) -> Option<String> {
+    if row.collapse_proxy > 0.0 {
+        Some("proxy".to_string())
+    } else if !row.collapse_reasons.is_empty() {
+        Some(row.collapse_reasons.clone())
+    } else {
+        None
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct DiversityController {
+    schedule: DiversitySchedule,
+    boost_remaining: usize,
+}
+
+impl DiversityController {
+    pub fn new(schedule: DiversitySchedule) -> Self {
+        Self {
+            schedule,
+            boost_remaining: 0,
+        }
+    }
+
+    pub fn schedule(&self) -> DiversitySchedule {
+        self.schedule
+    }
+
+    /// Pressure for `depth`, given the row the previous depth recorded.
+    pub fn next(&mut self, depth: usize, previous: Option<&TraceRow>) -> DiversityPressure {
+        let (pressure, trigger) = match self.schedule {
+            DiversitySchedule::Constant { pressure } => (pressure, DiversityTrigger::Constant),
+            DiversitySchedule::DepthDecay {
+                initial,
+                decay,
+                floor,
+            } => {
+                let steps = depth.saturating_sub(1).min(i32::MAX as usize) as i32;
+                (
+                    (initial * decay.powi(steps)).max(floor),
+                    DiversityTrigger::DepthDecay,
+                )
+            }
+            DiversitySchedule::CollapseBoost { base, boost, hold } => {
+                if let Some(signal) = previous.and_then(collapse_signal) {
+                    self.boost_remaining = hold;
+                    (boost, DiversityTrigger::Collapse(signal))
+                } else if self.boost_remaining > 0 {
+                    self.boost_remaining -= 1;
+                    (boost, DiversityTrigger::BoostHold)
+                } else {
+                    (base, DiversityTrigger::Base)
+                }
+            }
+        };
+        let pressure = if pressure.is_finite() {
+            pressure.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        DiversityPressure {
+            pressure,
+            epsilon_effect: crate::diversity::epsilon_effect(pressure),
+            trigger,
+        }
+    }
+}
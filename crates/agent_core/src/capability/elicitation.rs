@@ -0,0 +1,192 @@
+//! Preference elicitation from pairwise "which design do you prefer?"
+//! answers over front members.
+//!
+//! Answers are fit with an L2-regularized logistic regression on objective
+//! differences, `P(left preferred) = sigmoid(sharpness * w . (left - right))`,
+//! shrunk towards the prior profile. Negative weights are clipped before the
+//! result is normalized into a `PreferenceProfile`.
+
+use std::collections::BTreeSet;
+
+use core_types::ObjectiveVector;
+use memory_space::{DesignState, StateId};
+use profile::PreferenceProfile;
+
+use crate::{Phase45Controller, ProfileUpdateType};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ElicitationConfig {
+    /// Scales `w . diff` before the sigmoid; objective differences are small.
+    pub sharpness: f64,
+    pub learning_rate: f64,
+    pub iterations: usize,
+    /// Strength of the pull back towards the prior profile.
+    pub l2: f64,
+}
+
+impl Default for ElicitationConfig {
+    fn default() -> Self {
+        Self {
+            sharpness: 10.0,
+            learning_rate: 0.5,
+            iterations: 200,
+            l2: 0.05,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PairwiseQuery {
+    pub left: StateId,
+    pub right: StateId,
+    pub left_objective: ObjectiveVector,
+    pub right_objective: ObjectiveVector,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PairwisePreference {
+    Left,
+    Right,
+}
+
+#[derive(Clone, Debug)]
+pub struct PreferenceElicitor {
+    config: ElicitationConfig,
+    prior: PreferenceProfile,
+    asked: BTreeSet<(StateId, StateId)>,
+    /// `(left - right, left preferred)`.
+    answers: Vec<([f64; 4], bool)>,
+}
+
+impl PreferenceElicitor {
+    pub fn new(prior: PreferenceProfile, config: ElicitationConfig) -> Self {
+        Self {
+            config,
+            prior: prior.normalized(),
+            asked: BTreeSet::new(),
+            answers: Vec::new(),
+        }
+    }
+
+    pub fn answer_count(&self) -> usize {
+        self.answers.len()
+    }
+
+    /// The unasked pair of `front` the current fit is least sure about;
+    /// `None` once every pair has been asked.
+    pub fn next_query(&self, front: &[(DesignState, ObjectiveVector)]) -> Option<PairwiseQuery> {
+        let weights = self.weights();
+        let mut best: Option<(f64, usize, usize)> = None;
+        for i in 0..front.len() {
+            for j in i + 1..front.len() {
+                if self.asked.contains(&pair_key(front[i].0.id, front[j].0.id)) {
+                    continue;
+                }
+                let diff = difference(&front[i].1, &front[j].1);
+                let margin = (self.config.sharpness * dot(&weights, &diff)).abs();
+                if best.is_none_or(|(m, _, _)| margin < m) {
+                    best = Some((margin, i, j));
+                }
+            }
+        }
+        best.map(|(_, i, j)| PairwiseQuery {
+            left: front[i].0.id,
+            right: front[j].0.id,
+            left_objective: front[i].1.clone(),
+            right_objective: front[j].1.clone(),
+        })
+    }
+
+    pub fn record(&mut self, query: &PairwiseQuery, answer: PairwisePreference) {
+        self.asked.insert(pair_key(query.left, query.right));
+        self.answers.push((
+            difference(&query.left_objective, &query.right_objective),
+            answer == PairwisePreference::Left,
+        ));
+    }
+
+    /// Profile fit to the answers so far; the prior when there are none.
+    pub fn fit(&self) -> PreferenceProfile {
+        let w = self.weights();
+        let clipped = w.map(|x| x.max(0.0));
+        if clipped.iter().sum::<f64>() <= f64::EPSILON {
+            return self.prior.clone();
+        }
+        PreferenceProfile {
+            struct_weight: clipped[0],
+            field_weight: clipped[1],
+            risk_weight: clipped[2],
+            cost_weight: clipped[3],
+        }
+        .normalized()
+    }
+
+    /// Fits the profile and reports it to `controller` as an explicit user
+    /// update, which bypasses the controller's cooldown.
+    pub fn commit(
+        &self,
+        controller: &mut Phase45Controller,
+        depth: usize,
+        stability_index: f64,
+    ) -> PreferenceProfile {
+        let profile = self.fit();
+        controller.on_profile_update(depth, stability_index, ProfileUpdateType::TypeAExplicit);
+        profile
+    }
+
+    fn weights(&self) -> [f64; 4] {
+        let prior = profile_to_array(&self.prior);
+        let mut w = prior;
+        if self.answers.is_empty() {
+            return w;
+        }
+        let n = self.answers.len() as f64;
+        let k = self.config.sharpness;
+        for _ in 0..self.config.iterations {
+            let mut grad = [0.0; 4];
+            for (diff, left) in &self.answers {
+                let p = sigmoid(k * dot(&w, diff));
+                let err = p - if *left { 1.0 } else { 0.0 };
+                for (g, d) in grad.iter_mut().zip(diff) {
+                    *g += err * k * d / n;
+                }
+            }
+            for ((wi, g), p) in w.iter_mut().zip(grad).zip(prior) {
+                *wi -= self.config.learning_rate * (g + self.config.l2 * (*wi - p));
+            }
+        }
+        w
+    }
+}
+
+fn pair_key(a: StateId, b: StateId) -> (StateId, StateId) {
+    if a <= b { (a, b) } else { (b, a) }
+}
+
+fn difference(left: &ObjectiveVector, right: &ObjectiveVector) -> [f64; 4] {
+    [
+        left.f_struct - right.f_struct,
+        left.f_field - right.f_field,
+        left.f_risk - right.f_risk,
+        left.f_shape - right.f_shape,
+    ]
+}
+
+fn profile_to_array(profile: &PreferenceProfile) -> [f64; 4] {
+    [
+        profile.struct_weight,
+        profile.field_weight,
+        profile.risk_weight,
+        profile.cost_weight,
+    ]
+}
+
+fn dot(w: &[f64; 4], d: &[f64; 4]) -> f64 {
+    w.iter().zip(d).map(|(a, b)| a * b).sum()
+}
+
+fn sigmoid(x: f64) -> f64 {
+    1.0 / (1.0 + (-x).exp())
+}
@@ -0,0 +1,71 @@
+//! Equivalence classes of generated candidates.
+//!
+//! `apply_add_constraint` and `apply_add_node` name what they add after the
+//! rule that added it (`constraint:<rule id>`, `generated_by_<rule id>`), and
+//! `apply_modify_attribute` leaves the graph as it was. Sibling rules of one
+//! kind therefore produce states that differ only in those names, yet carry
+//! different ids and would be evaluated and ranked as distinct designs.
+//! Keys are canonicalized here for comparison only; states keep their names.
+
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+
+use hybrid_vm::CONSTRAINT_ATTRIBUTE_PREFIX;
+use memory_space::StructuralGraph;
+
+use crate::capability::apply::GENERATED_ATTRIBUTE_PREFIX;
+use crate::capability::state_archive::canonical_hash_with;
+
+/// `key` with a rule id suffix of a generated attribute replaced by `*`;
+/// every other key unchanged.
+pub fn canonical_attribute_key(key: &str) -> Cow<'_, str> {
+    for prefix in [CONSTRAINT_ATTRIBUTE_PREFIX, GENERATED_ATTRIBUTE_PREFIX] {
+        if let Some(suffix) = key.strip_prefix(prefix)
+            && !suffix.is_empty()
+            && suffix.bytes().all(|b| b.is_ascii_digit())
+        {
+            return Cow::Owned(format!("{prefix}*"));
+        }
+    }
+    Cow::Borrowed(key)
+}
+
+/// `canonical_hash` over canonical attribute keys: equal for graphs that
+/// differ only in node ids and rule-derived attribute names.
+pub fn equivalence_hash(graph: &StructuralGraph) -> u64 {
+    canonical_hash_with(graph, canonical_attribute_key)
+}
+
+/// Admits the first candidate of each equivalence class and counts the
+/// rest. Feed candidates in a deterministic order so the same one survives
+/// every run.
+#[derive(Clone, Debug, Default)]
+pub struct EquivalenceReducer {
+    seen: BTreeSet<u64>,
+    merged: usize,
+}
+
+impl EquivalenceReducer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether a candidate with this `equivalence_hash` is the first of its
+    /// class; a later one is counted as merged.
+    pub fn admit(&mut self, hash: u64) -> bool {
+        let first = self.seen.insert(hash);
+        if !first {
+            self.merged += 1;
+        }
+        first
+    }
+
+    pub fn admit_graph(&mut self, graph: &StructuralGraph) -> bool {
+        self.admit(equivalence_hash(graph))
+    }
+
+    /// Candidates merged into an earlier equivalent so far.
+    pub fn merged(&self) -> usize {
+        self.merged
+    }
+}
@@ -2,16 +2,135 @@ use std::sync::Mutex;
 
 use core_types::ObjectiveVector;
 use field_engine::FieldEngine;
-use hybrid_vm::{Chm, Evaluator, HybridVM, StructuralEvaluator};
+use hybrid_vm::{
+    Chm, Contribution, Evaluator, HybridVM, ObjectiveAttribution, ResourceBudget, ResourceCheck,
+    StructuralEvaluator,
+};
 use memory_space::{DesignState, MemoryInterferenceTelemetry};
 
-use crate::SystemEvaluator;
+use super::performance::{PerformanceEstimate, PerformanceModel};
+use super::reliability::{ReliabilityModel, ReliabilityReport};
+use crate::cost_model::CostModel;
 use crate::domain::{Hypothesis, Score};
+use crate::{Aggregator, EvaluationPolicy, ObjectiveAxis, SystemEvaluator};
+
+/// Aggregated objective of one candidate plus the per-objective sample variance.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PolicyEvaluation {
+    pub objective: ObjectiveVector,
+    pub variance: ObjectiveVector,
+    pub samples: usize,
+}
+
+pub fn evaluate_with_policy(
+    evaluator: &dyn Evaluator,
+    state: &DesignState,
+    policy: EvaluationPolicy,
+) -> PolicyEvaluation {
+    aggregate_samples(policy, || evaluator.evaluate(state))
+}
+
+/// `evaluate_with_policy` for a state derived from `parent`, letting the
+/// evaluator reuse the parent's work through `Evaluator::evaluate_child`.
+pub fn evaluate_child_with_policy(
+    evaluator: &dyn Evaluator,
+    parent: &DesignState,
+    child: &DesignState,
+    policy: EvaluationPolicy,
+) -> PolicyEvaluation {
+    aggregate_samples(policy, || evaluator.evaluate_child(parent, child))
+}
+
+pub(super) fn aggregate_samples(
+    policy: EvaluationPolicy,
+    mut sample: impl FnMut() -> ObjectiveVector,
+) -> PolicyEvaluation {
+    let n = policy.samples();
+    let aggregator = match policy {
+        EvaluationPolicy::Single => Aggregator::Mean,
+        EvaluationPolicy::Repeated { aggregator, .. } => aggregator,
+    };
+    let samples = (0..n)
+        .map(|_| objective_array(&sample()))
+        .collect::<Vec<_>>();
+    let mut objective = [0.0; 4];
+    let mut variance = [0.0; 4];
+    for dim in 0..4 {
+        let mut values = samples.iter().map(|s| s[dim]).collect::<Vec<_>>();
+        let mean = values.iter().sum::<f64>() / n as f64;
+        variance[dim] = if n > 1 {
+            values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1) as f64
+        } else {
+            0.0
+        };
+        objective[dim] = match aggregator {
+            Aggregator::Mean => mean,
+            Aggregator::Median => {
+                values.sort_by(f64::total_cmp);
+                let mid = n / 2;
+                if n.is_multiple_of(2) {
+                    (values[mid - 1] + values[mid]) / 2.0
+                } else {
+                    values[mid]
+                }
+            }
+        };
+    }
+    PolicyEvaluation {
+        objective: objective_from_array(objective),
+        variance: objective_from_array(variance),
+        samples: n,
+    }
+}
+
+fn objective_array(obj: &ObjectiveVector) -> [f64; 4] {
+    [obj.f_struct, obj.f_field, obj.f_risk, obj.f_shape]
+}
+
+fn objective_from_array(v: [f64; 4]) -> ObjectiveVector {
+    ObjectiveVector {
+        f_struct: v[0],
+        f_field: v[1],
+        f_risk: v[2],
+        f_shape: v[3],
+    }
+}
 
 pub trait EvaluationCapability: Send + Sync {
     fn evaluate(&self, hypothesis: &Hypothesis) -> Score;
 }
 
+/// A model owning one `SystemEvaluator` axis.
+pub(crate) enum AxisModel {
+    /// `1 - normalized cost`.
+    Cost(Box<dyn CostModel>),
+    Reliability(ReliabilityModel),
+    Performance(PerformanceModel),
+    /// Headroom under the tightest limit.
+    ResourceBudget(ResourceBudget),
+}
+
+impl AxisModel {
+    /// Term name in `explain`.
+    fn name(&self) -> &'static str {
+        match self {
+            Self::Cost(_) => "cost_model",
+            Self::Reliability(_) => "reliability_model",
+            Self::Performance(_) => "performance_model",
+            Self::ResourceBudget(_) => "resource_budget",
+        }
+    }
+
+    fn score(&self, state: &DesignState) -> f64 {
+        match self {
+            Self::Cost(model) => 1.0 - model.normalized_cost(state),
+            Self::Reliability(model) => model.score(state),
+            Self::Performance(model) => model.score(&model.estimate(state)),
+            Self::ResourceBudget(budget) => budget.score(state),
+        }
+    }
+}
+
 impl<'a> SystemEvaluator<'a> {
     pub fn with_base(
         chm: &'a Chm,
@@ -22,6 +141,70 @@ impl<'a> SystemEvaluator<'a> {
         Ok(Self {
             vm: Mutex::new(vm),
             _chm: chm,
+            axis_models: [None, None, None, None],
+        })
+    }
+
+    /// Makes `model` the owner of `axis`, dropping any previous owner.
+    fn own(mut self, axis: ObjectiveAxis, model: AxisModel) -> Self {
+        self.axis_models[axis.index()] = Some(model);
+        self
+    }
+
+    fn models(&self) -> impl Iterator<Item = &AxisModel> {
+        self.axis_models.iter().flatten()
+    }
+
+    /// Model name owning `axis`, as reported by `explain`; `None` when the
+    /// axis keeps the structural score.
+    pub fn axis_owner(&self, axis: ObjectiveAxis) -> Option<&'static str> {
+        self.axis_models[axis.index()].as_ref().map(AxisModel::name)
+    }
+
+    /// Owns `f_shape` with `1 - normalized cost`.
+    pub fn with_cost_model(self, model: impl CostModel + 'static) -> Self {
+        self.own(ObjectiveAxis::Shape, AxisModel::Cost(Box::new(model)))
+    }
+
+    /// Owns `f_risk` with simulated reliability.
+    pub fn with_reliability_model(self, model: ReliabilityModel) -> Self {
+        self.own(ObjectiveAxis::Risk, AxisModel::Reliability(model))
+    }
+
+    /// Owns `axis` with `PerformanceModel::score`. There is no dedicated
+    /// performance dimension, so the axis's structural score is dropped.
+    pub fn with_performance_model(self, model: PerformanceModel, axis: ObjectiveAxis) -> Self {
+        self.own(axis, AxisModel::Performance(model))
+    }
+
+    /// Scores `axis` by how much of the tightest memory/power limit the
+    /// nodes' `memory_kb`/`power_mw` annotations leave free.
+    pub fn with_resource_budget(self, budget: ResourceBudget, axis: ObjectiveAxis) -> Self {
+        self.own(axis, AxisModel::ResourceBudget(budget))
+    }
+
+    /// Usage against each limit; `None` without a resource budget.
+    pub fn resource_checks(&self, state: &DesignState) -> Option<Vec<ResourceCheck>> {
+        self.models().find_map(|model| match model {
+            AxisModel::ResourceBudget(budget) => Some(budget.check(state)),
+            _ => None,
+        })
+    }
+
+    /// Latency/throughput estimate to check against `PerformanceTargets`;
+    /// `None` without a performance model.
+    pub fn performance_estimate(&self, state: &DesignState) -> Option<PerformanceEstimate> {
+        self.models().find_map(|model| match model {
+            AxisModel::Performance(model) => Some(model.estimate(state)),
+            _ => None,
+        })
+    }
+
+    /// Per-node criticality behind `f_risk`; `None` without a reliability model.
+    pub fn reliability_report(&self, state: &DesignState) -> Option<ReliabilityReport> {
+        self.models().find_map(|model| match model {
+            AxisModel::Reliability(model) => Some(model.analyze(state)),
+            _ => None,
         })
     }
 
@@ -36,7 +219,15 @@ impl<'a> SystemEvaluator<'a> {
 impl Evaluator for SystemEvaluator<'_> {
     fn evaluate(&self, state: &DesignState) -> ObjectiveVector {
         match self.vm.lock() {
-            Ok(mut vm) => vm.evaluate(state).clamped(),
+            Ok(mut vm) => {
+                let mut objective = vm.evaluate(state);
+                for axis in ObjectiveAxis::ALL {
+                    if let Some(model) = &self.axis_models[axis.index()] {
+                        axis.set(&mut objective, model.score(state));
+                    }
+                }
+                objective.clamped()
+            }
             Err(_) => ObjectiveVector {
                 f_struct: 0.0,
                 f_field: 0.0,
@@ -45,4 +236,17 @@ impl Evaluator for SystemEvaluator<'_> {
             },
         }
     }
+
+    /// The VM's structural breakdown, with each axis a model replaces
+    /// reduced to a single term naming that model.
+    fn explain(&self, state: &DesignState) -> Option<ObjectiveAttribution> {
+        let mut attribution = self.vm.lock().ok()?.explain(state);
+        for axis in ObjectiveAxis::ALL {
+            if let Some(model) = &self.axis_models[axis.index()] {
+                *attribution.axes_mut()[axis.index()] =
+                    vec![Contribution::new(model.name(), model.score(state))];
+            }
+        }
+        Some(attribution)
+    }
 }
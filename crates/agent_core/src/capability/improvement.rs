@@ -0,0 +1,77 @@
+//! Progress measured against the first depth that produced a front.
+//!
+//! Raw objective values mean little to someone who did not design the
+//! evaluator; "hypervolume up 40% since depth 1" does. `ImprovementTracker`
+//! captures that first front as a baseline and reports every later depth as
+//! a change from it.
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImprovementBaseline {
+    pub depth: usize,
+    /// Front hypervolume over raw objectives.
+    pub hypervolume: f64,
+    /// Highest `scalar_score` on the front.
+    pub best_score: f64,
+}
+
+/// Reported in `SearchCoreResult::improvement`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImprovementSummary {
+    pub baseline: ImprovementBaseline,
+    /// Values at the last depth that produced a front.
+    pub last: ImprovementBaseline,
+}
+
+impl ImprovementSummary {
+    pub fn hv_improvement(&self) -> f64 {
+        self.last.hypervolume - self.baseline.hypervolume
+    }
+
+    pub fn best_score_improvement(&self) -> f64 {
+        self.last.best_score - self.baseline.best_score
+    }
+
+    /// Hypervolume gain as a fraction of the baseline; `None` when the
+    /// baseline front had no volume.
+    pub fn relative_hv_improvement(&self) -> Option<f64> {
+        (self.baseline.hypervolume > 0.0).then(|| self.hv_improvement() / self.baseline.hypervolume)
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ImprovementTracker {
+    baseline: Option<ImprovementBaseline>,
+    last: Option<ImprovementBaseline>,
+}
+
+impl ImprovementTracker {
+    /// Records a depth's front and returns its `(hypervolume, best_score)`
+    /// change from the baseline, which the first call sets.
+    pub fn observe(&mut self, depth: usize, hypervolume: f64, best_score: f64) -> (f64, f64) {
+        let current = ImprovementBaseline {
+            depth,
+            hypervolume,
+            best_score,
+        };
+        let baseline = *self.baseline.get_or_insert(current);
+        self.last = Some(current);
+        (
+            hypervolume - baseline.hypervolume,
+            best_score - baseline.best_score,
+        )
+    }
+
+    pub fn baseline(&self) -> Option<ImprovementBaseline> {
+        self.baseline
+    }
+
+    /// `None` until a depth has been observed.
+    pub fn summary(&self) -> Option<ImprovementSummary> {
+        Some(ImprovementSummary {
+            baseline: self.baseline?,
+            last: self.last?,
+        })
+    }
+}
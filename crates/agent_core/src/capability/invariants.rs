@@ -0,0 +1,173 @@
+//! Run-time invariant checks for `SearchConfig::validate_invariants`.
+//!
+//! The beam relies on a few properties its tests pin down: the weak-axis
+//! weight `norm_alpha` (λ) lies in `[0, 1]`, normalized objectives stay in
+//! `[0, 1]`, each ranked front is in rank order, and no state sits in the
+//! beam twice. A custom evaluator or rule set can break them without any
+//! test noticing, so the search can recheck them every depth and record
+//! what it finds as diagnostics instead of panicking.
+
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+
+use core_types::ObjectiveVector;
+use memory_space::{DesignState, StateId};
+
+use crate::engine::normalization::soft_dominance_scores;
+use crate::{EpsilonConstraint, ObjectiveAxis, ObjectiveDirections};
+
+/// Slack for comparing recomputed ranking scores.
+const ORDER_EPSILON: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Invariant {
+    /// `SearchConfig::norm_alpha` outside `[0, 1]`.
+    NormAlphaRange,
+    /// A normalized objective outside `[0, 1]`.
+    NormalizedRange,
+    /// A ranked front out of rank order.
+    FrontOrder,
+    /// A state id more than once in the beam.
+    UniqueStateIds,
+}
+
+impl Invariant {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::NormAlphaRange => "norm_alpha_range",
+            Self::NormalizedRange => "normalized_range",
+            Self::FrontOrder => "front_order",
+            Self::UniqueStateIds => "unique_state_ids",
+        }
+    }
+}
+
+/// One failed check.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InvariantViolation {
+    pub depth: usize,
+    pub invariant: Invariant,
+    /// The offending state, when the check is about one.
+    pub state_id: Option<StateId>,
+    pub detail: String,
+}
+
+impl Display for InvariantViolation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "depth {}: {}", self.depth, self.invariant.name())?;
+        if let Some(state_id) = self.state_id {
+            write!(f, " at state {:032x}", state_id.as_u128())?;
+        }
+        write!(f, ": {}", self.detail)
+    }
+}
+
+pub fn check_norm_alpha(depth: usize, norm_alpha: f64) -> Option<InvariantViolation> {
+    (!(0.0..=1.0).contains(&norm_alpha)).then(|| InvariantViolation {
+        depth,
+        invariant: Invariant::NormAlphaRange,
+        state_id: None,
+        detail: format!("norm_alpha {norm_alpha} is outside [0, 1]"),
+    })
+}
+
+/// One violation per candidate with an axis outside `[0, 1]`.
+pub fn check_normalized(
+    depth: usize,
+    normalized: &[(DesignState, ObjectiveVector)],
+) -> Vec<InvariantViolation> {
+    let axes = [
+        ObjectiveAxis::Struct,
+        ObjectiveAxis::Field,
+        ObjectiveAxis::Risk,
+        ObjectiveAxis::Shape,
+    ];
+    normalized
+        .iter()
+        .filter_map(|(state, objective)| {
+            // NaN fails the range check too.
+            let outside = axes
+                .into_iter()
+                .filter(|axis| !(0.0..=1.0).contains(&axis.value(objective)))
+                .collect::<Vec<_>>();
+            (!outside.is_empty()).then(|| InvariantViolation {
+                depth,
+                invariant: Invariant::NormalizedRange,
+                state_id: Some(state.id),
+                detail: format!("{outside:?} outside [0, 1]"),
+            })
+        })
+        .collect()
+}
+
+/// `ranked` is in `soft_front_rank` order: soft-dominance scores, recomputed
+/// at `temperature`, never increase. Reports the first state that outranks
+/// its predecessor.
+pub fn check_soft_front_order(
+    depth: usize,
+    ranked: &[(DesignState, ObjectiveVector)],
+    temperature: f64,
+) -> Option<InvariantViolation> {
+    let objectives = ranked.iter().map(|(_, o)| o.clone()).collect::<Vec<_>>();
+    let scores = soft_dominance_scores(&objectives, temperature);
+    let at = scores
+        .windows(2)
+        .position(|pair| pair[1] > pair[0] + ORDER_EPSILON)?;
+    Some(InvariantViolation {
+        depth,
+        invariant: Invariant::FrontOrder,
+        state_id: Some(ranked[at + 1].0.id),
+        detail: format!(
+            "soft-dominance score {:.6} at position {} exceeds {:.6} before it",
+            scores[at + 1],
+            at + 1,
+            scores[at]
+        ),
+    })
+}
+
+/// `ranked` is in `epsilon_constraint_rank` order: violation ascending,
+/// then the optimized axis under `directions` descending.
+pub fn check_constrained_order(
+    depth: usize,
+    ranked: &[(DesignState, ObjectiveVector)],
+    constraint: &EpsilonConstraint,
+    directions: ObjectiveDirections,
+) -> Option<InvariantViolation> {
+    let key = |o: &ObjectiveVector| constraint.key(o, directions);
+    let at = ranked.windows(2).position(|pair| {
+        let (prev_violation, prev_value) = key(&pair[0].1);
+        let (violation, value) = key(&pair[1].1);
+        violation < prev_violation || (violation == prev_violation && value > prev_value)
+    })?;
+    Some(InvariantViolation {
+        depth,
+        invariant: Invariant::FrontOrder,
+        state_id: Some(ranked[at + 1].0.id),
+        detail: format!(
+            "position {} ranks ahead of position {} under the epsilon constraint",
+            at + 1,
+            at
+        ),
+    })
+}
+
+/// One violation per state id repeated in `beam`.
+pub fn check_unique_ids(depth: usize, beam: &[DesignState]) -> Vec<InvariantViolation> {
+    let mut seen = BTreeSet::new();
+    let mut reported = BTreeSet::new();
+    beam.iter()
+        .filter(|state| !seen.insert(state.id) && reported.insert(state.id))
+        .map(|state| InvariantViolation {
+            depth,
+            invariant: Invariant::UniqueStateIds,
+            state_id: Some(state.id),
+            detail: format!(
+                "state appears {} times in the beam",
+                beam.iter().filter(|s| s.id == state.id).count()
+            ),
+        })
+        .collect()
+}
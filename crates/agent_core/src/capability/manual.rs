@@ -0,0 +1,208 @@
+//! Human-driven search: each depth offers every rule application on the
+//! current beam with a preview, and the caller picks what survives.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use field_engine::{FieldEngine, TargetField};
+use hybrid_vm::{HybridVM, RuleId};
+use memory_space::StateId;
+
+use super::beam::{AnytimeSearch, record_attribution};
+use super::preview::{PreviewContext, RulePreview, preview_rule};
+use crate::capability::search_tree::SearchTreeNode;
+use crate::capability::state_archive::StateOrigin;
+
+#[derive(Clone, Debug)]
+pub struct ManualCandidate {
+    /// Beam state the rule would be applied to.
+    pub parent: StateId,
+    pub preview: RulePreview,
+}
+
+/// Candidates for one depth, from `AnytimeSearch::propose`.
+#[derive(Clone, Debug)]
+pub struct ManualProposal {
+    pub depth: usize,
+    /// Beam order, then rule order within each parent.
+    pub candidates: Vec<ManualCandidate>,
+}
+
+/// What was offered at a depth and what the caller kept.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ManualChoice {
+    pub depth: usize,
+    /// Every offered state, in proposal order.
+    pub offered: Vec<StateId>,
+    /// Kept states, in the order the caller listed them.
+    pub selected: Vec<StateId>,
+    /// Rules that produced `selected`, aligned with it.
+    pub rules: Vec<RuleId>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ManualSelectionError {
+    /// The proposal was not made for the next depth of this run.
+    StaleProposal {
+        expected: usize,
+        found: usize,
+    },
+    EmptySelection,
+    UnknownCandidate(usize),
+    Finished,
+}
+
+impl std::fmt::Display for ManualSelectionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::StaleProposal { expected, found } => {
+                write!(f, "proposal is for depth {found}, expected {expected}")
+            }
+            Self::EmptySelection => write!(f, "at least one candidate must be selected"),
+            Self::UnknownCandidate(index) => write!(f, "no candidate at index {index}"),
+            Self::Finished => write!(f, "search is already finished"),
+        }
+    }
+}
+
+impl std::error::Error for ManualSelectionError {}
+
+impl AnytimeSearch<'_, '_> {
+    /// Previews every applicable rule on every beam state for the next
+    /// depth. Objectives come from a single evaluation regardless of
+    /// `SearchConfig::evaluation`. Empty once the run is finished or at a
+    /// dead end.
+    pub fn propose(&self, field: &FieldEngine, target: &TargetField) -> ManualProposal {
+        let depth = self.depth + 1;
+        if self.is_finished() {
+            return ManualProposal {
+                depth,
+                candidates: Vec::new(),
+            };
+        }
+        let search = self.search;
+        let ctx = PreviewContext {
+            evaluator: search.evaluator,
+            field,
+            target,
+            chm: search.chm,
+        };
+        let candidates = self
+            .frontier
+            .iter()
+            .flat_map(|state| {
+                HybridVM::applicable_rules(search.shm, state)
+                    .into_iter()
+                    .filter_map(|rule| {
+                        let preview = preview_rule(state, rule, &ctx).ok()?;
+                        Some(ManualCandidate {
+                            parent: state.id,
+                            preview,
+                        })
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        ManualProposal { depth, candidates }
+    }
+
+    /// Advances one depth keeping exactly the `selected` candidates (by
+    /// index into the proposal, duplicates ignored) and records the choice.
+    pub fn select(
+        &mut self,
+        proposal: ManualProposal,
+        selected: &[usize],
+    ) -> Result<ManualChoice, ManualSelectionError> {
+        if self.is_finished() {
+            return Err(ManualSelectionError::Finished);
+        }
+        let expected = self.depth + 1;
+        if proposal.depth != expected {
+            return Err(ManualSelectionError::StaleProposal {
+                expected,
+                found: proposal.depth,
+            });
+        }
+        if selected.is_empty() {
+            return Err(ManualSelectionError::EmptySelection);
+        }
+        if let Some(&index) = selected.iter().find(|&&i| i >= proposal.candidates.len()) {
+            return Err(ManualSelectionError::UnknownCandidate(index));
+        }
+
+        let depth = proposal.depth;
+        let mut seen = BTreeSet::new();
+        let order = selected
+            .iter()
+            .copied()
+            .filter(|i| seen.insert(*i))
+            .collect::<Vec<_>>();
+        if let Some(tree) = self.tree.as_mut() {
+            for candidate in &proposal.candidates {
+                tree.record(SearchTreeNode {
+                    state_id: candidate.preview.state.id,
+                    parent: Some(candidate.parent),
+                    rule_id: Some(candidate.preview.rule_id),
+                    depth,
+                    objective: candidate.preview.objective_after.clone(),
+                    kept: false,
+                });
+            }
+        }
+        let raw = proposal
+            .candidates
+            .iter()
+            .map(|c| (c.preview.state.id, c.preview.objective_after.clone()))
+            .collect::<BTreeMap<_, _>>();
+        let choice = ManualChoice {
+            depth,
+            offered: proposal
+                .candidates
+                .iter()
+                .map(|c| c.preview.state.id)
+                .collect(),
+            selected: order
+                .iter()
+                .map(|&i| proposal.candidates[i].preview.state.id)
+                .collect(),
+            rules: order
+                .iter()
+                .map(|&i| proposal.candidates[i].preview.rule_id)
+                .collect(),
+        };
+        if self.search.config.explain {
+            for &i in &order {
+                let preview = &proposal.candidates[i].preview;
+                record_attribution(
+                    &mut self.attributions,
+                    self.search,
+                    &preview.state,
+                    &preview.objective_after,
+                );
+            }
+        }
+        let frontier = order
+            .iter()
+            .map(|&i| proposal.candidates[i].preview.state.clone())
+            .collect();
+        let origins = proposal
+            .candidates
+            .iter()
+            .map(|c| {
+                let origin = StateOrigin {
+                    parent: Some(c.parent),
+                    rule_id: Some(c.preview.rule_id),
+                };
+                (c.preview.state.id, origin)
+            })
+            .collect();
+        self.advance(depth, frontier, &raw, &BTreeSet::new(), &origins);
+        self.manual_choices.push(choice.clone());
+        Ok(choice)
+    }
+
+    /// Choices made so far through `select`.
+    pub fn manual_choices(&self) -> &[ManualChoice] {
+        &self.manual_choices
+    }
+}
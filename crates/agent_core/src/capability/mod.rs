@@ -1,17 +1,91 @@
+pub mod alpha_trace;
 pub mod apply;
 pub mod beam;
+pub mod budget;
+pub mod calibration;
+pub mod convergence;
+pub mod crossover;
+pub mod dispatch;
+pub mod diversity_schedule;
+pub mod elicitation;
+pub mod equivalence;
 pub mod evaluation;
+pub mod improvement;
+pub mod invariants;
+pub mod manual;
 pub mod memory;
+pub mod novelty;
+pub mod performance;
+pub mod preview;
+pub mod ranking_cache;
+pub mod reliability;
+pub mod repair;
+pub mod rewrite;
+pub mod rng;
+pub mod rule_sampling;
+pub mod sanitize;
 pub mod scoring;
 pub mod search;
+pub mod search_tree;
 pub mod selection;
 pub mod simulation;
+pub mod state_archive;
+pub mod suggestion;
 
-pub use evaluation::EvaluationCapability;
+pub use alpha_trace::{
+    AlphaBranch, AlphaConfig, AlphaDecision, AlphaInputs, AlphaSteps, AlphaTrace, decide_alpha,
+};
+pub use budget::{BudgetHandle, BudgetPolicy, BudgetPool, BudgetStats, PRIORITY_LEVELS};
+pub use calibration::{
+    CalibrationConfig, CalibrationReport, RuleCalibration, calibrate_effects, sample_corpus,
+};
+pub use convergence::{ConvergenceConfig, ConvergenceMonitor, ConvergenceReason};
+pub use crossover::{CrossoverStats, recombine};
+pub use dispatch::{
+    AsyncEvaluator, DispatchConfig, DispatchStats, EvaluationDispatcher, EvaluationError,
+    EvaluationJob, InfallibleEvaluator,
+};
+pub use diversity_schedule::{
+    DiversityController, DiversityPressure, DiversitySchedule, DiversityTrigger, collapse_signal,
+};
+pub use elicitation::{ElicitationConfig, PairwisePreference, PairwiseQuery, PreferenceElicitor};
+pub use equivalence::{EquivalenceReducer, canonical_attribute_key, equivalence_hash};
+pub use evaluation::{
+    EvaluationCapability, PolicyEvaluation, evaluate_child_with_policy, evaluate_with_policy,
+};
+pub use improvement::{ImprovementBaseline, ImprovementSummary, ImprovementTracker};
+pub use invariants::{
+    Invariant, InvariantViolation, check_constrained_order, check_norm_alpha, check_normalized,
+    check_soft_front_order, check_unique_ids,
+};
+pub use manual::{ManualCandidate, ManualChoice, ManualProposal, ManualSelectionError};
 pub use memory::MemoryCapability;
+pub use novelty::{NoveltyArchive, NoveltyConfig};
+pub use performance::{
+    PerformanceEstimate, PerformanceEvaluator, PerformanceModel, PerformanceTargets,
+    PerformanceViolation, evaluate_hypothesis_with_performance,
+};
+pub use preview::{PreviewContext, RulePreview, preview_rule};
+pub use ranking_cache::{
+    DEFAULT_RANKING_CACHE_CAPACITY, RankingCacheStats, RankingKey, RuleRankingCache,
+};
+pub use reliability::{NodeCriticality, ReliabilityModel, ReliabilityReport};
+pub use repair::{RepairConfig, RepairStats, apply_atomic_repaired, repair};
+pub use rewrite::{NodeMatch, Production, RewriteEngine, RewriteError, RewriteRule, Slot};
+pub use rng::{RngStream, philox4x32};
+pub use rule_sampling::{
+    BoltzmannSelection, CategorySoftSampling, CategorySoftSelection, SamplingContext,
+    SelectionStrategy, StratifiedSelection, TournamentSelection, selection_strategy,
+};
+pub use sanitize::{QuarantinedCandidate, non_finite_axes, quarantine};
 pub use scoring::{LinearObjectiveScorer, ScoringCapability};
 pub use search::{
     SearchCapability, SearchCoreResult, SearchHit, execute_balanced_core,
     execute_baseline_off_core, execute_soft_search_core, execute_trace_core, rank_hits_with_scorer,
 };
+pub use search_tree::{DEFAULT_MAX_TREE_NODES, SearchTree, SearchTreeNode};
 pub use simulation::SimulationCapability;
+pub use state_archive::{ArchivedState, SimilarState, StateArchive, StateOrigin, canonical_hash};
+pub use suggestion::{
+    RuleSuggester, SuggestedRule, SuggestionConfig, SuggestionOutcome, SuggestionRejection,
+};
@@ -0,0 +1,137 @@
+//! Novelty archive for trace runs: field projections of every accepted
+//! state, so beam selection can favour structures unlike anything the run
+//! has already kept.
+
+use std::collections::VecDeque;
+
+use core_types::ObjectiveVector;
+use field_engine::{FieldEngine, FieldVector};
+use memory_space::DesignState;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoveltyConfig {
+    /// Nearest archive entries averaged into a novelty score.
+    pub k: usize,
+    /// Share of the acceptance score given to novelty; 0 only records
+    /// archive statistics and leaves selection unchanged.
+    pub weight: f64,
+    /// Oldest entries are dropped beyond this many.
+    pub capacity: usize,
+}
+
+impl Default for NoveltyConfig {
+    fn default() -> Self {
+        Self {
+            k: 5,
+            weight: 0.3,
+            capacity: 1000,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct NoveltyArchive {
+    config: NoveltyConfig,
+    entries: VecDeque<FieldVector>,
+}
+
+impl NoveltyArchive {
+    pub fn new(config: NoveltyConfig) -> Self {
+        Self {
+            config,
+            entries: VecDeque::new(),
+        }
+    }
+
+    pub fn config(&self) -> NoveltyConfig {
+        self.config
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Mean distance from `projection` to its `k` nearest archive entries;
+    /// 0 for an empty archive.
+    pub fn novelty(&self, projection: &FieldVector) -> f64 {
+        let mut distances = self
+            .entries
+            .iter()
+            .map(|entry| crate::diversity::l2_distance(entry, projection))
+            .collect::<Vec<_>>();
+        if distances.is_empty() {
+            return 0.0;
+        }
+        distances.sort_by(f64::total_cmp);
+        let k = self.config.k.clamp(1, distances.len());
+        distances[..k].iter().sum::<f64>() / k as f64
+    }
+
+    pub fn insert(&mut self, projection: FieldVector) {
+        if self.config.capacity == 0 {
+            return;
+        }
+        self.entries.push_back(projection);
+        while self.entries.len() > self.config.capacity {
+            self.entries.pop_front();
+        }
+    }
+
+    /// Keeps the `beam` candidates with the highest
+    /// `(1 - weight) * scalar_score + weight * novelty`, novelty scaled by
+    /// the largest novelty among the candidates. Ties keep input order.
+    pub fn select(
+        &self,
+        candidates: Vec<(DesignState, ObjectiveVector)>,
+        field: &FieldEngine,
+        beam: usize,
+    ) -> Vec<(DesignState, ObjectiveVector)> {
+        let weight = self.config.weight.clamp(0.0, 1.0);
+        let novelty = candidates
+            .iter()
+            .map(|(state, _)| self.novelty(&field.aggregate_state(state)))
+            .collect::<Vec<_>>();
+        let max_novelty = novelty.iter().copied().fold(0.0, f64::max);
+        let mut scored = candidates
+            .into_iter()
+            .zip(novelty)
+            .map(|((state, objective), novelty)| {
+                let scaled = if max_novelty > 0.0 {
+                    novelty / max_novelty
+                } else {
+                    0.0
+                };
+                let score = (1.0 - weight) * crate::scalar_score(&objective) + weight * scaled;
+                (score, state, objective)
+            })
+            .collect::<Vec<_>>();
+        scored.sort_by(|a, b| b.0.total_cmp(&a.0));
+        scored
+            .into_iter()
+            .take(beam)
+            .map(|(_, state, objective)| (state, objective))
+            .collect()
+    }
+
+    /// Archives the projections of `accepted` and returns their mean
+    /// novelty against the archive as it was before.
+    pub fn accept(&mut self, accepted: &[DesignState], field: &FieldEngine) -> f64 {
+        if accepted.is_empty() {
+            return 0.0;
+        }
+        let projections = accepted
+            .iter()
+            .map(|state| field.aggregate_state(state))
+            .collect::<Vec<_>>();
+        let mean = projections.iter().map(|p| self.novelty(p)).sum::<f64>() / accepted.len() as f64;
+        for projection in projections {
+            self.insert(projection);
+        }
+        mean
+    }
+}
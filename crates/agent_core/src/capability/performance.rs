@@ -0,0 +1,307 @@
+//! Topology-based latency and throughput estimates.
+//!
+//! Latency is the critical (longest) path from an entry point through its
+//! dependencies, summing per-kind service times inflated by fan-in queueing.
+//! Throughput is the request rate per entry point the most loaded node can
+//! sustain; load splits evenly across same-kind replicas behind one caller.
+
+use std::collections::BTreeMap;
+
+use core_types::ObjectiveVector;
+use hybrid_vm::{
+    BudgetCheck, DesignHypothesis, DesignProjection, Evaluator, HybridVM, RequirementKind,
+    SemanticError,
+};
+use memory_space::{DesignNode, DesignState, NodeId, Value};
+
+use super::reliability::{dependencies_first, dependency_groups};
+use crate::ObjectiveAxis;
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PerformanceModel {
+    pub service_times_ms: BTreeMap<String, f64>,
+    pub default_service_time_ms: f64,
+    /// Requests per second one instance sustains; scaled by `replicas`.
+    pub capacities_rps: BTreeMap<String, f64>,
+    pub default_capacity_rps: f64,
+    /// Service time grows by this fraction per caller beyond the first.
+    pub fan_in_penalty: f64,
+    /// Latency scoring 0; the latency half of `score` is linear below it.
+    pub latency_budget_ms: f64,
+    /// Throughput scoring 1; the throughput half of `score` saturates there.
+    pub throughput_target_rps: f64,
+}
+
+impl Default for PerformanceModel {
+    fn default() -> Self {
+        Self {
+            service_times_ms: BTreeMap::from([
+                ("Database".to_string(), 8.0),
+                ("Cache".to_string(), 0.5),
+                ("Queue".to_string(), 2.0),
+                ("LoadBalancer".to_string(), 0.3),
+                ("Service".to_string(), 4.0),
+            ]),
+            default_service_time_ms: 2.0,
+            capacities_rps: BTreeMap::from([
+                ("Database".to_string(), 500.0),
+                ("Cache".to_string(), 20_000.0),
+                ("Queue".to_string(), 5_000.0),
+                ("LoadBalancer".to_string(), 50_000.0),
+                ("Service".to_string(), 1_000.0),
+            ]),
+            default_capacity_rps: 2_000.0,
+            fan_in_penalty: 0.25,
+            latency_budget_ms: 200.0,
+            throughput_target_rps: 1_000.0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PerformanceEstimate {
+    pub latency_ms: f64,
+    /// Sustainable request rate per entry point; infinite for an empty graph.
+    pub throughput_rps: f64,
+    /// Entry point first.
+    pub critical_path: Vec<NodeId>,
+    pub bottleneck: Option<NodeId>,
+}
+
+/// Quantitative limits; `None` leaves a dimension unchecked.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PerformanceTargets {
+    pub max_latency_ms: Option<f64>,
+    pub min_throughput_rps: Option<f64>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PerformanceViolation {
+    Latency { estimated_ms: f64, limit_ms: f64 },
+    Throughput { estimated_rps: f64, limit_rps: f64 },
+}
+
+impl PerformanceTargets {
+    pub fn violations(&self, estimate: &PerformanceEstimate) -> Vec<PerformanceViolation> {
+        let mut out = Vec::new();
+        if let Some(limit) = self.max_latency_ms
+            && estimate.latency_ms > limit
+        {
+            out.push(PerformanceViolation::Latency {
+                estimated_ms: estimate.latency_ms,
+                limit_ms: limit,
+            });
+        }
+        if let Some(limit) = self.min_throughput_rps
+            && estimate.throughput_rps < limit
+        {
+            out.push(PerformanceViolation::Throughput {
+                estimated_rps: estimate.throughput_rps,
+                limit_rps: limit,
+            });
+        }
+        out
+    }
+
+    pub fn is_met(&self, estimate: &PerformanceEstimate) -> bool {
+        self.violations(estimate).is_empty()
+    }
+
+    /// The targets as hypothesis checks on the Performance requirement.
+    /// Throughput is checked as milliseconds per request at the bottleneck
+    /// (`1000 / rps`), so a rate below its target reads as an exceeded
+    /// budget like a latency over its limit.
+    pub fn budget_checks(&self, estimate: &PerformanceEstimate) -> Vec<BudgetCheck> {
+        let check = |resource: &str, used: f64, limit: f64| BudgetCheck {
+            kind: Some(RequirementKind::Performance),
+            resource: resource.to_string(),
+            used,
+            limit,
+        };
+        let per_request_ms = |rps: f64| {
+            if rps > 0.0 {
+                1000.0 / rps
+            } else {
+                f64::INFINITY
+            }
+        };
+        let mut out = Vec::new();
+        if let Some(limit) = self.max_latency_ms {
+            out.push(check("latency_ms", estimate.latency_ms, limit));
+        }
+        if let Some(limit) = self.min_throughput_rps {
+            out.push(check(
+                "ms_per_request",
+                per_request_ms(estimate.throughput_rps),
+                per_request_ms(limit),
+            ));
+        }
+        out
+    }
+}
+
+/// `HybridVM::evaluate_hypothesis_for_state` with `state`'s estimated
+/// latency and throughput checked against `targets`: an unmet target
+/// violates the Performance requirement even when the projection favors
+/// it, and meeting every target clears it.
+pub fn evaluate_hypothesis_with_performance(
+    vm: &HybridVM,
+    projection: &DesignProjection,
+    state: &DesignState,
+    model: &PerformanceModel,
+    targets: &PerformanceTargets,
+) -> Result<DesignHypothesis, SemanticError> {
+    let checks = targets.budget_checks(&model.estimate(state));
+    vm.evaluate_hypothesis_with_checks(projection, state, &checks)
+}
+
+impl PerformanceModel {
+    pub fn service_time_ms(&self, node: &DesignNode, callers: usize) -> f64 {
+        let base = self
+            .service_times_ms
+            .get(&node.kind)
+            .copied()
+            .unwrap_or(self.default_service_time_ms);
+        base * (1.0 + self.fan_in_penalty * callers.saturating_sub(1) as f64)
+    }
+
+    pub fn capacity_rps(&self, node: &DesignNode) -> f64 {
+        let unit = self
+            .capacities_rps
+            .get(&node.kind)
+            .copied()
+            .unwrap_or(self.default_capacity_rps);
+        let replicas = match node.attributes.get("replicas") {
+            Some(Value::Int(n)) => (*n).max(1) as f64,
+            Some(Value::Float(n)) => n.max(1.0),
+            _ => 1.0,
+        };
+        unit * replicas
+    }
+
+    pub fn estimate(&self, state: &DesignState) -> PerformanceEstimate {
+        let graph = &state.graph;
+        let order = dependencies_first(graph);
+        let groups = dependency_groups(graph);
+        let mut callers: BTreeMap<NodeId, usize> = BTreeMap::new();
+        for (_, to) in graph.edges() {
+            *callers.entry(*to).or_default() += 1;
+        }
+        let fan_in = |id: &NodeId| callers.get(id).copied().unwrap_or(0);
+
+        // Longest path to a sink, dependencies first.
+        let mut path_ms: BTreeMap<NodeId, (f64, Option<NodeId>)> = BTreeMap::new();
+        for id in &order {
+            let Some(node) = graph.nodes().get(id) else {
+                continue;
+            };
+            let next = groups
+                .get(id)
+                .into_iter()
+                .flatten()
+                .flatten()
+                .filter_map(|dep| path_ms.get(dep).map(|(ms, _)| (*ms, *dep)))
+                .max_by(|a, b| a.0.total_cmp(&b.0).then(b.1.cmp(&a.1)));
+            let own = self.service_time_ms(node, fan_in(id));
+            path_ms.insert(
+                *id,
+                (own + next.map_or(0.0, |(ms, _)| ms), next.map(|(_, d)| d)),
+            );
+        }
+        let entries = order
+            .iter()
+            .filter(|id| fan_in(id) == 0)
+            .copied()
+            .collect::<Vec<_>>();
+        let start = entries
+            .iter()
+            .filter_map(|id| path_ms.get(id).map(|(ms, _)| (*ms, *id)))
+            .max_by(|a, b| a.0.total_cmp(&b.0).then(b.1.cmp(&a.1)));
+        let mut critical_path = Vec::new();
+        let mut cursor = start.map(|(_, id)| id);
+        while let Some(id) = cursor {
+            critical_path.push(id);
+            cursor = path_ms.get(&id).and_then(|(_, next)| *next);
+        }
+
+        // Unit load per entry point, propagated dependents first.
+        let mut load: BTreeMap<NodeId, f64> = entries.iter().map(|id| (*id, 1.0)).collect();
+        for id in order.iter().rev() {
+            let own = load.get(id).copied().unwrap_or(0.0);
+            for group in groups.get(id).into_iter().flatten() {
+                let share = own / group.len() as f64;
+                for dep in group {
+                    *load.entry(*dep).or_default() += share;
+                }
+            }
+        }
+        let bottleneck = load
+            .iter()
+            .filter(|(_, l)| **l > 0.0)
+            .filter_map(|(id, l)| {
+                let node = graph.nodes().get(id)?;
+                Some((self.capacity_rps(node) / l, *id))
+            })
+            .min_by(|a, b| a.0.total_cmp(&b.0).then(a.1.cmp(&b.1)));
+
+        PerformanceEstimate {
+            latency_ms: start.map_or(0.0, |(ms, _)| ms),
+            throughput_rps: bottleneck.map_or(f64::INFINITY, |(rps, _)| rps),
+            critical_path,
+            bottleneck: bottleneck.map(|(_, id)| id),
+        }
+    }
+
+    /// Mean of a latency score (`1 - latency / budget`) and a throughput
+    /// score (`throughput / target`, capped), both in `[0, 1]`.
+    pub fn score(&self, estimate: &PerformanceEstimate) -> f64 {
+        let latency = if self.latency_budget_ms > 0.0 {
+            (1.0 - estimate.latency_ms / self.latency_budget_ms).clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let throughput = if self.throughput_target_rps > 0.0 {
+            (estimate.throughput_rps / self.throughput_target_rps).clamp(0.0, 1.0)
+        } else {
+            1.0
+        };
+        0.5 * (latency + throughput)
+    }
+}
+
+/// Wraps another evaluator and replaces one objective axis with the
+/// performance score. `ObjectiveVector` has no performance dimension, so
+/// `axis` gives up its base score; pick the one the search should trade
+/// for performance.
+pub struct PerformanceEvaluator<'a> {
+    pub base: &'a dyn Evaluator,
+    pub model: PerformanceModel,
+    pub axis: ObjectiveAxis,
+}
+
+impl PerformanceEvaluator<'_> {
+    fn with_performance(
+        &self,
+        mut objective: ObjectiveVector,
+        state: &DesignState,
+    ) -> ObjectiveVector {
+        self.axis.set(
+            &mut objective,
+            self.model.score(&self.model.estimate(state)),
+        );
+        objective.clamped()
+    }
+}
+
+impl Evaluator for PerformanceEvaluator<'_> {
+    fn evaluate(&self, state: &DesignState) -> ObjectiveVector {
+        self.with_performance(self.base.evaluate(state), state)
+    }
+
+    fn evaluate_child(&self, parent: &DesignState, child: &DesignState) -> ObjectiveVector {
+        self.with_performance(self.base.evaluate_child(parent, child), child)
+    }
+}
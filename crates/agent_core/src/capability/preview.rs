@@ -0,0 +1,81 @@
+//! Dry-run of a single rule: what `apply_atomic` would produce and how it
+//! would move the objectives, field resonance, and CHM risk, with nothing
+//! committed.
+
+use core_types::ObjectiveVector;
+use field_engine::{FieldEngine, TargetField, resonance_score};
+use hybrid_vm::{Chm, DesignRule, Evaluator, RuleId};
+use memory_space::{DesignState, GraphDiff};
+
+use super::apply::{ApplyError, apply_atomic, parse_rule_history};
+
+pub struct PreviewContext<'a> {
+    pub evaluator: &'a dyn Evaluator,
+    pub field: &'a FieldEngine,
+    pub target: &'a TargetField,
+    pub chm: &'a Chm,
+}
+
+#[derive(Clone, Debug)]
+pub struct RulePreview {
+    pub rule_id: RuleId,
+    /// The preview is computed either way; manual callers decide whether an
+    /// unmet precondition blocks the commit.
+    pub precondition_met: bool,
+    /// The state `apply_atomic` would return.
+    pub state: DesignState,
+    pub diff: GraphDiff,
+    pub objective_before: ObjectiveVector,
+    pub objective_after: ObjectiveVector,
+    pub resonance_before: f64,
+    pub resonance_after: f64,
+    /// Mean `-strength` of CHM edges from rules already in the state's
+    /// history to this rule; positive when the rule has historically
+    /// conflicted with what was applied before. 0 without such edges.
+    pub chm_risk_delta: f64,
+}
+
+impl RulePreview {
+    /// `after - before` per axis.
+    pub fn objective_delta(&self) -> ObjectiveVector {
+        ObjectiveVector {
+            f_struct: self.objective_after.f_struct - self.objective_before.f_struct,
+            f_field: self.objective_after.f_field - self.objective_before.f_field,
+            f_risk: self.objective_after.f_risk - self.objective_before.f_risk,
+            f_shape: self.objective_after.f_shape - self.objective_before.f_shape,
+        }
+    }
+
+    pub fn resonance_delta(&self) -> f64 {
+        self.resonance_after - self.resonance_before
+    }
+}
+
+pub fn preview_rule(
+    state: &DesignState,
+    rule: &DesignRule,
+    ctx: &PreviewContext,
+) -> Result<RulePreview, ApplyError> {
+    let next = apply_atomic(rule, state)?;
+    let strengths = parse_rule_history(&state.profile_snapshot)
+        .into_iter()
+        .filter_map(|prev| ctx.chm.strength(prev, rule.id))
+        .collect::<Vec<_>>();
+    let chm_risk_delta = if strengths.is_empty() {
+        0.0
+    } else {
+        -strengths.iter().sum::<f64>() / strengths.len() as f64
+    };
+
+    Ok(RulePreview {
+        rule_id: rule.id,
+        precondition_met: rule.precondition.evaluate(state),
+        diff: GraphDiff::between(&state.graph, &next.graph),
+        objective_before: ctx.evaluator.evaluate(state),
+        objective_after: ctx.evaluator.evaluate_child(state, &next),
+        resonance_before: resonance_score(&ctx.field.aggregate_state(state), ctx.target),
+        resonance_after: resonance_score(&ctx.field.aggregate_state(&next), ctx.target),
+        chm_risk_delta,
+        state: next,
+    })
+}
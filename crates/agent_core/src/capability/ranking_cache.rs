@@ -0,0 +1,193 @@
+//! Memoized rule rankings.
+//!
+//! Many frontier states share the same applicable rules, and a
+//! `SelectionStrategy` that ignores its sampling context (see
+//! `SelectionStrategy::ignores_context`) ranks an identical set
+//! identically. `RuleRankingCache` stores the ranking under the set's
+//! signature plus the field and lambda levels it was computed at, bucketed so
+//! that nearby levels share an entry. Rule priorities are part of the
+//! signature, so re-weighting rules never serves a stale ranking; profile
+//! updates that change what a ranking means without touching the rules call
+//! `on_profile_update`.
+//!
+//! A cache belongs to one strategy instance: the strategy's own parameters
+//! are not part of the key.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use hybrid_vm::{DesignRule, RuleId};
+
+use super::rng::mix64;
+use crate::ProfileUpdateType;
+
+pub const DEFAULT_RANKING_CACHE_CAPACITY: usize = 1024;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct RankingKey {
+    /// Order-independent hash of the rule ids, priorities and categories.
+    pub rules: u64,
+    pub max_select: usize,
+    pub field_bucket: i64,
+    pub lambda_bucket: i64,
+}
+
+/// Counters reported in `SearchCoreResult::ranking_cache`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RankingCacheStats {
+    pub hits: usize,
+    pub misses: usize,
+    /// Times the cache was cleared by `on_profile_update`.
+    pub invalidations: usize,
+}
+
+impl RankingCacheStats {
+    /// `hits / (hits + misses)`; 0 before the first lookup.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+
+    pub fn absorb(&mut self, other: RankingCacheStats) {
+        self.hits += other.hits;
+        self.misses += other.misses;
+        self.invalidations += other.invalidations;
+    }
+}
+
+pub struct RuleRankingCache {
+    capacity: usize,
+    field_step: f64,
+    lambda_step: f64,
+    entries: BTreeMap<RankingKey, Vec<RuleId>>,
+    /// Insertion order, oldest first, for eviction.
+    order: VecDeque<RankingKey>,
+    stats: RankingCacheStats,
+}
+
+impl Default for RuleRankingCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_RANKING_CACHE_CAPACITY)
+    }
+}
+
+impl RuleRankingCache {
+    /// Buckets field and lambda levels in steps of 0.05.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            field_step: 0.05,
+            lambda_step: 0.05,
+            entries: BTreeMap::new(),
+            order: VecDeque::new(),
+            stats: RankingCacheStats::default(),
+        }
+    }
+
+    /// Bucket widths for the field and lambda levels; non-positive widths
+    /// put every level in one bucket.
+    pub fn with_buckets(mut self, field_step: f64, lambda_step: f64) -> Self {
+        self.field_step = field_step;
+        self.lambda_step = lambda_step;
+        self
+    }
+
+    pub fn key(
+        &self,
+        rules: &[&DesignRule],
+        max_select: usize,
+        field: f64,
+        lambda: f64,
+    ) -> RankingKey {
+        RankingKey {
+            rules: rule_set_signature(rules),
+            max_select,
+            field_bucket: bucket(field, self.field_step),
+            lambda_bucket: bucket(lambda, self.lambda_step),
+        }
+    }
+
+    /// The cached ranking of `rules`, or `rank(rules)` stored for next time.
+    pub fn get_or_rank<'r>(
+        &mut self,
+        rules: Vec<&'r DesignRule>,
+        max_select: usize,
+        field: f64,
+        lambda: f64,
+        rank: impl FnOnce(Vec<&'r DesignRule>) -> Vec<&'r DesignRule>,
+    ) -> Vec<&'r DesignRule> {
+        let key = self.key(&rules, max_select, field, lambda);
+        if let Some(ids) = self.entries.get(&key) {
+            let by_id = rules
+                .iter()
+                .map(|rule| (rule.id, *rule))
+                .collect::<BTreeMap<_, _>>();
+            // A signature collision shows up as an id outside this set.
+            if let Some(ranked) = ids
+                .iter()
+                .map(|id| by_id.get(id).copied())
+                .collect::<Option<Vec<_>>>()
+            {
+                self.stats.hits += 1;
+                return ranked;
+            }
+        }
+        self.stats.misses += 1;
+        let ranked = rank(rules);
+        let ids = ranked.iter().map(|rule| rule.id).collect();
+        if self.entries.insert(key, ids).is_none() {
+            self.order.push_back(key);
+        }
+        while self.entries.len() > self.capacity {
+            let Some(oldest) = self.order.pop_front() else {
+                break;
+            };
+            self.entries.remove(&oldest);
+        }
+        ranked
+    }
+
+    /// Drops every ranking; they were computed under the old profile.
+    pub fn on_profile_update(&mut self, kind: ProfileUpdateType) {
+        let _ = kind;
+        self.entries.clear();
+        self.order.clear();
+        self.stats.invalidations += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn stats(&self) -> RankingCacheStats {
+        self.stats
+    }
+}
+
+fn bucket(value: f64, step: f64) -> i64 {
+    if step > 0.0 && value.is_finite() {
+        (value / step).floor() as i64
+    } else {
+        0
+    }
+}
+
+/// Sum of per-rule mixes, so the signature ignores list order.
+fn rule_set_signature(rules: &[&DesignRule]) -> u64 {
+    rules.iter().fold(rules.len() as u64, |acc, rule| {
+        let id = rule.id.as_u128();
+        let word = mix64(id as u64)
+            ^ mix64((id >> 64) as u64 ^ 0x9e37_79b9_7f4a_7c15)
+            ^ mix64(rule.priority.to_bits() ^ 0x6a09_e667_f3bc_c909)
+            ^ mix64(rule.category.clone() as u64 ^ 0xbb67_ae85_84ca_a73b);
+        acc.wrapping_add(mix64(word))
+    })
+}
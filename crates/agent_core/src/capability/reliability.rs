@@ -0,0 +1,250 @@
+//! Failure-propagation reliability analysis.
+//!
+//! An edge `from -> to` means `from` depends on `to`. A node is down when it
+//! fails on its own, or when every target of some outgoing kind group is
+//! down: targets of the same kind behind one caller are treated as redundant
+//! alternate paths. Entry points (nodes without callers) define availability.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use memory_space::{DesignState, NodeId, StructuralGraph};
+
+use super::rule_sampling::SplitMix64;
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReliabilityModel {
+    /// Independent per-trial failure probability by node kind.
+    pub failure_probabilities: BTreeMap<String, f64>,
+    pub default_failure_probability: f64,
+    pub trials: usize,
+    pub seed: u64,
+}
+
+impl Default for ReliabilityModel {
+    fn default() -> Self {
+        Self {
+            failure_probabilities: BTreeMap::from([
+                ("Database".to_string(), 0.02),
+                ("Cache".to_string(), 0.05),
+                ("Queue".to_string(), 0.03),
+                ("LoadBalancer".to_string(), 0.01),
+                ("Service".to_string(), 0.04),
+            ]),
+            default_failure_probability: 0.05,
+            trials: 512,
+            seed: 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeCriticality {
+    pub node: NodeId,
+    pub kind: String,
+    pub failure_probability: f64,
+    /// Nodes down, itself included, when only this node fails.
+    pub blast_radius: usize,
+    /// Share of entry points lost when only this node fails.
+    pub criticality: f64,
+    /// Failing alone takes down at least one entry point.
+    pub single_point_of_failure: bool,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ReliabilityReport {
+    /// Mean share of entry points up across trials; 1.0 for an empty graph.
+    pub reliability: f64,
+    pub trials: usize,
+    /// Most critical first, ties by node id.
+    pub nodes: Vec<NodeCriticality>,
+}
+
+impl ReliabilityReport {
+    pub fn single_points_of_failure(&self) -> impl Iterator<Item = &NodeCriticality> {
+        self.nodes.iter().filter(|n| n.single_point_of_failure)
+    }
+}
+
+impl ReliabilityModel {
+    pub fn failure_probability(&self, kind: &str) -> f64 {
+        self.failure_probabilities
+            .get(kind)
+            .copied()
+            .unwrap_or(self.default_failure_probability)
+            .clamp(0.0, 1.0)
+    }
+
+    /// Monte Carlo estimate of `ReliabilityReport::reliability` only.
+    pub fn score(&self, state: &DesignState) -> f64 {
+        Propagation::new(&state.graph).map_or(1.0, |p| self.simulate(&state.graph, &p))
+    }
+
+    pub fn analyze(&self, state: &DesignState) -> ReliabilityReport {
+        let graph = &state.graph;
+        let Some(propagation) = Propagation::new(graph) else {
+            return ReliabilityReport {
+                reliability: 1.0,
+                trials: 0,
+                nodes: Vec::new(),
+            };
+        };
+        let entries = propagation.entries.len() as f64;
+        let mut nodes = graph
+            .nodes()
+            .values()
+            .map(|node| {
+                let down = propagation.down(&BTreeSet::from([node.id]));
+                let lost = propagation
+                    .entries
+                    .iter()
+                    .filter(|e| down.contains(e))
+                    .count();
+                NodeCriticality {
+                    node: node.id,
+                    kind: node.kind.clone(),
+                    failure_probability: self.failure_probability(&node.kind),
+                    blast_radius: down.len(),
+                    criticality: lost as f64 / entries,
+                    single_point_of_failure: lost > 0,
+                }
+            })
+            .collect::<Vec<_>>();
+        nodes.sort_by(|a, b| {
+            b.criticality
+                .total_cmp(&a.criticality)
+                .then(b.blast_radius.cmp(&a.blast_radius))
+                .then(a.node.cmp(&b.node))
+        });
+        ReliabilityReport {
+            reliability: self.simulate(graph, &propagation),
+            trials: self.trials.max(1),
+            nodes,
+        }
+    }
+
+    fn simulate(&self, graph: &StructuralGraph, propagation: &Propagation) -> f64 {
+        let trials = self.trials.max(1);
+        let mut rng = SplitMix64::from_seed(self.seed);
+        let mut up = 0.0;
+        for _ in 0..trials {
+            let failed = graph
+                .nodes()
+                .values()
+                .filter(|node| rng.next_f64() < self.failure_probability(&node.kind))
+                .map(|node| node.id)
+                .collect::<BTreeSet<_>>();
+            let down = propagation.down(&failed);
+            let alive = propagation
+                .entries
+                .iter()
+                .filter(|e| !down.contains(e))
+                .count();
+            up += alive as f64 / propagation.entries.len() as f64;
+        }
+        up / trials as f64
+    }
+}
+
+struct Propagation {
+    /// Dependencies before dependents.
+    order: Vec<NodeId>,
+    entries: Vec<NodeId>,
+    /// Per node, its dependencies grouped by kind; each group is redundant.
+    groups: BTreeMap<NodeId, Vec<Vec<NodeId>>>,
+}
+
+impl Propagation {
+    /// `None` for an empty graph.
+    fn new(graph: &StructuralGraph) -> Option<Self> {
+        if graph.nodes().is_empty() {
+            return None;
+        }
+        let mut callers = graph
+            .nodes()
+            .keys()
+            .map(|id| (*id, 0usize))
+            .collect::<BTreeMap<_, _>>();
+        for (_, to) in graph.edges() {
+            *callers.entry(*to).or_default() += 1;
+        }
+        let entries = callers
+            .iter()
+            .filter(|(_, n)| **n == 0)
+            .map(|(id, _)| *id)
+            .collect();
+
+        Some(Self {
+            order: dependencies_first(graph),
+            entries,
+            groups: dependency_groups(graph),
+        })
+    }
+
+    fn down(&self, failed: &BTreeSet<NodeId>) -> BTreeSet<NodeId> {
+        let mut down = BTreeSet::new();
+        for id in &self.order {
+            let starved = self.groups.get(id).is_some_and(|groups| {
+                groups
+                    .iter()
+                    .any(|group| group.iter().all(|dep| down.contains(dep)))
+            });
+            if failed.contains(id) || starved {
+                down.insert(*id);
+            }
+        }
+        down
+    }
+}
+
+/// Each node's dependencies grouped by target kind. Targets in one group are
+/// interchangeable replicas.
+pub(crate) fn dependency_groups(graph: &StructuralGraph) -> BTreeMap<NodeId, Vec<Vec<NodeId>>> {
+    let mut by_kind: BTreeMap<NodeId, BTreeMap<&str, Vec<NodeId>>> = BTreeMap::new();
+    for (from, to) in graph.edges() {
+        let kind = graph.nodes().get(to).map_or("", |n| n.kind.as_str());
+        by_kind
+            .entry(*from)
+            .or_default()
+            .entry(kind)
+            .or_default()
+            .push(*to);
+    }
+    by_kind
+        .into_iter()
+        .map(|(id, kinds)| (id, kinds.into_values().collect()))
+        .collect()
+}
+
+/// Topological order with every node after all nodes it depends on (sinks
+/// first). Kahn's algorithm over reversed edges.
+pub(crate) fn dependencies_first(graph: &StructuralGraph) -> Vec<NodeId> {
+    let mut pending = graph
+        .nodes()
+        .keys()
+        .map(|id| (*id, 0usize))
+        .collect::<BTreeMap<_, _>>();
+    for (from, _) in graph.edges() {
+        *pending.entry(*from).or_default() += 1;
+    }
+    let mut queue = pending
+        .iter()
+        .filter(|(_, n)| **n == 0)
+        .map(|(id, _)| *id)
+        .collect::<VecDeque<_>>();
+    let mut order = Vec::with_capacity(pending.len());
+    while let Some(id) = queue.pop_front() {
+        order.push(id);
+        for (from, _) in graph.edges().iter().filter(|(_, to)| *to == id) {
+            if let Some(n) = pending.get_mut(from) {
+                *n -= 1;
+                if *n == 0 {
+                    queue.push_back(*from);
+                }
+            }
+        }
+    }
+    order
+}
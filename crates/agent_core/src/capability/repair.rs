@@ -0,0 +1,193 @@
+//! Structural repair after destructive transformations.
+//!
+//! Removing a node (or an edge) can split the component it belonged to and
+//! strand part of the design; a search then spends depths wiring it back.
+//! `repair` fixes the split right after the transformation:
+//!
+//! * reconnect: each stranded piece gets one edge from the largest surviving
+//!   piece of its former component, preferably from a node that touched what
+//!   was removed, into the piece's first root;
+//! * collapse: a stranded piece whose nodes carry no attributes is dropped
+//!   instead, since nothing in it is worth reconnecting.
+//!
+//! New edges always join two weak components, so the graph stays acyclic.
+//! Components that were already separate before the transformation are left
+//! alone.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use hybrid_vm::DesignRule;
+use memory_space::{DesignState, NodeId, StructuralGraph};
+
+use super::apply::{ApplyError, apply_atomic};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RepairConfig {
+    pub reconnect_orphans: bool,
+    pub collapse_empty_clusters: bool,
+}
+
+impl Default for RepairConfig {
+    fn default() -> Self {
+        Self {
+            reconnect_orphans: true,
+            collapse_empty_clusters: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RepairStats {
+    /// Stranded pieces wired back with one edge.
+    pub reconnected: usize,
+    /// Stranded attribute-less pieces removed.
+    pub collapsed: usize,
+}
+
+impl RepairStats {
+    pub fn total(&self) -> usize {
+        self.reconnected + self.collapsed
+    }
+
+    pub fn absorb(&mut self, other: RepairStats) {
+        self.reconnected += other.reconnected;
+        self.collapsed += other.collapsed;
+    }
+}
+
+/// `apply_atomic` followed by `repair` of whatever the rule stranded. The
+/// child keeps the id and history `apply_atomic` gave it.
+pub fn apply_atomic_repaired(
+    rule: &DesignRule,
+    state: &DesignState,
+    config: RepairConfig,
+) -> Result<(DesignState, RepairStats), ApplyError> {
+    let child = apply_atomic(rule, state)?;
+    let (graph, stats) = repair(&state.graph, &child.graph, config);
+    if stats.total() == 0 {
+        return Ok((child, stats));
+    }
+    let repaired = DesignState::new(child.id, Arc::new(graph), child.profile_snapshot.clone());
+    Ok((repaired, stats))
+}
+
+/// Repairs `after` against the graph it was derived from. A no-op unless
+/// `after` lost nodes or edges that `before` had.
+pub fn repair(
+    before: &StructuralGraph,
+    after: &StructuralGraph,
+    config: RepairConfig,
+) -> (StructuralGraph, RepairStats) {
+    let mut stats = RepairStats::default();
+    let removed_nodes = before
+        .nodes()
+        .keys()
+        .filter(|id| !after.nodes().contains_key(id))
+        .copied()
+        .collect::<BTreeSet<_>>();
+    let removed_edges = before
+        .edges()
+        .iter()
+        .filter(|edge| !after.edges().contains(edge))
+        .collect::<Vec<_>>();
+    if removed_nodes.is_empty() && removed_edges.is_empty() {
+        return (after.clone(), stats);
+    }
+    // Survivors that touched a removed node or edge, the natural place to
+    // hang a stranded piece back on.
+    let touched = removed_edges
+        .iter()
+        .flat_map(|(from, to)| [*from, *to])
+        .filter(|id| after.nodes().contains_key(id))
+        .collect::<BTreeSet<_>>();
+
+    let before_component = component_index(before);
+    let mut groups: BTreeMap<usize, Vec<BTreeSet<NodeId>>> = BTreeMap::new();
+    for piece in weak_components(after) {
+        let first = piece.first().copied();
+        if let Some(group) = first.and_then(|id| before_component.get(&id)) {
+            groups.entry(*group).or_default().push(piece);
+        }
+    }
+
+    let mut repaired = after.clone();
+    for mut pieces in groups.into_values() {
+        if pieces.len() < 2 {
+            continue;
+        }
+        pieces.sort_by(|l, r| {
+            r.len()
+                .cmp(&l.len())
+                .then_with(|| l.first().cmp(&r.first()))
+        });
+        let anchor = &pieces[0];
+        let Some(&source) = anchor
+            .iter()
+            .find(|id| touched.contains(id))
+            .or_else(|| anchor.first())
+        else {
+            continue;
+        };
+        for piece in &pieces[1..] {
+            let empty = piece
+                .iter()
+                .all(|id| after.nodes()[id].attributes.is_empty());
+            if config.collapse_empty_clusters && empty {
+                for id in piece {
+                    repaired = repaired.with_node_removed(*id);
+                }
+                stats.collapsed += 1;
+            } else if config.reconnect_orphans {
+                let root = piece
+                    .iter()
+                    .find(|id| !after.edges().iter().any(|(_, to)| to == *id))
+                    .or_else(|| piece.first());
+                if let Some(&root) = root {
+                    repaired = repaired.with_edge_added(source, root);
+                    stats.reconnected += 1;
+                }
+            }
+        }
+    }
+    (repaired, stats)
+}
+
+/// Weakly connected components, each sorted by id, in order of first id.
+fn weak_components(graph: &StructuralGraph) -> Vec<BTreeSet<NodeId>> {
+    let mut neighbors: BTreeMap<NodeId, Vec<NodeId>> =
+        graph.nodes().keys().map(|id| (*id, Vec::new())).collect();
+    for (from, to) in graph.edges() {
+        neighbors.entry(*from).or_default().push(*to);
+        neighbors.entry(*to).or_default().push(*from);
+    }
+    let mut seen = BTreeSet::new();
+    let mut components = Vec::new();
+    for start in graph.nodes().keys() {
+        if !seen.insert(*start) {
+            continue;
+        }
+        let mut component = BTreeSet::from([*start]);
+        let mut stack = vec![*start];
+        while let Some(id) = stack.pop() {
+            for next in &neighbors[&id] {
+                if seen.insert(*next) {
+                    component.insert(*next);
+                    stack.push(*next);
+                }
+            }
+        }
+        components.push(component);
+    }
+    components
+}
+
+fn component_index(graph: &StructuralGraph) -> BTreeMap<NodeId, usize> {
+    weak_components(graph)
+        .into_iter()
+        .enumerate()
+        .flat_map(|(index, component)| component.into_iter().map(move |id| (id, index)))
+        .collect()
+}
@@ -0,0 +1,330 @@
+//! Graph grammar: parameterized rewrite rules applied around an anchor node.
+//!
+//! A rule's left-hand side selects anchor nodes; its right-hand side is a
+//! list of productions executed in order. Productions refer to the anchor or
+//! to nodes created earlier in the same rule by their local label.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use hybrid_vm::{AttributePredicate, RuleId};
+use memory_space::{DesignNode, DesignState, NodeId, StructuralGraph, Value};
+
+use super::apply::{append_rule_history, deterministic_uuid};
+
+/// Left-hand side. An empty match selects every node.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeMatch {
+    pub kind: Option<String>,
+    pub attributes: Vec<AttributePredicate>,
+    /// Require at least this many incoming edges.
+    pub min_in_degree: usize,
+}
+
+impl NodeMatch {
+    pub fn kind(kind: impl Into<String>) -> Self {
+        Self {
+            kind: Some(kind.into()),
+            ..Self::default()
+        }
+    }
+
+    fn matches(&self, graph: &StructuralGraph, node: &DesignNode) -> bool {
+        self.kind.as_ref().is_none_or(|k| node.kind == *k)
+            && self.attributes.iter().all(|p| p.matches(node))
+            && graph
+                .edges()
+                .iter()
+                .filter(|(_, to)| *to == node.id)
+                .count()
+                >= self.min_in_degree
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Slot {
+    Anchor,
+    /// A node created by an earlier `AddNode`/`CopyAnchor` in the same rule.
+    New(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Production {
+    AddNode {
+        label: String,
+        kind: String,
+        attributes: BTreeMap<String, Value>,
+    },
+    /// New node with the anchor's kind and attributes.
+    CopyAnchor { label: String },
+    /// Fails with `RewriteError::WouldCycle` if the edge would close a cycle.
+    AddEdge { from: Slot, to: Slot },
+    /// Moves every edge pointing at the anchor onto `to`.
+    RedirectIncoming { to: Slot },
+    /// Duplicates the anchor's outgoing edges onto `to`.
+    CopyOutgoing { to: Slot },
+    SetAttribute {
+        node: Slot,
+        key: String,
+        value: Value,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RewriteRule {
+    pub id: RuleId,
+    pub name: String,
+    pub lhs: NodeMatch,
+    pub rhs: Vec<Production>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RewriteError {
+    NoMatch,
+    UnknownSlot(String),
+    /// An `AddEdge` production would close a cycle (or a self loop).
+    WouldCycle {
+        from: NodeId,
+        to: NodeId,
+    },
+    /// The production result failed `StructuralGraph::validate`.
+    InvalidResult,
+}
+
+impl std::fmt::Display for RewriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoMatch => write!(f, "no node matches the rule's left-hand side"),
+            Self::UnknownSlot(label) => write!(f, "production refers to unknown node '{label}'"),
+            Self::WouldCycle { from, to } => {
+                write!(f, "edge {from:?} -> {to:?} would close a cycle")
+            }
+            Self::InvalidResult => write!(f, "rewrite produced an invalid graph"),
+        }
+    }
+}
+
+impl std::error::Error for RewriteError {}
+
+impl RewriteRule {
+    /// Puts a new `new_kind` node between the anchor and all of its callers,
+    /// e.g. a cache in front of a database.
+    pub fn insert_in_front(id: RuleId, target_kind: &str, new_kind: &str) -> Self {
+        let label = new_kind.to_lowercase();
+        Self {
+            id,
+            name: format!("insert {new_kind} in front of {target_kind}"),
+            lhs: NodeMatch::kind(target_kind),
+            rhs: vec![
+                Production::AddNode {
+                    label: label.clone(),
+                    kind: new_kind.to_string(),
+                    attributes: BTreeMap::new(),
+                },
+                Production::RedirectIncoming {
+                    to: Slot::New(label.clone()),
+                },
+                Production::AddEdge {
+                    from: Slot::New(label),
+                    to: Slot::Anchor,
+                },
+            ],
+        }
+    }
+
+    /// Duplicates the anchor and routes its callers through a load balancer
+    /// that fans out to both copies.
+    pub fn split_with_load_balancer(id: RuleId, target_kind: &str) -> Self {
+        Self {
+            id,
+            name: format!("split {target_kind} behind load balancer"),
+            lhs: NodeMatch::kind(target_kind),
+            rhs: vec![
+                Production::CopyAnchor {
+                    label: "replica".to_string(),
+                },
+                Production::CopyOutgoing {
+                    to: Slot::New("replica".to_string()),
+                },
+                Production::AddNode {
+                    label: "lb".to_string(),
+                    kind: "LoadBalancer".to_string(),
+                    attributes: BTreeMap::new(),
+                },
+                Production::RedirectIncoming {
+                    to: Slot::New("lb".to_string()),
+                },
+                Production::AddEdge {
+                    from: Slot::New("lb".to_string()),
+                    to: Slot::Anchor,
+                },
+                Production::AddEdge {
+                    from: Slot::New("lb".to_string()),
+                    to: Slot::New("replica".to_string()),
+                },
+            ],
+        }
+    }
+
+    /// Anchor candidates in ascending id order.
+    pub fn matches(&self, graph: &StructuralGraph) -> Vec<NodeId> {
+        graph
+            .nodes()
+            .values()
+            .filter(|node| self.lhs.matches(graph, node))
+            .map(|node| node.id)
+            .collect()
+    }
+
+    /// Rewrites around the first match.
+    pub fn apply(&self, state: &DesignState) -> Result<DesignState, RewriteError> {
+        let anchor = *self
+            .matches(&state.graph)
+            .first()
+            .ok_or(RewriteError::NoMatch)?;
+        self.apply_at(state, anchor)
+    }
+
+    pub fn apply_at(
+        &self,
+        state: &DesignState,
+        anchor: NodeId,
+    ) -> Result<DesignState, RewriteError> {
+        let Some(anchor_node) = state.graph.nodes().get(&anchor).cloned() else {
+            return Err(RewriteError::NoMatch);
+        };
+        if !self.lhs.matches(&state.graph, &anchor_node) {
+            return Err(RewriteError::NoMatch);
+        }
+
+        let mut graph = (*state.graph).clone();
+        let mut created: BTreeMap<&str, NodeId> = BTreeMap::new();
+        let resolve = |slot: &Slot, created: &BTreeMap<&str, NodeId>| match slot {
+            Slot::Anchor => Ok(anchor),
+            Slot::New(label) => created
+                .get(label.as_str())
+                .copied()
+                .ok_or_else(|| RewriteError::UnknownSlot(label.clone())),
+        };
+
+        for (idx, production) in self.rhs.iter().enumerate() {
+            match production {
+                Production::AddNode {
+                    label,
+                    kind,
+                    attributes,
+                } => {
+                    let id = self.fresh_id(state, anchor, idx);
+                    graph = graph.with_node_added(DesignNode::new(
+                        id,
+                        kind.clone(),
+                        attributes.clone(),
+                    ));
+                    created.insert(label, id);
+                }
+                Production::CopyAnchor { label } => {
+                    let id = self.fresh_id(state, anchor, idx);
+                    graph = graph.with_node_added(DesignNode::new(
+                        id,
+                        anchor_node.kind.clone(),
+                        anchor_node.attributes.clone(),
+                    ));
+                    created.insert(label, id);
+                }
+                Production::AddEdge { from, to } => {
+                    let (from, to) = (resolve(from, &created)?, resolve(to, &created)?);
+                    let next = graph.with_edge_added(from, to);
+                    // Both ends resolved to existing nodes, so a missing edge
+                    // was refused as a cycle or self loop.
+                    if !next.edges().contains(&(from, to)) {
+                        return Err(RewriteError::WouldCycle { from, to });
+                    }
+                    graph = next;
+                }
+                Production::RedirectIncoming { to } => {
+                    let to = resolve(to, &created)?;
+                    let callers = graph
+                        .edges()
+                        .iter()
+                        .filter(|(from, target)| *target == anchor && *from != to)
+                        .map(|(from, _)| *from)
+                        .collect::<Vec<_>>();
+                    for from in callers {
+                        graph = graph
+                            .with_edge_removed(from, anchor)
+                            .with_edge_added(from, to);
+                    }
+                }
+                Production::CopyOutgoing { to } => {
+                    let to = resolve(to, &created)?;
+                    let targets = graph
+                        .edges()
+                        .iter()
+                        .filter(|(from, _)| *from == anchor)
+                        .map(|(_, target)| *target)
+                        .collect::<Vec<_>>();
+                    for target in targets {
+                        graph = graph.with_edge_added(to, target);
+                    }
+                }
+                Production::SetAttribute { node, key, value } => {
+                    let id = resolve(node, &created)?;
+                    let mut nodes = graph.nodes().clone();
+                    if let Some(n) = nodes.get_mut(&id) {
+                        n.attributes.insert(key.clone(), value.clone());
+                    }
+                    graph = StructuralGraph::new(nodes, graph.edges().clone());
+                }
+            }
+        }
+        if graph.validate().is_err() {
+            return Err(RewriteError::InvalidResult);
+        }
+
+        let snapshot = append_rule_history(&state.profile_snapshot, self.id);
+        let id = deterministic_uuid(
+            state.id.as_u128(),
+            self.id.as_u128(),
+            anchor.as_u128() ^ 0x6A,
+        );
+        Ok(DesignState::new(id, Arc::new(graph), snapshot))
+    }
+
+    fn fresh_id(&self, state: &DesignState, anchor: NodeId, production: usize) -> NodeId {
+        deterministic_uuid(
+            state.id.as_u128() ^ anchor.as_u128(),
+            self.id.as_u128(),
+            0xB0 + production as u128,
+        )
+    }
+}
+
+/// A set of rewrite rules usable as an extra candidate generator.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RewriteEngine {
+    pub rules: Vec<RewriteRule>,
+}
+
+impl RewriteEngine {
+    pub fn new(rules: Vec<RewriteRule>) -> Self {
+        Self { rules }
+    }
+
+    /// One successor per (rule, anchor) pair that rewrites cleanly.
+    pub fn expand(&self, state: &DesignState) -> Vec<(RuleId, DesignState)> {
+        self.rules
+            .iter()
+            .flat_map(|rule| {
+                rule.matches(&state.graph)
+                    .into_iter()
+                    .filter_map(move |anchor| rule.apply_at(state, anchor).ok())
+                    .map(move |next| (rule.id, next))
+            })
+            .collect()
+    }
+}
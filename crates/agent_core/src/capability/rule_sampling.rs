@@ -0,0 +1,335 @@
+use std::collections::BTreeMap;
+
+use hybrid_vm::DesignRule;
+
+use super::rng::{RngStream, mix64};
+use crate::runtime::trace_helpers::{
+    category_soft_logits, rule_category_name, select_rules_category_soft,
+};
+use crate::{RuleSelectionKind, SoftTraceParams};
+
+/// Per-call inputs a strategy may use to vary its draw.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SamplingContext {
+    pub depth: usize,
+    pub max_depth: usize,
+    pub seed: u64,
+    pub state_id: u128,
+}
+
+impl SamplingContext {
+    /// The random stream owned by this depth and state.
+    pub fn stream(&self) -> RngStream {
+        RngStream::new(self.seed, self.depth, self.state_id)
+    }
+}
+
+/// Chooses which applicable rules are expanded for one frontier state.
+pub trait SelectionStrategy {
+    fn name(&self) -> &'static str;
+    fn select<'r>(
+        &self,
+        rules: Vec<&'r DesignRule>,
+        max_select: usize,
+        ctx: SamplingContext,
+    ) -> Vec<&'r DesignRule>;
+
+    /// True when `select` ignores its `SamplingContext`, so equal rule sets
+    /// always get equal selections and may be served from a
+    /// `RuleRankingCache`.
+    fn ignores_context(&self) -> bool {
+        false
+    }
+}
+
+/// The original priority/category-balanced softmax ranking.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CategorySoftSelection {
+    pub alpha: f64,
+    pub temperature: f64,
+    pub entropy_beta: f64,
+}
+
+impl SelectionStrategy for CategorySoftSelection {
+    fn name(&self) -> &'static str {
+        "category_soft"
+    }
+
+    fn ignores_context(&self) -> bool {
+        true
+    }
+
+    fn select<'r>(
+        &self,
+        rules: Vec<&'r DesignRule>,
+        max_select: usize,
+        _ctx: SamplingContext,
+    ) -> Vec<&'r DesignRule> {
+        select_rules_category_soft(
+            rules,
+            max_select,
+            self.alpha,
+            self.temperature,
+            self.entropy_beta,
+        )
+        .0
+    }
+}
+
+/// Samples from the same category-balanced softmax as
+/// `CategorySoftSelection` instead of taking its top k. Each rule's Gumbel
+/// perturbation comes from its own stream keyed by rule id, so the draw is
+/// reproducible for a given seed, depth and state, whatever order the rules
+/// arrive in. Ties fall back to rule id.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CategorySoftSampling {
+    pub alpha: f64,
+    pub temperature: f64,
+    pub entropy_beta: f64,
+}
+
+impl SelectionStrategy for CategorySoftSampling {
+    fn name(&self) -> &'static str {
+        "category_soft_sample"
+    }
+
+    fn select<'r>(
+        &self,
+        rules: Vec<&'r DesignRule>,
+        max_select: usize,
+        ctx: SamplingContext,
+    ) -> Vec<&'r DesignRule> {
+        if rules.is_empty() {
+            return Vec::new();
+        }
+        let (logits, _) =
+            category_soft_logits(rules, self.alpha, self.temperature, self.entropy_beta);
+        let stream = ctx.stream();
+        let mut keyed = logits
+            .into_iter()
+            .map(|(rule, logit)| {
+                let noise = stream.for_item(rule.id.as_u128()).next_gumbel();
+                (rule, logit + noise)
+            })
+            .collect::<Vec<_>>();
+        keyed.sort_by(|(l_rule, l_key), (r_rule, r_key)| {
+            r_key
+                .total_cmp(l_key)
+                .then_with(|| l_rule.id.cmp(&r_rule.id))
+        });
+        keyed
+            .into_iter()
+            .take(max_select.max(1))
+            .map(|(rule, _)| rule)
+            .collect()
+    }
+}
+
+/// Repeatedly draws `size` rules at random and keeps the highest-priority one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TournamentSelection {
+    pub size: usize,
+}
+
+impl SelectionStrategy for TournamentSelection {
+    fn name(&self) -> &'static str {
+        "tournament"
+    }
+
+    fn select<'r>(
+        &self,
+        rules: Vec<&'r DesignRule>,
+        max_select: usize,
+        ctx: SamplingContext,
+    ) -> Vec<&'r DesignRule> {
+        let mut pool = sorted_by_id(rules);
+        let mut rng = ctx.stream();
+        let mut selected = Vec::new();
+        while !pool.is_empty() && selected.len() < max_select.max(1) {
+            let mut winner = rng.next_index(pool.len());
+            for _ in 1..self.size.max(1) {
+                let challenger = rng.next_index(pool.len());
+                if priority_order(pool[challenger], pool[winner]).is_lt() {
+                    winner = challenger;
+                }
+            }
+            selected.push(pool.remove(winner));
+        }
+        selected
+    }
+}
+
+/// Samples without replacement with weights `exp(priority / T(depth))`,
+/// where `T` anneals geometrically from `initial` to `final` over the run.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoltzmannSelection {
+    pub initial_temperature: f64,
+    pub final_temperature: f64,
+}
+
+impl BoltzmannSelection {
+    pub fn temperature_at(&self, depth: usize, max_depth: usize) -> f64 {
+        let t0 = self.initial_temperature.max(1e-6);
+        let t1 = self.final_temperature.max(1e-6);
+        let progress = if max_depth <= 1 {
+            1.0
+        } else {
+            (depth.saturating_sub(1) as f64 / (max_depth - 1) as f64).clamp(0.0, 1.0)
+        };
+        t0 * (t1 / t0).powf(progress)
+    }
+}
+
+impl SelectionStrategy for BoltzmannSelection {
+    fn name(&self) -> &'static str {
+        "boltzmann"
+    }
+
+    fn select<'r>(
+        &self,
+        rules: Vec<&'r DesignRule>,
+        max_select: usize,
+        ctx: SamplingContext,
+    ) -> Vec<&'r DesignRule> {
+        let t = self.temperature_at(ctx.depth, ctx.max_depth);
+        let mut pool = sorted_by_id(rules);
+        let max_priority = pool
+            .iter()
+            .map(|r| r.priority)
+            .fold(f64::NEG_INFINITY, f64::max);
+        let mut rng = ctx.stream();
+        let mut selected = Vec::new();
+        while !pool.is_empty() && selected.len() < max_select.max(1) {
+            let weights = pool
+                .iter()
+                .map(|r| ((r.priority - max_priority) / t).exp())
+                .collect::<Vec<_>>();
+            let total = weights.iter().sum::<f64>();
+            let mut draw = rng.next_f64() * total;
+            let mut pick = pool.len() - 1;
+            for (idx, w) in weights.iter().enumerate() {
+                if draw < *w {
+                    pick = idx;
+                    break;
+                }
+                draw -= w;
+            }
+            selected.push(pool.remove(pick));
+        }
+        selected
+    }
+}
+
+/// Round-robin over strata keyed by rule category and the sign of the
+/// summed expected effect, highest priority first within each stratum.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StratifiedSelection;
+
+impl SelectionStrategy for StratifiedSelection {
+    fn name(&self) -> &'static str {
+        "stratified"
+    }
+
+    fn ignores_context(&self) -> bool {
+        true
+    }
+
+    fn select<'r>(
+        &self,
+        rules: Vec<&'r DesignRule>,
+        max_select: usize,
+        _ctx: SamplingContext,
+    ) -> Vec<&'r DesignRule> {
+        let mut strata: BTreeMap<(&'static str, bool), Vec<&'r DesignRule>> = BTreeMap::new();
+        for rule in rules {
+            strata
+                .entry((rule_category_name(&rule.category), effect_is_positive(rule)))
+                .or_default()
+                .push(rule);
+        }
+        for members in strata.values_mut() {
+            members.sort_by(|l, r| priority_order(l, r));
+            members.reverse();
+        }
+        let mut selected = Vec::new();
+        while selected.len() < max_select.max(1) {
+            let mut progressed = false;
+            for members in strata.values_mut() {
+                if selected.len() >= max_select.max(1) {
+                    break;
+                }
+                if let Some(rule) = members.pop() {
+                    selected.push(rule);
+                    progressed = true;
+                }
+            }
+            if !progressed {
+                break;
+            }
+        }
+        selected
+    }
+}
+
+/// Builds the strategy selected by `params.rule_selection`.
+pub fn selection_strategy(params: &SoftTraceParams) -> Box<dyn SelectionStrategy> {
+    match params.rule_selection {
+        RuleSelectionKind::CategorySoft => Box::new(CategorySoftSelection {
+            alpha: params.alpha,
+            temperature: params.temperature,
+            entropy_beta: params.entropy_beta,
+        }),
+        RuleSelectionKind::CategorySoftSample => Box::new(CategorySoftSampling {
+            alpha: params.alpha,
+            temperature: params.temperature,
+            entropy_beta: params.entropy_beta,
+        }),
+        RuleSelectionKind::Tournament { size } => Box::new(TournamentSelection { size }),
+        RuleSelectionKind::Boltzmann {
+            initial_temperature,
+            final_temperature,
+        } => Box::new(BoltzmannSelection {
+            initial_temperature,
+            final_temperature,
+        }),
+        RuleSelectionKind::Stratified => Box::new(StratifiedSelection),
+    }
+}
+
+fn effect_is_positive(rule: &DesignRule) -> bool {
+    let e = &rule.expected_effect;
+    e.delta_struct + e.delta_field + e.delta_risk + e.delta_cost >= 0.0
+}
+
+/// Higher priority first, then lower id.
+fn priority_order(l: &DesignRule, r: &DesignRule) -> std::cmp::Ordering {
+    r.priority
+        .total_cmp(&l.priority)
+        .then_with(|| l.id.cmp(&r.id))
+}
+
+fn sorted_by_id(mut rules: Vec<&DesignRule>) -> Vec<&DesignRule> {
+    rules.sort_by_key(|r| r.id);
+    rules
+}
+
+pub(crate) struct SplitMix64(u64);
+
+impl SplitMix64 {
+    pub(crate) fn from_seed(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e3779b97f4a7c15);
+        mix64(self.0)
+    }
+
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub(crate) fn next_index(&mut self, len: usize) -> usize {
+        (self.next_u64() % len as u64) as usize
+    }
+}
@@ -0,0 +1,74 @@
+//! Quarantine for candidates scored with NaN or infinite objectives.
+//!
+//! Ranking compares objectives with `partial_cmp(..).unwrap_or(Equal)` and
+//! normalization folds every sample into running medians, so one non-finite
+//! component silently reorders a front or poisons the statistics of every
+//! later depth. Candidates are checked here right after evaluation and the
+//! offenders dropped with a diagnostic instead.
+
+use std::fmt::{Display, Formatter};
+
+use core_types::ObjectiveVector;
+use hybrid_vm::RuleId;
+use memory_space::StateId;
+
+use crate::ObjectiveAxis;
+
+const AXES: [ObjectiveAxis; 4] = [
+    ObjectiveAxis::Struct,
+    ObjectiveAxis::Field,
+    ObjectiveAxis::Risk,
+    ObjectiveAxis::Shape,
+];
+
+/// A candidate left out of ranking because its objective was not finite.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct QuarantinedCandidate {
+    /// 0 for the initial state.
+    pub depth: usize,
+    pub state_id: StateId,
+    /// `None` for the initial state and crossover offspring.
+    pub rule_id: Option<RuleId>,
+    /// Axes whose value was NaN or infinite, in objective order.
+    pub axes: Vec<ObjectiveAxis>,
+}
+
+impl Display for QuarantinedCandidate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "depth {}: state {:032x}",
+            self.depth,
+            self.state_id.as_u128()
+        )?;
+        if let Some(rule_id) = self.rule_id {
+            write!(f, " (rule {:032x})", rule_id.as_u128())?;
+        }
+        write!(f, " scored non-finite {:?}", self.axes)
+    }
+}
+
+/// Axes of `objective` that are NaN or infinite.
+pub fn non_finite_axes(objective: &ObjectiveVector) -> Vec<ObjectiveAxis> {
+    AXES.into_iter()
+        .filter(|axis| !axis.value(objective).is_finite())
+        .collect()
+}
+
+/// The diagnostic for `state_id` when `objective` has to be quarantined,
+/// `None` when it is safe to rank.
+pub fn quarantine(
+    depth: usize,
+    state_id: StateId,
+    rule_id: Option<RuleId>,
+    objective: &ObjectiveVector,
+) -> Option<QuarantinedCandidate> {
+    let axes = non_finite_axes(objective);
+    (!axes.is_empty()).then_some(QuarantinedCandidate {
+        depth,
+        state_id,
+        rule_id,
+        axes,
+    })
+}
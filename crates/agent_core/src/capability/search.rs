@@ -1,13 +1,17 @@
 use std::collections::{BTreeMap, VecDeque};
 
 use core_types::ObjectiveVector;
-use field_engine::{FieldEngine, FieldVector};
 use hybrid_vm::{HybridVM, StructuralEvaluator};
 use memory_space::DesignState;
 
 use crate::capability::ScoringCapability;
+use crate::capability::alpha_trace::{AlphaConfig, AlphaInputs, AlphaTrace};
+use crate::capability::improvement::{ImprovementSummary, ImprovementTracker};
+use crate::capability::ranking_cache::{RankingCacheStats, RuleRankingCache};
+use crate::capability::sanitize::QuarantinedCandidate;
 use crate::domain::DomainError;
 use crate::domain::{AgentEvent, Hypothesis, Score};
+use crate::runtime::candidate_pipeline::PipelineThroughput;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SearchHit {
@@ -24,6 +28,19 @@ pub struct SearchCoreResult {
     pub best: Hypothesis,
     pub trace: Vec<crate::TraceRow>,
     pub events: Vec<AgentEvent>,
+    /// Frozen warm-up statistics, reusable via `TraceRunConfig::calibration`.
+    pub calibration: Option<crate::GlobalRobustStats>,
+    /// Rule-ranking reuse across frontier states; all zero for strategies
+    /// that depend on the state.
+    pub ranking_cache: RankingCacheStats,
+    /// First and last front of the run; `None` if no depth produced one.
+    pub improvement: Option<ImprovementSummary>,
+    /// Candidate pipeline work summed over every depth.
+    pub stage_throughput: PipelineThroughput,
+    /// Candidates dropped for a NaN or infinite objective, in depth order.
+    pub quarantined: Vec<QuarantinedCandidate>,
+    /// One controller decision per depth under `adaptive_alpha`.
+    pub alpha_trace: Option<AlphaTrace>,
 }
 
 pub fn rank_hits_with_scorer<S: ScoringCapability>(
@@ -51,7 +68,7 @@ pub fn execute_soft_search_core(
     const HV_STOP_EPS: f64 = 1e-6;
     let shm = HybridVM::default_shm();
     let _chm = crate::runtime::trace_helpers::make_dense_trace_chm(&shm, config.seed);
-    let field = FieldEngine::new(256);
+    let field = config.field_engine();
     let mut hybrid_vm = match HybridVM::with_default_memory(StructuralEvaluator::default()) {
         Ok(vm) => vm,
         Err(err) => {
@@ -65,6 +82,12 @@ pub fn execute_soft_search_core(
                     name: "trace.hybrid_vm.init_error".to_string(),
                     value: err.to_string(),
                 })],
+                calibration: None,
+                ranking_cache: RankingCacheStats::default(),
+                improvement: None,
+                stage_throughput: PipelineThroughput::default(),
+                quarantined: Vec::new(),
+                alpha_trace: None,
             };
         }
     };
@@ -74,10 +97,19 @@ pub fn execute_soft_search_core(
     )];
     let mut rows = Vec::with_capacity(config.depth);
     let mut lambda = 0.5f64;
-    let mut field_cache: BTreeMap<(u128, u128, usize, usize), FieldVector> = BTreeMap::new();
-    let mut field_cache_order: VecDeque<(u128, u128, usize, usize)> = VecDeque::new();
-    let mut estimator = crate::GlobalRobustEstimator::default();
-    let warmup_depths = 10usize;
+    let mut estimator = crate::GlobalRobustEstimator::new(config.warmup);
+    let mut normalizer = crate::DepthNormalizer::new(config.normalization, config.warmup);
+    if let Some(stats) = &config.calibration {
+        estimator.import(stats.clone());
+        normalizer.estimator_mut().import(stats.clone());
+    }
+    let normalization_mode = config.normalization.mode();
+    // An imported calibration leaves nothing to warm up.
+    let warmup_depths = if config.calibration.is_some() {
+        0
+    } else {
+        config.warmup.warmup_depths
+    };
     let mut events = Vec::new();
 
     let initial_alpha = if config.adaptive_alpha {
@@ -90,35 +122,61 @@ pub fn execute_soft_search_core(
         config.norm_alpha
     };
     let mut adaptive_state = crate::AdaptiveAlphaState::new(initial_alpha);
+    let mut alpha_trace = config
+        .adaptive_alpha
+        .then(|| AlphaTrace::new(AlphaConfig::default(), initial_alpha));
     let mut delta_hv_window = VecDeque::<f64>::new();
+    let mut convergence = config
+        .convergence
+        .map(crate::capability::convergence::ConvergenceMonitor::new);
+    let mut novelty = config
+        .novelty
+        .map(crate::capability::novelty::NoveltyArchive::new);
+    let mut diversity = config
+        .diversity
+        .map(crate::capability::diversity_schedule::DiversityController::new);
+    let strategy = crate::capability::rule_sampling::selection_strategy(&params);
+    let mut ranking_cache = RuleRankingCache::default();
+    let mut field_cache = crate::runtime::trace_helpers::FieldCache::default();
+    let mut improvement = ImprovementTracker::default();
+    let mut stage_throughput = PipelineThroughput::default();
+    let mut quarantined = Vec::new();
+    // Mean front resonance of the previous depth.
+    let mut field_level = 0.0f64;
 
+    let call_counts = crate::CallCountSession::start();
     for depth in 1..=config.depth {
-        let calls_start = crate::DISTANCE_CALL_COUNT.load(std::sync::atomic::Ordering::Relaxed);
-        let nn_calls_start =
-            crate::NN_DISTANCE_CALL_COUNT.load(std::sync::atomic::Ordering::Relaxed);
+        let calls_start = call_counts.counts();
         let norm_alpha_val = if config.adaptive_alpha {
             adaptive_state.alpha
         } else {
             config.norm_alpha
         };
         let mu = 0.0f64;
+        let pressure = diversity
+            .as_mut()
+            .map(|controller| controller.next(depth, rows.last()));
         let batch = crate::runtime::trace_helpers::build_soft_candidates_for_frontier(
             &mut hybrid_vm,
             &frontier,
             config.beam.max(1),
             depth,
             crate::runtime::trace_helpers::SoftSelectionParams {
-                alpha: params.alpha,
-                temperature: params.temperature,
-                entropy_beta: params.entropy_beta,
+                strategy: strategy.as_ref(),
+                seed: config.seed,
+                max_depth: config.depth,
+                field_level,
+                lambda,
             },
             crate::runtime::trace_helpers::SoftCandidateContext {
                 field: &field,
                 shm: &shm,
                 field_profile: params.field_profile,
+                repair: config.repair,
             },
+            config.candidate_pipeline,
+            &mut ranking_cache,
             &mut field_cache,
-            &mut field_cache_order,
         );
         let candidates = batch.candidates;
         let expanded_categories_count = batch.depth_category_counts.len();
@@ -132,6 +190,11 @@ pub fn execute_soft_search_core(
         let field_score_us = batch.field_score_us;
         let field_aggregate_us = batch.field_aggregate_us;
         let field_total_us = batch.field_total_us;
+        let repairs = batch.repairs;
+        stage_throughput.absorb(batch.throughput);
+        let quarantined_count = batch.quarantined.len();
+        let merged_duplicates = batch.merged_duplicates;
+        quarantined.extend(batch.quarantined);
 
         if let Some(path) = &config.raw_output_path {
             let objectives = candidates
@@ -144,29 +207,22 @@ pub fn execute_soft_search_core(
                 objectives,
             });
         }
+        // Everything past the raw dump ranks, so minimized axes flip here.
+        let candidates = candidates
+            .into_iter()
+            .map(|(state, obj)| {
+                let oriented = config.directions.orient(&obj);
+                (state, oriented)
+            })
+            .collect::<Vec<_>>();
 
-        if depth <= warmup_depths {
-            estimator.samples.extend(
-                candidates.iter().map(|(_, o)| {
-                    crate::ObjectiveRaw(crate::runtime::trace_helpers::obj_to_arr(o))
-                }),
-            );
-            if depth == warmup_depths {
-                estimator.frozen = crate::runtime::trace_helpers::robust_stats_from_samples(
-                    &estimator.samples,
-                    norm_alpha_val,
-                );
-            }
-        }
+        let raw_samples = candidates
+            .iter()
+            .map(|(_, o)| crate::ObjectiveRaw(crate::runtime::trace_helpers::obj_to_arr(o)))
+            .collect::<Vec<_>>();
+        estimator.observe(&raw_samples, norm_alpha_val);
         let stats = estimator
-            .frozen
-            .clone()
-            .or_else(|| {
-                crate::runtime::trace_helpers::robust_stats_from_samples(
-                    &estimator.samples,
-                    norm_alpha_val,
-                )
-            })
+            .stats(norm_alpha_val)
             .unwrap_or(crate::GlobalRobustStats {
                 alpha_used: norm_alpha_val,
                 median: [0.0; 4],
@@ -240,7 +296,7 @@ pub fn execute_soft_search_core(
                 norm_mad_3: stats.mad[3] as f32,
                 median_nn_dist_all_depth: 0.0,
                 collapse_flag: false,
-                normalization_mode: "global_robust".to_string(),
+                normalization_mode: normalization_mode.clone(),
                 unique_norm_vec_count: 0,
                 norm_dim_mad_zero_count: 0,
                 mean_nn_dist_raw: 0.0,
@@ -263,11 +319,26 @@ pub fn execute_soft_search_core(
                 effective_dim: 0,
                 effective_dim_ratio: 0.0,
                 collapse_reasons: String::new(),
+                convergence_reason: String::new(),
+                novelty_archive_size: novelty.as_ref().map_or(0, |a| a.len()),
+                novelty_mean: 0.0,
+                repairs_reconnected: repairs.reconnected,
+                repairs_collapsed: repairs.collapsed,
+                hv_improvement: 0.0,
+                best_score_improvement: 0.0,
+                quarantined_count,
+                merged_duplicates,
+                diversity_trigger: String::new(),
+                correlation_id: String::new(),
             });
             continue;
         }
 
-        let (normalized, _) = crate::normalize_by_depth(candidates, norm_alpha_val);
+        let raw_by_id = candidates
+            .iter()
+            .map(|(state, o)| (state.id, o.clone()))
+            .collect::<BTreeMap<_, _>>();
+        let normalized = normalizer.normalize(candidates, norm_alpha_val);
         let norm_data: Vec<[f64; 4]> = normalized
             .iter()
             .map(|(_, obj)| crate::runtime::trace_helpers::obj_to_arr(obj))
@@ -284,6 +355,23 @@ pub fn execute_soft_search_core(
             )];
             continue;
         }
+        // Un-normalized objectives keep hypervolume comparable across depths.
+        let front_raw = front
+            .iter()
+            .filter_map(|(state, _)| raw_by_id.get(&state.id))
+            .collect::<Vec<_>>();
+        let front_hv_raw = crate::hv_4d_from_origin_normalized(
+            &front_raw
+                .iter()
+                .map(|o| crate::runtime::trace_helpers::obj_to_arr(o))
+                .collect::<Vec<_>>(),
+        );
+        let best_score = front_raw
+            .iter()
+            .map(|o| crate::scalar_score(o))
+            .fold(f64::NEG_INFINITY, f64::max);
+        let (hv_improvement, best_score_improvement) =
+            improvement.observe(depth, front_hv_raw, best_score);
 
         let front_norm = front
             .iter()
@@ -301,6 +389,7 @@ pub fn execute_soft_search_core(
                 .collect::<Vec<_>>(),
         );
         let resonance_avg = front.iter().map(|(_, o)| o.f_field).sum::<f64>() / front.len() as f64;
+        field_level = resonance_avg;
         let pareto_mean_nn = crate::engine::pareto::mean_nn_dist_norm(&front_norm, &stats.weights);
         let pareto_spacing = crate::engine::pareto::spacing_norm(&front_norm, &stats.weights);
         let pareto_hv_2d = crate::engine::pareto::pareto_hv_2d_norm(&front_norm);
@@ -334,15 +423,17 @@ pub fn execute_soft_search_core(
             pareto_mean_nn,
         );
 
-        if config.adaptive_alpha && depth > warmup_depths {
-            adaptive_state = crate::calculate_adaptive_alpha(
-                &adaptive_state,
+        if let Some(trace) = alpha_trace.as_mut() {
+            let inputs = AlphaInputs::new(
+                adaptive_state.alpha,
                 &stats,
                 pareto_mean_nn,
                 front.len(),
                 0.01,
                 stability_metrics.effective_dim,
             );
+            let decision = trace.record(depth, inputs, depth <= warmup_depths);
+            adaptive_state = adaptive_state.apply(decision);
         }
         let norm_dim_mad_zero_count = stats.mad.iter().filter(|&&m| m.abs() < 1e-9).count();
 
@@ -350,10 +441,7 @@ pub fn execute_soft_search_core(
         let pareto_mean_nn_raw = crate::engine::pareto::pareto_mean_nn_distance(&front_refs);
         let pareto_spacing_raw = crate::engine::pareto::pareto_spacing_metric(&front_refs);
 
-        let calls_end = crate::DISTANCE_CALL_COUNT.load(std::sync::atomic::Ordering::Relaxed);
-        let nn_calls_end = crate::NN_DISTANCE_CALL_COUNT.load(std::sync::atomic::Ordering::Relaxed);
-        let distance_calls = calls_end.saturating_sub(calls_start);
-        let nn_distance_calls = nn_calls_end.saturating_sub(nn_calls_start);
+        let calls = call_counts.counts().since(calls_start);
         let mem_telemetry = hybrid_vm.take_memory_telemetry();
 
         rows.push(crate::TraceRow {
@@ -368,8 +456,8 @@ pub fn execute_soft_search_core(
             pareto_size: front.len(),
             diversity: depth_boundary_diversity as f32,
             resonance_avg: resonance_avg as f32,
-            pressure: 0.0,
-            epsilon_effect: 0.0,
+            pressure: pressure.as_ref().map_or(0.0, |p| p.pressure as f32),
+            epsilon_effect: pressure.as_ref().map_or(0.0, |p| p.epsilon_effect as f32),
             target_local_weight: 0.5,
             target_global_weight: 0.5,
             local_global_distance: 0.0,
@@ -410,15 +498,15 @@ pub fn execute_soft_search_core(
             norm_mad_3: stats.mad[3] as f32,
             median_nn_dist_all_depth: 0.0,
             collapse_flag: false,
-            normalization_mode: "global_robust".to_string(),
+            normalization_mode: normalization_mode.clone(),
             unique_norm_vec_count,
             norm_dim_mad_zero_count,
             mean_nn_dist_raw: pareto_mean_nn_raw as f32,
             mean_nn_dist_norm: pareto_mean_nn as f32,
             pareto_spacing_raw: pareto_spacing_raw as f32,
             pareto_spacing_norm: pareto_spacing as f32,
-            distance_calls,
-            nn_distance_calls,
+            distance_calls: calls.distance,
+            nn_distance_calls: calls.nn_distance,
             weak_dim_count,
             effective_dim_count,
             redundancy_flags: stability_metrics.redundancy_flags.join("|"),
@@ -427,10 +515,39 @@ pub fn execute_soft_search_core(
             effective_dim: stability_metrics.effective_dim,
             effective_dim_ratio: stability_metrics.effective_dim_ratio as f32,
             collapse_reasons: stability_metrics.collapse_reasons.join("|"),
+            convergence_reason: String::new(),
+            novelty_archive_size: 0,
+            novelty_mean: 0.0,
+            repairs_reconnected: repairs.reconnected,
+            repairs_collapsed: repairs.collapsed,
+            hv_improvement: hv_improvement as f32,
+            best_score_improvement: best_score_improvement as f32,
+            quarantined_count,
+            merged_duplicates,
+            diversity_trigger: pressure
+                .as_ref()
+                .map(|p| p.trigger.label())
+                .unwrap_or_default(),
+            correlation_id: String::new(),
         });
 
-        let (selected, current_hv, delta_hv_selected) = if config.hv_guided {
+        let novelty_selection = novelty.as_ref().filter(|a| a.config().weight > 0.0);
+        let (selected, current_hv, delta_hv_selected) = if let Some(archive) = novelty_selection {
+            (archive.select(front, &field, config.beam.max(1)), 0.0, 0.0)
+        } else if config.hv_guided {
             crate::engine::pareto::select_beam_hv_guided_norm(front, front_norm, config.beam.max(1))
+        } else if let Some(pressure) = &pressure {
+            (
+                crate::engine::pareto::select_beam_pressure_norm(
+                    front,
+                    front_norm,
+                    config.beam.max(1),
+                    &stats.weights,
+                    pressure.pressure,
+                ),
+                0.0,
+                0.0,
+            )
         } else {
             (
                 crate::engine::pareto::select_beam_maxmin_norm(
@@ -443,7 +560,7 @@ pub fn execute_soft_search_core(
                 0.0,
             )
         };
-        if config.hv_guided {
+        if config.hv_guided && novelty_selection.is_none() {
             eprintln!(
                 "hv_guided iteration={} current_HV={:.8} delta_HV_selected={:.8} frontier_size={}",
                 depth,
@@ -460,11 +577,27 @@ pub fn execute_soft_search_core(
             .into_iter()
             .map(|(s, _)| s)
             .collect::<Vec<DesignState>>();
+        if let Some(archive) = novelty.as_mut() {
+            let mean = archive.accept(&frontier, &field);
+            if let Some(row) = rows.last_mut() {
+                row.novelty_archive_size = archive.len();
+                row.novelty_mean = mean as f32;
+            }
+        }
         if frontier.is_empty() {
             frontier = vec![crate::runtime::trace_helpers::trace_initial_state(
                 config.seed,
             )];
         }
+        if let Some(monitor) = convergence.as_mut()
+            && let Some(reason) =
+                monitor.observe(front_hv_raw, entropy_per_depth as f64, lambda - lambda_old)
+        {
+            if let Some(row) = rows.last_mut() {
+                row.convergence_reason = reason.as_str().to_string();
+            }
+            break;
+        }
         if config.hv_guided && delta_hv_window.len() == HV_STOP_WINDOW {
             let mean_delta = delta_hv_window.iter().sum::<f64>() / HV_STOP_WINDOW as f64;
             if mean_delta < HV_STOP_EPS {
@@ -492,6 +625,12 @@ pub fn execute_soft_search_core(
         },
         trace: rows,
         events,
+        calibration: estimator.export(),
+        ranking_cache: ranking_cache.stats(),
+        improvement: improvement.summary(),
+        stage_throughput,
+        quarantined,
+        alpha_trace,
     }
 }
 
@@ -500,13 +639,13 @@ pub fn execute_trace_core(config: crate::TraceRunConfig) -> SearchCoreResult {
 }
 
 pub fn execute_baseline_off_core(config: crate::TraceRunConfig) -> SearchCoreResult {
-    let trace = execute_soft_search_core(config, crate::SoftTraceParams::default()).trace;
+    let result = execute_soft_search_core(config, crate::SoftTraceParams::default());
     SearchCoreResult {
         best: Hypothesis {
             id: "baseline-off".to_string(),
-            content: format!("rows={}", trace.len()),
+            content: format!("rows={}", result.trace.len()),
         },
-        trace,
+        trace: result.trace,
         events: vec![
             AgentEvent::PersistMemory {
                 key: "trace/baseline_off".to_string(),
@@ -517,6 +656,12 @@ pub fn execute_baseline_off_core(config: crate::TraceRunConfig) -> SearchCoreRes
                 value: "1".to_string(),
             }),
         ],
+        calibration: result.calibration,
+        ranking_cache: result.ranking_cache,
+        improvement: result.improvement,
+        stage_throughput: result.stage_throughput,
+        quarantined: result.quarantined,
+        alpha_trace: result.alpha_trace,
     }
 }
 
@@ -525,13 +670,13 @@ pub fn execute_balanced_core(config: crate::TraceRunConfig, m: usize) -> SearchC
         alpha: (m as f64 / 10.0).clamp(0.1, 1.0),
         ..crate::SoftTraceParams::default()
     };
-    let trace = execute_soft_search_core(config, params).trace;
+    let result = execute_soft_search_core(config, params);
     SearchCoreResult {
         best: Hypothesis {
             id: "balanced".to_string(),
-            content: format!("rows={}", trace.len()),
+            content: format!("rows={}", result.trace.len()),
         },
-        trace,
+        trace: result.trace,
         events: vec![
             AgentEvent::PersistMemory {
                 key: "trace/balanced".to_string(),
@@ -542,5 +687,11 @@ pub fn execute_balanced_core(config: crate::TraceRunConfig, m: usize) -> SearchC
                 value: "1".to_string(),
             }),
         ],
+        calibration: result.calibration,
+        ranking_cache: result.ranking_cache,
+        improvement: result.improvement,
+        stage_throughput: result.stage_throughput,
+        quarantined: result.quarantined,
+        alpha_trace: result.alpha_trace,
     }
 }
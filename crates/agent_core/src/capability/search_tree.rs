@@ -0,0 +1,138 @@
+use std::fmt::Write as _;
+
+use core_types::ObjectiveVector;
+use hybrid_vm::RuleId;
+use memory_space::StateId;
+
+/// Default cap on recorded nodes; deep or wide runs stop recording past it.
+pub const DEFAULT_MAX_TREE_NODES: usize = 10_000;
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchTreeNode {
+    pub state_id: StateId,
+    /// `None` for the root.
+    pub parent: Option<StateId>,
+    /// Rule applied to `parent` to produce this state; `None` for the root
+    /// and for crossover children, whose `parent` is the left parent.
+    pub rule_id: Option<RuleId>,
+    pub depth: usize,
+    pub objective: ObjectiveVector,
+    /// Whether the state survived beam truncation at its depth.
+    pub kept: bool,
+}
+
+/// Every candidate a beam search expanded, linked to the state it came from.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SearchTree {
+    pub nodes: Vec<SearchTreeNode>,
+    pub max_nodes: usize,
+    /// Set once a node was dropped because `max_nodes` was reached.
+    pub truncated: bool,
+}
+
+impl SearchTree {
+    pub fn new(max_nodes: usize) -> Self {
+        Self {
+            nodes: Vec::new(),
+            max_nodes,
+            truncated: false,
+        }
+    }
+
+    /// Returns false (and marks the tree truncated) when full.
+    pub(crate) fn record(&mut self, node: SearchTreeNode) -> bool {
+        if self.nodes.len() >= self.max_nodes {
+            self.truncated = true;
+            return false;
+        }
+        self.nodes.push(node);
+        true
+    }
+
+    pub(crate) fn mark_kept(&mut self, depth: usize, kept: &[StateId]) {
+        for node in self.nodes.iter_mut().filter(|n| n.depth == depth) {
+            node.kept = kept.contains(&node.state_id);
+        }
+    }
+
+    pub fn root(&self) -> Option<&SearchTreeNode> {
+        self.nodes.iter().find(|n| n.parent.is_none())
+    }
+
+    pub fn children(&self, parent: StateId) -> impl Iterator<Item = &SearchTreeNode> {
+        self.nodes.iter().filter(move |n| n.parent == Some(parent))
+    }
+
+    /// Path from the root to `state_id`, root first. Empty if not recorded.
+    pub fn lineage(&self, state_id: StateId) -> Vec<&SearchTreeNode> {
+        let mut path = Vec::new();
+        let mut cursor = Some(state_id);
+        while let Some(id) = cursor {
+            let Some(node) = self.nodes.iter().find(|n| n.state_id == id) else {
+                break;
+            };
+            path.push(node);
+            cursor = node.parent;
+        }
+        path.reverse();
+        path
+    }
+
+    /// Graphviz rendering. Kept states are drawn bold, edges carry rule ids.
+    pub fn to_dot(&self) -> String {
+        let mut out = String::from("digraph search_tree {\n  node [shape=box];\n");
+        for node in &self.nodes {
+            let o = &node.objective;
+            let _ = writeln!(
+                out,
+                "  \"{}\" [label=\"d{} {}\\ns={:.3} f={:.3} r={:.3} c={:.3}\"{}];",
+                dot_id(node.state_id),
+                node.depth,
+                short_id(node.state_id),
+                o.f_struct,
+                o.f_field,
+                1.0 - o.f_risk,
+                1.0 - o.f_shape,
+                if node.kept { ", style=bold" } else { "" }
+            );
+        }
+        for node in &self.nodes {
+            if let Some(parent) = node.parent {
+                let label = node
+                    .rule_id
+                    .map(|id| id.as_u128().to_string())
+                    .unwrap_or_default();
+                let _ = writeln!(
+                    out,
+                    "  \"{}\" -> \"{}\" [label=\"{label}\"];",
+                    dot_id(parent),
+                    dot_id(node.state_id)
+                );
+            }
+        }
+        if self.truncated {
+            let _ = writeln!(
+                out,
+                "  truncated [shape=note, label=\"truncated at {} nodes\"];",
+                self.max_nodes
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+fn dot_id(id: StateId) -> String {
+    format!("{:032x}", id.as_u128())
+}
+
+fn short_id(id: StateId) -> String {
+    format!("{:08x}", id.as_u128() as u32)
+}
@@ -3,6 +3,8 @@ use std::collections::BTreeMap;
 use core_types::ObjectiveVector;
 use memory_space::{DesignState, StateId};
 
+use crate::{EpsilonConstraint, LexicographicLevel, LexicographicOrder, ObjectiveDirections};
+
 const SELECTION_W1_QUALITY: f64 = 0.60;
 const SELECTION_W2_PRESSURE: f64 = 0.25;
 const SELECTION_W3_STABILITY: f64 = 0.15;
@@ -45,6 +47,125 @@ pub fn soft_front_rank(
         .collect::<Vec<_>>()
 }
 
+/// Feasible candidates first (best `optimize` value under `directions`
+/// first), then infeasible ones by ascending violation. Ties break on state
+/// id.
+pub fn epsilon_constraint_rank(
+    candidates: Vec<(DesignState, ObjectiveVector)>,
+    constraint: &EpsilonConstraint,
+    directions: ObjectiveDirections,
+) -> Vec<(DesignState, ObjectiveVector)> {
+    let mut dedup: BTreeMap<StateId, (DesignState, ObjectiveVector)> = BTreeMap::new();
+    for (state, obj) in candidates {
+        dedup.entry(state.id).or_insert((state, obj));
+    }
+    let mut entries = dedup
+        .into_values()
+        .map(|(state, obj)| {
+            let (violation, value) = constraint.key(&obj, directions);
+            (violation, value, state, obj)
+        })
+        .collect::<Vec<_>>();
+    entries.sort_by(|l, r| {
+        l.0.total_cmp(&r.0)
+            .then_with(|| r.1.total_cmp(&l.1))
+            .then_with(|| l.2.id.cmp(&r.2.id))
+    });
+    entries
+        .into_iter()
+        .map(|(_, _, state, obj)| (state, obj))
+        .collect()
+}
+
+/// Candidates within tolerance of the best on the first level of `order`
+/// come first, ordered by the later levels, then the remaining candidates
+/// ranked the same way. Also returns how many candidates were still level
+/// with the best after each level. Ties break on state id.
+pub fn lexicographic_rank(
+    candidates: Vec<(DesignState, ObjectiveVector)>,
+    order: &LexicographicOrder,
+    directions: ObjectiveDirections,
+) -> (Vec<(DesignState, ObjectiveVector)>, Vec<usize>) {
+    let mut dedup: BTreeMap<StateId, (DesignState, ObjectiveVector)> = BTreeMap::new();
+    for (state, obj) in candidates {
+        dedup.entry(state.id).or_insert((state, obj));
+    }
+    let entries = dedup.into_values().collect::<Vec<_>>();
+    let levels = order.levels().collect::<Vec<_>>();
+    let values = entries
+        .iter()
+        .map(|(_, obj)| {
+            let oriented = directions.orient(obj);
+            levels
+                .iter()
+                .map(|level| level.axis.value(&oriented))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut tied = Vec::with_capacity(levels.len());
+    let mut leading = (0..entries.len()).collect::<Vec<_>>();
+    for (level, spec) in levels.iter().enumerate() {
+        leading = leading_band(leading, &values, level, spec).0;
+        tied.push(leading.len());
+    }
+
+    let mut order_idx = Vec::with_capacity(entries.len());
+    rank_band(
+        (0..entries.len()).collect(),
+        &values,
+        &levels,
+        0,
+        &mut order_idx,
+    );
+    let mut slots = entries.into_iter().map(Some).collect::<Vec<_>>();
+    let ranked = order_idx
+        .into_iter()
+        .filter_map(|idx| slots[idx].take())
+        .collect();
+    (ranked, tied)
+}
+
+/// Splits `indices` into those within `spec.tolerance` of the best value on
+/// `level` and the rest, both in their original order.
+fn leading_band(
+    indices: Vec<usize>,
+    values: &[Vec<f64>],
+    level: usize,
+    spec: &LexicographicLevel,
+) -> (Vec<usize>, Vec<usize>) {
+    let best = indices
+        .iter()
+        .map(|&i| values[i][level])
+        .fold(f64::NEG_INFINITY, f64::max);
+    let floor = best - spec.tolerance.max(0.0);
+    indices
+        .into_iter()
+        .partition(|&i| values[i][level] >= floor)
+}
+
+fn rank_band(
+    mut indices: Vec<usize>,
+    values: &[Vec<f64>],
+    levels: &[LexicographicLevel],
+    level: usize,
+    out: &mut Vec<usize>,
+) {
+    let Some(spec) = levels.get(level) else {
+        out.extend(indices);
+        return;
+    };
+    while !indices.is_empty() {
+        if indices.len() == 1 {
+            out.append(&mut indices);
+            return;
+        }
+        let (band, rest) = leading_band(indices, values, level, spec);
+        rank_band(band, values, levels, level + 1, out);
+        indices = rest;
+    }
+}
+
 fn selection_score(quality: f64, pressure: f64, stability: f64) -> f64 {
     SELECTION_W1_QUALITY * quality
         + SELECTION_W2_PRESSURE * pressure
@@ -0,0 +1,259 @@
+//! Archive of the states a run accepted, searchable by similarity.
+//!
+//! Every state that made it into a beam is kept with its depth, where it came
+//! from, its field projection and a canonical hash of its structure. The
+//! hash ignores node ids, so the same design reached along two paths (and
+//! given different ids on the way) is recognised as identical; otherwise
+//! closeness is measured between field projections. `find_similar` answers
+//! "what earlier design looks like this one", and `lineage` recovers the
+//! rule path that produced an entry.
+
+use std::borrow::Cow;
+use std::collections::BTreeMap;
+
+use field_engine::{FieldEngine, FieldVector};
+use hybrid_vm::RuleId;
+use memory_space::{DesignState, StateId, StructuralGraph, Value};
+
+use super::rng::mix64;
+
+/// How an archived state was produced; both `None` for the initial state.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StateOrigin {
+    pub parent: Option<StateId>,
+    /// `None` for crossover offspring.
+    pub rule_id: Option<RuleId>,
+}
+
+#[derive(Clone, Debug)]
+pub struct ArchivedState {
+    pub state: DesignState,
+    pub depth: usize,
+    pub origin: StateOrigin,
+    pub canonical_hash: u64,
+    pub projection: FieldVector,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct SimilarState<'a> {
+    pub entry: &'a ArchivedState,
+    /// 1 for a structurally identical state, else `1 / (1 + distance)`
+    /// between field projections.
+    pub similarity: f64,
+    pub identical: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct StateArchive {
+    field: FieldEngine,
+    entries: Vec<ArchivedState>,
+    by_id: BTreeMap<StateId, usize>,
+    by_hash: BTreeMap<u64, Vec<usize>>,
+}
+
+impl StateArchive {
+    /// Projects states through `field`; queries must come from the same
+    /// design space for distances to mean anything.
+    pub fn new(field: FieldEngine) -> Self {
+        Self {
+            field,
+            entries: Vec::new(),
+            by_id: BTreeMap::new(),
+            by_hash: BTreeMap::new(),
+        }
+    }
+
+    /// Adds `state` unless a state with its id is already archived; returns
+    /// whether it was added.
+    pub fn record(&mut self, state: &DesignState, depth: usize, origin: StateOrigin) -> bool {
+        if self.by_id.contains_key(&state.id) {
+            return false;
+        }
+        let index = self.entries.len();
+        let canonical_hash = canonical_hash(&state.graph);
+        self.entries.push(ArchivedState {
+            state: state.clone(),
+            depth,
+            origin,
+            canonical_hash,
+            projection: self.field.aggregate_state(state),
+        });
+        self.by_id.insert(state.id, index);
+        self.by_hash.entry(canonical_hash).or_default().push(index);
+        true
+    }
+
+    /// The `k` archived states most similar to `state`, most similar first;
+    /// ties go to the shallower, then earlier-archived entry. `state` itself
+    /// is never returned, archived or not.
+    pub fn find_similar(&self, state: &DesignState, k: usize) -> Vec<SimilarState<'_>> {
+        if k == 0 {
+            return Vec::new();
+        }
+        let hash = canonical_hash(&state.graph);
+        let projection = self.field.aggregate_state(state);
+        let mut scored = self
+            .entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.state.id != state.id)
+            .map(|(index, entry)| {
+                let identical = entry.canonical_hash == hash;
+                let similarity = if identical {
+                    1.0
+                } else {
+                    1.0 / (1.0 + crate::diversity::l2_distance(&entry.projection, &projection))
+                };
+                (index, similarity, identical)
+            })
+            .collect::<Vec<_>>();
+        scored.sort_by(|a, b| {
+            b.1.total_cmp(&a.1)
+                .then_with(|| self.entries[a.0].depth.cmp(&self.entries[b.0].depth))
+                .then_with(|| a.0.cmp(&b.0))
+        });
+        scored
+            .into_iter()
+            .take(k)
+            .map(|(index, similarity, identical)| SimilarState {
+                entry: &self.entries[index],
+                similarity,
+                identical,
+            })
+            .collect()
+    }
+
+    /// Archived states whose structure hashes to `hash`, in archive order.
+    pub fn with_hash(&self, hash: u64) -> impl Iterator<Item = &ArchivedState> {
+        self.by_hash
+            .get(&hash)
+            .into_iter()
+            .flatten()
+            .map(|&index| &self.entries[index])
+    }
+
+    pub fn get(&self, state_id: StateId) -> Option<&ArchivedState> {
+        self.by_id.get(&state_id).map(|&index| &self.entries[index])
+    }
+
+    /// Archived ancestors of `state_id` followed by the state itself, root
+    /// first; stops at the first parent that was never archived.
+    pub fn lineage(&self, state_id: StateId) -> Vec<&ArchivedState> {
+        let mut chain = Vec::new();
+        let mut current = self.get(state_id);
+        while let Some(entry) = current {
+            chain.push(entry);
+            if chain.len() > self.entries.len() {
+                break;
+            }
+            current = entry.origin.parent.and_then(|parent| self.get(parent));
+        }
+        chain.reverse();
+        chain
+    }
+
+    /// Entries in the order they were archived.
+    pub fn entries(&self) -> &[ArchivedState] {
+        &self.entries
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Structural hash that ignores node ids: node kinds and attributes refined
+/// twice over in- and out-neighbourhoods (Weisfeiler-Lehman), then combined
+/// order-independently.
+pub fn canonical_hash(graph: &StructuralGraph) -> u64 {
+    canonical_hash_with(graph, |key| Cow::Borrowed(key))
+}
+
+/// `canonical_hash` with every attribute key passed through `key` first.
+/// Attributes are hashed in canonical-key order.
+pub(crate) fn canonical_hash_with(
+    graph: &StructuralGraph,
+    key: impl Fn(&str) -> Cow<'_, str>,
+) -> u64 {
+    const ROUNDS: usize = 2;
+    let mut labels = graph
+        .nodes()
+        .iter()
+        .map(|(id, node)| {
+            let mut attributes = node
+                .attributes
+                .iter()
+                .map(|(k, value)| (key(k), value_bytes(value)))
+                .collect::<Vec<_>>();
+            attributes.sort();
+            let mut h = fnv(FNV_OFFSET, node.kind.as_bytes());
+            for (key, value) in &attributes {
+                h = fnv(h, key.as_bytes());
+                h = fnv(h, value);
+            }
+            (*id, h)
+        })
+        .collect::<BTreeMap<_, _>>();
+    for _ in 0..ROUNDS {
+        let mut incoming: BTreeMap<_, Vec<u64>> = BTreeMap::new();
+        let mut outgoing: BTreeMap<_, Vec<u64>> = BTreeMap::new();
+        for (from, to) in graph.edges() {
+            if let (Some(&l_from), Some(&l_to)) = (labels.get(from), labels.get(to)) {
+                outgoing.entry(*from).or_default().push(l_to);
+                incoming.entry(*to).or_default().push(l_from);
+            }
+        }
+        labels = labels
+            .iter()
+            .map(|(id, label)| {
+                let mut h = mix64(*label);
+                for (tag, neighbours) in [
+                    (0x51u64, outgoing.get_mut(id)),
+                    (0xa3, incoming.get_mut(id)),
+                ] {
+                    let mut sorted = neighbours.map(std::mem::take).unwrap_or_default();
+                    sorted.sort_unstable();
+                    h = mix64(h ^ tag);
+                    for neighbour in sorted {
+                        h = mix64(h ^ neighbour);
+                    }
+                }
+                (*id, h)
+            })
+            .collect();
+    }
+    let mut finals = labels.into_values().collect::<Vec<_>>();
+    finals.sort_unstable();
+    finals.into_iter().fold(
+        mix64(graph.nodes().len() as u64 ^ ((graph.edges().len() as u64) << 32)),
+        |acc, label| mix64(acc ^ label),
+    )
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+fn fnv(mut h: u64, bytes: &[u8]) -> u64 {
+    for byte in bytes {
+        h ^= u64::from(*byte);
+        h = h.wrapping_mul(0x0100_0000_01b3);
+    }
+    // Length-terminated so adjacent fields cannot run together.
+    h ^= bytes.len() as u64;
+    h.wrapping_mul(0x0100_0000_01b3)
+}
+
+fn value_bytes(value: &Value) -> Vec<u8> {
+    let (tag, mut bytes) = match value {
+        Value::Int(v) => (0u8, v.to_le_bytes().to_vec()),
+        Value::Float(v) => (1, v.to_bits().to_le_bytes().to_vec()),
+        Value::Bool(v) => (2, vec![u8::from(*v)]),
+        Value::Text(v) => (3, v.as_bytes().to_vec()),
+    };
+    bytes.insert(0, tag);
+    bytes
+}
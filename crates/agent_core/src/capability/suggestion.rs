@@ -0,0 +1,224 @@
+//! Extra rule candidates from outside the rule set, e.g. a language model or
+//! a heuristic service.
+//!
+//! A `RuleSuggester` proposes `DesignRule`s for one frontier state. Nothing
+//! it returns is trusted: each rule must pass `Precondition::validate`, hold
+//! on the state it was proposed for, and survive a sandboxed application
+//! whose preview is scored before the child joins the candidate pool.
+//! Admitted and rejected suggestions are both reported in
+//! `SearchResult::suggestions`, so suggested children can be told apart from
+//! rule-set ones.
+
+use std::collections::BTreeSet;
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use core_types::ObjectiveVector;
+use hybrid_vm::{DesignRule, PreconditionError, RuleId, Shm};
+use memory_space::{DesignState, GraphDiff, StateId};
+
+use super::apply::apply_atomic;
+use crate::BeamSearch;
+
+/// Source of rule candidates beyond `Shm`. Called once per frontier state
+/// and depth; implementations may block on a remote service.
+pub trait RuleSuggester: Send + Sync {
+    /// Recorded as the provenance of every suggestion.
+    fn name(&self) -> &str;
+
+    fn suggest(&self, state: &DesignState, depth: usize) -> Vec<DesignRule>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SuggestionConfig {
+    /// Suggestions past this many per state are dropped unexamined.
+    pub max_per_state: usize,
+    /// Rejects children whose previewed objective is dominated by their
+    /// parent's.
+    pub reject_regressions: bool,
+}
+
+impl Default for SuggestionConfig {
+    fn default() -> Self {
+        Self {
+            max_per_state: 8,
+            reject_regressions: true,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SuggestionRejection {
+    /// Reuses the id of a rule-set rule or of an earlier suggestion.
+    DuplicateId,
+    InvalidPrecondition(PreconditionError),
+    /// NaN or infinite priority or expected effect.
+    NonFinite,
+    PreconditionUnmet,
+    /// Applying the rule panicked in the sandbox or, in debug builds,
+    /// produced a graph that failed validation.
+    ApplyFailed,
+    /// The rule left the graph unchanged.
+    NoChange,
+    /// The preview scored the child with a NaN or infinite objective.
+    NonFiniteObjective,
+    /// The child is dominated by its parent; see
+    /// `SuggestionConfig::reject_regressions`.
+    Regression,
+}
+
+impl std::fmt::Display for SuggestionRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DuplicateId => f.write_str("rule id already in use"),
+            Self::InvalidPrecondition(err) => write!(f, "invalid precondition: {err}"),
+            Self::NonFinite => f.write_str("priority or expected effect is not finite"),
+            Self::PreconditionUnmet => f.write_str("precondition does not hold"),
+            Self::ApplyFailed => f.write_str("rule application failed"),
+            Self::NoChange => f.write_str("rule does not change the graph"),
+            Self::NonFiniteObjective => f.write_str("previewed objective is not finite"),
+            Self::Regression => f.write_str("child is dominated by its parent"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SuggestionOutcome {
+    Admitted {
+        child: StateId,
+        /// Previewed `child - parent` per axis.
+        objective_delta: ObjectiveVector,
+    },
+    Rejected(SuggestionRejection),
+}
+
+/// Provenance of one suggested rule.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SuggestedRule {
+    pub suggester: String,
+    pub rule_id: RuleId,
+    pub parent: StateId,
+    /// Depth the child would be expanded at.
+    pub depth: usize,
+    pub outcome: SuggestionOutcome,
+}
+
+impl SuggestedRule {
+    pub fn is_admitted(&self) -> bool {
+        matches!(self.outcome, SuggestionOutcome::Admitted { .. })
+    }
+
+    pub fn child(&self) -> Option<StateId> {
+        match self.outcome {
+            SuggestionOutcome::Admitted { child, .. } => Some(child),
+            SuggestionOutcome::Rejected(_) => None,
+        }
+    }
+}
+
+/// Asks `suggester` for rules on `state`, vets them, and returns the
+/// admitted `(rule, child)` pairs. Every suggestion examined is appended to
+/// `log`.
+pub(super) fn admit_suggestions(
+    search: &BeamSearch<'_>,
+    suggester: &dyn RuleSuggester,
+    config: SuggestionConfig,
+    state: &DesignState,
+    depth: usize,
+    log: &mut Vec<SuggestedRule>,
+) -> Vec<(RuleId, DesignState)> {
+    let mut seen = BTreeSet::new();
+    let mut admitted = Vec::new();
+    let mut parent_objective = None;
+    for rule in suggester
+        .suggest(state, depth)
+        .into_iter()
+        .take(config.max_per_state)
+    {
+        let outcome = match vet(search.shm, &rule, state, &mut seen) {
+            Err(rejection) => SuggestionOutcome::Rejected(rejection),
+            Ok(child) => {
+                let before = parent_objective
+                    .get_or_insert_with(|| search.evaluator.evaluate(state))
+                    .clone();
+                let after = search.evaluator.evaluate_child(state, &child);
+                if !is_finite(&after) {
+                    SuggestionOutcome::Rejected(SuggestionRejection::NonFiniteObjective)
+                } else if config.reject_regressions
+                    && search.config.directions.dominates(&before, &after)
+                {
+                    SuggestionOutcome::Rejected(SuggestionRejection::Regression)
+                } else {
+                    let outcome = SuggestionOutcome::Admitted {
+                        child: child.id,
+                        objective_delta: ObjectiveVector {
+                            f_struct: after.f_struct - before.f_struct,
+                            f_field: after.f_field - before.f_field,
+                            f_risk: after.f_risk - before.f_risk,
+                            f_shape: after.f_shape - before.f_shape,
+                        },
+                    };
+                    admitted.push((rule.id, child));
+                    outcome
+                }
+            }
+        };
+        log.push(SuggestedRule {
+            suggester: suggester.name().to_string(),
+            rule_id: rule.id,
+            parent: state.id,
+            depth,
+            outcome,
+        });
+    }
+    admitted
+}
+
+/// Static checks, then a sandboxed application. Returns the child state.
+fn vet(
+    shm: &Shm,
+    rule: &DesignRule,
+    state: &DesignState,
+    seen: &mut BTreeSet<RuleId>,
+) -> Result<DesignState, SuggestionRejection> {
+    if shm.rules().iter().any(|known| known.id == rule.id) || !seen.insert(rule.id) {
+        return Err(SuggestionRejection::DuplicateId);
+    }
+    rule.precondition
+        .validate()
+        .map_err(SuggestionRejection::InvalidPrecondition)?;
+    let effect = &rule.expected_effect;
+    let numbers = [
+        rule.priority,
+        effect.delta_struct,
+        effect.delta_field,
+        effect.delta_risk,
+        effect.delta_cost,
+    ];
+    if numbers.iter().any(|v| !v.is_finite()) {
+        return Err(SuggestionRejection::NonFinite);
+    }
+    if !rule.precondition.evaluate(state) {
+        return Err(SuggestionRejection::PreconditionUnmet);
+    }
+    let child = catch_unwind(AssertUnwindSafe(|| apply_atomic(rule, state)))
+        .map_err(|_| SuggestionRejection::ApplyFailed)?
+        .map_err(|_| SuggestionRejection::ApplyFailed)?;
+    if GraphDiff::between(&state.graph, &child.graph).is_empty() {
+        return Err(SuggestionRejection::NoChange);
+    }
+    Ok(child)
+}
+
+fn is_finite(objective: &ObjectiveVector) -> bool {
+    [
+        objective.f_struct,
+        objective.f_field,
+        objective.f_risk,
+        objective.f_shape,
+    ]
+    .iter()
+    .all(|v| v.is_finite())
+}
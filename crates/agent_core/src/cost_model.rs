@@ -0,0 +1,152 @@
+//! Deployment cost estimation behind the cost objective (`1 - f_shape`).
+
+use std::collections::BTreeMap;
+
+use memory_space::{DesignNode, DesignState, Value};
+
+pub trait CostModel: Send + Sync {
+    /// Absolute cost of the design in the model's unit (e.g. USD per month).
+    fn estimate(&self, state: &DesignState) -> f64;
+
+    /// Cost at which the normalized cost saturates at 1.
+    fn budget(&self) -> f64;
+
+    /// `estimate / budget`, clamped to `[0, 1]`; 1 when the budget is not
+    /// positive.
+    fn normalized_cost(&self, state: &DesignState) -> f64 {
+        let budget = self.budget();
+        if budget <= 0.0 {
+            return 1.0;
+        }
+        (self.estimate(state) / budget).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CostMultiplier {
+    /// Scales by the node's numeric `key` attribute, e.g. `replicas`.
+    /// Ignored when the attribute is missing or not a number.
+    PerUnit { key: String },
+    /// Scales by `factor` when `key` equals `value`.
+    WhenEquals {
+        key: String,
+        value: Value,
+        factor: f64,
+    },
+}
+
+impl CostMultiplier {
+    fn factor(&self, node: &DesignNode) -> f64 {
+        match self {
+            Self::PerUnit { key } => match node.attributes.get(key) {
+                Some(Value::Int(n)) => (*n).max(0) as f64,
+                Some(Value::Float(n)) => n.max(0.0),
+                _ => 1.0,
+            },
+            Self::WhenEquals { key, value, factor } => {
+                if node.attributes.get(key) == Some(value) {
+                    *factor
+                } else {
+                    1.0
+                }
+            }
+        }
+    }
+}
+
+/// Table-driven `CostModel`: a unit cost per node kind, a flat cost per edge,
+/// and attribute multipliers applied to each node's unit cost.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CostTable {
+    pub currency: String,
+    pub node_costs: BTreeMap<String, f64>,
+    /// Unit cost for kinds missing from `node_costs`.
+    pub default_node_cost: f64,
+    pub edge_cost: f64,
+    pub multipliers: Vec<CostMultiplier>,
+    pub budget: f64,
+}
+
+#[cfg(feature = "serde")]
+impl core_types::SchemaVersioned for CostTable {
+    const KIND: &'static str = "cost_table";
+    const VERSION: u32 = 1;
+}
+
+impl Default for CostTable {
+    /// Rough monthly list prices for a small cloud deployment.
+    fn default() -> Self {
+        Self {
+            currency: "USD/month".to_string(),
+            node_costs: BTreeMap::from([
+                ("Database".to_string(), 60.0),
+                ("Cache".to_string(), 25.0),
+                ("Queue".to_string(), 15.0),
+                ("LoadBalancer".to_string(), 18.0),
+                ("Service".to_string(), 20.0),
+                ("GeneratedNode".to_string(), 10.0),
+            ]),
+            default_node_cost: 10.0,
+            edge_cost: 1.0,
+            multipliers: vec![
+                CostMultiplier::PerUnit {
+                    key: "replicas".to_string(),
+                },
+                CostMultiplier::WhenEquals {
+                    key: "tier".to_string(),
+                    value: Value::Text("premium".to_string()),
+                    factor: 2.0,
+                },
+            ],
+            budget: 500.0,
+        }
+    }
+}
+
+impl CostTable {
+    pub fn node_cost(&self, node: &DesignNode) -> f64 {
+        let unit = self
+            .node_costs
+            .get(&node.kind)
+            .copied()
+            .unwrap_or(self.default_node_cost);
+        self.multipliers
+            .iter()
+            .fold(unit, |cost, multiplier| cost * multiplier.factor(node))
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn load(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let raw = std::fs::read_to_string(path)?;
+        let envelope: core_types::Versioned<Self> = serde_json::from_str(&raw)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        envelope
+            .into_checked()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(&core_types::Versioned::new(self.clone()))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, json)
+    }
+}
+
+impl CostModel for CostTable {
+    fn estimate(&self, state: &DesignState) -> f64 {
+        let nodes = state
+            .graph
+            .nodes()
+            .values()
+            .map(|node| self.node_cost(node))
+            .sum::<f64>();
+        nodes + self.edge_cost * state.graph.edges().len() as f64
+    }
+
+    fn budget(&self) -> f64 {
+        self.budget
+    }
+}
@@ -60,7 +60,7 @@ pub fn apply_diversity_pressure(
     )
 }
 
-fn l2_distance(a: &FieldVector, b: &FieldVector) -> f64 {
+pub(crate) fn l2_distance(a: &FieldVector, b: &FieldVector) -> f64 {
     let len = a.dimensions().min(b.dimensions());
     let mut sum = 0.0f64;
     for i in 0..len {
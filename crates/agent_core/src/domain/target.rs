@@ -42,14 +42,7 @@ where
 {
     let mut out = Vec::new();
     for c in categories {
-        let mapped = match c {
-            RuleCategory::Structural => NodeCategory::Abstraction,
-            RuleCategory::Performance => NodeCategory::Performance,
-            RuleCategory::Reliability => NodeCategory::Reliability,
-            RuleCategory::Cost => NodeCategory::CostSensitive,
-            RuleCategory::Refactor => NodeCategory::Control,
-            RuleCategory::ConstraintPropagation => NodeCategory::Constraint,
-        };
+        let mapped = node_category_for(&c);
         if !out.contains(&mapped) {
             out.push(mapped);
         }
@@ -57,6 +50,17 @@ where
     out
 }
 
+pub(crate) fn node_category_for(category: &RuleCategory) -> NodeCategory {
+    match category {
+        RuleCategory::Structural => NodeCategory::Abstraction,
+        RuleCategory::Performance => NodeCategory::Performance,
+        RuleCategory::Reliability => NodeCategory::Reliability,
+        RuleCategory::Cost => NodeCategory::CostSensitive,
+        RuleCategory::Refactor => NodeCategory::Control,
+        RuleCategory::ConstraintPropagation => NodeCategory::Constraint,
+    }
+}
+
 fn compose_category_field(field: &FieldEngine, categories: &[NodeCategory]) -> FieldVector {
     if categories.is_empty() {
         return FieldVector::zeros(field.dimensions());
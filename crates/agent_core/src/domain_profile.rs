@@ -0,0 +1,218 @@
+//! Domain presets: one artifact that sets rule priorities, the preference
+//! profile, the target field, and default constraints for a run.
+
+use std::collections::BTreeMap;
+
+use field_engine::{FieldEngine, FieldVector, TargetField};
+use hybrid_vm::{RuleCategory, RuleId, Shm};
+use profile::PreferenceProfile;
+
+use crate::domain::target::node_category_for;
+use crate::{EpsilonConstraint, ObjectiveAxis, ObjectiveTargets, SearchConfig};
+
+/// Target field as a weighted mix of rule-category basis vectors.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TargetFieldSpec {
+    pub weights: Vec<(RuleCategory, f32)>,
+}
+
+impl TargetFieldSpec {
+    /// Zero field when every weight is zero or the list is empty.
+    pub fn build(&self, field: &FieldEngine) -> TargetField {
+        let total: f32 = self.weights.iter().map(|(_, w)| w.max(0.0)).sum();
+        if total <= f32::EPSILON {
+            return TargetField {
+                data: FieldVector::zeros(field.dimensions()),
+            };
+        }
+        let mut data = FieldVector::zeros(field.dimensions());
+        for (category, weight) in &self.weights {
+            let basis = field.projector().basis_for(node_category_for(category));
+            data = data.add(&basis.scale(weight.max(0.0) / total));
+        }
+        TargetField { data }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DomainProfile {
+    pub name: String,
+    pub description: String,
+    /// Multiplies the priority of every rule in the category.
+    pub category_boosts: Vec<(RuleCategory, f64)>,
+    /// Absolute priorities, applied after the category boosts.
+    pub rule_priorities: BTreeMap<RuleId, f64>,
+    pub preference: PreferenceProfile,
+    pub target_field: TargetFieldSpec,
+    pub targets: Option<ObjectiveTargets>,
+    pub epsilon_constraint: Option<EpsilonConstraint>,
+}
+
+#[cfg(feature = "serde")]
+impl core_types::SchemaVersioned for DomainProfile {
+    const KIND: &'static str = "domain_profile";
+    const VERSION: u32 = 1;
+}
+
+impl DomainProfile {
+    pub const PRESET_NAMES: [&'static str; 3] = ["embedded", "web_backend", "data_pipeline"];
+
+    pub fn preset(name: &str) -> Option<Self> {
+        match name {
+            "embedded" => Some(Self::embedded()),
+            "web_backend" => Some(Self::web_backend()),
+            "data_pipeline" => Some(Self::data_pipeline()),
+            _ => None,
+        }
+    }
+
+    /// Resource-constrained targets: cost and reliability first, caching
+    /// layers demoted, risk held under an epsilon constraint.
+    pub fn embedded() -> Self {
+        Self {
+            name: "embedded".to_string(),
+            description: "Tight memory/CPU budget; favors cost and fail-safe rules".to_string(),
+            category_boosts: vec![
+                (RuleCategory::Cost, 1.3),
+                (RuleCategory::Reliability, 1.2),
+                (RuleCategory::Performance, 0.8),
+            ],
+            rule_priorities: BTreeMap::from([
+                (RuleId::from_u128(1004), 0.5),
+                (RuleId::from_u128(1017), 0.97),
+            ]),
+            preference: PreferenceProfile {
+                struct_weight: 0.2,
+                field_weight: 0.1,
+                risk_weight: 0.3,
+                cost_weight: 0.4,
+            },
+            target_field: TargetFieldSpec {
+                weights: vec![
+                    (RuleCategory::Cost, 0.5),
+                    (RuleCategory::Reliability, 0.3),
+                    (RuleCategory::ConstraintPropagation, 0.2),
+                ],
+            },
+            targets: None,
+            epsilon_constraint: Some(EpsilonConstraint {
+                optimize: ObjectiveAxis::Shape,
+                bounds: ObjectiveTargets {
+                    max_risk: 0.4,
+                    ..ObjectiveTargets::default()
+                },
+            }),
+        }
+    }
+
+    /// Request-serving services: latency and availability over cost.
+    pub fn web_backend() -> Self {
+        Self {
+            name: "web_backend".to_string(),
+            description: "Latency- and availability-sensitive request handling".to_string(),
+            category_boosts: vec![
+                (RuleCategory::Performance, 1.25),
+                (RuleCategory::Reliability, 1.15),
+            ],
+            rule_priorities: BTreeMap::from([(RuleId::from_u128(1013), 0.93)]),
+            preference: PreferenceProfile {
+                struct_weight: 0.25,
+                field_weight: 0.25,
+                risk_weight: 0.35,
+                cost_weight: 0.15,
+            },
+            target_field: TargetFieldSpec {
+                weights: vec![
+                    (RuleCategory::Performance, 0.4),
+                    (RuleCategory::Reliability, 0.4),
+                    (RuleCategory::Structural, 0.2),
+                ],
+            },
+            targets: Some(ObjectiveTargets {
+                max_risk: 0.5,
+                ..ObjectiveTargets::default()
+            }),
+            epsilon_constraint: None,
+        }
+    }
+
+    /// Batch/stream processing: clean staging and propagated constraints.
+    pub fn data_pipeline() -> Self {
+        Self {
+            name: "data_pipeline".to_string(),
+            description: "Staged data flow; favors layering and constraint propagation".to_string(),
+            category_boosts: vec![
+                (RuleCategory::Structural, 1.2),
+                (RuleCategory::ConstraintPropagation, 1.2),
+            ],
+            rule_priorities: BTreeMap::from([(RuleId::from_u128(1003), 0.94)]),
+            preference: PreferenceProfile {
+                struct_weight: 0.4,
+                field_weight: 0.2,
+                risk_weight: 0.2,
+                cost_weight: 0.2,
+            },
+            target_field: TargetFieldSpec {
+                weights: vec![
+                    (RuleCategory::Structural, 0.4),
+                    (RuleCategory::ConstraintPropagation, 0.4),
+                    (RuleCategory::Performance, 0.2),
+                ],
+            },
+            targets: Some(ObjectiveTargets {
+                min_f_struct: 0.6,
+                ..ObjectiveTargets::default()
+            }),
+            epsilon_constraint: None,
+        }
+    }
+
+    /// Boosts by category, then applies absolute overrides. Unknown rule ids
+    /// are ignored.
+    pub fn configure_shm(&self, shm: &mut Shm) {
+        let boosted = shm
+            .rules()
+            .iter()
+            .map(|rule| {
+                let factor = self
+                    .category_boosts
+                    .iter()
+                    .filter(|(c, _)| *c == rule.category)
+                    .map(|(_, f)| *f)
+                    .product::<f64>();
+                (rule.id, rule.priority * factor)
+            })
+            .collect::<Vec<_>>();
+        for (id, priority) in boosted {
+            shm.set_priority(id, priority);
+        }
+        for (id, priority) in &self.rule_priorities {
+            shm.set_priority(*id, *priority);
+        }
+    }
+
+    /// Overrides `targets` and `epsilon_constraint`; other fields are kept.
+    pub fn configure_search(&self, config: &mut SearchConfig) {
+        config.targets = self.targets;
+        config.epsilon_constraint = self.epsilon_constraint;
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn load(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        let raw = std::fs::read_to_string(path)?;
+        let envelope: core_types::Versioned<Self> = serde_json::from_str(&raw)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        envelope
+            .into_checked()
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(&core_types::Versioned::new(self.clone()))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, json)
+    }
+}
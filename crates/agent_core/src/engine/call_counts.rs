@@ -0,0 +1,130 @@
+//! Distance-computation counters behind `TraceRow::distance_calls` and
+//! `TraceRow::nn_distance_calls`.
+//!
+//! Calls are counted per thread, into the innermost `CallCountSession` open
+//! on it, so runs on different threads (a parallel sweep, say) keep separate
+//! counts. A session's counts are added to the enclosing one when it ends.
+//! Every call also bumps the process-wide `DISTANCE_CALL_COUNT` and
+//! `NN_DISTANCE_CALL_COUNT`, which mix all runs and are only for debugging.
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::sync::atomic::Ordering;
+
+use crate::{DISTANCE_CALL_COUNT, NN_DISTANCE_CALL_COUNT};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DistanceCallCounts {
+    /// Pairwise distances between two objective vectors.
+    pub distance: usize,
+    /// Pairs examined by nearest-neighbour scans over a front.
+    pub nn_distance: usize,
+}
+
+impl DistanceCallCounts {
+    const ZERO: Self = Self {
+        distance: 0,
+        nn_distance: 0,
+    };
+
+    /// Calls made between `earlier` and `self`.
+    pub fn since(self, earlier: Self) -> Self {
+        Self {
+            distance: self.distance.saturating_sub(earlier.distance),
+            nn_distance: self.nn_distance.saturating_sub(earlier.nn_distance),
+        }
+    }
+}
+
+thread_local! {
+    static CURRENT: Cell<DistanceCallCounts> = const { Cell::new(DistanceCallCounts::ZERO) };
+}
+
+/// Counts this thread's calls from `start` until dropped. Not `Send`: the
+/// counts it reads belong to the thread that opened it.
+pub struct CallCountSession {
+    outer: DistanceCallCounts,
+    _thread: PhantomData<*const ()>,
+}
+
+impl CallCountSession {
+    pub fn start() -> Self {
+        Self {
+            outer: CURRENT.replace(DistanceCallCounts::ZERO),
+            _thread: PhantomData,
+        }
+    }
+
+    /// Calls counted so far, nested sessions that have ended included.
+    pub fn counts(&self) -> DistanceCallCounts {
+        CURRENT.get()
+    }
+}
+
+impl Drop for CallCountSession {
+    fn drop(&mut self) {
+        let inner = CURRENT.get();
+        CURRENT.set(DistanceCallCounts {
+            distance: self.outer.distance.saturating_add(inner.distance),
+            nn_distance: self.outer.nn_distance.saturating_add(inner.nn_distance),
+        });
+    }
+}
+
+pub(crate) fn record_distance_call() {
+    DISTANCE_CALL_COUNT.fetch_add(1, Ordering::Relaxed);
+    CURRENT.set(DistanceCallCounts {
+        distance: CURRENT.get().distance.saturating_add(1),
+        ..CURRENT.get()
+    });
+}
+
+pub(crate) fn record_nn_distance_calls(pairs: usize) {
+    NN_DISTANCE_CALL_COUNT.fetch_add(pairs, Ordering::Relaxed);
+    CURRENT.set(DistanceCallCounts {
+        nn_distance: CURRENT.get().nn_distance.saturating_add(pairs),
+        ..CURRENT.get()
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sessions_nest_and_stay_on_their_own_thread() {
+        let outer = CallCountSession::start();
+        record_distance_call();
+        {
+            let inner = CallCountSession::start();
+            record_nn_distance_calls(6);
+            assert_eq!(
+                inner.counts(),
+                DistanceCallCounts {
+                    distance: 0,
+                    nn_distance: 6
+                }
+            );
+        }
+        let elsewhere = std::thread::spawn(|| {
+            let session = CallCountSession::start();
+            record_distance_call();
+            record_distance_call();
+            session.counts()
+        })
+        .join()
+        .expect("counting thread");
+        assert_eq!(elsewhere.distance, 2);
+        let before = outer.counts();
+        record_distance_call();
+        assert_eq!(
+            outer.counts(),
+            DistanceCallCounts {
+                distance: 2,
+                nn_distance: 6
+            }
+        );
+        assert_eq!(outer.counts().since(before).distance, 1);
+        assert!(DISTANCE_CALL_COUNT.load(Ordering::Relaxed) >= 4);
+    }
+}
@@ -1,3 +1,4 @@
+pub mod call_counts;
 pub mod distance;
 pub mod normalization;
 pub mod pareto;
@@ -1,8 +1,10 @@
 use core_types::ObjectiveVector;
 use memory_space::DesignState;
 
-use crate::GlobalRobustStats;
 use crate::normalization;
+use crate::{
+    GlobalRobustStats, NormalizationConfig, NormalizationStrategy, ObjectiveRaw, WarmupConfig,
+};
 
 pub const EPSILON_JITTER: f64 = 1e-6;
 
@@ -204,6 +206,212 @@ pub fn normalize_by_depth_candidates(
     (normalized, stats)
 }
 
+/// Robust statistics pooled over the first depths of a run, then frozen so
+/// later depths are normalized on a fixed scale.
+#[derive(Clone, Debug, Default)]
+pub struct GlobalRobustEstimator {
+    warmup: WarmupConfig,
+    samples: Vec<ObjectiveRaw>,
+    frozen: Option<GlobalRobustStats>,
+    depths_observed: usize,
+    depths_since_freeze: usize,
+}
+
+impl GlobalRobustEstimator {
+    pub fn new(warmup: WarmupConfig) -> Self {
+        Self {
+            warmup,
+            ..Self::default()
+        }
+    }
+
+    /// Starts frozen at `stats`, e.g. from `export` of an earlier run.
+    pub fn with_calibration(warmup: WarmupConfig, stats: GlobalRobustStats) -> Self {
+        let mut estimator = Self::new(warmup);
+        estimator.import(stats);
+        estimator
+    }
+
+    pub fn warmup(&self) -> WarmupConfig {
+        self.warmup
+    }
+
+    /// Adds one depth's raw objectives, freezing or refreshing as configured.
+    pub fn observe(&mut self, samples: &[ObjectiveRaw], alpha: f64) {
+        self.depths_observed += 1;
+        if self.frozen.is_none() {
+            self.samples.extend_from_slice(samples);
+            if self.depths_observed >= self.warmup.warmup_depths.max(1) {
+                self.freeze(alpha);
+            }
+            return;
+        }
+        let Some(interval) = self.warmup.refresh_interval else {
+            return;
+        };
+        self.samples.extend_from_slice(samples);
+        self.depths_since_freeze += 1;
+        if self.depths_since_freeze >= interval.max(1) {
+            self.freeze(alpha);
+        }
+    }
+
+    /// The frozen statistics, or those pooled so far while warming up.
+    pub fn stats(&self, alpha: f64) -> Option<GlobalRobustStats> {
+        self.frozen.clone().or_else(|| {
+            crate::runtime::trace_helpers::robust_stats_from_samples(&self.samples, alpha)
+        })
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen.is_some()
+    }
+
+    /// The frozen statistics, for reuse by a later run via `import`.
+    pub fn export(&self) -> Option<GlobalRobustStats> {
+        self.frozen.clone()
+    }
+
+    /// Replaces the frozen statistics and drops pending samples.
+    pub fn import(&mut self, stats: GlobalRobustStats) {
+        self.frozen = Some(stats);
+        self.samples = Vec::new();
+        self.depths_since_freeze = 0;
+    }
+
+    fn freeze(&mut self, alpha: f64) {
+        if let Some(stats) =
+            crate::runtime::trace_helpers::robust_stats_from_samples(&self.samples, alpha)
+        {
+            self.import(stats);
+        }
+    }
+}
+
+/// Applies a `NormalizationConfig` depth by depth. `GlobalFrozen` axes share
+/// one `GlobalRobustEstimator`, so use one normalizer per run.
+#[derive(Clone, Debug, Default)]
+pub struct DepthNormalizer {
+    config: NormalizationConfig,
+    estimator: GlobalRobustEstimator,
+}
+
+impl DepthNormalizer {
+    pub fn new(config: NormalizationConfig, warmup: WarmupConfig) -> Self {
+        Self {
+            config,
+            estimator: GlobalRobustEstimator::new(warmup),
+        }
+    }
+
+    pub fn config(&self) -> &NormalizationConfig {
+        &self.config
+    }
+
+    pub fn estimator(&self) -> &GlobalRobustEstimator {
+        &self.estimator
+    }
+
+    pub fn estimator_mut(&mut self) -> &mut GlobalRobustEstimator {
+        &mut self.estimator
+    }
+
+    /// Maps one depth's candidates into `[0, 1]` per objective, keeping order.
+    pub fn normalize(
+        &mut self,
+        candidates: Vec<(DesignState, ObjectiveVector)>,
+        alpha: f64,
+    ) -> Vec<(DesignState, ObjectiveVector)> {
+        if candidates.is_empty() {
+            return Vec::new();
+        }
+        if self.config == NormalizationConfig::default() {
+            return crate::normalize_by_depth(candidates, alpha).0;
+        }
+
+        let raw = candidates
+            .iter()
+            .map(|(_, o)| ObjectiveRaw([o.f_struct, o.f_field, o.f_risk, o.f_shape]))
+            .collect::<Vec<_>>();
+        let global = if self
+            .config
+            .per_axis
+            .contains(&NormalizationStrategy::GlobalFrozen)
+        {
+            self.estimator.observe(&raw, alpha);
+            self.estimator.stats(alpha)
+        } else {
+            None
+        };
+        let mut scaled: [Vec<f64>; 4] = Default::default();
+        for (i, strategy) in self.config.per_axis.into_iter().enumerate() {
+            let column = raw.iter().map(|r| r.0[i]).collect::<Vec<_>>();
+            scaled[i] = match strategy {
+                NormalizationStrategy::PerDepthRobust => {
+                    let jittered = candidates
+                        .iter()
+                        .zip(&column)
+                        .map(|((state, _), v)| {
+                            epsilon_jitter(*v, state_id_to_u64(state.id.as_u128()), i as u64)
+                        })
+                        .collect::<Vec<_>>();
+                    per_depth_robust(&jittered)
+                }
+                NormalizationStrategy::GlobalFrozen => match &global {
+                    Some(stats) => raw
+                        .iter()
+                        .map(|r| {
+                            let z = crate::engine::pareto::normalize_objective(r, stats).0[i];
+                            soft_sigmoid(z).clamp(0.0, 1.0)
+                        })
+                        .collect(),
+                    None => vec![0.5; raw.len()],
+                },
+                NormalizationStrategy::MinMax => normalization::minmax::minmax_scale(&column, 0.5),
+                NormalizationStrategy::Rank => normalization::rank::rank_scale(&column, 0.5),
+            };
+        }
+
+        candidates
+            .into_iter()
+            .enumerate()
+            .map(|(idx, (state, _))| {
+                let value = |i: usize| scaled[i].get(idx).copied().unwrap_or(0.5);
+                (
+                    state,
+                    ObjectiveVector {
+                        f_struct: value(0),
+                        f_field: value(1),
+                        f_risk: value(2),
+                        f_shape: value(3),
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+/// Median/MAD standardization of one column, min-max scaled; non-finite
+/// values fall back to 0.5.
+fn per_depth_robust(column: &[f64]) -> Vec<f64> {
+    let med = median(column.to_vec());
+    let mad = compute_mad(column, med);
+    let standardized = column
+        .iter()
+        .map(|v| (v - med) / (mad + 1e-12))
+        .collect::<Vec<_>>();
+    normalization::depth::normalize_by_depth(&standardized, 0)
+        .into_iter()
+        .map(|v| {
+            if v.is_finite() {
+                v.clamp(0.0, 1.0)
+            } else {
+                0.5
+            }
+        })
+        .collect()
+}
+
 fn state_id_to_u64(raw: u128) -> u64 {
     (raw as u64) ^ ((raw >> 64) as u64)
 }
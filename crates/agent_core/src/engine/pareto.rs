@@ -1,7 +1,8 @@
 use core_types::ObjectiveVector;
 use memory_space::DesignState;
 
-use crate::{DISTANCE_CALL_COUNT, NN_DISTANCE_CALL_COUNT, ObjectiveNorm, ObjectiveRaw};
+use crate::engine::call_counts::{record_distance_call, record_nn_distance_calls};
+use crate::{ObjectiveNorm, ObjectiveRaw};
 
 const HV_EPS: f64 = 1e-12;
 
@@ -17,6 +18,86 @@ pub fn dominates(a: &ObjectiveVector, b: &ObjectiveVector) -> bool {
     all_ge && one_gt
 }
 
+/// Dominance with a per-objective tolerance: `a` may trail `b` by up to `eps`
+/// and must lead by more than `eps` somewhere. Zero `eps` equals `dominates`.
+pub fn epsilon_dominates(a: &ObjectiveVector, b: &ObjectiveVector, eps: &ObjectiveVector) -> bool {
+    let pairs = [
+        (a.f_struct, b.f_struct, eps.f_struct),
+        (a.f_field, b.f_field, eps.f_field),
+        (a.f_risk, b.f_risk, eps.f_risk),
+        (a.f_shape, b.f_shape, eps.f_shape),
+    ];
+    let all_ge = pairs.iter().all(|(x, y, e)| *x >= *y - *e);
+    let one_gt = pairs.iter().any(|(x, y, e)| *x > *y + *e);
+    all_ge && one_gt
+}
+
+/// Additive epsilon-dominance: `a` counts as at least as good as `b` on an
+/// objective when it trails by no more than `tolerance`. Unlike
+/// `epsilon_dominates`, points within tolerance of each other dominate one
+/// another, so a front keeps only one of them. Zero `tolerance` equals
+/// `dominates`.
+pub fn dominates_with_tolerance(
+    a: &ObjectiveVector,
+    b: &ObjectiveVector,
+    tolerance: &ObjectiveVector,
+) -> bool {
+    let pairs = [
+        (a.f_struct, b.f_struct, tolerance.f_struct),
+        (a.f_field, b.f_field, tolerance.f_field),
+        (a.f_risk, b.f_risk, tolerance.f_risk),
+        (a.f_shape, b.f_shape, tolerance.f_shape),
+    ];
+    let all_ge = pairs.iter().all(|(x, y, e)| *x + *e >= *y);
+    let one_gt = pairs.iter().any(|(x, y, e)| *x + *e > *y);
+    all_ge && one_gt
+}
+
+/// `dominates`, or `dominates_with_tolerance` when a tolerance is set.
+pub fn dominates_within(
+    a: &ObjectiveVector,
+    b: &ObjectiveVector,
+    tolerance: Option<&ObjectiveVector>,
+) -> bool {
+    match tolerance {
+        Some(tolerance) => dominates_with_tolerance(a, b, tolerance),
+        None => dominates(a, b),
+    }
+}
+
+/// Standard error of the difference of two `samples`-sample means.
+pub fn noise_epsilon(
+    var_a: &ObjectiveVector,
+    var_b: &ObjectiveVector,
+    samples: usize,
+) -> ObjectiveVector {
+    let n = samples.max(1) as f64;
+    let se = |x: f64, y: f64| ((x.max(0.0) + y.max(0.0)) / n).sqrt();
+    ObjectiveVector {
+        f_struct: se(var_a.f_struct, var_b.f_struct),
+        f_field: se(var_a.f_field, var_b.f_field),
+        f_risk: se(var_a.f_risk, var_b.f_risk),
+        f_shape: se(var_a.f_shape, var_b.f_shape),
+    }
+}
+
+/// `oriented` lowered by one standard error of its `samples`-sample mean
+/// on every axis: the pessimistic end of its confidence band.
+pub fn lower_confidence_bound(
+    oriented: &ObjectiveVector,
+    variance: &ObjectiveVector,
+    samples: usize,
+) -> ObjectiveVector {
+    let n = samples.max(1) as f64;
+    let lower = |x: f64, var: f64| x - (var.max(0.0) / n).sqrt();
+    ObjectiveVector {
+        f_struct: lower(oriented.f_struct, variance.f_struct),
+        f_field: lower(oriented.f_field, variance.f_field),
+        f_risk: lower(oriented.f_risk, variance.f_risk),
+        f_shape: lower(oriented.f_shape, variance.f_shape),
+    }
+}
+
 fn median_pairwise_l2(front: &[&ObjectiveVector]) -> f64 {
     if front.len() < 2 {
         return 1e-9;
@@ -33,7 +114,7 @@ fn median_pairwise_l2(front: &[&ObjectiveVector]) -> f64 {
 }
 
 fn objective_energy_distance(a: &ObjectiveVector, b: &ObjectiveVector, tau: f64) -> f64 {
-    DISTANCE_CALL_COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    record_distance_call();
     let d = crate::engine::distance::objective_l2_distance(a, b);
     let t = tau.max(1e-9);
     let k = (-(d * d) / t).exp();
@@ -45,10 +126,7 @@ pub fn pareto_mean_nn_distance(front: &[&ObjectiveVector]) -> f64 {
         return 0.0;
     }
     let tau = median_pairwise_l2(front);
-    NN_DISTANCE_CALL_COUNT.fetch_add(
-        front.len() * (front.len() - 1),
-        std::sync::atomic::Ordering::Relaxed,
-    );
+    record_nn_distance_calls(front.len() * (front.len() - 1));
     let mut sum = 0.0;
     for (i, obj) in front.iter().enumerate() {
         let mut best = f64::INFINITY;
@@ -148,7 +226,7 @@ pub fn normalize_objective(raw: &ObjectiveRaw, stats: &crate::GlobalRobustStats)
 }
 
 pub fn norm_distance(a: &ObjectiveNorm, b: &ObjectiveNorm, weights: &[f64; 4]) -> f64 {
-    DISTANCE_CALL_COUNT.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
+    record_distance_call();
     let mut s = 0.0;
     let mut w_sum = 0.0;
     for (i, w) in weights.iter().copied().enumerate().take(4) {
@@ -169,10 +247,7 @@ pub fn mean_nn_dist_norm(vs: &[ObjectiveNorm], weights: &[f64; 4]) -> f64 {
     if vs.len() < 2 {
         return 0.0;
     }
-    NN_DISTANCE_CALL_COUNT.fetch_add(
-        vs.len() * (vs.len() - 1),
-        std::sync::atomic::Ordering::Relaxed,
-    );
+    record_nn_distance_calls(vs.len() * (vs.len() - 1));
     let mut sum = 0.0;
     for (i, v) in vs.iter().enumerate() {
         let mut best = f64::INFINITY;
@@ -306,6 +381,82 @@ pub fn select_beam_maxmin_norm(
     selected_idx.into_iter().map(|i| front[i].clone()).collect()
 }
 
+/// Greedy beam selection that gives `pressure` of each pick's score to its
+/// distance from the states already picked and the rest to `scalar_score`,
+/// both min-max scaled over the remaining candidates. Pressure 1 picks the
+/// same beam as `select_beam_maxmin_norm`.
+pub fn select_beam_pressure_norm(
+    front: Vec<(DesignState, ObjectiveVector)>,
+    norms: Vec<ObjectiveNorm>,
+    beam: usize,
+    weights: &[f64; 4],
+    pressure: f64,
+) -> Vec<(DesignState, ObjectiveVector)> {
+    if pressure >= 1.0 {
+        return select_beam_maxmin_norm(front, norms, beam, weights);
+    }
+    if front.is_empty() {
+        return Vec::new();
+    }
+    let pressure = pressure.max(0.0);
+    let beam = beam.max(1).min(front.len());
+    let scores = front
+        .iter()
+        .map(|(_, obj)| crate::scalar_score(obj))
+        .collect::<Vec<_>>();
+    let mut used = vec![false; front.len()];
+    let mut selected_idx = Vec::with_capacity(beam);
+    let mut seed = 0usize;
+    for (i, s) in scores.iter().enumerate() {
+        if *s > scores[seed] {
+            seed = i;
+        }
+    }
+    selected_idx.push(seed);
+    used[seed] = true;
+    while selected_idx.len() < beam {
+        let remaining = (0..front.len()).filter(|i| !used[*i]).collect::<Vec<_>>();
+        if remaining.is_empty() {
+            break;
+        }
+        let dmins = remaining
+            .iter()
+            .map(|&i| {
+                selected_idx
+                    .iter()
+                    .map(|j| norm_distance(&norms[i], &norms[*j], weights))
+                    .fold(f64::INFINITY, f64::min)
+            })
+            .collect::<Vec<_>>();
+        let scale = |v: f64, lo: f64, hi: f64| {
+            if hi - lo > 1e-12 {
+                (v - lo) / (hi - lo)
+            } else {
+                0.0
+            }
+        };
+        let (d_lo, d_hi) = min_max(dmins.iter().copied());
+        let (s_lo, s_hi) = min_max(remaining.iter().map(|&i| scores[i]));
+        let mut best = (remaining[0], f64::NEG_INFINITY);
+        for (&i, &d) in remaining.iter().zip(&dmins) {
+            let value =
+                pressure * scale(d, d_lo, d_hi) + (1.0 - pressure) * scale(scores[i], s_lo, s_hi);
+            if value > best.1 {
+                best = (i, value);
+            }
+        }
+        used[best.0] = true;
+        selected_idx.push(best.0);
+    }
+    selected_idx.into_iter().map(|i| front[i].clone()).collect()
+}
+
+fn min_max(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    values.fold((f64::INFINITY, f64::NEG_INFINITY), |(lo, hi), v| {
+        (lo.min(v), hi.max(v))
+    })
+}
+
 pub fn hv_4d_from_origin_normalized(points: &[[f64; 4]]) -> f64 {
     let mut unique = Vec::<[f64; 4]>::new();
     for p in points {
@@ -364,7 +515,7 @@ fn dominates_norm(a: &[f64; 4], b: &[f64; 4]) -> bool {
     all_ge && one_gt
 }
 
-fn pareto_rank_and_domination(points: &[[f64; 4]]) -> (Vec<usize>, Vec<usize>) {
+pub(crate) fn pareto_rank_and_domination(points: &[[f64; 4]]) -> (Vec<usize>, Vec<usize>) {
     let n = points.len();
     let mut dominated_by = vec![0usize; n];
     let mut dominates_to: Vec<Vec<usize>> = vec![Vec::new(); n];
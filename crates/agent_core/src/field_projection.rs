@@ -0,0 +1,82 @@
+//! Snapshot of the field projection space for offline inspection: the
+//! per-category basis vectors every node projection mixes in, and the
+//! aggregate projection of selected states. Complex components are written
+//! as `[re, im]` pairs.
+
+use field_engine::{FieldEngine, FieldVector};
+use memory_space::{DesignState, StateId};
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct CategoryBasis {
+    pub category: String,
+    pub vector: Vec<[f32; 2]>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct StateProjection {
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub state_id: StateId,
+    pub node_count: usize,
+    pub vector: Vec<[f32; 2]>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FieldProjectionExport {
+    pub dimensions: usize,
+    /// Weight of the per-node hash component in a node projection.
+    pub alpha: f32,
+    /// Weight of the category basis component.
+    pub beta: f32,
+    pub basis: Vec<CategoryBasis>,
+    pub states: Vec<StateProjection>,
+}
+
+#[cfg(feature = "serde")]
+impl core_types::SchemaVersioned for FieldProjectionExport {
+    const KIND: &'static str = "field_projection";
+    const VERSION: u32 = 1;
+}
+
+impl FieldProjectionExport {
+    pub fn capture(field: &FieldEngine, states: &[DesignState]) -> Self {
+        Self {
+            dimensions: field.dimensions(),
+            alpha: field.projector().alpha(),
+            beta: field.projector().beta(),
+            basis: field
+                .category_basis()
+                .into_iter()
+                .map(|(category, vector)| CategoryBasis {
+                    category: format!("{category:?}"),
+                    vector: components(&vector),
+                })
+                .collect(),
+            states: states
+                .iter()
+                .map(|state| StateProjection {
+                    state_id: state.id,
+                    node_count: state.graph.nodes().len(),
+                    vector: components(&field.aggregate_state(state)),
+                })
+                .collect(),
+        }
+    }
+
+    /// Writes the export as a `core_types::Versioned` JSON envelope.
+    #[cfg(feature = "serde")]
+    pub fn write_json(&self, path: impl AsRef<std::path::Path>) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(&core_types::Versioned::new(self.clone()))
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        std::fs::write(path, json)
+    }
+}
+
+fn components(vector: &FieldVector) -> Vec<[f32; 2]> {
+    vector.data.iter().map(|v| [v.re, v.im]).collect()
+}
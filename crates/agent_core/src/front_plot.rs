@@ -0,0 +1,299 @@
+//! Plot-ready data for Pareto fronts, so clients can chart the final front
+//! and each depth's front without recomputing anything.
+//!
+//! Objectives are oriented by the run's `ObjectiveDirections` and min-max
+//! scaled over every front in the plot, so 1 is the best value seen on an
+//! axis and fronts of different depths share one coordinate system. Each
+//! front carries its points' dominance ranks, a 2-D projection per
+//! objective pair with that pair's non-dominated staircase and knee, and
+//! the front's overall knee.
+
+use std::collections::BTreeMap;
+
+use core_types::ObjectiveVector;
+use memory_space::StateId;
+
+use crate::capability::search_tree::SearchTree;
+use crate::engine::pareto::pareto_rank_and_domination;
+use crate::{DepthFront, ObjectiveAxis, ObjectiveDirections, SearchResult};
+
+const AXES: [ObjectiveAxis; 4] = [
+    ObjectiveAxis::Struct,
+    ObjectiveAxis::Field,
+    ObjectiveAxis::Risk,
+    ObjectiveAxis::Shape,
+];
+
+/// A front to plot: its depth (`None` for the final front) and its states'
+/// raw objectives.
+pub type FrontInput = (Option<usize>, Vec<(StateId, ObjectiveVector)>);
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct PlotPoint {
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub state_id: StateId,
+    /// Raw evaluator values.
+    pub objective: ObjectiveVector,
+    /// Oriented and scaled to `[0, 1]`, 1 = best; ordered struct, field,
+    /// risk, shape.
+    pub normalized: [f64; 4],
+    /// Non-dominated sorting rank within the front; 1 = non-dominated.
+    pub rank: usize,
+}
+
+/// One objective pair of a front, in normalized coordinates.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Projection2d {
+    pub x: ObjectiveAxis,
+    pub y: ObjectiveAxis,
+    /// `[x, y]` per point, in `FrontSeries::points` order.
+    pub coordinates: Vec<[f64; 2]>,
+    /// Indices of the points non-dominated on this pair, by ascending x:
+    /// the staircase to draw.
+    pub staircase: Vec<usize>,
+    /// Staircase point farthest beyond the line through its two ends,
+    /// toward the ideal corner; `None` for staircases of fewer than three
+    /// points or without a bulge.
+    pub knee: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FrontSeries {
+    /// `None` for the final front.
+    pub depth: Option<usize>,
+    pub points: Vec<PlotPoint>,
+    /// One per objective pair: (struct, field), (struct, risk), ...,
+    /// (risk, shape).
+    pub projections: Vec<Projection2d>,
+    /// Rank-1 point nearest the ideal point `[1, 1, 1, 1]`.
+    pub knee: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct FrontPlot {
+    pub directions: ObjectiveDirections,
+    /// Raw `[min, max]` per axis over every point, for axis labels.
+    pub raw_bounds: [[f64; 2]; 4],
+    /// Final front first when present, then depth fronts by depth.
+    pub series: Vec<FrontSeries>,
+}
+
+#[cfg(feature = "serde")]
+impl core_types::SchemaVersioned for FrontPlot {
+    const KIND: &'static str = "front_plot";
+    const VERSION: u32 = 1;
+}
+
+impl FrontPlot {
+    /// Plots `fronts`; the scaling spans all of them.
+    pub fn build(
+        directions: ObjectiveDirections,
+        fronts: impl IntoIterator<Item = FrontInput>,
+    ) -> Self {
+        let mut fronts = fronts.into_iter().collect::<Vec<_>>();
+        fronts.sort_by_key(|(depth, _)| depth.map_or(0, |d| d + 1));
+        let all = fronts.iter().flat_map(|(_, points)| points);
+        let mut raw_bounds = [[f64::INFINITY, f64::NEG_INFINITY]; 4];
+        let mut oriented_bounds = raw_bounds;
+        for (_, objective) in all {
+            let oriented = directions.orient(objective);
+            for (i, axis) in AXES.into_iter().enumerate() {
+                widen(&mut raw_bounds[i], axis.value(objective));
+                widen(&mut oriented_bounds[i], axis.value(&oriented));
+            }
+        }
+        if fronts.iter().all(|(_, points)| points.is_empty()) {
+            raw_bounds = [[0.0, 0.0]; 4];
+        }
+        let series = fronts
+            .into_iter()
+            .map(|(depth, points)| series(depth, points, directions, &oriented_bounds))
+            .collect();
+        Self {
+            directions,
+            raw_bounds,
+            series,
+        }
+    }
+
+    /// The final front and every recorded depth front of `result`, with
+    /// objectives read from its search tree; `None` without one
+    /// (`SearchConfig::record_tree`). States the tree dropped are left out.
+    pub fn from_search(result: &SearchResult, directions: ObjectiveDirections) -> Option<Self> {
+        let tree = result.search_tree.as_ref()?;
+        let objectives = tree_objectives(tree);
+        let final_front = lookup(&objectives, result.final_frontier.iter().map(|s| s.id));
+        let mut fronts = vec![(None, final_front)];
+        fronts.extend(depth_fronts(&result.depth_fronts, tree));
+        Some(Self::build(directions, fronts))
+    }
+
+    pub fn final_front(&self) -> Option<&FrontSeries> {
+        self.series.iter().find(|s| s.depth.is_none())
+    }
+
+    pub fn depth(&self, depth: usize) -> Option<&FrontSeries> {
+        self.series.iter().find(|s| s.depth == Some(depth))
+    }
+
+    /// The plot in a `core_types::Versioned` JSON envelope.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&core_types::Versioned::new(self.clone()))
+    }
+}
+
+fn tree_objectives(tree: &SearchTree) -> BTreeMap<StateId, &ObjectiveVector> {
+    tree.nodes
+        .iter()
+        .map(|n| (n.state_id, &n.objective))
+        .collect()
+}
+
+fn lookup(
+    objectives: &BTreeMap<StateId, &ObjectiveVector>,
+    ids: impl IntoIterator<Item = StateId>,
+) -> Vec<(StateId, ObjectiveVector)> {
+    ids.into_iter()
+        .filter_map(|id| objectives.get(&id).map(|o| (id, (*o).clone())))
+        .collect()
+}
+
+/// `fronts` with objectives read from `tree`.
+pub(crate) fn depth_fronts(fronts: &[DepthFront], tree: &SearchTree) -> Vec<FrontInput> {
+    let objectives = tree_objectives(tree);
+    fronts
+        .iter()
+        .map(|f| {
+            (
+                Some(f.depth),
+                lookup(&objectives, f.state_ids.iter().copied()),
+            )
+        })
+        .collect()
+}
+
+fn widen(bounds: &mut [f64; 2], value: f64) {
+    bounds[0] = bounds[0].min(value);
+    bounds[1] = bounds[1].max(value);
+}
+
+/// A constant axis scales to 0.5.
+fn scale(value: f64, [min, max]: [f64; 2]) -> f64 {
+    if max > min {
+        ((value - min) / (max - min)).clamp(0.0, 1.0)
+    } else {
+        0.5
+    }
+}
+
+fn series(
+    depth: Option<usize>,
+    points: Vec<(StateId, ObjectiveVector)>,
+    directions: ObjectiveDirections,
+    oriented_bounds: &[[f64; 2]; 4],
+) -> FrontSeries {
+    let normalized = points
+        .iter()
+        .map(|(_, objective)| {
+            let oriented = directions.orient(objective);
+            let mut out = [0.0; 4];
+            for (i, axis) in AXES.into_iter().enumerate() {
+                out[i] = scale(axis.value(&oriented), oriented_bounds[i]);
+            }
+            out
+        })
+        .collect::<Vec<_>>();
+    let (ranks, _) = pareto_rank_and_domination(&normalized);
+    let knee = ranks
+        .iter()
+        .enumerate()
+        .filter(|(_, rank)| **rank == 1)
+        .map(|(i, _)| {
+            let gap = normalized[i].iter().map(|v| (1.0 - v).powi(2)).sum::<f64>();
+            (i, gap)
+        })
+        .min_by(|l, r| l.1.total_cmp(&r.1))
+        .map(|(i, _)| i);
+    let mut projections = Vec::with_capacity(6);
+    for x in 0..AXES.len() {
+        for y in x + 1..AXES.len() {
+            let coordinates = normalized.iter().map(|n| [n[x], n[y]]).collect::<Vec<_>>();
+            let staircase = staircase(&coordinates);
+            let knee = knee_2d(&coordinates, &staircase);
+            projections.push(Projection2d {
+                x: AXES[x],
+                y: AXES[y],
+                coordinates,
+                staircase,
+                knee,
+            });
+        }
+    }
+    FrontSeries {
+        depth,
+        points: points
+            .into_iter()
+            .zip(normalized)
+            .zip(ranks)
+            .map(|(((state_id, objective), normalized), rank)| PlotPoint {
+                state_id,
+                objective,
+                normalized,
+                rank,
+            })
+            .collect(),
+        projections,
+        knee,
+    }
+}
+
+/// Points no other point beats on both coordinates, by ascending x (then
+/// descending y); duplicates keep their first index.
+fn staircase(coordinates: &[[f64; 2]]) -> Vec<usize> {
+    let beats = |a: [f64; 2], b: [f64; 2]| a[0] >= b[0] && a[1] >= b[1] && a != b;
+    let mut out = (0..coordinates.len())
+        .filter(|&i| {
+            !coordinates.iter().any(|c| beats(*c, coordinates[i]))
+                && coordinates[..i].iter().all(|c| *c != coordinates[i])
+        })
+        .collect::<Vec<_>>();
+    out.sort_by(|&l, &r| {
+        coordinates[l][0]
+            .total_cmp(&coordinates[r][0])
+            .then(coordinates[r][1].total_cmp(&coordinates[l][1]))
+    });
+    out
+}
+
+fn knee_2d(coordinates: &[[f64; 2]], staircase: &[usize]) -> Option<usize> {
+    if staircase.len() < 3 {
+        return None;
+    }
+    let a = coordinates[staircase[0]];
+    let b = coordinates[staircase[staircase.len() - 1]];
+    let (dx, dy) = (b[0] - a[0], b[1] - a[1]);
+    let length = dx.hypot(dy);
+    if length == 0.0 {
+        return None;
+    }
+    // Positive on the side of the ideal corner: with x ascending and y
+    // descending along the staircase, that is left of a -> b.
+    staircase[1..staircase.len() - 1]
+        .iter()
+        .map(|&i| {
+            let p = coordinates[i];
+            (i, (dx * (p[1] - a[1]) - dy * (p[0] - a[0])) / length)
+        })
+        .filter(|(_, distance)| *distance > 0.0)
+        .max_by(|l, r| l.1.total_cmp(&r.1))
+        .map(|(i, _)| i)
+}
@@ -1,7 +1,10 @@
 // ALLOW_LIB_LOOP: temporarily allowed until phase3.14
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 use std::sync::atomic::AtomicUsize;
 
+/// Process-wide totals across every run, for debugging; per-run counts come
+/// from `CallCountSession`.
 pub static DISTANCE_CALL_COUNT: AtomicUsize = AtomicUsize::new(0);
 pub static NN_DISTANCE_CALL_COUNT: AtomicUsize = AtomicUsize::new(0);
 pub(crate) const SOFT_PARETO_TEMPERATURE: f64 = 0.05;
@@ -9,47 +12,119 @@ pub(crate) const SOFT_PARETO_TEMPERATURE: f64 = 0.05;
 pub mod adapters;
 pub mod agent;
 pub mod capability;
+pub mod cost_model;
 pub mod domain;
+pub mod domain_profile;
+pub mod field_projection;
+pub mod front_plot;
+pub mod pack_registry;
+pub mod pipeline;
+#[cfg(feature = "serde")]
+pub mod playground;
 pub mod ports;
 pub mod prelude;
+pub mod representatives;
+#[cfg(feature = "serde")]
+pub mod result_repository;
 pub mod runtime;
+#[cfg(feature = "schema")]
+pub mod schemas;
 
 mod diversity;
 mod engine;
 mod normalization;
 mod stability;
 
+use capability::budget::BudgetStats;
+use capability::crossover::CrossoverStats;
+use capability::dispatch::DispatchStats;
+use capability::invariants::InvariantViolation;
+use capability::sanitize::QuarantinedCandidate;
+use capability::search_tree::SearchTree;
+use capability::state_archive::StateArchive;
+use capability::suggestion::SuggestedRule;
 use core_types::ObjectiveVector;
 use field_engine::{FieldEngine, TargetField};
 use hybrid_vm::Chm;
 use hybrid_vm::{DesignRule, Shm, Transformation};
-use hybrid_vm::{Evaluator, HybridVM};
+use hybrid_vm::{Evaluator, HybridVM, ObjectiveAttribution};
 use memory_space::{DesignState, StateId, Uuid};
 use stability::*;
 
-pub use engine::pareto::dominates;
+pub use capability::apply::ApplyError;
+pub use capability::beam::{AnytimeSearch, SearchHooks};
+pub use capability::convergence::{ConvergenceConfig, ConvergenceReason};
+pub use capability::diversity_schedule::{DiversitySchedule, DiversityTrigger};
+pub use capability::manual::{ManualCandidate, ManualChoice, ManualProposal, ManualSelectionError};
+pub use capability::novelty::NoveltyConfig;
+pub use capability::preview::{PreviewContext, RulePreview};
+pub use capability::repair::RepairConfig;
+pub use engine::call_counts::{CallCountSession, DistanceCallCounts};
+pub use engine::normalization::{DepthNormalizer, GlobalRobustEstimator};
+pub use engine::pareto::{
+    dominates, dominates_with_tolerance, dominates_within, epsilon_dominates,
+    lower_confidence_bound, noise_epsilon,
+};
+pub use runtime::candidate_pipeline::{
+    CandidatePipelineConfig, CandidateStage, PipelineThroughput, StageThroughput,
+};
+pub use runtime::semantic_bench::{
+    LatencyStats, ScalingPoint, SemanticBenchConfig, SemanticBenchResult,
+};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct ParetoFront {
     pub states: Vec<(StateId, ObjectiveVector)>,
+    tolerance: Option<ObjectiveVector>,
+    directions: ObjectiveDirections,
 }
 
 impl ParetoFront {
     pub fn new() -> Self {
-        Self { states: Vec::new() }
+        Self {
+            states: Vec::new(),
+            tolerance: None,
+            directions: ObjectiveDirections::default(),
+        }
+    }
+
+    /// A front under `dominates_with_tolerance`: a new point within
+    /// `tolerance` of a member is rejected rather than added.
+    pub fn with_tolerance(tolerance: ObjectiveVector) -> Self {
+        Self {
+            states: Vec::new(),
+            tolerance: Some(tolerance),
+            directions: ObjectiveDirections::default(),
+        }
+    }
+
+    /// Compares inserted objectives under `directions` instead of
+    /// maximizing every axis. Members keep their raw values.
+    pub fn with_directions(mut self, directions: ObjectiveDirections) -> Self {
+        self.directions = directions;
+        self
+    }
+
+    pub fn tolerance(&self) -> Option<&ObjectiveVector> {
+        self.tolerance.as_ref()
+    }
+
+    pub fn directions(&self) -> ObjectiveDirections {
+        self.directions
     }
 
     pub fn insert(&mut self, state_id: StateId, obj: ObjectiveVector) {
+        let (tolerance, directions) = (self.tolerance.as_ref(), self.directions);
         if self
             .states
             .iter()
-            .any(|(_, existing)| dominates(existing, &obj))
+            .any(|(_, existing)| directions.dominates_within(existing, &obj, tolerance))
         {
             return;
         }
 
         self.states
-            .retain(|(_, existing)| !dominates(&obj, existing));
+            .retain(|(_, existing)| !directions.dominates_within(&obj, existing, tolerance));
 
         if let Some(existing) = self
             .states
@@ -74,28 +149,564 @@ impl Default for ParetoFront {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SearchConfig {
     pub beam_width: usize,
     pub max_depth: usize,
     pub norm_alpha: f64,
+    /// Stop expanding once any frontier member meets these targets.
+    pub targets: Option<ObjectiveTargets>,
+    pub evaluation: EvaluationPolicy,
+    /// Capture every expansion into `SearchResult::search_tree`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub record_tree: bool,
+    /// Node budget for the recorded tree; see `SearchTree::truncated`.
+    #[cfg_attr(feature = "serde", serde(default = "default_max_tree_nodes"))]
+    pub max_tree_nodes: usize,
+    /// Replaces Pareto ranking with constrained single-objective selection.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub epsilon_constraint: Option<EpsilonConstraint>,
+    /// Replaces Pareto ranking with a strict priority order; ignored while
+    /// `epsilon_constraint` is set.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub lexicographic: Option<LexicographicOrder>,
+    /// Frontier pairs recombined per depth via `capability::crossover`;
+    /// 0 disables crossover.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub crossover_pairs: usize,
+    /// Per-objective normalization ahead of Pareto ranking.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub normalization: NormalizationConfig,
+    /// Warm-up of the statistics behind `NormalizationStrategy::GlobalFrozen`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub warmup: WarmupConfig,
+    /// Per-objective tolerance for the best-so-far archive, as in
+    /// `ParetoFront::with_tolerance`, ordered struct, field, risk, shape;
+    /// `None` keeps strict dominance.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub dominance_tolerance: Option<[f64; 4]>,
+    /// Record `Evaluator::explain` for every evaluated candidate in
+    /// `SearchResult::attributions`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub explain: bool,
+    /// Which objectives are minimized. Ranking, the best-so-far archive and
+    /// the `epsilon_constraint` optimize axis honor it; `targets`, the
+    /// constraint bounds, the search tree, and attributions keep raw
+    /// evaluator values.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub directions: ObjectiveDirections,
+    /// Recheck the beam's invariants every depth and report failures in
+    /// `SearchResult::invariant_violations`; see `capability::invariants`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub validate_invariants: bool,
+}
+
+#[cfg(feature = "serde")]
+fn default_max_tree_nodes() -> usize {
+    capability::search_tree::DEFAULT_MAX_TREE_NODES
+}
+
+/// How often each candidate is evaluated. `Repeated` is meant for stochastic
+/// evaluators (e.g. external simulators).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum EvaluationPolicy {
+    #[default]
+    Single,
+    Repeated {
+        n: usize,
+        aggregator: Aggregator,
+    },
+}
+
+impl EvaluationPolicy {
+    /// Evaluations per candidate.
+    pub fn samples(self) -> usize {
+        match self {
+            Self::Single => 1,
+            Self::Repeated { n, .. } => n.max(1),
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum Aggregator {
+    Mean,
+    Median,
+}
+
+/// Goal thresholds on raw objectives. Risk and cost follow
+/// `need_from_objective`: risk = `1 - f_risk`, cost = `1 - f_shape`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ObjectiveTargets {
+    pub min_f_struct: f64,
+    pub min_f_field: f64,
+    pub max_risk: f64,
+    pub max_cost: f64,
+}
+
+impl Default for ObjectiveTargets {
+    fn default() -> Self {
+        Self {
+            min_f_struct: 0.0,
+            min_f_field: 0.0,
+            max_risk: 1.0,
+            max_cost: 1.0,
+        }
+    }
+}
+
+/// Raw objective to maximize. `Risk` and `Shape` maximize `f_risk` and
+/// `f_shape`, i.e. minimize risk and cost.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ObjectiveAxis {
+    Struct,
+    Field,
+    Risk,
+    Shape,
+}
+
+impl ObjectiveAxis {
+    pub const ALL: [Self; 4] = [Self::Struct, Self::Field, Self::Risk, Self::Shape];
+
+    pub fn value(self, obj: &ObjectiveVector) -> f64 {
+        match self {
+            Self::Struct => obj.f_struct,
+            Self::Field => obj.f_field,
+            Self::Risk => obj.f_risk,
+            Self::Shape => obj.f_shape,
+        }
+    }
+
+    pub fn set(self, obj: &mut ObjectiveVector, value: f64) {
+        match self {
+            Self::Struct => obj.f_struct = value,
+            Self::Field => obj.f_field = value,
+            Self::Risk => obj.f_risk = value,
+            Self::Shape => obj.f_shape = value,
+        }
+    }
+
+    /// Position in `[f_struct, f_field, f_risk, f_shape]`.
+    pub(crate) fn index(self) -> usize {
+        match self {
+            Self::Struct => 0,
+            Self::Field => 1,
+            Self::Risk => 2,
+            Self::Shape => 3,
+        }
+    }
+
+    /// Label used in reports.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Struct => "struct",
+            Self::Field => "field",
+            Self::Risk => "risk",
+            Self::Shape => "shape",
+        }
+    }
+}
+
+/// Whether larger or smaller values of an objective are better.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum ObjectiveDirection {
+    #[default]
+    Maximize,
+    Minimize,
+}
+
+impl ObjectiveDirection {
+    /// Label used in reports.
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Maximize => "max",
+            Self::Minimize => "min",
+        }
+    }
+
+    /// `value` mapped so that larger is better. Objectives live in `[0, 1]`,
+    /// so a minimized value ranks as `1 - value`.
+    pub fn orient(self, value: f64) -> f64 {
+        match self {
+            Self::Maximize => value,
+            Self::Minimize => 1.0 - value,
+        }
+    }
+}
+
+/// One direction per objective, ordered like `ObjectiveAxis`. The default
+/// maximizes all four, which is how objectives were always ranked.
+///
+/// Ranking code works on maximized values only: `orient` a raw objective
+/// before dominance, normalization, hypervolume, or scoring, and report the
+/// raw one.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ObjectiveDirections {
+    pub per_axis: [ObjectiveDirection; 4],
+}
+
+impl ObjectiveDirections {
+    /// Maximizes every axis except `axes`.
+    pub fn minimizing(axes: &[ObjectiveAxis]) -> Self {
+        let mut directions = Self::default();
+        for axis in axes {
+            directions.per_axis[axis.index()] = ObjectiveDirection::Minimize;
+        }
+        directions
+    }
+
+    pub fn direction(&self, axis: ObjectiveAxis) -> ObjectiveDirection {
+        self.per_axis[axis.index()]
+    }
+
+    pub fn is_all_maximize(&self) -> bool {
+        self.per_axis
+            .iter()
+            .all(|d| *d == ObjectiveDirection::Maximize)
+    }
+
+    /// `obj` with every minimized axis flipped, ready for maximizing code.
+    pub fn orient(&self, obj: &ObjectiveVector) -> ObjectiveVector {
+        let [s, f, r, c] = self.per_axis;
+        ObjectiveVector {
+            f_struct: s.orient(obj.f_struct),
+            f_field: f.orient(obj.f_field),
+            f_risk: r.orient(obj.f_risk),
+            f_shape: c.orient(obj.f_shape),
+        }
+    }
+
+    /// `dominates` on raw objectives.
+    pub fn dominates(&self, a: &ObjectiveVector, b: &ObjectiveVector) -> bool {
+        dominates(&self.orient(a), &self.orient(b))
+    }
+
+    /// `dominates_within` on raw objectives; tolerances are magnitudes and
+    /// are not flipped.
+    pub fn dominates_within(
+        &self,
+        a: &ObjectiveVector,
+        b: &ObjectiveVector,
+        tolerance: Option<&ObjectiveVector>,
+    ) -> bool {
+        dominates_within(&self.orient(a), &self.orient(b), tolerance)
+    }
+
+    /// `epsilon_dominates` on raw objectives.
+    pub fn epsilon_dominates(
+        &self,
+        a: &ObjectiveVector,
+        b: &ObjectiveVector,
+        eps: &ObjectiveVector,
+    ) -> bool {
+        epsilon_dominates(&self.orient(a), &self.orient(b), eps)
+    }
+
+    /// `scalar_score` of a raw objective.
+    pub fn scalar_score(&self, obj: &ObjectiveVector) -> f64 {
+        scalar_score(&self.orient(obj))
+    }
+
+    /// `hv_4d_from_origin_normalized` of raw objectives.
+    pub fn hypervolume(&self, objs: &[ObjectiveVector]) -> f64 {
+        let points = objs
+            .iter()
+            .map(|o| {
+                let o = self.orient(o);
+                [o.f_struct, o.f_field, o.f_risk, o.f_shape]
+            })
+            .collect::<Vec<_>>();
+        hv_4d_from_origin_normalized(&points)
+    }
+
+    /// E.g. `struct:max field:max risk:max shape:min`.
+    pub fn describe(&self) -> String {
+        [
+            ObjectiveAxis::Struct,
+            ObjectiveAxis::Field,
+            ObjectiveAxis::Risk,
+            ObjectiveAxis::Shape,
+        ]
+        .iter()
+        .map(|axis| format!("{}:{}", axis.name(), self.direction(*axis).label()))
+        .collect::<Vec<_>>()
+        .join(" ")
+    }
+}
+
+/// How one objective is mapped into `[0, 1]` before Pareto ranking.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum NormalizationStrategy {
+    /// Median/MAD of the current depth, then min-max scaled.
+    #[default]
+    PerDepthRobust,
+    /// Robust statistics from the run's `GlobalRobustEstimator` (see
+    /// `WarmupConfig`), squashed through a sigmoid.
+    GlobalFrozen,
+    /// Min-max over the current depth on raw values.
+    MinMax,
+    /// Fractional rank within the current depth; ties share their mean rank.
+    Rank,
+}
+
+impl NormalizationStrategy {
+    /// Stable label recorded in `TraceRow::normalization_mode`.
+    pub fn mode(self) -> &'static str {
+        match self {
+            Self::PerDepthRobust => "per_depth_robust",
+            Self::GlobalFrozen => "global_frozen",
+            Self::MinMax => "min_max",
+            Self::Rank => "rank",
+        }
+    }
+}
+
+/// One strategy per objective, ordered like `ObjectiveAxis`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct NormalizationConfig {
+    pub per_axis: [NormalizationStrategy; 4],
+}
+
+impl NormalizationConfig {
+    pub fn uniform(strategy: NormalizationStrategy) -> Self {
+        Self {
+            per_axis: [strategy; 4],
+        }
+    }
+
+    pub fn with_axis(mut self, axis: ObjectiveAxis, strategy: NormalizationStrategy) -> Self {
+        self.per_axis[axis.index()] = strategy;
+        self
+    }
+
+    pub fn strategy(&self, axis: ObjectiveAxis) -> NormalizationStrategy {
+        self.per_axis[axis.index()]
+    }
+
+    /// The strategy's label when all axes agree, otherwise
+    /// `struct=<mode>,field=<mode>,risk=<mode>,shape=<mode>`.
+    pub fn mode(&self) -> String {
+        let first = self.per_axis[0];
+        if self.per_axis.iter().all(|s| *s == first) {
+            return first.mode().to_string();
+        }
+        ["struct", "field", "risk", "shape"]
+            .iter()
+            .zip(self.per_axis)
+            .map(|(axis, strategy)| format!("{axis}={}", strategy.mode()))
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// Epsilon-constraint selection: candidates meeting `bounds` are ranked by
+/// `optimize` alone. While nothing is feasible the beam keeps the candidates
+/// with the smallest `ObjectiveTargets::violation`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct EpsilonConstraint {
+    pub optimize: ObjectiveAxis,
+    pub bounds: ObjectiveTargets,
+}
+
+impl EpsilonConstraint {
+    /// `(violation, value)`: smaller violation ranks first, then larger
+    /// value. `bounds` are checked on raw values; `optimize` is read under
+    /// `directions`, so a minimized axis prefers its lowest raw value.
+    pub fn key(&self, obj: &ObjectiveVector, directions: ObjectiveDirections) -> (f64, f64) {
+        let value = self.optimize.value(obj);
+        (
+            self.bounds.violation(obj),
+            directions.direction(self.optimize).orient(value),
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct LexicographicLevel {
+    pub axis: ObjectiveAxis,
+    /// Candidates within this much of the best value on `axis` tie on this
+    /// level and are told apart by the next one.
+    pub tolerance: f64,
+}
+
+/// Lexicographic selection for strict priority orders (safety > cost >
+/// performance): a candidate ahead on an earlier level beats one behind on
+/// it whatever the later levels say. Values are compared under
+/// `SearchConfig::directions`; ties left after the last level break on
+/// state id.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct LexicographicOrder {
+    /// Highest priority first; unused slots are `None`.
+    pub levels: [Option<LexicographicLevel>; 4],
+}
+
+impl LexicographicOrder {
+    /// Appends a level below the existing ones; ignored once all four slots
+    /// are taken.
+    pub fn then(mut self, axis: ObjectiveAxis, tolerance: f64) -> Self {
+        if let Some(slot) = self.levels.iter_mut().find(|l| l.is_none()) {
+            *slot = Some(LexicographicLevel { axis, tolerance });
+        }
+        self
+    }
+
+    pub fn levels(&self) -> impl Iterator<Item = LexicographicLevel> + '_ {
+        self.levels.iter().flatten().copied()
+    }
+
+    /// `Greater` when `a` ranks ahead of `b`: decided by the first level on
+    /// which they differ by more than its tolerance.
+    pub fn compare(
+        &self,
+        a: &ObjectiveVector,
+        b: &ObjectiveVector,
+        directions: ObjectiveDirections,
+    ) -> std::cmp::Ordering {
+        let (a, b) = (directions.orient(a), directions.orient(b));
+        self.levels()
+            .map(|level| {
+                let diff = level.axis.value(&a) - level.axis.value(&b);
+                if diff.abs() <= level.tolerance {
+                    std::cmp::Ordering::Equal
+                } else {
+                    diff.total_cmp(&0.0)
+                }
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    }
+}
+
+/// How far the candidates of one depth stayed level with the best under a
+/// `LexicographicOrder`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LexicographicTieStats {
+    pub depth: usize,
+    pub candidates: usize,
+    /// Per level: candidates within tolerance of the best on that level
+    /// and every earlier one. A last entry above 1 means state id picked
+    /// the top candidate.
+    pub tied: Vec<usize>,
+}
+
+#[cfg(feature = "serde")]
+impl core_types::SchemaVersioned for SearchResult {
+    const KIND: &'static str = "search_result";
+    const VERSION: u32 = 1;
+}
+
+#[cfg(feature = "serde")]
+impl core_types::SchemaVersioned for TraceRow {
+    const KIND: &'static str = "trace_row";
+    const VERSION: u32 = 1;
+}
+
+#[cfg(feature = "serde")]
+impl core_types::SchemaVersioned for GlobalRobustStats {
+    const KIND: &'static str = "global_robust_stats";
+    const VERSION: u32 = 1;
+}
+
+impl ObjectiveTargets {
+    pub fn is_met(&self, obj: &ObjectiveVector) -> bool {
+        obj.f_struct >= self.min_f_struct
+            && obj.f_field >= self.min_f_field
+            && 1.0 - obj.f_risk <= self.max_risk
+            && 1.0 - obj.f_shape <= self.max_cost
+    }
+
+    /// Summed shortfall over every unmet threshold; 0 when `is_met`.
+    pub fn violation(&self, obj: &ObjectiveVector) -> f64 {
+        (self.min_f_struct - obj.f_struct).max(0.0)
+            + (self.min_f_field - obj.f_field).max(0.0)
+            + (1.0 - obj.f_risk - self.max_risk).max(0.0)
+            + (1.0 - obj.f_shape - self.max_cost).max(0.0)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SearchMode {
     Auto,
     Manual,
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DepthFront {
     pub depth: usize,
     pub state_ids: Vec<StateId>,
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct SearchResult {
     pub final_frontier: Vec<DesignState>,
     pub depth_fronts: Vec<DepthFront>,
+    /// First depth whose frontier met `SearchConfig::targets` (0 = initial state).
+    pub targets_met_at: Option<usize>,
+    /// Per-candidate sample variance; empty under `EvaluationPolicy::Single`.
+    pub objective_variance: BTreeMap<StateId, ObjectiveVector>,
+    /// Present when `SearchConfig::record_tree` is set.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub search_tree: Option<SearchTree>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub crossover: CrossoverStats,
+    /// Selections made through `AnytimeSearch::select`, one per manual depth.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub manual_choices: Vec<ManualChoice>,
+    /// Totals from `BeamSearch::search_dispatched`; zero otherwise.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub dispatch: DispatchStats,
+    /// Per-candidate objective breakdown when `SearchConfig::explain` is set
+    /// and the evaluator supports it. Each axis sums to the objective the
+    /// candidate was ranked with.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub attributions: BTreeMap<StateId, ObjectiveAttribution>,
+    /// Rules proposed through `BeamSearch::search_with_suggester`, in the
+    /// order they were examined; admitted children carry the suggested
+    /// rule's id in the search tree.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub suggestions: Vec<SuggestedRule>,
+    /// Totals from `BeamSearch::search_budgeted`; zero otherwise.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub budget: BudgetStats,
+    /// Accepted states from `BeamSearch::search_archived`; not serialized.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub archive: Option<StateArchive>,
+    /// Candidates left out of ranking for a NaN or infinite objective.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub quarantined: Vec<QuarantinedCandidate>,
+    /// One entry per expanded depth under `SearchConfig::lexicographic`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub lexicographic_ties: Vec<LexicographicTieStats>,
+    /// Failed checks under `SearchConfig::validate_invariants`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub invariant_violations: Vec<InvariantViolation>,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -112,9 +723,18 @@ pub struct BeamSearch<'a> {
     pub config: SearchConfig,
 }
 
+/// The VM's structural objective, with selected axes replaced by models.
+///
+/// Each axis has at most one owning model: the cost model owns `f_shape`,
+/// the reliability model `f_risk`, and the performance model and resource
+/// budget the axis they are given. Configuring a model on an axis that
+/// already has one replaces it, so the last `with_*` call per axis wins and
+/// no two models ever write the same axis.
 pub struct SystemEvaluator<'a> {
     pub(crate) vm: std::sync::Mutex<HybridVM>,
     pub(crate) _chm: &'a Chm,
+    /// Owner of each axis, indexed by `ObjectiveAxis::index`.
+    pub(crate) axis_models: [Option<capability::evaluation::AxisModel>; 4],
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -141,6 +761,8 @@ pub struct Phase45Log {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct TraceRow {
     pub depth: usize,
     pub lambda: f32,
@@ -213,6 +835,45 @@ pub struct TraceRow {
     pub effective_dim: usize,
     pub effective_dim_ratio: f32,
     pub collapse_reasons: String,
+    /// Set on the last row when `TraceRunConfig::convergence` stopped the run.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub convergence_reason: String,
+    /// Archive entries after this depth's accepted states were added; 0
+    /// without `TraceRunConfig::novelty`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub novelty_archive_size: usize,
+    /// Mean novelty of this depth's accepted states against the archive
+    /// before they were added.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub novelty_mean: f32,
+    /// Stranded pieces reconnected by `TraceRunConfig::repair` this depth.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub repairs_reconnected: usize,
+    /// Stranded attribute-less pieces it removed instead.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub repairs_collapsed: usize,
+    /// Front hypervolume over raw objectives minus that of the first depth
+    /// with a front; 0 on that depth and on depths without candidates.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub hv_improvement: f32,
+    /// Same for the best front member's `scalar_score`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub best_score_improvement: f32,
+    /// Candidates dropped this depth for a NaN or infinite objective.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub quarantined_count: usize,
+    /// Why this depth ran at its `pressure` under `TraceRunConfig::diversity`;
+    /// empty without a schedule.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub diversity_trigger: String,
+    /// Candidates merged into an equivalent sibling before evaluation; see
+    /// `CandidatePipelineConfig::merge_equivalent`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub merged_duplicates: usize,
+    /// `CorrelationId` of the pipeline run that produced this row, as 16
+    /// hex digits; empty outside a correlated run.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub correlation_id: String,
 }
 
 impl Default for TraceRow {
@@ -288,6 +949,17 @@ impl Default for TraceRow {
             effective_dim: 0,
             effective_dim_ratio: 0.0,
             collapse_reasons: String::new(),
+            convergence_reason: String::new(),
+            novelty_archive_size: 0,
+            novelty_mean: 0.0,
+            repairs_reconnected: 0,
+            repairs_collapsed: 0,
+            hv_improvement: 0.0,
+            best_score_improvement: 0.0,
+            quarantined_count: 0,
+            diversity_trigger: String::new(),
+            merged_duplicates: 0,
+            correlation_id: String::new(),
         }
     }
 }
@@ -409,6 +1081,7 @@ pub struct ObjectiveRaw(pub [f64; 4]);
 pub struct ObjectiveNorm(pub [f64; 4]);
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct GlobalRobustStats {
     pub median: [f64; 4],
     pub mad: [f64; 4],
@@ -436,8 +1109,22 @@ impl AdaptiveAlphaState {
             d_prev: 0.0,
         }
     }
+
+    /// The state after `decision`; unchanged when its branch holds alpha.
+    pub fn apply(&self, decision: &capability::AlphaDecision) -> Self {
+        if decision.branch.holds() {
+            return self.clone();
+        }
+        Self {
+            alpha: decision.alpha,
+            alpha_prev: self.alpha,
+            d_prev: decision.inputs.mean_nn_dist,
+        }
+    }
 }
 
+/// One controller step under `AlphaConfig::default()`; see
+/// `capability::alpha_trace` for the recorded form.
 pub fn calculate_adaptive_alpha(
     state: &AdaptiveAlphaState,
     stats: &GlobalRobustStats,
@@ -446,76 +1133,40 @@ pub fn calculate_adaptive_alpha(
     d_target: f64,
     effective_dim: usize,
 ) -> AdaptiveAlphaState {
-    // Rule 4: Effective Dimension Guarantee
-    // If effective_dim < 3, alpha adjustment is invalid.
-    if effective_dim < 3 {
-        return state.clone();
-    }
-
-    let alpha_min = 0.01;
-    let alpha_max = 0.20;
-    let r0 = 0.25;
-    let r1 = 0.75;
-    let k = 0.05;
-    let beta = 0.2;
-    let rho_max = 0.35;
-    let delta = 0.1 * d_target;
-
-    // 1. Input Metrics
-    let s_count = stats
-        .active_dims
-        .iter()
-        .zip(stats.weak_dims.iter())
-        .filter(|&(&a, &w)| a && !w)
-        .count() as f64;
-    let w_count = stats.weak_dims.iter().filter(|&&w| w).count() as f64;
-    let e_count = s_count + w_count;
-
-    // 2. Base Alpha (State-based)
-    let r = w_count / e_count.max(1.0);
-    let ratio_factor = ((r - r0) / (r1 - r0)).clamp(0.0, 1.0);
-    let alpha_base = alpha_min + (alpha_max - alpha_min) * ratio_factor;
-
-    // 3. Feedback Correction (Collapse Proxy)
-    // If d < d_target, we need MORE alpha (to increase distance).
-    // If d > d_target, we can reduce alpha.
-    let error = ((d_target - mean_nn_dist) / d_target).clamp(-1.0, 1.0);
-    let alpha_fb = alpha_base + k * error;
-
-    // 4. Smoothing
-    let alpha_target = (1.0 - beta) * state.alpha + beta * alpha_fb;
-
-    // 5. Hysteresis (Deadband)
-    let mut next_alpha = alpha_target;
-    if (mean_nn_dist - d_target).abs() < delta && pareto_size > 1 {
-        // Inside deadband, keep previous alpha to prevent chattering
-        next_alpha = state.alpha;
-    }
-
-    // 6. Safety Valve (Outlier Dominance Suppression)
-    // alpha * W / (S + alpha * W) <= rho_max
-    // alpha * W <= rho_max * S + rho_max * alpha * W
-    // alpha * W * (1 - rho_max) <= rho_max * S
-    // alpha <= (rho_max * S) / (W * (1 - rho_max))
-    if w_count > 0.0 {
-        let max_allowed = (rho_max * s_count) / (w_count * (1.0 - rho_max));
-        next_alpha = next_alpha.min(max_allowed);
-    }
-
-    // Final Clamp
-    next_alpha = next_alpha.clamp(alpha_min, alpha_max);
+    let inputs = capability::AlphaInputs::new(
+        state.alpha,
+        stats,
+        mean_nn_dist,
+        pareto_size,
+        d_target,
+        effective_dim,
+    );
+    state.apply(&capability::decide_alpha(
+        &capability::AlphaConfig::default(),
+        0,
+        inputs,
+    ))
+}
 
-    AdaptiveAlphaState {
-        alpha: next_alpha,
-        alpha_prev: state.alpha,
-        d_prev: mean_nn_dist,
-    }
+/// When a `GlobalRobustEstimator` freezes its pooled statistics.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct WarmupConfig {
+    /// Depths pooled before the statistics freeze.
+    pub warmup_depths: usize,
+    /// Once frozen, re-estimate from the depths observed since the last
+    /// freeze every this many depths; `None` keeps the first freeze.
+    pub refresh_interval: Option<usize>,
 }
 
-#[derive(Clone, Debug, Default)]
-struct GlobalRobustEstimator {
-    samples: Vec<ObjectiveRaw>,
-    frozen: Option<GlobalRobustStats>,
+impl Default for WarmupConfig {
+    fn default() -> Self {
+        Self {
+            warmup_depths: 10,
+            refresh_interval: None,
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -527,6 +1178,40 @@ pub struct TraceRunConfig {
     pub adaptive_alpha: bool,
     pub hv_guided: bool,
     pub raw_output_path: Option<PathBuf>,
+    pub normalization: NormalizationConfig,
+    pub warmup: WarmupConfig,
+    /// Frozen statistics exported from an earlier run; skips the warm-up.
+    pub calibration: Option<GlobalRobustStats>,
+    /// Stop before `depth` once the run converges; `None` runs every depth.
+    pub convergence: Option<ConvergenceConfig>,
+    /// Clamped into `1..=field_engine::MAX_DIMENSIONS`.
+    pub field_dimensions: usize,
+    /// Archive accepted states and blend their novelty into beam selection;
+    /// `None` selects on objectives alone.
+    pub novelty: Option<NoveltyConfig>,
+    /// Repair what destructive rules strand before candidates are scored;
+    /// `None` scores them as the rule left them.
+    pub repair: Option<RepairConfig>,
+    /// Worker counts for the per-depth candidate pipeline.
+    pub candidate_pipeline: CandidatePipelineConfig,
+    /// Per-depth diversity pressure on beam selection; `None` keeps plain
+    /// max-min selection and logs no pressure.
+    pub diversity: Option<DiversitySchedule>,
+    /// Which objectives are minimized. Normalization, the front, and the
+    /// hypervolume and best-score rows see oriented values; raw objective
+    /// dumps keep evaluator values.
+    pub directions: ObjectiveDirections,
+}
+
+/// Field dimensionality the trace and bench runners used before it became
+/// configurable.
+pub const DEFAULT_FIELD_DIMENSIONS: usize = 256;
+
+impl TraceRunConfig {
+    /// The field engine the trace runners build for this config.
+    pub fn field_engine(&self) -> FieldEngine {
+        FieldEngine::new(self.field_dimensions.clamp(1, field_engine::MAX_DIMENSIONS))
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -577,6 +1262,8 @@ pub struct BenchConfig {
     pub warmup: usize,
     pub seed: u64,
     pub norm_alpha: f64,
+    pub field_dimensions: usize,
+    pub candidate_pipeline: CandidatePipelineConfig,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -593,6 +1280,11 @@ pub struct BenchResult {
     pub avg_pareto_us: f64,
     pub avg_lambda_us: f64,
     pub lambda_final: f64,
+    /// Share of rule rankings served from `RuleRankingCache` over the
+    /// measured iterations.
+    pub ranking_cache_hit_rate: f64,
+    /// Candidate pipeline work summed over the measured iterations.
+    pub stage_throughput: PipelineThroughput,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -605,6 +1297,24 @@ pub struct SoftTraceParams {
     pub lambda_k: f64,
     pub lambda_ema: f64,
     pub field_profile: bool,
+    pub rule_selection: RuleSelectionKind,
+}
+
+/// Per-state rule sampling used by the soft trace; see `capability::rule_sampling`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum RuleSelectionKind {
+    #[default]
+    CategorySoft,
+    /// `CategorySoft` weights, sampled reproducibly instead of ranked.
+    CategorySoftSample,
+    Tournament {
+        size: usize,
+    },
+    Boltzmann {
+        initial_temperature: f64,
+        final_temperature: f64,
+    },
+    Stratified,
 }
 
 impl Default for SoftTraceParams {
@@ -618,6 +1328,7 @@ impl Default for SoftTraceParams {
             lambda_k: 0.05,
             lambda_ema: 0.2,
             field_profile: true,
+            rule_selection: RuleSelectionKind::CategorySoft,
         }
     }
 }
@@ -754,6 +1465,19 @@ pub fn run_bench_baseline_off_soft(config: BenchConfig, params: SoftTraceParams)
     runtime::bench::run_baseline_off_soft(config, params)
 }
 
+/// Latency of the semantic layer operations; see `runtime::semantic_bench`.
+pub fn run_semantic_bench(config: &SemanticBenchConfig) -> SemanticBenchResult {
+    runtime::semantic_bench::run(config)
+}
+
+pub fn run_bench_selection_strategies(
+    config: BenchConfig,
+    params: SoftTraceParams,
+    kinds: &[RuleSelectionKind],
+) -> Vec<(RuleSelectionKind, BenchResult)> {
+    runtime::bench::compare_selection_strategies(config, params, kinds)
+}
+
 pub(crate) fn normalize_by_depth(
     candidates: Vec<(DesignState, ObjectiveVector)>,
     alpha: f64,
@@ -769,11 +1493,21 @@ pub fn hv_4d_from_origin_normalized(points: &[[f64; 4]]) -> f64 {
     engine::pareto::hv_4d_from_origin_normalized(points)
 }
 
-pub fn apply_atomic(rule: &DesignRule, state: &DesignState) -> DesignState {
+pub fn apply_atomic(rule: &DesignRule, state: &DesignState) -> Result<DesignState, ApplyError> {
     capability::apply::apply_atomic(rule, state)
 }
 
-pub fn apply_macro(op: &MacroOperator, state: &DesignState) -> DesignState {
+/// `apply_atomic` without committing: the would-be state plus its graph diff
+/// and objective, resonance, and CHM risk deltas.
+pub fn preview_rule(
+    state: &DesignState,
+    rule: &DesignRule,
+    ctx: &PreviewContext,
+) -> Result<RulePreview, ApplyError> {
+    capability::preview::preview_rule(state, rule, ctx)
+}
+
+pub fn apply_macro(op: &MacroOperator, state: &DesignState) -> Result<DesignState, ApplyError> {
     capability::apply::apply_macro(op, state)
 }
 
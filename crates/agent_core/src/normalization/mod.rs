@@ -1,3 +1,4 @@
 pub mod depth;
 pub mod minmax;
+pub mod rank;
 pub mod robust;
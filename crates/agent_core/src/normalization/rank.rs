@@ -0,0 +1,25 @@
+/// Fractional ranks in `[0, 1]`, smallest value first. Tied values share
+/// their mean rank; a single value maps to `empty_value`.
+pub fn rank_scale(values: &[f64], empty_value: f64) -> Vec<f64> {
+    if values.len() < 2 {
+        return vec![empty_value; values.len()];
+    }
+
+    let mut order = (0..values.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| values[a].total_cmp(&values[b]));
+    let last = (values.len() - 1) as f64;
+    let mut out = vec![empty_value; values.len()];
+    let mut start = 0;
+    while start < order.len() {
+        let mut end = start + 1;
+        while end < order.len() && values[order[end]] == values[order[start]] {
+            end += 1;
+        }
+        let mean_rank = (start + end - 1) as f64 * 0.5;
+        for &idx in &order[start..end] {
+            out[idx] = mean_rank / last;
+        }
+        start = end;
+    }
+    out
+}
@@ -0,0 +1,549 @@
+//! Local registry for sharing rule packs and domain profiles between teams.
+//!
+//! Layout under the registry root, one directory per installed version:
+//!
+//! ```text
+//! <root>/<name>/<major.minor.patch>/manifest.json   PackManifest envelope
+//! <root>/<name>/<major.minor.patch>/rules.json      RulePack envelope
+//! <root>/<name>/<major.minor.patch>/profile.json    DomainProfile envelope
+//! ```
+//!
+//! `rules.json` and `profile.json` are optional. The manifest lists every
+//! rule id the pack defines and a checksum per file; loading verifies the
+//! checksums first. Installed versions are immutable: a changed pack gets a
+//! new version. Checksums (FNV-1a) catch corrupted or hand-edited files;
+//! they are not signatures.
+
+use std::collections::BTreeMap;
+#[cfg(feature = "serde")]
+use std::collections::BTreeSet;
+use std::fmt::{Display, Formatter};
+use std::io;
+#[cfg(feature = "serde")]
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use hybrid_vm::{DesignRule, RuleId};
+#[cfg(feature = "serde")]
+use hybrid_vm::{RulePack, Shm};
+
+use crate::domain_profile::DomainProfile;
+
+#[cfg(feature = "serde")]
+const MANIFEST_FILE: &str = "manifest.json";
+#[cfg(feature = "serde")]
+const RULES_FILE: &str = "rules.json";
+#[cfg(feature = "serde")]
+const PROFILE_FILE: &str = "profile.json";
+
+/// `major.minor.patch`, ordered numerically.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub struct PackVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl PackVersion {
+    pub fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+}
+
+impl Display for PackVersion {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+impl FromStr for PackVersion {
+    type Err = PackError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts = s
+            .split('.')
+            .map(|p| p.parse::<u64>().ok().filter(|_| !p.starts_with('+')))
+            .collect::<Option<Vec<_>>>();
+        match parts.as_deref() {
+            Some(&[major, minor, patch]) => Ok(Self::new(major, minor, patch)),
+            _ => Err(PackError::InvalidVersion(s.to_string())),
+        }
+    }
+}
+
+impl TryFrom<String> for PackVersion {
+    type Error = PackError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<PackVersion> for String {
+    fn from(version: PackVersion) -> Self {
+        version.to_string()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PackRef {
+    pub name: String,
+    pub version: PackVersion,
+}
+
+impl Display for PackRef {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}@{}", self.name, self.version)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PackManifest {
+    pub name: String,
+    pub version: PackVersion,
+    pub description: String,
+    /// Every rule id in `rules.json`, in file order.
+    pub rule_ids: Vec<RuleId>,
+    /// Name of the profile in `profile.json`, when the pack has one.
+    pub profile: Option<String>,
+    /// File name to `fnv1a64:<hex>` of its bytes.
+    pub checksums: BTreeMap<String, String>,
+}
+
+#[cfg(feature = "serde")]
+impl core_types::SchemaVersioned for PackManifest {
+    const KIND: &'static str = "pack_manifest";
+    const VERSION: u32 = 1;
+}
+
+impl PackManifest {
+    pub fn pack_ref(&self) -> PackRef {
+        PackRef {
+            name: self.name.clone(),
+            version: self.version,
+        }
+    }
+}
+
+/// What `PackRegistry::install` writes.
+#[derive(Clone, Debug)]
+pub struct PackBundle {
+    pub name: String,
+    pub version: PackVersion,
+    pub description: String,
+    pub rules: Vec<DesignRule>,
+    pub profile: Option<DomainProfile>,
+}
+
+/// A rule id defined by more than one pack.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuleIdOverlap {
+    pub rule_id: RuleId,
+    /// In the order the packs were given.
+    pub packs: Vec<PackRef>,
+}
+
+#[derive(Debug)]
+pub enum PackError {
+    Io(io::Error),
+    /// Names are non-empty `[a-z0-9_-]`, so they are safe as directories.
+    InvalidName(String),
+    InvalidVersion(String),
+    AlreadyInstalled(PackRef),
+    NotFound {
+        name: String,
+        /// `None` when no version of the pack is installed.
+        version: Option<PackVersion>,
+    },
+    /// A rule id appears twice within one bundle.
+    DuplicateRuleId(RuleId),
+    ChecksumMismatch {
+        pack: PackRef,
+        file: String,
+        expected: String,
+        /// `None` when the file is missing.
+        actual: Option<String>,
+    },
+    /// The pack's `rules.json` defines other ids than its manifest lists.
+    ManifestMismatch(PackRef),
+    MissingProfile(PackRef),
+    RuleIdOverlap(Vec<RuleIdOverlap>),
+}
+
+impl Display for PackError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "pack registry i/o: {err}"),
+            Self::InvalidName(name) => write!(f, "invalid pack name {name:?}"),
+            Self::InvalidVersion(version) => {
+                write!(
+                    f,
+                    "invalid pack version {version:?}, expected major.minor.patch"
+                )
+            }
+            Self::AlreadyInstalled(pack) => write!(f, "{pack} is already installed"),
+            Self::NotFound {
+                name,
+                version: Some(version),
+            } => write!(f, "{name}@{version} is not installed"),
+            Self::NotFound {
+                name,
+                version: None,
+            } => write!(f, "no version of {name} is installed"),
+            Self::DuplicateRuleId(id) => write!(f, "rule {:032x} is defined twice", id.as_u128()),
+            Self::ChecksumMismatch {
+                pack,
+                file,
+                expected,
+                actual: Some(actual),
+            } => write!(f, "{pack}: {file} is {actual}, manifest says {expected}"),
+            Self::ChecksumMismatch {
+                pack,
+                file,
+                actual: None,
+                ..
+            } => write!(f, "{pack}: {file} is missing"),
+            Self::ManifestMismatch(pack) => {
+                write!(f, "{pack}: rules do not match the manifest's rule ids")
+            }
+            Self::MissingProfile(pack) => write!(f, "{pack} has no domain profile"),
+            Self::RuleIdOverlap(overlaps) => {
+                write!(f, "packs define overlapping rule ids:")?;
+                for overlap in overlaps {
+                    let packs = overlap
+                        .packs
+                        .iter()
+                        .map(PackRef::to_string)
+                        .collect::<Vec<_>>();
+                    write!(
+                        f,
+                        " {:032x} ({})",
+                        overlap.rule_id.as_u128(),
+                        packs.join(", ")
+                    )?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for PackError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PackError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+/// Needs the `serde` feature, which the on-disk envelopes are written with.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug)]
+pub struct PackRegistry {
+    root: PathBuf,
+}
+
+#[cfg(feature = "serde")]
+impl PackRegistry {
+    /// Creates `root` if needed.
+    pub fn open(root: impl AsRef<Path>) -> io::Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        std::fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Writes `bundle` as a new version and returns its manifest. Rule ids
+    /// are not checked against other packs here; see `overlaps`.
+    pub fn install(&self, bundle: &PackBundle) -> Result<PackManifest, PackError> {
+        validate_name(&bundle.name)?;
+        let pack = PackRef {
+            name: bundle.name.clone(),
+            version: bundle.version,
+        };
+        let mut seen = BTreeSet::new();
+        if let Some(rule) = bundle.rules.iter().find(|r| !seen.insert(r.id)) {
+            return Err(PackError::DuplicateRuleId(rule.id));
+        }
+        let dir = self.dir(&pack);
+        if dir.exists() {
+            return Err(PackError::AlreadyInstalled(pack));
+        }
+        // Staged next to the final directory so a failed write leaves no
+        // half-installed version behind.
+        let staging = self
+            .root
+            .join(&pack.name)
+            .join(format!("{}.partial", pack.version));
+        let _ = std::fs::remove_dir_all(&staging);
+        std::fs::create_dir_all(&staging)?;
+        let staged = write_contents(&staging, bundle).and_then(|checksums| {
+            let manifest = PackManifest {
+                name: bundle.name.clone(),
+                version: bundle.version,
+                description: bundle.description.clone(),
+                rule_ids: bundle.rules.iter().map(|r| r.id).collect(),
+                profile: bundle.profile.as_ref().map(|p| p.name.clone()),
+                checksums,
+            };
+            write_envelope(&staging.join(MANIFEST_FILE), &manifest)?;
+            std::fs::rename(&staging, &dir)?;
+            Ok(manifest)
+        });
+        if staged.is_err() {
+            let _ = std::fs::remove_dir_all(&staging);
+        }
+        Ok(staged?)
+    }
+
+    /// Every installed version, by name then version.
+    pub fn list(&self) -> Result<Vec<PackManifest>, PackError> {
+        let mut manifests = Vec::new();
+        for name in subdirectories(&self.root)? {
+            for version in self.versions(&name)? {
+                manifests.push(self.manifest(&PackRef {
+                    name: name.clone(),
+                    version,
+                })?);
+            }
+        }
+        Ok(manifests)
+    }
+
+    /// Installed versions of `name`, oldest first.
+    pub fn versions(&self, name: &str) -> Result<Vec<PackVersion>, PackError> {
+        validate_name(name)?;
+        let dir = self.root.join(name);
+        if !dir.exists() {
+            return Ok(Vec::new());
+        }
+        let mut versions = subdirectories(&dir)?
+            .iter()
+            .filter_map(|v| v.parse().ok())
+            .collect::<Vec<PackVersion>>();
+        versions.sort();
+        Ok(versions)
+    }
+
+    /// `version` of `name`, or its newest version when `None`.
+    pub fn resolve(
+        &self,
+        name: &str,
+        version: Option<PackVersion>,
+    ) -> Result<PackManifest, PackError> {
+        let versions = self.versions(name)?;
+        let found = match version {
+            Some(version) => versions.contains(&version).then_some(version),
+            None => versions.last().copied(),
+        };
+        let version = found.ok_or_else(|| PackError::NotFound {
+            name: name.to_string(),
+            version,
+        })?;
+        self.manifest(&PackRef {
+            name: name.to_string(),
+            version,
+        })
+    }
+
+    /// Recomputes every file checksum against the manifest.
+    pub fn verify(&self, pack: &PackRef) -> Result<PackManifest, PackError> {
+        let manifest = self.manifest(pack)?;
+        let dir = self.dir(pack);
+        for (file, expected) in &manifest.checksums {
+            let actual = match std::fs::read(dir.join(file)) {
+                Ok(bytes) => Some(checksum(&bytes)),
+                Err(err) if err.kind() == io::ErrorKind::NotFound => None,
+                Err(err) => return Err(err.into()),
+            };
+            if actual.as_ref() != Some(expected) {
+                return Err(PackError::ChecksumMismatch {
+                    pack: pack.clone(),
+                    file: file.clone(),
+                    expected: expected.clone(),
+                    actual,
+                });
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Rule ids defined by more than one of `packs`, from their manifests.
+    pub fn overlaps(&self, packs: &[PackRef]) -> Result<Vec<RuleIdOverlap>, PackError> {
+        let mut owners: BTreeMap<RuleId, Vec<PackRef>> = BTreeMap::new();
+        for pack in packs {
+            for id in self.manifest(pack)?.rule_ids {
+                owners.entry(id).or_default().push(pack.clone());
+            }
+        }
+        Ok(owners
+            .into_iter()
+            .filter(|(_, packs)| packs.len() > 1)
+            .map(|(rule_id, packs)| RuleIdOverlap { rule_id, packs })
+            .collect())
+    }
+
+    /// The verified rules of `packs`, in pack order, as one rule set.
+    /// Fails on any rule id defined by two of them.
+    pub fn load_shm(&self, packs: &[PackRef]) -> Result<Shm, PackError> {
+        let overlaps = self.overlaps(packs)?;
+        if !overlaps.is_empty() {
+            return Err(PackError::RuleIdOverlap(overlaps));
+        }
+        let mut rules = Vec::new();
+        for pack in packs {
+            rules.extend(self.load_rules(pack)?);
+        }
+        let name = packs
+            .iter()
+            .map(PackRef::to_string)
+            .collect::<Vec<_>>()
+            .join("+");
+        Ok(RulePack::new(name, rules).into())
+    }
+
+    pub fn load_rules(&self, pack: &PackRef) -> Result<Vec<DesignRule>, PackError> {
+        let manifest = self.verify(pack)?;
+        let path = self.dir(pack).join(RULES_FILE);
+        let rules = if path.exists() {
+            RulePack::load(path)?.rules
+        } else {
+            Vec::new()
+        };
+        if rules
+            .iter()
+            .map(|r| r.id)
+            .ne(manifest.rule_ids.iter().copied())
+        {
+            return Err(PackError::ManifestMismatch(pack.clone()));
+        }
+        Ok(rules)
+    }
+
+    pub fn load_profile(&self, pack: &PackRef) -> Result<DomainProfile, PackError> {
+        let manifest = self.verify(pack)?;
+        if manifest.profile.is_none() {
+            return Err(PackError::MissingProfile(pack.clone()));
+        }
+        Ok(DomainProfile::load(self.dir(pack).join(PROFILE_FILE))?)
+    }
+
+    pub fn uninstall(&self, pack: &PackRef) -> Result<bool, PackError> {
+        validate_name(&pack.name)?;
+        match std::fs::remove_dir_all(self.dir(pack)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn manifest(&self, pack: &PackRef) -> Result<PackManifest, PackError> {
+        validate_name(&pack.name)?;
+        let path = self.dir(pack).join(MANIFEST_FILE);
+        if !path.exists() {
+            return Err(PackError::NotFound {
+                name: pack.name.clone(),
+                version: Some(pack.version),
+            });
+        }
+        let raw = std::fs::read_to_string(path)?;
+        let envelope: core_types::Versioned<PackManifest> =
+            serde_json::from_str(&raw).map_err(invalid_data)?;
+        Ok(envelope.into_checked().map_err(invalid_data)?)
+    }
+
+    fn dir(&self, pack: &PackRef) -> PathBuf {
+        self.root.join(&pack.name).join(pack.version.to_string())
+    }
+}
+
+#[cfg(feature = "serde")]
+fn validate_name(name: &str) -> Result<(), PackError> {
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(PackError::InvalidName(name.to_string()))
+    }
+}
+
+#[cfg(feature = "serde")]
+/// Writes the bundle's rules and profile into `dir` and returns their
+/// checksums.
+fn write_contents(dir: &Path, bundle: &PackBundle) -> io::Result<BTreeMap<String, String>> {
+    let mut checksums = BTreeMap::new();
+    if !bundle.rules.is_empty() {
+        let path = dir.join(RULES_FILE);
+        RulePack::new(bundle.name.clone(), bundle.rules.clone()).save(&path)?;
+        checksums.insert(RULES_FILE.to_string(), checksum(&std::fs::read(path)?));
+    }
+    if let Some(profile) = &bundle.profile {
+        let path = dir.join(PROFILE_FILE);
+        profile.save(&path)?;
+        checksums.insert(PROFILE_FILE.to_string(), checksum(&std::fs::read(path)?));
+    }
+    Ok(checksums)
+}
+
+#[cfg(feature = "serde")]
+fn write_envelope<T>(path: &Path, value: &T) -> io::Result<()>
+where
+    T: core_types::SchemaVersioned + serde::Serialize + Clone,
+{
+    let json = serde_json::to_string_pretty(&core_types::Versioned::new(value.clone()))
+        .map_err(invalid_data)?;
+    std::fs::write(path, json)
+}
+
+#[cfg(feature = "serde")]
+/// Directory names under `dir`, sorted; staging directories are skipped.
+fn subdirectories(dir: &Path) -> io::Result<Vec<String>> {
+    let mut names = Vec::new();
+    for entry in std::fs::read_dir(dir)? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir()
+            && let Some(name) = entry.file_name().to_str()
+            && !name.ends_with(".partial")
+        {
+            names.push(name.to_string());
+        }
+    }
+    names.sort();
+    Ok(names)
+}
+
+#[cfg(feature = "serde")]
+fn checksum(bytes: &[u8]) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in bytes {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    format!("fnv1a64:{hash:016x}")
+}
+
+#[cfg(feature = "serde")]
+fn invalid_data(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
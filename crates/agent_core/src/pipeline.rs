@@ -0,0 +1,595 @@
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use core_types::ObjectiveVector;
+use core_types::clock::Stopwatch;
+use hybrid_vm::{
+    ArtifactFormat, ArtifactValidationReport, Chm, ConceptGraphBuilder, ConceptUnitV2,
+    CorrelationId, DesignCard, Evaluator, GeneratedArtifact, HybridVM, L2Mode,
+    ObjectiveAttribution, SemanticError, Shm, StructuralEvaluator,
+};
+use memory_space::DesignState;
+
+use crate::capability::evaluate_with_policy;
+use crate::capability::search_tree::{DEFAULT_MAX_TREE_NODES, SearchTree};
+use crate::domain_profile::DomainProfile;
+use crate::front_plot::{self, FrontPlot};
+use crate::representatives::{RepresentativePick, select_representatives};
+use crate::runtime::trace_join::CorrelatedTraces;
+use crate::{
+    BeamSearch, DepthFront, EvaluationPolicy, NormalizationConfig, ObjectiveDirections,
+    SearchConfig, SearchMode, TraceRow, TraceRowBuilder, WarmupConfig, noise_epsilon,
+};
+
+/// Stages in execution order. A checkpoint records the last completed one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PipelineStage {
+    Analyze,
+    RebuildConcepts,
+    SeedState,
+    Search,
+    Cards,
+    Artifacts,
+}
+
+impl PipelineStage {
+    pub const ALL: [PipelineStage; 6] = [
+        PipelineStage::Analyze,
+        PipelineStage::RebuildConcepts,
+        PipelineStage::SeedState,
+        PipelineStage::Search,
+        PipelineStage::Cards,
+        PipelineStage::Artifacts,
+    ];
+
+    fn next(self) -> Option<Self> {
+        let idx = Self::ALL.iter().position(|s| *s == self)?;
+        Self::ALL.get(idx + 1).copied()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PipelineConfig {
+    pub l2_mode: L2Mode,
+    pub search: SearchConfig,
+    pub search_mode: SearchMode,
+    pub artifact_formats: Vec<ArtifactFormat>,
+    /// Stop after this stage; `None` runs every stage.
+    pub stop_after: Option<PipelineStage>,
+    /// Representative designs picked from the front after search; see
+    /// `representatives::select_representatives`.
+    pub recommended_designs: usize,
+}
+
+impl Default for PipelineConfig {
+    fn default() -> Self {
+        Self {
+            l2_mode: L2Mode::Stable,
+            search: SearchConfig {
+                beam_width: 4,
+                max_depth: 3,
+                norm_alpha: 0.1,
+                targets: None,
+                evaluation: EvaluationPolicy::Single,
+                record_tree: false,
+                max_tree_nodes: DEFAULT_MAX_TREE_NODES,
+                epsilon_constraint: None,
+                lexicographic: None,
+                crossover_pairs: 0,
+                normalization: NormalizationConfig::default(),
+                warmup: WarmupConfig::default(),
+                dominance_tolerance: None,
+                explain: false,
+                directions: ObjectiveDirections::default(),
+                validate_invariants: false,
+            },
+            search_mode: SearchMode::Auto,
+            artifact_formats: vec![
+                ArtifactFormat::Rust,
+                ArtifactFormat::Sql,
+                ArtifactFormat::Mermaid,
+            ],
+            stop_after: None,
+            recommended_designs: 3,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParetoEntry {
+    pub state: DesignState,
+    pub objective: ObjectiveVector,
+    /// Sample variance under `EvaluationPolicy::Repeated`, zero otherwise.
+    pub variance: ObjectiveVector,
+    /// Present when `SearchConfig::explain` is set.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub attribution: Option<ObjectiveAttribution>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StageTiming {
+    pub stage: PipelineStage,
+    pub elapsed_us: u128,
+}
+
+/// Intermediate results captured after each stage. Feeding it back through
+/// `DesignPipeline::resume` continues from the first incomplete stage.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PipelineCheckpoint {
+    pub text: String,
+    pub last_completed: Option<PipelineStage>,
+    pub concepts: Vec<ConceptUnitV2>,
+    pub initial_state: Option<DesignState>,
+    pub pareto_front: Vec<ParetoEntry>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub recommended: Vec<RepresentativePick>,
+    pub depth_fronts: Vec<DepthFront>,
+    pub targets_met_at: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub search_tree: Option<SearchTree>,
+    pub cards: Vec<DesignCard>,
+    pub artifacts: BTreeMap<String, Vec<GeneratedArtifact>>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub artifact_validation: Option<ArtifactValidationReport>,
+    pub timings: Vec<StageTiming>,
+    /// Tags this run's entries in the VM traces and in `search_trace`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub correlation_id: Option<CorrelationId>,
+    /// One row per entry of `depth_fronts`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub search_trace: Vec<TraceRow>,
+    /// VM `trace_seq` when the search started.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub search_seq: Option<u64>,
+}
+
+#[cfg(feature = "serde")]
+impl core_types::SchemaVersioned for PipelineCheckpoint {
+    const KIND: &'static str = "pipeline_checkpoint";
+    const VERSION: u32 = 1;
+}
+
+impl PipelineCheckpoint {
+    pub fn is_complete(&self, stop_after: Option<PipelineStage>) -> bool {
+        let target = stop_after.unwrap_or(PipelineStage::Artifacts);
+        self.last_completed.is_some_and(|s| s >= target)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PipelineReport {
+    pub concepts: Vec<ConceptUnitV2>,
+    pub initial_state: Option<DesignState>,
+    pub pareto_front: Vec<ParetoEntry>,
+    /// Picks from `pareto_front`, `index` pointing into it.
+    pub recommended: Vec<RepresentativePick>,
+    pub depth_fronts: Vec<DepthFront>,
+    /// See `SearchResult::targets_met_at`.
+    pub targets_met_at: Option<usize>,
+    /// Recorded when `SearchConfig::record_tree` is set.
+    pub search_tree: Option<SearchTree>,
+    pub cards: Vec<DesignCard>,
+    /// Keyed by `ArtifactFormat` debug name ("Rust", "Sql", "Mermaid").
+    pub artifacts: BTreeMap<String, Vec<GeneratedArtifact>>,
+    /// Checks of every generated artifact against the concepts'
+    /// requirements.
+    pub artifact_validation: Option<ArtifactValidationReport>,
+    pub timings: Vec<StageTiming>,
+    pub last_completed: Option<PipelineStage>,
+    /// `SearchConfig::directions` the front was ranked under; objectives
+    /// above are raw.
+    pub directions: ObjectiveDirections,
+    /// See `DesignPipeline::correlated_traces`.
+    pub correlation_id: Option<CorrelationId>,
+    pub search_trace: Vec<TraceRow>,
+}
+
+impl PipelineReport {
+    /// Plain-text summary of the Pareto front, one block per entry in front
+    /// order, with each entry's objective breakdown when it was explained.
+    /// Led by the objective directions when any axis is minimized and
+    /// followed by the recommended designs.
+    pub fn render_front(&self) -> String {
+        let mut out = String::new();
+        if !self.directions.is_all_maximize() {
+            out.push_str(&format!("directions: {}\n", self.directions.describe()));
+        }
+        for (rank, entry) in self.pareto_front.iter().enumerate() {
+            let o = &entry.objective;
+            out.push_str(&format!(
+                "#{} {:032x}: f_struct {:.2}, f_field {:.2}, f_risk {:.2}, f_shape {:.2}\n",
+                rank + 1,
+                entry.state.id.as_u128(),
+                o.f_struct,
+                o.f_field,
+                o.f_risk,
+                o.f_shape
+            ));
+            if let Some(attribution) = &entry.attribution {
+                for line in attribution.to_string().lines() {
+                    out.push_str("    ");
+                    out.push_str(line);
+                    out.push('\n');
+                }
+            }
+        }
+        if !self.recommended.is_empty() {
+            out.push_str("recommended:\n");
+            for pick in &self.recommended {
+                out.push_str(&format!("  #{} {}\n", pick.index + 1, pick.rationale));
+            }
+        }
+        out
+    }
+
+    /// Plot data for the final front and, when the search tree was
+    /// recorded, every depth front.
+    pub fn front_plot(&self) -> FrontPlot {
+        let final_front = self
+            .pareto_front
+            .iter()
+            .map(|e| (e.state.id, e.objective.clone()))
+            .collect();
+        let mut fronts = vec![(None, final_front)];
+        if let Some(tree) = &self.search_tree {
+            fronts.extend(front_plot::depth_fronts(&self.depth_fronts, tree));
+        }
+        FrontPlot::build(self.directions, fronts)
+    }
+}
+
+#[derive(Debug)]
+pub struct PipelineError {
+    pub stage: PipelineStage,
+    pub source: SemanticError,
+}
+
+impl Display for PipelineError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "pipeline stage {:?} failed: {}", self.stage, self.source)
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
+pub type StateSeeder = Arc<dyn Fn(&[ConceptUnitV2]) -> DesignState + Send + Sync>;
+
+/// Orchestrates text → concepts → search → cards/artifacts on one `HybridVM`.
+pub struct DesignPipeline {
+    vm: HybridVM,
+    shm: Shm,
+    chm: Chm,
+    evaluator: Box<dyn Evaluator + Send + Sync>,
+    seeder: StateSeeder,
+    config: PipelineConfig,
+    checkpoint: PipelineCheckpoint,
+    profile: Option<DomainProfile>,
+}
+
+impl DesignPipeline {
+    pub fn new(vm: HybridVM) -> Self {
+        Self {
+            vm,
+            shm: HybridVM::default_shm(),
+            chm: HybridVM::empty_chm(),
+            evaluator: Box::new(StructuralEvaluator::default()),
+            seeder: Arc::new(seed_state_from_concepts),
+            config: PipelineConfig::default(),
+            checkpoint: PipelineCheckpoint::default(),
+            profile: None,
+        }
+    }
+
+    pub fn with_config(mut self, config: PipelineConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_shm(mut self, shm: Shm) -> Self {
+        self.shm = shm;
+        self
+    }
+
+    /// Reprioritizes the current rule set and sets search constraints, so
+    /// call it after `with_shm`/`with_config`. The Pareto front is then
+    /// ordered by the profile's preference score.
+    pub fn with_domain_profile(mut self, profile: DomainProfile) -> Self {
+        profile.configure_shm(&mut self.shm);
+        profile.configure_search(&mut self.config.search);
+        self.profile = Some(profile);
+        self
+    }
+
+    pub fn domain_profile(&self) -> Option<&DomainProfile> {
+        self.profile.as_ref()
+    }
+
+    pub fn with_chm(mut self, chm: Chm) -> Self {
+        self.chm = chm;
+        self
+    }
+
+    pub fn with_evaluator(mut self, evaluator: impl Evaluator + Send + Sync + 'static) -> Self {
+        self.evaluator = Box::new(evaluator);
+        self
+    }
+
+    pub fn with_seeder(
+        mut self,
+        seeder: impl Fn(&[ConceptUnitV2]) -> DesignState + Send + Sync + 'static,
+    ) -> Self {
+        self.seeder = Arc::new(seeder);
+        self
+    }
+
+    /// Warm start: searches from `state` instead of one seeded from concepts.
+    pub fn with_seed_state(self, state: DesignState) -> Self {
+        self.with_seeder(move |_| state.clone())
+    }
+
+    pub fn config(&self) -> &PipelineConfig {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut PipelineConfig {
+        &mut self.config
+    }
+
+    pub fn vm(&self) -> &HybridVM {
+        &self.vm
+    }
+
+    pub fn vm_mut(&mut self) -> &mut HybridVM {
+        &mut self.vm
+    }
+
+    pub fn checkpoint(&self) -> &PipelineCheckpoint {
+        &self.checkpoint
+    }
+
+    pub fn into_vm(self) -> HybridVM {
+        self.vm
+    }
+
+    /// Starts a fresh run for `text` under a new correlation id, discarding
+    /// any previous checkpoint.
+    pub fn run(&mut self, text: &str) -> Result<PipelineReport, PipelineError> {
+        self.checkpoint = PipelineCheckpoint {
+            text: text.to_string(),
+            correlation_id: Some(CorrelationId::generate()),
+            ..PipelineCheckpoint::default()
+        };
+        self.resume()
+    }
+
+    /// Continues from the stored checkpoint (e.g. after a failed stage or an
+    /// earlier `stop_after`). VM trace entries made meanwhile carry the
+    /// checkpoint's correlation id.
+    pub fn resume(&mut self) -> Result<PipelineReport, PipelineError> {
+        let id = *self
+            .checkpoint
+            .correlation_id
+            .get_or_insert_with(CorrelationId::generate);
+        let previous = self.vm.correlation_id();
+        self.vm.set_correlation_id(Some(id));
+        let result = self.run_stages();
+        self.vm.set_correlation_id(previous);
+        result.map(|()| self.report())
+    }
+
+    fn run_stages(&mut self) -> Result<(), PipelineError> {
+        let stop_after = self.config.stop_after;
+        while !self.checkpoint.is_complete(stop_after) {
+            let stage = match self.checkpoint.last_completed {
+                None => PipelineStage::Analyze,
+                Some(done) => match done.next() {
+                    Some(next) => next,
+                    None => break,
+                },
+            };
+            let started = Stopwatch::start();
+            self.run_stage(stage)
+                .map_err(|source| PipelineError { stage, source })?;
+            self.checkpoint.timings.push(StageTiming {
+                stage,
+                elapsed_us: started.elapsed().as_micros(),
+            });
+            self.checkpoint.last_completed = Some(stage);
+        }
+        Ok(())
+    }
+
+    pub fn resume_from(
+        &mut self,
+        checkpoint: PipelineCheckpoint,
+    ) -> Result<PipelineReport, PipelineError> {
+        self.checkpoint = checkpoint;
+        self.resume()
+    }
+
+    fn run_stage(&mut self, stage: PipelineStage) -> Result<(), SemanticError> {
+        match stage {
+            PipelineStage::Analyze => {
+                let text = self.checkpoint.text.clone();
+                self.vm.analyze_text(&text)?;
+            }
+            PipelineStage::RebuildConcepts => {
+                self.vm.rebuild_l2_from_l1_with_mode(self.config.l2_mode)?;
+                self.checkpoint.concepts = self.vm.project_phase_a_v2()?;
+            }
+            PipelineStage::SeedState => {
+                self.checkpoint.initial_state = Some((self.seeder)(&self.checkpoint.concepts));
+            }
+            PipelineStage::Search => {
+                let initial = self
+                    .checkpoint
+                    .initial_state
+                    .clone()
+                    .ok_or(SemanticError::MissingField("initial_state"))?;
+                self.checkpoint.search_seq = Some(self.vm.trace_seq());
+                let search = BeamSearch {
+                    shm: &self.shm,
+                    chm: &self.chm,
+                    evaluator: self.evaluator.as_ref(),
+                    config: self.config.search,
+                };
+                let result = search.search_with_mode(&initial, self.config.search_mode);
+                self.checkpoint.pareto_front = pareto_front(
+                    result.final_frontier,
+                    self.evaluator.as_ref(),
+                    self.config.search.evaluation,
+                    self.config.search.explain,
+                    self.config.search.directions,
+                );
+                if let Some(profile) = &self.profile {
+                    self.checkpoint.pareto_front.sort_by(|l, r| {
+                        profile
+                            .preference
+                            .score(&r.objective)
+                            .total_cmp(&profile.preference.score(&l.objective))
+                    });
+                }
+                let front = self
+                    .checkpoint
+                    .pareto_front
+                    .iter()
+                    .map(|e| (e.state.id, e.objective.clone()))
+                    .collect::<Vec<_>>();
+                self.checkpoint.recommended = select_representatives(
+                    &front,
+                    self.config.search.directions,
+                    self.config.recommended_designs,
+                );
+                let tag = self
+                    .checkpoint
+                    .correlation_id
+                    .map(|id| id.to_string())
+                    .unwrap_or_default();
+                self.checkpoint.search_trace = result
+                    .depth_fronts
+                    .iter()
+                    .map(|front| {
+                        TraceRowBuilder::new()
+                            .apply(|r| {
+                                r.depth = front.depth;
+                                r.correlation_id = tag.clone();
+                            })
+                            .build()
+                    })
+                    .collect();
+                self.checkpoint.depth_fronts = result.depth_fronts;
+                self.checkpoint.targets_met_at = result.targets_met_at;
+                self.checkpoint.search_tree = result.search_tree;
+            }
+            PipelineStage::Cards => {
+                self.checkpoint.cards = self.vm.get_design_cards()?;
+            }
+            PipelineStage::Artifacts => {
+                let mut artifacts = BTreeMap::new();
+                for format in &self.config.artifact_formats {
+                    artifacts.insert(format!("{format:?}"), self.vm.generate_artifacts(*format)?);
+                }
+                let all = artifacts.values().flatten().cloned().collect::<Vec<_>>();
+                self.checkpoint.artifact_validation = Some(self.vm.validate_artifacts(&all)?);
+                self.checkpoint.artifacts = artifacts;
+            }
+        }
+        Ok(())
+    }
+
+    fn report(&self) -> PipelineReport {
+        let cp = &self.checkpoint;
+        PipelineReport {
+            concepts: cp.concepts.clone(),
+            initial_state: cp.initial_state.clone(),
+            pareto_front: cp.pareto_front.clone(),
+            recommended: cp.recommended.clone(),
+            depth_fronts: cp.depth_fronts.clone(),
+            targets_met_at: cp.targets_met_at,
+            search_tree: cp.search_tree.clone(),
+            cards: cp.cards.clone(),
+            artifacts: cp.artifacts.clone(),
+            artifact_validation: cp.artifact_validation.clone(),
+            timings: cp.timings.clone(),
+            last_completed: cp.last_completed,
+            directions: self.config.search.directions,
+            correlation_id: cp.correlation_id,
+            search_trace: cp.search_trace.clone(),
+        }
+    }
+
+    /// The current run's semantic operations, VM evaluations and search
+    /// depths, joined on its correlation id; `None` before the first run.
+    /// Only entries still in the VM traces are included.
+    pub fn correlated_traces(&self) -> Option<CorrelatedTraces> {
+        let cp = &self.checkpoint;
+        let id = cp.correlation_id?;
+        Some(
+            CorrelatedTraces::join(
+                id,
+                self.vm.semantic_trace(),
+                self.vm.trace(),
+                &cp.search_trace,
+            )
+            .with_depth_fronts(cp.depth_fronts.clone())
+            .with_search_seq(cp.search_seq),
+        )
+    }
+}
+
+/// Default seeding: the `ConceptGraphBuilder` graph for the current concepts.
+pub fn seed_state_from_concepts(concepts: &[ConceptUnitV2]) -> DesignState {
+    ConceptGraphBuilder::new()
+        .with_profile("pipeline:seed")
+        .build(concepts)
+        .state
+}
+
+/// Non-dominated members of `frontier` under `directions`. Under repeated
+/// evaluation a member is only discarded when another beats it by more
+/// than the noise epsilon.
+fn pareto_front(
+    frontier: Vec<DesignState>,
+    evaluator: &dyn Evaluator,
+    policy: EvaluationPolicy,
+    explain: bool,
+    directions: ObjectiveDirections,
+) -> Vec<ParetoEntry> {
+    let scored = frontier
+        .into_iter()
+        .map(|state| {
+            let evaluation = evaluate_with_policy(evaluator, &state, policy);
+            let attribution = if explain {
+                evaluator
+                    .explain(&state)
+                    .map(|a| a.reconciled(&evaluation.objective, "unattributed"))
+            } else {
+                None
+            };
+            (
+                ParetoEntry {
+                    state,
+                    objective: evaluation.objective,
+                    variance: evaluation.variance,
+                    attribution,
+                },
+                evaluation.samples,
+            )
+        })
+        .collect::<Vec<_>>();
+    scored
+        .iter()
+        .filter(|(candidate, samples)| {
+            !scored.iter().any(|(other, _)| {
+                let eps = noise_epsilon(&other.variance, &candidate.variance, *samples);
+                directions.epsilon_dominates(&other.objective, &candidate.objective, &eps)
+            })
+        })
+        .map(|(entry, _)| entry.clone())
+        .collect()
+}
@@ -0,0 +1,74 @@
+//! Browser-facing entry point. Everything crosses the boundary as `&str`,
+//! integers, or JSON strings so the type can be exported with wasm-bindgen
+//! (`--features wasm`) without extra glue. The VM is fully in-memory.
+
+use hybrid_vm::{HybridVM, StructuralEvaluator};
+use serde_json::json;
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::pipeline::{DesignPipeline, PipelineCheckpoint, PipelineConfig, PipelineStage};
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct Playground {
+    pipeline: DesignPipeline,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Playground {
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Result<Playground, String> {
+        let vm = HybridVM::in_memory(StructuralEvaluator::default()).map_err(|e| e.to_string())?;
+        Ok(Self {
+            pipeline: DesignPipeline::new(vm),
+        })
+    }
+
+    /// Returns `{"concept_id", "l1_refs", "abstraction", "polarity"}` as JSON.
+    #[cfg_attr(feature = "wasm", wasm_bindgen(js_name = analyzeText))]
+    pub fn analyze_text(&mut self, text: &str) -> Result<String, String> {
+        let unit = self
+            .pipeline
+            .vm_mut()
+            .analyze_text(text)
+            .map_err(|e| e.to_string())?;
+        Ok(json!({
+            "concept_id": unit.id.0,
+            "l1_refs": unit
+                .l1_refs
+                .iter()
+                .map(|id| format!("{:032x}", id.0))
+                .collect::<Vec<_>>(),
+            "abstraction": unit.a,
+            "polarity": unit.polarity,
+        })
+        .to_string())
+    }
+
+    /// Beam search over everything analyzed so far. Returns
+    /// `{"pareto_front", "recommended", "depth_fronts", "targets_met_at"}` as
+    /// JSON.
+    pub fn search(&mut self, beam_width: u32, max_depth: u32) -> Result<String, String> {
+        let mut config = PipelineConfig {
+            stop_after: Some(PipelineStage::Search),
+            ..PipelineConfig::default()
+        };
+        config.search.beam_width = beam_width as usize;
+        config.search.max_depth = max_depth as usize;
+        *self.pipeline.config_mut() = config;
+        let report = self
+            .pipeline
+            .resume_from(PipelineCheckpoint {
+                last_completed: Some(PipelineStage::Analyze),
+                ..PipelineCheckpoint::default()
+            })
+            .map_err(|e| e.to_string())?;
+        let out = json!({
+            "pareto_front": report.pareto_front,
+            "recommended": report.recommended,
+            "depth_fronts": report.depth_fronts,
+            "targets_met_at": report.targets_met_at,
+        });
+        Ok(out.to_string())
+    }
+}
@@ -0,0 +1,137 @@
+//! Picks a handful of states that stand for a large front, each with a
+//! sentence saying why it was picked.
+//!
+//! Picks come in a fixed order until `k` are made: the knee (the
+//! non-dominated state nearest the ideal point), the best state on each
+//! objective that varies across the front, then max-min diverse states,
+//! each the one farthest from every earlier pick. Distances are measured
+//! in `FrontPlot`'s normalized coordinates, so minimized axes count the
+//! same as maximized ones.
+
+use core_types::ObjectiveVector;
+use memory_space::StateId;
+
+use crate::front_plot::FrontPlot;
+use crate::{ObjectiveAxis, ObjectiveDirection, ObjectiveDirections};
+
+const AXES: [ObjectiveAxis; 4] = [
+    ObjectiveAxis::Struct,
+    ObjectiveAxis::Field,
+    ObjectiveAxis::Risk,
+    ObjectiveAxis::Shape,
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PickReason {
+    Knee,
+    /// Best on this axis.
+    Extreme(ObjectiveAxis),
+    /// Farthest from the earlier picks.
+    Diverse,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RepresentativePick {
+    pub state_id: StateId,
+    /// Position in the front the pick was made from.
+    pub index: usize,
+    pub reason: PickReason,
+    pub rationale: String,
+}
+
+/// Up to `k` picks from `front` (raw objectives under `directions`), in
+/// pick order. Fewer come back when the front runs out of distinct states.
+pub fn select_representatives(
+    front: &[(StateId, ObjectiveVector)],
+    directions: ObjectiveDirections,
+    k: usize,
+) -> Vec<RepresentativePick> {
+    let plot = FrontPlot::build(directions, [(None, front.to_vec())]);
+    let Some(series) = plot.series.first() else {
+        return Vec::new();
+    };
+    let points = &series.points;
+    let mut picks: Vec<RepresentativePick> = Vec::new();
+    let taken = |picks: &[RepresentativePick], i: usize| picks.iter().any(|p| p.index == i);
+
+    if let Some(knee) = series.knee.filter(|_| k > 0) {
+        let gap = distance(&points[knee].normalized, &[1.0; 4]);
+        let candidates = points.iter().filter(|p| p.rank == 1).count();
+        picks.push(RepresentativePick {
+            state_id: points[knee].state_id,
+            index: knee,
+            reason: PickReason::Knee,
+            rationale: format!(
+                "knee: nearest the ideal point of {candidates} non-dominated designs \
+                 (normalized gap {gap:.2})"
+            ),
+        });
+    }
+
+    for (a, axis) in AXES.into_iter().enumerate() {
+        let [min, max] = plot.raw_bounds[a];
+        if picks.len() >= k || min == max {
+            continue;
+        }
+        // Highest normalized value, non-dominated first, then front order.
+        let best = (0..points.len())
+            .min_by(|&l, &r| {
+                points[r].normalized[a]
+                    .total_cmp(&points[l].normalized[a])
+                    .then(points[l].rank.cmp(&points[r].rank))
+                    .then(l.cmp(&r))
+            })
+            .expect("non-empty front");
+        if taken(&picks, best) {
+            continue;
+        }
+        let extreme = match directions.direction(axis) {
+            ObjectiveDirection::Maximize => "highest",
+            ObjectiveDirection::Minimize => "lowest",
+        };
+        picks.push(RepresentativePick {
+            state_id: points[best].state_id,
+            index: best,
+            reason: PickReason::Extreme(axis),
+            rationale: format!(
+                "extreme: {extreme} f_{} ({:.2}; front spans {min:.2}..{max:.2})",
+                axis.name(),
+                axis.value(&points[best].objective),
+            ),
+        });
+    }
+
+    while picks.len() < k {
+        let farthest = (0..points.len())
+            .filter(|&i| !taken(&picks, i))
+            .map(|i| {
+                let nearest = picks
+                    .iter()
+                    .map(|p| distance(&points[i].normalized, &points[p.index].normalized))
+                    .fold(f64::INFINITY, f64::min);
+                (i, nearest)
+            })
+            .max_by(|l, r| l.1.total_cmp(&r.1).then(r.0.cmp(&l.0)));
+        // The knee is always picked first, so `nearest` is finite.
+        let Some((i, nearest)) = farthest.filter(|(_, d)| *d > 0.0) else {
+            break;
+        };
+        picks.push(RepresentativePick {
+            state_id: points[i].state_id,
+            index: i,
+            reason: PickReason::Diverse,
+            rationale: format!("diverse: {nearest:.2} from the nearest earlier pick"),
+        });
+    }
+    picks
+}
+
+fn distance(a: &[f64; 4], b: &[f64; 4]) -> f64 {
+    a.iter()
+        .zip(b)
+        .map(|(x, y)| (x - y).powi(2))
+        .sum::<f64>()
+        .sqrt()
+}
@@ -0,0 +1,258 @@
+//! File-backed store for finished runs: one JSON envelope per run under a
+//! root directory, retrievable by id, tag, date, or objective thresholds.
+
+use std::collections::BTreeSet;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use core_types::clock;
+use hybrid_vm::ProfileApplication;
+use memory_space::DesignState;
+
+use crate::pipeline::{ParetoEntry, PipelineReport, StageTiming};
+use crate::{ObjectiveTargets, SearchConfig};
+
+/// What produced a run, enough to reproduce or compare it.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct RunManifest {
+    pub input_text: String,
+    pub search: Option<SearchConfig>,
+    pub domain_profile: Option<String>,
+    pub notes: String,
+    /// Preference profile the run was made with, from
+    /// `ProfileStore::apply`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub profile: Option<ProfileApplication>,
+}
+
+#[cfg(feature = "serde")]
+impl core_types::SchemaVersioned for RunManifest {
+    const KIND: &'static str = "run_manifest";
+    const VERSION: u32 = 1;
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TraceSummary {
+    /// Frontier size per depth, depth 0 first.
+    pub front_sizes: Vec<usize>,
+    pub targets_met_at: Option<usize>,
+    pub timings: Vec<StageTiming>,
+}
+
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StoredRun {
+    /// Assigned by `ResultRepository::store`.
+    pub id: String,
+    pub tags: BTreeSet<String>,
+    pub created_at_ms: u64,
+    pub manifest: RunManifest,
+    pub front: Vec<ParetoEntry>,
+    pub trace: TraceSummary,
+}
+
+#[cfg(feature = "serde")]
+impl core_types::SchemaVersioned for StoredRun {
+    const KIND: &'static str = "stored_run";
+    const VERSION: u32 = 1;
+}
+
+impl StoredRun {
+    pub fn from_report(
+        manifest: RunManifest,
+        report: &PipelineReport,
+        tags: impl IntoIterator<Item = impl Into<String>>,
+    ) -> Self {
+        Self {
+            id: String::new(),
+            tags: tags.into_iter().map(Into::into).collect(),
+            created_at_ms: clock::unix_time_millis(),
+            manifest,
+            front: report.pareto_front.clone(),
+            trace: TraceSummary {
+                front_sizes: report
+                    .depth_fronts
+                    .iter()
+                    .map(|f| f.state_ids.len())
+                    .collect(),
+                targets_met_at: report.targets_met_at,
+                timings: report.timings.clone(),
+            },
+        }
+    }
+
+    /// Front entry `rank` (front order), to seed a new run.
+    pub fn seed_state(&self, rank: usize) -> Option<DesignState> {
+        self.front.get(rank).map(|entry| entry.state.clone())
+    }
+}
+
+/// All set criteria must hold.
+#[derive(Clone, Debug, Default)]
+pub struct RunFilter {
+    pub tag: Option<String>,
+    pub created_after_ms: Option<u64>,
+    pub created_before_ms: Option<u64>,
+    /// Name of the preference profile applied to the run.
+    pub profile: Option<String>,
+    /// At least one front entry must meet these targets.
+    pub objectives: Option<ObjectiveTargets>,
+}
+
+impl RunFilter {
+    pub fn tagged(tag: impl Into<String>) -> Self {
+        Self {
+            tag: Some(tag.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn matches(&self, run: &StoredRun) -> bool {
+        self.tag.as_ref().is_none_or(|t| run.tags.contains(t))
+            && self
+                .created_after_ms
+                .is_none_or(|after| run.created_at_ms >= after)
+            && self
+                .created_before_ms
+                .is_none_or(|before| run.created_at_ms < before)
+            && self.profile.as_ref().is_none_or(|name| {
+                run.manifest
+                    .profile
+                    .as_ref()
+                    .is_some_and(|applied| &applied.name == name)
+            })
+            && self
+                .objectives
+                .is_none_or(|targets| run.front.iter().any(|e| targets.is_met(&e.objective)))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ResultRepository {
+    root: PathBuf,
+}
+
+impl ResultRepository {
+    /// Creates `root` if needed.
+    pub fn open(root: impl AsRef<Path>) -> io::Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        std::fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// Persists `run` under a fresh id (`<created_at_ms>-<n>`) and returns it.
+    pub fn store(&self, mut run: StoredRun) -> io::Result<String> {
+        let mut n = 0usize;
+        run.id = loop {
+            let id = format!("{:013}-{n}", run.created_at_ms);
+            if !self.path(&id).exists() {
+                break id;
+            }
+            n += 1;
+        };
+        self.write(&run)?;
+        Ok(run.id)
+    }
+
+    pub fn get(&self, id: &str) -> io::Result<Option<StoredRun>> {
+        let path = self.path(id);
+        if !path.exists() {
+            return Ok(None);
+        }
+        let raw = std::fs::read_to_string(path)?;
+        let envelope: core_types::Versioned<StoredRun> =
+            serde_json::from_str(&raw).map_err(invalid_data)?;
+        envelope.into_checked().map(Some).map_err(invalid_data)
+    }
+
+    /// Matching runs, oldest first.
+    pub fn list(&self, filter: &RunFilter) -> io::Result<Vec<StoredRun>> {
+        let mut ids = Vec::new();
+        for entry in std::fs::read_dir(&self.root)? {
+            let path = entry?.path();
+            if path.extension().is_some_and(|ext| ext == "json")
+                && let Some(stem) = path.file_stem().and_then(|s| s.to_str())
+            {
+                ids.push(stem.to_string());
+            }
+        }
+        ids.sort();
+        let mut runs = Vec::new();
+        for id in ids {
+            if let Some(run) = self.get(&id)?
+                && filter.matches(&run)
+            {
+                runs.push(run);
+            }
+        }
+        Ok(runs)
+    }
+
+    /// Returns false when the run does not exist.
+    pub fn tag(&self, id: &str, tag: impl Into<String>) -> io::Result<bool> {
+        self.update(id, |run| {
+            run.tags.insert(tag.into());
+        })
+    }
+
+    pub fn untag(&self, id: &str, tag: &str) -> io::Result<bool> {
+        self.update(id, |run| {
+            run.tags.remove(tag);
+        })
+    }
+
+    pub fn delete(&self, id: &str) -> io::Result<bool> {
+        match std::fs::remove_file(self.path(id)) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Stored front entry `rank` of run `id`, for `DesignPipeline::with_seed_state`.
+    pub fn seed_state(&self, id: &str, rank: usize) -> io::Result<Option<DesignState>> {
+        Ok(self.get(id)?.and_then(|run| run.seed_state(rank)))
+    }
+
+    fn update(&self, id: &str, edit: impl FnOnce(&mut StoredRun)) -> io::Result<bool> {
+        let Some(mut run) = self.get(id)? else {
+            return Ok(false);
+        };
+        edit(&mut run);
+        self.write(&run)?;
+        Ok(true)
+    }
+
+    fn write(&self, run: &StoredRun) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(&core_types::Versioned::new(run.clone()))
+            .map_err(invalid_data)?;
+        std::fs::write(self.path(&run.id), json)
+    }
+
+    /// Ids become file stems; anything but `[A-Za-z0-9-]` is replaced so an
+    /// id cannot escape `root`.
+    fn path(&self, id: &str) -> PathBuf {
+        let safe = id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect::<String>();
+        self.root.join(format!("{safe}.json"))
+    }
+}
+
+fn invalid_data(e: impl Into<Box<dyn std::error::Error + Send + Sync>>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, e)
+}
@@ -14,6 +14,24 @@ pub fn run_baseline_off_balanced(config: crate::BenchConfig, m: usize) -> crate:
     run_baseline_off_soft(config, params)
 }
 
+/// Runs the soft-trace benchmark once per rule selection strategy.
+pub fn compare_selection_strategies(
+    config: crate::BenchConfig,
+    params: crate::SoftTraceParams,
+    kinds: &[crate::RuleSelectionKind],
+) -> Vec<(crate::RuleSelectionKind, crate::BenchResult)> {
+    kinds
+        .iter()
+        .map(|kind| {
+            let params = crate::SoftTraceParams {
+                rule_selection: *kind,
+                ..params
+            };
+            (*kind, run_baseline_off_soft(config, params))
+        })
+        .collect()
+}
+
 pub fn run_baseline_off_soft(
     config: crate::BenchConfig,
     params: crate::SoftTraceParams,
@@ -28,12 +46,24 @@ pub fn run_baseline_off_soft(
             adaptive_alpha: false,
             hv_guided: false,
             raw_output_path: None,
+            normalization: crate::NormalizationConfig::default(),
+            warmup: crate::WarmupConfig::default(),
+            calibration: None,
+            convergence: None,
+            field_dimensions: config.field_dimensions,
+            novelty: None,
+            repair: None,
+            candidate_pipeline: config.candidate_pipeline,
+            diversity: None,
+            directions: crate::ObjectiveDirections::default(),
         };
         let _ = crate::runtime::execute_soft_trace(cfg, params);
     }
 
     let mut total_ms = 0.0f64;
     let mut lambda_final = 0.0f64;
+    let mut ranking_cache = crate::capability::RankingCacheStats::default();
+    let mut stage_throughput = crate::PipelineThroughput::default();
     for i in 0..iterations {
         let cfg = crate::TraceRunConfig {
             depth: config.depth,
@@ -43,11 +73,23 @@ pub fn run_baseline_off_soft(
             adaptive_alpha: false,
             hv_guided: false,
             raw_output_path: None,
+            normalization: crate::NormalizationConfig::default(),
+            warmup: crate::WarmupConfig::default(),
+            calibration: None,
+            convergence: None,
+            field_dimensions: config.field_dimensions,
+            novelty: None,
+            repair: None,
+            candidate_pipeline: config.candidate_pipeline,
+            diversity: None,
+            directions: crate::ObjectiveDirections::default(),
         };
-        let start = std::time::Instant::now();
-        let rows = crate::runtime::execute_soft_trace(cfg, params);
+        let start = core_types::clock::Stopwatch::start();
+        let result = crate::capability::search::execute_soft_search_core(cfg, params);
         total_ms += start.elapsed().as_secs_f64() * 1000.0;
-        lambda_final += rows.last().map(|r| r.lambda as f64).unwrap_or(0.5);
+        lambda_final += result.trace.last().map(|r| r.lambda as f64).unwrap_or(0.5);
+        ranking_cache.absorb(result.ranking_cache);
+        stage_throughput.absorb(result.stage_throughput);
     }
 
     let denom = iterations as f64;
@@ -64,5 +106,7 @@ pub fn run_baseline_off_soft(
         avg_pareto_us: 0.0,
         avg_lambda_us: 0.0,
         lambda_final: lambda_final / denom,
+        ranking_cache_hit_rate: ranking_cache.hit_rate(),
+        stage_throughput,
     }
 }
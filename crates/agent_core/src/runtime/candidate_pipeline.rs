@@ -0,0 +1,319 @@
+//! Candidate processing within one depth as a staged pipeline:
+//! generate → project → score → normalize, joined by bounded queues.
+//!
+//! Generation, projection and normalization run on worker pools. Scoring
+//! stays on the calling thread and sees candidates in submission order,
+//! because `HybridVM::evaluate` updates recall and mode state as it goes;
+//! the output is therefore identical for every worker configuration.
+
+use std::collections::BTreeMap;
+use std::sync::mpsc::{Receiver, SyncSender, sync_channel};
+use std::sync::{Arc, Mutex};
+use std::thread::{Scope, ScopedJoinHandle};
+
+use core_types::clock::Stopwatch;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum CandidateStage {
+    Generate,
+    Project,
+    Score,
+    Normalize,
+}
+
+impl CandidateStage {
+    pub const ALL: [CandidateStage; 4] = [
+        CandidateStage::Generate,
+        CandidateStage::Project,
+        CandidateStage::Score,
+        CandidateStage::Normalize,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            CandidateStage::Generate => "generate",
+            CandidateStage::Project => "project",
+            CandidateStage::Score => "score",
+            CandidateStage::Normalize => "normalize",
+        }
+    }
+}
+
+/// Worker counts for the parallel stages. Scoring always has one worker;
+/// see the module docs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CandidatePipelineConfig {
+    pub generate_workers: usize,
+    pub project_workers: usize,
+    pub normalize_workers: usize,
+    /// Capacity of each queue between stages.
+    pub queue_capacity: usize,
+    /// Evaluate only the first of candidates that differ in nothing but
+    /// node ids and rule-derived attribute names; see
+    /// `capability::equivalence`. Off in `serial` and `parallel`, which
+    /// keeps traces as they were before merging existed.
+    pub merge_equivalent: bool,
+}
+
+pub const DEFAULT_QUEUE_CAPACITY: usize = 64;
+
+impl CandidatePipelineConfig {
+    /// Every stage inline on the calling thread; no threads are spawned.
+    pub fn serial() -> Self {
+        Self {
+            generate_workers: 1,
+            project_workers: 1,
+            normalize_workers: 1,
+            queue_capacity: DEFAULT_QUEUE_CAPACITY,
+            merge_equivalent: false,
+        }
+    }
+
+    /// `workers` threads for each parallel stage.
+    pub fn parallel(workers: usize) -> Self {
+        Self {
+            generate_workers: workers,
+            project_workers: workers,
+            normalize_workers: workers,
+            queue_capacity: DEFAULT_QUEUE_CAPACITY,
+            merge_equivalent: false,
+        }
+    }
+
+    pub fn workers(&self, stage: CandidateStage) -> usize {
+        match stage {
+            CandidateStage::Generate => self.generate_workers.max(1),
+            CandidateStage::Project => self.project_workers.max(1),
+            CandidateStage::Score => 1,
+            CandidateStage::Normalize => self.normalize_workers.max(1),
+        }
+    }
+
+    fn is_serial(&self) -> bool {
+        CandidateStage::ALL.iter().all(|s| self.workers(*s) == 1)
+    }
+}
+
+impl Default for CandidatePipelineConfig {
+    fn default() -> Self {
+        Self::serial()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StageThroughput {
+    pub items: usize,
+    /// Time spent inside the stage, summed over its workers.
+    pub busy_us: f64,
+    pub workers: usize,
+}
+
+impl StageThroughput {
+    /// Items per second with the stage's workers busy concurrently.
+    pub fn items_per_sec(&self) -> f64 {
+        if self.busy_us <= 0.0 {
+            return 0.0;
+        }
+        self.items as f64 * self.workers.max(1) as f64 / (self.busy_us / 1_000_000.0)
+    }
+
+    pub fn absorb(&mut self, other: StageThroughput) {
+        self.items += other.items;
+        self.busy_us += other.busy_us;
+        self.workers = self.workers.max(other.workers);
+    }
+
+    fn record(&mut self, started: Stopwatch) {
+        self.items += 1;
+        self.busy_us += started.elapsed().as_secs_f64() * 1_000_000.0;
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PipelineThroughput {
+    pub generate: StageThroughput,
+    pub project: StageThroughput,
+    pub score: StageThroughput,
+    pub normalize: StageThroughput,
+}
+
+impl PipelineThroughput {
+    pub fn stage(&self, stage: CandidateStage) -> StageThroughput {
+        match stage {
+            CandidateStage::Generate => self.generate,
+            CandidateStage::Project => self.project,
+            CandidateStage::Score => self.score,
+            CandidateStage::Normalize => self.normalize,
+        }
+    }
+
+    pub fn absorb(&mut self, other: PipelineThroughput) {
+        self.generate.absorb(other.generate);
+        self.project.absorb(other.project);
+        self.score.absorb(other.score);
+        self.normalize.absorb(other.normalize);
+    }
+}
+
+/// Runs every job through the four stages and returns the results in job
+/// order.
+pub(crate) fn run_candidate_pipeline<J, A, B, C, D>(
+    jobs: Vec<J>,
+    config: CandidatePipelineConfig,
+    generate: impl Fn(J) -> A + Sync,
+    project: impl Fn(A) -> B + Sync,
+    mut score: impl FnMut(B) -> C,
+    normalize: impl Fn(C) -> D + Sync,
+) -> (Vec<D>, PipelineThroughput)
+where
+    J: Send,
+    A: Send,
+    B: Send,
+    C: Send,
+    D: Send,
+{
+    let single = StageThroughput {
+        workers: 1,
+        ..StageThroughput::default()
+    };
+    let mut throughput = PipelineThroughput {
+        generate: single,
+        project: single,
+        score: single,
+        normalize: single,
+    };
+
+    if config.is_serial() || jobs.len() <= 1 {
+        let mut out = Vec::with_capacity(jobs.len());
+        for job in jobs {
+            let t = Stopwatch::start();
+            let a = generate(job);
+            throughput.generate.record(t);
+            let t = Stopwatch::start();
+            let b = project(a);
+            throughput.project.record(t);
+            let t = Stopwatch::start();
+            let c = score(b);
+            throughput.score.record(t);
+            let t = Stopwatch::start();
+            out.push(normalize(c));
+            throughput.normalize.record(t);
+        }
+        return (out, throughput);
+    }
+
+    let capacity = config.queue_capacity.max(1);
+    let mut indexed = std::thread::scope(|scope| {
+        let (job_tx, job_rx) = sync_channel::<(usize, J)>(capacity);
+        let (generated_tx, generated_rx) = sync_channel::<(usize, A)>(capacity);
+        let (projected_tx, projected_rx) = sync_channel::<(usize, B)>(capacity);
+        let (scored_tx, scored_rx) = sync_channel::<(usize, C)>(capacity);
+        let (out_tx, out_rx) = sync_channel::<(usize, D)>(capacity);
+
+        scope.spawn(move || {
+            for job in jobs.into_iter().enumerate() {
+                if job_tx.send(job).is_err() {
+                    break;
+                }
+            }
+        });
+        let generators = spawn_stage(
+            scope,
+            config.workers(CandidateStage::Generate),
+            job_rx,
+            generated_tx,
+            &generate,
+        );
+        let projectors = spawn_stage(
+            scope,
+            config.workers(CandidateStage::Project),
+            generated_rx,
+            projected_tx,
+            &project,
+        );
+        let normalizers = spawn_stage(
+            scope,
+            config.workers(CandidateStage::Normalize),
+            scored_rx,
+            out_tx,
+            &normalize,
+        );
+        let collector = scope.spawn(move || out_rx.into_iter().collect::<Vec<_>>());
+
+        // Projection finishes out of order; score strictly by job index.
+        let mut pending = BTreeMap::new();
+        let mut next = 0usize;
+        for (idx, item) in projected_rx {
+            pending.insert(idx, item);
+            while let Some(item) = pending.remove(&next) {
+                let t = Stopwatch::start();
+                let scored = score(item);
+                throughput.score.record(t);
+                if scored_tx.send((next, scored)).is_err() {
+                    break;
+                }
+                next += 1;
+            }
+        }
+        drop(scored_tx);
+
+        throughput.generate = join_stage(generators);
+        throughput.project = join_stage(projectors);
+        throughput.normalize = join_stage(normalizers);
+        collector
+            .join()
+            .expect("candidate pipeline collector panicked")
+    });
+    indexed.sort_by_key(|(idx, _)| *idx);
+    (indexed.into_iter().map(|(_, d)| d).collect(), throughput)
+}
+
+fn spawn_stage<'scope, 'env, I, O, F>(
+    scope: &'scope Scope<'scope, 'env>,
+    workers: usize,
+    input: Receiver<(usize, I)>,
+    output: SyncSender<(usize, O)>,
+    f: &'scope F,
+) -> Vec<ScopedJoinHandle<'scope, StageThroughput>>
+where
+    I: Send + 'scope,
+    O: Send + 'scope,
+    F: Fn(I) -> O + Sync,
+{
+    let input = Arc::new(Mutex::new(input));
+    (0..workers)
+        .map(|_| {
+            let input = Arc::clone(&input);
+            let output = output.clone();
+            scope.spawn(move || {
+                let mut stats = StageThroughput::default();
+                loop {
+                    let next = input
+                        .lock()
+                        .expect("candidate pipeline queue poisoned")
+                        .recv();
+                    let Ok((idx, item)) = next else {
+                        break;
+                    };
+                    let t = Stopwatch::start();
+                    let out = f(item);
+                    stats.record(t);
+                    if output.send((idx, out)).is_err() {
+                        break;
+                    }
+                }
+                stats
+            })
+        })
+        .collect()
+}
+
+fn join_stage(handles: Vec<ScopedJoinHandle<'_, StageThroughput>>) -> StageThroughput {
+    let workers = handles.len();
+    let mut total = StageThroughput::default();
+    for handle in handles {
+        total.absorb(handle.join().expect("candidate pipeline worker panicked"));
+    }
+    total.workers = workers;
+    total
+}
@@ -1,14 +1,27 @@
 pub mod bench;
+pub mod candidate_pipeline;
 pub mod dispatcher;
 pub mod lifecycle;
 pub mod orchestrator;
 pub mod phase1;
 pub mod registry;
+pub mod semantic_bench;
+pub mod sweep;
 pub mod trace;
+pub mod trace_columns;
+pub mod trace_diff;
 pub(crate) mod trace_helpers;
+pub mod trace_join;
 
 pub use dispatcher::Dispatcher;
 pub use lifecycle::{AgentLifecycle, NoopLifecycle};
-pub use orchestrator::{Orchestrator, execute_soft_trace};
+pub use orchestrator::{Orchestrator, execute_soft_trace, execute_soft_trace_calibrated};
 pub use registry::AgentRegistry;
 pub use trace::{execute_trace, execute_trace_baseline_off, execute_trace_baseline_off_balanced};
+#[cfg(feature = "compression")]
+pub use trace_columns::CompressedTrace;
+pub use trace_columns::{ColumnKind, ColumnValues, ColumnarTrace};
+pub use trace_diff::{
+    TraceDiffConfig, TraceDiffReport, TraceDiffVerdict, trace_diff, trace_diff_with,
+};
+pub use trace_join::{CorrelatedTraces, TimelineEntry};
@@ -91,6 +91,15 @@ pub fn execute_soft_trace(
     config: crate::TraceRunConfig,
     params: crate::SoftTraceParams,
 ) -> Vec<crate::TraceRow> {
+    execute_soft_trace_calibrated(config, params).0
+}
+
+/// Like `execute_soft_trace`, also returning the frozen warm-up statistics
+/// for `TraceRunConfig::calibration` of a later run.
+pub fn execute_soft_trace_calibrated(
+    config: crate::TraceRunConfig,
+    params: crate::SoftTraceParams,
+) -> (Vec<crate::TraceRow>, Option<crate::GlobalRobustStats>) {
     let result = crate::capability::search::execute_soft_search_core(config, params);
     for event in result.events {
         if let AgentEvent::WriteRawObjectives {
@@ -106,5 +115,5 @@ pub fn execute_soft_trace(
             );
         }
     }
-    result.trace
+    (result.trace, result.calibration)
 }
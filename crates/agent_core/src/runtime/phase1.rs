@@ -37,7 +37,7 @@ fn run_phase1_variant(
     const HV_STOP_EPS: f64 = 1e-6;
     let shm = hybrid_vm::HybridVM::default_shm();
     let chm = crate::runtime::trace_helpers::make_dense_trace_chm(&shm, config.seed);
-    let field = field_engine::FieldEngine::new(256);
+    let field = field_engine::FieldEngine::new(crate::DEFAULT_FIELD_DIMENSIONS);
     let mut hybrid_vm =
         match hybrid_vm::HybridVM::with_default_memory(hybrid_vm::StructuralEvaluator::default()) {
             Ok(vm) => vm,
@@ -47,12 +47,7 @@ fn run_phase1_variant(
         config.seed,
     )];
     let mut lambda = 0.5f64;
-    let mut field_cache: std::collections::BTreeMap<
-        (u128, u128, usize, usize),
-        field_engine::FieldVector,
-    > = std::collections::BTreeMap::new();
-    let mut field_cache_order: std::collections::VecDeque<(u128, u128, usize, usize)> =
-        std::collections::VecDeque::new();
+    let mut field_cache = crate::runtime::trace_helpers::FieldCache::default();
     let mut raw_rows = Vec::new();
     let mut summary_rows = Vec::new();
     let mut delta_hv_window = std::collections::VecDeque::<f64>::new();
@@ -84,15 +79,11 @@ fn run_phase1_variant(
                             .to_string(),
                     )
                     .or_insert(0) += 1;
-                let new_state = crate::apply_atomic(rule, state);
+                let Ok(new_state) = crate::apply_atomic(rule, state) else {
+                    continue;
+                };
                 let key = (new_state.id.as_u128(), rule.id.as_u128(), depth, state_idx);
-                let _ = crate::runtime::trace_helpers::bounded_cache_get_or_insert(
-                    &mut field_cache,
-                    &mut field_cache_order,
-                    key,
-                    || field.aggregate_state(&new_state),
-                )
-                .0;
+                let _ = field_cache.get_or_insert(key, &field, &new_state);
                 let obj = hybrid_vm.evaluate(&new_state);
                 let obj = match variant {
                     crate::Phase1Variant::Base => obj.clamped(),
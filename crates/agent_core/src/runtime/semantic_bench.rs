@@ -0,0 +1,261 @@
+//! Latency benchmarks for the semantic layer: `analyze_text`, L2 rebuilds
+//! as L1 grows, recall as the concept store grows, and snapshot/diff.
+//!
+//! The search benchmark in `bench` never touches these paths, so without
+//! this a regression in clustering or recall only shows up as a slow GUI.
+//! Inputs are synthetic and seeded: the same config measures the same work.
+
+use core_types::clock::Stopwatch;
+use hybrid_vm::{HybridVM, StructuralEvaluator};
+use memory_store::InMemoryStore;
+use semantic_dhm::{
+    ConceptId, ConceptQuery, ConceptUnit, D_SEM, D_STRUCT, L1Id, MeaningLayerState,
+    RequirementRole, SemanticDhm, SemanticL1Dhm, SemanticUnitL1, SemanticUnitL1Input, Snapshotable,
+};
+
+use crate::capability::rng::RngStream;
+
+type L1Store = SemanticL1Dhm<InMemoryStore<L1Id, SemanticUnitL1>>;
+type L2Store = SemanticDhm<InMemoryStore<ConceptId, ConceptUnit>>;
+
+/// Requirement sentences `analyze_text` is timed on, cycled through by
+/// sample index.
+const CORPUS: [&str; 6] = [
+    "応答時間を200ms以下に短縮する",
+    "クラウド依存は禁止。オンプレミスで運用する",
+    "決済APIの信頼性を向上させる",
+    "メモリ使用量を512MB以下に抑える",
+    "監査ログを90日間保持する",
+    "高速なAPI。ただしコストは抑える",
+];
+
+/// Number of centers synthetic L1 vectors are drawn around, so clustering
+/// sees real groups instead of only singletons.
+const L1_CLUSTERS: usize = 8;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SemanticBenchConfig {
+    pub iterations: usize,
+    pub warmup: usize,
+    pub seed: u64,
+    /// L1 unit counts `rebuild_l2` is measured at.
+    pub l1_counts: Vec<usize>,
+    /// Concept store sizes `recall` is measured at.
+    pub store_sizes: Vec<usize>,
+    pub recall_top_k: usize,
+}
+
+impl Default for SemanticBenchConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 10,
+            warmup: 2,
+            seed: 42,
+            l1_counts: vec![16, 64, 256],
+            store_sizes: vec![100, 1_000, 10_000],
+            recall_top_k: 10,
+        }
+    }
+}
+
+/// Summary of one operation's timed samples.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LatencyStats {
+    pub samples: usize,
+    pub avg_us: f64,
+    pub min_us: f64,
+    pub p50_us: f64,
+    pub p95_us: f64,
+    pub max_us: f64,
+}
+
+impl LatencyStats {
+    pub fn from_samples(samples_us: &[f64]) -> Self {
+        if samples_us.is_empty() {
+            return Self::default();
+        }
+        let mut sorted = samples_us.to_vec();
+        sorted.sort_by(f64::total_cmp);
+        let n = sorted.len();
+        let rank = |q: f64| sorted[((n - 1) as f64 * q).round() as usize];
+        Self {
+            samples: n,
+            avg_us: sorted.iter().sum::<f64>() / n as f64,
+            min_us: sorted[0],
+            p50_us: rank(0.5),
+            p95_us: rank(0.95),
+            max_us: sorted[n - 1],
+        }
+    }
+}
+
+/// Latency at one input size.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScalingPoint {
+    pub size: usize,
+    pub latency: LatencyStats,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SemanticBenchResult {
+    pub iterations: usize,
+    /// One text on a fresh in-memory VM per sample.
+    pub analyze_text: LatencyStats,
+    /// By L1 unit count.
+    pub rebuild_l2: Vec<ScalingPoint>,
+    /// By concept store size.
+    pub recall: Vec<ScalingPoint>,
+    /// At the largest L1 count.
+    pub snapshot: LatencyStats,
+    /// Between the largest-L1 snapshot and one taken after dropping a unit.
+    pub snapshot_diff: LatencyStats,
+}
+
+pub fn run(config: &SemanticBenchConfig) -> SemanticBenchResult {
+    let iterations = config.iterations.max(1);
+    let largest = config.l1_counts.iter().copied().max().unwrap_or(0);
+    let (snapshot, snapshot_diff) = measure_snapshots(config, largest, iterations);
+    SemanticBenchResult {
+        iterations,
+        analyze_text: measure(config.warmup, iterations, |i| {
+            let mut vm = HybridVM::in_memory(StructuralEvaluator::default()).expect("vm");
+            let text = CORPUS[i % CORPUS.len()];
+            let start = Stopwatch::start();
+            vm.analyze_text(text).expect("analyze_text");
+            elapsed_us(start)
+        }),
+        rebuild_l2: config
+            .l1_counts
+            .iter()
+            .map(|&size| {
+                let l1 = l1_store(config.seed, size);
+                let units = l1.all_units();
+                let latency = measure(config.warmup, iterations, |_| {
+                    let mut dhm = SemanticDhm::in_memory().expect("dhm");
+                    let start = Stopwatch::start();
+                    dhm.rebuild_l2_from_l1(&units).expect("rebuild");
+                    elapsed_us(start)
+                });
+                ScalingPoint { size, latency }
+            })
+            .collect(),
+        recall: config
+            .store_sizes
+            .iter()
+            .map(|&size| {
+                let mut dhm = SemanticDhm::in_memory().expect("dhm");
+                let queries = (0..size)
+                    .map(|i| query(config.seed, i as u128))
+                    .collect::<Vec<_>>();
+                dhm.insert_query_batch(&queries).expect("populate");
+                let latency = measure(config.warmup, iterations, |i| {
+                    let probe = query(config.seed ^ 0x5eed, i as u128);
+                    let start = Stopwatch::start();
+                    std::hint::black_box(dhm.recall(&probe, config.recall_top_k));
+                    elapsed_us(start)
+                });
+                ScalingPoint { size, latency }
+            })
+            .collect(),
+        snapshot,
+        snapshot_diff,
+    }
+}
+
+fn measure_snapshots(
+    config: &SemanticBenchConfig,
+    l1_count: usize,
+    iterations: usize,
+) -> (LatencyStats, LatencyStats) {
+    let mut l1 = l1_store(config.seed, l1_count);
+    let mut dhm = SemanticDhm::in_memory().expect("dhm");
+    let before = layer_state(&l1, &mut dhm);
+    if let Some(unit) = l1.all_units().first() {
+        l1.remove(unit.id).expect("remove l1");
+    }
+    let after = layer_state(&l1, &mut dhm);
+
+    let snapshot = measure(config.warmup, iterations, |_| {
+        let start = Stopwatch::start();
+        std::hint::black_box(before.snapshot());
+        elapsed_us(start)
+    });
+    let (left, right) = (before.snapshot(), after.snapshot());
+    let diff = measure(config.warmup, iterations, |_| {
+        let start = Stopwatch::start();
+        std::hint::black_box(semantic_dhm::compare_snapshots(&left, &right).expect("diff"));
+        elapsed_us(start)
+    });
+    (snapshot, diff)
+}
+
+fn layer_state(l1: &L1Store, dhm: &mut L2Store) -> MeaningLayerState {
+    let l1_units = l1.all_units();
+    dhm.rebuild_l2_from_l1(&l1_units).expect("rebuild");
+    MeaningLayerState {
+        algorithm_version: dhm.l2_config().algorithm_version,
+        strategy: dhm.l2_config().strategy,
+        l1_units,
+        l2_units: dhm.all_concepts(),
+    }
+}
+
+/// Runs `sample` `warmup` times unrecorded, then `iterations` times, and
+/// summarizes the microseconds it reports.
+fn measure(warmup: usize, iterations: usize, mut sample: impl FnMut(usize) -> f64) -> LatencyStats {
+    for i in 0..warmup {
+        sample(i);
+    }
+    let samples = (0..iterations)
+        .map(|i| sample(warmup + i))
+        .collect::<Vec<_>>();
+    LatencyStats::from_samples(&samples)
+}
+
+fn elapsed_us(start: Stopwatch) -> f64 {
+    start.elapsed().as_secs_f64() * 1_000_000.0
+}
+
+fn l1_store(seed: u64, count: usize) -> L1Store {
+    let mut l1 = SemanticL1Dhm::in_memory().expect("l1");
+    let inputs = (0..count)
+        .map(|i| {
+            let center = RngStream::new(seed, 0, (i % L1_CLUSTERS) as u128);
+            let mut noise = RngStream::new(seed, 1, i as u128);
+            SemanticUnitL1Input {
+                role: if i.is_multiple_of(3) {
+                    RequirementRole::Constraint
+                } else {
+                    RequirementRole::Goal
+                },
+                polarity: 1,
+                abstraction: noise.next_f64() as f32,
+                vector: random_vector(center, D_SEM)
+                    .into_iter()
+                    .map(|v| v + 0.1 * (noise.next_f64() as f32 - 0.5))
+                    .collect(),
+                source_text: format!("requirement {i}"),
+                role_confidence: 1.0,
+                abstraction_confidence: 1.0,
+            }
+        })
+        .collect::<Vec<_>>();
+    l1.insert_batch(&inputs).expect("populate l1");
+    l1
+}
+
+fn query(seed: u64, item: u128) -> ConceptQuery {
+    let mut rng = RngStream::new(seed, 2, item);
+    ConceptQuery {
+        v: random_vector(rng.clone(), D_SEM),
+        a: rng.next_f64() as f32,
+        s: random_vector(rng.for_item(1), D_STRUCT),
+        polarity: 1,
+    }
+}
+
+fn random_vector(mut rng: RngStream, dim: usize) -> Vec<f32> {
+    (0..dim)
+        .map(|_| rng.next_f64() as f32 * 2.0 - 1.0)
+        .collect()
+}
@@ -0,0 +1,280 @@
+//! Parameter sweeps over soft-trace runs.
+//!
+//! Each point of a grid (or random sample) of parameter values runs
+//! `runs_per_point` traces with consecutive seeds; points are ranked by final
+//! hypervolume, then collapse rate, then speed.
+
+use crate::capability::rule_sampling::SplitMix64;
+use crate::{SoftTraceParams, TraceRunConfig};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SweepParam {
+    /// `TraceRunConfig::norm_alpha`.
+    NormAlpha,
+    Alpha,
+    Temperature,
+    EntropyBeta,
+    LambdaMin,
+    LambdaTargetEntropy,
+    LambdaK,
+    LambdaEma,
+}
+
+impl SweepParam {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::NormAlpha => "norm_alpha",
+            Self::Alpha => "alpha",
+            Self::Temperature => "temperature",
+            Self::EntropyBeta => "entropy_beta",
+            Self::LambdaMin => "lambda_min",
+            Self::LambdaTargetEntropy => "lambda_target_entropy",
+            Self::LambdaK => "lambda_k",
+            Self::LambdaEma => "lambda_ema",
+        }
+    }
+
+    fn apply(self, config: &mut TraceRunConfig, params: &mut SoftTraceParams, value: f64) {
+        match self {
+            Self::NormAlpha => config.norm_alpha = value,
+            Self::Alpha => params.alpha = value,
+            Self::Temperature => params.temperature = value,
+            Self::EntropyBeta => params.entropy_beta = value,
+            Self::LambdaMin => params.lambda_min = value,
+            Self::LambdaTargetEntropy => params.lambda_target_entropy = value,
+            Self::LambdaK => params.lambda_k = value,
+            Self::LambdaEma => params.lambda_ema = value,
+        }
+    }
+}
+
+/// `steps` evenly spaced grid values from `min` to `max`; random search
+/// samples uniformly from the same interval.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ParamRange {
+    pub param: SweepParam,
+    pub min: f64,
+    pub max: f64,
+    pub steps: usize,
+}
+
+impl ParamRange {
+    pub fn grid_values(&self) -> Vec<f64> {
+        match self.steps {
+            0 => Vec::new(),
+            1 => vec![self.min],
+            n => (0..n)
+                .map(|i| self.min + (self.max - self.min) * i as f64 / (n - 1) as f64)
+                .collect(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SweepStrategy {
+    /// Every combination of `ParamRange::grid_values`.
+    Grid,
+    Random {
+        samples: usize,
+        seed: u64,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SweepConfig {
+    pub base: TraceRunConfig,
+    pub params: SoftTraceParams,
+    pub ranges: Vec<ParamRange>,
+    pub strategy: SweepStrategy,
+    /// Traces per point, seeded `base.seed`, `base.seed + 1`, ...
+    pub runs_per_point: usize,
+    /// Worker threads; timings are per run, so contention inflates them.
+    pub parallelism: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SweepOutcome {
+    /// In `SweepConfig::ranges` order.
+    pub values: Vec<(SweepParam, f64)>,
+    /// Mean `pareto_hv_2d` of the last trace row.
+    pub final_hv: f64,
+    /// Mean share of rows with `collapse_flag`.
+    pub collapse_rate: f64,
+    pub avg_per_depth_ms: f64,
+    pub runs: usize,
+}
+
+/// Best first.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SweepReport {
+    pub outcomes: Vec<SweepOutcome>,
+}
+
+#[cfg(feature = "serde")]
+impl core_types::SchemaVersioned for SweepReport {
+    const KIND: &'static str = "sweep_report";
+    const VERSION: u32 = 1;
+}
+
+impl SweepReport {
+    pub fn best(&self) -> Option<&SweepOutcome> {
+        self.outcomes.first()
+    }
+
+    /// Header `rank,<param names>,final_hv,collapse_rate,avg_per_depth_ms,runs`.
+    pub fn to_csv(&self) -> String {
+        let mut header = vec!["rank".to_string()];
+        if let Some(first) = self.outcomes.first() {
+            header.extend(first.values.iter().map(|(p, _)| p.name().to_string()));
+        }
+        header
+            .extend(["final_hv", "collapse_rate", "avg_per_depth_ms", "runs"].map(str::to_string));
+        let mut out = header.join(",");
+        out.push('\n');
+        for (rank, outcome) in self.outcomes.iter().enumerate() {
+            let mut fields = vec![(rank + 1).to_string()];
+            fields.extend(outcome.values.iter().map(|(_, v)| v.to_string()));
+            fields.push(outcome.final_hv.to_string());
+            fields.push(outcome.collapse_rate.to_string());
+            fields.push(outcome.avg_per_depth_ms.to_string());
+            fields.push(outcome.runs.to_string());
+            out.push_str(&fields.join(","));
+            out.push('\n');
+        }
+        out
+    }
+
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&core_types::Versioned::new(self.clone()))
+    }
+}
+
+#[derive(Clone, Copy, Default)]
+struct RunMetrics {
+    final_hv: f64,
+    collapse_rate: f64,
+    per_depth_ms: f64,
+}
+
+pub fn run_sweep(config: &SweepConfig) -> SweepReport {
+    let points = sweep_points(&config.ranges, config.strategy);
+    let runs = config.runs_per_point.max(1);
+    let jobs = (0..points.len())
+        .flat_map(|p| (0..runs).map(move |r| (p, r)))
+        .collect::<Vec<_>>();
+    let run_job = |&(p, r): &(usize, usize)| {
+        let mut trace = config.base.clone();
+        let mut params = config.params;
+        for (range, value) in config.ranges.iter().zip(&points[p]) {
+            range.param.apply(&mut trace, &mut params, *value);
+        }
+        trace.seed = trace.seed.wrapping_add(r as u64);
+        run_once(trace, params)
+    };
+
+    let workers = config.parallelism.clamp(1, jobs.len().max(1));
+    let metrics = if workers == 1 {
+        jobs.iter().map(run_job).collect::<Vec<_>>()
+    } else {
+        let mut metrics = vec![RunMetrics::default(); jobs.len()];
+        std::thread::scope(|scope| {
+            let handles = (0..workers)
+                .map(|w| {
+                    let jobs = &jobs;
+                    let run_job = &run_job;
+                    scope.spawn(move || {
+                        (w..jobs.len())
+                            .step_by(workers)
+                            .map(|i| (i, run_job(&jobs[i])))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            for handle in handles {
+                for (i, m) in handle.join().expect("sweep worker panicked") {
+                    metrics[i] = m;
+                }
+            }
+        });
+        metrics
+    };
+
+    let mut outcomes = points
+        .iter()
+        .enumerate()
+        .map(|(p, values)| {
+            let mine = jobs
+                .iter()
+                .zip(&metrics)
+                .filter(|((point, _), _)| *point == p)
+                .map(|(_, m)| *m)
+                .collect::<Vec<_>>();
+            let n = mine.len().max(1) as f64;
+            SweepOutcome {
+                values: config
+                    .ranges
+                    .iter()
+                    .map(|r| r.param)
+                    .zip(values.iter().copied())
+                    .collect(),
+                final_hv: mine.iter().map(|m| m.final_hv).sum::<f64>() / n,
+                collapse_rate: mine.iter().map(|m| m.collapse_rate).sum::<f64>() / n,
+                avg_per_depth_ms: mine.iter().map(|m| m.per_depth_ms).sum::<f64>() / n,
+                runs: mine.len(),
+            }
+        })
+        .collect::<Vec<_>>();
+    outcomes.sort_by(|a, b| {
+        b.final_hv
+            .total_cmp(&a.final_hv)
+            .then(a.collapse_rate.total_cmp(&b.collapse_rate))
+            .then(a.avg_per_depth_ms.total_cmp(&b.avg_per_depth_ms))
+    });
+    SweepReport { outcomes }
+}
+
+fn run_once(config: TraceRunConfig, params: SoftTraceParams) -> RunMetrics {
+    let start = core_types::clock::Stopwatch::start();
+    let rows = crate::runtime::execute_soft_trace(config, params);
+    let elapsed_ms = start.elapsed().as_secs_f64() * 1000.0;
+    let depths = rows.len().max(1) as f64;
+    RunMetrics {
+        final_hv: rows.last().map_or(0.0, |r| r.pareto_hv_2d as f64),
+        collapse_rate: rows.iter().filter(|r| r.collapse_flag).count() as f64 / depths,
+        per_depth_ms: elapsed_ms / depths,
+    }
+}
+
+fn sweep_points(ranges: &[ParamRange], strategy: SweepStrategy) -> Vec<Vec<f64>> {
+    match strategy {
+        SweepStrategy::Grid => ranges.iter().fold(vec![Vec::new()], |points, range| {
+            points
+                .iter()
+                .flat_map(|prefix| {
+                    range.grid_values().into_iter().map(move |v| {
+                        let mut point = prefix.clone();
+                        point.push(v);
+                        point
+                    })
+                })
+                .collect()
+        }),
+        SweepStrategy::Random { samples, seed } => {
+            let mut rng = SplitMix64::from_seed(seed);
+            (0..samples)
+                .map(|_| {
+                    ranges
+                        .iter()
+                        .map(|r| r.min + (r.max - r.min) * rng.next_f64())
+                        .collect()
+                })
+                .collect()
+        }
+    }
+}
@@ -0,0 +1,981 @@
+//! Compact storage for long trace runs.
+//!
+//! `ColumnarTrace` keeps one byte buffer per `TraceRow` field. Floats are
+//! stored as the XOR of consecutive bit patterns and integers as zigzag
+//! deltas, both as LEB128 varints, so metrics that stay flat across depths
+//! cost one byte per row. Text columns only spell out a value when it
+//! changes. Columns are decoded on demand, one at a time.
+//!
+//! With the `compression` feature, `CompressedTrace` additionally zstd-packs
+//! every column on its own so a saved trace can be reopened and a single
+//! metric plotted without inflating the rest.
+
+use std::io;
+
+use crate::TraceRow;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColumnKind {
+    F32,
+    UInt,
+    Bool,
+    Text,
+}
+
+/// One decoded column.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ColumnValues {
+    F32(Vec<f32>),
+    UInt(Vec<usize>),
+    Bool(Vec<bool>),
+    Text(Vec<String>),
+}
+
+impl ColumnValues {
+    /// Numeric view for plotting; `None` for text columns.
+    pub fn to_f64(&self) -> Option<Vec<f64>> {
+        match self {
+            Self::F32(values) => Some(values.iter().map(|v| f64::from(*v)).collect()),
+            Self::UInt(values) => Some(values.iter().map(|v| *v as f64).collect()),
+            Self::Bool(values) => Some(values.iter().map(|v| f64::from(u8::from(*v))).collect()),
+            Self::Text(_) => None,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Self::F32(values) => values.len(),
+            Self::UInt(values) => values.len(),
+            Self::Bool(values) => values.len(),
+            Self::Text(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Accessor {
+    F32(fn(&TraceRow) -> f32, fn(&mut TraceRow, f32)),
+    UInt(fn(&TraceRow) -> usize, fn(&mut TraceRow, usize)),
+    Bool(fn(&TraceRow) -> bool, fn(&mut TraceRow, bool)),
+    Text(fn(&TraceRow) -> &str, fn(&mut TraceRow, String)),
+}
+
+impl Accessor {
+    fn kind(self) -> ColumnKind {
+        match self {
+            Self::F32(..) => ColumnKind::F32,
+            Self::UInt(..) => ColumnKind::UInt,
+            Self::Bool(..) => ColumnKind::Bool,
+            Self::Text(..) => ColumnKind::Text,
+        }
+    }
+}
+
+/// Every `TraceRow` field, in declaration order.
+const COLUMNS: &[(&str, Accessor)] = &[
+    ("depth", Accessor::UInt(|r| r.depth, |r, v| r.depth = v)),
+    ("lambda", Accessor::F32(|r| r.lambda, |r, v| r.lambda = v)),
+    (
+        "delta_lambda",
+        Accessor::F32(|r| r.delta_lambda, |r, v| r.delta_lambda = v),
+    ),
+    (
+        "tau_prime",
+        Accessor::F32(|r| r.tau_prime, |r, v| r.tau_prime = v),
+    ),
+    (
+        "conf_chm",
+        Accessor::F32(|r| r.conf_chm, |r, v| r.conf_chm = v),
+    ),
+    (
+        "density",
+        Accessor::F32(|r| r.density, |r, v| r.density = v),
+    ),
+    ("k", Accessor::UInt(|r| r.k, |r, v| r.k = v)),
+    (
+        "h_profile",
+        Accessor::F32(|r| r.h_profile, |r, v| r.h_profile = v),
+    ),
+    (
+        "pareto_size",
+        Accessor::UInt(|r| r.pareto_size, |r, v| r.pareto_size = v),
+    ),
+    (
+        "diversity",
+        Accessor::F32(|r| r.diversity, |r, v| r.diversity = v),
+    ),
+    (
+        "resonance_avg",
+        Accessor::F32(|r| r.resonance_avg, |r, v| r.resonance_avg = v),
+    ),
+    (
+        "pressure",
+        Accessor::F32(|r| r.pressure, |r, v| r.pressure = v),
+    ),
+    (
+        "epsilon_effect",
+        Accessor::F32(|r| r.epsilon_effect, |r, v| r.epsilon_effect = v),
+    ),
+    (
+        "target_local_weight",
+        Accessor::F32(|r| r.target_local_weight, |r, v| r.target_local_weight = v),
+    ),
+    (
+        "target_global_weight",
+        Accessor::F32(
+            |r| r.target_global_weight,
+            |r, v| r.target_global_weight = v,
+        ),
+    ),
+    (
+        "local_global_distance",
+        Accessor::F32(
+            |r| r.local_global_distance,
+            |r, v| r.local_global_distance = v,
+        ),
+    ),
+    (
+        "field_min_distance",
+        Accessor::F32(|r| r.field_min_distance, |r, v| r.field_min_distance = v),
+    ),
+    (
+        "field_rejected_count",
+        Accessor::UInt(
+            |r| r.field_rejected_count,
+            |r, v| r.field_rejected_count = v,
+        ),
+    ),
+    ("mu", Accessor::F32(|r| r.mu, |r, v| r.mu = v)),
+    ("dhm_k", Accessor::UInt(|r| r.dhm_k, |r, v| r.dhm_k = v)),
+    (
+        "dhm_norm",
+        Accessor::F32(|r| r.dhm_norm, |r, v| r.dhm_norm = v),
+    ),
+    (
+        "dhm_resonance_mean",
+        Accessor::F32(|r| r.dhm_resonance_mean, |r, v| r.dhm_resonance_mean = v),
+    ),
+    (
+        "dhm_score_ratio",
+        Accessor::F32(|r| r.dhm_score_ratio, |r, v| r.dhm_score_ratio = v),
+    ),
+    (
+        "dhm_build_us",
+        Accessor::F32(|r| r.dhm_build_us, |r, v| r.dhm_build_us = v),
+    ),
+    (
+        "expanded_categories_count",
+        Accessor::UInt(
+            |r| r.expanded_categories_count,
+            |r, v| r.expanded_categories_count = v,
+        ),
+    ),
+    (
+        "selected_rules_count",
+        Accessor::UInt(
+            |r| r.selected_rules_count,
+            |r, v| r.selected_rules_count = v,
+        ),
+    ),
+    (
+        "per_category_selected",
+        Accessor::Text(
+            |r| r.per_category_selected.as_str(),
+            |r, v| r.per_category_selected = v,
+        ),
+    ),
+    (
+        "entropy_per_depth",
+        Accessor::F32(|r| r.entropy_per_depth, |r, v| r.entropy_per_depth = v),
+    ),
+    (
+        "unique_category_count_per_depth",
+        Accessor::UInt(
+            |r| r.unique_category_count_per_depth,
+            |r, v| r.unique_category_count_per_depth = v,
+        ),
+    ),
+    (
+        "pareto_front_size_per_depth",
+        Accessor::UInt(
+            |r| r.pareto_front_size_per_depth,
+            |r, v| r.pareto_front_size_per_depth = v,
+        ),
+    ),
+    (
+        "pareto_mean_nn_dist",
+        Accessor::F32(|r| r.pareto_mean_nn_dist, |r, v| r.pareto_mean_nn_dist = v),
+    ),
+    (
+        "pareto_spacing",
+        Accessor::F32(|r| r.pareto_spacing, |r, v| r.pareto_spacing = v),
+    ),
+    (
+        "pareto_hv_2d",
+        Accessor::F32(|r| r.pareto_hv_2d, |r, v| r.pareto_hv_2d = v),
+    ),
+    (
+        "field_extract_us",
+        Accessor::F32(|r| r.field_extract_us, |r, v| r.field_extract_us = v),
+    ),
+    (
+        "field_score_us",
+        Accessor::F32(|r| r.field_score_us, |r, v| r.field_score_us = v),
+    ),
+    (
+        "field_aggregate_us",
+        Accessor::F32(|r| r.field_aggregate_us, |r, v| r.field_aggregate_us = v),
+    ),
+    (
+        "field_total_us",
+        Accessor::F32(|r| r.field_total_us, |r, v| r.field_total_us = v),
+    ),
+    (
+        "norm_median_0",
+        Accessor::F32(|r| r.norm_median_0, |r, v| r.norm_median_0 = v),
+    ),
+    (
+        "norm_median_1",
+        Accessor::F32(|r| r.norm_median_1, |r, v| r.norm_median_1 = v),
+    ),
+    (
+        "norm_median_2",
+        Accessor::F32(|r| r.norm_median_2, |r, v| r.norm_median_2 = v),
+    ),
+    (
+        "norm_median_3",
+        Accessor::F32(|r| r.norm_median_3, |r, v| r.norm_median_3 = v),
+    ),
+    (
+        "norm_mad_0",
+        Accessor::F32(|r| r.norm_mad_0, |r, v| r.norm_mad_0 = v),
+    ),
+    (
+        "norm_mad_1",
+        Accessor::F32(|r| r.norm_mad_1, |r, v| r.norm_mad_1 = v),
+    ),
+    (
+        "norm_mad_2",
+        Accessor::F32(|r| r.norm_mad_2, |r, v| r.norm_mad_2 = v),
+    ),
+    (
+        "norm_mad_3",
+        Accessor::F32(|r| r.norm_mad_3, |r, v| r.norm_mad_3 = v),
+    ),
+    (
+        "median_nn_dist_all_depth",
+        Accessor::F32(
+            |r| r.median_nn_dist_all_depth,
+            |r, v| r.median_nn_dist_all_depth = v,
+        ),
+    ),
+    (
+        "collapse_flag",
+        Accessor::Bool(|r| r.collapse_flag, |r, v| r.collapse_flag = v),
+    ),
+    (
+        "normalization_mode",
+        Accessor::Text(
+            |r| r.normalization_mode.as_str(),
+            |r, v| r.normalization_mode = v,
+        ),
+    ),
+    (
+        "unique_norm_vec_count",
+        Accessor::UInt(
+            |r| r.unique_norm_vec_count,
+            |r, v| r.unique_norm_vec_count = v,
+        ),
+    ),
+    (
+        "norm_dim_mad_zero_count",
+        Accessor::UInt(
+            |r| r.norm_dim_mad_zero_count,
+            |r, v| r.norm_dim_mad_zero_count = v,
+        ),
+    ),
+    (
+        "mean_nn_dist_raw",
+        Accessor::F32(|r| r.mean_nn_dist_raw, |r, v| r.mean_nn_dist_raw = v),
+    ),
+    (
+        "mean_nn_dist_norm",
+        Accessor::F32(|r| r.mean_nn_dist_norm, |r, v| r.mean_nn_dist_norm = v),
+    ),
+    (
+        "pareto_spacing_raw",
+        Accessor::F32(|r| r.pareto_spacing_raw, |r, v| r.pareto_spacing_raw = v),
+    ),
+    (
+        "pareto_spacing_norm",
+        Accessor::F32(|r| r.pareto_spacing_norm, |r, v| r.pareto_spacing_norm = v),
+    ),
+    (
+        "distance_calls",
+        Accessor::UInt(|r| r.distance_calls, |r, v| r.distance_calls = v),
+    ),
+    (
+        "nn_distance_calls",
+        Accessor::UInt(|r| r.nn_distance_calls, |r, v| r.nn_distance_calls = v),
+    ),
+    (
+        "weak_dim_count",
+        Accessor::UInt(|r| r.weak_dim_count, |r, v| r.weak_dim_count = v),
+    ),
+    (
+        "effective_dim_count",
+        Accessor::UInt(|r| r.effective_dim_count, |r, v| r.effective_dim_count = v),
+    ),
+    (
+        "alpha_t",
+        Accessor::F32(|r| r.alpha_t, |r, v| r.alpha_t = v),
+    ),
+    (
+        "weak_contrib_ratio",
+        Accessor::F32(|r| r.weak_contrib_ratio, |r, v| r.weak_contrib_ratio = v),
+    ),
+    (
+        "collapse_proxy",
+        Accessor::F32(|r| r.collapse_proxy, |r, v| r.collapse_proxy = v),
+    ),
+    (
+        "avg_tau_mem",
+        Accessor::F32(|r| r.avg_tau_mem, |r, v| r.avg_tau_mem = v),
+    ),
+    (
+        "avg_delta_norm",
+        Accessor::F32(|r| r.avg_delta_norm, |r, v| r.avg_delta_norm = v),
+    ),
+    (
+        "memory_hit_rate",
+        Accessor::F32(|r| r.memory_hit_rate, |r, v| r.memory_hit_rate = v),
+    ),
+    (
+        "redundancy_flags",
+        Accessor::Text(
+            |r| r.redundancy_flags.as_str(),
+            |r, v| r.redundancy_flags = v,
+        ),
+    ),
+    (
+        "saturation_flags",
+        Accessor::Text(
+            |r| r.saturation_flags.as_str(),
+            |r, v| r.saturation_flags = v,
+        ),
+    ),
+    (
+        "discrete_saturation_count",
+        Accessor::UInt(
+            |r| r.discrete_saturation_count,
+            |r, v| r.discrete_saturation_count = v,
+        ),
+    ),
+    (
+        "effective_dim",
+        Accessor::UInt(|r| r.effective_dim, |r, v| r.effective_dim = v),
+    ),
+    (
+        "effective_dim_ratio",
+        Accessor::F32(|r| r.effective_dim_ratio, |r, v| r.effective_dim_ratio = v),
+    ),
+    (
+        "collapse_reasons",
+        Accessor::Text(
+            |r| r.collapse_reasons.as_str(),
+            |r, v| r.collapse_reasons = v,
+        ),
+    ),
+    (
+        "convergence_reason",
+        Accessor::Text(
+            |r| r.convergence_reason.as_str(),
+            |r, v| r.convergence_reason = v,
+        ),
+    ),
+    (
+        "novelty_archive_size",
+        Accessor::UInt(
+            |r| r.novelty_archive_size,
+            |r, v| r.novelty_archive_size = v,
+        ),
+    ),
+    (
+        "novelty_mean",
+        Accessor::F32(|r| r.novelty_mean, |r, v| r.novelty_mean = v),
+    ),
+    (
+        "repairs_reconnected",
+        Accessor::UInt(|r| r.repairs_reconnected, |r, v| r.repairs_reconnected = v),
+    ),
+    (
+        "repairs_collapsed",
+        Accessor::UInt(|r| r.repairs_collapsed, |r, v| r.repairs_collapsed = v),
+    ),
+    (
+        "hv_improvement",
+        Accessor::F32(|r| r.hv_improvement, |r, v| r.hv_improvement = v),
+    ),
+    (
+        "best_score_improvement",
+        Accessor::F32(
+            |r| r.best_score_improvement,
+            |r, v| r.best_score_improvement = v,
+        ),
+    ),
+    (
+        "quarantined_count",
+        Accessor::UInt(|r| r.quarantined_count, |r, v| r.quarantined_count = v),
+    ),
+    (
+        "diversity_trigger",
+        Accessor::Text(
+            |r| r.diversity_trigger.as_str(),
+            |r, v| r.diversity_trigger = v,
+        ),
+    ),
+    (
+        "merged_duplicates",
+        Accessor::UInt(|r| r.merged_duplicates, |r, v| r.merged_duplicates = v),
+    ),
+    (
+        "correlation_id",
+        Accessor::Text(|r| r.correlation_id.as_str(), |r, v| r.correlation_id = v),
+    ),
+];
+
+/// Column-per-field, delta-encoded copy of a trace.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnarTrace {
+    rows: usize,
+    /// Aligned with `COLUMNS`.
+    columns: Vec<Vec<u8>>,
+}
+
+impl ColumnarTrace {
+    pub fn from_rows(rows: &[TraceRow]) -> Self {
+        let columns = COLUMNS
+            .iter()
+            .map(|(_, accessor)| encode_column(*accessor, rows))
+            .collect();
+        Self {
+            rows: rows.len(),
+            columns,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    pub fn column_names() -> impl Iterator<Item = &'static str> {
+        COLUMNS.iter().map(|(name, _)| *name)
+    }
+
+    pub fn column_kind(name: &str) -> Option<ColumnKind> {
+        column_index(name).map(|i| COLUMNS[i].1.kind())
+    }
+
+    /// Bytes held by the encoded columns.
+    pub fn encoded_len(&self) -> usize {
+        self.columns.iter().map(Vec::len).sum()
+    }
+
+    /// Decodes only the named column.
+    pub fn column(&self, name: &str) -> Option<ColumnValues> {
+        let index = column_index(name)?;
+        decode_values(COLUMNS[index].1.kind(), &self.columns[index], self.rows).ok()
+    }
+
+    pub fn to_rows(&self) -> Vec<TraceRow> {
+        self.rows_at(&(0..self.rows).collect::<Vec<_>>())
+    }
+
+    /// Every `every`-th row plus the last one, for plotting long runs.
+    /// Collapse episodes (runs of rows with `collapse_flag`) are kept whole,
+    /// together with the row on either side of each episode, so thinning
+    /// never hides a collapse.
+    pub fn downsample(&self, every: usize) -> Vec<TraceRow> {
+        let every = every.max(1);
+        let collapse = match self.column("collapse_flag") {
+            Some(ColumnValues::Bool(flags)) => flags,
+            _ => vec![false; self.rows],
+        };
+        let keep = (0..self.rows)
+            .filter(|&i| {
+                i % every == 0
+                    || i + 1 == self.rows
+                    || collapse[i]
+                    || collapse.get(i + 1).copied().unwrap_or(false)
+                    || (i > 0 && collapse[i - 1])
+            })
+            .collect::<Vec<_>>();
+        self.rows_at(&keep)
+    }
+
+    fn rows_at(&self, indices: &[usize]) -> Vec<TraceRow> {
+        let mut rows = vec![TraceRow::default(); indices.len()];
+        for ((_, accessor), bytes) in COLUMNS.iter().zip(&self.columns) {
+            let values = decode_values(accessor.kind(), bytes, self.rows)
+                .expect("columns built by ColumnarTrace decode");
+            for (row, &i) in rows.iter_mut().zip(indices) {
+                match (accessor, &values) {
+                    (Accessor::F32(_, set), ColumnValues::F32(v)) => set(row, v[i]),
+                    (Accessor::UInt(_, set), ColumnValues::UInt(v)) => set(row, v[i]),
+                    (Accessor::Bool(_, set), ColumnValues::Bool(v)) => set(row, v[i]),
+                    (Accessor::Text(_, set), ColumnValues::Text(v)) => set(row, v[i].clone()),
+                    _ => unreachable!("decode_values follows the accessor kind"),
+                }
+            }
+        }
+        rows
+    }
+}
+
+fn column_index(name: &str) -> Option<usize> {
+    COLUMNS.iter().position(|(n, _)| *n == name)
+}
+
+fn encode_column(accessor: Accessor, rows: &[TraceRow]) -> Vec<u8> {
+    let mut out = Vec::new();
+    match accessor {
+        Accessor::F32(get, _) => {
+            let mut prev = 0u32;
+            for row in rows {
+                let bits = get(row).to_bits();
+                write_varint(&mut out, u64::from(bits ^ prev));
+                prev = bits;
+            }
+        }
+        Accessor::UInt(get, _) => {
+            let mut prev = 0i64;
+            for row in rows {
+                let value = get(row) as i64;
+                write_varint(&mut out, zigzag(value.wrapping_sub(prev)));
+                prev = value;
+            }
+        }
+        Accessor::Bool(get, _) => out.extend(rows.iter().map(|row| u8::from(get(row)))),
+        Accessor::Text(get, _) => {
+            let mut prev = "";
+            for row in rows {
+                let value = get(row);
+                // 0 repeats the previous value; n + 1 introduces n new bytes.
+                if !out.is_empty() && value == prev {
+                    write_varint(&mut out, 0);
+                } else {
+                    write_varint(&mut out, value.len() as u64 + 1);
+                    out.extend_from_slice(value.as_bytes());
+                }
+                prev = value;
+            }
+        }
+    }
+    out
+}
+
+fn decode_values(kind: ColumnKind, bytes: &[u8], rows: usize) -> io::Result<ColumnValues> {
+    let mut cursor = 0usize;
+    // Every value takes at least one byte, so a forged `rows` cannot size
+    // the buffers past the column itself.
+    let capacity = rows.min(bytes.len());
+    let values = match kind {
+        ColumnKind::F32 => {
+            let mut prev = 0u32;
+            let mut values = Vec::with_capacity(capacity);
+            for _ in 0..rows {
+                let xor = u32::try_from(read_varint(bytes, &mut cursor)?)
+                    .map_err(|_| invalid("f32 delta out of range"))?;
+                prev ^= xor;
+                values.push(f32::from_bits(prev));
+            }
+            ColumnValues::F32(values)
+        }
+        ColumnKind::UInt => {
+            let mut prev = 0i64;
+            let mut values = Vec::with_capacity(capacity);
+            for _ in 0..rows {
+                prev = prev.wrapping_add(unzigzag(read_varint(bytes, &mut cursor)?));
+                values.push(usize::try_from(prev).map_err(|_| invalid("negative count"))?);
+            }
+            ColumnValues::UInt(values)
+        }
+        ColumnKind::Bool => {
+            let flags = bytes.get(..rows).ok_or_else(|| invalid("bool column"))?;
+            cursor = rows;
+            ColumnValues::Bool(flags.iter().map(|b| *b != 0).collect())
+        }
+        ColumnKind::Text => {
+            let mut values: Vec<String> = Vec::with_capacity(capacity);
+            for _ in 0..rows {
+                let tag = read_varint(bytes, &mut cursor)? as usize;
+                if tag == 0 {
+                    let prev = values
+                        .last()
+                        .cloned()
+                        .ok_or_else(|| invalid("text repeat"))?;
+                    values.push(prev);
+                    continue;
+                }
+                let end = cursor
+                    .checked_add(tag - 1)
+                    .filter(|end| *end <= bytes.len())
+                    .ok_or_else(|| invalid("text column"))?;
+                let text = std::str::from_utf8(&bytes[cursor..end])
+                    .map_err(|_| invalid("text column is not utf-8"))?;
+                values.push(text.to_string());
+                cursor = end;
+            }
+            ColumnValues::Text(values)
+        }
+    };
+    if cursor != bytes.len() {
+        return Err(invalid("trailing bytes in column"));
+    }
+    Ok(values)
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        out.push((value as u8) | 0x80);
+        value >>= 7;
+    }
+    out.push(value as u8);
+}
+
+fn read_varint(bytes: &[u8], cursor: &mut usize) -> io::Result<u64> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = *bytes
+            .get(*cursor)
+            .ok_or_else(|| invalid("truncated varint"))?;
+        *cursor += 1;
+        value |= u64::from(byte & 0x7f) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(invalid("varint too long"))
+}
+
+fn zigzag(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+fn unzigzag(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(feature = "compression")]
+pub use compressed::CompressedTrace;
+
+#[cfg(feature = "compression")]
+mod compressed {
+    use std::io::{self, Read, Write};
+    use std::path::Path;
+
+    use super::{COLUMNS, ColumnKind, ColumnValues, ColumnarTrace, column_index, decode_values};
+    use super::{encode_column, invalid};
+    use crate::TraceRow;
+
+    const MAGIC: &[u8; 8] = b"DBMTRC\0\x01";
+    const LEVEL: i32 = 3;
+
+    /// Per-column zstd frames of a `ColumnarTrace`, as written to disk.
+    #[derive(Clone, Debug, PartialEq)]
+    pub struct CompressedTrace {
+        rows: usize,
+        /// Aligned with `COLUMNS`.
+        frames: Vec<Vec<u8>>,
+    }
+
+    impl CompressedTrace {
+        pub fn compress(trace: &ColumnarTrace) -> io::Result<Self> {
+            let frames = trace
+                .columns
+                .iter()
+                .map(|bytes| zstd::bulk::compress(bytes, LEVEL))
+                .collect::<io::Result<Vec<_>>>()?;
+            Ok(Self {
+                rows: trace.rows,
+                frames,
+            })
+        }
+
+        pub fn len(&self) -> usize {
+            self.rows
+        }
+
+        pub fn is_empty(&self) -> bool {
+            self.rows == 0
+        }
+
+        pub fn compressed_len(&self) -> usize {
+            self.frames.iter().map(Vec::len).sum()
+        }
+
+        /// Inflates and decodes only the named column.
+        pub fn column(&self, name: &str) -> io::Result<Option<ColumnValues>> {
+            let Some(index) = column_index(name) else {
+                return Ok(None);
+            };
+            let bytes = zstd::decode_all(self.frames[index].as_slice())?;
+            decode_values(COLUMNS[index].1.kind(), &bytes, self.rows).map(Some)
+        }
+
+        /// Inflates every column, checking that each one decodes.
+        pub fn to_columnar(&self) -> io::Result<ColumnarTrace> {
+            let mut columns = Vec::with_capacity(COLUMNS.len());
+            for ((_, accessor), frame) in COLUMNS.iter().zip(&self.frames) {
+                let bytes = zstd::decode_all(frame.as_slice())?;
+                decode_values(accessor.kind(), &bytes, self.rows)?;
+                columns.push(bytes);
+            }
+            Ok(ColumnarTrace {
+                rows: self.rows,
+                columns,
+            })
+        }
+
+        /// Layout: magic, row count, column count, then per column its name,
+        /// kind tag and zstd frame, each length-prefixed.
+        pub fn write_to(&self, mut out: impl Write) -> io::Result<()> {
+            out.write_all(MAGIC)?;
+            out.write_all(&(self.rows as u64).to_le_bytes())?;
+            out.write_all(&(COLUMNS.len() as u32).to_le_bytes())?;
+            for ((name, accessor), frame) in COLUMNS.iter().zip(&self.frames) {
+                out.write_all(&(name.len() as u16).to_le_bytes())?;
+                out.write_all(name.as_bytes())?;
+                out.write_all(&[kind_tag(accessor.kind())])?;
+                out.write_all(&(frame.len() as u64).to_le_bytes())?;
+                out.write_all(frame)?;
+            }
+            Ok(())
+        }
+
+        /// Columns are matched by name: unknown ones are skipped and missing
+        /// ones read back as `TraceRow::default()` values, so traces written
+        /// before a field was added still load.
+        pub fn read_from(mut input: impl Read) -> io::Result<Self> {
+            let mut magic = [0u8; 8];
+            input.read_exact(&mut magic)?;
+            if &magic != MAGIC {
+                return Err(invalid("not a compressed trace"));
+            }
+            let rows = usize::try_from(read_u64(&mut input)?)
+                .map_err(|_| invalid("row count out of range"))?;
+            let count = read_u32(&mut input)?;
+            let mut frames: Vec<Option<Vec<u8>>> = vec![None; COLUMNS.len()];
+            for _ in 0..count {
+                let mut name = vec![0u8; usize::from(read_u16(&mut input)?)];
+                input.read_exact(&mut name)?;
+                let mut tag = [0u8; 1];
+                input.read_exact(&mut tag)?;
+                let len = read_u64(&mut input)?;
+                let mut frame = Vec::new();
+                (&mut input).take(len).read_to_end(&mut frame)?;
+                if frame.len() as u64 != len {
+                    return Err(io::Error::from(io::ErrorKind::UnexpectedEof));
+                }
+                let name = String::from_utf8(name).map_err(|_| invalid("column name"))?;
+                let Some(index) = column_index(&name) else {
+                    continue;
+                };
+                if tag[0] != kind_tag(COLUMNS[index].1.kind()) {
+                    return Err(invalid("column kind changed"));
+                }
+                frames[index] = Some(frame);
+            }
+            // Each value encodes to at least one byte, so every frame has to
+            // inflate to `rows` bytes or more.
+            let backed = frames.iter().flatten().all(|frame| {
+                matches!(
+                    zstd::zstd_safe::get_frame_content_size(frame),
+                    Ok(Some(size)) if size >= rows as u64
+                )
+            });
+            if !backed || (rows > 0 && frames.iter().all(Option::is_none)) {
+                return Err(invalid("row count exceeds the column data"));
+            }
+            let defaults = vec![TraceRow::default(); rows];
+            let frames = frames
+                .into_iter()
+                .zip(COLUMNS)
+                .map(|(frame, (_, accessor))| match frame {
+                    Some(frame) => Ok(frame),
+                    None => zstd::bulk::compress(&encode_column(*accessor, &defaults), 1),
+                })
+                .collect::<io::Result<Vec<_>>>()?;
+            Ok(Self { rows, frames })
+        }
+
+        pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+            let path = path.as_ref();
+            if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+                std::fs::create_dir_all(parent)?;
+            }
+            let mut out = io::BufWriter::new(std::fs::File::create(path)?);
+            self.write_to(&mut out)?;
+            out.flush()
+        }
+
+        pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+            Self::read_from(io::BufReader::new(std::fs::File::open(path)?))
+        }
+    }
+
+    fn kind_tag(kind: ColumnKind) -> u8 {
+        match kind {
+            ColumnKind::F32 => 0,
+            ColumnKind::UInt => 1,
+            ColumnKind::Bool => 2,
+            ColumnKind::Text => 3,
+        }
+    }
+
+    fn read_u16(input: &mut impl Read) -> io::Result<u16> {
+        let mut buf = [0u8; 2];
+        input.read_exact(&mut buf)?;
+        Ok(u16::from_le_bytes(buf))
+    }
+
+    fn read_u32(input: &mut impl Read) -> io::Result<u32> {
+        let mut buf = [0u8; 4];
+        input.read_exact(&mut buf)?;
+        Ok(u32::from_le_bytes(buf))
+    }
+
+    fn read_u64(input: &mut impl Read) -> io::Result<u64> {
+        let mut buf = [0u8; 8];
+        input.read_exact(&mut buf)?;
+        Ok(u64::from_le_bytes(buf))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ColumnKind, ColumnValues, ColumnarTrace};
+    use crate::TraceRow;
+
+    fn synthetic(rows: usize) -> Vec<TraceRow> {
+        (0..rows)
+            .map(|depth| TraceRow {
+                depth,
+                lambda: 0.5 + (depth % 7) as f32 * 0.01,
+                diversity: if depth < 50 { 0.4 } else { 0.25 },
+                pareto_size: 3 + depth % 4,
+                collapse_flag: (120..124).contains(&depth),
+                normalization_mode: if depth < 10 { "warmup" } else { "frozen" }.to_string(),
+                ..TraceRow::default()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn columnar_round_trip_is_lossless_and_smaller() {
+        let rows = synthetic(300);
+        let trace = ColumnarTrace::from_rows(&rows);
+
+        assert_eq!(trace.to_rows(), rows);
+        assert_eq!(trace.len(), 300);
+        // Flat metrics cost one byte per row, far below four bytes per field.
+        assert!(trace.encoded_len() < 300 * ColumnarTrace::column_names().count() * 2);
+        assert_eq!(
+            ColumnarTrace::column_kind("normalization_mode"),
+            Some(ColumnKind::Text)
+        );
+        let Some(ColumnValues::UInt(pareto)) = trace.column("pareto_size") else {
+            panic!("pareto_size column");
+        };
+        assert_eq!(pareto[5], 4);
+        assert_eq!(
+            trace
+                .column("diversity")
+                .and_then(|c| c.to_f64())
+                .map(|v| v[60]),
+            Some(0.25)
+        );
+        assert!(trace.column("no_such_metric").is_none());
+        assert_eq!(ColumnarTrace::from_rows(&[]).to_rows(), Vec::new());
+    }
+
+    #[test]
+    fn downsampling_keeps_collapse_episodes_and_their_edges() {
+        let trace = ColumnarTrace::from_rows(&synthetic(300));
+        let depths = trace
+            .downsample(50)
+            .iter()
+            .map(|row| row.depth)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            depths,
+            vec![0, 50, 100, 119, 120, 121, 122, 123, 124, 150, 200, 250, 299]
+        );
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn compressed_trace_saves_loads_and_decodes_columns_lazily() {
+        use super::CompressedTrace;
+
+        let rows = synthetic(2_000);
+        let trace = ColumnarTrace::from_rows(&rows);
+        let compressed = CompressedTrace::compress(&trace).expect("compress");
+        assert!(compressed.compressed_len() < trace.encoded_len());
+
+        let path =
+            std::env::temp_dir().join(format!("trace_columns_{}.dbmtrc", std::process::id()));
+        compressed.save(&path).expect("save");
+        let loaded = CompressedTrace::load(&path).expect("load");
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(loaded, compressed);
+        assert_eq!(
+            loaded.column("collapse_flag").expect("decode"),
+            trace.column("collapse_flag")
+        );
+        assert_eq!(loaded.to_columnar().expect("inflate").to_rows(), rows);
+
+        let mut bytes = Vec::new();
+        compressed.write_to(&mut bytes).expect("write");
+        assert!(CompressedTrace::read_from(&bytes[..bytes.len() - 3]).is_err());
+    }
+
+    #[cfg(feature = "compression")]
+    #[test]
+    fn forged_row_counts_fail_to_load_instead_of_allocating() {
+        use super::CompressedTrace;
+
+        let rows = synthetic(200);
+        let compressed =
+            CompressedTrace::compress(&ColumnarTrace::from_rows(&rows)).expect("compress");
+        let mut bytes = Vec::new();
+        compressed.write_to(&mut bytes).expect("write");
+        let path = std::env::temp_dir().join(format!(
+            "trace_columns_forged_{}.dbmtrc",
+            std::process::id()
+        ));
+        for forged in [u64::MAX, 1 << 40, 201] {
+            bytes[8..16].copy_from_slice(&forged.to_le_bytes());
+            std::fs::write(&path, &bytes).expect("write");
+            let err = CompressedTrace::load(&path).expect_err("forged row count");
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        }
+        let _ = std::fs::remove_file(&path);
+
+        assert!(super::decode_values(ColumnKind::UInt, &[0, 2, 2], usize::MAX).is_err());
+    }
+}
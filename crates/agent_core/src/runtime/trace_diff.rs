@@ -0,0 +1,353 @@
+//! Row-by-row comparison of two trace runs, for regression hunting and for
+//! tests that pin down determinism.
+//!
+//! Rows are aligned by `depth`. Numeric metrics differing by more than their
+//! threshold and text fields that differ at all count as divergences;
+//! wall-clock timings and the process-wide distance counters are skipped
+//! unless `TraceDiffConfig::ignore_instrumentation` is cleared.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::TraceRow;
+
+type NumericMetric = (&'static str, fn(&TraceRow) -> f64);
+type TextField = (&'static str, fn(&TraceRow) -> &str);
+
+const NUMERIC_METRICS: &[NumericMetric] = &[
+    ("lambda", |r| r.lambda as f64),
+    ("delta_lambda", |r| r.delta_lambda as f64),
+    ("tau_prime", |r| r.tau_prime as f64),
+    ("conf_chm", |r| r.conf_chm as f64),
+    ("density", |r| r.density as f64),
+    ("k", |r| r.k as f64),
+    ("h_profile", |r| r.h_profile as f64),
+    ("pareto_size", |r| r.pareto_size as f64),
+    ("diversity", |r| r.diversity as f64),
+    ("resonance_avg", |r| r.resonance_avg as f64),
+    ("pressure", |r| r.pressure as f64),
+    ("epsilon_effect", |r| r.epsilon_effect as f64),
+    ("target_local_weight", |r| r.target_local_weight as f64),
+    ("target_global_weight", |r| r.target_global_weight as f64),
+    ("local_global_distance", |r| r.local_global_distance as f64),
+    ("field_min_distance", |r| r.field_min_distance as f64),
+    ("field_rejected_count", |r| r.field_rejected_count as f64),
+    ("mu", |r| r.mu as f64),
+    ("dhm_k", |r| r.dhm_k as f64),
+    ("dhm_norm", |r| r.dhm_norm as f64),
+    ("dhm_resonance_mean", |r| r.dhm_resonance_mean as f64),
+    ("dhm_score_ratio", |r| r.dhm_score_ratio as f64),
+    ("dhm_build_us", |r| r.dhm_build_us as f64),
+    ("expanded_categories_count", |r| {
+        r.expanded_categories_count as f64
+    }),
+    ("selected_rules_count", |r| r.selected_rules_count as f64),
+    ("entropy_per_depth", |r| r.entropy_per_depth as f64),
+    ("unique_category_count_per_depth", |r| {
+        r.unique_category_count_per_depth as f64
+    }),
+    ("pareto_front_size_per_depth", |r| {
+        r.pareto_front_size_per_depth as f64
+    }),
+    ("pareto_mean_nn_dist", |r| r.pareto_mean_nn_dist as f64),
+    ("pareto_spacing", |r| r.pareto_spacing as f64),
+    ("pareto_hv_2d", |r| r.pareto_hv_2d as f64),
+    ("field_extract_us", |r| r.field_extract_us as f64),
+    ("field_score_us", |r| r.field_score_us as f64),
+    ("field_aggregate_us", |r| r.field_aggregate_us as f64),
+    ("field_total_us", |r| r.field_total_us as f64),
+    ("norm_median_0", |r| r.norm_median_0 as f64),
+    ("norm_median_1", |r| r.norm_median_1 as f64),
+    ("norm_median_2", |r| r.norm_median_2 as f64),
+    ("norm_median_3", |r| r.norm_median_3 as f64),
+    ("norm_mad_0", |r| r.norm_mad_0 as f64),
+    ("norm_mad_1", |r| r.norm_mad_1 as f64),
+    ("norm_mad_2", |r| r.norm_mad_2 as f64),
+    ("norm_mad_3", |r| r.norm_mad_3 as f64),
+    ("median_nn_dist_all_depth", |r| {
+        r.median_nn_dist_all_depth as f64
+    }),
+    ("collapse_flag", |r| f64::from(u8::from(r.collapse_flag))),
+    ("unique_norm_vec_count", |r| r.unique_norm_vec_count as f64),
+    ("norm_dim_mad_zero_count", |r| {
+        r.norm_dim_mad_zero_count as f64
+    }),
+    ("mean_nn_dist_raw", |r| r.mean_nn_dist_raw as f64),
+    ("mean_nn_dist_norm", |r| r.mean_nn_dist_norm as f64),
+    ("pareto_spacing_raw", |r| r.pareto_spacing_raw as f64),
+    ("pareto_spacing_norm", |r| r.pareto_spacing_norm as f64),
+    ("distance_calls", |r| r.distance_calls as f64),
+    ("nn_distance_calls", |r| r.nn_distance_calls as f64),
+    ("weak_dim_count", |r| r.weak_dim_count as f64),
+    ("effective_dim_count", |r| r.effective_dim_count as f64),
+    ("alpha_t", |r| r.alpha_t as f64),
+    ("weak_contrib_ratio", |r| r.weak_contrib_ratio as f64),
+    ("collapse_proxy", |r| r.collapse_proxy as f64),
+    ("avg_tau_mem", |r| r.avg_tau_mem as f64),
+    ("avg_delta_norm", |r| r.avg_delta_norm as f64),
+    ("memory_hit_rate", |r| r.memory_hit_rate as f64),
+    ("discrete_saturation_count", |r| {
+        r.discrete_saturation_count as f64
+    }),
+    ("effective_dim", |r| r.effective_dim as f64),
+    ("effective_dim_ratio", |r| r.effective_dim_ratio as f64),
+    ("novelty_archive_size", |r| r.novelty_archive_size as f64),
+    ("novelty_mean", |r| r.novelty_mean as f64),
+    ("repairs_reconnected", |r| r.repairs_reconnected as f64),
+    ("repairs_collapsed", |r| r.repairs_collapsed as f64),
+    ("hv_improvement", |r| r.hv_improvement as f64),
+    ("best_score_improvement", |r| {
+        r.best_score_improvement as f64
+    }),
+    ("quarantined_count", |r| r.quarantined_count as f64),
+    ("merged_duplicates", |r| r.merged_duplicates as f64),
+];
+
+const TEXT_FIELDS: &[TextField] = &[
+    ("per_category_selected", |r| &r.per_category_selected),
+    ("normalization_mode", |r| &r.normalization_mode),
+    ("redundancy_flags", |r| &r.redundancy_flags),
+    ("saturation_flags", |r| &r.saturation_flags),
+    ("collapse_reasons", |r| &r.collapse_reasons),
+    ("convergence_reason", |r| &r.convergence_reason),
+    ("diversity_trigger", |r| &r.diversity_trigger),
+];
+
+/// Wall-clock timings and counters read from process-wide atomics; neither
+/// repeats between otherwise identical runs.
+pub const INSTRUMENTATION_METRICS: &[&str] = &[
+    "dhm_build_us",
+    "field_extract_us",
+    "field_score_us",
+    "field_aggregate_us",
+    "field_total_us",
+    "distance_calls",
+    "nn_distance_calls",
+];
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TraceDiffConfig {
+    /// Largest absolute delta tolerated for metrics without an override.
+    pub default_threshold: f64,
+    /// Per-metric overrides, keyed by `TraceRow` field name.
+    pub thresholds: BTreeMap<String, f64>,
+    /// Skip `INSTRUMENTATION_METRICS`.
+    pub ignore_instrumentation: bool,
+}
+
+impl Default for TraceDiffConfig {
+    fn default() -> Self {
+        Self {
+            default_threshold: 1e-6,
+            thresholds: BTreeMap::new(),
+            ignore_instrumentation: true,
+        }
+    }
+}
+
+impl TraceDiffConfig {
+    pub fn threshold(&self, metric: &str) -> f64 {
+        self.thresholds
+            .get(metric)
+            .copied()
+            .unwrap_or(self.default_threshold)
+    }
+
+    pub fn with_threshold(mut self, metric: impl Into<String>, threshold: f64) -> Self {
+        self.thresholds.insert(metric.into(), threshold);
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MetricDelta {
+    pub depth: usize,
+    pub metric: String,
+    pub a: f64,
+    pub b: f64,
+    /// `b - a`.
+    pub delta: f64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TextMismatch {
+    pub depth: usize,
+    pub field: String,
+    pub a: String,
+    pub b: String,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TraceDiffVerdict {
+    /// Every compared value is bit-for-bit equal.
+    Identical,
+    /// Some metrics differ, all within their thresholds.
+    WithinTolerance,
+    Diverged,
+}
+
+impl TraceDiffVerdict {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Identical => "identical",
+            Self::WithinTolerance => "within_tolerance",
+            Self::Diverged => "diverged",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TraceDiffReport {
+    /// Depths present in both runs.
+    pub compared_depths: usize,
+    /// Depths only `b` reached.
+    pub missing_in_a: Vec<usize>,
+    /// Depths only `a` reached.
+    pub missing_in_b: Vec<usize>,
+    /// Deltas above their threshold, by depth then metric order.
+    pub deltas: Vec<MetricDelta>,
+    pub text_mismatches: Vec<TextMismatch>,
+    /// Largest absolute delta per metric, including those within threshold.
+    pub max_abs_delta: BTreeMap<String, f64>,
+    /// Lowest depth with an over-threshold delta, a text mismatch, or a row
+    /// missing from one side.
+    pub first_divergence: Option<usize>,
+    pub verdict: TraceDiffVerdict,
+}
+
+impl TraceDiffReport {
+    pub fn is_match(&self) -> bool {
+        self.verdict != TraceDiffVerdict::Diverged
+    }
+
+    /// One line for test failure messages and logs.
+    pub fn summary(&self) -> String {
+        let mut line = format!(
+            "{}: {} depths compared, {} metric deltas, {} text mismatches",
+            self.verdict.as_str(),
+            self.compared_depths,
+            self.deltas.len(),
+            self.text_mismatches.len()
+        );
+        if !self.missing_in_a.is_empty() || !self.missing_in_b.is_empty() {
+            line.push_str(&format!(
+                ", depths missing in a {:?} / in b {:?}",
+                self.missing_in_a, self.missing_in_b
+            ));
+        }
+        if let Some(depth) = self.first_divergence {
+            line.push_str(&format!(", first divergence at depth {depth}"));
+        }
+        line
+    }
+}
+
+/// `trace_diff_with` under `TraceDiffConfig::default()`.
+pub fn trace_diff(a: &[TraceRow], b: &[TraceRow]) -> TraceDiffReport {
+    trace_diff_with(a, b, &TraceDiffConfig::default())
+}
+
+pub fn trace_diff_with(
+    a: &[TraceRow],
+    b: &[TraceRow],
+    config: &TraceDiffConfig,
+) -> TraceDiffReport {
+    let rows_a = by_depth(a);
+    let rows_b = by_depth(b);
+    let missing_in_a = rows_b
+        .keys()
+        .filter(|depth| !rows_a.contains_key(depth))
+        .copied()
+        .collect::<Vec<_>>();
+    let missing_in_b = rows_a
+        .keys()
+        .filter(|depth| !rows_b.contains_key(depth))
+        .copied()
+        .collect::<Vec<_>>();
+
+    let mut deltas = Vec::new();
+    let mut text_mismatches = Vec::new();
+    let mut max_abs_delta = BTreeMap::<String, f64>::new();
+    let mut diverged_depths = missing_in_a
+        .iter()
+        .chain(&missing_in_b)
+        .copied()
+        .collect::<BTreeSet<_>>();
+    let mut exact = true;
+    let mut compared_depths = 0;
+
+    for (depth, row_a) in &rows_a {
+        let Some(row_b) = rows_b.get(depth) else {
+            continue;
+        };
+        compared_depths += 1;
+        for (metric, value) in NUMERIC_METRICS {
+            if config.ignore_instrumentation && INSTRUMENTATION_METRICS.contains(metric) {
+                continue;
+            }
+            let (x, y) = (value(row_a), value(row_b));
+            if x.to_bits() == y.to_bits() {
+                continue;
+            }
+            exact = false;
+            // NaN on either side compares as an infinite delta.
+            let delta = y - x;
+            let abs = if delta.is_nan() {
+                f64::INFINITY
+            } else {
+                delta.abs()
+            };
+            let max = max_abs_delta.entry((*metric).to_string()).or_insert(0.0);
+            *max = max.max(abs);
+            if abs > config.threshold(metric) {
+                diverged_depths.insert(*depth);
+                deltas.push(MetricDelta {
+                    depth: *depth,
+                    metric: (*metric).to_string(),
+                    a: x,
+                    b: y,
+                    delta,
+                });
+            }
+        }
+        for (field, value) in TEXT_FIELDS {
+            let (x, y) = (value(row_a), value(row_b));
+            if x != y {
+                diverged_depths.insert(*depth);
+                text_mismatches.push(TextMismatch {
+                    depth: *depth,
+                    field: (*field).to_string(),
+                    a: x.to_string(),
+                    b: y.to_string(),
+                });
+            }
+        }
+    }
+
+    let first_divergence = diverged_depths.first().copied();
+    let verdict = if first_divergence.is_some() {
+        TraceDiffVerdict::Diverged
+    } else if exact {
+        TraceDiffVerdict::Identical
+    } else {
+        TraceDiffVerdict::WithinTolerance
+    };
+    TraceDiffReport {
+        compared_depths,
+        missing_in_a,
+        missing_in_b,
+        deltas,
+        text_mismatches,
+        max_abs_delta,
+        first_divergence,
+        verdict,
+    }
+}
+
+fn by_depth(rows: &[TraceRow]) -> BTreeMap<usize, &TraceRow> {
+    rows.iter().map(|row| (row.depth, row)).collect()
+}
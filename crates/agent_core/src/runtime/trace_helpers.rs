@@ -1,12 +1,21 @@
 use std::collections::{BTreeMap, VecDeque};
-use std::sync::Arc;
-use std::time::Instant;
+use std::sync::{Arc, Mutex};
 
 use core_types::ObjectiveVector;
+use core_types::clock::Stopwatch;
 use field_engine::{FieldEngine, FieldVector};
-use hybrid_vm::{DesignRule, HybridVM, RuleCategory, RuleId, Shm};
+use hybrid_vm::{DesignRule, HybridVM, RuleCategory, Shm};
 use memory_space::{DesignNode, DesignState, StructuralGraph, Uuid, Value};
 
+use crate::capability::equivalence::{EquivalenceReducer, equivalence_hash};
+use crate::capability::ranking_cache::RuleRankingCache;
+use crate::capability::repair::{RepairConfig, RepairStats, apply_atomic_repaired};
+use crate::capability::rule_sampling::{SamplingContext, SelectionStrategy};
+use crate::capability::sanitize::{QuarantinedCandidate, quarantine};
+use crate::runtime::candidate_pipeline::{
+    CandidatePipelineConfig, PipelineThroughput, run_candidate_pipeline,
+};
+
 const FIELD_CACHE_CAPACITY: usize = 50_000;
 
 pub(crate) fn make_dense_trace_chm(shm: &Shm, seed: u64) -> hybrid_vm::Chm {
@@ -58,7 +67,7 @@ pub(crate) fn variance(v: &[f64]) -> f64 {
     crate::engine::statistics::variance(v)
 }
 
-fn elapsed_us(start: Instant) -> f64 {
+fn elapsed_us(start: Stopwatch) -> f64 {
     start.elapsed().as_secs_f64() * 1_000_000.0
 }
 
@@ -111,15 +120,23 @@ pub(crate) struct SoftCandidateBatch {
     pub(crate) field_aggregate_us: f64,
     pub(crate) field_total_us: f64,
     pub(crate) chm_us: f64,
+    pub(crate) repairs: RepairStats,
+    pub(crate) throughput: PipelineThroughput,
+    pub(crate) quarantined: Vec<QuarantinedCandidate>,
+    /// Candidates left unevaluated as equivalent to an earlier one.
+    pub(crate) merged_duplicates: usize,
 }
 
-type FieldCacheKey = (u128, u128, usize, usize);
+pub(crate) type FieldCacheKey = (u128, u128, usize, usize);
 
-#[derive(Clone, Copy, Debug)]
-pub(crate) struct SoftSelectionParams {
-    pub(crate) alpha: f64,
-    pub(crate) temperature: f64,
-    pub(crate) entropy_beta: f64,
+#[derive(Clone, Copy)]
+pub(crate) struct SoftSelectionParams<'a> {
+    pub(crate) strategy: &'a dyn SelectionStrategy,
+    pub(crate) seed: u64,
+    pub(crate) max_depth: usize,
+    /// Field and lambda levels the ranking cache buckets on.
+    pub(crate) field_level: f64,
+    pub(crate) lambda: f64,
 }
 
 #[derive(Clone, Copy)]
@@ -127,28 +144,62 @@ pub(crate) struct SoftCandidateContext<'a> {
     pub(crate) field: &'a FieldEngine,
     pub(crate) shm: &'a Shm,
     pub(crate) field_profile: bool,
+    pub(crate) repair: Option<RepairConfig>,
+}
+
+/// State aggregates kept across depths, oldest evicted first past
+/// `FIELD_CACHE_CAPACITY`.
+#[derive(Default)]
+pub(crate) struct FieldCache {
+    entries: BTreeMap<FieldCacheKey, FieldVector>,
+    order: VecDeque<FieldCacheKey>,
 }
 
-pub(crate) fn bounded_cache_get_or_insert(
-    cache: &mut BTreeMap<FieldCacheKey, FieldVector>,
-    order: &mut VecDeque<FieldCacheKey>,
-    key: FieldCacheKey,
-    compute: impl FnOnce() -> FieldVector,
-) -> (FieldVector, bool) {
-    if let Some(found) = cache.get(&key) {
-        return (found.clone(), true);
+impl FieldCache {
+    /// An entry whose dimensionality does not match `field` is stale.
+    fn get(&self, key: &FieldCacheKey, field: &FieldEngine) -> Option<FieldVector> {
+        self.entries
+            .get(key)
+            .filter(|found| field.check_dimensions(found).is_ok())
+            .cloned()
     }
-    let value = compute();
-    cache.insert(key, value.clone());
-    order.push_back(key);
-    while cache.len() > FIELD_CACHE_CAPACITY {
-        if let Some(old) = order.pop_front() {
-            cache.remove(&old);
-        } else {
-            break;
+
+    /// Replaces a stale entry in place.
+    fn insert(&mut self, key: FieldCacheKey, value: FieldVector) {
+        if self.entries.insert(key, value).is_some() {
+            return;
+        }
+        self.order.push_back(key);
+        while self.entries.len() > FIELD_CACHE_CAPACITY {
+            let Some(old) = self.order.pop_front() else {
+                break;
+            };
+            self.entries.remove(&old);
+        }
+    }
+
+    /// Cached aggregate of `state` under `field`, and whether it was a hit.
+    pub(crate) fn get_or_insert(
+        &mut self,
+        key: FieldCacheKey,
+        field: &FieldEngine,
+        state: &DesignState,
+    ) -> (FieldVector, bool) {
+        if let Some(found) = self.get(&key, field) {
+            return (found, true);
         }
+        let value = field.aggregate_state(state);
+        self.insert(key, value.clone());
+        (value, false)
     }
-    (value, false)
+}
+
+/// Per-candidate field timings, in microseconds.
+#[derive(Clone, Copy, Default)]
+struct FieldTimings {
+    extract_us: f64,
+    aggregate_us: f64,
+    score_us: f64,
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -157,71 +208,143 @@ pub(crate) fn build_soft_candidates_for_frontier(
     frontier: &[DesignState],
     beam: usize,
     depth: usize,
-    selection: SoftSelectionParams,
+    selection: SoftSelectionParams<'_>,
     ctx: SoftCandidateContext<'_>,
-    field_cache: &mut BTreeMap<FieldCacheKey, FieldVector>,
-    field_cache_order: &mut VecDeque<FieldCacheKey>,
+    pipeline: CandidatePipelineConfig,
+    ranking_cache: &mut RuleRankingCache,
+    field_cache: &mut FieldCache,
 ) -> SoftCandidateBatch {
     let mut batch = SoftCandidateBatch::default();
-    let mut partials: Vec<(DesignState, ObjectiveVector, RuleId, usize, f64)> = Vec::new();
+    let mut jobs: Vec<(usize, &DesignRule)> = Vec::new();
 
     for (state_idx, state) in frontier.iter().enumerate() {
-        let (selected_rules, per_state_counts, _availability_counts) = select_rules_category_soft(
-            HybridVM::applicable_rules(ctx.shm, state),
-            (beam.max(1) * 5).max(1),
-            selection.alpha,
-            selection.temperature,
-            selection.entropy_beta,
-        );
+        let rules = HybridVM::applicable_rules(ctx.shm, state);
+        let max_select = (beam.max(1) * 5).max(1);
+        let sampling = SamplingContext {
+            depth,
+            max_depth: selection.max_depth,
+            seed: selection.seed,
+            state_id: state.id.as_u128(),
+        };
+        let selected_rules = if selection.strategy.ignores_context() {
+            ranking_cache.get_or_rank(
+                rules,
+                max_select,
+                selection.field_level,
+                selection.lambda,
+                |rules| selection.strategy.select(rules, max_select, sampling),
+            )
+        } else {
+            selection.strategy.select(rules, max_select, sampling)
+        };
         batch.depth_selected_rules_count += selected_rules.len();
-        for (cat, c) in per_state_counts {
-            *batch.depth_category_counts.entry(cat).or_insert(0) += c;
+        for rule in &selected_rules {
+            *batch
+                .depth_category_counts
+                .entry(rule_category_name(&rule.category).to_string())
+                .or_insert(0) += 1;
         }
-        for rule in selected_rules {
-            let new_state = crate::apply_atomic(rule, state);
-            let obj = vm.evaluate(&new_state);
-            let t_chm = Instant::now();
-            batch.chm_us += elapsed_us(t_chm);
+        jobs.extend(selected_rules.into_iter().map(|rule| (state_idx, rule)));
+    }
+
+    let mut reducer = EquivalenceReducer::new();
+    // Projection workers share the cache; aggregates are computed unlocked.
+    let shared_cache = Mutex::new(std::mem::take(field_cache));
+    let (scored, throughput) = run_candidate_pipeline(
+        jobs,
+        pipeline,
+        |(state_idx, rule)| {
+            let state = &frontier[state_idx];
+            let applied = match ctx.repair {
+                Some(config) => apply_atomic_repaired(rule, state, config),
+                None => crate::apply_atomic(rule, state).map(|s| (s, RepairStats::default())),
+            };
+            applied
+                .ok()
+                .map(|(new_state, repairs)| (new_state, rule.id, state_idx, repairs))
+        },
+        |applied| {
+            // In debug builds a rule whose child fails graph validation yields
+            // no candidate.
+            let (state, rule_id, state_idx, repairs) = applied?;
+            let key = (state.id.as_u128(), rule_id.as_u128(), depth, state_idx);
+            let t_extract = Stopwatch::start();
+            let cached = shared_cache
+                .lock()
+                .expect("field cache lock")
+                .get(&key, ctx.field);
+            let mut timings = FieldTimings {
+                extract_us: elapsed_us(t_extract),
+                ..FieldTimings::default()
+            };
+            if cached.is_none() {
+                let t_aggregate = Stopwatch::start();
+                let value = ctx.field.aggregate_state(&state);
+                timings.aggregate_us = elapsed_us(t_aggregate);
+                shared_cache
+                    .lock()
+                    .expect("field cache lock")
+                    .insert(key, value);
+            }
+            let class = pipeline
+                .merge_equivalent
+                .then(|| equivalence_hash(&state.graph));
+            Some((state, rule_id, class, repairs, timings))
+        },
+        |projected| {
+            let Some((state, rule_id, class, repairs, mut timings)) = projected else {
+                return (None, RepairStats::default(), FieldTimings::default());
+            };
+            // Scoring sees candidates in submission order, so the same
+            // member of each class is kept whatever the worker counts.
+            if class.is_some_and(|hash| !reducer.admit(hash)) {
+                return (None, repairs, timings);
+            }
+            let t_score = Stopwatch::start();
+            let obj = vm.evaluate(&state);
+            timings.score_us = elapsed_us(t_score);
             let pre_score = 0.4 * obj.f_struct + 0.2 * obj.f_risk + 0.2 * obj.f_shape;
-            partials.push((new_state, obj.clamped(), rule.id, state_idx, pre_score));
+            (Some((state, rule_id, obj, pre_score)), repairs, timings)
+        },
+        |(scored, repairs, timings)| {
+            let verdict = scored.map(|(state, rule_id, obj, pre_score)| {
+                // Checked before clamping, which would turn an infinity into a bound.
+                match quarantine(depth, state.id, Some(rule_id), &obj) {
+                    Some(diagnostic) => Err(diagnostic),
+                    None => Ok((state, obj.clamped(), pre_score)),
+                }
+            });
+            (verdict, repairs, timings)
+        },
+    );
+    *field_cache = shared_cache.into_inner().expect("field cache lock");
+    batch.throughput = throughput;
+    batch.chm_us = throughput.score.busy_us;
+    batch.merged_duplicates = reducer.merged();
+    let mut partials = Vec::with_capacity(scored.len());
+    for (verdict, repairs, timings) in scored {
+        batch.repairs.absorb(repairs);
+        if ctx.field_profile {
+            batch.field_extract_us += timings.extract_us;
+            batch.field_aggregate_us += timings.aggregate_us;
+            batch.field_score_us += timings.score_us;
+            batch.field_total_us += timings.extract_us + timings.aggregate_us + timings.score_us;
+        }
+        match verdict {
+            Some(Ok(candidate)) => partials.push(candidate),
+            Some(Err(diagnostic)) => batch.quarantined.push(diagnostic),
+            None => {}
         }
     }
 
-    partials.sort_by(|(ls, _, _, _, lscore), (rs, _, _, _, rscore)| {
+    partials.sort_by(|(ls, _, lscore), (rs, _, rscore)| {
         rscore
             .partial_cmp(lscore)
             .unwrap_or(std::cmp::Ordering::Equal)
             .then_with(|| ls.id.cmp(&rs.id))
     });
 
-    let detailed_n = (beam.max(1) * 5).min(partials.len());
-    for (idx, (state, obj, rule_id, state_idx, _)) in partials.iter_mut().enumerate() {
-        if idx >= detailed_n {
-            break;
-        }
-        let t_total = Instant::now();
-        let key = (state.id.as_u128(), rule_id.as_u128(), depth, *state_idx);
-        let t_extract = Instant::now();
-        let t_agg = Instant::now();
-        let (_projection, cache_hit) =
-            bounded_cache_get_or_insert(field_cache, field_cache_order, key, || {
-                ctx.field.aggregate_state(state)
-            });
-        if ctx.field_profile && !cache_hit {
-            batch.field_aggregate_us += elapsed_us(t_agg);
-        }
-        if ctx.field_profile {
-            batch.field_extract_us += elapsed_us(t_extract);
-        }
-        let t_score = Instant::now();
-        if ctx.field_profile {
-            batch.field_score_us += elapsed_us(t_score);
-            batch.field_total_us += elapsed_us(t_total);
-        }
-        *obj = obj.clone().clamped();
-    }
-
-    batch.candidates = partials.into_iter().map(|(s, o, _, _, _)| (s, o)).collect();
+    batch.candidates = partials.into_iter().map(|(s, o, _)| (s, o)).collect();
     batch
 }
 
@@ -240,29 +363,8 @@ pub(crate) fn select_rules_category_soft(
         return (Vec::new(), BTreeMap::new(), BTreeMap::new());
     }
 
-    let mut availability_counts: BTreeMap<String, usize> = BTreeMap::new();
-    for rule in &rules {
-        *availability_counts
-            .entry(rule_category_name(&rule.category).to_string())
-            .or_insert(0) += 1;
-    }
-
-    let n_total = rules.len() as f64;
-    let k = availability_counts.len().max(1) as f64;
-    let uniform = 1.0 / k;
-    let entropy = shannon_entropy_from_counts(&availability_counts);
-    let t = temperature.max(1e-6);
-
-    let mut scored: Vec<(&DesignRule, f64)> = rules
-        .into_iter()
-        .map(|rule| {
-            let cat = rule_category_name(&rule.category).to_string();
-            let p_i = *availability_counts.get(&cat).unwrap_or(&0) as f64 / n_total;
-            let w_balance = (-alpha * (p_i - uniform)).exp();
-            let s_final = rule.priority * w_balance + entropy_beta * entropy;
-            (rule, s_final / t)
-        })
-        .collect();
+    let (mut scored, availability_counts) =
+        category_soft_logits(rules, alpha, temperature, entropy_beta);
 
     let max_logit = scored
         .iter()
@@ -302,6 +404,40 @@ pub(crate) fn select_rules_category_soft(
     (selected, selected_counts, availability_counts)
 }
 
+/// Category-balanced softmax logits `s_final / T` for each rule, plus the
+/// per-category availability counts they were balanced against.
+pub(crate) fn category_soft_logits(
+    rules: Vec<&DesignRule>,
+    alpha: f64,
+    temperature: f64,
+    entropy_beta: f64,
+) -> (Vec<(&DesignRule, f64)>, BTreeMap<String, usize>) {
+    let mut availability_counts: BTreeMap<String, usize> = BTreeMap::new();
+    for rule in &rules {
+        *availability_counts
+            .entry(rule_category_name(&rule.category).to_string())
+            .or_insert(0) += 1;
+    }
+
+    let n_total = rules.len() as f64;
+    let k = availability_counts.len().max(1) as f64;
+    let uniform = 1.0 / k;
+    let entropy = shannon_entropy_from_counts(&availability_counts);
+    let t = temperature.max(1e-6);
+
+    let scored: Vec<(&DesignRule, f64)> = rules
+        .into_iter()
+        .map(|rule| {
+            let cat = rule_category_name(&rule.category).to_string();
+            let p_i = *availability_counts.get(&cat).unwrap_or(&0) as f64 / n_total;
+            let w_balance = (-alpha * (p_i - uniform)).exp();
+            let s_final = rule.priority * w_balance + entropy_beta * entropy;
+            (rule, s_final / t)
+        })
+        .collect();
+    (scored, availability_counts)
+}
+
 pub(crate) fn format_category_counts(counts: &BTreeMap<String, usize>) -> String {
     if counts.is_empty() {
         return String::new();
@@ -0,0 +1,140 @@
+//! Joins what one correlated run left in the `HybridVM` traces with its
+//! search trace, so a requirement can be followed from the analysis that
+//! created its L1 unit, through the L2 rebuild, to the states the search
+//! kept at each depth.
+//!
+//! Semantic events and VM evaluations are ordered by their shared `seq`.
+//! Search rows carry no `seq`; they are placed at the VM's `trace_seq` when
+//! the search started, in depth order.
+
+use std::fmt::Write;
+
+use hybrid_vm::{CorrelationId, HybridTraceRow, SemanticTraceEvent};
+
+use crate::{DepthFront, TraceRow};
+
+/// Every trace entry tagged with one `CorrelationId`.
+#[derive(Clone, Debug)]
+pub struct CorrelatedTraces {
+    pub correlation_id: CorrelationId,
+    pub semantic: Vec<SemanticTraceEvent>,
+    pub evaluations: Vec<HybridTraceRow>,
+    pub search: Vec<TraceRow>,
+    /// Kept states per depth, matched to `search` rows by depth.
+    pub depth_fronts: Vec<DepthFront>,
+    /// VM `trace_seq` when the search started; search rows go last when
+    /// unknown.
+    pub search_seq: Option<u64>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum TimelineEntry<'a> {
+    Semantic(&'a SemanticTraceEvent),
+    Evaluation(&'a HybridTraceRow),
+    SearchDepth {
+        row: &'a TraceRow,
+        front: Option<&'a DepthFront>,
+    },
+}
+
+impl CorrelatedTraces {
+    /// Keeps the entries of each trace tagged with `correlation_id`.
+    pub fn join(
+        correlation_id: CorrelationId,
+        semantic: &[SemanticTraceEvent],
+        evaluations: &[HybridTraceRow],
+        search: &[TraceRow],
+    ) -> Self {
+        let tag = correlation_id.to_string();
+        Self {
+            correlation_id,
+            semantic: semantic
+                .iter()
+                .filter(|e| e.correlation_id == Some(correlation_id))
+                .cloned()
+                .collect(),
+            evaluations: evaluations
+                .iter()
+                .filter(|r| r.correlation_id == Some(correlation_id))
+                .cloned()
+                .collect(),
+            search: search
+                .iter()
+                .filter(|r| r.correlation_id == tag)
+                .cloned()
+                .collect(),
+            depth_fronts: Vec::new(),
+            search_seq: None,
+        }
+    }
+
+    pub fn with_depth_fronts(mut self, depth_fronts: Vec<DepthFront>) -> Self {
+        self.depth_fronts = depth_fronts;
+        self
+    }
+
+    pub fn with_search_seq(mut self, search_seq: Option<u64>) -> Self {
+        self.search_seq = search_seq;
+        self
+    }
+
+    /// All entries, oldest first.
+    pub fn timeline(&self) -> Vec<TimelineEntry<'_>> {
+        let mut vm = self
+            .semantic
+            .iter()
+            .map(|e| (e.seq, TimelineEntry::Semantic(e)))
+            .chain(
+                self.evaluations
+                    .iter()
+                    .map(|r| (r.seq, TimelineEntry::Evaluation(r))),
+            )
+            .collect::<Vec<_>>();
+        vm.sort_by_key(|(seq, _)| *seq);
+        let mut search = self.search.iter().collect::<Vec<_>>();
+        search.sort_by_key(|r| r.depth);
+        let split = self
+            .search_seq
+            .map_or(vm.len(), |at| vm.partition_point(|(seq, _)| *seq < at));
+        let after = vm.split_off(split);
+        vm.into_iter()
+            .map(|(_, entry)| entry)
+            .chain(search.into_iter().map(|row| TimelineEntry::SearchDepth {
+                row,
+                front: self.depth_fronts.iter().find(|f| f.depth == row.depth),
+            }))
+            .chain(after.into_iter().map(|(_, entry)| entry))
+            .collect()
+    }
+
+    /// One line per timeline entry, led by the correlation id.
+    pub fn render(&self) -> String {
+        let mut out = format!("correlation {}\n", self.correlation_id);
+        for entry in self.timeline() {
+            match entry {
+                TimelineEntry::Semantic(event) => {
+                    let _ = writeln!(out, "[{}] {}", event.seq, event.operation);
+                }
+                TimelineEntry::Evaluation(row) => {
+                    let o = &row.objective;
+                    let _ = writeln!(
+                        out,
+                        "[{}] evaluate depth {} ({:?}): f_struct {:.2}, f_field {:.2}, f_risk {:.2}, f_shape {:.2}",
+                        row.seq, row.depth, row.mode, o.f_struct, o.f_field, o.f_risk, o.f_shape
+                    );
+                }
+                TimelineEntry::SearchDepth { row, front } => {
+                    let states = front.map_or_else(String::new, |f| {
+                        f.state_ids
+                            .iter()
+                            .map(|id| format!("{:032x}", id.as_u128()))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    });
+                    let _ = writeln!(out, "[search] depth {}: kept [{states}]", row.depth);
+                }
+            }
+        }
+        out
+    }
+}
@@ -0,0 +1,72 @@
+//! JSON Schemas for the data formats this crate and the VM export.
+//!
+//! Each schema describes the payload (the `data` field where a
+//! `core_types::Versioned` envelope is used) and is written as
+//! `<kind>.v<version>.schema.json`, so tooling can pick the schema matching
+//! an export's `kind`/`version` tags.
+
+use std::io;
+use std::path::{Path, PathBuf};
+
+use core_types::SchemaVersioned;
+use hybrid_vm::{DecisionReport, DesignCard, SimulationReport};
+use schemars::{JsonSchema, Schema};
+
+use crate::TraceRow;
+use crate::field_projection::FieldProjectionExport;
+use crate::front_plot::FrontPlot;
+use crate::result_repository::RunManifest;
+
+#[derive(Clone, Debug)]
+pub struct ExportSchema {
+    pub kind: &'static str,
+    pub version: u32,
+    pub schema: Schema,
+}
+
+impl ExportSchema {
+    pub fn of<T: JsonSchema>(kind: &'static str, version: u32) -> Self {
+        Self {
+            kind,
+            version,
+            schema: schemars::schema_for!(T),
+        }
+    }
+
+    pub fn versioned<T: JsonSchema + SchemaVersioned>() -> Self {
+        Self::of::<T>(T::KIND, T::VERSION)
+    }
+
+    pub fn file_name(&self) -> String {
+        format!("{}.v{}.schema.json", self.kind, self.version)
+    }
+}
+
+pub fn all() -> Vec<ExportSchema> {
+    vec![
+        ExportSchema::versioned::<TraceRow>(),
+        ExportSchema::versioned::<RunManifest>(),
+        ExportSchema::versioned::<SimulationReport>(),
+        ExportSchema::versioned::<DesignCard>(),
+        ExportSchema::versioned::<FieldProjectionExport>(),
+        ExportSchema::versioned::<FrontPlot>(),
+        // recomposer has no `SchemaVersioned` impl; the version lives here.
+        ExportSchema::of::<DecisionReport>("decision_report", 1),
+    ]
+}
+
+/// Writes every schema from `all` into `dir` (created if needed) and
+/// returns the written paths.
+pub fn write_all(dir: impl AsRef<Path>) -> io::Result<Vec<PathBuf>> {
+    let dir = dir.as_ref();
+    std::fs::create_dir_all(dir)?;
+    let mut written = Vec::new();
+    for export in all() {
+        let path = dir.join(export.file_name());
+        let json = serde_json::to_string_pretty(&export.schema)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        std::fs::write(&path, json)?;
+        written.push(path);
+    }
+    Ok(written)
+}
@@ -4,3 +4,9 @@ mod deterministic;
 mod hv_policy_contract;
 #[path = "contract/hypervolume_monotonicity.rs"]
 mod hypervolume_monotonicity;
+#[path = "contract/pack_registry.rs"]
+mod pack_registry;
+#[path = "contract/schemas.rs"]
+mod schemas;
+#[path = "contract/serde_roundtrip.rs"]
+mod serde_roundtrip;
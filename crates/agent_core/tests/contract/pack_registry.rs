@@ -0,0 +1,112 @@
+#[cfg(feature = "serde")]
+#[test]
+fn pack_registry_installs_verifies_and_loads_packs_by_version() {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use agent_core::domain_profile::DomainProfile;
+    use agent_core::pack_registry::{PackBundle, PackError, PackRegistry, PackVersion};
+
+    let root = std::env::temp_dir().join(format!(
+        "agent_core_pack_registry_{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos()
+    ));
+    let registry = PackRegistry::open(&root).expect("open");
+    let rules = hybrid_vm::Shm::with_default_rules().rules().to_vec();
+    let bundle = |name: &str, version: &str, range: std::ops::Range<usize>| PackBundle {
+        name: name.to_string(),
+        version: version.parse().expect("version"),
+        description: format!("{name} rules"),
+        rules: rules[range].to_vec(),
+        profile: None,
+    };
+    registry
+        .install(&bundle("core", "1.0.0", 0..2))
+        .expect("install");
+    let latest = registry
+        .install(&PackBundle {
+            profile: Some(DomainProfile::embedded()),
+            ..bundle("core", "1.10.0", 0..2)
+        })
+        .expect("install");
+    let extra = registry
+        .install(&bundle("extra", "0.1.0", 2..4))
+        .expect("install");
+    let clash = registry
+        .install(&bundle("clash", "1.0.0", 1..2))
+        .expect("install");
+
+    assert!(matches!(
+        registry.install(&bundle("core", "1.0.0", 0..1)),
+        Err(PackError::AlreadyInstalled(_))
+    ));
+    assert!(matches!(
+        registry.install(&bundle("../core", "1.0.0", 0..1)),
+        Err(PackError::InvalidName(_))
+    ));
+    assert!("1.2".parse::<PackVersion>().is_err());
+    let listed = registry
+        .list()
+        .expect("list")
+        .iter()
+        .map(|m| m.pack_ref().to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        listed,
+        vec!["clash@1.0.0", "core@1.0.0", "core@1.10.0", "extra@0.1.0"]
+    );
+    assert_eq!(registry.resolve("core", None).expect("resolve"), latest);
+    assert!(matches!(
+        registry.resolve("core", Some(PackVersion::new(2, 0, 0))),
+        Err(PackError::NotFound { .. })
+    ));
+
+    let shm = registry
+        .load_shm(&[latest.pack_ref(), extra.pack_ref()])
+        .expect("load");
+    assert_eq!(
+        shm.rules().iter().map(|r| r.id).collect::<Vec<_>>(),
+        rules[..4].iter().map(|r| r.id).collect::<Vec<_>>()
+    );
+    let Err(PackError::RuleIdOverlap(overlaps)) =
+        registry.load_shm(&[latest.pack_ref(), clash.pack_ref()])
+    else {
+        panic!("overlapping rule ids must not load");
+    };
+    assert_eq!(overlaps.len(), 1);
+    assert_eq!(overlaps[0].rule_id, rules[1].id);
+    assert_eq!(overlaps[0].packs, vec![latest.pack_ref(), clash.pack_ref()]);
+
+    let profile = registry.load_profile(&latest.pack_ref()).expect("profile");
+    assert_eq!(profile, DomainProfile::embedded());
+    assert!(matches!(
+        registry.load_profile(&extra.pack_ref()),
+        Err(PackError::MissingProfile(_))
+    ));
+
+    let manifest: serde_json::Value = serde_json::from_str(
+        &std::fs::read_to_string(root.join("core/1.10.0/manifest.json")).expect("read"),
+    )
+    .expect("json");
+    assert_eq!(manifest["kind"], "pack_manifest");
+    assert_eq!(manifest["data"]["version"], "1.10.0");
+
+    let rules_file = root.join("extra/0.1.0/rules.json");
+    let tampered = std::fs::read_to_string(&rules_file)
+        .expect("read")
+        .replace("0.", "1.");
+    std::fs::write(&rules_file, tampered).expect("write");
+    assert!(matches!(
+        registry.verify(&extra.pack_ref()),
+        Err(PackError::ChecksumMismatch {
+            actual: Some(_),
+            ..
+        })
+    ));
+    assert!(registry.load_shm(&[extra.pack_ref()]).is_err());
+    assert!(registry.uninstall(&extra.pack_ref()).expect("uninstall"));
+    assert!(registry.versions("extra").expect("versions").is_empty());
+    let _ = std::fs::remove_dir_all(root);
+}
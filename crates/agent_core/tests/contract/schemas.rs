@@ -0,0 +1,39 @@
+#[cfg(feature = "schema")]
+#[test]
+fn schemas_are_written_per_kind_and_version() {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    let dir = std::env::temp_dir().join(format!(
+        "agent_core_schemas_{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos()
+    ));
+    let written = agent_core::schemas::write_all(&dir).expect("write schemas");
+    let names = written
+        .iter()
+        .filter_map(|p| p.file_name()?.to_str().map(str::to_string))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            "trace_row.v1.schema.json",
+            "run_manifest.v1.schema.json",
+            "simulation_report.v1.schema.json",
+            "design_card.v1.schema.json",
+            "field_projection.v1.schema.json",
+            "front_plot.v1.schema.json",
+            "decision_report.v1.schema.json",
+        ]
+    );
+
+    let raw = std::fs::read_to_string(dir.join("trace_row.v1.schema.json")).expect("read");
+    let schema: serde_json::Value = serde_json::from_str(&raw).expect("json");
+    assert_eq!(schema["title"], "TraceRow");
+    assert!(schema["properties"]["convergence_reason"].is_object());
+    let required = schema["required"].as_array().expect("required");
+    assert!(required.iter().any(|f| f == "depth"));
+    assert!(!required.iter().any(|f| f == "convergence_reason"));
+    let _ = std::fs::remove_dir_all(dir);
+}
@@ -0,0 +1,226 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use agent_core::capability::{
+    AlphaTrace, Invariant, InvariantViolation, SuggestedRule, SuggestionOutcome,
+    SuggestionRejection,
+};
+use agent_core::pipeline::{DesignPipeline, PipelineCheckpoint, PipelineStage};
+use agent_core::{
+    LexicographicTieStats, ManualChoice, NormalizationConfig, SearchResult, TraceRow,
+    TraceRunConfig, WarmupConfig,
+};
+use core_types::Versioned;
+use hybrid_vm::{HybridVM, PreconditionError, StructuralEvaluator};
+use memory_space::Uuid;
+
+fn round_trip<T>(value: T) -> T
+where
+    T: core_types::SchemaVersioned + serde::Serialize + serde::de::DeserializeOwned,
+{
+    let json = serde_json::to_string(&Versioned::new(value)).expect("serialize");
+    serde_json::from_str::<Versioned<T>>(&json)
+        .expect("deserialize")
+        .into_checked()
+        .expect("schema")
+}
+
+#[test]
+fn trace_rows_round_trip() {
+    let rows = agent_core::generate_trace(TraceRunConfig {
+        depth: 2,
+        beam: 2,
+        seed: 11,
+        norm_alpha: 0.1,
+        adaptive_alpha: false,
+        hv_guided: false,
+        raw_output_path: None,
+        normalization: NormalizationConfig::default(),
+        warmup: WarmupConfig::default(),
+        calibration: None,
+        convergence: None,
+        field_dimensions: agent_core::DEFAULT_FIELD_DIMENSIONS,
+        novelty: None,
+        repair: None,
+        candidate_pipeline: agent_core::CandidatePipelineConfig::default(),
+        diversity: None,
+        directions: agent_core::ObjectiveDirections::default(),
+    });
+    assert!(!rows.is_empty());
+    for row in rows {
+        let decoded: TraceRow = round_trip(row.clone());
+        assert_eq!(decoded, row);
+    }
+}
+
+#[test]
+fn pipeline_checkpoint_and_search_result_round_trip() {
+    let dir = std::env::temp_dir().join(format!(
+        "agent_core_serde_{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos()
+    ));
+    let mut pipeline = DesignPipeline::new(HybridVM::for_cli_storage(dir).expect("vm"));
+    pipeline.run("高速化を重視する").expect("pipeline run");
+    let checkpoint = pipeline.checkpoint().clone();
+    assert_eq!(checkpoint.last_completed, Some(PipelineStage::Artifacts));
+
+    let decoded: PipelineCheckpoint = round_trip(checkpoint.clone());
+    assert_eq!(decoded.text, checkpoint.text);
+    assert_eq!(decoded.concepts, checkpoint.concepts);
+    assert_eq!(decoded.depth_fronts, checkpoint.depth_fronts);
+    assert_eq!(decoded.artifacts, checkpoint.artifacts);
+    assert_eq!(decoded.cards.len(), checkpoint.cards.len());
+    assert_eq!(
+        decoded.initial_state.map(|s| s.graph),
+        checkpoint.initial_state.map(|s| s.graph)
+    );
+
+    let result = SearchResult {
+        final_frontier: checkpoint
+            .pareto_front
+            .iter()
+            .map(|e| e.state.clone())
+            .collect(),
+        depth_fronts: checkpoint.depth_fronts.clone(),
+        targets_met_at: Some(2),
+        objective_variance: checkpoint
+            .pareto_front
+            .iter()
+            .map(|e| (e.state.id, e.variance.clone()))
+            .collect(),
+        search_tree: None,
+        crossover: Default::default(),
+        dispatch: Default::default(),
+        budget: Default::default(),
+        archive: None,
+        quarantined: Vec::new(),
+        lexicographic_ties: vec![LexicographicTieStats {
+            depth: 1,
+            candidates: 5,
+            tied: vec![3, 1],
+        }],
+        invariant_violations: vec![InvariantViolation {
+            depth: 2,
+            invariant: Invariant::UniqueStateIds,
+            state_id: Some(Uuid::from_u128(12)),
+            detail: "state appears 2 times in the beam".into(),
+        }],
+        attributions: checkpoint
+            .pareto_front
+            .iter()
+            .map(|e| {
+                (
+                    e.state.id,
+                    StructuralEvaluator::default().explain_state(&e.state),
+                )
+            })
+            .collect(),
+        manual_choices: vec![ManualChoice {
+            depth: 1,
+            offered: vec![Uuid::from_u128(11), Uuid::from_u128(12)],
+            selected: vec![Uuid::from_u128(12)],
+            rules: vec![Uuid::from_u128(3)],
+        }],
+        suggestions: vec![SuggestedRule {
+            suggester: "heuristic".into(),
+            rule_id: Uuid::from_u128(21),
+            parent: Uuid::from_u128(11),
+            depth: 1,
+            outcome: SuggestionOutcome::Rejected(SuggestionRejection::InvalidPrecondition(
+                PreconditionError::EmptyRange,
+            )),
+        }],
+    };
+    let decoded: SearchResult = round_trip(result.clone());
+    assert_eq!(decoded.manual_choices, result.manual_choices);
+    assert_eq!(decoded.suggestions, result.suggestions);
+    assert_eq!(decoded.depth_fronts, result.depth_fronts);
+    assert_eq!(decoded.targets_met_at, Some(2));
+    assert_eq!(decoded.lexicographic_ties, result.lexicographic_ties);
+    assert_eq!(decoded.invariant_violations, result.invariant_violations);
+    assert_eq!(decoded.objective_variance, result.objective_variance);
+    // serde_json's default float parsing may be off by an ulp.
+    assert_eq!(
+        decoded.attributions.keys().collect::<Vec<_>>(),
+        result.attributions.keys().collect::<Vec<_>>()
+    );
+    for (decoded, original) in decoded
+        .attributions
+        .values()
+        .zip(result.attributions.values())
+    {
+        for (d, o) in decoded.axes().into_iter().zip(original.axes()) {
+            assert_eq!(d.len(), o.len());
+            for (d, o) in d.iter().zip(o) {
+                assert_eq!(d.name, o.name);
+                assert!((d.value - o.value).abs() < 1e-12);
+            }
+        }
+    }
+    assert_eq!(
+        decoded
+            .final_frontier
+            .iter()
+            .map(|s| s.id)
+            .collect::<Vec<_>>(),
+        result
+            .final_frontier
+            .iter()
+            .map(|s| s.id)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn alpha_traces_save_and_load_for_replay() {
+    let result = agent_core::capability::execute_soft_search_core(
+        TraceRunConfig {
+            depth: 3,
+            beam: 2,
+            seed: 11,
+            norm_alpha: 0.1,
+            adaptive_alpha: true,
+            hv_guided: false,
+            raw_output_path: None,
+            normalization: NormalizationConfig::default(),
+            warmup: WarmupConfig {
+                warmup_depths: 1,
+                refresh_interval: None,
+            },
+            calibration: None,
+            convergence: None,
+            field_dimensions: agent_core::DEFAULT_FIELD_DIMENSIONS,
+            novelty: None,
+            repair: None,
+            candidate_pipeline: agent_core::CandidatePipelineConfig::default(),
+            diversity: None,
+            directions: agent_core::ObjectiveDirections::default(),
+        },
+        agent_core::SoftTraceParams::default(),
+    );
+    let trace = result.alpha_trace.expect("adaptive run");
+    let path = std::env::temp_dir().join(format!(
+        "agent_core_alpha_trace_{}.json",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos()
+    ));
+    trace.save(&path).expect("save");
+    let loaded = AlphaTrace::load(&path).expect("load");
+    std::fs::remove_file(&path).ok();
+    assert_eq!(loaded.config, trace.config);
+    assert_eq!(loaded.decisions.len(), trace.decisions.len());
+    for (loaded, recorded) in loaded.decisions.iter().zip(&trace.decisions) {
+        assert_eq!(
+            (loaded.depth, loaded.branch),
+            (recorded.depth, recorded.branch)
+        );
+        assert_eq!(loaded.steps.is_some(), recorded.steps.is_some());
+        assert!((loaded.alpha - recorded.alpha).abs() < 1e-12);
+    }
+    let decoded: AlphaTrace = round_trip(trace.clone());
+    assert_eq!(decoded.decisions.len(), trace.decisions.len());
+}
@@ -1,6 +1,74 @@
+#[path = "engine/adaptive_alpha.rs"]
+mod adaptive_alpha;
+#[path = "engine/apply_props.rs"]
+mod apply_props;
+#[path = "engine/beam.rs"]
+mod beam;
+#[path = "engine/budget.rs"]
+mod budget;
+#[path = "engine/calibration.rs"]
+mod calibration;
+#[path = "engine/candidate_pipeline.rs"]
+mod candidate_pipeline;
+#[path = "engine/common.rs"]
+mod common;
+#[path = "engine/convergence.rs"]
+mod convergence;
+#[path = "engine/cost_model.rs"]
+mod cost_model;
+#[path = "engine/crossover.rs"]
+mod crossover;
 #[path = "engine/diversity.rs"]
 mod diversity;
+#[path = "engine/domain_profile.rs"]
+mod domain_profile;
+#[path = "engine/elicitation.rs"]
+mod elicitation;
+#[path = "engine/equivalence.rs"]
+mod equivalence;
+#[path = "engine/field_projection.rs"]
+mod field_projection;
+#[path = "engine/front_plot.rs"]
+mod front_plot;
 #[path = "engine/hypervolume.rs"]
 mod hypervolume;
+#[path = "engine/improvement.rs"]
+mod improvement;
+#[path = "engine/normalization.rs"]
+mod normalization;
+#[path = "engine/novelty.rs"]
+mod novelty;
 #[path = "engine/pareto.rs"]
 mod pareto;
+#[path = "engine/performance.rs"]
+mod performance;
+#[path = "engine/pipeline.rs"]
+mod pipeline;
+#[path = "engine/playground.rs"]
+mod playground;
+#[path = "engine/preview.rs"]
+mod preview;
+#[path = "engine/reliability.rs"]
+mod reliability;
+#[path = "engine/repair.rs"]
+mod repair;
+#[path = "engine/representatives.rs"]
+mod representatives;
+#[path = "engine/resource_budget.rs"]
+mod resource_budget;
+#[path = "engine/result_repository.rs"]
+mod result_repository;
+#[path = "engine/rewrite.rs"]
+mod rewrite;
+#[path = "engine/rule_sampling.rs"]
+mod rule_sampling;
+#[path = "engine/semantic_bench.rs"]
+mod semantic_bench;
+#[path = "engine/state_archive.rs"]
+mod state_archive;
+#[path = "engine/suggestion.rs"]
+mod suggestion;
+#[path = "engine/sweep.rs"]
+mod sweep;
+#[path = "engine/trace_join.rs"]
+mod trace_join;
@@ -0,0 +1,160 @@
+use agent_core::capability::{
+    AlphaBranch, AlphaConfig, AlphaInputs, AlphaTrace, decide_alpha, execute_soft_search_core,
+};
+use agent_core::{
+    AdaptiveAlphaState, GlobalRobustStats, NormalizationConfig, SoftTraceParams, TraceRunConfig,
+    WarmupConfig, calculate_adaptive_alpha,
+};
+
+fn inputs(alpha: f64, strong_dims: usize, weak_dims: usize, mean_nn_dist: f64) -> AlphaInputs {
+    AlphaInputs {
+        alpha,
+        strong_dims,
+        weak_dims,
+        mean_nn_dist,
+        pareto_size: 3,
+        d_target: 0.01,
+        effective_dim: strong_dims + weak_dims,
+    }
+}
+
+#[test]
+fn decisions_record_the_branch_and_every_step() {
+    let config = AlphaConfig::default();
+
+    let held = decide_alpha(&config, 4, inputs(0.05, 1, 1, 0.0));
+    assert_eq!(held.branch, AlphaBranch::LowDimension);
+    assert_eq!((held.steps, held.alpha), (None, 0.05));
+
+    let fed = decide_alpha(&config, 5, inputs(0.05, 2, 1, 0.005));
+    assert_eq!(fed.branch, AlphaBranch::Feedback);
+    let steps = fed.steps.expect("controller ran");
+    assert!((steps.base - (0.01 + 0.19 / 6.0)).abs() < 1e-12);
+    assert!((steps.feedback - (steps.base + 0.025)).abs() < 1e-12);
+    assert!((steps.target - (0.04 + 0.2 * steps.feedback)).abs() < 1e-12);
+    assert!(!steps.safety_valve && !steps.clamped);
+    assert_eq!(fed.alpha, steps.target);
+
+    let valved = decide_alpha(&config, 6, inputs(0.2, 1, 3, 0.0));
+    let steps = valved.steps.expect("controller ran");
+    let cap = 0.35 / (3.0 * 0.65);
+    assert!((steps.target - 0.21).abs() < 1e-12);
+    assert_eq!(steps.safety_cap, Some(cap));
+    assert!(steps.safety_valve && !steps.clamped);
+    assert_eq!(valved.alpha, cap);
+
+    let clamped = decide_alpha(&config, 7, inputs(0.01, 3, 0, 0.05));
+    let steps = clamped.steps.expect("controller ran");
+    assert_eq!(steps.safety_cap, None);
+    assert!(steps.clamped && steps.unclamped < config.alpha_min);
+    assert_eq!(clamped.alpha, config.alpha_min);
+
+    let kept = decide_alpha(&config, 8, inputs(0.05, 2, 1, 0.0105));
+    assert_eq!(kept.branch, AlphaBranch::Deadband);
+    assert_eq!(kept.alpha, 0.05);
+}
+
+#[test]
+fn calculate_adaptive_alpha_holds_the_state_below_three_dimensions() {
+    let stats = GlobalRobustStats {
+        median: [0.0; 4],
+        mad: [1.0; 4],
+        mean: [0.0; 4],
+        std: [1.0; 4],
+        active_dims: [true, true, true, false],
+        weak_dims: [false, false, true, false],
+        weights: [1.0; 4],
+        mad_zero_count: 0,
+        alpha_used: 0.05,
+    };
+    let state = AdaptiveAlphaState {
+        d_prev: 0.3,
+        ..AdaptiveAlphaState::new(0.05)
+    };
+    assert_eq!(
+        calculate_adaptive_alpha(&state, &stats, 0.005, 3, 0.01, 2),
+        state
+    );
+
+    let next = calculate_adaptive_alpha(&state, &stats, 0.005, 3, 0.01, 3);
+    let expected = decide_alpha(&AlphaConfig::default(), 0, inputs(0.05, 2, 1, 0.005));
+    assert_eq!(next.alpha, expected.alpha);
+    assert_eq!((next.alpha_prev, next.d_prev), (0.05, 0.005));
+}
+
+fn adaptive_config() -> TraceRunConfig {
+    TraceRunConfig {
+        depth: 6,
+        beam: 4,
+        seed: 5,
+        norm_alpha: 0.1,
+        adaptive_alpha: true,
+        hv_guided: false,
+        raw_output_path: None,
+        normalization: NormalizationConfig::default(),
+        warmup: WarmupConfig {
+            warmup_depths: 2,
+            refresh_interval: None,
+        },
+        calibration: None,
+        convergence: None,
+        field_dimensions: 16,
+        novelty: None,
+        repair: None,
+        candidate_pipeline: agent_core::CandidatePipelineConfig::default(),
+        diversity: None,
+        directions: agent_core::ObjectiveDirections::default(),
+    }
+}
+
+#[test]
+fn trace_runs_log_one_decision_per_depth_and_replay_it() {
+    let result = execute_soft_search_core(adaptive_config(), SoftTraceParams::default());
+    let trace = result.alpha_trace.expect("adaptive run");
+    assert_eq!(trace.decisions.len(), result.trace.len());
+    for (decision, row) in trace.decisions.iter().zip(&result.trace) {
+        assert_eq!(decision.depth, row.depth);
+        assert_eq!(decision.inputs.alpha as f32, row.alpha_t);
+        assert_eq!(decision.branch == AlphaBranch::Warmup, row.depth <= 2);
+    }
+    assert_eq!(trace.branch_count(AlphaBranch::Warmup), 2);
+
+    assert_eq!(trace.replay(trace.config, trace.initial_alpha), trace);
+
+    let capped = AlphaConfig {
+        alpha_max: 0.02,
+        ..AlphaConfig::default()
+    };
+    let replayed = trace.replay(capped, 0.02);
+    assert_eq!(replayed.decisions.len(), trace.decisions.len());
+    for (replay, recorded) in replayed.decisions.iter().zip(&trace.decisions) {
+        assert_eq!(
+            replay.branch == AlphaBranch::Warmup,
+            recorded.branch == AlphaBranch::Warmup
+        );
+        assert_eq!(replay.inputs.mean_nn_dist, recorded.inputs.mean_nn_dist);
+        assert!(replay.alpha <= 0.02);
+    }
+
+    let fixed = execute_soft_search_core(
+        TraceRunConfig {
+            adaptive_alpha: false,
+            ..adaptive_config()
+        },
+        SoftTraceParams::default(),
+    );
+    assert_eq!(fixed.alpha_trace, None);
+}
+
+#[test]
+fn an_empty_trace_replays_to_its_initial_alpha() {
+    let trace = AlphaTrace::new(AlphaConfig::default(), 0.07);
+    assert_eq!(trace.alpha(), 0.07);
+    assert!(
+        trace
+            .replay(AlphaConfig::default(), 0.03)
+            .decisions
+            .is_empty()
+    );
+    assert!(trace.safety_valve_depths().is_empty());
+}
@@ -0,0 +1,167 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use agent_core::apply_atomic;
+use hybrid_vm::{DesignRule, EffectVector, Precondition, RuleCategory, Transformation};
+use memory_space::{DesignNode, DesignState, StructuralGraph, Uuid};
+use proptest::prelude::*;
+
+fn transformation() -> impl Strategy<Value = Transformation> {
+    prop_oneof![
+        Just(Transformation::AddNode),
+        Just(Transformation::RemoveNode),
+        Just(Transformation::ModifyAttribute),
+        Just(Transformation::AddConstraint),
+        Just(Transformation::RewireDependency),
+    ]
+}
+
+fn rule(id: u128, transformation: Transformation) -> DesignRule {
+    DesignRule {
+        id: Uuid::from_u128(id),
+        category: RuleCategory::Structural,
+        priority: 0.5,
+        precondition: Precondition::Always,
+        transformation,
+        expected_effect: EffectVector {
+            delta_struct: 0.0,
+            delta_field: 0.0,
+            delta_risk: 0.0,
+            delta_cost: 0.0,
+        },
+    }
+}
+
+/// Builds a seed graph from arbitrary node ids and edge index pairs; edges
+/// that would dangle or close a cycle are dropped by `with_edge_added`.
+fn seed_graph() -> impl Strategy<Value = StructuralGraph> {
+    (
+        proptest::collection::btree_set(1u128..64, 0..8usize),
+        proptest::collection::vec((0usize..8, 0usize..8), 0..12usize),
+    )
+        .prop_map(|(ids, edges)| {
+            let ids = ids.into_iter().collect::<Vec<_>>();
+            let mut graph = StructuralGraph::default();
+            for id in &ids {
+                graph = graph.with_node_added(DesignNode::new(
+                    Uuid::from_u128(*id),
+                    "Seed",
+                    BTreeMap::new(),
+                ));
+            }
+            if !ids.is_empty() {
+                for (from, to) in edges {
+                    graph = graph.with_edge_added(
+                        Uuid::from_u128(ids[from % ids.len()]),
+                        Uuid::from_u128(ids[to % ids.len()]),
+                    );
+                }
+            }
+            graph
+        })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(256))]
+
+    /// 任意の規則列を適用しても、グラフ不変条件と履歴長が保たれる
+    #[test]
+    fn apply_atomic_sequences_preserve_graph_invariants(
+        graph in seed_graph(),
+        steps in proptest::collection::vec((1000u128..1010, transformation()), 1..24usize),
+    ) {
+        let mut state = DesignState::new(Uuid::from_u128(1), Arc::new(graph), "history:");
+        for (idx, (rule_id, transformation)) in steps.into_iter().enumerate() {
+            let before_nodes = state.graph.nodes().len();
+            let next = apply_atomic(&rule(rule_id, transformation.clone()), &state)
+                .expect("valid seed graphs stay valid");
+            prop_assert_eq!(next.graph.validate(), Ok(()));
+
+            let after_nodes = next.graph.nodes().len();
+            match transformation {
+                Transformation::AddNode => prop_assert!(after_nodes <= before_nodes + 1),
+                Transformation::RemoveNode => {
+                    prop_assert_eq!(after_nodes, before_nodes.saturating_sub(1))
+                }
+                _ => prop_assert_eq!(after_nodes, before_nodes),
+            }
+            let history_len = next
+                .profile_snapshot
+                .trim_start_matches("history:")
+                .split(',')
+                .filter(|s| !s.is_empty())
+                .count();
+            prop_assert_eq!(history_len, idx + 1);
+            state = next;
+        }
+    }
+
+    /// 上限を超える属性を持つ種グラフでは、パニックせず違反を返す
+    /// (検証はデバッグビルドのみ)
+    #[cfg(debug_assertions)]
+    #[test]
+    fn apply_atomic_reports_oversized_attributes(
+        graph in seed_graph(),
+        extra in 1usize..64,
+        transformation in transformation(),
+    ) {
+        use agent_core::ApplyError;
+        use memory_space::{GraphViolation, MAX_ATTRIBUTE_BYTES, Value};
+
+        // Below every seed id and paired with a node above them, so
+        // `RemoveNode` (which drops the largest id) never removes it.
+        let oversized = Uuid::from_u128(0);
+        let mut attrs = BTreeMap::new();
+        attrs.insert(
+            "blob".to_string(),
+            Value::Text("x".repeat(MAX_ATTRIBUTE_BYTES + extra)),
+        );
+        let graph = graph
+            .with_node_added(DesignNode::new(oversized, "Seed", attrs))
+            .with_node_added(DesignNode::new(Uuid::from_u128(64), "Seed", BTreeMap::new()));
+        let state = DesignState::new(Uuid::from_u128(1), Arc::new(graph), "history:");
+
+        let err = apply_atomic(&rule(1000, transformation), &state)
+            .expect_err("oversized attribute must be rejected");
+        let ApplyError::InvalidResult(violations) = err;
+        let reported = violations.iter().any(|v| {
+            matches!(
+                v,
+                GraphViolation::AttributeTooLarge { node, key, .. }
+                    if *node == oversized && key == "blob"
+            )
+        });
+        prop_assert!(reported);
+    }
+}
+
+#[test]
+fn add_constraint_changes_the_structural_objective() {
+    use hybrid_vm::{AttributeWeights, Evaluator, StructuralEvaluator};
+
+    let mut graph = StructuralGraph::default();
+    for id in 1..=3u128 {
+        graph = graph.with_node_added(DesignNode::new(
+            Uuid::from_u128(id),
+            "Seed",
+            BTreeMap::new(),
+        ));
+    }
+    let graph = graph
+        .with_edge_added(Uuid::from_u128(1), Uuid::from_u128(2))
+        .with_edge_added(Uuid::from_u128(2), Uuid::from_u128(3));
+    let before = DesignState::new(Uuid::from_u128(1), Arc::new(graph), "history:");
+    let after = apply_atomic(&rule(1002, Transformation::AddConstraint), &before).expect("apply");
+    assert_eq!(before.graph.edges(), after.graph.edges());
+
+    let evaluator =
+        StructuralEvaluator::default().with_attribute_weights(AttributeWeights::BALANCED);
+    assert!(evaluator.evaluate(&after).f_struct > evaluator.evaluate(&before).f_struct);
+
+    let topology_only =
+        StructuralEvaluator::default().with_attribute_weights(AttributeWeights::NONE);
+    assert_eq!(
+        topology_only.evaluate(&after).f_struct,
+        topology_only.evaluate(&before).f_struct
+    );
+}
@@ -0,0 +1,988 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use agent_core::capability::{
+    DEFAULT_MAX_TREE_NODES, DispatchConfig, DispatchStats, EvaluationDispatcher,
+    InfallibleEvaluator, Invariant, StateArchive, check_constrained_order, check_normalized,
+    check_soft_front_order, check_unique_ids, evaluate_with_policy, non_finite_axes,
+};
+use agent_core::{
+    Aggregator, BeamSearch, EpsilonConstraint, EvaluationPolicy, LexicographicOrder,
+    ManualSelectionError, NormalizationConfig, ObjectiveAxis, ObjectiveDirections,
+    ObjectiveTargets, SearchConfig, SearchHooks, SearchMode, WarmupConfig,
+};
+use core_types::ObjectiveVector;
+use core_types::progress::ChannelProgress;
+use field_engine::{FieldEngine, TargetField};
+use hybrid_vm::{Evaluator, HybridVM, StructuralEvaluator};
+use memory_space::{DesignNode, DesignState, DesignStateDocument, StructuralGraph, Uuid};
+
+struct NodeCountEvaluator;
+
+impl Evaluator for NodeCountEvaluator {
+    fn evaluate(&self, state: &DesignState) -> ObjectiveVector {
+        let nodes = state.graph.nodes().len() as f64;
+        ObjectiveVector {
+            f_struct: (nodes / 10.0).min(1.0),
+            f_field: 0.5,
+            f_risk: 0.5,
+            f_shape: 0.5,
+        }
+    }
+}
+
+/// NaN `f_field` on even node counts, infinite `f_risk` past five nodes.
+struct PoisonedEvaluator;
+
+impl Evaluator for PoisonedEvaluator {
+    fn evaluate(&self, state: &DesignState) -> ObjectiveVector {
+        let nodes = state.graph.nodes().len();
+        ObjectiveVector {
+            f_struct: (nodes as f64 / 10.0).min(1.0),
+            f_field: if nodes.is_multiple_of(2) {
+                f64::NAN
+            } else {
+                0.5
+            },
+            f_risk: if nodes > 5 { f64::INFINITY } else { 0.5 },
+            f_shape: 0.5,
+        }
+    }
+}
+
+fn seed_state() -> DesignState {
+    let mut graph = StructuralGraph::default();
+    for i in 1..=3u128 {
+        graph = graph.with_node_added(DesignNode::new(
+            Uuid::from_u128(i),
+            format!("N{i}"),
+            BTreeMap::new(),
+        ));
+    }
+    graph = graph
+        .with_edge_added(Uuid::from_u128(1), Uuid::from_u128(2))
+        .with_edge_added(Uuid::from_u128(2), Uuid::from_u128(3));
+    DesignState::new(Uuid::from_u128(100), Arc::new(graph), "history:")
+}
+
+fn config(targets: Option<ObjectiveTargets>) -> SearchConfig {
+    SearchConfig {
+        beam_width: 4,
+        max_depth: 4,
+        norm_alpha: 0.1,
+        targets,
+        evaluation: EvaluationPolicy::Single,
+        record_tree: false,
+        max_tree_nodes: DEFAULT_MAX_TREE_NODES,
+        epsilon_constraint: None,
+        lexicographic: None,
+        crossover_pairs: 0,
+        normalization: NormalizationConfig::default(),
+        warmup: WarmupConfig::default(),
+        dominance_tolerance: None,
+        explain: false,
+        directions: agent_core::ObjectiveDirections::default(),
+        validate_invariants: false,
+    }
+}
+
+fn run_with(config: SearchConfig) -> agent_core::SearchResult {
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    let search = BeamSearch {
+        shm: &shm,
+        chm: &chm,
+        evaluator: &NodeCountEvaluator,
+        config,
+    };
+    search.search_with_mode(&seed_state(), SearchMode::Manual)
+}
+
+fn run(targets: Option<ObjectiveTargets>) -> agent_core::SearchResult {
+    run_with(config(targets))
+}
+
+#[test]
+fn search_without_targets_runs_to_max_depth() {
+    let result = run(None);
+    assert_eq!(result.targets_met_at, None);
+    assert_eq!(result.depth_fronts.len(), 4);
+}
+
+#[test]
+fn search_stops_at_first_depth_meeting_targets() {
+    let targets = ObjectiveTargets {
+        min_f_struct: 0.4,
+        ..ObjectiveTargets::default()
+    };
+    let result = run(Some(targets));
+    assert_eq!(result.targets_met_at, Some(1));
+    assert_eq!(result.depth_fronts.len(), 1);
+}
+
+#[test]
+fn initial_state_meeting_targets_skips_expansion() {
+    let targets = ObjectiveTargets {
+        max_cost: 0.5,
+        ..ObjectiveTargets::default()
+    };
+    let result = run(Some(targets));
+    assert_eq!(result.targets_met_at, Some(0));
+    assert!(result.depth_fronts.is_empty());
+    assert_eq!(result.final_frontier[0].id, Uuid::from_u128(100));
+}
+
+#[test]
+fn recorded_tree_links_every_front_back_to_the_root() {
+    let result = run_with(SearchConfig {
+        record_tree: true,
+        ..config(None)
+    });
+    assert!(run(None).search_tree.is_none());
+    let tree = result.search_tree.expect("tree recorded");
+    assert!(!tree.truncated);
+    let root = tree.root().expect("root");
+    assert_eq!(root.state_id, Uuid::from_u128(100));
+    assert!(tree.children(root.state_id).count() > 0);
+
+    for front in &result.depth_fronts {
+        for id in &front.state_ids {
+            let lineage = tree.lineage(*id);
+            assert_eq!(lineage.len(), front.depth + 1);
+            assert_eq!(lineage[0].state_id, root.state_id);
+            assert!(lineage.iter().all(|node| node.kept));
+            assert!(lineage[1..].iter().all(|node| node.rule_id.is_some()));
+        }
+    }
+
+    let dot = tree.to_dot();
+    assert!(dot.starts_with("digraph search_tree {"));
+    assert_eq!(dot.matches(" -> ").count(), tree.nodes.len() - 1);
+    let json: serde_json::Value = serde_json::from_str(&tree.to_json()).expect("json");
+    assert_eq!(
+        json["nodes"].as_array().map(Vec::len),
+        Some(tree.nodes.len())
+    );
+
+    let capped = run_with(SearchConfig {
+        record_tree: true,
+        max_tree_nodes: 5,
+        ..config(None)
+    })
+    .search_tree
+    .expect("tree recorded");
+    assert_eq!(capped.nodes.len(), 5);
+    assert!(capped.truncated);
+    assert!(capped.to_dot().contains("truncated at 5 nodes"));
+}
+
+#[test]
+fn anytime_steps_match_the_batch_search() {
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    let search = BeamSearch {
+        shm: &shm,
+        chm: &chm,
+        evaluator: &NodeCountEvaluator,
+        config: config(None),
+    };
+    let mut run = search.start(&seed_state());
+    assert_eq!(run.depth(), 0);
+    assert_eq!(run.best_front().len(), 1);
+    assert_eq!(run.best_front()[0].0.id, Uuid::from_u128(100));
+
+    let mut best_struct = run.best_front()[0].1.f_struct;
+    while run.step() {
+        let front_best = run
+            .best_front()
+            .iter()
+            .map(|(_, obj)| obj.f_struct)
+            .fold(f64::MIN, f64::max);
+        assert!(front_best >= best_struct);
+        best_struct = front_best;
+    }
+    assert!(run.is_finished());
+    assert!(!run.step());
+    assert_eq!(run.depth(), 4);
+
+    let stepped = run.finish(SearchMode::Manual);
+    let batch = run_with(config(None));
+    assert_eq!(stepped.depth_fronts, batch.depth_fronts);
+
+    let mut early = search.start(&seed_state());
+    assert!(early.step());
+    let partial = early.finish(SearchMode::Manual);
+    assert_eq!(partial.depth_fronts.len(), 1);
+    assert_eq!(partial.depth_fronts[0], batch.depth_fronts[0]);
+}
+
+#[test]
+fn progress_reports_each_depth_and_matches_the_plain_search() {
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    let search = BeamSearch {
+        shm: &shm,
+        chm: &chm,
+        evaluator: &NodeCountEvaluator,
+        config: config(None),
+    };
+    let (progress, updates) = ChannelProgress::new();
+    let result = search.search_with_progress(&seed_state(), SearchMode::Manual, &progress);
+    assert_eq!(result.depth_fronts, run(None).depth_fronts);
+    let counts = updates
+        .try_iter()
+        .map(|u| (u.phase, u.processed, u.total))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        counts,
+        (0..=4)
+            .map(|depth| ("beam_search".to_string(), depth, 4))
+            .collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn dominance_tolerance_never_grows_the_best_front() {
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    let best_front_sizes = |tolerance: Option<f64>| {
+        let search = BeamSearch {
+            shm: &shm,
+            chm: &chm,
+            evaluator: &NodeCountEvaluator,
+            config: SearchConfig {
+                dominance_tolerance: tolerance.map(|e| [e; 4]),
+                explain: false,
+                directions: agent_core::ObjectiveDirections::default(),
+                ..config(None)
+            },
+        };
+        let mut run = search.start(&seed_state());
+        let mut sizes = Vec::new();
+        while run.step() {
+            sizes.push(run.best_front().len());
+        }
+        sizes
+    };
+
+    let strict = best_front_sizes(None);
+    let tolerant = best_front_sizes(Some(0.05));
+    assert_eq!(strict.len(), tolerant.len());
+    assert!(strict.iter().zip(&tolerant).all(|(s, t)| t <= s));
+    assert!(best_front_sizes(Some(1.0)).iter().all(|&size| size == 1));
+}
+
+#[test]
+fn manual_selection_drives_the_beam_and_records_choices() {
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    let search = BeamSearch {
+        shm: &shm,
+        chm: &chm,
+        evaluator: &NodeCountEvaluator,
+        config: SearchConfig {
+            record_tree: true,
+            ..config(None)
+        },
+    };
+    let field = FieldEngine::new(8);
+    let target = TargetField::fixed(8);
+    let mut run = search.start(&seed_state());
+
+    let proposal = run.propose(&field, &target);
+    assert_eq!(proposal.depth, 1);
+    assert!(proposal.candidates.len() > 1);
+    assert!(
+        proposal
+            .candidates
+            .iter()
+            .all(|c| c.parent == Uuid::from_u128(100))
+    );
+    let last = proposal.candidates.len() - 1;
+    let picked = proposal.candidates[last].preview.state.id;
+    let rule = proposal.candidates[last].preview.rule_id;
+    let offered = proposal.candidates.len();
+
+    assert_eq!(
+        run.select(proposal.clone(), &[]),
+        Err(ManualSelectionError::EmptySelection)
+    );
+    assert_eq!(
+        run.select(proposal.clone(), &[offered]),
+        Err(ManualSelectionError::UnknownCandidate(offered))
+    );
+    let choice = run.select(proposal.clone(), &[last, last]).expect("select");
+    assert_eq!(choice.selected, vec![picked]);
+    assert_eq!(choice.rules, vec![rule]);
+    assert_eq!(choice.offered.len(), offered);
+    assert_eq!(run.depth(), 1);
+    assert_eq!(
+        run.frontier().iter().map(|s| s.id).collect::<Vec<_>>(),
+        vec![picked]
+    );
+    assert_eq!(
+        run.select(proposal, &[0]),
+        Err(ManualSelectionError::StaleProposal {
+            expected: 2,
+            found: 1
+        })
+    );
+
+    let next = run.propose(&field, &target);
+    assert!(next.candidates.iter().all(|c| c.parent == picked));
+    run.select(next, &[0]).expect("select");
+
+    let result = run.finish(SearchMode::Manual);
+    assert_eq!(result.manual_choices.len(), 2);
+    assert_eq!(result.manual_choices[0], choice);
+    assert_eq!(result.depth_fronts[0].state_ids, vec![picked]);
+    let tree = result.search_tree.expect("tree recorded");
+    assert_eq!(tree.lineage(picked).len(), 2);
+}
+
+#[test]
+fn crossover_children_are_counted_when_enabled() {
+    let plain = run(None);
+    assert_eq!(plain.crossover.generated, 0);
+
+    let result = run_with(SearchConfig {
+        crossover_pairs: 3,
+        record_tree: true,
+        ..config(None)
+    });
+    assert!(result.crossover.generated > 0);
+    assert!(result.crossover.survived <= result.crossover.generated);
+    assert!((0.0..=1.0).contains(&result.crossover.survival_rate()));
+    let tree = result.search_tree.expect("tree");
+    assert_eq!(
+        tree.nodes
+            .iter()
+            .filter(|n| n.parent.is_some() && n.rule_id.is_none())
+            .count(),
+        result.crossover.generated
+    );
+}
+
+/// Structure grows with node count while risk grows with it too.
+struct TradeoffEvaluator;
+
+impl Evaluator for TradeoffEvaluator {
+    fn evaluate(&self, state: &DesignState) -> ObjectiveVector {
+        let nodes = state.graph.nodes().len() as f64;
+        ObjectiveVector {
+            f_struct: (nodes / 10.0).min(1.0),
+            f_field: 0.5,
+            f_risk: (1.0 - nodes / 10.0).max(0.0),
+            f_shape: 0.5,
+        }
+    }
+}
+
+#[test]
+fn epsilon_constraint_maximizes_one_objective_within_bounds() {
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    let constraint = EpsilonConstraint {
+        optimize: ObjectiveAxis::Struct,
+        bounds: ObjectiveTargets {
+            max_risk: 0.5,
+            ..ObjectiveTargets::default()
+        },
+    };
+    let search = BeamSearch {
+        shm: &shm,
+        chm: &chm,
+        evaluator: &TradeoffEvaluator,
+        config: SearchConfig {
+            epsilon_constraint: Some(constraint),
+            ..config(None)
+        },
+    };
+    let mut run = search.start(&seed_state());
+    while run.step() {}
+    let (best, obj) = run.best_front()[0].clone();
+    assert_eq!(run.best_front().len(), 1);
+    assert!(constraint.bounds.is_met(&obj));
+    assert_eq!(best.graph.nodes().len(), 5);
+
+    let result = run.finish(SearchMode::Auto);
+    let objectives = result
+        .final_frontier
+        .iter()
+        .map(|s| TradeoffEvaluator.evaluate(s))
+        .collect::<Vec<_>>();
+    assert!(constraint.bounds.is_met(&objectives[0]));
+    assert!(
+        objectives
+            .iter()
+            .all(|o| !constraint.bounds.is_met(o) || o.f_struct <= objectives[0].f_struct)
+    );
+}
+
+#[test]
+fn lexicographic_order_ranks_by_priority_within_tolerance_bands() {
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    // Safety first, with one node of slack; structure decides inside the band.
+    let order = LexicographicOrder::default()
+        .then(ObjectiveAxis::Risk, 0.15)
+        .then(ObjectiveAxis::Struct, 0.0);
+    let search = BeamSearch {
+        shm: &shm,
+        chm: &chm,
+        evaluator: &TradeoffEvaluator,
+        config: SearchConfig {
+            lexicographic: Some(order),
+            ..config(None)
+        },
+    };
+    let mut run = search.start(&seed_state());
+    while run.step() {}
+    let (_, best) = run.best_front()[0].clone();
+    assert_eq!(run.best_front().len(), 1);
+
+    let result = run.finish(SearchMode::Auto);
+    let objectives = result
+        .final_frontier
+        .iter()
+        .map(|s| TradeoffEvaluator.evaluate(s))
+        .collect::<Vec<_>>();
+    let safest = objectives
+        .iter()
+        .map(|o| o.f_risk)
+        .fold(f64::NEG_INFINITY, f64::max);
+    assert!(objectives[0].f_risk >= safest - 0.15);
+    assert!(
+        objectives
+            .iter()
+            .filter(|o| o.f_risk >= safest - 0.15)
+            .all(|o| o.f_struct <= objectives[0].f_struct),
+        "{objectives:?}"
+    );
+    assert_ne!(
+        order.compare(&best, &objectives[0], ObjectiveDirections::default()),
+        std::cmp::Ordering::Less
+    );
+
+    // Auto mode keeps the last depth's front; every expanded depth has stats.
+    assert_eq!(
+        result.lexicographic_ties.len(),
+        result.depth_fronts[0].depth
+    );
+    for (depth, stats) in result.lexicographic_ties.iter().enumerate() {
+        assert_eq!(stats.depth, depth + 1);
+        assert_eq!(stats.tied.len(), 2);
+        assert!(stats.candidates >= stats.tied[0]);
+        assert!(stats.tied[0] >= stats.tied[1] && stats.tied[1] >= 1);
+    }
+}
+
+#[test]
+fn lexicographic_compare_ignores_differences_inside_the_band() {
+    let obj = |f_risk, f_shape| ObjectiveVector {
+        f_struct: 0.5,
+        f_field: 0.5,
+        f_risk,
+        f_shape,
+    };
+    let order = LexicographicOrder::default()
+        .then(ObjectiveAxis::Risk, 0.05)
+        .then(ObjectiveAxis::Shape, 0.0);
+    let directions = ObjectiveDirections::default();
+    // Safer by more than the band wins even at a much higher cost.
+    assert!(
+        order
+            .compare(&obj(0.9, 0.1), &obj(0.8, 0.9), directions)
+            .is_gt()
+    );
+    // Inside the band the cost level decides.
+    assert!(
+        order
+            .compare(&obj(0.9, 0.1), &obj(0.87, 0.9), directions)
+            .is_lt()
+    );
+    // Minimizing flips which end of an axis is preferred.
+    let minimized = ObjectiveDirections::minimizing(&[ObjectiveAxis::Shape]);
+    assert!(
+        order
+            .compare(&obj(0.9, 0.1), &obj(0.87, 0.9), minimized)
+            .is_gt()
+    );
+    assert!(
+        LexicographicOrder::default()
+            .compare(&obj(0.1, 0.1), &obj(0.9, 0.9), directions)
+            .is_eq()
+    );
+}
+
+#[test]
+fn minimized_objective_steers_the_best_front_the_other_way() {
+    let best_struct = |directions| {
+        let shm = HybridVM::default_shm();
+        let chm = HybridVM::empty_chm();
+        let search = BeamSearch {
+            shm: &shm,
+            chm: &chm,
+            evaluator: &NodeCountEvaluator,
+            config: SearchConfig {
+                directions,
+                ..config(None)
+            },
+        };
+        let mut run = search.start(&seed_state());
+        while run.step() {}
+        run.best_front()
+            .iter()
+            .map(|(_, o)| o.f_struct)
+            .collect::<Vec<_>>()
+    };
+    let maximized = best_struct(ObjectiveDirections::default());
+    let minimized = best_struct(ObjectiveDirections::minimizing(&[ObjectiveAxis::Struct]));
+    // Archived objectives stay raw; only the preferred end changes.
+    assert!(
+        minimized.iter().cloned().fold(f64::INFINITY, f64::min)
+            < maximized.iter().cloned().fold(f64::INFINITY, f64::min),
+        "{minimized:?} vs {maximized:?}"
+    );
+    assert!(minimized.iter().all(|v| *v <= 0.3), "{minimized:?}");
+}
+
+#[test]
+fn epsilon_constraint_optimizes_a_minimized_axis_downward() {
+    let best_struct = |directions| {
+        let shm = HybridVM::default_shm();
+        let chm = HybridVM::empty_chm();
+        let search = BeamSearch {
+            shm: &shm,
+            chm: &chm,
+            evaluator: &NodeCountEvaluator,
+            config: SearchConfig {
+                epsilon_constraint: Some(EpsilonConstraint {
+                    optimize: ObjectiveAxis::Struct,
+                    bounds: ObjectiveTargets::default(),
+                }),
+                directions,
+                validate_invariants: true,
+                ..config(None)
+            },
+        };
+        let mut run = search.start(&seed_state());
+        while run.step() {}
+        let result = run.finish(SearchMode::Auto);
+        assert!(result.invariant_violations.is_empty());
+        result
+            .final_frontier
+            .iter()
+            .map(|s| NodeCountEvaluator.evaluate(s).f_struct)
+            .collect::<Vec<_>>()
+    };
+    let maximized = best_struct(ObjectiveDirections::default());
+    let minimized = best_struct(ObjectiveDirections::minimizing(&[ObjectiveAxis::Struct]));
+    assert!(
+        minimized[0] < maximized[0],
+        "{minimized:?} vs {maximized:?}"
+    );
+    assert!(
+        minimized.iter().all(|v| *v >= minimized[0]),
+        "{minimized:?}"
+    );
+}
+
+struct AlternatingNoiseEvaluator {
+    calls: AtomicUsize,
+}
+
+impl Evaluator for AlternatingNoiseEvaluator {
+    fn evaluate(&self, state: &DesignState) -> ObjectiveVector {
+        let call = self.calls.fetch_add(1, Ordering::Relaxed);
+        let noise = if call.is_multiple_of(2) { 0.1 } else { -0.1 };
+        let mut obj = NodeCountEvaluator.evaluate(state);
+        obj.f_field += noise;
+        obj
+    }
+}
+
+#[test]
+fn repeated_policy_aggregates_samples_and_reports_variance() {
+    let evaluator = AlternatingNoiseEvaluator {
+        calls: AtomicUsize::new(0),
+    };
+    let policy = EvaluationPolicy::Repeated {
+        n: 4,
+        aggregator: Aggregator::Median,
+    };
+    let evaluation = evaluate_with_policy(&evaluator, &seed_state(), policy);
+    assert_eq!(evaluation.samples, 4);
+    assert!((evaluation.objective.f_field - 0.5).abs() < 1e-9);
+    assert!(evaluation.variance.f_field > 0.0);
+    assert_eq!(evaluation.variance.f_struct, 0.0);
+
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    let search = BeamSearch {
+        shm: &shm,
+        chm: &chm,
+        evaluator: &evaluator,
+        config: SearchConfig {
+            beam_width: 2,
+            max_depth: 1,
+            norm_alpha: 0.1,
+            targets: None,
+            evaluation: policy,
+            record_tree: false,
+            max_tree_nodes: DEFAULT_MAX_TREE_NODES,
+            epsilon_constraint: None,
+            lexicographic: None,
+            crossover_pairs: 0,
+            normalization: NormalizationConfig::default(),
+            warmup: WarmupConfig::default(),
+            dominance_tolerance: None,
+            explain: false,
+            directions: agent_core::ObjectiveDirections::default(),
+            validate_invariants: false,
+        },
+    };
+    let result = search.search_with_mode(&seed_state(), SearchMode::Auto);
+    assert!(
+        result
+            .final_frontier
+            .iter()
+            .all(|state| result.objective_variance.contains_key(&state.id))
+    );
+}
+
+#[test]
+fn noisy_best_front_keeps_states_within_the_noise_band() {
+    let evaluator = AlternatingNoiseEvaluator {
+        calls: AtomicUsize::new(0),
+    };
+    let policy = EvaluationPolicy::Repeated {
+        n: 2,
+        aggregator: Aggregator::Mean,
+    };
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    let search = BeamSearch {
+        shm: &shm,
+        chm: &chm,
+        evaluator: &evaluator,
+        config: SearchConfig {
+            evaluation: policy,
+            ..config(None)
+        },
+    };
+    let mut run = search.start(&seed_state());
+    while run.step() {}
+    let front = run.best_front().to_vec();
+    let result = run.finish(SearchMode::Auto);
+    assert!(!front.is_empty());
+    for (a, a_obj) in &front {
+        for (b, b_obj) in &front {
+            let eps = agent_core::noise_epsilon(
+                &result.objective_variance[&a.id],
+                &result.objective_variance[&b.id],
+                2,
+            );
+            assert!(!agent_core::epsilon_dominates(a_obj, b_obj, &eps));
+        }
+    }
+}
+
+#[test]
+fn imported_document_seeds_the_same_search_as_the_original_state() {
+    let json = DesignStateDocument::new(seed_state()).to_json();
+    let imported = DesignStateDocument::from_json(&json)
+        .expect("import")
+        .into_state();
+
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    let search = BeamSearch {
+        shm: &shm,
+        chm: &chm,
+        evaluator: &NodeCountEvaluator,
+        config: config(None),
+    };
+    let from_import = search.search_with_mode(&imported, SearchMode::Manual);
+    let from_seed = run(None);
+    assert_eq!(from_import.depth_fronts, from_seed.depth_fronts);
+}
+
+#[test]
+fn dispatched_search_matches_sequential_search() {
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    let search = BeamSearch {
+        shm: &shm,
+        chm: &chm,
+        evaluator: &NodeCountEvaluator,
+        config: SearchConfig {
+            record_tree: true,
+            ..config(None)
+        },
+    };
+    let dispatcher = EvaluationDispatcher::new(
+        Arc::new(InfallibleEvaluator(NodeCountEvaluator)),
+        DispatchConfig {
+            concurrency: 4,
+            ..DispatchConfig::default()
+        },
+    );
+
+    let sequential = search.search_with_mode(&seed_state(), SearchMode::Manual);
+    let dispatched = search.search_dispatched(&seed_state(), SearchMode::Manual, &dispatcher);
+
+    assert_eq!(dispatched.depth_fronts, sequential.depth_fronts);
+    assert_eq!(
+        dispatched.search_tree.map(|t| t.nodes),
+        sequential.search_tree.map(|t| t.nodes)
+    );
+    assert_eq!(sequential.dispatch, DispatchStats::default());
+    assert!(dispatched.dispatch.evaluated > 0);
+    assert_eq!(dispatched.dispatch.failed, 0);
+}
+
+#[test]
+fn combined_hooks_run_one_loop_and_share_the_zero_depth_guard() {
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    let dispatcher = EvaluationDispatcher::new(
+        Arc::new(InfallibleEvaluator(NodeCountEvaluator)),
+        DispatchConfig::default(),
+    );
+    let run_hooked = |config: SearchConfig| {
+        let search = BeamSearch {
+            shm: &shm,
+            chm: &chm,
+            evaluator: &NodeCountEvaluator,
+            config,
+        };
+        let (progress, updates) = ChannelProgress::new();
+        let hooks = SearchHooks {
+            dispatcher: Some(&dispatcher),
+            archive: Some(StateArchive::new(FieldEngine::new(16))),
+            progress: Some(&progress),
+            ..SearchHooks::default()
+        };
+        let result = search.search_with(&seed_state(), SearchMode::Manual, hooks);
+        let last = updates.try_iter().last().map(|u| (u.processed, u.total));
+        (result, last)
+    };
+
+    let (result, last) = run_hooked(config(None));
+    assert_eq!(result.depth_fronts, run(None).depth_fronts);
+    assert!(result.dispatch.evaluated > 0);
+    assert!(
+        result
+            .archive
+            .is_some_and(|a| a.get(seed_state().id).is_some())
+    );
+    assert_eq!(last, Some((4, 4)));
+
+    let (empty, last) = run_hooked(SearchConfig {
+        max_depth: 0,
+        ..config(None)
+    });
+    assert_eq!(empty.final_frontier.len(), 1);
+    assert_eq!(empty.depth_fronts[0].state_ids, vec![seed_state().id]);
+    assert_eq!(empty.dispatch, DispatchStats::default());
+    let archive = empty.archive.expect("archive returned");
+    assert_eq!(archive.get(seed_state().id).map(|e| e.depth), Some(0));
+    assert_eq!(last, Some((0, 0)));
+}
+
+#[test]
+fn explain_records_attributions_that_sum_to_ranked_objectives() {
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    let evaluator = StructuralEvaluator::new(10, 20);
+    let search = BeamSearch {
+        shm: &shm,
+        chm: &chm,
+        evaluator: &evaluator,
+        config: SearchConfig {
+            max_depth: 2,
+            record_tree: true,
+            explain: true,
+            directions: agent_core::ObjectiveDirections::default(),
+            ..config(None)
+        },
+    };
+    let result = search.search_with_mode(&seed_state(), SearchMode::Auto);
+    let tree = result.search_tree.as_ref().expect("tree");
+
+    assert!(result.attributions.contains_key(&seed_state().id));
+    assert_eq!(result.attributions.len(), tree.nodes.len());
+    for node in &tree.nodes {
+        let totals = result.attributions[&node.state_id].totals();
+        assert!((totals.f_struct - node.objective.f_struct).abs() < 1e-9);
+        assert!((totals.f_field - node.objective.f_field).abs() < 1e-9);
+        assert!((totals.f_risk - node.objective.f_risk).abs() < 1e-9);
+        assert!((totals.f_shape - node.objective.f_shape).abs() < 1e-9);
+    }
+
+    // Evaluators without a breakdown simply leave the map empty.
+    assert!(
+        run_with(SearchConfig {
+            explain: true,
+            directions: agent_core::ObjectiveDirections::default(),
+            ..config(None)
+        })
+        .attributions
+        .is_empty()
+    );
+}
+
+#[test]
+fn non_finite_objectives_are_quarantined_with_diagnostics() {
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    let search = BeamSearch {
+        shm: &shm,
+        chm: &chm,
+        evaluator: &PoisonedEvaluator,
+        config: config(None),
+    };
+    let mut run = search.start(&seed_state());
+    while run.step() {
+        for (_, objective) in run.best_front() {
+            assert!(non_finite_axes(objective).is_empty());
+        }
+    }
+    assert!(!run.quarantined().is_empty());
+    let result = run.finish(SearchMode::Manual);
+
+    for diagnostic in &result.quarantined {
+        assert!(diagnostic.depth > 0);
+        assert!(diagnostic.rule_id.is_some());
+        assert!(!diagnostic.axes.is_empty());
+        assert!(diagnostic.to_string().contains("non-finite"));
+    }
+    assert!(
+        result
+            .quarantined
+            .iter()
+            .any(|d| d.axes.contains(&ObjectiveAxis::Field))
+    );
+    let ranked = result
+        .depth_fronts
+        .iter()
+        .flat_map(|front| &front.state_ids)
+        .collect::<Vec<_>>();
+    assert!(
+        result
+            .quarantined
+            .iter()
+            .all(|d| !ranked.contains(&&d.state_id))
+    );
+}
+
+#[test]
+fn validated_search_reports_invariant_violations_without_panicking() {
+    let clean = run_with(SearchConfig {
+        validate_invariants: true,
+        ..config(None)
+    });
+    assert!(clean.invariant_violations.is_empty());
+    assert!(run(None).invariant_violations.is_empty());
+
+    let constrained = run_with(SearchConfig {
+        validate_invariants: true,
+        epsilon_constraint: Some(EpsilonConstraint {
+            optimize: ObjectiveAxis::Struct,
+            bounds: ObjectiveTargets::default(),
+        }),
+        ..config(None)
+    });
+    assert!(constrained.invariant_violations.is_empty());
+
+    let result = run_with(SearchConfig {
+        validate_invariants: true,
+        norm_alpha: 1.5,
+        ..config(None)
+    });
+    assert_eq!(
+        result.invariant_violations.len(),
+        result.depth_fronts.len(),
+        "one norm_alpha violation per expanded depth"
+    );
+    let first = &result.invariant_violations[0];
+    assert_eq!(
+        (first.depth, first.invariant),
+        (1, Invariant::NormAlphaRange)
+    );
+    assert!(first.to_string().starts_with("depth 1: norm_alpha_range"));
+}
+
+#[test]
+fn invariant_checks_flag_unsorted_fronts_and_repeated_states() {
+    let objective = |f_struct: f64| ObjectiveVector {
+        f_struct,
+        f_field: 0.5,
+        f_risk: 0.5,
+        f_shape: 0.5,
+    };
+    let state = seed_state();
+    let mut other = seed_state();
+    other.id = Uuid::from_u128(101);
+
+    let sorted = vec![
+        (state.clone(), objective(0.9)),
+        (other.clone(), objective(0.1)),
+    ];
+    let reversed = vec![
+        (state.clone(), objective(0.1)),
+        (other.clone(), objective(0.9)),
+    ];
+    assert!(check_soft_front_order(1, &sorted, 0.1).is_none());
+    let violation = check_soft_front_order(1, &reversed, 0.1).expect("out of order");
+    assert_eq!(violation.invariant, Invariant::FrontOrder);
+    assert_eq!(violation.state_id, Some(other.id));
+
+    let constraint = EpsilonConstraint {
+        optimize: ObjectiveAxis::Struct,
+        bounds: ObjectiveTargets::default(),
+    };
+    let maximize = ObjectiveDirections::default();
+    assert!(check_constrained_order(1, &sorted, &constraint, maximize).is_none());
+    assert!(check_constrained_order(1, &reversed, &constraint, maximize).is_some());
+    let minimize = ObjectiveDirections::minimizing(&[ObjectiveAxis::Struct]);
+    assert!(check_constrained_order(1, &sorted, &constraint, minimize).is_some());
+    assert!(check_constrained_order(1, &reversed, &constraint, minimize).is_none());
+
+    let out_of_range = check_normalized(2, &[(state.clone(), objective(1.2))]);
+    assert_eq!(out_of_range.len(), 1);
+    assert_eq!(out_of_range[0].invariant, Invariant::NormalizedRange);
+
+    let repeated = check_unique_ids(3, &[state.clone(), other, state.clone()]);
+    assert_eq!(repeated.len(), 1);
+    assert_eq!(repeated[0].state_id, Some(state.id));
+    assert!(repeated[0].detail.contains("2 times"));
+}
+
+#[test]
+fn explained_front_carries_attributions_into_the_rendered_report() {
+    use agent_core::pipeline::{DesignPipeline, PipelineConfig, PipelineStage};
+
+    use crate::common::temp_vm;
+
+    let mut config = PipelineConfig {
+        stop_after: Some(PipelineStage::Search),
+        ..PipelineConfig::default()
+    };
+    config.search.explain = true;
+    let mut pipeline = DesignPipeline::new(temp_vm("explain")).with_config(config);
+    let report = pipeline.run("高速化を重視する").expect("pipeline run");
+
+    assert!(!report.pareto_front.is_empty());
+    for entry in &report.pareto_front {
+        let totals = entry.attribution.as_ref().expect("explained").totals();
+        assert!((totals.f_struct - entry.objective.f_struct).abs() < 1e-9);
+        assert!((totals.f_risk - entry.objective.f_risk).abs() < 1e-9);
+    }
+    let rendered = report.render_front();
+    assert!(rendered.starts_with("#1 "));
+    assert!(rendered.contains("    f_struct "));
+    assert!(rendered.contains("node_ratio"));
+}
@@ -0,0 +1,126 @@
+use agent_core::capability::{BudgetPolicy, BudgetPool, DEFAULT_MAX_TREE_NODES};
+use agent_core::{
+    BeamSearch, EvaluationPolicy, NormalizationConfig, SearchConfig, SearchMode, WarmupConfig,
+};
+use core_types::ObjectiveVector;
+use hybrid_vm::{Evaluator, HybridVM};
+use memory_space::DesignState;
+
+use crate::common::seed_state;
+
+struct NodeCountEvaluator;
+
+impl Evaluator for NodeCountEvaluator {
+    fn evaluate(&self, state: &DesignState) -> ObjectiveVector {
+        ObjectiveVector {
+            f_struct: (state.graph.nodes().len() as f64 / 10.0).min(1.0),
+            f_field: 0.5,
+            f_risk: 0.5,
+            f_shape: 0.5,
+        }
+    }
+}
+
+fn search_config() -> SearchConfig {
+    SearchConfig {
+        beam_width: 4,
+        max_depth: 6,
+        norm_alpha: 0.1,
+        targets: None,
+        evaluation: EvaluationPolicy::Single,
+        record_tree: false,
+        max_tree_nodes: DEFAULT_MAX_TREE_NODES,
+        epsilon_constraint: None,
+        lexicographic: None,
+        crossover_pairs: 2,
+        normalization: NormalizationConfig::default(),
+        warmup: WarmupConfig::default(),
+        dominance_tolerance: None,
+        explain: false,
+        directions: agent_core::ObjectiveDirections::default(),
+        validate_invariants: false,
+    }
+}
+
+#[test]
+fn fair_share_splits_the_pool_and_releases_unused_shares() {
+    let pool = BudgetPool::new(100, BudgetPolicy::FairShare);
+    let mut heavy = pool.join(3, 0);
+    let mut light = pool.join(1, 0);
+    assert_eq!(heavy.draw(1_000), 75);
+    assert_eq!(light.draw(10), 10);
+    assert_eq!(light.draw(1_000), 15);
+    assert_eq!(heavy.draw(1), 0);
+    assert_eq!(pool.remaining(), 0);
+
+    let pool = BudgetPool::new(100, BudgetPolicy::FairShare);
+    let mut first = pool.join(1, 0);
+    let second = pool.join(1, 0);
+    assert_eq!(first.draw(1_000), 50);
+    drop(second);
+    assert_eq!(first.draw(1_000), 50);
+}
+
+#[test]
+fn priority_reserve_holds_back_lower_priorities_only_while_outranked() {
+    let pool = BudgetPool::new(100, BudgetPolicy::Priority { reserve: 30 });
+    let mut low = pool.join(1, 0);
+    let mut high = pool.join(1, 2);
+    assert_eq!(low.draw(1_000), 70);
+    assert_eq!(low.draw(1), 0);
+    assert_eq!(high.draw(10), 10);
+    drop(high);
+    assert_eq!(low.draw(1_000), 20);
+    assert_eq!(pool.remaining(), 0);
+}
+
+#[test]
+fn parallel_searches_never_exceed_the_shared_pool() {
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    let search = BeamSearch {
+        shm: &shm,
+        chm: &chm,
+        evaluator: &NodeCountEvaluator,
+        config: search_config(),
+    };
+    let unbounded = search.search_with_mode(&seed_state("history:"), SearchMode::Auto);
+    assert_eq!(unbounded.budget, Default::default());
+
+    let pool = BudgetPool::new(60, BudgetPolicy::FairShare).with_degrade_below(0.5);
+    // Joined up front so each search is entitled to a third from the start.
+    let members = (0..3).map(|_| pool.join(1, 0)).collect::<Vec<_>>();
+    let results = std::thread::scope(|scope| {
+        let handles = members
+            .into_iter()
+            .map(|member| {
+                let (shm, chm) = (&shm, &chm);
+                scope.spawn(move || {
+                    let search = BeamSearch {
+                        shm,
+                        chm,
+                        evaluator: &NodeCountEvaluator,
+                        config: search_config(),
+                    };
+                    search.search_budgeted(&seed_state("history:"), SearchMode::Auto, member)
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("search panicked"))
+            .collect::<Vec<_>>()
+    });
+
+    let drawn = results.iter().map(|r| r.budget.drawn).sum::<usize>();
+    assert!(drawn <= 60);
+    assert_eq!(drawn, pool.used());
+    assert!(results.iter().all(|r| r.budget.drawn <= 20));
+    assert!(results.iter().any(|r| r.budget.denied > 0));
+    assert!(results.iter().any(|r| r.budget.degraded_depths > 0));
+    assert!(
+        results
+            .iter()
+            .all(|r| !r.final_frontier.is_empty() && r.final_frontier.len() <= 4)
+    );
+}
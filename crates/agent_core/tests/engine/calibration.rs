@@ -0,0 +1,74 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use agent_core::capability::{CalibrationConfig, calibrate_effects, sample_corpus};
+use hybrid_vm::{HybridVM, RulePack, StructuralEvaluator};
+use memory_space::{DesignNode, DesignState, StructuralGraph, Uuid, Value};
+
+fn seed_state() -> DesignState {
+    let mut attrs = BTreeMap::new();
+    for idx in 0..3 {
+        attrs.insert(format!("a{idx}"), Value::Int(idx));
+    }
+    let graph = StructuralGraph::default()
+        .with_node_added(DesignNode::new(Uuid::from_u128(1), "Api", attrs))
+        .with_node_added(DesignNode::new(Uuid::from_u128(2), "Db", BTreeMap::new()))
+        .with_node_added(DesignNode::new(
+            Uuid::from_u128(3),
+            "Cache",
+            BTreeMap::new(),
+        ))
+        .with_edge_added(Uuid::from_u128(1), Uuid::from_u128(2));
+    DesignState::new(Uuid::from_u128(100), Arc::new(graph), "history:")
+}
+
+#[test]
+fn calibration_measures_deltas_and_writes_back_a_pack() {
+    let shm = HybridVM::default_shm();
+    let config = CalibrationConfig {
+        corpus_size: 12,
+        walk_depth: 3,
+        seed: 11,
+    };
+    let corpus = sample_corpus(&[seed_state()], &shm, config);
+    assert_eq!(corpus.len(), 12);
+    let again = sample_corpus(&[seed_state()], &shm, config);
+    assert_eq!(
+        corpus.iter().map(|s| s.id).collect::<Vec<_>>(),
+        again.iter().map(|s| s.id).collect::<Vec<_>>()
+    );
+
+    let report = calibrate_effects(shm.rules(), &corpus, &StructuralEvaluator::default());
+    assert_eq!(report.rules.len(), shm.rules().len());
+    assert!(report.rules.iter().any(|r| r.samples > 0));
+    assert!(report.mean_error().is_finite());
+    for cal in &report.rules {
+        assert!(cal.error() >= 0.0);
+        if cal.samples == 0 {
+            assert!(report.unexercised().contains(&cal.rule_id));
+        }
+    }
+
+    let pack = RulePack::new("default", shm.rules().to_vec());
+    let calibrated = report.calibrated_pack(&pack);
+    let exercised = report
+        .rules
+        .iter()
+        .find(|r| r.samples > 0)
+        .expect("exercised");
+    let rule = calibrated
+        .rules
+        .iter()
+        .find(|r| r.id == exercised.rule_id)
+        .expect("rule");
+    assert_eq!(rule.expected_effect, exercised.measured);
+
+    let path = std::env::temp_dir().join(format!(
+        "agent_core_calibrated_pack_{}.json",
+        std::process::id()
+    ));
+    calibrated.save(&path).expect("save");
+    let loaded = RulePack::load(&path).expect("load");
+    assert_eq!(loaded.rules.len(), pack.rules.len());
+    let _ = std::fs::remove_file(path);
+}
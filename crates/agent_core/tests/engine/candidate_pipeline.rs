@@ -0,0 +1,94 @@
+use agent_core::capability::execute_soft_search_core;
+use agent_core::{
+    BenchConfig, CandidatePipelineConfig, CandidateStage, NormalizationConfig, SoftTraceParams,
+    TraceRunConfig, WarmupConfig,
+};
+
+fn trace_config(candidate_pipeline: CandidatePipelineConfig) -> TraceRunConfig {
+    TraceRunConfig {
+        depth: 4,
+        beam: 4,
+        seed: 11,
+        norm_alpha: 0.1,
+        adaptive_alpha: false,
+        hv_guided: false,
+        raw_output_path: None,
+        normalization: NormalizationConfig::default(),
+        warmup: WarmupConfig::default(),
+        calibration: None,
+        convergence: None,
+        field_dimensions: 16,
+        novelty: None,
+        repair: None,
+        candidate_pipeline,
+        diversity: None,
+        directions: agent_core::ObjectiveDirections::default(),
+    }
+}
+
+#[test]
+fn parallel_stages_reproduce_the_serial_search() {
+    let serial = execute_soft_search_core(
+        trace_config(CandidatePipelineConfig::serial()),
+        SoftTraceParams::default(),
+    );
+    let parallel = execute_soft_search_core(
+        trace_config(CandidatePipelineConfig {
+            queue_capacity: 2,
+            ..CandidatePipelineConfig::parallel(3)
+        }),
+        SoftTraceParams::default(),
+    );
+
+    assert_eq!(serial.best, parallel.best);
+    assert_eq!(serial.trace.len(), parallel.trace.len());
+    for (s, p) in serial.trace.iter().zip(&parallel.trace) {
+        assert_eq!(s.lambda, p.lambda);
+        assert_eq!(s.pareto_front_size_per_depth, p.pareto_front_size_per_depth);
+        assert_eq!(s.pareto_hv_2d, p.pareto_hv_2d);
+    }
+
+    let throughput = parallel.stage_throughput;
+    let generated = throughput.stage(CandidateStage::Generate).items;
+    assert!(generated > 0);
+    for stage in CandidateStage::ALL {
+        assert_eq!(throughput.stage(stage).items, generated, "{}", stage.name());
+    }
+    assert_eq!(throughput.project.workers, 3);
+    assert_eq!(throughput.score.workers, 1);
+    assert_eq!(serial.stage_throughput.project.items, generated);
+}
+
+#[test]
+fn bench_reports_per_stage_throughput() {
+    let result = agent_core::run_bench(BenchConfig {
+        depth: 2,
+        beam: 2,
+        iterations: 2,
+        warmup: 0,
+        seed: 3,
+        norm_alpha: 0.1,
+        field_dimensions: 16,
+        candidate_pipeline: CandidatePipelineConfig::parallel(2),
+    });
+    for stage in CandidateStage::ALL {
+        let stats = result.stage_throughput.stage(stage);
+        assert!(stats.items > 0, "{}", stage.name());
+        assert!(stats.items_per_sec() > 0.0, "{}", stage.name());
+    }
+}
+
+#[test]
+fn field_timings_split_extract_aggregate_and_score() {
+    let result = execute_soft_search_core(
+        trace_config(CandidatePipelineConfig::parallel(2)),
+        SoftTraceParams::default(),
+    );
+    assert!(!result.trace.is_empty());
+    for row in &result.trace {
+        let parts = row.field_extract_us + row.field_aggregate_us + row.field_score_us;
+        assert!((row.field_total_us - parts).abs() <= 1e-3 * parts.max(1.0));
+    }
+    assert!(result.trace.iter().any(|row| row.field_aggregate_us > 0.0));
+    assert!(result.trace.iter().any(|row| row.field_score_us > 0.0));
+}
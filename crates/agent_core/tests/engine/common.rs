@@ -0,0 +1,35 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use hybrid_vm::HybridVM;
+use memory_space::{DesignNode, DesignState, StructuralGraph, Uuid};
+
+/// A `Service -> Database` pair.
+pub fn seed_state(history: &str) -> DesignState {
+    let graph = StructuralGraph::default()
+        .with_node_added(DesignNode::new(
+            Uuid::from_u128(1),
+            "Service",
+            BTreeMap::new(),
+        ))
+        .with_node_added(DesignNode::new(
+            Uuid::from_u128(2),
+            "Database",
+            BTreeMap::new(),
+        ))
+        .with_edge_added(Uuid::from_u128(1), Uuid::from_u128(2));
+    DesignState::new(Uuid::from_u128(100), Arc::new(graph), history)
+}
+
+/// A VM on fresh file storage under the temp dir, named after `tag`.
+pub fn temp_vm(tag: &str) -> HybridVM {
+    let dir = std::env::temp_dir().join(format!(
+        "agent_core_{tag}_{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos()
+    ));
+    HybridVM::for_cli_storage(dir).expect("vm")
+}
@@ -0,0 +1,69 @@
+use agent_core::capability::ConvergenceMonitor;
+use agent_core::{
+    ConvergenceConfig, ConvergenceReason, NormalizationConfig, SoftTraceParams, TraceRunConfig,
+    WarmupConfig,
+};
+
+#[test]
+fn monitor_requires_a_full_window_per_criterion() {
+    let mut hv = ConvergenceMonitor::new(ConvergenceConfig {
+        window: 2,
+        ..ConvergenceConfig::default()
+    });
+    assert_eq!(hv.observe(0.2, 1.0, 0.1), None);
+    assert_eq!(hv.observe(0.3, 1.0, 0.1), None);
+    assert_eq!(hv.observe(0.3, 1.0, 0.1), None);
+    assert_eq!(
+        hv.observe(0.3, 1.0, 0.1),
+        Some(ConvergenceReason::HypervolumeStagnant)
+    );
+
+    let mut entropy = ConvergenceMonitor::new(ConvergenceConfig {
+        window: 2,
+        hv_epsilon: None,
+        entropy_threshold: Some(0.5),
+        lambda_epsilon: Some(1e-3),
+    });
+    assert_eq!(entropy.observe(0.0, 0.1, 0.1), None);
+    assert_eq!(entropy.observe(0.0, 0.9, 0.0), None);
+    assert_eq!(entropy.observe(0.0, 0.1, 0.1), None);
+    assert_eq!(
+        entropy.observe(0.0, 0.1, 0.1),
+        Some(ConvergenceReason::LowEntropy)
+    );
+    assert_eq!(ConvergenceReason::LambdaStable.as_str(), "lambda_stable");
+}
+
+#[test]
+fn converged_trace_stops_early_and_annotates_the_last_row() {
+    let config = TraceRunConfig {
+        depth: 12,
+        beam: 2,
+        seed: 3,
+        norm_alpha: 0.1,
+        adaptive_alpha: false,
+        hv_guided: false,
+        raw_output_path: None,
+        normalization: NormalizationConfig::default(),
+        warmup: WarmupConfig::default(),
+        calibration: None,
+        convergence: Some(ConvergenceConfig {
+            window: 2,
+            hv_epsilon: Some(1.0),
+            entropy_threshold: None,
+            lambda_epsilon: None,
+        }),
+        field_dimensions: agent_core::DEFAULT_FIELD_DIMENSIONS,
+        novelty: None,
+        repair: None,
+        candidate_pipeline: agent_core::CandidatePipelineConfig::default(),
+        diversity: None,
+        directions: agent_core::ObjectiveDirections::default(),
+    };
+    let rows = agent_core::runtime::execute_soft_trace(config, SoftTraceParams::default());
+
+    assert!(rows.len() < 12, "ran {} depths", rows.len());
+    let (last, earlier) = rows.split_last().expect("rows");
+    assert_eq!(last.convergence_reason, "hv_stagnant");
+    assert!(earlier.iter().all(|row| row.convergence_reason.is_empty()));
+}
@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use agent_core::cost_model::{CostModel, CostTable};
+use agent_core::{ObjectiveAxis, SystemEvaluator};
+use field_engine::FieldEngine;
+use hybrid_vm::{Chm, Evaluator, Resource, ResourceBudget, StructuralEvaluator};
+use memory_space::{DesignNode, DesignState, StructuralGraph, Uuid, Value};
+
+fn state(replicas: i64) -> DesignState {
+    let node = |id: u128, kind: &str, replicas: i64| {
+        let attrs = BTreeMap::from([("replicas".to_string(), Value::Int(replicas))]);
+        DesignNode::new(Uuid::from_u128(id), kind, attrs)
+    };
+    let graph = StructuralGraph::default()
+        .with_node_added(node(1, "Service", replicas))
+        .with_node_added(node(2, "Database", 1))
+        .with_edge_added(Uuid::from_u128(1), Uuid::from_u128(2));
+    DesignState::new(
+        Uuid::from_u128(replicas as u128),
+        Arc::new(graph),
+        "history:",
+    )
+}
+
+#[test]
+fn cost_table_drives_the_system_evaluator_cost_objective() {
+    let table = CostTable::default();
+    assert_eq!(table.estimate(&state(1)), 20.0 + 60.0 + 1.0);
+    assert_eq!(table.estimate(&state(3)), 60.0 + 60.0 + 1.0);
+
+    let chm = Chm::default();
+    let field = FieldEngine::new(16);
+    let evaluator = SystemEvaluator::with_base(&chm, &field, StructuralEvaluator::default())
+        .expect("evaluator")
+        .with_cost_model(table.clone());
+    let cheap = evaluator.evaluate(&state(1));
+    let scaled = evaluator.evaluate(&state(3));
+    assert!((cheap.f_shape - (1.0 - 81.0 / table.budget)).abs() < 1e-9);
+    assert!(scaled.f_shape < cheap.f_shape);
+    assert_eq!(
+        evaluator.axis_owner(ObjectiveAxis::Shape),
+        Some("cost_model")
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn cost_table_round_trips_through_json() {
+    let table = CostTable::default();
+    let path =
+        std::env::temp_dir().join(format!("agent_core_cost_table_{}.json", std::process::id()));
+    table.save(&path).expect("save");
+    assert_eq!(CostTable::load(&path).expect("load"), table);
+    let _ = std::fs::remove_file(path);
+}
+
+#[test]
+fn last_model_configured_on_an_axis_owns_it() {
+    let chm = Chm::default();
+    let field = FieldEngine::new(16);
+    let budget = ResourceBudget::default().with_limit(Resource::Memory, 1024.0);
+    let base = || {
+        SystemEvaluator::with_base(&chm, &field, StructuralEvaluator::default()).expect("evaluator")
+    };
+
+    let cost_then_budget = base()
+        .with_cost_model(CostTable::default())
+        .with_resource_budget(budget.clone(), ObjectiveAxis::Shape);
+    assert_eq!(
+        cost_then_budget.axis_owner(ObjectiveAxis::Shape),
+        Some("resource_budget")
+    );
+    assert_eq!(
+        cost_then_budget.evaluate(&state(1)).f_shape,
+        budget.score(&state(1))
+    );
+
+    let budget_then_cost = base()
+        .with_resource_budget(budget, ObjectiveAxis::Shape)
+        .with_cost_model(CostTable::default());
+    assert_eq!(
+        budget_then_cost.axis_owner(ObjectiveAxis::Shape),
+        Some("cost_model")
+    );
+    assert!(budget_then_cost.resource_checks(&state(1)).is_none());
+    assert_eq!(budget_then_cost.axis_owner(ObjectiveAxis::Risk), None);
+}
@@ -0,0 +1,61 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use agent_core::capability::recombine;
+use memory_space::{DesignNode, DesignState, StructuralGraph, Uuid, Value};
+
+fn node(id: u128, kind: &str, attrs: &[(&str, i64)]) -> DesignNode {
+    let attributes = attrs
+        .iter()
+        .map(|(k, v)| (k.to_string(), Value::Int(*v)))
+        .collect::<BTreeMap<_, _>>();
+    DesignNode::new(Uuid::from_u128(id), kind, attributes)
+}
+
+fn state(id: u128, nodes: Vec<DesignNode>, edges: &[(u128, u128)], history: &str) -> DesignState {
+    let mut graph = StructuralGraph::default();
+    for n in nodes {
+        graph = graph.with_node_added(n);
+    }
+    for (from, to) in edges {
+        graph = graph.with_edge_added(Uuid::from_u128(*from), Uuid::from_u128(*to));
+    }
+    DesignState::new(Uuid::from_u128(id), Arc::new(graph), history)
+}
+
+#[test]
+fn recombine_unions_parents_with_left_bias() {
+    let left = state(
+        1,
+        vec![node(1, "Api", &[("port", 80)]), node(2, "Db", &[])],
+        &[(1, 2)],
+        "history:1001",
+    );
+    let right = state(
+        2,
+        vec![
+            node(1, "Gateway", &[("port", 443), ("tls", 1)]),
+            node(2, "Db", &[]),
+            node(3, "Cache", &[]),
+        ],
+        &[(2, 1), (1, 3)],
+        "history:1002,1001",
+    );
+
+    let child = recombine(&left, &right).expect("novel child");
+    let merged = &child.graph.nodes()[&Uuid::from_u128(1)];
+    assert_eq!(merged.kind, "Api");
+    assert_eq!(merged.attributes["port"], Value::Int(80));
+    assert_eq!(merged.attributes["tls"], Value::Int(1));
+    assert_eq!(child.graph.nodes().len(), 3);
+
+    let edges = child.graph.edges();
+    assert!(edges.contains(&(Uuid::from_u128(1), Uuid::from_u128(2))));
+    assert!(edges.contains(&(Uuid::from_u128(1), Uuid::from_u128(3))));
+    assert!(!edges.contains(&(Uuid::from_u128(2), Uuid::from_u128(1))));
+    assert!(child.graph.validate().is_ok());
+    assert_eq!(child.profile_snapshot, "history:1001,1002");
+
+    assert_eq!(recombine(&left, &right).map(|s| s.id), Some(child.id));
+    assert!(recombine(&left, &left).is_none());
+}
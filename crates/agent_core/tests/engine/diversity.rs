@@ -6,3 +6,113 @@ fn profile_modulation_is_bounded() {
         assert!((0.0..=2.0).contains(&h));
     }
 }
+
+fn trace_config(diversity: Option<agent_core::DiversitySchedule>) -> agent_core::TraceRunConfig {
+    agent_core::TraceRunConfig {
+        depth: 4,
+        beam: 3,
+        seed: 11,
+        norm_alpha: 0.1,
+        adaptive_alpha: false,
+        hv_guided: false,
+        raw_output_path: None,
+        normalization: agent_core::NormalizationConfig::default(),
+        warmup: agent_core::WarmupConfig::default(),
+        calibration: None,
+        convergence: None,
+        field_dimensions: 16,
+        novelty: None,
+        repair: None,
+        candidate_pipeline: agent_core::CandidatePipelineConfig::default(),
+        diversity,
+        directions: agent_core::ObjectiveDirections::default(),
+    }
+}
+
+#[test]
+fn collapse_boost_holds_after_a_collapse_signal() {
+    use agent_core::capability::DiversityController;
+    use agent_core::{DiversitySchedule, DiversityTrigger, TraceRow};
+
+    let mut controller = DiversityController::new(DiversitySchedule::CollapseBoost {
+        base: 0.2,
+        boost: 0.9,
+        hold: 1,
+    });
+    let calm = TraceRow::default();
+    let collapsed = TraceRow {
+        collapse_proxy: 1.0,
+        ..TraceRow::default()
+    };
+
+    let first = controller.next(1, None);
+    assert_eq!(first.trigger, DiversityTrigger::Base);
+    assert!((first.pressure - 0.2).abs() < 1e-12);
+    let boosted = controller.next(2, Some(&collapsed));
+    assert_eq!(boosted.trigger.label(), "collapse:proxy");
+    assert!((boosted.pressure - 0.9).abs() < 1e-12);
+    assert!(boosted.epsilon_effect > first.epsilon_effect);
+    assert_eq!(
+        controller.next(3, Some(&calm)).trigger,
+        DiversityTrigger::BoostHold
+    );
+    assert_eq!(
+        controller.next(4, Some(&calm)).trigger,
+        DiversityTrigger::Base
+    );
+}
+
+#[test]
+fn depth_decay_falls_to_its_floor() {
+    use agent_core::DiversitySchedule;
+    use agent_core::capability::DiversityController;
+
+    let mut controller = DiversityController::new(DiversitySchedule::DepthDecay {
+        initial: 0.8,
+        decay: 0.5,
+        floor: 0.3,
+    });
+    let pressures = (1..=4)
+        .map(|depth| controller.next(depth, None).pressure)
+        .collect::<Vec<_>>();
+    assert_eq!(pressures, vec![0.8, 0.4, 0.3, 0.3]);
+}
+
+#[test]
+fn scheduled_trace_logs_pressure_and_trigger_per_depth() {
+    use agent_core::{DiversitySchedule, SoftTraceParams};
+
+    let plain =
+        agent_core::runtime::execute_soft_trace(trace_config(None), SoftTraceParams::default());
+    assert!(plain.iter().all(|row| row.diversity_trigger.is_empty()));
+    assert!(plain.iter().all(|row| row.pressure == 0.0));
+
+    // Full pressure selects exactly like the unscheduled max-min beam.
+    let full = agent_core::runtime::execute_soft_trace(
+        trace_config(Some(DiversitySchedule::Constant { pressure: 1.0 })),
+        SoftTraceParams::default(),
+    );
+    assert_eq!(full.len(), plain.len());
+    for (full, plain) in full.iter().zip(&plain) {
+        assert_eq!(full.pareto_size, plain.pareto_size);
+        assert_eq!(full.per_category_selected, plain.per_category_selected);
+        assert_eq!(full.diversity_trigger, "constant");
+        assert_eq!(full.pressure, 1.0);
+        assert!(full.epsilon_effect > 0.0);
+    }
+
+    let boosted = agent_core::runtime::execute_soft_trace(
+        trace_config(Some(DiversitySchedule::default())),
+        SoftTraceParams::default(),
+    );
+    assert!(!boosted.is_empty());
+    for pair in boosted.windows(2) {
+        let expect_collapse = agent_core::capability::collapse_signal(&pair[0]).is_some();
+        assert_eq!(
+            pair[1].diversity_trigger.starts_with("collapse:"),
+            expect_collapse,
+            "{:?}",
+            pair[1].diversity_trigger
+        );
+    }
+}
@@ -0,0 +1,45 @@
+#[cfg(feature = "serde")]
+#[test]
+fn domain_profile_presets_configure_pipeline_and_round_trip() {
+    use agent_core::domain_profile::DomainProfile;
+    use agent_core::pipeline::DesignPipeline;
+    use field_engine::FieldEngine;
+    use hybrid_vm::{HybridVM, RuleId};
+
+    use crate::common::temp_vm;
+
+    for name in DomainProfile::PRESET_NAMES {
+        let profile = DomainProfile::preset(name).expect("preset");
+        assert_eq!(profile.name, name);
+        let target = profile.target_field.build(&FieldEngine::new(16));
+        assert!(target.data.data.iter().any(|v| v.norm() > 0.0), "{name}");
+    }
+    assert!(DomainProfile::preset("mainframe").is_none());
+
+    let profile = DomainProfile::embedded();
+    let pipeline = DesignPipeline::new(temp_vm("profile")).with_domain_profile(profile.clone());
+    assert_eq!(
+        pipeline.config().search.epsilon_constraint,
+        profile.epsilon_constraint
+    );
+    let mut shm = HybridVM::default_shm();
+    profile.configure_shm(&mut shm);
+    let priority = |id: u128| {
+        shm.rules()
+            .iter()
+            .find(|r| r.id == RuleId::from_u128(id))
+            .map(|r| r.priority)
+    };
+    assert_eq!(priority(1004), Some(0.5));
+    assert_eq!(priority(1017), Some(0.97));
+    // Reliability rule 1005 (0.82) boosted by 1.2.
+    assert!((priority(1005).expect("rule") - 0.984).abs() < 1e-9);
+
+    let path = std::env::temp_dir().join(format!(
+        "agent_core_domain_profile_{}.json",
+        std::process::id()
+    ));
+    profile.save(&path).expect("save");
+    assert_eq!(DomainProfile::load(&path).expect("load"), profile);
+    let _ = std::fs::remove_file(path);
+}
@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use agent_core::capability::{ElicitationConfig, PairwisePreference, PreferenceElicitor};
+use agent_core::{Phase45Controller, ProfileUpdateType};
+use core_types::{ObjectiveVector, ProfileVector};
+use memory_space::{DesignNode, DesignState, StructuralGraph, Uuid};
+
+fn state(id: u128) -> DesignState {
+    let graph = StructuralGraph::default().with_node_added(DesignNode::new(
+        Uuid::from_u128(id),
+        "Service",
+        BTreeMap::new(),
+    ));
+    DesignState::new(Uuid::from_u128(id), Arc::new(graph), "history:")
+}
+
+fn front() -> Vec<(DesignState, ObjectiveVector)> {
+    [
+        (0.9, 0.5, 0.1, 0.5),
+        (0.7, 0.5, 0.3, 0.5),
+        (0.5, 0.5, 0.5, 0.5),
+        (0.3, 0.5, 0.7, 0.5),
+        (0.1, 0.5, 0.9, 0.5),
+        (0.6, 0.2, 0.6, 0.4),
+    ]
+    .into_iter()
+    .enumerate()
+    .map(|(i, (f_struct, f_field, f_risk, f_shape))| {
+        (
+            state(i as u128 + 1),
+            ObjectiveVector {
+                f_struct,
+                f_field,
+                f_risk,
+                f_shape,
+            },
+        )
+    })
+    .collect()
+}
+
+fn uniform() -> ProfileVector {
+    ProfileVector {
+        struct_weight: 0.25,
+        field_weight: 0.25,
+        risk_weight: 0.25,
+        cost_weight: 0.25,
+    }
+}
+
+#[test]
+fn answers_from_a_risk_averse_user_shift_weight_to_risk() {
+    let hidden = ProfileVector {
+        struct_weight: 0.1,
+        field_weight: 0.1,
+        risk_weight: 0.7,
+        cost_weight: 0.1,
+    };
+    let front = front();
+    let mut elicitor = PreferenceElicitor::new(uniform(), ElicitationConfig::default());
+    assert_eq!(elicitor.fit(), uniform());
+
+    while let Some(query) = elicitor.next_query(&front) {
+        let answer = if hidden.score(&query.left_objective) >= hidden.score(&query.right_objective)
+        {
+            PairwisePreference::Left
+        } else {
+            PairwisePreference::Right
+        };
+        elicitor.record(&query, answer);
+    }
+    assert_eq!(elicitor.answer_count(), front.len() * (front.len() - 1) / 2);
+
+    let fitted = elicitor.fit();
+    assert!(fitted.risk_weight > fitted.struct_weight);
+    assert!(fitted.risk_weight > 0.25);
+    let total =
+        fitted.struct_weight + fitted.field_weight + fitted.risk_weight + fitted.cost_weight;
+    assert!((total - 1.0).abs() < 1e-9);
+}
+
+#[test]
+fn commit_is_an_explicit_update_that_skips_the_cooldown() {
+    let mut controller = Phase45Controller::new(0.5);
+    controller.on_profile_update(0, 0.5, ProfileUpdateType::TypeBStructural);
+    assert_eq!(controller.k(), 3);
+    controller.on_profile_update(1, 0.9, ProfileUpdateType::TypeCStatistical);
+    assert_eq!(controller.k(), 3);
+
+    let front = front();
+    let mut elicitor = PreferenceElicitor::new(uniform(), ElicitationConfig::default());
+    let query = elicitor.next_query(&front).expect("query");
+    elicitor.record(&query, PairwisePreference::Left);
+    let profile = elicitor.commit(&mut controller, 1, 0.9);
+    assert_eq!(controller.k(), 4);
+    assert_eq!(profile, elicitor.fit());
+}
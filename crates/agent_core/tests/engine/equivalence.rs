@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use agent_core::capability::{
+    EquivalenceReducer, canonical_attribute_key, canonical_hash, equivalence_hash,
+};
+use agent_core::{
+    CandidatePipelineConfig, NormalizationConfig, SoftTraceParams, TraceRunConfig, WarmupConfig,
+    apply_atomic,
+};
+use hybrid_vm::{DesignRule, EffectVector, Precondition, RuleCategory, Transformation};
+use memory_space::{DesignNode, DesignState, StructuralGraph, Uuid};
+
+fn rule(id: u128, transformation: Transformation) -> DesignRule {
+    DesignRule {
+        id: Uuid::from_u128(id),
+        category: RuleCategory::Structural,
+        priority: 0.5,
+        precondition: Precondition::Always,
+        transformation,
+        expected_effect: EffectVector {
+            delta_struct: 0.0,
+            delta_field: 0.0,
+            delta_risk: 0.0,
+            delta_cost: 0.0,
+        },
+    }
+}
+
+fn seed_state() -> DesignState {
+    let mut graph = StructuralGraph::default();
+    for i in 1..=3u128 {
+        graph = graph.with_node_added(DesignNode::new(
+            Uuid::from_u128(i),
+            format!("N{i}"),
+            BTreeMap::new(),
+        ));
+    }
+    graph = graph.with_edge_added(Uuid::from_u128(1), Uuid::from_u128(2));
+    DesignState::new(Uuid::from_u128(100), Arc::new(graph), "history:")
+}
+
+#[test]
+fn generated_keys_lose_their_rule_id_only() {
+    assert_eq!(canonical_attribute_key("constraint:42"), "constraint:*");
+    assert_eq!(canonical_attribute_key("generated_by_7"), "generated_by_*");
+    assert_eq!(
+        canonical_attribute_key("constraint:latency"),
+        "constraint:latency"
+    );
+    assert_eq!(canonical_attribute_key("constraint:"), "constraint:");
+    assert_eq!(canonical_attribute_key("owner"), "owner");
+}
+
+#[test]
+fn sibling_rules_of_one_kind_fall_into_one_class() {
+    let seed = seed_state();
+    for transformation in [
+        Transformation::AddConstraint,
+        Transformation::AddNode,
+        Transformation::ModifyAttribute,
+    ] {
+        let a = apply_atomic(&rule(11, transformation.clone()), &seed).expect("apply");
+        let b = apply_atomic(&rule(12, transformation.clone()), &seed).expect("apply");
+        assert_ne!(a.id, b.id, "{transformation:?}");
+        assert_eq!(
+            equivalence_hash(&a.graph),
+            equivalence_hash(&b.graph),
+            "{transformation:?}"
+        );
+    }
+    let a = apply_atomic(&rule(11, Transformation::AddConstraint), &seed).expect("apply");
+    let b = apply_atomic(&rule(12, Transformation::AddConstraint), &seed).expect("apply");
+    assert_ne!(canonical_hash(&a.graph), canonical_hash(&b.graph));
+
+    let removed = apply_atomic(&rule(13, Transformation::RemoveNode), &seed).expect("apply");
+    let mut reducer = EquivalenceReducer::new();
+    assert!(reducer.admit_graph(&a.graph));
+    assert!(!reducer.admit_graph(&b.graph));
+    assert!(reducer.admit_graph(&removed.graph));
+    assert_eq!(reducer.merged(), 1);
+}
+
+fn trace_config(candidate_pipeline: CandidatePipelineConfig) -> TraceRunConfig {
+    TraceRunConfig {
+        depth: 3,
+        beam: 3,
+        seed: 7,
+        norm_alpha: 0.1,
+        adaptive_alpha: false,
+        hv_guided: false,
+        raw_output_path: None,
+        normalization: NormalizationConfig::default(),
+        warmup: WarmupConfig::default(),
+        calibration: None,
+        convergence: None,
+        field_dimensions: 16,
+        novelty: None,
+        repair: None,
+        candidate_pipeline,
+        diversity: None,
+        directions: agent_core::ObjectiveDirections::default(),
+    }
+}
+
+#[test]
+fn merged_duplicates_are_counted_per_depth_and_independent_of_workers() {
+    let plain = agent_core::runtime::execute_soft_trace(
+        trace_config(CandidatePipelineConfig::serial()),
+        SoftTraceParams::default(),
+    );
+    assert!(plain.iter().all(|row| row.merged_duplicates == 0));
+
+    let merging = |config: CandidatePipelineConfig| {
+        agent_core::runtime::execute_soft_trace(
+            trace_config(CandidatePipelineConfig {
+                merge_equivalent: true,
+                ..config
+            }),
+            SoftTraceParams::default(),
+        )
+    };
+    let serial = merging(CandidatePipelineConfig::serial());
+    assert!(serial.iter().any(|row| row.merged_duplicates > 0));
+    // The first depth expands the same frontier either way; merging only
+    // leaves fewer states to rank.
+    assert!(serial[0].pareto_size <= plain[0].pareto_size);
+
+    let parallel = merging(CandidatePipelineConfig::parallel(3));
+    assert_eq!(
+        serial
+            .iter()
+            .map(|row| (row.merged_duplicates, row.pareto_size))
+            .collect::<Vec<_>>(),
+        parallel
+            .iter()
+            .map(|row| (row.merged_duplicates, row.pareto_size))
+            .collect::<Vec<_>>()
+    );
+}
@@ -0,0 +1,57 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use agent_core::field_projection::FieldProjectionExport;
+
+#[test]
+fn field_projection_export_uses_the_configured_dimensions() {
+    let config = agent_core::TraceRunConfig {
+        depth: 2,
+        beam: 2,
+        seed: 5,
+        norm_alpha: 0.1,
+        adaptive_alpha: false,
+        hv_guided: false,
+        raw_output_path: None,
+        normalization: agent_core::NormalizationConfig::default(),
+        warmup: agent_core::WarmupConfig::default(),
+        calibration: None,
+        convergence: None,
+        field_dimensions: 24,
+        novelty: None,
+        repair: None,
+        candidate_pipeline: agent_core::CandidatePipelineConfig::default(),
+        diversity: None,
+        directions: agent_core::ObjectiveDirections::default(),
+    };
+    assert_eq!(agent_core::generate_trace(config.clone()).len(), 2);
+
+    let field = config.field_engine();
+    let graph = memory_space::StructuralGraph::default().with_node_added(
+        memory_space::DesignNode::new(memory_space::Uuid::from_u128(1), "Api", Default::default()),
+    );
+    let state = memory_space::DesignState::new(
+        memory_space::Uuid::from_u128(9),
+        std::sync::Arc::new(graph),
+        "history:",
+    );
+    let export = FieldProjectionExport::capture(&field, std::slice::from_ref(&state));
+    assert_eq!(export.dimensions, 24);
+    assert_eq!(export.basis.len(), 10);
+    assert!(export.basis.iter().all(|b| b.vector.len() == 24));
+    assert_eq!(export.states[0].state_id, state.id);
+    assert_eq!(export.states[0].vector.len(), 24);
+
+    let path = std::env::temp_dir().join(format!(
+        "agent_core_field_projection_{}.json",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos()
+    ));
+    export.write_json(&path).expect("write");
+    let raw = std::fs::read_to_string(&path).expect("read");
+    let envelope: serde_json::Value = serde_json::from_str(&raw).expect("json");
+    assert_eq!(envelope["kind"], "field_projection");
+    assert_eq!(envelope["data"]["basis"][0]["category"], "Interface");
+    let _ = std::fs::remove_file(path);
+}
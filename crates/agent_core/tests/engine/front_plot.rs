@@ -0,0 +1,103 @@
+use agent_core::front_plot::FrontPlot;
+use agent_core::{ObjectiveAxis, ObjectiveDirections};
+use core_types::ObjectiveVector;
+use memory_space::{StateId, Uuid};
+
+fn point(id: u128, f_struct: f64, f_field: f64, f_risk: f64) -> (StateId, ObjectiveVector) {
+    (
+        Uuid::from_u128(id),
+        ObjectiveVector {
+            f_struct,
+            f_field,
+            f_risk,
+            f_shape: 0.5,
+        },
+    )
+}
+
+#[test]
+fn fronts_share_oriented_coordinates_with_ranks_and_knees() {
+    let a = point(1, 0.0, 1.0, 0.5);
+    let b = point(2, 0.8, 0.8, 0.5);
+    let c = point(3, 1.0, 0.0, 0.5);
+    // Dominated by `b` once risk is minimized.
+    let d = point(4, 0.4, 0.4, 0.9);
+    let plot = FrontPlot::build(
+        ObjectiveDirections::minimizing(&[ObjectiveAxis::Risk]),
+        [
+            (Some(1), vec![a.clone(), d.clone()]),
+            (None, vec![a, b, c, d]),
+        ],
+    );
+
+    assert_eq!(plot.raw_bounds[2], [0.5, 0.9]);
+    assert_eq!(plot.series[0].depth, None);
+    let last = plot.final_front().expect("final front");
+    assert_eq!(
+        last.points.iter().map(|p| p.rank).collect::<Vec<_>>(),
+        vec![1, 1, 1, 2]
+    );
+    // Minimized risk: lowest raw value is best; constant shape sits at 0.5.
+    assert_eq!(last.points[0].normalized, [0.0, 1.0, 1.0, 0.5]);
+    assert_eq!(last.points[3].normalized, [0.4, 0.4, 0.0, 0.5]);
+    assert_eq!(last.knee, Some(1));
+
+    assert_eq!(last.projections.len(), 6);
+    let struct_field = &last.projections[0];
+    assert_eq!(
+        (struct_field.x, struct_field.y),
+        (ObjectiveAxis::Struct, ObjectiveAxis::Field)
+    );
+    assert_eq!(struct_field.staircase, vec![0, 1, 2]);
+    assert_eq!(struct_field.knee, Some(1));
+    let risk_shape = &last.projections[5];
+    assert_eq!(risk_shape.staircase, vec![0]);
+    assert_eq!(risk_shape.knee, None);
+
+    let depth = plot.depth(1).expect("depth 1");
+    assert_eq!(depth.points[1].normalized, last.points[3].normalized);
+    // Ranks are within a front: nothing at depth 1 dominates `d`.
+    assert_eq!(depth.points[1].rank, 1);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn front_plot_covers_the_final_and_recorded_depth_fronts() {
+    use agent_core::pipeline::{DesignPipeline, PipelineConfig, PipelineStage};
+
+    use crate::common::temp_vm;
+
+    let mut config = PipelineConfig {
+        stop_after: Some(PipelineStage::Search),
+        ..PipelineConfig::default()
+    };
+    config.search.record_tree = true;
+    let mut pipeline = DesignPipeline::new(temp_vm("front_plot")).with_config(config);
+    let report = pipeline.run("高速化を重視する").expect("pipeline run");
+
+    let plot = report.front_plot();
+    assert_eq!(plot.series.len(), report.depth_fronts.len() + 1);
+    let last = plot.final_front().expect("final front");
+    assert_eq!(last.points.len(), report.pareto_front.len());
+    assert_eq!(last.points[0].state_id, report.pareto_front[0].state.id);
+    assert!(last.points.iter().any(|p| p.rank == 1));
+    assert!(
+        plot.series
+            .iter()
+            .flat_map(|s| &s.points)
+            .all(|p| p.normalized.iter().all(|v| (0.0..=1.0).contains(v)))
+    );
+    assert!(
+        last.projections
+            .iter()
+            .all(|p| p.coordinates.len() == last.points.len())
+    );
+
+    let envelope: serde_json::Value =
+        serde_json::from_str(&plot.to_json().expect("json")).expect("parse");
+    assert_eq!(envelope["kind"], "front_plot");
+    assert_eq!(
+        envelope["data"]["series"][0]["depth"],
+        serde_json::Value::Null
+    );
+}
@@ -0,0 +1,124 @@
+use agent_core::capability::{ImprovementTracker, execute_soft_search_core};
+use agent_core::{NormalizationConfig, SoftTraceParams, TraceRunConfig, WarmupConfig};
+
+#[test]
+fn tracker_measures_every_depth_against_the_first() {
+    let mut tracker = ImprovementTracker::default();
+    assert!(tracker.summary().is_none());
+    assert_eq!(tracker.observe(2, 0.4, 0.5), (0.0, 0.0));
+    let (hv, score) = tracker.observe(3, 0.6, 0.45);
+    assert!((hv - 0.2).abs() < 1e-12 && (score + 0.05).abs() < 1e-12);
+    tracker.observe(4, 0.5, 0.7);
+
+    let summary = tracker.summary().expect("observed");
+    assert_eq!(summary.baseline.depth, 2);
+    assert_eq!(summary.last.depth, 4);
+    assert!((summary.hv_improvement() - 0.1).abs() < 1e-12);
+    assert!((summary.best_score_improvement() - 0.2).abs() < 1e-12);
+    assert!((summary.relative_hv_improvement().expect("volume") - 0.25).abs() < 1e-12);
+
+    let mut flat = ImprovementTracker::default();
+    flat.observe(1, 0.0, 0.1);
+    flat.observe(2, 0.3, 0.1);
+    assert_eq!(
+        flat.summary().expect("observed").relative_hv_improvement(),
+        None
+    );
+}
+
+fn trace_config(seed: u64) -> TraceRunConfig {
+    TraceRunConfig {
+        depth: 6,
+        beam: 4,
+        seed,
+        norm_alpha: 0.1,
+        adaptive_alpha: false,
+        hv_guided: false,
+        raw_output_path: None,
+        normalization: NormalizationConfig::default(),
+        warmup: WarmupConfig::default(),
+        calibration: None,
+        convergence: None,
+        field_dimensions: 16,
+        novelty: None,
+        repair: None,
+        candidate_pipeline: agent_core::CandidatePipelineConfig::default(),
+        diversity: None,
+        directions: agent_core::ObjectiveDirections::default(),
+    }
+}
+
+#[test]
+fn trace_rows_and_summary_report_change_since_the_first_front() {
+    let result = execute_soft_search_core(trace_config(5), SoftTraceParams::default());
+    let fronts = result
+        .trace
+        .iter()
+        .filter(|row| row.pareto_front_size_per_depth > 0)
+        .collect::<Vec<_>>();
+    let summary = result.improvement.expect("run produced a front");
+
+    assert_eq!(summary.baseline.depth, fronts[0].depth);
+    assert_eq!(fronts[0].hv_improvement, 0.0);
+    assert_eq!(fronts[0].best_score_improvement, 0.0);
+    let last = fronts.last().expect("fronts");
+    assert_eq!(summary.last.depth, last.depth);
+    assert!((summary.hv_improvement() - last.hv_improvement as f64).abs() < 1e-6);
+    assert!((summary.best_score_improvement() - last.best_score_improvement as f64).abs() < 1e-6);
+    assert!(summary.baseline.best_score.is_finite() && summary.last.hypervolume >= 0.0);
+}
+
+#[test]
+fn minimized_objectives_reorient_the_front_and_its_hypervolume() {
+    let summary = |directions| {
+        let config = TraceRunConfig {
+            directions,
+            ..trace_config(5)
+        };
+        execute_soft_search_core(config, SoftTraceParams::default())
+            .improvement
+            .expect("run produced a front")
+    };
+    let maximized = summary(agent_core::ObjectiveDirections::default());
+    assert_eq!(
+        maximized,
+        summary(agent_core::ObjectiveDirections::default())
+    );
+    let minimized = summary(agent_core::ObjectiveDirections::minimizing(&[
+        agent_core::ObjectiveAxis::Risk,
+    ]));
+    assert_ne!(
+        (
+            maximized.baseline.hypervolume,
+            maximized.baseline.best_score
+        ),
+        (
+            minimized.baseline.hypervolume,
+            minimized.baseline.best_score
+        )
+    );
+}
+
+#[test]
+fn distance_call_counts_are_not_shared_between_concurrent_runs() {
+    let calls = |rows: &[agent_core::TraceRow]| {
+        rows.iter()
+            .map(|row| (row.distance_calls, row.nn_distance_calls))
+            .collect::<Vec<_>>()
+    };
+    let alone = calls(&execute_soft_search_core(trace_config(5), SoftTraceParams::default()).trace);
+    assert!(alone.iter().any(|(distance, nn)| distance + nn > 0));
+
+    let concurrent = std::thread::scope(|scope| {
+        let runs = [5, 6, 5, 7]
+            .map(|seed| {
+                scope.spawn(move || {
+                    execute_soft_search_core(trace_config(seed), SoftTraceParams::default())
+                })
+            })
+            .map(|handle| handle.join().expect("search thread"));
+        runs.map(|run| calls(&run.trace))
+    });
+    assert_eq!(concurrent[0], alone);
+    assert_eq!(concurrent[2], alone);
+}
@@ -0,0 +1,193 @@
+use std::sync::Arc;
+
+use agent_core::{
+    DepthNormalizer, GlobalRobustEstimator, NormalizationConfig, NormalizationStrategy,
+    ObjectiveAxis, ObjectiveRaw, SoftTraceParams, TraceRunConfig, WarmupConfig,
+};
+use core_types::ObjectiveVector;
+use memory_space::{DesignState, StructuralGraph, Uuid};
+
+fn candidates(structs: &[f64]) -> Vec<(DesignState, ObjectiveVector)> {
+    structs
+        .iter()
+        .enumerate()
+        .map(|(i, &f_struct)| {
+            (
+                DesignState::new(
+                    Uuid::from_u128(i as u128 + 1),
+                    Arc::new(StructuralGraph::default()),
+                    "history:",
+                ),
+                ObjectiveVector {
+                    f_struct,
+                    f_field: 0.5,
+                    f_risk: 1.0 - f_struct,
+                    f_shape: 0.5,
+                },
+            )
+        })
+        .collect()
+}
+
+fn structs(normalized: &[(DesignState, ObjectiveVector)]) -> Vec<f64> {
+    normalized.iter().map(|(_, o)| o.f_struct).collect()
+}
+
+#[test]
+fn rank_strategy_spreads_values_and_shares_ties() {
+    let mut normalizer = DepthNormalizer::new(
+        NormalizationConfig::uniform(NormalizationStrategy::Rank),
+        WarmupConfig::default(),
+    );
+    let out = normalizer.normalize(candidates(&[0.9, 0.1, 0.5, 0.5]), 0.1);
+
+    assert_eq!(structs(&out), vec![1.0, 0.0, 0.5, 0.5]);
+    assert_eq!(out[0].1.f_risk, 0.0);
+    assert_eq!(out[0].1.f_field, 0.5);
+}
+
+#[test]
+fn strategies_apply_per_axis() {
+    let config = NormalizationConfig::uniform(NormalizationStrategy::MinMax)
+        .with_axis(ObjectiveAxis::Risk, NormalizationStrategy::Rank);
+    let mut normalizer = DepthNormalizer::new(config, WarmupConfig::default());
+    let out = normalizer.normalize(candidates(&[0.2, 0.4, 0.3]), 0.1);
+
+    let struct_scaled = structs(&out);
+    assert!((struct_scaled[2] - 0.5).abs() < 1e-9);
+    assert_eq!(
+        out.iter().map(|(_, o)| o.f_risk).collect::<Vec<_>>(),
+        vec![1.0, 0.0, 0.5]
+    );
+    assert_eq!(
+        config.strategy(ObjectiveAxis::Risk),
+        NormalizationStrategy::Rank
+    );
+    assert_eq!(
+        config.mode(),
+        "struct=min_max,field=min_max,risk=rank,shape=min_max"
+    );
+    assert_eq!(NormalizationConfig::default().mode(), "per_depth_robust");
+}
+
+#[test]
+fn global_frozen_stops_adapting_after_warmup() {
+    let mut normalizer = DepthNormalizer::new(
+        NormalizationConfig::uniform(NormalizationStrategy::GlobalFrozen),
+        WarmupConfig {
+            warmup_depths: 1,
+            refresh_interval: None,
+        },
+    );
+    let first = normalizer.normalize(candidates(&[0.2, 0.4, 0.6]), 0.1);
+    let later = normalizer.normalize(candidates(&[0.4, 0.9, 0.95]), 0.1);
+
+    // 0.4 maps identically once the warm-up statistics are frozen, even
+    // though it is now the smallest value of its depth.
+    assert!((first[1].1.f_struct - later[0].1.f_struct).abs() < 1e-12);
+    assert!(later[2].1.f_struct > later[1].1.f_struct);
+    assert!(later.iter().all(|(_, o)| (0.0..=1.0).contains(&o.f_struct)));
+}
+
+#[test]
+fn trace_rows_record_the_configured_mode() {
+    let rows = agent_core::generate_trace(TraceRunConfig {
+        depth: 2,
+        beam: 2,
+        seed: 11,
+        norm_alpha: 0.1,
+        adaptive_alpha: false,
+        hv_guided: false,
+        raw_output_path: None,
+        normalization: NormalizationConfig::uniform(NormalizationStrategy::Rank),
+        warmup: WarmupConfig::default(),
+        calibration: None,
+        convergence: None,
+        field_dimensions: agent_core::DEFAULT_FIELD_DIMENSIONS,
+        novelty: None,
+        repair: None,
+        candidate_pipeline: agent_core::CandidatePipelineConfig::default(),
+        diversity: None,
+        directions: agent_core::ObjectiveDirections::default(),
+    });
+
+    assert!(!rows.is_empty());
+    assert!(rows.iter().all(|row| row.normalization_mode == "rank"));
+}
+
+fn raw(values: &[f64]) -> Vec<ObjectiveRaw> {
+    values.iter().map(|v| ObjectiveRaw([*v; 4])).collect()
+}
+
+#[test]
+fn estimator_freezes_after_warmup_and_refreshes_on_interval() {
+    let warmup = WarmupConfig {
+        warmup_depths: 2,
+        refresh_interval: Some(2),
+    };
+    let mut estimator = GlobalRobustEstimator::new(warmup);
+    estimator.observe(&raw(&[0.1, 0.2]), 0.1);
+    assert!(!estimator.is_frozen());
+    assert!(estimator.stats(0.1).is_some());
+
+    estimator.observe(&raw(&[0.3]), 0.1);
+    let frozen = estimator.export().expect("frozen after warm-up");
+    assert!((frozen.median[0] - 0.2).abs() < 1e-12);
+
+    estimator.observe(&raw(&[0.8]), 0.1);
+    assert_eq!(estimator.export(), Some(frozen));
+    estimator.observe(&raw(&[0.9]), 0.1);
+    let refreshed = estimator.export().expect("still frozen");
+    assert!((refreshed.median[0] - 0.85).abs() < 1e-12);
+
+    let mut fixed = GlobalRobustEstimator::new(WarmupConfig {
+        refresh_interval: None,
+        ..warmup
+    });
+    for values in [[0.1, 0.2], [0.3, 0.3], [0.9, 0.9], [0.9, 0.9]] {
+        fixed.observe(&raw(&values), 0.1);
+    }
+    assert!((fixed.export().expect("frozen").median[0] - 0.25).abs() < 1e-12);
+}
+
+#[test]
+fn exported_calibration_seeds_a_later_trace() {
+    let config = TraceRunConfig {
+        depth: 3,
+        beam: 2,
+        seed: 5,
+        norm_alpha: 0.1,
+        adaptive_alpha: false,
+        hv_guided: false,
+        raw_output_path: None,
+        normalization: NormalizationConfig::default(),
+        warmup: WarmupConfig {
+            warmup_depths: 2,
+            refresh_interval: None,
+        },
+        calibration: None,
+        convergence: None,
+        field_dimensions: agent_core::DEFAULT_FIELD_DIMENSIONS,
+        novelty: None,
+        repair: None,
+        candidate_pipeline: agent_core::CandidatePipelineConfig::default(),
+        diversity: None,
+        directions: agent_core::ObjectiveDirections::default(),
+    };
+    let (_, calibration) = agent_core::runtime::execute_soft_trace_calibrated(
+        config.clone(),
+        SoftTraceParams::default(),
+    );
+    let mut stats = calibration.expect("warm-up completes within three depths");
+    stats.median = [0.25; 4];
+
+    let (rows, exported) = agent_core::runtime::execute_soft_trace_calibrated(
+        TraceRunConfig {
+            calibration: Some(stats.clone()),
+            ..config
+        },
+        SoftTraceParams::default(),
+    );
+    assert_eq!(exported, Some(stats));
+    assert!(rows.iter().all(|row| row.norm_median_0 == 0.25));
+}
@@ -0,0 +1,65 @@
+use agent_core::capability::NoveltyArchive;
+use agent_core::{
+    NormalizationConfig, NoveltyConfig, SoftTraceParams, TraceRunConfig, WarmupConfig,
+};
+use field_engine::{FieldVector, Scalar};
+
+fn point(x: f32) -> FieldVector {
+    FieldVector {
+        data: vec![Scalar::new(x, 0.0), Scalar::new(0.0, 0.0)],
+    }
+}
+
+#[test]
+fn novelty_is_the_mean_distance_to_the_k_nearest_entries() {
+    let mut archive = NoveltyArchive::new(NoveltyConfig {
+        k: 2,
+        weight: 0.5,
+        capacity: 3,
+    });
+    assert_eq!(archive.novelty(&point(1.0)), 0.0);
+
+    for x in [0.0, 1.0, 10.0] {
+        archive.insert(point(x));
+    }
+    // Nearest to 2.0 are 1.0 and 0.0.
+    assert!((archive.novelty(&point(2.0)) - 1.5).abs() < 1e-9);
+
+    // Capacity 3 drops the oldest entry (0.0), leaving 1.0 and 4.0 nearest.
+    archive.insert(point(4.0));
+    assert_eq!(archive.len(), 3);
+    assert!((archive.novelty(&point(0.0)) - 2.5).abs() < 1e-9);
+}
+
+#[test]
+fn novelty_trace_grows_the_archive_and_reports_its_statistics() {
+    let config = TraceRunConfig {
+        depth: 4,
+        beam: 3,
+        seed: 11,
+        norm_alpha: 0.1,
+        adaptive_alpha: false,
+        hv_guided: false,
+        raw_output_path: None,
+        normalization: NormalizationConfig::default(),
+        warmup: WarmupConfig::default(),
+        calibration: None,
+        convergence: None,
+        field_dimensions: 16,
+        novelty: Some(NoveltyConfig::default()),
+        repair: None,
+        candidate_pipeline: agent_core::CandidatePipelineConfig::default(),
+        diversity: None,
+        directions: agent_core::ObjectiveDirections::default(),
+    };
+    let rows = agent_core::runtime::execute_soft_trace(config, SoftTraceParams::default());
+
+    let sizes = rows
+        .iter()
+        .map(|row| row.novelty_archive_size)
+        .collect::<Vec<_>>();
+    assert!(sizes.windows(2).all(|pair| pair[0] <= pair[1]), "{sizes:?}");
+    assert!(*sizes.last().expect("rows") > 0);
+    assert!(rows.iter().all(|row| row.novelty_mean.is_finite()));
+    assert!(rows.iter().skip(1).any(|row| row.novelty_mean > 0.0));
+}
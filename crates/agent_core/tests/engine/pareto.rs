@@ -17,3 +17,144 @@ fn dominates_is_strict_and_maximize_based() {
     assert!(agent_core::dominates(&a, &b));
     assert!(!agent_core::dominates(&b, &a));
 }
+
+#[test]
+fn epsilon_dominance_ignores_differences_within_noise() {
+    let a = ObjectiveVector {
+        f_struct: 0.82,
+        f_field: 0.8,
+        f_risk: 0.8,
+        f_shape: 0.8,
+    };
+    let b = ObjectiveVector {
+        f_struct: 0.8,
+        f_field: 0.8,
+        f_risk: 0.8,
+        f_shape: 0.8,
+    };
+    let zero = ObjectiveVector {
+        f_struct: 0.0,
+        f_field: 0.0,
+        f_risk: 0.0,
+        f_shape: 0.0,
+    };
+    let var = ObjectiveVector {
+        f_struct: 0.01,
+        f_field: 0.01,
+        f_risk: 0.01,
+        f_shape: 0.01,
+    };
+    assert!(agent_core::epsilon_dominates(&a, &b, &zero));
+    let eps = agent_core::noise_epsilon(&var, &var, 4);
+    assert!(!agent_core::epsilon_dominates(&a, &b, &eps));
+    assert!(!agent_core::epsilon_dominates(&b, &a, &eps));
+}
+
+#[test]
+fn lower_confidence_bound_subtracts_one_standard_error() {
+    let lower =
+        agent_core::lower_confidence_bound(&obj(0.8, 0.5, 0.5, 0.5), &obj(0.04, 0.0, 0.0, -1.0), 4);
+    assert!((lower.f_struct - 0.7).abs() < 1e-12);
+    assert_eq!((lower.f_field, lower.f_shape), (0.5, 0.5));
+}
+
+fn obj(f_struct: f64, f_field: f64, f_risk: f64, f_shape: f64) -> ObjectiveVector {
+    ObjectiveVector {
+        f_struct,
+        f_field,
+        f_risk,
+        f_shape,
+    }
+}
+
+#[test]
+fn zero_tolerance_matches_strict_dominance() {
+    let zero = obj(0.0, 0.0, 0.0, 0.0);
+    let points = [
+        obj(0.8, 0.8, 0.8, 0.8),
+        obj(0.7, 0.8, 0.8, 0.8),
+        obj(0.8, 0.7, 0.9, 0.8),
+        obj(0.8, 0.8, 0.8, 0.8),
+    ];
+    for a in &points {
+        for b in &points {
+            assert_eq!(
+                agent_core::dominates_with_tolerance(a, b, &zero),
+                agent_core::dominates(a, b),
+                "{a:?} vs {b:?}"
+            );
+        }
+    }
+}
+
+#[test]
+fn tolerance_merges_noisy_front_members() {
+    // Trade-offs of 1e-6 that only exist because of floating-point noise.
+    let noisy = (0..10)
+        .map(|i| {
+            let jitter = i as f64 * 1e-6;
+            obj(0.5 + jitter, 0.5 - jitter, 0.6, 0.4)
+        })
+        .chain([obj(0.9, 0.1, 0.6, 0.4), obj(0.1, 0.9, 0.6, 0.4)])
+        .collect::<Vec<_>>();
+
+    let mut strict = agent_core::ParetoFront::new();
+    let mut tolerant = agent_core::ParetoFront::with_tolerance(obj(1e-3, 1e-3, 1e-3, 1e-3));
+    for (i, point) in noisy.iter().enumerate() {
+        strict.insert(memory_space::Uuid::from_u128(i as u128 + 1), point.clone());
+        tolerant.insert(memory_space::Uuid::from_u128(i as u128 + 1), point.clone());
+    }
+
+    assert_eq!(strict.states.len(), 12);
+    // One representative of the noisy cluster plus the two real extremes.
+    assert_eq!(tolerant.states.len(), 3);
+    assert!(tolerant.tolerance().is_some());
+
+    let mut wide = agent_core::ParetoFront::with_tolerance(obj(1.0, 1.0, 1.0, 1.0));
+    for (i, point) in noisy.iter().enumerate() {
+        wide.insert(memory_space::Uuid::from_u128(i as u128 + 1), point.clone());
+    }
+    assert_eq!(wide.states.len(), 1);
+}
+
+#[test]
+fn minimized_axes_flip_dominance_score_and_hypervolume() {
+    use agent_core::{ObjectiveAxis, ObjectiveDirection, ObjectiveDirections, ParetoFront};
+    use memory_space::Uuid;
+
+    let cheap = ObjectiveVector {
+        f_struct: 0.6,
+        f_field: 0.5,
+        f_risk: 0.5,
+        f_shape: 0.2,
+    };
+    let costly = ObjectiveVector {
+        f_shape: 0.7,
+        ..cheap.clone()
+    };
+    let default = ObjectiveDirections::default();
+    assert!(default.is_all_maximize());
+    assert!(default.dominates(&costly, &cheap));
+
+    let directions = ObjectiveDirections::minimizing(&[ObjectiveAxis::Shape]);
+    assert_eq!(
+        directions.direction(ObjectiveAxis::Shape),
+        ObjectiveDirection::Minimize
+    );
+    assert_eq!(
+        directions.describe(),
+        "struct:max field:max risk:max shape:min"
+    );
+    assert!(directions.dominates(&cheap, &costly));
+    assert!(!directions.dominates(&costly, &cheap));
+    assert!(directions.scalar_score(&cheap) > directions.scalar_score(&costly));
+    assert!(
+        directions.hypervolume(std::slice::from_ref(&cheap))
+            > directions.hypervolume(std::slice::from_ref(&costly))
+    );
+
+    let mut front = ParetoFront::new().with_directions(directions);
+    front.insert(Uuid::from_u128(1), costly);
+    front.insert(Uuid::from_u128(2), cheap.clone());
+    assert_eq!(front.states, vec![(Uuid::from_u128(2), cheap)]);
+}
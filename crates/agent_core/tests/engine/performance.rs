@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use agent_core::ObjectiveAxis;
+use agent_core::capability::{
+    PerformanceEvaluator, PerformanceModel, PerformanceTargets, PerformanceViolation, RewriteRule,
+    evaluate_hypothesis_with_performance,
+};
+use hybrid_vm::{Evaluator, HybridVM, RequirementKind, RuleId, StructuralEvaluator};
+use memory_space::{DesignNode, DesignState, StructuralGraph, Uuid, Value};
+
+fn chain(service_replicas: i64) -> DesignState {
+    let replicas = BTreeMap::from([("replicas".to_string(), Value::Int(service_replicas))]);
+    let graph = StructuralGraph::default()
+        .with_node_added(DesignNode::new(Uuid::from_u128(1), "Api", BTreeMap::new()))
+        .with_node_added(DesignNode::new(Uuid::from_u128(2), "Service", replicas))
+        .with_node_added(DesignNode::new(
+            Uuid::from_u128(3),
+            "Database",
+            BTreeMap::new(),
+        ))
+        .with_edge_added(Uuid::from_u128(1), Uuid::from_u128(2))
+        .with_edge_added(Uuid::from_u128(2), Uuid::from_u128(3));
+    DesignState::new(Uuid::from_u128(7), Arc::new(graph), "history:")
+}
+
+#[test]
+fn chain_latency_is_the_sum_of_service_times() {
+    let model = PerformanceModel::default();
+    let estimate = model.estimate(&chain(1));
+    assert_eq!(estimate.latency_ms, 2.0 + 4.0 + 8.0);
+    assert_eq!(
+        estimate.critical_path,
+        (1..=3).map(Uuid::from_u128).collect::<Vec<_>>()
+    );
+    assert_eq!(estimate.throughput_rps, 500.0);
+    assert_eq!(estimate.bottleneck, Some(Uuid::from_u128(3)));
+
+    let targets = PerformanceTargets {
+        max_latency_ms: Some(10.0),
+        min_throughput_rps: Some(400.0),
+    };
+    assert_eq!(
+        targets.violations(&estimate),
+        vec![PerformanceViolation::Latency {
+            estimated_ms: 14.0,
+            limit_ms: 10.0,
+        }]
+    );
+}
+
+#[test]
+fn replicas_split_load_and_fan_in_adds_queueing() {
+    let model = PerformanceModel {
+        capacities_rps: BTreeMap::from([("Service".to_string(), 100.0)]),
+        ..PerformanceModel::default()
+    };
+    let single = model.estimate(&chain(1));
+    assert_eq!(single.throughput_rps, 100.0);
+    assert_eq!(model.estimate(&chain(3)).throughput_rps, 300.0);
+
+    let split = RewriteRule::split_with_load_balancer(RuleId::from_u128(5002), "Service")
+        .apply(&chain(1))
+        .expect("Service present");
+    let estimate = model.estimate(&split);
+    assert_eq!(estimate.throughput_rps, 200.0);
+    // Api -> LoadBalancer -> Service -> Database, with two callers on the db.
+    assert_eq!(estimate.latency_ms, 2.0 + 0.3 + 4.0 + 8.0 * 1.25);
+    assert_eq!(estimate.critical_path.len(), 4);
+}
+
+#[test]
+fn performance_evaluator_replaces_the_chosen_axis() {
+    let base = StructuralEvaluator::default();
+    let model = PerformanceModel::default();
+    let evaluator = PerformanceEvaluator {
+        base: &base,
+        model: model.clone(),
+        axis: ObjectiveAxis::Field,
+    };
+    let state = chain(1);
+    let objective = evaluator.evaluate(&state);
+    let expected = model.score(&model.estimate(&state));
+    assert_eq!(objective.f_field, expected);
+    assert_eq!(objective.f_struct, base.evaluate(&state).f_struct);
+    assert!(expected > 0.0 && expected < 1.0);
+}
+
+#[test]
+#[allow(deprecated)]
+fn performance_targets_decide_the_performance_requirement() {
+    let store_dir = std::env::temp_dir().join(format!(
+        "agent_core_performance_hypothesis_{}",
+        std::process::id()
+    ));
+    let mut vm = HybridVM::for_cli_storage(&store_dir).expect("vm");
+    vm.analyze_text("高速なAPI").expect("analyze");
+    let projection = vm.project_phase_a();
+    let model = PerformanceModel::default();
+    let violates_performance = |targets: PerformanceTargets| {
+        evaluate_hypothesis_with_performance(&vm, &projection, &chain(1), &model, &targets)
+            .expect("hypothesis")
+            .violated_requirements()
+            .any(|r| r.kind == RequirementKind::Performance)
+    };
+
+    // The chain takes 14ms and sustains 500 rps.
+    assert!(violates_performance(PerformanceTargets {
+        max_latency_ms: Some(10.0),
+        min_throughput_rps: None,
+    }));
+    assert!(violates_performance(PerformanceTargets {
+        max_latency_ms: None,
+        min_throughput_rps: Some(800.0),
+    }));
+    assert!(!violates_performance(PerformanceTargets {
+        max_latency_ms: Some(20.0),
+        min_throughput_rps: Some(400.0),
+    }));
+    let _ = std::fs::remove_dir_all(store_dir);
+}
@@ -0,0 +1,55 @@
+use agent_core::pipeline::{DesignPipeline, PipelineConfig, PipelineStage};
+use hybrid_vm::ValidationStatus;
+
+use crate::common::temp_vm;
+
+#[test]
+fn run_produces_front_cards_and_artifacts() {
+    let mut pipeline = DesignPipeline::new(temp_vm("pipeline_full"));
+    let report = pipeline
+        .run("高速化を重視する。セキュリティを確保する。")
+        .expect("pipeline run");
+
+    assert_eq!(report.last_completed, Some(PipelineStage::Artifacts));
+    assert!(!report.concepts.is_empty());
+    assert!(report.initial_state.is_some());
+    assert!(!report.pareto_front.is_empty());
+    assert!(!report.cards.is_empty());
+    assert_eq!(
+        report.artifacts.keys().cloned().collect::<Vec<_>>(),
+        vec!["Mermaid", "Rust", "Sql"]
+    );
+    let validation = report.artifact_validation.expect("artifact validation");
+    assert!(
+        validation
+            .structural_checks()
+            .all(|c| c.status == ValidationStatus::Pass),
+        "{:?}",
+        validation.checks
+    );
+    assert_eq!(report.timings.len(), PipelineStage::ALL.len());
+}
+
+#[test]
+fn stop_after_then_resume_continues_remaining_stages() {
+    let config = PipelineConfig {
+        stop_after: Some(PipelineStage::SeedState),
+        ..PipelineConfig::default()
+    };
+    let mut pipeline = DesignPipeline::new(temp_vm("pipeline_resume")).with_config(config);
+    let partial = pipeline.run("応答時間を短縮する").expect("partial run");
+    assert_eq!(partial.last_completed, Some(PipelineStage::SeedState));
+    assert!(partial.pareto_front.is_empty());
+    assert!(partial.artifacts.is_empty());
+    assert!(partial.artifact_validation.is_none());
+
+    let checkpoint = pipeline.checkpoint().clone();
+    pipeline.config_mut().stop_after = None;
+    let full = pipeline.resume_from(checkpoint).expect("resume");
+    assert_eq!(full.last_completed, Some(PipelineStage::Artifacts));
+    assert_eq!(full.concepts, partial.concepts);
+    assert_eq!(
+        full.timings.iter().map(|t| t.stage).collect::<Vec<_>>(),
+        PipelineStage::ALL.to_vec()
+    );
+}
@@ -0,0 +1,24 @@
+#[cfg(feature = "serde")]
+#[test]
+fn playground_runs_analyze_and_search_in_memory() {
+    use agent_core::playground::Playground;
+
+    let mut playground = Playground::new().expect("playground");
+    let analyzed: serde_json::Value = serde_json::from_str(
+        &playground
+            .analyze_text("応答時間を短縮する")
+            .expect("analyze"),
+    )
+    .expect("json");
+    assert!(!analyzed["l1_refs"].as_array().expect("l1 refs").is_empty());
+
+    let searched: serde_json::Value =
+        serde_json::from_str(&playground.search(2, 2).expect("search")).expect("json");
+    assert!(
+        !searched["pareto_front"]
+            .as_array()
+            .expect("front")
+            .is_empty()
+    );
+    assert_eq!(searched["recommended"][0]["reason"], "Knee");
+}
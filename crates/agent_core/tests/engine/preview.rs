@@ -0,0 +1,90 @@
+use agent_core::{PreviewContext, apply_atomic, preview_rule};
+use field_engine::{FieldEngine, TargetField};
+use hybrid_vm::{
+    Chm, DesignRule, EffectVector, Evaluator, Precondition, RuleCategory, StructuralEvaluator,
+    Transformation,
+};
+use memory_space::Uuid;
+
+use crate::common::seed_state;
+
+fn rule(id: u128, transformation: Transformation, precondition: Precondition) -> DesignRule {
+    DesignRule {
+        id: Uuid::from_u128(id),
+        category: RuleCategory::Structural,
+        priority: 0.5,
+        precondition,
+        transformation,
+        expected_effect: EffectVector {
+            delta_struct: 0.0,
+            delta_field: 0.0,
+            delta_risk: 0.0,
+            delta_cost: 0.0,
+        },
+    }
+}
+
+#[test]
+fn preview_matches_apply_and_leaves_the_state_untouched() {
+    let state = seed_state("history:");
+    let before = state.clone();
+    let add = rule(100, Transformation::AddNode, Precondition::Always);
+    let evaluator = StructuralEvaluator::default();
+    let field = FieldEngine::new(8);
+    let target = TargetField::fixed(8);
+    let chm = Chm::default();
+    let ctx = PreviewContext {
+        evaluator: &evaluator,
+        field: &field,
+        target: &target,
+        chm: &chm,
+    };
+
+    let preview = preview_rule(&state, &add, &ctx).expect("preview");
+
+    assert_eq!(state.id, before.id);
+    assert_eq!(state.graph, before.graph);
+    let applied = apply_atomic(&add, &state).expect("apply");
+    assert_eq!(preview.state.id, applied.id);
+    assert_eq!(preview.state.graph, applied.graph);
+    assert!(preview.precondition_met);
+    assert_eq!(preview.diff.added_nodes.len(), 1);
+    assert!(preview.diff.removed_nodes.is_empty());
+    assert_eq!(preview.objective_before, evaluator.evaluate(&state));
+    assert_eq!(preview.objective_after, evaluator.evaluate(&preview.state));
+    let delta = preview.objective_delta();
+    assert_eq!(
+        delta.f_struct,
+        preview.objective_after.f_struct - preview.objective_before.f_struct
+    );
+    assert!(preview.resonance_delta().is_finite());
+    assert_eq!(preview.chm_risk_delta, 0.0);
+}
+
+#[test]
+fn preview_reports_unmet_preconditions_and_chm_conflicts() {
+    let prior = Uuid::from_u128(7);
+    let state = seed_state(&format!("history:{}", prior.as_u128()));
+    let remove = rule(
+        200,
+        Transformation::RemoveNode,
+        Precondition::NodeCount { min: 5, max: None },
+    );
+    let evaluator = StructuralEvaluator::default();
+    let field = FieldEngine::new(8);
+    let target = TargetField::fixed(8);
+    let mut chm = Chm::default();
+    chm.insert_edge(prior, remove.id, -0.6);
+    let ctx = PreviewContext {
+        evaluator: &evaluator,
+        field: &field,
+        target: &target,
+        chm: &chm,
+    };
+
+    let preview = preview_rule(&state, &remove, &ctx).expect("preview");
+
+    assert!(!preview.precondition_met);
+    assert_eq!(preview.diff.removed_nodes.len(), 1);
+    assert!((preview.chm_risk_delta - 0.6).abs() < 1e-12);
+}
@@ -0,0 +1,55 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use agent_core::capability::{ReliabilityModel, RewriteRule};
+use hybrid_vm::RuleId;
+use memory_space::{DesignNode, DesignState, StructuralGraph, Uuid};
+
+fn chain() -> DesignState {
+    let mut graph = StructuralGraph::default();
+    for (id, kind) in [(1, "Api"), (2, "Service"), (3, "Database")] {
+        graph = graph.with_node_added(DesignNode::new(Uuid::from_u128(id), kind, BTreeMap::new()));
+    }
+    let graph = graph
+        .with_edge_added(Uuid::from_u128(1), Uuid::from_u128(2))
+        .with_edge_added(Uuid::from_u128(2), Uuid::from_u128(3));
+    DesignState::new(Uuid::from_u128(7), Arc::new(graph), "history:")
+}
+
+#[test]
+fn chain_nodes_are_all_single_points_of_failure() {
+    let report = ReliabilityModel::default().analyze(&chain());
+    assert_eq!(report.single_points_of_failure().count(), 3);
+    let db = &report.nodes[0];
+    assert_eq!(db.kind, "Database");
+    assert_eq!(db.blast_radius, 3);
+    assert_eq!(db.criticality, 1.0);
+    assert!(report.reliability > 0.0 && report.reliability < 1.0);
+    assert_eq!(ReliabilityModel::default().analyze(&chain()), report);
+}
+
+#[test]
+fn redundant_replicas_remove_the_service_single_point_of_failure() {
+    let model = ReliabilityModel {
+        trials: 4096,
+        ..ReliabilityModel::default()
+    };
+    let before = chain();
+    let after = RewriteRule::split_with_load_balancer(RuleId::from_u128(5002), "Service")
+        .apply(&before)
+        .expect("Service present");
+
+    let report = model.analyze(&after);
+    let services = report
+        .nodes
+        .iter()
+        .filter(|n| n.kind == "Service")
+        .collect::<Vec<_>>();
+    assert_eq!(services.len(), 2);
+    assert!(
+        services
+            .iter()
+            .all(|n| !n.single_point_of_failure && n.blast_radius == 1)
+    );
+    assert!(report.reliability > model.score(&before));
+}
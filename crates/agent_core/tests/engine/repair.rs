@@ -0,0 +1,134 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use agent_core::capability::{RepairConfig, apply_atomic_repaired, repair};
+use agent_core::{
+    NormalizationConfig, SoftTraceParams, TraceRunConfig, WarmupConfig, apply_atomic,
+};
+use hybrid_vm::{DesignRule, EffectVector, Precondition, RuleCategory, Transformation};
+use memory_space::{DesignNode, DesignState, StructuralGraph, Uuid, Value};
+
+fn node(id: u128, attributed: bool) -> DesignNode {
+    let mut attributes = BTreeMap::new();
+    if attributed {
+        attributes.insert("port".to_string(), Value::Int(80));
+    }
+    DesignNode::new(Uuid::from_u128(id), "Service", attributes)
+}
+
+fn remove_node_rule() -> DesignRule {
+    DesignRule {
+        id: Uuid::from_u128(700),
+        category: RuleCategory::Structural,
+        priority: 0.5,
+        precondition: Precondition::Always,
+        transformation: Transformation::RemoveNode,
+        expected_effect: EffectVector {
+            delta_struct: 0.0,
+            delta_field: 0.0,
+            delta_risk: 0.0,
+            delta_cost: 0.0,
+        },
+    }
+}
+
+/// Hub 9 (removed by `RemoveNode`, which drops the highest id) joins 1, 2
+/// and 3; node 5 was on its own from the start.
+fn hub_state() -> DesignState {
+    let id = Uuid::from_u128;
+    let graph = StructuralGraph::default()
+        .with_node_added(node(1, true))
+        .with_node_added(node(2, true))
+        .with_node_added(node(3, false))
+        .with_node_added(node(5, false))
+        .with_node_added(node(9, true))
+        .with_edge_added(id(1), id(9))
+        .with_edge_added(id(9), id(2))
+        .with_edge_added(id(9), id(3));
+    DesignState::new(Uuid::from_u128(100), Arc::new(graph), "history:")
+}
+
+#[test]
+fn stranded_pieces_are_reconnected_or_collapsed() {
+    let id = Uuid::from_u128;
+    let state = hub_state();
+    let plain = apply_atomic(&remove_node_rule(), &state).expect("apply");
+    let (repaired, stats) =
+        apply_atomic_repaired(&remove_node_rule(), &state, RepairConfig::default()).expect("apply");
+
+    assert_eq!((stats.reconnected, stats.collapsed), (1, 1));
+    assert_eq!(repaired.id, plain.id);
+    assert_eq!(repaired.profile_snapshot, plain.profile_snapshot);
+    let nodes = repaired.graph.nodes().keys().copied().collect::<Vec<_>>();
+    assert_eq!(nodes, vec![id(1), id(2), id(5)]);
+    assert!(repaired.graph.edges().contains(&(id(1), id(2))));
+    assert_eq!(repaired.graph.edges().len(), 1);
+    assert!(repaired.graph.validate().is_ok());
+
+    // Reconnecting only keeps the attribute-less piece and wires it back too.
+    let (graph, stats) = repair(
+        &state.graph,
+        &plain.graph,
+        RepairConfig {
+            reconnect_orphans: true,
+            collapse_empty_clusters: false,
+        },
+    );
+    assert_eq!((stats.reconnected, stats.collapsed), (2, 0));
+    assert_eq!(graph.nodes().len(), 4);
+    assert!(graph.edges().contains(&(id(1), id(3))));
+}
+
+#[test]
+fn repair_leaves_intact_graphs_alone() {
+    let state = hub_state();
+    let (graph, stats) = repair(&state.graph, &state.graph, RepairConfig::default());
+    assert_eq!(stats.total(), 0);
+    assert_eq!(graph, state.graph.as_ref().clone());
+
+    let disabled = RepairConfig {
+        reconnect_orphans: false,
+        collapse_empty_clusters: false,
+    };
+    let (repaired, stats) =
+        apply_atomic_repaired(&remove_node_rule(), &state, disabled).expect("apply");
+    assert_eq!(stats.total(), 0);
+    let plain = apply_atomic(&remove_node_rule(), &state).expect("apply");
+    assert_eq!(repaired.id, plain.id);
+    assert_eq!(repaired.graph, plain.graph);
+}
+
+#[test]
+fn repair_counters_appear_in_trace_rows_only_when_enabled() {
+    let config = |repair| TraceRunConfig {
+        depth: 6,
+        beam: 4,
+        seed: 23,
+        norm_alpha: 0.1,
+        adaptive_alpha: false,
+        hv_guided: false,
+        raw_output_path: None,
+        normalization: NormalizationConfig::default(),
+        warmup: WarmupConfig::default(),
+        calibration: None,
+        convergence: None,
+        field_dimensions: 16,
+        novelty: None,
+        repair,
+        candidate_pipeline: agent_core::CandidatePipelineConfig::default(),
+        diversity: None,
+        directions: agent_core::ObjectiveDirections::default(),
+    };
+    let plain = agent_core::runtime::execute_soft_trace(config(None), SoftTraceParams::default());
+    assert!(
+        plain
+            .iter()
+            .all(|row| row.repairs_reconnected == 0 && row.repairs_collapsed == 0)
+    );
+
+    let repaired = agent_core::runtime::execute_soft_trace(
+        config(Some(RepairConfig::default())),
+        SoftTraceParams::default(),
+    );
+    assert_eq!(repaired.len(), plain.len());
+}
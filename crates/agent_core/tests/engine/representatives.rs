@@ -0,0 +1,92 @@
+use agent_core::representatives::{PickReason, select_representatives};
+use agent_core::{ObjectiveAxis, ObjectiveDirections};
+use core_types::ObjectiveVector;
+use memory_space::{StateId, Uuid};
+
+fn point(id: u128, f_struct: f64, f_field: f64, f_risk: f64) -> (StateId, ObjectiveVector) {
+    (
+        Uuid::from_u128(id),
+        ObjectiveVector {
+            f_struct,
+            f_field,
+            f_risk,
+            f_shape: 0.5,
+        },
+    )
+}
+
+#[test]
+fn picks_knee_then_extremes_then_diverse_states_with_rationale() {
+    let front = vec![
+        point(1, 0.0, 1.0, 0.5),
+        point(2, 0.7, 0.7, 0.5),
+        point(3, 1.0, 0.0, 0.5),
+        point(4, 0.2, 0.2, 0.4),
+        point(5, 0.69, 0.71, 0.5),
+    ];
+    let directions = ObjectiveDirections::minimizing(&[ObjectiveAxis::Risk]);
+    let picks = select_representatives(&front, directions, 10);
+
+    let reasons = picks.iter().map(|p| p.reason).collect::<Vec<_>>();
+    assert_eq!(
+        reasons,
+        vec![
+            PickReason::Knee,
+            PickReason::Extreme(ObjectiveAxis::Struct),
+            PickReason::Extreme(ObjectiveAxis::Field),
+            PickReason::Extreme(ObjectiveAxis::Risk),
+            PickReason::Diverse,
+        ]
+    );
+    assert_eq!(
+        picks.iter().map(|p| p.index).collect::<Vec<_>>(),
+        vec![1, 2, 0, 3, 4]
+    );
+    assert_eq!(picks[0].state_id, front[1].0);
+    assert!(picks[0].rationale.starts_with("knee: "));
+    assert_eq!(
+        picks[3].rationale,
+        "extreme: lowest f_risk (0.40; front spans 0.40..0.50)"
+    );
+    assert!(picks[4].rationale.starts_with("diverse: 0.01 "));
+
+    let two = select_representatives(&front, directions, 2);
+    assert_eq!(two, picks[..2]);
+    assert!(select_representatives(&front, directions, 0).is_empty());
+    assert!(select_representatives(&[], directions, 3).is_empty());
+}
+
+#[test]
+fn duplicate_states_end_the_diverse_fill() {
+    let front = vec![point(1, 0.5, 0.5, 0.5), point(2, 0.5, 0.5, 0.5)];
+    let picks = select_representatives(&front, ObjectiveDirections::default(), 3);
+    assert_eq!(picks.len(), 1);
+    assert_eq!(picks[0].reason, PickReason::Knee);
+}
+
+#[test]
+fn search_recommends_designs_from_the_front() {
+    use agent_core::pipeline::{DesignPipeline, PipelineConfig, PipelineStage};
+
+    use crate::common::temp_vm;
+
+    let mut config = PipelineConfig {
+        stop_after: Some(PipelineStage::Search),
+        recommended_designs: 2,
+        ..PipelineConfig::default()
+    };
+    config.search.beam_width = 6;
+    let mut pipeline = DesignPipeline::new(temp_vm("recommend")).with_config(config);
+    let report = pipeline
+        .run("高速化を重視する。セキュリティを確保する。")
+        .expect("pipeline run");
+
+    assert!(!report.recommended.is_empty());
+    assert!(report.recommended.len() <= 2);
+    let knee = &report.recommended[0];
+    assert_eq!(knee.reason, agent_core::representatives::PickReason::Knee);
+    assert_eq!(knee.state_id, report.pareto_front[knee.index].state.id);
+    let rendered = report.render_front();
+    assert!(rendered.contains("recommended:\n"));
+    assert!(rendered.contains(&format!("  #{} knee: ", knee.index + 1)));
+}
@@ -0,0 +1,40 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use agent_core::{ObjectiveAxis, SystemEvaluator};
+use field_engine::FieldEngine;
+use hybrid_vm::{Chm, Evaluator, Resource, ResourceBudget, StructuralEvaluator};
+use memory_space::{DesignNode, DesignState, StructuralGraph, Uuid, Value};
+
+#[test]
+fn resource_budget_scores_memory_headroom_on_the_chosen_axis() {
+    let state = |memory_kb: &[i64]| {
+        let mut graph = StructuralGraph::default();
+        for (i, kb) in memory_kb.iter().enumerate() {
+            let attrs = BTreeMap::from([("memory_kb".to_string(), Value::Int(*kb))]);
+            graph = graph.with_node_added(DesignNode::new(
+                Uuid::from_u128(i as u128 + 1),
+                "Service",
+                attrs,
+            ));
+        }
+        DesignState::new(Uuid::from_u128(100), Arc::new(graph), "history:")
+    };
+
+    let budget = ResourceBudget::from_requirement_text("メモリ512MB以下");
+    assert_eq!(budget.limit(Resource::Memory), Some(524_288.0));
+
+    let chm = Chm::default();
+    let field = FieldEngine::new(16);
+    let evaluator = SystemEvaluator::with_base(&chm, &field, StructuralEvaluator::default())
+        .expect("evaluator")
+        .with_resource_budget(budget, ObjectiveAxis::Shape);
+    let quarter = state(&[65_536, 65_536]);
+    let over = state(&[524_288, 1]);
+    assert!((evaluator.evaluate(&quarter).f_shape - 0.75).abs() < 1e-9);
+    assert_eq!(evaluator.evaluate(&over).f_shape, 0.0);
+    let checks = evaluator.resource_checks(&over).expect("budget set");
+    assert!(checks[0].is_exceeded());
+    let attribution = evaluator.explain(&quarter).expect("attribution");
+    assert_eq!(attribution.f_shape[0].name, "resource_budget");
+}
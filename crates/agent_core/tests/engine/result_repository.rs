@@ -0,0 +1,96 @@
+#[cfg(feature = "serde")]
+#[test]
+fn result_repository_stores_filters_and_warm_starts() {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use agent_core::ObjectiveTargets;
+    use agent_core::pipeline::DesignPipeline;
+    use agent_core::result_repository::{ResultRepository, RunFilter, RunManifest, StoredRun};
+
+    use crate::common::temp_vm;
+
+    let text = "高速化を重視する。";
+    let mut pipeline = DesignPipeline::new(temp_vm("result_repository_a"));
+    let report = pipeline.run(text).expect("pipeline run");
+    let mut profiles = hybrid_vm::ProfileStore::default();
+    profiles
+        .save(
+            "alice",
+            core_types::ProfileVector {
+                struct_weight: 1.0,
+                field_weight: 1.0,
+                risk_weight: 1.0,
+                cost_weight: 1.0,
+            },
+            hybrid_vm::DecisionWeights::default(),
+        )
+        .expect("profile");
+    profiles.set_default("alice").expect("default");
+    let (_, applied) = profiles.apply(None).expect("applied");
+    let manifest = RunManifest {
+        input_text: text.to_string(),
+        search: Some(pipeline.config().search),
+        profile: Some(applied.clone()),
+        ..RunManifest::default()
+    };
+
+    let root = std::env::temp_dir().join(format!(
+        "agent_core_result_repo_{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos()
+    ));
+    let repo = ResultRepository::open(&root).expect("open");
+    let run = StoredRun::from_report(manifest, &report, ["baseline", "latency"]);
+    let first = repo.store(run.clone()).expect("store");
+    let second = repo.store(run).expect("store");
+    assert_ne!(first, second);
+
+    let stored = repo.get(&first).expect("get").expect("present");
+    assert_eq!(stored.manifest.profile, Some(applied));
+    let by_profile = RunFilter {
+        profile: Some("alice".to_string()),
+        ..RunFilter::default()
+    };
+    assert_eq!(repo.list(&by_profile).expect("list").len(), 2);
+    let other_profile = RunFilter {
+        profile: Some("bob".to_string()),
+        ..RunFilter::default()
+    };
+    assert!(repo.list(&other_profile).expect("list").is_empty());
+    assert_eq!(stored.front.len(), report.pareto_front.len());
+    assert_eq!(stored.trace.front_sizes.len(), report.depth_fronts.len());
+    assert_eq!(
+        repo.list(&RunFilter::tagged("latency"))
+            .expect("list")
+            .len(),
+        2
+    );
+
+    assert!(repo.untag(&second, "latency").expect("untag"));
+    assert!(repo.tag(&second, "candidate").expect("tag"));
+    let tagged = repo.list(&RunFilter::tagged("latency")).expect("list");
+    assert_eq!(
+        tagged.iter().map(|r| r.id.as_str()).collect::<Vec<_>>(),
+        vec![first.as_str()]
+    );
+    let unreachable = RunFilter {
+        objectives: Some(ObjectiveTargets {
+            min_f_struct: 1.1,
+            ..ObjectiveTargets::default()
+        }),
+        ..RunFilter::default()
+    };
+    assert!(repo.list(&unreachable).expect("list").is_empty());
+
+    let seed = repo.seed_state(&first, 0).expect("read").expect("seed");
+    let mut warm =
+        DesignPipeline::new(temp_vm("result_repository_b")).with_seed_state(seed.clone());
+    let warm_report = warm.run(text).expect("warm run");
+    assert_eq!(warm_report.initial_state.map(|s| s.id), Some(seed.id));
+
+    assert!(repo.delete(&second).expect("delete"));
+    assert!(repo.get(&second).expect("get").is_none());
+    let _ = std::fs::remove_dir_all(root);
+}
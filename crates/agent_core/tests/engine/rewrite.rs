@@ -0,0 +1,117 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use agent_core::capability::{
+    NodeMatch, Production, RewriteEngine, RewriteError, RewriteRule, Slot,
+};
+use hybrid_vm::RuleId;
+use memory_space::{DesignNode, DesignState, StructuralGraph, Uuid};
+
+fn state(nodes: &[(u128, &str)], edges: &[(u128, u128)]) -> DesignState {
+    let mut graph = StructuralGraph::default();
+    for (id, kind) in nodes {
+        graph = graph.with_node_added(DesignNode::new(
+            Uuid::from_u128(*id),
+            *kind,
+            BTreeMap::new(),
+        ));
+    }
+    for (from, to) in edges {
+        graph = graph.with_edge_added(Uuid::from_u128(*from), Uuid::from_u128(*to));
+    }
+    DesignState::new(Uuid::from_u128(99), Arc::new(graph), "history:")
+}
+
+fn of_kind<'a>(state: &'a DesignState, kind: &str) -> Vec<&'a DesignNode> {
+    state
+        .graph
+        .nodes()
+        .values()
+        .filter(|n| n.kind == kind)
+        .collect()
+}
+
+#[test]
+fn insert_in_front_routes_callers_through_the_new_node() {
+    let base = state(&[(1, "Api"), (2, "Worker"), (3, "Db")], &[(1, 3), (2, 3)]);
+    let rule = RewriteRule::insert_in_front(RuleId::from_u128(5001), "Db", "Cache");
+
+    let next = rule.apply(&base).expect("Db present");
+    let cache = of_kind(&next, "Cache")[0].id;
+    let db = Uuid::from_u128(3);
+    let edges = next.graph.edges();
+    assert!(edges.contains(&(Uuid::from_u128(1), cache)));
+    assert!(edges.contains(&(Uuid::from_u128(2), cache)));
+    assert!(edges.contains(&(cache, db)));
+    assert_eq!(edges.iter().filter(|(_, to)| *to == db).count(), 1);
+    assert!(next.graph.validate().is_ok());
+    assert_eq!(next.profile_snapshot, "history:5001");
+    assert_eq!(rule.apply(&base).map(|s| s.id), Ok(next.id));
+
+    let no_db = state(&[(1, "Api")], &[]);
+    assert_eq!(rule.apply(&no_db).err(), Some(RewriteError::NoMatch));
+}
+
+#[test]
+fn split_with_load_balancer_duplicates_the_anchor() {
+    let base = state(&[(1, "Api"), (2, "Service"), (3, "Db")], &[(1, 2), (2, 3)]);
+    let rule = RewriteRule::split_with_load_balancer(RuleId::from_u128(5002), "Service");
+
+    let next = rule.apply(&base).expect("Service present");
+    let services = of_kind(&next, "Service");
+    assert_eq!(services.len(), 2);
+    let lb = of_kind(&next, "LoadBalancer")[0].id;
+    let edges = next.graph.edges();
+    assert!(edges.contains(&(Uuid::from_u128(1), lb)));
+    for service in services {
+        assert!(edges.contains(&(lb, service.id)));
+        assert!(edges.contains(&(service.id, Uuid::from_u128(3))));
+    }
+    assert!(next.graph.validate().is_ok());
+}
+
+#[test]
+fn add_edge_closing_a_cycle_is_an_error() {
+    let base = state(&[(1, "Api"), (2, "Db")], &[(1, 2)]);
+    let rule = RewriteRule {
+        id: RuleId::from_u128(5003),
+        name: "mirror Db both ways".to_string(),
+        lhs: NodeMatch::kind("Db"),
+        rhs: vec![
+            Production::CopyAnchor {
+                label: "replica".to_string(),
+            },
+            Production::AddEdge {
+                from: Slot::Anchor,
+                to: Slot::New("replica".to_string()),
+            },
+            Production::AddEdge {
+                from: Slot::New("replica".to_string()),
+                to: Slot::Anchor,
+            },
+        ],
+    };
+    let db = Uuid::from_u128(2);
+    match rule.apply(&base) {
+        Err(RewriteError::WouldCycle { from, to }) => assert_eq!(to, db, "{from:?}"),
+        other => panic!("expected WouldCycle, got {other:?}"),
+    }
+}
+
+#[test]
+fn engine_expands_every_match() {
+    let base = state(&[(1, "Api"), (2, "Db"), (3, "Db")], &[(1, 2), (1, 3)]);
+    let engine = RewriteEngine::new(vec![
+        RewriteRule::insert_in_front(RuleId::from_u128(5001), "Db", "Cache"),
+        RewriteRule::split_with_load_balancer(RuleId::from_u128(5002), "Queue"),
+    ]);
+
+    let successors = engine.expand(&base);
+    assert_eq!(successors.len(), 2);
+    assert!(
+        successors
+            .iter()
+            .all(|(id, _)| *id == RuleId::from_u128(5001))
+    );
+    assert_ne!(successors[0].1.id, successors[1].1.id);
+}
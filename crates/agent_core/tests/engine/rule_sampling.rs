@@ -0,0 +1,220 @@
+use std::collections::BTreeSet;
+
+use agent_core::capability::{
+    BoltzmannSelection, CategorySoftSampling, CategorySoftSelection, RuleRankingCache,
+    SamplingContext, SelectionStrategy, StratifiedSelection, TournamentSelection,
+};
+use agent_core::{
+    BenchConfig, CandidatePipelineConfig, ProfileUpdateType, RuleSelectionKind, SoftTraceParams,
+};
+use hybrid_vm::HybridVM;
+
+fn ctx(depth: usize) -> SamplingContext {
+    SamplingContext {
+        depth,
+        max_depth: 10,
+        seed: 7,
+        state_id: 42,
+    }
+}
+
+#[test]
+fn strategies_select_distinct_rules_within_budget() {
+    let shm = HybridVM::default_shm();
+    let strategies: [&dyn SelectionStrategy; 4] = [
+        &TournamentSelection { size: 3 },
+        &CategorySoftSampling {
+            alpha: 0.6,
+            temperature: 0.7,
+            entropy_beta: 0.25,
+        },
+        &BoltzmannSelection {
+            initial_temperature: 1.0,
+            final_temperature: 0.05,
+        },
+        &StratifiedSelection,
+    ];
+    for strategy in strategies {
+        let rules = shm.rules().iter().collect::<Vec<_>>();
+        let selected = strategy.select(rules, 5, ctx(1));
+        assert_eq!(selected.len(), 5, "{}", strategy.name());
+        let ids = selected.iter().map(|r| r.id).collect::<BTreeSet<_>>();
+        assert_eq!(ids.len(), 5, "{}", strategy.name());
+
+        let again = strategy.select(shm.rules().iter().collect(), 5, ctx(1));
+        assert_eq!(
+            selected.iter().map(|r| r.id).collect::<Vec<_>>(),
+            again.iter().map(|r| r.id).collect::<Vec<_>>()
+        );
+    }
+}
+
+fn ids(rules: &[&hybrid_vm::DesignRule]) -> Vec<hybrid_vm::RuleId> {
+    rules.iter().map(|r| r.id).collect()
+}
+
+#[test]
+fn soft_sampling_is_reproducible_and_independent_of_rule_order() {
+    let shm = HybridVM::default_shm();
+    let strategy = CategorySoftSampling {
+        alpha: 0.6,
+        temperature: 5.0,
+        entropy_beta: 0.25,
+    };
+    let forward = shm.rules().iter().collect::<Vec<_>>();
+    let reversed = forward.iter().rev().copied().collect::<Vec<_>>();
+
+    let picked = strategy.select(forward.clone(), 4, ctx(2));
+    assert_eq!(ids(&picked), ids(&strategy.select(reversed, 4, ctx(2))));
+
+    // Other depths and states draw from their own streams.
+    let draws = (0..8)
+        .map(|state_id| {
+            let ctx = SamplingContext { state_id, ..ctx(2) };
+            ids(&strategy.select(forward.clone(), 4, ctx))
+        })
+        .collect::<BTreeSet<_>>();
+    assert!(draws.len() > 1);
+    assert_ne!(
+        (1..6)
+            .map(|d| ids(&strategy.select(forward.clone(), 4, ctx(d))))
+            .collect::<BTreeSet<_>>()
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn soft_sampling_approaches_top_k_as_temperature_falls() {
+    let shm = HybridVM::default_shm();
+    let rules = || shm.rules().iter().collect::<Vec<_>>();
+    let greedy = CategorySoftSelection {
+        alpha: 0.6,
+        temperature: 1e-4,
+        entropy_beta: 0.25,
+    };
+    let sampled = CategorySoftSampling {
+        alpha: 0.6,
+        temperature: 1e-4,
+        entropy_beta: 0.25,
+    };
+    let top = greedy.select(rules(), 3, ctx(1));
+    let drawn = sampled.select(rules(), 3, ctx(1));
+    assert_eq!(
+        ids(&top).into_iter().collect::<BTreeSet<_>>(),
+        ids(&drawn).into_iter().collect::<BTreeSet<_>>()
+    );
+}
+
+#[test]
+fn stratified_selection_takes_one_rule_per_stratum_first() {
+    let shm = HybridVM::default_shm();
+    let stratum = |r: &hybrid_vm::DesignRule| {
+        let e = &r.expected_effect;
+        (
+            format!("{:?}", r.category),
+            e.delta_struct + e.delta_field + e.delta_risk + e.delta_cost >= 0.0,
+        )
+    };
+    let strata = shm.rules().iter().map(stratum).collect::<BTreeSet<_>>();
+    let selected = StratifiedSelection.select(shm.rules().iter().collect(), strata.len(), ctx(1));
+    let covered = selected.iter().map(|r| stratum(r)).collect::<BTreeSet<_>>();
+    assert_eq!(covered, strata);
+}
+
+#[test]
+fn boltzmann_temperature_anneals_towards_final() {
+    let strategy = BoltzmannSelection {
+        initial_temperature: 1.0,
+        final_temperature: 0.01,
+    };
+    assert!((strategy.temperature_at(1, 10) - 1.0).abs() < 1e-12);
+    assert!((strategy.temperature_at(10, 10) - 0.01).abs() < 1e-12);
+    assert!(strategy.temperature_at(5, 10) < strategy.temperature_at(4, 10));
+}
+
+#[test]
+fn bench_harness_compares_selection_strategies() {
+    let config = BenchConfig {
+        depth: 2,
+        beam: 2,
+        iterations: 1,
+        warmup: 0,
+        seed: 3,
+        norm_alpha: 0.1,
+        field_dimensions: 64,
+        candidate_pipeline: CandidatePipelineConfig::default(),
+    };
+    let kinds = [
+        RuleSelectionKind::CategorySoft,
+        RuleSelectionKind::CategorySoftSample,
+        RuleSelectionKind::Tournament { size: 2 },
+        RuleSelectionKind::Stratified,
+    ];
+    let results =
+        agent_core::run_bench_selection_strategies(config, SoftTraceParams::default(), &kinds);
+    assert_eq!(results.len(), kinds.len());
+    for ((kind, result), expected) in results.iter().zip(kinds) {
+        assert_eq!(*kind, expected);
+        assert_eq!(result.depth, 2);
+    }
+}
+
+#[test]
+fn ranking_cache_reuses_rankings_until_rules_or_profile_change() {
+    let mut shm = HybridVM::default_shm();
+    let strategy = CategorySoftSelection {
+        alpha: 0.6,
+        temperature: 0.7,
+        entropy_beta: 0.25,
+    };
+    assert!(strategy.ignores_context());
+    assert!(!TournamentSelection { size: 2 }.ignores_context());
+    let direct = ids(&strategy.select(shm.rules().iter().collect(), 5, ctx(1)));
+
+    let mut cache = RuleRankingCache::new(8);
+    let rank = |rules| strategy.select(rules, 5, ctx(1));
+    let first = cache.get_or_rank(shm.rules().iter().collect(), 5, 0.31, 0.5, rank);
+    let reversed = shm.rules().iter().rev().collect();
+    let second = cache.get_or_rank(reversed, 5, 0.33, 0.52, |_| unreachable!());
+    assert_eq!(ids(&first), direct);
+    assert_eq!(ids(&second), direct);
+    assert_eq!((cache.stats().hits, cache.stats().misses), (1, 1));
+
+    // Another lambda bucket, then a re-weighted rule, each miss once.
+    cache.get_or_rank(shm.rules().iter().collect(), 5, 0.31, 0.9, rank);
+    let boosted = shm.rules()[0].id;
+    shm.set_priority(boosted, 10.0);
+    let reweighted = cache.get_or_rank(shm.rules().iter().collect(), 5, 0.31, 0.5, |rules| {
+        strategy.select(rules, 5, ctx(1))
+    });
+    assert_eq!(reweighted[0].id, boosted);
+    assert_eq!(cache.stats().misses, 3);
+    assert_eq!(cache.len(), 3);
+
+    cache.on_profile_update(ProfileUpdateType::TypeAExplicit);
+    assert!(cache.is_empty());
+    assert_eq!(cache.stats().invalidations, 1);
+}
+
+#[test]
+fn bench_reports_ranking_cache_hit_rate() {
+    let config = BenchConfig {
+        depth: 3,
+        beam: 3,
+        iterations: 1,
+        warmup: 0,
+        seed: 5,
+        norm_alpha: 0.1,
+        field_dimensions: 64,
+        candidate_pipeline: CandidatePipelineConfig::default(),
+    };
+    let kinds = [
+        RuleSelectionKind::CategorySoft,
+        RuleSelectionKind::Tournament { size: 2 },
+    ];
+    let results =
+        agent_core::run_bench_selection_strategies(config, SoftTraceParams::default(), &kinds);
+    assert!(results[0].1.ranking_cache_hit_rate > 0.0);
+    assert_eq!(results[1].1.ranking_cache_hit_rate, 0.0);
+}
@@ -0,0 +1,43 @@
+use agent_core::{LatencyStats, SemanticBenchConfig};
+
+#[test]
+fn semantic_bench_reports_latency_per_operation_and_size() {
+    let config = SemanticBenchConfig {
+        iterations: 3,
+        warmup: 1,
+        seed: 7,
+        l1_counts: vec![4, 12],
+        store_sizes: vec![10, 50],
+        recall_top_k: 5,
+    };
+    let result = agent_core::run_semantic_bench(&config);
+    assert_eq!(result.iterations, 3);
+    assert_eq!(
+        result.rebuild_l2.iter().map(|p| p.size).collect::<Vec<_>>(),
+        config.l1_counts
+    );
+    assert_eq!(
+        result.recall.iter().map(|p| p.size).collect::<Vec<_>>(),
+        config.store_sizes
+    );
+    let all = [result.analyze_text, result.snapshot, result.snapshot_diff]
+        .into_iter()
+        .chain(result.rebuild_l2.iter().map(|p| p.latency))
+        .chain(result.recall.iter().map(|p| p.latency));
+    for stats in all {
+        assert_eq!(stats.samples, 3);
+        assert!(stats.min_us <= stats.p50_us && stats.p50_us <= stats.p95_us);
+        assert!(stats.p95_us <= stats.max_us && stats.avg_us <= stats.max_us);
+    }
+}
+
+#[test]
+fn latency_stats_summarize_samples() {
+    let stats = LatencyStats::from_samples(&[4.0, 1.0, 3.0, 2.0, 100.0]);
+    assert_eq!(stats.samples, 5);
+    assert_eq!(stats.min_us, 1.0);
+    assert_eq!(stats.p50_us, 3.0);
+    assert_eq!(stats.p95_us, 100.0);
+    assert_eq!(stats.avg_us, 22.0);
+    assert_eq!(LatencyStats::from_samples(&[]), LatencyStats::default());
+}
@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use agent_core::capability::{DEFAULT_MAX_TREE_NODES, StateArchive, StateOrigin, canonical_hash};
+use agent_core::{
+    BeamSearch, EvaluationPolicy, NormalizationConfig, SearchConfig, SearchMode, WarmupConfig,
+};
+use core_types::ObjectiveVector;
+use field_engine::FieldEngine;
+use hybrid_vm::{Evaluator, HybridVM};
+use memory_space::{DesignNode, DesignState, StructuralGraph, Uuid, Value};
+
+struct NodeCountEvaluator;
+
+impl Evaluator for NodeCountEvaluator {
+    fn evaluate(&self, state: &DesignState) -> ObjectiveVector {
+        ObjectiveVector {
+            f_struct: (state.graph.nodes().len() as f64 / 10.0).min(1.0),
+            f_field: 0.5,
+            f_risk: 0.5,
+            f_shape: 0.5,
+        }
+    }
+}
+
+/// Service -> Database, with the given node ids.
+fn pair_state(state: u128, service: u128, database: u128) -> DesignState {
+    let graph = StructuralGraph::default()
+        .with_node_added(DesignNode::new(
+            Uuid::from_u128(service),
+            "Service",
+            BTreeMap::from([("port".to_string(), Value::Int(80))]),
+        ))
+        .with_node_added(DesignNode::new(
+            Uuid::from_u128(database),
+            "Database",
+            BTreeMap::new(),
+        ))
+        .with_edge_added(Uuid::from_u128(service), Uuid::from_u128(database));
+    DesignState::new(Uuid::from_u128(state), Arc::new(graph), "history:")
+}
+
+fn search_config() -> SearchConfig {
+    SearchConfig {
+        beam_width: 3,
+        max_depth: 3,
+        norm_alpha: 0.1,
+        targets: None,
+        evaluation: EvaluationPolicy::Single,
+        record_tree: true,
+        max_tree_nodes: DEFAULT_MAX_TREE_NODES,
+        epsilon_constraint: None,
+        lexicographic: None,
+        crossover_pairs: 1,
+        normalization: NormalizationConfig::default(),
+        warmup: WarmupConfig::default(),
+        dominance_tolerance: None,
+        explain: false,
+        directions: agent_core::ObjectiveDirections::default(),
+        validate_invariants: false,
+    }
+}
+
+#[test]
+fn canonical_hash_ignores_node_ids_but_not_structure() {
+    let left = pair_state(1, 10, 20);
+    let relabelled = pair_state(2, 30, 5);
+    assert_eq!(
+        canonical_hash(&left.graph),
+        canonical_hash(&relabelled.graph)
+    );
+
+    let reversed = left
+        .graph
+        .with_edge_removed(Uuid::from_u128(10), Uuid::from_u128(20));
+    let reversed = reversed.with_edge_added(Uuid::from_u128(20), Uuid::from_u128(10));
+    assert_ne!(canonical_hash(&left.graph), canonical_hash(&reversed));
+    assert_ne!(
+        canonical_hash(&left.graph),
+        canonical_hash(&StructuralGraph::default())
+    );
+}
+
+#[test]
+fn archived_run_records_every_beam_with_provenance() {
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    let search = BeamSearch {
+        shm: &shm,
+        chm: &chm,
+        evaluator: &NodeCountEvaluator,
+        config: search_config(),
+    };
+    let seed = pair_state(100, 1, 2);
+    let result = search.search_archived(
+        &seed,
+        SearchMode::Manual,
+        StateArchive::new(FieldEngine::new(16)),
+    );
+    let archive = result.archive.as_ref().expect("archive returned");
+
+    let root = archive.get(seed.id).expect("initial state archived");
+    assert_eq!((root.depth, root.origin), (0, StateOrigin::default()));
+    for front in &result.depth_fronts {
+        for id in &front.state_ids {
+            let entry = archive.get(*id).expect("accepted state archived");
+            assert!(entry.depth <= front.depth);
+        }
+    }
+
+    // Archived provenance agrees with the search tree.
+    let tree = result.search_tree.as_ref().expect("tree recorded");
+    let lineage = archive.lineage(result.final_frontier[0].id);
+    assert_eq!(lineage[0].state.id, seed.id);
+    assert_eq!(
+        lineage.iter().map(|e| e.depth).collect::<Vec<_>>(),
+        (0..lineage.len()).collect::<Vec<_>>()
+    );
+    for entry in &lineage[1..] {
+        let node = tree
+            .nodes
+            .iter()
+            .find(|node| node.state_id == entry.state.id)
+            .expect("archived state in tree");
+        assert_eq!(entry.origin.parent, node.parent);
+        assert_eq!(entry.origin.rule_id, node.rule_id);
+    }
+
+    let plain = search.search_with_mode(&seed, SearchMode::Manual);
+    assert!(plain.archive.is_none());
+}
+
+#[test]
+fn find_similar_ranks_identical_structures_first_and_skips_the_query() {
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    let search = BeamSearch {
+        shm: &shm,
+        chm: &chm,
+        evaluator: &NodeCountEvaluator,
+        config: search_config(),
+    };
+    let seed = pair_state(100, 1, 2);
+    let result = search.search_archived(
+        &seed,
+        SearchMode::Auto,
+        StateArchive::new(FieldEngine::new(16)),
+    );
+    let archive = result.archive.expect("archive returned");
+    assert!(archive.len() > 3);
+
+    let similar = archive.find_similar(&seed, 3);
+    assert_eq!(similar.len(), 3);
+    assert!(similar.iter().all(|s| s.entry.state.id != seed.id));
+    assert!(
+        similar
+            .windows(2)
+            .all(|pair| pair[0].similarity >= pair[1].similarity)
+    );
+    assert!(
+        similar
+            .iter()
+            .all(|s| s.similarity > 0.0 && s.similarity <= 1.0)
+    );
+
+    // The seed under other ids is recognised as the archived initial state.
+    let twin = pair_state(999, 40, 41);
+    let best = archive.find_similar(&twin, 1);
+    assert_eq!(best[0].entry.state.id, seed.id);
+    assert!(best[0].identical);
+    assert_eq!(best[0].similarity, 1.0);
+    assert_eq!(
+        archive
+            .with_hash(canonical_hash(&twin.graph))
+            .next()
+            .map(|e| e.state.id),
+        Some(seed.id)
+    );
+    assert!(archive.find_similar(&twin, 0).is_empty());
+}
@@ -0,0 +1,221 @@
+use agent_core::capability::{
+    DEFAULT_MAX_TREE_NODES, RuleSuggester, SuggestionConfig, SuggestionOutcome, SuggestionRejection,
+};
+use agent_core::{
+    BeamSearch, EvaluationPolicy, NormalizationConfig, SearchConfig, SearchMode, WarmupConfig,
+};
+use core_types::ObjectiveVector;
+use hybrid_vm::{
+    DesignRule, EffectVector, Evaluator, HybridVM, Precondition, PreconditionError, RuleCategory,
+    Transformation,
+};
+use memory_space::{DesignState, Uuid};
+
+use crate::common::seed_state;
+
+struct NodeCountEvaluator;
+
+impl Evaluator for NodeCountEvaluator {
+    fn evaluate(&self, state: &DesignState) -> ObjectiveVector {
+        ObjectiveVector {
+            f_struct: (state.graph.nodes().len() as f64 / 10.0).min(1.0),
+            f_field: 0.5,
+            f_risk: 0.5,
+            f_shape: 0.5,
+        }
+    }
+}
+
+fn rule(id: u128, transformation: Transformation, precondition: Precondition) -> DesignRule {
+    DesignRule {
+        id: Uuid::from_u128(id),
+        category: RuleCategory::Structural,
+        priority: 0.5,
+        precondition,
+        transformation,
+        expected_effect: EffectVector {
+            delta_struct: 0.0,
+            delta_field: 0.0,
+            delta_risk: 0.0,
+            delta_cost: 0.0,
+        },
+    }
+}
+
+/// One useful rule among a batch that trips every rejection reason.
+struct MixedSuggester;
+
+impl RuleSuggester for MixedSuggester {
+    fn name(&self) -> &str {
+        "mixed"
+    }
+
+    fn suggest(&self, _state: &DesignState, _depth: usize) -> Vec<DesignRule> {
+        let known = HybridVM::rules(&HybridVM::default_shm())[0].id.as_u128();
+        let mut non_finite = rule(905, Transformation::AddNode, Precondition::Always);
+        non_finite.expected_effect.delta_risk = f64::INFINITY;
+        vec![
+            rule(900, Transformation::AddNode, Precondition::Always),
+            rule(900, Transformation::AddNode, Precondition::Always),
+            rule(known, Transformation::AddNode, Precondition::Always),
+            rule(
+                903,
+                Transformation::AddNode,
+                Precondition::NodeCount {
+                    min: 5,
+                    max: Some(1),
+                },
+            ),
+            rule(904, Transformation::AddNode, Precondition::Custom(|_| true)),
+            non_finite,
+            rule(
+                906,
+                Transformation::AddNode,
+                Precondition::NodeCount {
+                    min: 100,
+                    max: None,
+                },
+            ),
+            rule(907, Transformation::ModifyAttribute, Precondition::Always),
+            rule(908, Transformation::RemoveNode, Precondition::Always),
+        ]
+    }
+}
+
+fn search_config() -> SearchConfig {
+    SearchConfig {
+        beam_width: 2,
+        max_depth: 2,
+        norm_alpha: 0.1,
+        targets: None,
+        evaluation: EvaluationPolicy::Single,
+        record_tree: true,
+        max_tree_nodes: DEFAULT_MAX_TREE_NODES,
+        epsilon_constraint: None,
+        lexicographic: None,
+        crossover_pairs: 0,
+        normalization: NormalizationConfig::default(),
+        warmup: WarmupConfig::default(),
+        dominance_tolerance: None,
+        explain: false,
+        directions: agent_core::ObjectiveDirections::default(),
+        validate_invariants: false,
+    }
+}
+
+#[test]
+fn suggestions_are_vetted_before_joining_the_candidate_pool() {
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    let search = BeamSearch {
+        shm: &shm,
+        chm: &chm,
+        evaluator: &NodeCountEvaluator,
+        config: search_config(),
+    };
+    let seed = seed_state("history:");
+    let result = search.search_with_suggester(
+        &seed,
+        SearchMode::Manual,
+        &MixedSuggester,
+        SuggestionConfig {
+            max_per_state: 16,
+            ..SuggestionConfig::default()
+        },
+    );
+
+    let first_depth = result
+        .suggestions
+        .iter()
+        .filter(|s| s.parent == seed.id)
+        .collect::<Vec<_>>();
+    assert_eq!(first_depth.len(), 9);
+    assert!(
+        first_depth
+            .iter()
+            .all(|s| s.suggester == "mixed" && s.depth == 1)
+    );
+    let outcomes = first_depth
+        .iter()
+        .map(|s| s.outcome.clone())
+        .collect::<Vec<_>>();
+    let SuggestionOutcome::Admitted {
+        child,
+        objective_delta,
+    } = outcomes[0].clone()
+    else {
+        panic!("useful suggestion rejected: {:?}", outcomes[0]);
+    };
+    assert!((objective_delta.f_struct - 0.1).abs() < 1e-12);
+    assert_eq!(
+        outcomes[1..],
+        [
+            SuggestionOutcome::Rejected(SuggestionRejection::DuplicateId),
+            SuggestionOutcome::Rejected(SuggestionRejection::DuplicateId),
+            SuggestionOutcome::Rejected(SuggestionRejection::InvalidPrecondition(
+                PreconditionError::EmptyRange
+            )),
+            SuggestionOutcome::Rejected(SuggestionRejection::InvalidPrecondition(
+                PreconditionError::NotDeclarative
+            )),
+            SuggestionOutcome::Rejected(SuggestionRejection::NonFinite),
+            SuggestionOutcome::Rejected(SuggestionRejection::PreconditionUnmet),
+            SuggestionOutcome::Rejected(SuggestionRejection::NoChange),
+            SuggestionOutcome::Rejected(SuggestionRejection::Regression),
+        ]
+    );
+
+    // The admitted child is an ordinary candidate, tagged with the
+    // suggested rule in the search tree.
+    let tree = result.search_tree.as_ref().expect("tree recorded");
+    let node = tree
+        .children(seed.id)
+        .find(|node| node.state_id == child)
+        .expect("suggested child in tree");
+    assert_eq!(node.rule_id, Some(Uuid::from_u128(900)));
+    assert_eq!(first_depth[0].child(), Some(child));
+    assert!(
+        result
+            .suggestions
+            .iter()
+            .filter(|s| s.depth == 2)
+            .any(|s| s.is_admitted())
+    );
+}
+
+#[test]
+fn suggestions_beyond_the_per_state_cap_are_not_examined() {
+    let shm = HybridVM::default_shm();
+    let chm = HybridVM::empty_chm();
+    let search = BeamSearch {
+        shm: &shm,
+        chm: &chm,
+        evaluator: &NodeCountEvaluator,
+        config: SearchConfig {
+            max_depth: 1,
+            ..search_config()
+        },
+    };
+    let config = SuggestionConfig {
+        max_per_state: 3,
+        reject_regressions: false,
+    };
+    let result = search.search_with_suggester(
+        &seed_state("history:"),
+        SearchMode::Auto,
+        &MixedSuggester,
+        config,
+    );
+    assert_eq!(result.suggestions.len(), 3);
+    assert_eq!(
+        result
+            .suggestions
+            .iter()
+            .filter(|s| s.is_admitted())
+            .count(),
+        1
+    );
+
+    let plain = search.search_with_mode(&seed_state("history:"), SearchMode::Auto);
+    assert!(plain.suggestions.is_empty());
+}
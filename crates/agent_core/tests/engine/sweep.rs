@@ -0,0 +1,101 @@
+use agent_core::runtime::sweep::{ParamRange, SweepConfig, SweepParam, SweepStrategy, run_sweep};
+use agent_core::{NormalizationConfig, SoftTraceParams, TraceRunConfig, WarmupConfig};
+
+fn sweep(strategy: SweepStrategy, parallelism: usize) -> SweepConfig {
+    SweepConfig {
+        base: TraceRunConfig {
+            depth: 2,
+            beam: 2,
+            seed: 9,
+            norm_alpha: 0.1,
+            adaptive_alpha: false,
+            hv_guided: false,
+            raw_output_path: None,
+            normalization: NormalizationConfig::default(),
+            warmup: WarmupConfig::default(),
+            calibration: None,
+            convergence: None,
+            field_dimensions: agent_core::DEFAULT_FIELD_DIMENSIONS,
+            novelty: None,
+            repair: None,
+            candidate_pipeline: agent_core::CandidatePipelineConfig::default(),
+            diversity: None,
+            directions: agent_core::ObjectiveDirections::default(),
+        },
+        params: SoftTraceParams::default(),
+        ranges: vec![
+            ParamRange {
+                param: SweepParam::Temperature,
+                min: 0.2,
+                max: 1.0,
+                steps: 2,
+            },
+            ParamRange {
+                param: SweepParam::NormAlpha,
+                min: 0.05,
+                max: 0.2,
+                steps: 2,
+            },
+        ],
+        strategy,
+        runs_per_point: 1,
+        parallelism,
+    }
+}
+
+#[test]
+fn grid_sweep_ranks_every_combination() {
+    let report = run_sweep(&sweep(SweepStrategy::Grid, 2));
+
+    assert_eq!(report.outcomes.len(), 4);
+    assert!(
+        report
+            .outcomes
+            .windows(2)
+            .all(|w| w[0].final_hv >= w[1].final_hv)
+    );
+    let mut points = report
+        .outcomes
+        .iter()
+        .map(|o| (o.values[0].1, o.values[1].1))
+        .collect::<Vec<_>>();
+    points.sort_by(|a, b| a.partial_cmp(b).expect("finite"));
+    assert_eq!(
+        points,
+        vec![(0.2, 0.05), (0.2, 0.2), (1.0, 0.05), (1.0, 0.2)]
+    );
+
+    let csv = report.to_csv();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("rank,temperature,norm_alpha,final_hv,collapse_rate,avg_per_depth_ms,runs")
+    );
+    assert_eq!(lines.count(), 4);
+}
+
+#[test]
+fn random_sweep_is_seeded_and_parallelism_does_not_change_outcomes() {
+    let strategy = SweepStrategy::Random {
+        samples: 3,
+        seed: 17,
+    };
+    let sequential = run_sweep(&sweep(strategy, 1));
+    let parallel = run_sweep(&sweep(strategy, 3));
+
+    assert_eq!(sequential.outcomes.len(), 3);
+    for outcome in &sequential.outcomes {
+        assert!((0.2..=1.0).contains(&outcome.values[0].1));
+        assert!((0.05..=0.2).contains(&outcome.values[1].1));
+    }
+    let key = |r: &agent_core::runtime::sweep::SweepReport| {
+        let mut k = r
+            .outcomes
+            .iter()
+            .map(|o| (o.values[0].1, o.final_hv, o.collapse_rate))
+            .collect::<Vec<_>>();
+        k.sort_by(|a, b| a.partial_cmp(b).expect("finite"));
+        k
+    };
+    assert_eq!(key(&sequential), key(&parallel));
+}
@@ -0,0 +1,124 @@
+use agent_core::runtime::trace_join::{CorrelatedTraces, TimelineEntry};
+use agent_core::{TraceRow, TraceRowBuilder};
+use core_types::ObjectiveVector;
+use hybrid_vm::{
+    CorrelationId, ExecutionMode, HybridTraceRow, L2ChangeSet, SemanticOperation,
+    SemanticTraceEvent,
+};
+
+fn event(seq: u64, id: Option<CorrelationId>) -> SemanticTraceEvent {
+    SemanticTraceEvent {
+        seq,
+        correlation_id: id,
+        operation: SemanticOperation::RebuildL2 {
+            changes: L2ChangeSet::default(),
+        },
+    }
+}
+
+fn evaluation(seq: u64, id: Option<CorrelationId>) -> HybridTraceRow {
+    HybridTraceRow {
+        request_id: 0,
+        depth: 1,
+        mode: ExecutionMode::RecallFirst,
+        objective: ObjectiveVector {
+            f_struct: 0.0,
+            f_field: 0.0,
+            f_risk: 0.0,
+            f_shape: 0.0,
+        },
+        mode_switch: None,
+        seq,
+        correlation_id: id,
+    }
+}
+
+fn search_row(depth: usize, id: CorrelationId) -> TraceRow {
+    TraceRowBuilder::new()
+        .apply(|r| {
+            r.depth = depth;
+            r.correlation_id = id.to_string();
+        })
+        .build()
+}
+
+#[test]
+fn timeline_keeps_one_run_and_places_search_at_its_start_seq() {
+    let run = CorrelationId(7);
+    let other = CorrelationId(8);
+    let semantic = [
+        event(0, Some(run)),
+        event(1, Some(other)),
+        event(4, Some(run)),
+    ];
+    let evaluations = [evaluation(2, Some(run)), evaluation(3, None)];
+    let search = [search_row(1, run), search_row(0, run), search_row(0, other)];
+    let traces =
+        CorrelatedTraces::join(run, &semantic, &evaluations, &search).with_search_seq(Some(3));
+
+    let order = traces
+        .timeline()
+        .into_iter()
+        .map(|entry| match entry {
+            TimelineEntry::Semantic(e) => format!("s{}", e.seq),
+            TimelineEntry::Evaluation(r) => format!("e{}", r.seq),
+            TimelineEntry::SearchDepth { row, .. } => format!("d{}", row.depth),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(order, ["s0", "e2", "d0", "d1", "s4"]);
+    assert!(
+        traces
+            .render()
+            .starts_with("correlation 0000000000000007\n")
+    );
+}
+
+#[test]
+fn correlated_traces_interleave_semantic_operations_with_search_depths() {
+    use agent_core::pipeline::{DesignPipeline, PipelineConfig, PipelineStage};
+
+    use crate::common::temp_vm;
+
+    let config = PipelineConfig {
+        stop_after: Some(PipelineStage::Search),
+        ..PipelineConfig::default()
+    };
+    let mut pipeline = DesignPipeline::new(temp_vm("correlate")).with_config(config);
+    let first = pipeline.run("高速化を重視する").expect("first run");
+    let report = pipeline.run("セキュリティを確保する").expect("second run");
+    let id = report.correlation_id.expect("correlation id");
+    assert_ne!(first.correlation_id, Some(id));
+    assert_eq!(pipeline.vm().correlation_id(), None);
+    assert_eq!(report.search_trace.len(), report.depth_fronts.len());
+    assert!(
+        report
+            .search_trace
+            .iter()
+            .all(|row| row.correlation_id == id.to_string())
+    );
+
+    let traces = pipeline.correlated_traces().expect("traces");
+    let timeline = traces.timeline();
+    let names = timeline
+        .iter()
+        .map(|entry| match entry {
+            TimelineEntry::Semantic(event) => event.operation.name().to_string(),
+            TimelineEntry::Evaluation(_) => "evaluate".to_string(),
+            TimelineEntry::SearchDepth { row, .. } => format!("depth {}", row.depth),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(names[..2], ["analyze", "rebuild_l2"]);
+    assert_eq!(names.len(), 2 + report.depth_fronts.len());
+    let TimelineEntry::Semantic(analyzed) = timeline[0] else {
+        panic!("analysis first");
+    };
+    let SemanticOperation::Analyze { added, .. } = &analyzed.operation else {
+        panic!("analyze event");
+    };
+    assert!(!added.is_empty());
+    let TimelineEntry::SearchDepth { front, .. } = timeline[2] else {
+        panic!("search after the rebuild");
+    };
+    assert_eq!(front, report.depth_fronts.first());
+    assert!(traces.render().contains("] analyze inline"));
+}
@@ -9,6 +9,16 @@ fn stability_depth50_beam5() {
         adaptive_alpha: false,
         hv_guided: false,
         raw_output_path: None,
+        normalization: agent_core::NormalizationConfig::default(),
+        warmup: agent_core::WarmupConfig::default(),
+        calibration: None,
+        convergence: None,
+        field_dimensions: agent_core::DEFAULT_FIELD_DIMENSIONS,
+        novelty: None,
+        repair: None,
+        candidate_pipeline: agent_core::CandidatePipelineConfig::default(),
+        diversity: None,
+        directions: agent_core::ObjectiveDirections::default(),
     };
     let rows = agent_core::runtime::execute_soft_trace(cfg, agent_core::SoftTraceParams::default());
     assert!(!rows.is_empty());
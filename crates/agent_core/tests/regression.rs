@@ -2,3 +2,5 @@
 mod golden;
 #[path = "regression/reproducibility.rs"]
 mod reproducibility;
+#[path = "regression/trace_diff.rs"]
+mod trace_diff;
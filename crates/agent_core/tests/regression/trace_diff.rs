@@ -0,0 +1,110 @@
+use agent_core::runtime::{TraceDiffConfig, TraceDiffVerdict, trace_diff, trace_diff_with};
+use agent_core::{
+    NormalizationConfig, SoftTraceParams, TraceRow, TraceRowBuilder, TraceRunConfig, WarmupConfig,
+};
+
+fn run(seed: u64) -> Vec<TraceRow> {
+    let config = TraceRunConfig {
+        depth: 4,
+        beam: 3,
+        seed,
+        norm_alpha: 0.1,
+        adaptive_alpha: false,
+        hv_guided: false,
+        raw_output_path: None,
+        normalization: NormalizationConfig::default(),
+        warmup: WarmupConfig::default(),
+        calibration: None,
+        convergence: None,
+        field_dimensions: 32,
+        novelty: None,
+        repair: None,
+        candidate_pipeline: agent_core::CandidatePipelineConfig::default(),
+        diversity: None,
+        directions: agent_core::ObjectiveDirections::default(),
+    };
+    agent_core::runtime::execute_soft_trace(config, SoftTraceParams::default())
+}
+
+fn row(depth: usize, diversity: f32) -> TraceRow {
+    TraceRowBuilder::new()
+        .apply(|row| {
+            row.depth = depth;
+            row.diversity = diversity;
+        })
+        .build()
+}
+
+#[test]
+fn same_seed_runs_are_identical() {
+    let report = trace_diff(&run(7), &run(7));
+    assert_eq!(
+        report.verdict,
+        TraceDiffVerdict::Identical,
+        "{}",
+        report.summary()
+    );
+    assert_eq!(report.compared_depths, 4);
+    assert_eq!(report.first_divergence, None);
+}
+
+#[test]
+fn deltas_are_judged_against_per_metric_thresholds() {
+    let a = vec![row(1, 0.5), row(2, 0.5), row(3, 0.5)];
+    let b = vec![row(1, 0.5), row(2, 0.5001), row(3, 0.7)];
+
+    let strict = trace_diff(&a, &b);
+    assert_eq!(strict.verdict, TraceDiffVerdict::Diverged);
+    assert_eq!(strict.first_divergence, Some(2));
+    assert_eq!(strict.deltas.len(), 2);
+    assert_eq!(strict.deltas[1].metric, "diversity");
+    assert!((strict.deltas[1].delta - 0.2).abs() < 1e-6);
+
+    let loose = trace_diff_with(
+        &a,
+        &b,
+        &TraceDiffConfig::default().with_threshold("diversity", 0.01),
+    );
+    assert_eq!(loose.first_divergence, Some(3));
+    assert!(loose.max_abs_delta["diversity"] > 0.19);
+
+    let tolerant = trace_diff_with(
+        &a,
+        &b,
+        &TraceDiffConfig::default().with_threshold("diversity", 0.5),
+    );
+    assert_eq!(tolerant.verdict, TraceDiffVerdict::WithinTolerance);
+    assert!(tolerant.is_match());
+}
+
+#[test]
+fn missing_depths_and_text_fields_count_as_divergence() {
+    let a = vec![row(1, 0.5), row(2, 0.5)];
+    let mut b = vec![row(1, 0.5)];
+    let report = trace_diff(&a, &b);
+    assert_eq!(report.missing_in_b, vec![2]);
+    assert_eq!(report.first_divergence, Some(2));
+
+    b[0].convergence_reason = "hv_stagnant".to_string();
+    let report = trace_diff(&a, &b);
+    assert_eq!(report.first_divergence, Some(1));
+    assert_eq!(report.text_mismatches[0].field, "convergence_reason");
+    assert!(report.summary().starts_with("diverged"));
+}
+
+#[test]
+fn instrumentation_is_ignored_unless_requested() {
+    let a = vec![row(1, 0.5)];
+    let mut b = vec![row(1, 0.5)];
+    b[0].field_total_us = 120.0;
+    assert_eq!(trace_diff(&a, &b).verdict, TraceDiffVerdict::Identical);
+
+    let config = TraceDiffConfig {
+        ignore_instrumentation: false,
+        ..TraceDiffConfig::default()
+    };
+    assert_eq!(
+        trace_diff_with(&a, &b, &config).deltas[0].metric,
+        "field_total_us"
+    );
+}
@@ -138,6 +138,15 @@ impl Chm {
             .unwrap_or_default()
     }
 
+    /// Strength of the `from -> to` edge, if one exists.
+    pub fn strength(&self, from: RuleId, to: RuleId) -> Option<f64> {
+        self.rule_graph
+            .get(&from)?
+            .iter()
+            .find(|edge| edge.to_rule == to)
+            .map(|edge| edge.strength)
+    }
+
     pub fn update_strength(&mut self, from: RuleId, to: RuleId, delta: f64) {
         if from == to {
             return;
@@ -250,6 +259,8 @@ mod tests {
 
         let related = chm.related_rules(r1);
         assert_eq!(related, vec![r2, r3]);
+        assert_eq!(chm.strength(r1, r3), Some(-0.1));
+        assert_eq!(chm.strength(r2, r1), None);
     }
 
     #[test]
@@ -0,0 +1,254 @@
+//! Golden-trace conformance harness.
+//!
+//! A handful of canonical trace configurations, spanning the soft,
+//! baseline-off and balanced search variants plus hypervector guidance and
+//! novelty, are run and their full `TraceRow` exports compared against
+//! JSON files in `golden/` with `agent_core::runtime::trace_diff_with`.
+//!
+//! A mismatch fails the test. When the change is intended, rerun with
+//! `CONFORMANCE_BLESS=1 cargo test -p conformance` to rewrite the golden
+//! files, and commit them with the change that caused them.
+
+use std::fmt::{Display, Formatter};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use agent_core::runtime::{TraceDiffConfig, TraceDiffReport, trace_diff_with};
+use agent_core::{
+    NormalizationConfig, NoveltyConfig, SoftTraceParams, TraceRow, TraceRunConfig, WarmupConfig,
+};
+use core_types::{SchemaVersioned, Versioned};
+use serde::{Deserialize, Serialize};
+
+/// Set to `1` or `true` to rewrite golden files instead of comparing.
+pub const BLESS_ENV: &str = "CONFORMANCE_BLESS";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TraceVariant {
+    Soft(SoftTraceParams),
+    BaselineOff,
+    /// Balanced selection with this many per-category slots.
+    Balanced(usize),
+}
+
+#[derive(Clone, Debug)]
+pub struct CanonicalCase {
+    /// Also the golden file stem.
+    pub name: &'static str,
+    pub variant: TraceVariant,
+    pub config: TraceRunConfig,
+}
+
+impl CanonicalCase {
+    pub fn run(&self) -> Vec<TraceRow> {
+        let config = self.config.clone();
+        match self.variant {
+            TraceVariant::Soft(params) => agent_core::runtime::execute_soft_trace(config, params),
+            TraceVariant::BaselineOff => agent_core::runtime::execute_trace_baseline_off(config),
+            TraceVariant::Balanced(m) => {
+                agent_core::runtime::execute_trace_baseline_off_balanced(config, m)
+            }
+        }
+    }
+}
+
+/// Small configuration shared by the canonical cases.
+pub fn base_config(seed: u64) -> TraceRunConfig {
+    TraceRunConfig {
+        depth: 4,
+        beam: 3,
+        seed,
+        norm_alpha: 0.1,
+        adaptive_alpha: false,
+        hv_guided: false,
+        raw_output_path: None,
+        normalization: NormalizationConfig::default(),
+        warmup: WarmupConfig::default(),
+        calibration: None,
+        convergence: None,
+        field_dimensions: agent_core::DEFAULT_FIELD_DIMENSIONS,
+        novelty: None,
+        repair: None,
+        candidate_pipeline: agent_core::CandidatePipelineConfig::default(),
+        diversity: None,
+        directions: agent_core::ObjectiveDirections::default(),
+    }
+}
+
+pub fn canonical_cases() -> Vec<CanonicalCase> {
+    vec![
+        CanonicalCase {
+            name: "soft_default",
+            variant: TraceVariant::Soft(SoftTraceParams::default()),
+            config: base_config(42),
+        },
+        CanonicalCase {
+            name: "soft_hv_guided",
+            variant: TraceVariant::Soft(SoftTraceParams::default()),
+            config: TraceRunConfig {
+                hv_guided: true,
+                ..base_config(7)
+            },
+        },
+        CanonicalCase {
+            name: "soft_novelty",
+            variant: TraceVariant::Soft(SoftTraceParams::default()),
+            config: TraceRunConfig {
+                novelty: Some(NoveltyConfig::default()),
+                repair: None,
+                ..base_config(42)
+            },
+        },
+        CanonicalCase {
+            name: "baseline_off",
+            variant: TraceVariant::BaselineOff,
+            config: base_config(42),
+        },
+        CanonicalCase {
+            name: "balanced_m2",
+            variant: TraceVariant::Balanced(2),
+            config: base_config(42),
+        },
+    ]
+}
+
+/// Comparator used for golden checks: `TraceDiffConfig::default()` with
+/// timings and distance counters ignored.
+pub fn golden_diff_config() -> TraceDiffConfig {
+    TraceDiffConfig::default()
+}
+
+/// Zeroes wall-clock timings so blessing the same behaviour twice leaves
+/// the golden files byte-identical.
+pub fn scrub_timings(rows: &[TraceRow]) -> Vec<TraceRow> {
+    rows.iter()
+        .cloned()
+        .map(|mut row| {
+            row.dhm_build_us = 0.0;
+            row.field_extract_us = 0.0;
+            row.field_score_us = 0.0;
+            row.field_aggregate_us = 0.0;
+            row.field_total_us = 0.0;
+            row
+        })
+        .collect()
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GoldenTrace {
+    pub case: String,
+    pub rows: Vec<TraceRow>,
+}
+
+impl SchemaVersioned for GoldenTrace {
+    const KIND: &'static str = "golden_trace";
+    const VERSION: u32 = 1;
+}
+
+pub fn golden_dir() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("golden")
+}
+
+pub fn golden_path(dir: &Path, case: &str) -> PathBuf {
+    dir.join(format!("{case}.json"))
+}
+
+pub fn bless_requested() -> bool {
+    std::env::var(BLESS_ENV).is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+}
+
+#[derive(Debug)]
+pub enum GoldenOutcome {
+    /// Matched within tolerance.
+    Matched(TraceDiffReport),
+    Blessed(PathBuf),
+}
+
+#[derive(Debug)]
+pub enum GoldenError {
+    Missing(PathBuf),
+    Io(io::Error),
+    Invalid(String),
+    Mismatch(Box<TraceDiffReport>),
+}
+
+impl Display for GoldenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Missing(path) => write!(
+                f,
+                "golden file {} is missing; run with {BLESS_ENV}=1 to create it",
+                path.display()
+            ),
+            Self::Io(err) => write!(f, "golden io error: {err}"),
+            Self::Invalid(message) => write!(f, "invalid golden file: {message}"),
+            Self::Mismatch(report) => write!(
+                f,
+                "trace differs from golden ({}); rerun with {BLESS_ENV}=1 if the change is intended",
+                report.summary()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for GoldenError {}
+
+impl From<io::Error> for GoldenError {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+pub fn read_golden(path: &Path) -> Result<GoldenTrace, GoldenError> {
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            return Err(GoldenError::Missing(path.to_path_buf()));
+        }
+        Err(err) => return Err(err.into()),
+    };
+    serde_json::from_slice::<Versioned<GoldenTrace>>(&bytes)
+        .map_err(|e| GoldenError::Invalid(e.to_string()))?
+        .into_checked()
+        .map_err(|e| GoldenError::Invalid(e.to_string()))
+}
+
+pub fn write_golden(path: &Path, golden: &GoldenTrace) -> Result<(), GoldenError> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut json = serde_json::to_string_pretty(&Versioned::new(golden.clone()))
+        .map_err(|e| GoldenError::Invalid(e.to_string()))?;
+    json.push('\n');
+    std::fs::write(path, json)?;
+    Ok(())
+}
+
+/// Compares `rows` with the golden file for `case` in `dir`, or rewrites
+/// that file when `bless` is set.
+pub fn check_golden(
+    dir: &Path,
+    case: &str,
+    rows: &[TraceRow],
+    config: &TraceDiffConfig,
+    bless: bool,
+) -> Result<GoldenOutcome, GoldenError> {
+    let path = golden_path(dir, case);
+    if bless {
+        write_golden(
+            &path,
+            &GoldenTrace {
+                case: case.to_string(),
+                rows: scrub_timings(rows),
+            },
+        )?;
+        return Ok(GoldenOutcome::Blessed(path));
+    }
+    let golden = read_golden(&path)?;
+    let report = trace_diff_with(&golden.rows, rows, config);
+    if report.is_match() {
+        Ok(GoldenOutcome::Matched(report))
+    } else {
+        Err(GoldenError::Mismatch(Box::new(report)))
+    }
+}
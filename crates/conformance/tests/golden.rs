@@ -0,0 +1,68 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use conformance::{
+    GoldenError, GoldenOutcome, bless_requested, canonical_cases, check_golden, golden_diff_config,
+    golden_dir,
+};
+
+#[test]
+fn canonical_traces_match_golden_files() {
+    let bless = bless_requested();
+    let mut failures = Vec::new();
+    for case in canonical_cases() {
+        let rows = case.run();
+        match check_golden(
+            &golden_dir(),
+            case.name,
+            &rows,
+            &golden_diff_config(),
+            bless,
+        ) {
+            Ok(GoldenOutcome::Matched(_)) => {}
+            Ok(GoldenOutcome::Blessed(path)) => eprintln!("blessed {}", path.display()),
+            Err(err) => failures.push(format!("{}: {err}", case.name)),
+        }
+    }
+    assert!(failures.is_empty(), "{}", failures.join("\n"));
+}
+
+#[test]
+fn bless_then_compare_round_trips_and_catches_drift() {
+    let dir = std::env::temp_dir().join(format!(
+        "conformance_golden_{}",
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .expect("clock")
+            .as_nanos()
+    ));
+    let case = &canonical_cases()[0];
+    let rows = case.run();
+    let config = golden_diff_config();
+
+    assert!(matches!(
+        check_golden(&dir, case.name, &rows, &config, false),
+        Err(GoldenError::Missing(_))
+    ));
+    assert!(matches!(
+        check_golden(&dir, case.name, &rows, &config, true),
+        Ok(GoldenOutcome::Blessed(_))
+    ));
+    assert!(matches!(
+        check_golden(&dir, case.name, &rows, &config, false),
+        Ok(GoldenOutcome::Matched(_))
+    ));
+
+    let mut drifted = rows.clone();
+    drifted[1].diversity += 0.01;
+    let err = check_golden(&dir, case.name, &drifted, &config, false).expect_err("drift");
+    assert!(
+        matches!(&err, GoldenError::Mismatch(report) if report.first_divergence == Some(drifted[1].depth))
+    );
+    assert!(err.to_string().contains("CONFORMANCE_BLESS=1"));
+
+    let tolerant = config.with_threshold("diversity", 0.05);
+    assert!(matches!(
+        check_golden(&dir, case.name, &drifted, &tolerant, false),
+        Ok(GoldenOutcome::Matched(_))
+    ));
+}
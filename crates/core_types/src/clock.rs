@@ -0,0 +1,80 @@
+//! Wall-clock and stopwatch helpers that degrade gracefully on
+//! `wasm32-unknown-unknown`, where `SystemTime::now` and `Instant::now` panic.
+//! On that target timestamps are a per-process logical counter and elapsed
+//! times are zero.
+//!
+//! This keys on the target rather than a feature because the clock, like
+//! `std::fs` and `std::process::id`, is missing by platform, not by choice,
+//! and Cargo unifies features across the graph: a default-on clock feature
+//! would come back through any dependent that keeps default features, and
+//! an opt-out one would leak into native builds. The same cfg keeps
+//! hybrid_vm's request ids off `process::id`; file access is avoided by
+//! picking the in-memory stores at runtime. agent_core's `wasm` feature only
+//! adds the wasm-bindgen exports.
+
+use std::time::Duration;
+
+/// Whether this build can read the OS clock.
+pub const HAS_SYSTEM_CLOCK: bool = !cfg!(all(target_arch = "wasm32", target_os = "unknown"));
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub fn unix_time_nanos() -> u128 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0)
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub fn unix_time_nanos() -> u128 {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    static TICK: AtomicU64 = AtomicU64::new(1);
+    TICK.fetch_add(1, Ordering::Relaxed) as u128
+}
+
+pub fn unix_time_secs() -> u64 {
+    (unix_time_nanos() / 1_000_000_000) as u64
+}
+
+pub fn unix_time_millis() -> u64 {
+    (unix_time_nanos() / 1_000_000) as u64
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct Stopwatch {
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    started: std::time::Instant,
+}
+
+impl Stopwatch {
+    pub fn start() -> Self {
+        Self {
+            #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+            started: std::time::Instant::now(),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        {
+            self.started.elapsed()
+        }
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        {
+            Duration::ZERO
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn native_clock_is_monotonic_enough_for_stopwatch() {
+        const { assert!(HAS_SYSTEM_CLOCK) };
+        let watch = Stopwatch::start();
+        assert!(unix_time_secs() > 0);
+        assert!(watch.elapsed() >= Duration::ZERO);
+    }
+}
@@ -1,4 +1,14 @@
+pub mod clock;
+pub mod progress;
+#[cfg(feature = "serde")]
+mod versioned;
+
+#[cfg(feature = "serde")]
+pub use versioned::{SchemaMismatch, SchemaVersioned, Versioned};
+
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ObjectiveVector {
     pub f_struct: f64,
     pub f_field: f64,
@@ -6,6 +16,12 @@ pub struct ObjectiveVector {
     pub f_shape: f64,
 }
 
+#[cfg(feature = "serde")]
+impl SchemaVersioned for ObjectiveVector {
+    const KIND: &'static str = "objective_vector";
+    const VERSION: u32 = 1;
+}
+
 impl ObjectiveVector {
     pub fn clamped(self) -> Self {
         Self {
@@ -18,6 +34,7 @@ impl ObjectiveVector {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ProfileVector {
     pub struct_weight: f64,
     pub field_weight: f64,
@@ -289,6 +306,8 @@ mod tests {
         SemanticLowering, StructureNode, StructureUnit, UnitNode, UnitRole, diff_design_ir,
         lower_design_to_numeric,
     };
+    #[cfg(feature = "serde")]
+    use super::{ObjectiveVector, Versioned};
 
     #[derive(Default)]
     struct DummyDesignCompiler;
@@ -394,4 +413,24 @@ mod tests {
         assert_eq!(diff.removed_units, vec!["unit:old".to_string()]);
         assert!(diff.changed_intent);
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn objective_vector_round_trips_through_versioned_json() {
+        let obj = ObjectiveVector {
+            f_struct: 0.25,
+            f_field: 0.5,
+            f_risk: 0.75,
+            f_shape: 1.0,
+        };
+        let json = serde_json::to_string(&Versioned::new(obj.clone())).expect("serialize");
+        assert!(json.contains("\"kind\":\"objective_vector\""));
+        assert!(json.contains("\"f_struct\":0.25"));
+        let decoded: Versioned<ObjectiveVector> = serde_json::from_str(&json).expect("parse");
+        assert_eq!(decoded.into_checked().expect("schema"), obj);
+
+        let stale = json.replace("\"version\":1", "\"version\":99");
+        let decoded: Versioned<ObjectiveVector> = serde_json::from_str(&stale).expect("parse");
+        assert!(decoded.into_checked().is_err());
+    }
 }
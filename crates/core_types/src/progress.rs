@@ -0,0 +1,180 @@
+//! Progress reporting for long operations.
+//!
+//! Expensive calls take a `&dyn ProgressSink` and drive a `ProgressTracker`
+//! per phase, which counts processed items against a total and estimates
+//! the time left from the rate so far. `NoopProgress` discards everything;
+//! `ChannelProgress` forwards updates over an mpsc channel so a CLI or GUI
+//! thread can render them while the work runs elsewhere.
+
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::Duration;
+
+use crate::clock::{self, Stopwatch};
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ProgressUpdate {
+    pub phase: String,
+    pub processed: u64,
+    pub total: u64,
+    /// Time spent in this phase so far.
+    pub elapsed: Duration,
+    /// `None` until something has been processed, and on targets without a
+    /// system clock.
+    pub eta: Option<Duration>,
+}
+
+impl ProgressUpdate {
+    /// Share of the phase done, in `[0, 1]`; an empty phase counts as done.
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            return 1.0;
+        }
+        (self.processed as f64 / self.total as f64).min(1.0)
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.processed >= self.total
+    }
+}
+
+pub trait ProgressSink {
+    fn report(&self, update: ProgressUpdate);
+}
+
+impl<F> ProgressSink for F
+where
+    F: Fn(ProgressUpdate),
+{
+    fn report(&self, update: ProgressUpdate) {
+        self(update)
+    }
+}
+
+/// Discards every update; the default for callers that do not watch.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NoopProgress;
+
+impl ProgressSink for NoopProgress {
+    fn report(&self, _update: ProgressUpdate) {}
+}
+
+/// Sends updates to the `Receiver` returned by `ChannelProgress::new`.
+/// Updates are dropped once the receiver is gone.
+#[derive(Clone, Debug)]
+pub struct ChannelProgress {
+    sender: Sender<ProgressUpdate>,
+}
+
+impl ChannelProgress {
+    pub fn new() -> (Self, Receiver<ProgressUpdate>) {
+        let (sender, receiver) = mpsc::channel();
+        (Self { sender }, receiver)
+    }
+}
+
+impl ProgressSink for ChannelProgress {
+    fn report(&self, update: ProgressUpdate) {
+        let _ = self.sender.send(update);
+    }
+}
+
+/// One phase of an operation. Reports on start, then at most about a
+/// hundred times as items are processed, then once more on `finish`.
+pub struct ProgressTracker<'a> {
+    sink: &'a dyn ProgressSink,
+    phase: String,
+    processed: u64,
+    total: u64,
+    stride: u64,
+    last_reported: u64,
+    watch: Stopwatch,
+}
+
+impl<'a> ProgressTracker<'a> {
+    pub fn start(sink: &'a dyn ProgressSink, phase: impl Into<String>, total: u64) -> Self {
+        let tracker = Self {
+            sink,
+            phase: phase.into(),
+            processed: 0,
+            total,
+            stride: (total / 100).max(1),
+            last_reported: 0,
+            watch: Stopwatch::start(),
+        };
+        tracker.report();
+        tracker
+    }
+
+    pub fn processed(&self) -> u64 {
+        self.processed
+    }
+
+    /// Counts `items` more as processed, capped at the total.
+    pub fn advance(&mut self, items: u64) {
+        self.processed = self.processed.saturating_add(items).min(self.total);
+        if self.processed - self.last_reported >= self.stride || self.processed == self.total {
+            self.last_reported = self.processed;
+            self.report();
+        }
+    }
+
+    /// Marks the phase complete, even if it ended before its total, and
+    /// reports that unless the last update already did.
+    pub fn finish(mut self) {
+        if self.last_reported < self.total {
+            self.processed = self.total;
+            self.report();
+        }
+    }
+
+    fn report(&self) {
+        let elapsed = self.watch.elapsed();
+        let eta = (clock::HAS_SYSTEM_CLOCK && self.processed > 0).then(|| {
+            let remaining = (self.total - self.processed) as f64;
+            elapsed.mul_f64(remaining / self.processed as f64)
+        });
+        self.sink.report(ProgressUpdate {
+            phase: self.phase.clone(),
+            processed: self.processed,
+            total: self.total,
+            elapsed,
+            eta,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tracker_throttles_updates_and_always_reports_completion() {
+        let (sink, updates) = ChannelProgress::new();
+        let mut tracker = ProgressTracker::start(&sink, "rebuild", 1_000);
+        for _ in 0..1_000 {
+            tracker.advance(1);
+        }
+        tracker.finish();
+        let updates = updates.try_iter().collect::<Vec<_>>();
+        assert_eq!(updates.len(), 101);
+        assert_eq!(updates[0].processed, 0);
+        assert_eq!(updates[0].eta, None);
+        assert_eq!(updates[1].processed, 10);
+        assert!(updates[1].eta.is_some());
+        let last = updates.last().expect("final update");
+        assert!(last.is_done());
+        assert_eq!(last.eta, Some(Duration::ZERO));
+
+        let (sink, updates) = ChannelProgress::new();
+        let mut tracker = ProgressTracker::start(&sink, "search", 4);
+        tracker.advance(1);
+        tracker.finish();
+        let phases = updates
+            .try_iter()
+            .map(|u| (u.processed, u.fraction()))
+            .collect::<Vec<_>>();
+        assert_eq!(phases, vec![(0, 0.0), (1, 0.25), (4, 1.0)]);
+        ProgressTracker::start(&NoopProgress, "empty", 0).finish();
+    }
+}
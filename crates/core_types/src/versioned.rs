@@ -0,0 +1,62 @@
+use std::fmt::{Display, Formatter};
+
+use serde::{Deserialize, Serialize};
+
+/// Stable identity of a type that is exchanged between processes.
+/// Bump `VERSION` whenever the serialized shape changes incompatibly.
+pub trait SchemaVersioned {
+    const KIND: &'static str;
+    const VERSION: u32;
+}
+
+/// Envelope carrying `kind`/`version` tags next to the payload so readers
+/// can reject data written by an incompatible build.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Versioned<T> {
+    pub kind: String,
+    pub version: u32,
+    pub data: T,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SchemaMismatch {
+    pub expected_kind: &'static str,
+    pub expected_version: u32,
+    pub found_kind: String,
+    pub found_version: u32,
+}
+
+impl Display for SchemaMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "schema mismatch: expected {}@v{}, found {}@v{}",
+            self.expected_kind, self.expected_version, self.found_kind, self.found_version
+        )
+    }
+}
+
+impl std::error::Error for SchemaMismatch {}
+
+impl<T: SchemaVersioned> Versioned<T> {
+    pub fn new(data: T) -> Self {
+        Self {
+            kind: T::KIND.to_string(),
+            version: T::VERSION,
+            data,
+        }
+    }
+
+    pub fn into_checked(self) -> Result<T, SchemaMismatch> {
+        if self.kind == T::KIND && self.version == T::VERSION {
+            Ok(self.data)
+        } else {
+            Err(SchemaMismatch {
+                expected_kind: T::KIND,
+                expected_version: T::VERSION,
+                found_kind: self.kind,
+                found_version: self.version,
+            })
+        }
+    }
+}
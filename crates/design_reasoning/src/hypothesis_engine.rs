@@ -1,13 +1,52 @@
-use semantic_dhm::{DerivedRequirement, DesignProjection, RequirementKind, SemanticError};
+use std::collections::BTreeMap;
+
+use semantic_dhm::{
+    DerivedRequirement, DesignProjection, RequirementKind, RequirementPriority, SemanticError,
+};
 
 const SCORE_PRECISION: f64 = 1000.0;
 
+/// A resource total measured on the design graph against a limit from the
+/// requirements, e.g. summed `memory_kb` against "512MB以下".
+#[derive(Clone, Debug, PartialEq)]
+pub struct BudgetCheck {
+    /// Requirement kind the limit stands for; `None` for resources no kind
+    /// covers (power).
+    pub kind: Option<RequirementKind>,
+    pub resource: String,
+    pub used: f64,
+    pub limit: f64,
+}
+
+impl BudgetCheck {
+    pub fn is_exceeded(&self) -> bool {
+        self.used > self.limit
+    }
+
+    /// `used / limit`; infinite for a used resource with a zero limit.
+    pub fn utilization(&self) -> f64 {
+        if self.limit > 0.0 {
+            self.used / self.limit
+        } else if self.used > 0.0 {
+            f64::INFINITY
+        } else {
+            0.0
+        }
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct DesignHypothesis {
     pub requirements: Vec<DerivedRequirement>,
     pub total_score: f64,
     pub normalized_score: f64,
     pub constraint_violation: bool,
+    /// A `Must` requirement is violated; the normalized score is then pinned
+    /// to -1 so no trade-off elsewhere can outweigh it.
+    pub must_violation: bool,
+    /// Budgets checked against a design graph; see
+    /// `HypothesisEngine::evaluate_hypothesis_budgeted`.
+    pub budget_checks: Vec<BudgetCheck>,
 }
 
 impl DesignHypothesis {
@@ -17,6 +56,19 @@ impl DesignHypothesis {
             .max_by(|l, r| l.strength.abs().total_cmp(&r.strength.abs()))
             .map(|d| d.kind)
     }
+
+    /// Requirements pulling against the design: constraint kinds with a
+    /// positive strength, the other kinds with a negative one. A kind with
+    /// budget checks is violated when one of them is exceeded instead.
+    pub fn violated_requirements(&self) -> impl Iterator<Item = &DerivedRequirement> {
+        self.requirements
+            .iter()
+            .filter(|d| is_violation(d, &self.budget_checks))
+    }
+
+    pub fn exceeded_budgets(&self) -> impl Iterator<Item = &BudgetCheck> {
+        self.budget_checks.iter().filter(|c| c.is_exceeded())
+    }
 }
 
 #[derive(Clone, Default)]
@@ -26,31 +78,74 @@ impl HypothesisEngine {
     pub fn evaluate_hypothesis(
         &self,
         projection: &DesignProjection,
+    ) -> Result<DesignHypothesis, SemanticError> {
+        self.evaluate_hypothesis_prioritized(projection, &BTreeMap::new())
+    }
+
+    /// Weights each requirement's strength by its kind's MoSCoW priority
+    /// (untagged kinds weigh as `Should`, so an empty map matches
+    /// `evaluate_hypothesis`). `Wont` requirements drop out of the score and
+    /// of the constraint check.
+    pub fn evaluate_hypothesis_prioritized(
+        &self,
+        projection: &DesignProjection,
+        priorities: &BTreeMap<RequirementKind, RequirementPriority>,
+    ) -> Result<DesignHypothesis, SemanticError> {
+        self.evaluate_hypothesis_budgeted(projection, priorities, &[])
+    }
+
+    /// `evaluate_hypothesis_prioritized` with resource budgets measured on
+    /// a design. A requirement kind with checks counts as violated exactly
+    /// when one of them is exceeded, replacing the guess from its strength;
+    /// an exceeded check without a kind is a constraint violation on its
+    /// own.
+    pub fn evaluate_hypothesis_budgeted(
+        &self,
+        projection: &DesignProjection,
+        priorities: &BTreeMap<RequirementKind, RequirementPriority>,
+        checks: &[BudgetCheck],
     ) -> Result<DesignHypothesis, SemanticError> {
         if projection.derived.is_empty() {
             return Err(SemanticError::InvalidInput(
                 "derived requirements are empty".to_string(),
             ));
         }
+        let priority = |kind| {
+            priorities
+                .get(&kind)
+                .copied()
+                .unwrap_or(RequirementPriority::Should)
+        };
         let strengths = projection
             .derived
             .iter()
-            .map(|d| f64::from(d.strength))
+            .map(|d| f64::from(d.strength) * priority(d.kind).weight())
             .collect::<Vec<_>>();
-        let total = strengths.iter().copied().sum::<f64>();
+        let mut total = strengths.iter().copied().sum::<f64>();
         let denom = strengths.iter().map(|s| s.abs()).sum::<f64>();
-        let normalized = if denom > 0.0 { total / denom } else { 0.0 };
+        let mut normalized = if denom > 0.0 { total / denom } else { 0.0 };
 
-        let constraint_violation = projection
+        let constraint_violation = projection.derived.iter().any(|d| {
+            priority(d.kind) != RequirementPriority::Wont
+                && is_constraint_kind(d.kind)
+                && is_violation(d, checks)
+        }) || checks.iter().any(|c| c.kind.is_none() && c.is_exceeded());
+        let must_violation = projection
             .derived
             .iter()
-            .any(|d| is_constraint_kind(d.kind) && d.strength > 0.0);
+            .any(|d| priority(d.kind) == RequirementPriority::Must && is_violation(d, checks));
+        if must_violation {
+            total -= denom;
+            normalized = -1.0;
+        }
 
         Ok(DesignHypothesis {
             requirements: projection.derived.clone(),
             total_score: quantize_score(total),
             normalized_score: quantize_score(normalized),
             constraint_violation,
+            must_violation,
+            budget_checks: checks.to_vec(),
         })
     }
 }
@@ -59,6 +154,24 @@ fn quantize_score(v: f64) -> f64 {
     (v * SCORE_PRECISION).round() / SCORE_PRECISION
 }
 
+/// Constraint kinds are violated by a positive pull (as in
+/// `constraint_violation`), the others by a negative one, unless `checks`
+/// measured the kind.
+fn is_violation(requirement: &DerivedRequirement, checks: &[BudgetCheck]) -> bool {
+    let mut measured = checks
+        .iter()
+        .filter(|c| c.kind == Some(requirement.kind))
+        .peekable();
+    if measured.peek().is_some() {
+        return measured.any(|c| c.is_exceeded());
+    }
+    if is_constraint_kind(requirement.kind) {
+        requirement.strength > 0.0
+    } else {
+        requirement.strength < 0.0
+    }
+}
+
 fn is_constraint_kind(kind: RequirementKind) -> bool {
     matches!(kind, RequirementKind::Memory | RequirementKind::NoCloud)
 }
@@ -6,7 +6,7 @@ pub mod projection_engine;
 pub mod snapshot_engine;
 pub mod structured_reasoning;
 
-pub use hypothesis_engine::{DesignHypothesis, HypothesisEngine};
+pub use hypothesis_engine::{BudgetCheck, DesignHypothesis, HypothesisEngine};
 pub use language_engine::{
     Explanation, LanguageEngine, LanguagePatternStore, LanguageState, LanguageStateV2,
     TEMPLATE_SELECTION_EPSILON, TemplateId, is_ambiguous_margin,
@@ -18,7 +18,10 @@ pub use phase1_engine::{
     sanitize_factors,
 };
 pub use projection_engine::ProjectionEngine;
-pub use snapshot_engine::{MeaningLayerSnapshotV2, SnapshotDiffV2, SnapshotEngine};
+pub use snapshot_engine::{
+    L1UnitDigest, L2UnitDigest, MeaningLayerSnapshotV2, SnapshotDiffV2, SnapshotEngine,
+    SnapshotFieldChange,
+};
 pub use structured_reasoning::{
     AxisCategory, IssueType, ModelConfig, OverallState, RealizationMode, RealizedExplanation,
     ReasoningAxis, SrtIssue, SrtStrength, StructuredExplanationResult, StructuredReasoningEngine,
@@ -1,25 +1,57 @@
+use std::ops::Range;
+
 use language_dhm::{EMBEDDING_DIM, LangId, LanguageDhm, LanguageUnit};
-use memory_store::FileStore;
+use memory_store::Store;
 use semantic_dhm::{
     ConceptId, ConceptUnit, L1Id, RequirementRole, SemanticDhm, SemanticError, SemanticL1Dhm,
     SemanticUnitL1, SemanticUnitL1Input,
 };
 
 const ABS_PRECISION: f64 = 1000.0;
+const CONFIDENCE_PRECISION: f64 = 1000.0;
 const ABSTRACTION_RULE_WEIGHT: f32 = 0.6;
 const ABSTRACTION_VECTOR_WEIGHT: f32 = 0.4;
+const ROLE_CONFIDENCE_FALLBACK: f32 = 0.6;
+const ROLE_CONFIDENCE_FALLBACK_QUANTIFIED: f32 = 0.35;
+
+const PROHIBITION_KEYWORDS: [&str; 5] = ["avoid", "prohibit", "forbid", "禁止", "避け"];
+const CONSTRAINT_KEYWORDS: [&str; 5] = ["must", "以下", "上限", "constraint", "制約"];
+const OPTIMIZATION_KEYWORDS: [&str; 4] = ["optimiz", "best", "できるだけ", "省エネ"];
 
 #[derive(Clone, Default)]
 pub struct MeaningEngine;
 
 impl MeaningEngine {
-    pub fn analyze_text(
+    pub fn analyze_text<LS, L1S, L2S>(
+        &self,
+        text: &str,
+        language_dhm: &mut LanguageDhm<LS>,
+        semantic_l1_dhm: &mut SemanticL1Dhm<L1S>,
+        semantic_dhm: &mut SemanticDhm<L2S>,
+    ) -> Result<ConceptUnit, SemanticError>
+    where
+        LS: Store<LangId, LanguageUnit>,
+        L1S: Store<L1Id, SemanticUnitL1>,
+        L2S: Store<ConceptId, ConceptUnit>,
+    {
+        self.analyze_text_in_context(text, language_dhm, semantic_l1_dhm, semantic_dhm, |_| true)
+    }
+
+    /// `analyze_text`, rebuilding L2 only from the stored units
+    /// `in_context` accepts; the units inserted from `text` always count.
+    pub fn analyze_text_in_context<LS, L1S, L2S>(
         &self,
         text: &str,
-        language_dhm: &mut LanguageDhm<FileStore<LangId, LanguageUnit>>,
-        semantic_l1_dhm: &mut SemanticL1Dhm<FileStore<L1Id, SemanticUnitL1>>,
-        semantic_dhm: &mut SemanticDhm<FileStore<ConceptId, ConceptUnit>>,
-    ) -> Result<ConceptUnit, SemanticError> {
+        language_dhm: &mut LanguageDhm<LS>,
+        semantic_l1_dhm: &mut SemanticL1Dhm<L1S>,
+        semantic_dhm: &mut SemanticDhm<L2S>,
+        in_context: impl Fn(L1Id) -> bool,
+    ) -> Result<ConceptUnit, SemanticError>
+    where
+        LS: Store<LangId, LanguageUnit>,
+        L1S: Store<L1Id, SemanticUnitL1>,
+        L2S: Store<ConceptId, ConceptUnit>,
+    {
         let embedding = self.embedding_from_text(text);
         let _ = language_dhm
             .insert(text, embedding)
@@ -28,13 +60,17 @@ impl MeaningEngine {
         let fragments = self.extract_l1_fragments(text);
         let mut inserted = Vec::new();
         for fragment in fragments {
-            let role = self.infer_requirement_role(&fragment);
+            let (role, role_confidence) = self.infer_requirement_role_with_confidence(&fragment);
+            let (abstraction, abstraction_confidence) =
+                self.infer_abstraction_with_confidence(&fragment);
             let l1_id = semantic_l1_dhm.insert(&SemanticUnitL1Input {
                 role,
                 polarity: self.infer_polarity(role),
-                abstraction: self.infer_abstraction(&fragment),
+                abstraction,
                 vector: self.embedding_from_text(&fragment),
                 source_text: fragment,
+                role_confidence,
+                abstraction_confidence,
             });
             let Some(unit) = semantic_l1_dhm.get(l1_id) else {
                 return Err(SemanticError::InconsistentState(
@@ -44,7 +80,12 @@ impl MeaningEngine {
             inserted.push(unit);
         }
 
-        semantic_dhm.rebuild_l2_from_l1(&semantic_l1_dhm.all_units())?;
+        let context = semantic_l1_dhm
+            .all_units()
+            .into_iter()
+            .filter(|u| in_context(u.id) || inserted.iter().any(|i| i.id == u.id))
+            .collect::<Vec<_>>();
+        semantic_dhm.rebuild_l2_from_l1(&context)?;
         let mut candidates = semantic_dhm
             .all_concepts()
             .into_iter()
@@ -101,31 +142,56 @@ impl MeaningEngine {
         out
     }
 
+    /// `extract_l1_fragments` with the byte range each fragment occupies in
+    /// `text`, matched left to right. Newlines are folded to spaces before
+    /// matching, which keeps byte offsets intact.
+    pub fn locate_l1_fragments(&self, text: &str) -> Vec<(String, Option<Range<usize>>)> {
+        let haystack = text.replace('\n', " ");
+        let mut cursor = 0;
+        self.extract_l1_fragments(text)
+            .into_iter()
+            .map(|fragment| {
+                let range = haystack[cursor..].find(&fragment).map(|offset| {
+                    let start = cursor + offset;
+                    start..start + fragment.len()
+                });
+                if let Some(range) = &range {
+                    cursor = range.end;
+                }
+                (fragment, range)
+            })
+            .collect()
+    }
+
     pub fn infer_requirement_role(&self, text: &str) -> RequirementRole {
+        self.infer_requirement_role_with_confidence(text).0
+    }
+
+    /// Returns the inferred role with a confidence in `[0, 1]`.
+    ///
+    /// Confidence is the share of keyword hits that back the chosen role, so
+    /// sentences mixing prohibition/constraint/optimization cues score low.
+    /// Sentences with no cue fall back to `Goal`; when they still carry numbers
+    /// they read like constraints and are scored lower still.
+    pub fn infer_requirement_role_with_confidence(&self, text: &str) -> (RequirementRole, f32) {
         let t = text.to_ascii_lowercase();
-        if t.contains("avoid")
-            || t.contains("prohibit")
-            || t.contains("forbid")
-            || t.contains("禁止")
-            || t.contains("避け")
-        {
-            RequirementRole::Prohibition
-        } else if t.contains("must")
-            || t.contains("以下")
-            || t.contains("上限")
-            || t.contains("constraint")
-            || t.contains("制約")
-        {
-            RequirementRole::Constraint
-        } else if t.contains("optimiz")
-            || t.contains("best")
-            || t.contains("できるだけ")
-            || t.contains("省エネ")
-        {
-            RequirementRole::Optimization
-        } else {
-            RequirementRole::Goal
-        }
+        let hits = |keywords: &[&str]| keywords.iter().filter(|k| t.contains(*k)).count();
+        let scored = [
+            (RequirementRole::Prohibition, hits(&PROHIBITION_KEYWORDS)),
+            (RequirementRole::Constraint, hits(&CONSTRAINT_KEYWORDS)),
+            (RequirementRole::Optimization, hits(&OPTIMIZATION_KEYWORDS)),
+        ];
+        let total = scored.iter().map(|(_, n)| *n).sum::<usize>();
+        let Some((role, n)) = scored.into_iter().find(|(_, n)| *n > 0) else {
+            let confidence = if text.chars().any(|c| c.is_ascii_digit()) {
+                ROLE_CONFIDENCE_FALLBACK_QUANTIFIED
+            } else {
+                ROLE_CONFIDENCE_FALLBACK
+            };
+            return (RequirementRole::Goal, confidence);
+        };
+        let confidence = n as f32 / total as f32;
+        (role, self.quantize_confidence(confidence))
     }
 
     pub fn infer_polarity(&self, role: RequirementRole) -> i8 {
@@ -136,10 +202,20 @@ impl MeaningEngine {
     }
 
     pub fn infer_abstraction(&self, text: &str) -> f32 {
+        self.infer_abstraction_with_confidence(text).0
+    }
+
+    /// Returns the abstraction score with a confidence in `[0, 1]` measured as
+    /// the agreement between the rule-based and vector-based estimates.
+    pub fn infer_abstraction_with_confidence(&self, text: &str) -> (f32, f32) {
         let rule_score = self.rule_abstraction_score(text);
         let vector_score = self.vector_abstraction_score(text);
         let mixed = ABSTRACTION_RULE_WEIGHT * rule_score + ABSTRACTION_VECTOR_WEIGHT * vector_score;
-        self.quantize_abstraction(mixed.clamp(0.0, 1.0))
+        let agreement = 1.0 - (rule_score - vector_score).abs();
+        (
+            self.quantize_abstraction(mixed.clamp(0.0, 1.0)),
+            self.quantize_confidence(agreement),
+        )
     }
 
     fn rule_abstraction_score(&self, text: &str) -> f32 {
@@ -226,6 +302,12 @@ impl MeaningEngine {
     fn quantize_abstraction(&self, v: f32) -> f32 {
         ((v as f64 * ABS_PRECISION).round() / ABS_PRECISION) as f32
     }
+
+    /// Clamps a confidence into `[0, 1]` and rounds it to `CONFIDENCE_PRECISION`.
+    fn quantize_confidence(&self, v: f32) -> f32 {
+        let v = v.clamp(0.0, 1.0) as f64;
+        ((v * CONFIDENCE_PRECISION).round() / CONFIDENCE_PRECISION) as f32
+    }
 }
 
 fn dot_norm(a: &[f32], b: &[f32]) -> f32 {
@@ -1,8 +1,10 @@
 use semantic_dhm::{
-    ConceptUnit, MeaningLayerSnapshot, MeaningLayerState, SemanticError, SemanticUnitL1,
-    SnapshotDiff, Snapshotable, compare_snapshots,
+    ConceptId, ConceptUnit, L1Id, L2Config, MeaningLayerSnapshot, MeaningLayerState,
+    RequirementRole, SemanticError, SemanticUnitL1, SnapshotDiff, Snapshotable, compare_snapshots,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
 use std::time::{SystemTime, UNIX_EPOCH};
 
 const SNAPSHOT_V2_VERSION: u16 = 2;
@@ -16,6 +18,124 @@ pub struct MeaningLayerSnapshotV2 {
     pub l2_hash: u64,
     pub timestamp_ms: u64,
     pub version: u16,
+    /// Per-unit digests used for drill-down. Empty for snapshots taken before
+    /// digests were recorded; such snapshots only support the boolean diff.
+    #[serde(default)]
+    pub l1_units: Vec<L1UnitDigest>,
+    #[serde(default)]
+    pub l2_units: Vec<L2UnitDigest>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L1UnitDigest {
+    pub id: L1Id,
+    pub role: RequirementRole,
+    pub polarity: i8,
+    /// Abstraction in millionths, the precision the hashes canonicalize
+    /// to; serialized as the float it stands for.
+    #[serde(rename = "abstraction", with = "micros")]
+    pub abstraction_micros: i32,
+    pub text_hash: u64,
+    pub vector_hash: u64,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L2UnitDigest {
+    pub id: ConceptId,
+    pub l1_refs: Vec<L1Id>,
+    pub polarity: i8,
+    /// Abstraction in millionths; see `L1UnitDigest::abstraction_micros`.
+    #[serde(rename = "abstraction", with = "micros")]
+    pub abstraction_micros: i32,
+    pub vector_hash: u64,
+    /// Grounding entries attached to the concept. The engine cannot see
+    /// grounding and leaves it at 0; the VM fills it in.
+    #[serde(default)]
+    pub grounding_count: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SnapshotFieldChange {
+    L1Role {
+        id: L1Id,
+        before: RequirementRole,
+        after: RequirementRole,
+    },
+    L1Polarity {
+        id: L1Id,
+        before: i8,
+        after: i8,
+    },
+    L1Abstraction {
+        id: L1Id,
+        /// In millionths.
+        #[serde(rename = "delta", with = "micros")]
+        delta_micros: i32,
+    },
+    L1SourceText {
+        id: L1Id,
+    },
+    L1Vector {
+        id: L1Id,
+    },
+    L2Refs {
+        id: ConceptId,
+        added: Vec<L1Id>,
+        removed: Vec<L1Id>,
+    },
+    L2Polarity {
+        id: ConceptId,
+        before: i8,
+        after: i8,
+    },
+    L2Abstraction {
+        id: ConceptId,
+        /// In millionths.
+        #[serde(rename = "delta", with = "micros")]
+        delta_micros: i32,
+    },
+    L2Vector {
+        id: ConceptId,
+    },
+}
+
+impl SnapshotFieldChange {
+    pub fn describe(&self) -> String {
+        match self {
+            Self::L1Role { id, before, after } => {
+                format!("L1 {}: role {:?} -> {:?}", id.0, before, after)
+            }
+            Self::L1Polarity { id, before, after } => {
+                format!("L1 {}: polarity {} -> {}", id.0, before, after)
+            }
+            Self::L1Abstraction { id, delta_micros } => {
+                format!(
+                    "L1 {}: abstraction {:+.3}",
+                    id.0,
+                    from_micros(*delta_micros)
+                )
+            }
+            Self::L1SourceText { id } => format!("L1 {}: source text changed", id.0),
+            Self::L1Vector { id } => format!("L1 {}: vector changed", id.0),
+            Self::L2Refs { id, added, removed } => format!(
+                "L2 {}: l1_refs +[{}] -[{}]",
+                id.0,
+                join_l1_ids(added),
+                join_l1_ids(removed)
+            ),
+            Self::L2Polarity { id, before, after } => {
+                format!("L2 {}: polarity {} -> {}", id.0, before, after)
+            }
+            Self::L2Abstraction { id, delta_micros } => {
+                format!(
+                    "L2 {}: abstraction {:+.3}",
+                    id.0,
+                    from_micros(*delta_micros)
+                )
+            }
+            Self::L2Vector { id } => format!("L2 {}: vector changed", id.0),
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
@@ -24,6 +144,22 @@ pub struct SnapshotDiffV2 {
     pub l1_changed: bool,
     pub l2_changed: bool,
     pub version_changed: bool,
+    #[serde(default)]
+    pub l1_added: Vec<L1Id>,
+    #[serde(default)]
+    pub l1_removed: Vec<L1Id>,
+    #[serde(default)]
+    pub l1_modified: Vec<L1Id>,
+    #[serde(default)]
+    pub l2_added: Vec<ConceptId>,
+    #[serde(default)]
+    pub l2_removed: Vec<ConceptId>,
+    #[serde(default)]
+    pub l2_modified: Vec<ConceptId>,
+    #[serde(default)]
+    pub field_changes: Vec<SnapshotFieldChange>,
+    #[serde(default)]
+    pub summary: String,
 }
 
 #[derive(Clone, Default)]
@@ -32,12 +168,13 @@ pub struct SnapshotEngine;
 impl SnapshotEngine {
     pub fn snapshot(
         &self,
-        algorithm_version: u32,
+        config: L2Config,
         l1_units: Vec<SemanticUnitL1>,
         l2_units: Vec<ConceptUnit>,
     ) -> Result<MeaningLayerSnapshot, SemanticError> {
         Ok(MeaningLayerState {
-            algorithm_version,
+            algorithm_version: config.algorithm_version,
+            strategy: config.strategy,
             l1_units,
             l2_units,
         }
@@ -57,11 +194,17 @@ impl SnapshotEngine {
         l1_units: &[SemanticUnitL1],
         l2_units: &[ConceptUnit],
     ) -> Result<MeaningLayerSnapshotV2, SemanticError> {
+        let mut l1_digests = l1_units.iter().map(digest_l1).collect::<Vec<_>>();
+        l1_digests.sort_by_key(|d| d.id);
+        let mut l2_digests = l2_units.iter().map(digest_l2).collect::<Vec<_>>();
+        l2_digests.sort_by_key(|d| d.id);
         Ok(MeaningLayerSnapshotV2 {
             l1_hash: hash_l1_units(l1_units),
             l2_hash: hash_l2_units(l2_units),
             timestamp_ms: now_timestamp_ms()?,
             version: SNAPSHOT_V2_VERSION,
+            l1_units: l1_digests,
+            l2_units: l2_digests,
         })
     }
 
@@ -70,15 +213,254 @@ impl SnapshotEngine {
         a: &MeaningLayerSnapshotV2,
         b: &MeaningLayerSnapshotV2,
     ) -> SnapshotDiffV2 {
-        SnapshotDiffV2 {
+        let mut diff = SnapshotDiffV2 {
             identical: a.l1_hash == b.l1_hash && a.l2_hash == b.l2_hash && a.version == b.version,
             l1_changed: a.l1_hash != b.l1_hash,
             l2_changed: a.l2_hash != b.l2_hash,
             version_changed: a.version != b.version,
+            l1_added: Vec::new(),
+            l1_removed: Vec::new(),
+            l1_modified: Vec::new(),
+            l2_added: Vec::new(),
+            l2_removed: Vec::new(),
+            l2_modified: Vec::new(),
+            field_changes: Vec::new(),
+            summary: String::new(),
+        };
+
+        let l1_before = a
+            .l1_units
+            .iter()
+            .map(|d| (d.id, d))
+            .collect::<BTreeMap<_, _>>();
+        let l1_after = b
+            .l1_units
+            .iter()
+            .map(|d| (d.id, d))
+            .collect::<BTreeMap<_, _>>();
+        diff.l1_removed = l1_before
+            .keys()
+            .filter(|id| !l1_after.contains_key(id))
+            .copied()
+            .collect();
+        for (id, after) in &l1_after {
+            let Some(before) = l1_before.get(id) else {
+                diff.l1_added.push(*id);
+                continue;
+            };
+            let changes = diff_l1_digest(before, after);
+            if !changes.is_empty() {
+                diff.l1_modified.push(*id);
+                diff.field_changes.extend(changes);
+            }
+        }
+
+        let l2_before = a
+            .l2_units
+            .iter()
+            .map(|d| (d.id, d))
+            .collect::<BTreeMap<_, _>>();
+        let l2_after = b
+            .l2_units
+            .iter()
+            .map(|d| (d.id, d))
+            .collect::<BTreeMap<_, _>>();
+        diff.l2_removed = l2_before
+            .keys()
+            .filter(|id| !l2_after.contains_key(id))
+            .copied()
+            .collect();
+        for (id, after) in &l2_after {
+            let Some(before) = l2_before.get(id) else {
+                diff.l2_added.push(*id);
+                continue;
+            };
+            let changes = diff_l2_digest(before, after);
+            if !changes.is_empty() {
+                diff.l2_modified.push(*id);
+                diff.field_changes.extend(changes);
+            }
         }
+
+        diff.summary = summarize_diff(&diff);
+        diff
+    }
+}
+
+impl L1UnitDigest {
+    pub fn abstraction(&self) -> f32 {
+        from_micros(self.abstraction_micros)
+    }
+}
+
+impl L2UnitDigest {
+    pub fn abstraction(&self) -> f32 {
+        from_micros(self.abstraction_micros)
+    }
+}
+
+fn to_micros(value: f32) -> i32 {
+    (value as f64 * 1e6).round() as i32
+}
+
+fn from_micros(micros: i32) -> f32 {
+    (micros as f64 / 1e6) as f32
+}
+
+/// Keeps digests `Eq` while their JSON stays the float it always was.
+mod micros {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(micros: &i32, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f32(super::from_micros(*micros))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<i32, D::Error> {
+        f32::deserialize(deserializer).map(super::to_micros)
+    }
+}
+
+fn digest_l1(unit: &SemanticUnitL1) -> L1UnitDigest {
+    L1UnitDigest {
+        id: unit.id,
+        role: unit.role,
+        polarity: unit.polarity.clamp(-1, 1),
+        abstraction_micros: to_micros(unit.abstraction.clamp(0.0, 1.0)),
+        text_hash: fnv1a64(
+            FNV_OFFSET_BASIS_64,
+            canonicalize_text(&unit.source_text).as_bytes(),
+        ),
+        vector_hash: fnv1a64(
+            FNV_OFFSET_BASIS_64,
+            canonicalize_f32_vec(&unit.vector).as_bytes(),
+        ),
+    }
+}
+
+fn digest_l2(unit: &ConceptUnit) -> L2UnitDigest {
+    let mut l1_refs = unit.l1_refs.clone();
+    l1_refs.sort();
+    let vectors = format!(
+        "{}|{}",
+        canonicalize_f32_vec(&unit.integrated_vector),
+        canonicalize_f32_vec(&unit.s)
+    );
+    L2UnitDigest {
+        id: unit.id,
+        l1_refs,
+        polarity: unit.polarity.clamp(-1, 1),
+        abstraction_micros: to_micros(unit.a.clamp(0.0, 1.0)),
+        vector_hash: fnv1a64(FNV_OFFSET_BASIS_64, vectors.as_bytes()),
+        grounding_count: 0,
+    }
+}
+
+fn diff_l1_digest(before: &L1UnitDigest, after: &L1UnitDigest) -> Vec<SnapshotFieldChange> {
+    let id = after.id;
+    let mut out = Vec::new();
+    if before.role != after.role {
+        out.push(SnapshotFieldChange::L1Role {
+            id,
+            before: before.role,
+            after: after.role,
+        });
+    }
+    if before.polarity != after.polarity {
+        out.push(SnapshotFieldChange::L1Polarity {
+            id,
+            before: before.polarity,
+            after: after.polarity,
+        });
+    }
+    // canonicalize_l1 と同じ 6 桁精度で比較し、ハッシュ差分と整合させる
+    if before.abstraction_micros != after.abstraction_micros {
+        out.push(SnapshotFieldChange::L1Abstraction {
+            id,
+            delta_micros: after.abstraction_micros - before.abstraction_micros,
+        });
+    }
+    if before.text_hash != after.text_hash {
+        out.push(SnapshotFieldChange::L1SourceText { id });
+    }
+    if before.vector_hash != after.vector_hash {
+        out.push(SnapshotFieldChange::L1Vector { id });
+    }
+    out
+}
+
+fn diff_l2_digest(before: &L2UnitDigest, after: &L2UnitDigest) -> Vec<SnapshotFieldChange> {
+    let id = after.id;
+    let mut out = Vec::new();
+    if before.l1_refs != after.l1_refs {
+        out.push(SnapshotFieldChange::L2Refs {
+            id,
+            added: after
+                .l1_refs
+                .iter()
+                .filter(|r| !before.l1_refs.contains(r))
+                .copied()
+                .collect(),
+            removed: before
+                .l1_refs
+                .iter()
+                .filter(|r| !after.l1_refs.contains(r))
+                .copied()
+                .collect(),
+        });
+    }
+    if before.polarity != after.polarity {
+        out.push(SnapshotFieldChange::L2Polarity {
+            id,
+            before: before.polarity,
+            after: after.polarity,
+        });
+    }
+    if before.abstraction_micros != after.abstraction_micros {
+        out.push(SnapshotFieldChange::L2Abstraction {
+            id,
+            delta_micros: after.abstraction_micros - before.abstraction_micros,
+        });
+    }
+    if before.vector_hash != after.vector_hash {
+        out.push(SnapshotFieldChange::L2Vector { id });
+    }
+    out
+}
+
+fn summarize_diff(diff: &SnapshotDiffV2) -> String {
+    if diff.identical {
+        return "identical".to_string();
     }
+    let mut parts = vec![
+        format!(
+            "L1 +{} -{} ~{}",
+            diff.l1_added.len(),
+            diff.l1_removed.len(),
+            diff.l1_modified.len()
+        ),
+        format!(
+            "L2 +{} -{} ~{}",
+            diff.l2_added.len(),
+            diff.l2_removed.len(),
+            diff.l2_modified.len()
+        ),
+    ];
+    if diff.version_changed {
+        parts.push("version changed".to_string());
+    }
+    parts.extend(diff.field_changes.iter().map(SnapshotFieldChange::describe));
+    parts.join("; ")
+}
+
+fn join_l1_ids(ids: &[L1Id]) -> String {
+    ids.iter()
+        .map(|id| id.0.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
 }
 
+// Browser builds have no SystemTime; snapshots are stamped 0 there.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
 fn now_timestamp_ms() -> Result<u64, SemanticError> {
     Ok(SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -86,6 +468,11 @@ fn now_timestamp_ms() -> Result<u64, SemanticError> {
         .as_millis() as u64)
 }
 
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn now_timestamp_ms() -> Result<u64, SemanticError> {
+    Ok(0)
+}
+
 fn hash_l1_units(l1_units: &[SemanticUnitL1]) -> u64 {
     let mut canonical = l1_units.iter().map(canonicalize_l1).collect::<Vec<_>>();
     canonical.sort();
@@ -1,18 +1,20 @@
 #![allow(clippy::field_reassign_with_default)]
 
+use std::collections::BTreeMap;
+
 use design_reasoning::{
     DesignFactor, DesignHypothesis, FactorType, HypothesisEngine, IssueType, LanguageEngine,
     LanguageState, LanguageStateV2, MeaningEngine, ModelConfig, OverallState, ProjectionEngine,
-    RealizationMode, ReasoningAxis, ScsInputs, SnapshotEngine, StructuredReasoningEngine,
-    StructuredReasoningInput, StructuredReasoningTrace, TEMPLATE_SELECTION_EPSILON, TemplateId,
-    ValidationError, canonical_srt_hash, compute_dependency_consistency, compute_scs_v1_1,
-    is_ambiguous_margin, normalize_realized_explanation_for_output, sanitize_factors,
-    validate_llm_output,
+    RealizationMode, ReasoningAxis, ScsInputs, SnapshotEngine, SnapshotFieldChange,
+    StructuredReasoningEngine, StructuredReasoningInput, StructuredReasoningTrace,
+    TEMPLATE_SELECTION_EPSILON, TemplateId, ValidationError, canonical_srt_hash,
+    compute_dependency_consistency, compute_scs_v1_1, is_ambiguous_margin,
+    normalize_realized_explanation_for_output, sanitize_factors, validate_llm_output,
 };
 use semantic_dhm::{
     ConceptId, ConceptUnit, ConceptUnitV2, DEFAULT_L2_CONFIG, DerivedRequirement, L1Id, L2Config,
-    MeaningLayerState, RequirementKind, RequirementRole, SemanticUnitL1, SemanticUnitL1V2,
-    Snapshotable, compare_snapshots,
+    MeaningLayerState, RequirementKind, RequirementPriority, RequirementRole, SemanticUnitL1,
+    SemanticUnitL1V2, Snapshotable, compare_snapshots,
 };
 
 fn mk_l1(
@@ -29,6 +31,8 @@ fn mk_l1(
         abstraction,
         vector: vec![1.0; semantic_dhm::D_SEM],
         source_text: text.to_string(),
+        role_confidence: 1.0,
+        abstraction_confidence: 1.0,
     }
 }
 
@@ -41,6 +45,7 @@ fn mk_l2(id: u64, refs: Vec<L1Id>) -> ConceptUnit {
         s: vec![0.5; semantic_dhm::D_STRUCT],
         polarity: 1,
         timestamp: 0,
+        access: semantic_dhm::AccessControl::default(),
     }
 }
 
@@ -65,6 +70,19 @@ fn split_english_conjunctions() {
     assert!(f.len() >= 2);
 }
 
+#[test]
+fn located_fragments_point_back_into_source_text() {
+    let engine = MeaningEngine;
+    let text = "fast api\nand low memory; fast api";
+    let located = engine.locate_l1_fragments(text);
+    assert_eq!(located.len(), 3);
+    for (fragment, range) in &located {
+        assert_eq!(&text[range.clone().expect("located")], fragment);
+    }
+    // Repeated fragments resolve to successive occurrences.
+    assert_eq!(located[2].1, Some(25..33));
+}
+
 #[test]
 fn role_prohibition_keywords() {
     let engine = MeaningEngine;
@@ -101,6 +119,39 @@ fn role_goal_default() {
     );
 }
 
+#[test]
+fn role_confidence_is_full_for_single_cue() {
+    let engine = MeaningEngine;
+    let (role, confidence) = engine.infer_requirement_role_with_confidence("optimize latency");
+    assert_eq!(role, RequirementRole::Optimization);
+    assert!((confidence - 1.0).abs() < 1e-6);
+}
+
+#[test]
+fn role_confidence_drops_for_mixed_cues() {
+    let engine = MeaningEngine;
+    let (role, confidence) = engine.infer_requirement_role_with_confidence("must avoid cloud");
+    assert_eq!(role, RequirementRole::Prohibition);
+    assert!(confidence <= 0.5);
+}
+
+#[test]
+fn role_confidence_low_for_quantified_goal_fallback() {
+    let engine = MeaningEngine;
+    let (role, quantified) = engine.infer_requirement_role_with_confidence("レスポンス200ms");
+    let (_, plain) = engine.infer_requirement_role_with_confidence("高速化したい");
+    assert_eq!(role, RequirementRole::Goal);
+    assert!(quantified < plain);
+}
+
+#[test]
+fn abstraction_confidence_is_clamped() {
+    let engine = MeaningEngine;
+    let (abstraction, confidence) = engine.infer_abstraction_with_confidence("メモリは512MB以下");
+    assert!((abstraction - engine.infer_abstraction("メモリは512MB以下")).abs() < 1e-6);
+    assert!((0.0..=1.0).contains(&confidence));
+}
+
 #[test]
 fn polarity_by_role() {
     let engine = MeaningEngine;
@@ -241,6 +292,46 @@ fn hypothesis_engine_no_violation_negative_constraint() {
     assert!(!h.constraint_violation);
 }
 
+#[test]
+fn hypothesis_engine_weights_by_priority_and_must_violations_dominate() {
+    let engine = HypothesisEngine;
+    let projection = semantic_dhm::DesignProjection {
+        source_l2_ids: vec![ConceptId(1)],
+        derived: vec![
+            DerivedRequirement {
+                kind: RequirementKind::Performance,
+                strength: 0.9,
+            },
+            DerivedRequirement {
+                kind: RequirementKind::Security,
+                strength: -0.3,
+            },
+        ],
+    };
+    let plain = engine
+        .evaluate_hypothesis(&projection)
+        .expect("hypothesis should evaluate");
+    let untagged = engine
+        .evaluate_hypothesis_prioritized(&projection, &BTreeMap::new())
+        .expect("hypothesis should evaluate");
+    assert_eq!(plain, untagged);
+    assert!(!plain.must_violation);
+
+    let wont_security = BTreeMap::from([(RequirementKind::Security, RequirementPriority::Wont)]);
+    let relaxed = engine
+        .evaluate_hypothesis_prioritized(&projection, &wont_security)
+        .expect("hypothesis should evaluate");
+    assert_eq!(relaxed.normalized_score, 1.0);
+
+    let must_security = BTreeMap::from([(RequirementKind::Security, RequirementPriority::Must)]);
+    let strict = engine
+        .evaluate_hypothesis_prioritized(&projection, &must_security)
+        .expect("hypothesis should evaluate");
+    assert!(strict.must_violation);
+    assert_eq!(strict.normalized_score, -1.0);
+    assert!(strict.total_score < 0.0);
+}
+
 #[test]
 fn projection_engine_is_deterministic() {
     let l1 = vec![
@@ -262,8 +353,10 @@ fn snapshot_compare_zero_diff_after_rebuild_like_cycle() {
         algorithm_version: L2Config {
             similarity_threshold: DEFAULT_L2_CONFIG.similarity_threshold,
             algorithm_version: DEFAULT_L2_CONFIG.algorithm_version,
+            strategy: DEFAULT_L2_CONFIG.strategy,
         }
         .algorithm_version,
+        strategy: DEFAULT_L2_CONFIG.strategy,
         l1_units: l1.clone(),
         l2_units: l2.clone(),
     };
@@ -279,10 +372,10 @@ fn snapshot_engine_compare_is_stable() {
     let l1 = vec![mk_l1(1, "goal", RequirementRole::Goal, 0.7, 1)];
     let l2 = semantic_dhm::build_l2_cache(&l1);
     let s1 = engine
-        .snapshot(DEFAULT_L2_CONFIG.algorithm_version, l1.clone(), l2.clone())
+        .snapshot(DEFAULT_L2_CONFIG, l1.clone(), l2.clone())
         .expect("snapshot should succeed");
     let s2 = engine
-        .snapshot(DEFAULT_L2_CONFIG.algorithm_version, l1, l2)
+        .snapshot(DEFAULT_L2_CONFIG, l1, l2)
         .expect("snapshot should succeed");
     let d = engine
         .compare(&s1, &s2)
@@ -302,6 +395,8 @@ fn language_engine_build_state_with_empty_l1() {
         total_score: 0.0,
         normalized_score: 0.0,
         constraint_violation: false,
+        must_violation: false,
+        budget_checks: vec![],
     };
     let state = engine.build_state(&projection, &[], &hypothesis);
     assert_eq!(state.selected_objective, None);
@@ -369,12 +464,16 @@ fn snapshot_v2_timestamp_is_ignored() {
         l2_hash: 20,
         timestamp_ms: 1000,
         version: 2,
+        l1_units: Vec::new(),
+        l2_units: Vec::new(),
     };
     let b = design_reasoning::MeaningLayerSnapshotV2 {
         l1_hash: 10,
         l2_hash: 20,
         timestamp_ms: 9999,
         version: 2,
+        l1_units: Vec::new(),
+        l2_units: Vec::new(),
     };
     let diff = engine.compare_snapshots_v2(&a, &b);
     assert!(diff.identical);
@@ -389,18 +488,80 @@ fn snapshot_v2_version_difference_detected() {
         l2_hash: 20,
         timestamp_ms: 1,
         version: 2,
+        l1_units: Vec::new(),
+        l2_units: Vec::new(),
     };
     let b = design_reasoning::MeaningLayerSnapshotV2 {
         l1_hash: 10,
         l2_hash: 20,
         timestamp_ms: 2,
         version: 3,
+        l1_units: Vec::new(),
+        l2_units: Vec::new(),
     };
     let diff = engine.compare_snapshots_v2(&a, &b);
     assert!(!diff.identical);
     assert!(diff.version_changed);
 }
 
+#[test]
+fn snapshot_v2_drill_down_lists_unit_changes() {
+    let engine = SnapshotEngine;
+    let l1_a = vec![
+        mk_l1(1, "goal", RequirementRole::Goal, 0.5, 1),
+        mk_l1(2, "keep", RequirementRole::Goal, 0.5, 1),
+    ];
+    let l1_b = vec![
+        mk_l1(1, "goal", RequirementRole::Constraint, 0.75, 1),
+        mk_l1(3, "new", RequirementRole::Goal, 0.5, 1),
+    ];
+    let l2_a = vec![mk_l2(10, vec![L1Id(1), L1Id(2)])];
+    let l2_b = vec![mk_l2(10, vec![L1Id(1), L1Id(3)]), mk_l2(11, vec![L1Id(3)])];
+    let s1 = engine.make_snapshot_v2(&l1_a, &l2_a).expect("snapshot");
+    let s2 = engine.make_snapshot_v2(&l1_b, &l2_b).expect("snapshot");
+    let diff = engine.compare_snapshots_v2(&s1, &s2);
+
+    assert_eq!(diff.l1_added, vec![L1Id(3)]);
+    assert_eq!(diff.l1_removed, vec![L1Id(2)]);
+    assert_eq!(diff.l1_modified, vec![L1Id(1)]);
+    assert_eq!(diff.l2_added, vec![ConceptId(11)]);
+    assert!(diff.l2_removed.is_empty());
+    assert_eq!(diff.l2_modified, vec![ConceptId(10)]);
+    assert!(diff.field_changes.contains(&SnapshotFieldChange::L1Role {
+        id: L1Id(1),
+        before: RequirementRole::Goal,
+        after: RequirementRole::Constraint,
+    }));
+    assert!(
+        diff.field_changes
+            .contains(&SnapshotFieldChange::L1Abstraction {
+                id: L1Id(1),
+                delta_micros: 250_000,
+            })
+    );
+    assert!(diff.field_changes.contains(&SnapshotFieldChange::L2Refs {
+        id: ConceptId(10),
+        added: vec![L1Id(3)],
+        removed: vec![L1Id(2)],
+    }));
+    assert!(diff.summary.starts_with("L1 +1 -1 ~1; L2 +1 -0 ~1"));
+    assert!(diff.summary.contains("L1 1: role Goal -> Constraint"));
+    assert!(diff.summary.contains("L2 10: l1_refs +[3] -[2]"));
+}
+
+#[test]
+fn snapshot_v2_identical_has_empty_drill_down() {
+    let engine = SnapshotEngine;
+    let l1 = vec![mk_l1(1, "goal", RequirementRole::Goal, 0.7, 1)];
+    let l2 = semantic_dhm::build_l2_cache(&l1);
+    let s1 = engine.make_snapshot_v2(&l1, &l2).expect("snapshot");
+    let s2 = engine.make_snapshot_v2(&l1, &l2).expect("snapshot");
+    let diff = engine.compare_snapshots_v2(&s1, &s2);
+    assert!(diff.field_changes.is_empty());
+    assert!(diff.l1_modified.is_empty() && diff.l2_modified.is_empty());
+    assert_eq!(diff.summary, "identical");
+}
+
 #[test]
 fn semantic_unit_l1_v2_clamps_ambiguity() {
     let l1 = mk_l1(10, "  FAST API  ", RequirementRole::Goal, 1.5, 1);
@@ -419,6 +580,7 @@ fn concept_unit_v2_clamps_stability() {
         s: vec![0.0; semantic_dhm::D_STRUCT],
         polarity: -1,
         timestamp: 0,
+        access: semantic_dhm::AccessControl::default(),
     };
     let v2 = ConceptUnitV2::try_from(c).expect("l2 v2");
     assert!((0.0..=1.0).contains(&v2.stability_score));
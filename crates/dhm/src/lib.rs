@@ -98,7 +98,15 @@ pub struct Dhm {
 
 impl Dhm {
     pub fn open(path: impl AsRef<Path>, mode: InterferenceMode) -> io::Result<Self> {
-        let store = HolographicVectorStore::open(path, 4)?;
+        Self::with_store(HolographicVectorStore::open(path, 4)?, mode)
+    }
+
+    /// Recall memory that lives only for the lifetime of this value.
+    pub fn in_memory(mode: InterferenceMode) -> io::Result<Self> {
+        Self::with_store(HolographicVectorStore::in_memory(4), mode)
+    }
+
+    fn with_store(store: HolographicVectorStore, mode: InterferenceMode) -> io::Result<Self> {
         let lambda = match mode {
             InterferenceMode::Disabled => 0.0,
             InterferenceMode::Contractive => 0.1,
@@ -122,9 +130,24 @@ impl Dhm {
         self.memory.apply_interference(base)
     }
 
+    /// Whether the last recall found a similar remembered objective.
+    pub fn last_recall_hit(&self) -> bool {
+        self.memory.last_lookup_hit()
+    }
+
     pub fn telemetry(&mut self) -> MemoryInterferenceTelemetry {
         self.memory.take_telemetry()
     }
+
+    pub fn cached_entries(&self) -> usize {
+        self.memory.cached_entries()
+    }
+
+    /// Frees cached entries recall no longer reads; see
+    /// `MemorySpace::trim_cache`.
+    pub fn trim_cache(&mut self) -> usize {
+        self.memory.trim_cache()
+    }
 }
 
 fn read_u64(raw: &[u8], idx: &mut usize) -> io::Result<u64> {
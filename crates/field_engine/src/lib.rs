@@ -5,6 +5,9 @@ use num_complex::Complex;
 
 pub type Scalar = Complex<f32>;
 
+/// Largest dimensionality `HybridProjector` accepts.
+pub const MAX_DIMENSIONS: usize = 1024;
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct FieldVector {
     pub data: Vec<Scalar>,
@@ -82,7 +85,7 @@ pub enum NodeCategory {
 }
 
 impl NodeCategory {
-    fn all() -> [NodeCategory; 10] {
+    pub fn all() -> [NodeCategory; 10] {
         [
             NodeCategory::Interface,
             NodeCategory::Storage,
@@ -152,7 +155,7 @@ pub struct HybridProjector {
 impl HybridProjector {
     pub fn new(dimension: usize, alpha: f32, beta: f32) -> Self {
         assert!(dimension > 0);
-        assert!(dimension <= 1024);
+        assert!(dimension <= MAX_DIMENSIONS);
 
         let mut category_basis = BTreeMap::new();
         for category in NodeCategory::all() {
@@ -267,10 +270,35 @@ impl FieldEngine {
         self.aggregate_nodes(&nodes)
     }
 
+    /// Zeros of the engine's dimensionality for an empty node list, unlike
+    /// `aggregate_with_projector`.
     pub fn aggregate_nodes(&self, nodes: &[DesignNode]) -> FieldVector {
+        if nodes.is_empty() {
+            return FieldVector::zeros(self.dimensions);
+        }
         aggregate_with_projector(nodes, &self.projector)
     }
 
+    /// The fixed per-category component every node projection mixes in,
+    /// in `NodeCategory::all` order.
+    pub fn category_basis(&self) -> Vec<(NodeCategory, FieldVector)> {
+        NodeCategory::all()
+            .into_iter()
+            .map(|category| (category, self.projector.basis_for(category)))
+            .collect()
+    }
+
+    pub fn check_dimensions(&self, vector: &FieldVector) -> Result<(), DimensionMismatch> {
+        if vector.dimensions() == self.dimensions {
+            Ok(())
+        } else {
+            Err(DimensionMismatch {
+                expected: self.dimensions,
+                found: vector.dimensions(),
+            })
+        }
+    }
+
     pub fn update_delta(
         &self,
         prev: &FieldVector,
@@ -290,6 +318,24 @@ impl FieldEngine {
     }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DimensionMismatch {
+    pub expected: usize,
+    pub found: usize,
+}
+
+impl std::fmt::Display for DimensionMismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "field vector has {} dimensions, engine is configured for {}",
+            self.found, self.expected
+        )
+    }
+}
+
+impl std::error::Error for DimensionMismatch {}
+
 pub fn aggregate(nodes: &[DesignNode]) -> FieldVector {
     let projector = HybridProjector::default_coefficients(64);
     aggregate_with_projector(nodes, &projector)
@@ -434,7 +480,8 @@ mod tests {
     use memory_space::{DesignNode, DesignState, StructuralGraph, Uuid, Value};
 
     use crate::{
-        FieldEngine, HybridProjector, NodeCategory, NodeProjector, TargetField, resonance_score,
+        DimensionMismatch, FieldEngine, HybridProjector, NodeCategory, NodeProjector, TargetField,
+        resonance_score,
     };
 
     #[test]
@@ -516,6 +563,29 @@ mod tests {
         let c = NodeCategory::Interface;
         assert_eq!(c.index(), 0);
     }
+
+    #[test]
+    fn engine_outputs_match_configured_dimensions() {
+        let engine = FieldEngine::new(32);
+        let basis = engine.category_basis();
+        assert_eq!(basis.len(), NodeCategory::all().len());
+        assert!(basis.iter().all(|(_, v)| v.dimensions() == 32));
+
+        let empty = DesignState::new(
+            Uuid::from_u128(1),
+            Arc::new(StructuralGraph::default()),
+            "history:",
+        );
+        let field = engine.aggregate_state(&empty);
+        assert_eq!(engine.check_dimensions(&field), Ok(()));
+        assert_eq!(
+            FieldEngine::new(16).check_dimensions(&field),
+            Err(DimensionMismatch {
+                expected: 16,
+                found: 32,
+            })
+        );
+    }
 }
 
 #[cfg(test)]
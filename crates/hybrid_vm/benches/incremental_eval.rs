@@ -0,0 +1,79 @@
+//! Full versus incremental `StructuralEvaluator` scoring of a one-rule child
+//! of a mid-sized design graph.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
+use hybrid_vm::{Evaluator, StructuralEvaluator};
+use memory_space::{DesignNode, DesignState, StructuralGraph, Uuid, Value};
+
+fn layered_graph(nodes: u128) -> StructuralGraph {
+    let mut graph = StructuralGraph::default();
+    for i in 0..nodes {
+        let category = ["api", "service", "store"][(i % 3) as usize];
+        let attributes =
+            BTreeMap::from([("category".to_string(), Value::Text(category.to_string()))]);
+        graph = graph.with_node_added(DesignNode::new(Uuid::from_u128(i), "Node", attributes));
+    }
+    for i in 1..nodes {
+        graph = graph.with_edge_added(Uuid::from_u128(i / 2), Uuid::from_u128(i));
+        if i >= 3 {
+            graph = graph.with_edge_added(Uuid::from_u128(i - 3), Uuid::from_u128(i));
+        }
+    }
+    graph
+}
+
+fn parent_and_child(nodes: u128) -> (DesignState, DesignState) {
+    let graph = layered_graph(nodes);
+    let child_graph = graph
+        .with_node_added(DesignNode::new(
+            Uuid::from_u128(nodes),
+            "Cache",
+            BTreeMap::new(),
+        ))
+        .with_edge_added(Uuid::from_u128(nodes / 2), Uuid::from_u128(nodes));
+    (
+        DesignState::new(Uuid::from_u128(1), Arc::new(graph), "history:"),
+        DesignState::new(Uuid::from_u128(2), Arc::new(child_graph), "history:1"),
+    )
+}
+
+fn incremental_eval(c: &mut Criterion) {
+    let mut group = c.benchmark_group("structural_eval");
+    for nodes in [64u128, 256, 1024] {
+        let (parent, child) = parent_and_child(nodes);
+        let full = StructuralEvaluator::default().with_aggregate_cache_capacity(0);
+        group.bench_with_input(BenchmarkId::new("full", nodes), &child, |b, child| {
+            b.iter(|| full.evaluate(black_box(child)))
+        });
+
+        let incremental = StructuralEvaluator::default();
+        let mut next_id = 2u128;
+        group.bench_with_input(
+            BenchmarkId::new("incremental", nodes),
+            &(parent, child),
+            |b, (parent, child)| {
+                b.iter_batched(
+                    // A fresh child id each round so the child is never a
+                    // cache hit and the diff is replayed every time.
+                    || {
+                        next_id += 1;
+                        DesignState::new(
+                            Uuid::from_u128(next_id),
+                            Arc::clone(&child.graph),
+                            "history:1",
+                        )
+                    },
+                    |child| incremental.evaluate_child(black_box(parent), &child),
+                    criterion::BatchSize::SmallInput,
+                )
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(benches, incremental_eval);
+criterion_main!(benches);
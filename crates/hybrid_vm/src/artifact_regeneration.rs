@@ -0,0 +1,502 @@
+//! Differential artifact regeneration between two meaning-layer snapshots.
+//!
+//! An artifact's source concepts are the ones it cites as `L2-<id>`; an
+//! artifact citing none (the SQL schema) covers every concept. An artifact
+//! is stale when one of its source concepts was added, modified or removed
+//! between the snapshots, or when an L1 unit behind one of them changed.
+//! Stale artifacts take the freshly rendered content; the rest keep their
+//! previous content byte for byte, so downstream diffs only show what the
+//! requirement change actually touched.
+//!
+//! When every stale artifact covers stale concepts only, as the per-concept
+//! Rust files do, just those concepts are rendered; an aggregate mixing
+//! stale and unchanged concepts needs the full render.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use design_reasoning::{MeaningLayerSnapshotV2, SnapshotDiffV2};
+use semantic_dhm::{ConceptId, L1Id};
+use serde::{Deserialize, Serialize};
+
+use crate::GeneratedArtifact;
+use crate::artifact_validation::cites_concept;
+
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum RegenerationReason {
+    /// No previous artifact had this file name.
+    NewFile,
+    ConceptAdded(ConceptId),
+    ConceptModified(ConceptId),
+    ConceptRemoved(ConceptId),
+    /// An L1 unit referenced by the concept was added, removed or edited.
+    RequirementChanged {
+        concept: ConceptId,
+        l1: L1Id,
+    },
+    /// The previous snapshot has no per-unit digests, so nothing narrower
+    /// than "the meaning layer changed" is known.
+    NoDigests,
+}
+
+impl fmt::Display for RegenerationReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NewFile => write!(f, "new file"),
+            Self::ConceptAdded(id) => write!(f, "L2-{} added", id.0),
+            Self::ConceptModified(id) => write!(f, "L2-{} modified", id.0),
+            Self::ConceptRemoved(id) => write!(f, "L2-{} removed", id.0),
+            Self::RequirementChanged { concept, l1 } => {
+                write!(f, "L1-{} of L2-{} changed", l1.0, concept.0)
+            }
+            Self::NoDigests => write!(f, "previous snapshot has no digests"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ArtifactChange {
+    Added,
+    Regenerated,
+    /// Previous content kept; also used for stale artifacts that rendered
+    /// to the same bytes.
+    Unchanged,
+    /// In the previous output only; the caller should delete the file.
+    Removed,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub file_name: String,
+    pub change: ArtifactChange,
+    /// Empty for unchanged artifacts.
+    pub reasons: Vec<RegenerationReason>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ArtifactManifest {
+    /// Current artifacts in render order, then removed ones.
+    pub entries: Vec<ManifestEntry>,
+}
+
+impl ArtifactManifest {
+    /// Entries whose file has to be written or deleted.
+    pub fn changed(&self) -> impl Iterator<Item = &ManifestEntry> {
+        self.entries
+            .iter()
+            .filter(|e| e.change != ArtifactChange::Unchanged)
+    }
+
+    /// One line per changed file: `<change> <file>: <reason>, ...`.
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        for entry in self.changed() {
+            let change = match entry.change {
+                ArtifactChange::Added => "added",
+                ArtifactChange::Regenerated => "regenerated",
+                ArtifactChange::Unchanged => "unchanged",
+                ArtifactChange::Removed => "removed",
+            };
+            let reasons = entry
+                .reasons
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ");
+            out.push_str(&format!("{change} {}: {reasons}\n", entry.file_name));
+        }
+        out
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArtifactRegeneration {
+    /// The current artifact set: fresh content for stale files, previous
+    /// content for the rest.
+    pub artifacts: Vec<GeneratedArtifact>,
+    pub manifest: ArtifactManifest,
+    /// Snapshot the artifacts now correspond to; pass it as the previous
+    /// snapshot next time.
+    pub snapshot: MeaningLayerSnapshotV2,
+}
+
+impl ArtifactRegeneration {
+    /// Merges `fresh`, rendered from the `current` meaning layer, into the
+    /// `previous_artifacts` rendered at `previous`.
+    pub fn merge(
+        fresh: Vec<GeneratedArtifact>,
+        previous_artifacts: &[GeneratedArtifact],
+        previous: &MeaningLayerSnapshotV2,
+        current: MeaningLayerSnapshotV2,
+        diff: &SnapshotDiffV2,
+    ) -> Self {
+        let changes = concept_changes(previous, &current, diff);
+        let no_digests = !diff.identical && previous.l2_units.is_empty();
+        let concepts = previous
+            .l2_units
+            .iter()
+            .chain(&current.l2_units)
+            .map(|d| d.id)
+            .collect::<BTreeSet<_>>();
+        let reasons_for = |contents: &[&str]| -> Vec<RegenerationReason> {
+            if no_digests {
+                return vec![RegenerationReason::NoDigests];
+            }
+            let cited = concepts
+                .iter()
+                .filter(|id| contents.iter().any(|c| cites_concept(c, **id)))
+                .collect::<Vec<_>>();
+            let mut reasons = changes
+                .iter()
+                .filter(|(id, _)| cited.is_empty() || cited.contains(id))
+                .flat_map(|(_, reasons)| reasons.iter().cloned())
+                .collect::<Vec<_>>();
+            reasons.sort();
+            reasons.dedup();
+            reasons
+        };
+
+        let previous_by_name = previous_artifacts
+            .iter()
+            .map(|a| (a.file_name.as_str(), a))
+            .collect::<BTreeMap<_, _>>();
+        let fresh_names = fresh
+            .iter()
+            .map(|a| a.file_name.clone())
+            .collect::<BTreeSet<_>>();
+        let mut artifacts = Vec::with_capacity(fresh.len());
+        let mut entries = Vec::new();
+        for artifact in fresh {
+            let entry = match previous_by_name.get(artifact.file_name.as_str()) {
+                None => {
+                    let mut reasons = vec![RegenerationReason::NewFile];
+                    reasons.extend(reasons_for(&[&artifact.content]));
+                    artifacts.push(artifact.clone());
+                    ManifestEntry {
+                        file_name: artifact.file_name,
+                        change: ArtifactChange::Added,
+                        reasons,
+                    }
+                }
+                Some(old) => {
+                    let reasons = reasons_for(&[&artifact.content, &old.content]);
+                    let regenerated = !reasons.is_empty() && old.content != artifact.content;
+                    artifacts.push(if regenerated {
+                        artifact.clone()
+                    } else {
+                        (*old).clone()
+                    });
+                    ManifestEntry {
+                        file_name: artifact.file_name,
+                        change: if regenerated {
+                            ArtifactChange::Regenerated
+                        } else {
+                            ArtifactChange::Unchanged
+                        },
+                        reasons: if regenerated { reasons } else { Vec::new() },
+                    }
+                }
+            };
+            entries.push(entry);
+        }
+        for old in previous_artifacts {
+            if !fresh_names.contains(&old.file_name) {
+                entries.push(ManifestEntry {
+                    file_name: old.file_name.clone(),
+                    change: ArtifactChange::Removed,
+                    reasons: reasons_for(&[&old.content]),
+                });
+            }
+        }
+        Self {
+            artifacts,
+            manifest: ArtifactManifest { entries },
+            snapshot: current,
+        }
+    }
+}
+
+/// Concepts a partial render has to redo: every concept added, modified,
+/// removed or behind a changed L1 unit. `None` when only a full render will
+/// do: nothing was rendered before, or the previous snapshot has no digests
+/// to narrow the change down.
+pub(crate) fn stale_concepts(
+    previous_artifacts: &[GeneratedArtifact],
+    previous: &MeaningLayerSnapshotV2,
+    current: &MeaningLayerSnapshotV2,
+    diff: &SnapshotDiffV2,
+) -> Option<BTreeSet<ConceptId>> {
+    if previous_artifacts.is_empty() || (!diff.identical && previous.l2_units.is_empty()) {
+        return None;
+    }
+    Some(
+        concept_changes(previous, current, diff)
+            .into_keys()
+            .collect(),
+    )
+}
+
+/// The current artifact set from `rendered`, the output for the `stale`
+/// concepts alone, plus the previous artifacts of the other concepts, in
+/// the order of the first concept each cites in `order`. `None` when a
+/// previous artifact that `rendered` replaces or that cites a stale concept
+/// also covers another concept, or cites none and so covers all of them.
+pub(crate) fn splice_partial_render(
+    rendered: Vec<GeneratedArtifact>,
+    previous_artifacts: &[GeneratedArtifact],
+    stale: &BTreeSet<ConceptId>,
+    order: &[ConceptId],
+) -> Option<Vec<GeneratedArtifact>> {
+    let concepts = order.iter().chain(stale).copied().collect::<BTreeSet<_>>();
+    let rendered_names = rendered
+        .iter()
+        .map(|a| a.file_name.clone())
+        .collect::<BTreeSet<_>>();
+    let mut artifacts = Vec::with_capacity(previous_artifacts.len() + rendered.len());
+    for old in previous_artifacts {
+        let cited = concepts
+            .iter()
+            .filter(|id| cites_concept(&old.content, **id))
+            .collect::<Vec<_>>();
+        let touched =
+            rendered_names.contains(&old.file_name) || cited.iter().any(|id| stale.contains(id));
+        if cited.is_empty() || (touched && cited.iter().any(|id| !stale.contains(id))) {
+            return None;
+        }
+        if !touched {
+            artifacts.push(old.clone());
+        }
+    }
+    artifacts.extend(rendered);
+    artifacts.sort_by_cached_key(|a| {
+        order
+            .iter()
+            .position(|id| cites_concept(&a.content, *id))
+            .unwrap_or(order.len())
+    });
+    Some(artifacts)
+}
+
+/// Why each concept changed between the snapshots.
+fn concept_changes(
+    previous: &MeaningLayerSnapshotV2,
+    current: &MeaningLayerSnapshotV2,
+    diff: &SnapshotDiffV2,
+) -> BTreeMap<ConceptId, Vec<RegenerationReason>> {
+    let mut changes = BTreeMap::<ConceptId, Vec<RegenerationReason>>::new();
+    for id in &diff.l2_added {
+        changes
+            .entry(*id)
+            .or_default()
+            .push(RegenerationReason::ConceptAdded(*id));
+    }
+    for id in &diff.l2_modified {
+        changes
+            .entry(*id)
+            .or_default()
+            .push(RegenerationReason::ConceptModified(*id));
+    }
+    for id in &diff.l2_removed {
+        changes
+            .entry(*id)
+            .or_default()
+            .push(RegenerationReason::ConceptRemoved(*id));
+    }
+    let changed_l1 = diff
+        .l1_added
+        .iter()
+        .chain(&diff.l1_removed)
+        .chain(&diff.l1_modified)
+        .collect::<BTreeSet<_>>();
+    for digest in previous.l2_units.iter().chain(&current.l2_units) {
+        for l1 in digest.l1_refs.iter().filter(|l1| changed_l1.contains(l1)) {
+            let reason = RegenerationReason::RequirementChanged {
+                concept: digest.id,
+                l1: *l1,
+            };
+            let reasons = changes.entry(digest.id).or_default();
+            if !reasons.contains(&reason) {
+                reasons.push(reason);
+            }
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use design_reasoning::L2UnitDigest;
+
+    use super::*;
+
+    fn artifact(file_name: &str, content: &str) -> GeneratedArtifact {
+        GeneratedArtifact {
+            file_name: file_name.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    fn snapshot(concepts: &[(u64, &[u64])]) -> MeaningLayerSnapshotV2 {
+        MeaningLayerSnapshotV2 {
+            l1_hash: 0,
+            l2_hash: 0,
+            timestamp_ms: 0,
+            version: 2,
+            l1_units: Vec::new(),
+            l2_units: concepts
+                .iter()
+                .map(|(id, refs)| L2UnitDigest {
+                    id: ConceptId(*id),
+                    l1_refs: refs.iter().map(|r| L1Id(*r as u128)).collect(),
+                    polarity: 0,
+                    abstraction_micros: 0,
+                    vector_hash: 0,
+                    grounding_count: 0,
+                })
+                .collect(),
+        }
+    }
+
+    fn diff() -> SnapshotDiffV2 {
+        SnapshotDiffV2 {
+            identical: false,
+            l1_changed: true,
+            l2_changed: true,
+            version_changed: false,
+            l1_added: Vec::new(),
+            l1_removed: Vec::new(),
+            l1_modified: Vec::new(),
+            l2_added: Vec::new(),
+            l2_removed: Vec::new(),
+            l2_modified: Vec::new(),
+            field_changes: Vec::new(),
+            summary: String::new(),
+        }
+    }
+
+    #[test]
+    fn only_artifacts_of_changed_concepts_are_regenerated() {
+        let previous = vec![
+            artifact("concept_1.rs", "// source_concept: L2-1 (hand-tuned)\n"),
+            artifact("concept_2.rs", "// source_concept: L2-2 v1\n"),
+            artifact("concept_3.rs", "// source_concept: L2-3\n"),
+            artifact("schema.sql", "-- v1\n"),
+        ];
+        let fresh = vec![
+            artifact("concept_1.rs", "// source_concept: L2-1\n"),
+            artifact("concept_2.rs", "// source_concept: L2-2 v2\n"),
+            artifact("concept_4.rs", "// source_concept: L2-4\n"),
+            artifact("schema.sql", "-- v2\n"),
+        ];
+        let before = snapshot(&[(1, &[1]), (2, &[2]), (3, &[3])]);
+        let after = snapshot(&[(1, &[1]), (2, &[2]), (4, &[4])]);
+        let diff = SnapshotDiffV2 {
+            l1_modified: vec![L1Id(2)],
+            l2_added: vec![ConceptId(4)],
+            l2_removed: vec![ConceptId(3)],
+            ..diff()
+        };
+        let out = ArtifactRegeneration::merge(fresh, &previous, &before, after.clone(), &diff);
+
+        assert_eq!(
+            out.artifacts[0], previous[0],
+            "unchanged file kept byte for byte"
+        );
+        assert_eq!(out.artifacts[1].content, "// source_concept: L2-2 v2\n");
+        assert_eq!(out.snapshot, after);
+        let summary = out
+            .manifest
+            .entries
+            .iter()
+            .map(|e| (e.file_name.as_str(), e.change))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                ("concept_1.rs", ArtifactChange::Unchanged),
+                ("concept_2.rs", ArtifactChange::Regenerated),
+                ("concept_4.rs", ArtifactChange::Added),
+                ("schema.sql", ArtifactChange::Regenerated),
+                ("concept_3.rs", ArtifactChange::Removed),
+            ]
+        );
+        assert_eq!(
+            out.manifest.entries[1].reasons,
+            [RegenerationReason::RequirementChanged {
+                concept: ConceptId(2),
+                l1: L1Id(2),
+            }]
+        );
+        // The schema cites no concept, so every change reaches it.
+        assert_eq!(out.manifest.entries[3].reasons.len(), 3);
+        assert_eq!(
+            out.manifest.render().lines().collect::<Vec<_>>(),
+            [
+                "regenerated concept_2.rs: L1-2 of L2-2 changed",
+                "added concept_4.rs: new file, L2-4 added",
+                "regenerated schema.sql: L2-4 added, L2-3 removed, L1-2 of L2-2 changed",
+                "removed concept_3.rs: L2-3 removed",
+            ]
+        );
+    }
+
+    #[test]
+    fn partial_renders_splice_into_the_previous_set_unless_an_aggregate_is_touched() {
+        let previous = vec![
+            artifact("concept_1.rs", "// source_concept: L2-1\n"),
+            artifact("concept_2.rs", "// source_concept: L2-2 v1\n"),
+            artifact("concept_3.rs", "// source_concept: L2-3\n"),
+        ];
+        let stale = [ConceptId(2), ConceptId(3), ConceptId(4)]
+            .into_iter()
+            .collect::<BTreeSet<_>>();
+        let order = [ConceptId(4), ConceptId(1), ConceptId(2)];
+        let rendered = vec![
+            artifact("concept_2.rs", "// source_concept: L2-2 v2\n"),
+            artifact("concept_4.rs", "// source_concept: L2-4\n"),
+        ];
+        let spliced = splice_partial_render(rendered.clone(), &previous, &stale, &order)
+            .expect("per-concept artifacts");
+        assert_eq!(
+            spliced,
+            [
+                rendered[1].clone(),
+                previous[0].clone(),
+                rendered[0].clone()
+            ]
+        );
+
+        let schema = artifact("schema.sql", "-- L2-1\n-- L2-2\n");
+        let with_schema = [previous.clone(), vec![schema]].concat();
+        assert_eq!(
+            splice_partial_render(rendered.clone(), &with_schema, &stale, &order),
+            None
+        );
+        let uncited = [previous, vec![artifact("graph.mmd", "graph TD\n")]].concat();
+        assert_eq!(
+            splice_partial_render(rendered, &uncited, &stale, &order),
+            None
+        );
+    }
+
+    #[test]
+    fn identical_snapshots_keep_every_previous_artifact() {
+        let previous = vec![artifact("graph.mmd", "graph TD\n  L2_1[\"L2-1\"]\n")];
+        let fresh = vec![artifact("graph.mmd", "graph TD\n  L2_1[\"L2-1 v2\"]\n")];
+        let same = snapshot(&[(1, &[1])]);
+        let out = ArtifactRegeneration::merge(
+            fresh,
+            &previous,
+            &same,
+            same.clone(),
+            &SnapshotDiffV2 {
+                identical: true,
+                l1_changed: false,
+                l2_changed: false,
+                ..diff()
+            },
+        );
+        assert_eq!(out.artifacts, previous);
+        assert_eq!(out.manifest.changed().count(), 0);
+    }
+}
@@ -0,0 +1,534 @@
+//! Post-generation checks on `GeneratedArtifact`s.
+//!
+//! Every artifact gets a structural check for its format (balanced
+//! delimiters for Rust, terminated statements for SQL, a graph header for
+//! Mermaid). Each requirement a concept actually asks for is then checked
+//! against the artifacts that cover the concept: `NoCloud` rejects external
+//! service references, `Memory` expects the constraint to be carried in a
+//! comment, and `Security` flags credentials in code.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use semantic_dhm::{ConceptId, ConceptUnitV2, RequirementKind};
+use serde::{Deserialize, Serialize};
+
+use crate::GeneratedArtifact;
+
+const EXTERNAL_SERVICE_MARKERS: [&str; 8] = [
+    "http://",
+    "https://",
+    "s3://",
+    "amazonaws",
+    "googleapis",
+    "azure",
+    "aws_sdk",
+    "gcp",
+];
+const MEMORY_MARKERS: [&str; 2] = ["memory", "メモリ"];
+const CREDENTIAL_MARKERS: [&str; 4] = ["password", "secret", "api_key", "token"];
+const SQL_KEYWORDS: [&str; 8] = [
+    "CREATE", "INSERT", "SELECT", "UPDATE", "DELETE", "ALTER", "DROP", "WITH",
+];
+
+/// Ordered from best to worst, so the worst of several statuses is `max`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ValidationStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactCheck {
+    pub file_name: String,
+    /// `None` for the structural check.
+    pub requirement: Option<RequirementKind>,
+    pub status: ValidationStatus,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactValidationReport {
+    pub checks: Vec<ArtifactCheck>,
+}
+
+impl ArtifactValidationReport {
+    /// Worst status over all checks; `Pass` when there are none.
+    pub fn status(&self) -> ValidationStatus {
+        self.checks
+            .iter()
+            .map(|c| c.status)
+            .max()
+            .unwrap_or(ValidationStatus::Pass)
+    }
+
+    /// Worst status per requirement across all artifacts.
+    pub fn requirement_statuses(&self) -> BTreeMap<RequirementKind, ValidationStatus> {
+        let mut out = BTreeMap::new();
+        for check in &self.checks {
+            if let Some(kind) = check.requirement {
+                let status = out.entry(kind).or_insert(ValidationStatus::Pass);
+                *status = (*status).max(check.status);
+            }
+        }
+        out
+    }
+
+    pub fn structural_checks(&self) -> impl Iterator<Item = &ArtifactCheck> {
+        self.checks.iter().filter(|c| c.requirement.is_none())
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &ArtifactCheck> {
+        self.checks
+            .iter()
+            .filter(|c| c.status == ValidationStatus::Fail)
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ArtifactValidator {
+    min_requirement_strength: f32,
+}
+
+impl Default for ArtifactValidator {
+    fn default() -> Self {
+        Self {
+            min_requirement_strength: 0.1,
+        }
+    }
+}
+
+impl ArtifactValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requirements weaker than this are not checked.
+    pub fn with_min_requirement_strength(mut self, strength: f32) -> Self {
+        self.min_requirement_strength = strength.max(0.0);
+        self
+    }
+
+    pub fn validate(
+        &self,
+        artifacts: &[GeneratedArtifact],
+        l2_units: &[ConceptUnitV2],
+    ) -> ArtifactValidationReport {
+        let mut checks = Vec::new();
+        for artifact in artifacts {
+            let syntax = Syntax::for_file(&artifact.file_name);
+            let (status, message) = match syntax {
+                Some(syntax) => syntax.check_structure(&artifact.content),
+                None => (
+                    ValidationStatus::Warn,
+                    "unknown artifact type; structure not checked".to_string(),
+                ),
+            };
+            checks.push(ArtifactCheck {
+                file_name: artifact.file_name.clone(),
+                requirement: None,
+                status,
+                message,
+            });
+
+            let lines = split_lines(&artifact.content, syntax);
+            for kind in self.required_kinds(&artifact.content, l2_units) {
+                let (status, message) = check_requirement(kind, &lines);
+                checks.push(ArtifactCheck {
+                    file_name: artifact.file_name.clone(),
+                    requirement: Some(kind),
+                    status,
+                    message,
+                });
+            }
+        }
+        ArtifactValidationReport { checks }
+    }
+
+    /// Requirements of the concepts the artifact cites as `L2-<id>`, or of
+    /// every concept when it cites none (aggregate artifacts such as SQL).
+    fn required_kinds(
+        &self,
+        content: &str,
+        l2_units: &[ConceptUnitV2],
+    ) -> BTreeSet<RequirementKind> {
+        let cited = l2_units
+            .iter()
+            .filter(|c| cites_concept(content, c.id))
+            .collect::<Vec<_>>();
+        let covered = if cited.is_empty() {
+            l2_units.iter().collect()
+        } else {
+            cited
+        };
+        covered
+            .into_iter()
+            .flat_map(|c| &c.derived_requirements)
+            .filter(|r| r.strength >= self.min_requirement_strength)
+            .map(|r| r.kind)
+            .collect()
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Syntax {
+    Rust,
+    Sql,
+    Mermaid,
+}
+
+impl Syntax {
+    fn for_file(file_name: &str) -> Option<Self> {
+        match file_name.rsplit_once('.').map(|(_, ext)| ext) {
+            Some("rs") => Some(Self::Rust),
+            Some("sql") => Some(Self::Sql),
+            Some("mmd") => Some(Self::Mermaid),
+            _ => None,
+        }
+    }
+
+    fn comment_prefix(self) -> &'static str {
+        match self {
+            Self::Rust => "//",
+            Self::Sql => "--",
+            Self::Mermaid => "%%",
+        }
+    }
+
+    fn quote(self) -> char {
+        match self {
+            Self::Sql => '\'',
+            Self::Rust | Self::Mermaid => '"',
+        }
+    }
+
+    fn check_structure(self, content: &str) -> (ValidationStatus, String) {
+        let code = split_lines(content, Some(self))
+            .into_iter()
+            .map(|(code, _)| code)
+            .collect::<Vec<_>>()
+            .join("\n");
+        let result = match self {
+            Self::Rust => check_delimiters(&code, self.quote()),
+            Self::Sql => {
+                check_delimiters(&code, self.quote()).and_then(|()| check_sql_statements(&code))
+            }
+            Self::Mermaid => check_mermaid_header(&code),
+        };
+        match result {
+            Ok(()) => (ValidationStatus::Pass, "structure ok".to_string()),
+            Err(message) => (ValidationStatus::Fail, message),
+        }
+    }
+}
+
+/// Splits each line into its code and comment parts. A comment marker
+/// inside a string literal does not start a comment.
+fn split_lines(content: &str, syntax: Option<Syntax>) -> Vec<(&str, &str)> {
+    content
+        .lines()
+        .map(|line| match syntax.and_then(|s| comment_start(line, s)) {
+            Some(at) => (&line[..at], &line[at..]),
+            None => (line, ""),
+        })
+        .collect()
+}
+
+fn comment_start(line: &str, syntax: Syntax) -> Option<usize> {
+    let prefix = syntax.comment_prefix();
+    let mut in_string = false;
+    let mut escaped = false;
+    for (at, c) in line.char_indices() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == syntax.quote() {
+                in_string = false;
+            }
+        } else if c == syntax.quote() {
+            in_string = true;
+        } else if line[at..].starts_with(prefix) {
+            return Some(at);
+        }
+    }
+    None
+}
+
+fn check_requirement(kind: RequirementKind, lines: &[(&str, &str)]) -> (ValidationStatus, String) {
+    let code_hit = |markers: &[&'static str]| {
+        lines
+            .iter()
+            .find_map(|(code, _)| first_marker(code, markers))
+    };
+    let comment_hit = |markers: &[&'static str]| {
+        lines
+            .iter()
+            .find_map(|(_, comment)| first_marker(comment, markers))
+    };
+    match kind {
+        RequirementKind::NoCloud => {
+            if let Some(marker) = code_hit(&EXTERNAL_SERVICE_MARKERS) {
+                (
+                    ValidationStatus::Fail,
+                    format!("external service reference `{marker}` in code"),
+                )
+            } else if let Some(marker) = comment_hit(&EXTERNAL_SERVICE_MARKERS) {
+                (
+                    ValidationStatus::Warn,
+                    format!("external service reference `{marker}` in a comment"),
+                )
+            } else {
+                (
+                    ValidationStatus::Pass,
+                    "no external service references".to_string(),
+                )
+            }
+        }
+        RequirementKind::Memory => {
+            if comment_hit(&MEMORY_MARKERS).is_some() {
+                (
+                    ValidationStatus::Pass,
+                    "memory constraint recorded in comments".to_string(),
+                )
+            } else {
+                (
+                    ValidationStatus::Warn,
+                    "memory constraint not recorded in comments".to_string(),
+                )
+            }
+        }
+        RequirementKind::Security => match code_hit(&CREDENTIAL_MARKERS) {
+            Some(marker) => (
+                ValidationStatus::Warn,
+                format!("possible credential `{marker}` in code"),
+            ),
+            None => (ValidationStatus::Pass, "no credentials in code".to_string()),
+        },
+        RequirementKind::Performance | RequirementKind::Reliability => (
+            ValidationStatus::Pass,
+            "no artifact rule for this requirement".to_string(),
+        ),
+    }
+}
+
+fn first_marker<'a>(text: &str, markers: &[&'a str]) -> Option<&'a str> {
+    let lower = text.to_lowercase();
+    markers.iter().copied().find(|m| lower.contains(m))
+}
+
+pub(crate) fn cites_concept(content: &str, id: ConceptId) -> bool {
+    let needle = format!("L2-{}", id.0);
+    content.match_indices(&needle).any(|(at, _)| {
+        !content[at + needle.len()..]
+            .chars()
+            .next()
+            .is_some_and(|c| c.is_ascii_digit())
+    })
+}
+
+/// `quote` opens and closes string literals, whose contents are skipped.
+/// Rust char literals are not recognised, so `'{'` would count as a brace.
+fn check_delimiters(code: &str, quote: char) -> Result<(), String> {
+    let mut stack = Vec::new();
+    let mut in_string = false;
+    let mut escaped = false;
+    for (line_no, line) in code.lines().enumerate() {
+        for c in line.chars() {
+            if in_string {
+                if escaped {
+                    escaped = false;
+                } else if c == '\\' {
+                    escaped = true;
+                } else if c == quote {
+                    in_string = false;
+                }
+                continue;
+            }
+            match c {
+                c if c == quote => in_string = true,
+                '(' | '[' | '{' => stack.push((c, line_no + 1)),
+                ')' | ']' | '}' => {
+                    let expected = match c {
+                        ')' => '(',
+                        ']' => '[',
+                        _ => '{',
+                    };
+                    match stack.pop() {
+                        Some((open, _)) if open == expected => {}
+                        _ => return Err(format!("unbalanced `{c}` on line {}", line_no + 1)),
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+    if in_string {
+        return Err("unterminated string literal".to_string());
+    }
+    match stack.pop() {
+        Some((open, line)) => Err(format!("unclosed `{open}` from line {line}")),
+        None => Ok(()),
+    }
+}
+
+fn check_sql_statements(code: &str) -> Result<(), String> {
+    let trimmed = code.trim();
+    if trimmed.is_empty() {
+        return Err("no SQL statements".to_string());
+    }
+    if !trimmed.ends_with(';') {
+        return Err("last SQL statement is not terminated with `;`".to_string());
+    }
+    for statement in trimmed.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+        let keyword = statement
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_ascii_uppercase();
+        if !SQL_KEYWORDS.contains(&keyword.as_str()) {
+            return Err(format!(
+                "unexpected SQL statement starting with `{keyword}`"
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn check_mermaid_header(code: &str) -> Result<(), String> {
+    let header = code.lines().map(str::trim).find(|l| !l.is_empty());
+    match header {
+        Some(h) if h.starts_with("graph") || h.starts_with("flowchart") => Ok(()),
+        Some(h) => Err(format!("unexpected Mermaid header `{h}`")),
+        None => Err("empty Mermaid diagram".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use semantic_dhm::DerivedRequirement;
+
+    use super::*;
+
+    fn concept(id: u64, requirements: &[(RequirementKind, f32)]) -> ConceptUnitV2 {
+        ConceptUnitV2 {
+            id: ConceptId(id),
+            derived_requirements: requirements
+                .iter()
+                .map(|(kind, strength)| DerivedRequirement {
+                    kind: *kind,
+                    strength: *strength,
+                })
+                .collect(),
+            causal_links: Vec::new(),
+            stability_score: 1.0,
+        }
+    }
+
+    fn artifact(file_name: &str, content: &str) -> GeneratedArtifact {
+        GeneratedArtifact {
+            file_name: file_name.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    #[test]
+    fn structure_is_checked_per_format() {
+        let report = ArtifactValidator::new().validate(
+            &[
+                artifact("a.rs", "fn f() { let s = \"}\"; } // }"),
+                artifact("b.rs", "fn f() { (]"),
+                artifact(
+                    "c.sql",
+                    "-- note\nCREATE TABLE t (x TEXT);\nINSERT INTO t VALUES (')');",
+                ),
+                artifact("d.sql", "CREATE TABLE t (x TEXT)"),
+                artifact("e.mmd", "%% header\ngraph TD\n  A --> B"),
+                artifact("f.txt", "free text"),
+            ],
+            &[],
+        );
+        let statuses = report
+            .structural_checks()
+            .map(|c| c.status)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            statuses,
+            vec![
+                ValidationStatus::Pass,
+                ValidationStatus::Fail,
+                ValidationStatus::Pass,
+                ValidationStatus::Fail,
+                ValidationStatus::Pass,
+                ValidationStatus::Warn,
+            ]
+        );
+        assert_eq!(report.status(), ValidationStatus::Fail);
+    }
+
+    #[test]
+    fn requirements_are_checked_against_the_artifacts_covering_them() {
+        let concepts = [
+            concept(
+                1,
+                &[
+                    (RequirementKind::NoCloud, 0.6),
+                    (RequirementKind::Memory, 0.5),
+                ],
+            ),
+            concept(
+                2,
+                &[
+                    (RequirementKind::NoCloud, -0.6),
+                    (RequirementKind::Performance, 0.05),
+                ],
+            ),
+        ];
+        let report = ArtifactValidator::new().validate(
+            &[
+                artifact(
+                    "concept_1.rs",
+                    "// source_concept: L2-1\n// requirement: Memory\nconst URL: &str = \"https://api\";",
+                ),
+                artifact("concept_2.rs", "// source_concept: L2-2\n// see https://docs\n"),
+                artifact("schema.sql", "-- see https://docs\nCREATE TABLE t (x TEXT);"),
+            ],
+            &concepts,
+        );
+        let find = |file: &str, kind| {
+            report
+                .checks
+                .iter()
+                .find(|c| c.file_name == file && c.requirement == Some(kind))
+                .map(|c| c.status)
+        };
+        assert_eq!(
+            find("concept_1.rs", RequirementKind::NoCloud),
+            Some(ValidationStatus::Fail)
+        );
+        assert_eq!(
+            find("concept_1.rs", RequirementKind::Memory),
+            Some(ValidationStatus::Pass)
+        );
+        // Concept 2 asks for nothing above the strength threshold.
+        assert_eq!(find("concept_2.rs", RequirementKind::NoCloud), None);
+        assert_eq!(find("concept_2.rs", RequirementKind::Performance), None);
+        // SQL cites no concept, so it answers for all of them.
+        assert_eq!(
+            find("schema.sql", RequirementKind::NoCloud),
+            Some(ValidationStatus::Warn)
+        );
+        assert_eq!(
+            find("schema.sql", RequirementKind::Memory),
+            Some(ValidationStatus::Warn)
+        );
+        assert_eq!(
+            report.requirement_statuses(),
+            BTreeMap::from([
+                (RequirementKind::Memory, ValidationStatus::Warn),
+                (RequirementKind::NoCloud, ValidationStatus::Fail),
+            ])
+        );
+    }
+}
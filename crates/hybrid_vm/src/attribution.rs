@@ -0,0 +1,121 @@
+//! Named, additive breakdown of an `ObjectiveVector`.
+//!
+//! Each axis is a list of contributions whose values sum to the objective
+//! value on that axis. Nonlinear steps (square roots, sigmoids, clamping)
+//! are spread over their inputs in proportion to each input's share, so a
+//! contribution reads as "how much of this score came from that term".
+
+use std::fmt::{Display, Formatter};
+
+use core_types::ObjectiveVector;
+use serde::{Deserialize, Serialize};
+
+/// Residuals smaller than this are not worth a line of their own.
+const RESIDUAL_EPSILON: f64 = 1e-9;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Contribution {
+    pub name: String,
+    pub value: f64,
+}
+
+impl Contribution {
+    pub fn new(name: impl Into<String>, value: f64) -> Self {
+        Self {
+            name: name.into(),
+            value,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ObjectiveAttribution {
+    pub f_struct: Vec<Contribution>,
+    pub f_field: Vec<Contribution>,
+    pub f_risk: Vec<Contribution>,
+    pub f_shape: Vec<Contribution>,
+}
+
+impl ObjectiveAttribution {
+    pub const AXIS_NAMES: [&'static str; 4] = ["f_struct", "f_field", "f_risk", "f_shape"];
+
+    /// Axes in `[f_struct, f_field, f_risk, f_shape]` order.
+    pub fn axes(&self) -> [&Vec<Contribution>; 4] {
+        [&self.f_struct, &self.f_field, &self.f_risk, &self.f_shape]
+    }
+
+    pub fn axes_mut(&mut self) -> [&mut Vec<Contribution>; 4] {
+        [
+            &mut self.f_struct,
+            &mut self.f_field,
+            &mut self.f_risk,
+            &mut self.f_shape,
+        ]
+    }
+
+    /// Per-axis sums of the contributions.
+    pub fn totals(&self) -> ObjectiveVector {
+        let [f_struct, f_field, f_risk, f_shape] =
+            self.axes().map(|axis| axis.iter().map(|c| c.value).sum());
+        ObjectiveVector {
+            f_struct,
+            f_field,
+            f_risk,
+            f_shape,
+        }
+    }
+
+    /// Adds a `label` contribution on every axis whose total differs from
+    /// `objective`, so the attribution accounts for the reported value
+    /// exactly, e.g. after clamping or a memory adjustment the breakdown
+    /// cannot see.
+    pub fn reconciled(mut self, objective: &ObjectiveVector, label: &str) -> Self {
+        let totals = self.totals();
+        let targets = [
+            objective.f_struct,
+            objective.f_field,
+            objective.f_risk,
+            objective.f_shape,
+        ];
+        let sums = [
+            totals.f_struct,
+            totals.f_field,
+            totals.f_risk,
+            totals.f_shape,
+        ];
+        for ((axis, target), sum) in self.axes_mut().into_iter().zip(targets).zip(sums) {
+            let residual = target - sum;
+            if residual.abs() > RESIDUAL_EPSILON {
+                axis.push(Contribution::new(label, residual));
+            }
+        }
+        self
+    }
+}
+
+/// One line per axis, largest contributions first:
+/// `f_struct 0.62 = baseline +0.95, node_ratio -0.20, ...`.
+impl Display for ObjectiveAttribution {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (i, (name, axis)) in Self::AXIS_NAMES.iter().zip(self.axes()).enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let total = axis.iter().map(|c| c.value).sum::<f64>();
+            write!(f, "{name} {total:.2} =")?;
+            let mut ordered = axis.iter().collect::<Vec<_>>();
+            ordered.sort_by(|l, r| r.value.abs().total_cmp(&l.value.abs()));
+            for (j, contribution) in ordered.into_iter().enumerate() {
+                let separator = if j == 0 { " " } else { ", " };
+                write!(
+                    f,
+                    "{separator}{} {:+.2}",
+                    contribution.name, contribution.value
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,324 @@
+//! Ordered revisit plan for a change to one L1 unit.
+//!
+//! A change flows downstream along the causal links of every concept: the
+//! changed unit first, then the units its links point at, and so on. Those
+//! units are put in topological order (a cycle is broken at its unit
+//! closest to the change), and each concept referencing one of them becomes
+//! a step, placed at its earliest affected unit. Each step carries a rough
+//! effort estimate: unstable concepts with many links cost more to revisit.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+use std::fmt::Write as _;
+
+use semantic_dhm::{ConceptId, ConceptUnit, ConceptUnitV2, L1Id, SemanticError};
+use serde::{Deserialize, Serialize};
+
+use crate::GeneratedArtifact;
+
+/// Link count at which a concept's link load counts as half of the maximum.
+const HALF_LOAD_LINKS: f64 = 4.0;
+/// Share of the effort score taken by instability; links get the rest.
+const INSTABILITY_WEIGHT: f64 = 0.6;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum EffortLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl EffortLevel {
+    fn from_score(score: f64) -> Self {
+        if score < 1.0 / 3.0 {
+            Self::Low
+        } else if score < 2.0 / 3.0 {
+            Self::Medium
+        } else {
+            Self::High
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChangeStep {
+    pub concept_id: ConceptId,
+    /// The concept's affected unit that comes first in the plan.
+    pub via: L1Id,
+    /// Causal hops from the changed unit to `via`.
+    pub distance: usize,
+    /// Design cards of the concept's affected units, in plan order.
+    pub cards: Vec<String>,
+    pub stability_score: f64,
+    /// L1 refs plus causal links.
+    pub link_count: usize,
+    /// In [0, 1].
+    pub effort_score: f64,
+    pub effort: EffortLevel,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChangePlan {
+    pub target: L1Id,
+    /// Affected L1 units in topological order, `target` first.
+    pub affected_l1: Vec<L1Id>,
+    /// Concepts to revisit, first one first.
+    pub steps: Vec<ChangeStep>,
+}
+
+impl ChangePlan {
+    /// Plans a change to `target` over `concepts`. A unit no concept
+    /// references yields a plan without steps.
+    pub fn build(target: L1Id, concepts: &[ConceptUnit]) -> Result<Self, SemanticError> {
+        let mut views = Vec::with_capacity(concepts.len());
+        let mut downstream: BTreeMap<L1Id, BTreeSet<L1Id>> = BTreeMap::new();
+        for concept in concepts {
+            let v2 = ConceptUnitV2::try_from(concept)?;
+            for link in v2.causal_links.iter().filter(|link| link.from != link.to) {
+                downstream.entry(link.from).or_default().insert(link.to);
+            }
+            views.push((concept, v2));
+        }
+
+        let distances = distances_from(target, &downstream);
+        let affected_l1 = topological_order(&distances, &downstream);
+        let position = affected_l1
+            .iter()
+            .enumerate()
+            .map(|(index, id)| (*id, index))
+            .collect::<BTreeMap<_, _>>();
+
+        let mut steps = views
+            .into_iter()
+            .filter_map(|(concept, v2)| {
+                let mut affected = concept
+                    .l1_refs
+                    .iter()
+                    .filter(|id| position.contains_key(id))
+                    .copied()
+                    .collect::<Vec<_>>();
+                affected.sort_by_key(|id| position[id]);
+                affected.dedup();
+                let via = *affected.first()?;
+                let link_count = concept.l1_refs.len() + v2.causal_links.len();
+                let effort_score = effort_score(v2.stability_score, link_count);
+                Some(ChangeStep {
+                    concept_id: concept.id,
+                    via,
+                    distance: distances[&via],
+                    cards: affected.iter().map(|id| format!("CARD-{}", id.0)).collect(),
+                    stability_score: v2.stability_score,
+                    link_count,
+                    effort_score,
+                    effort: EffortLevel::from_score(effort_score),
+                })
+            })
+            .collect::<Vec<_>>();
+        // Costlier concepts first within a position, so they get started early.
+        steps.sort_by(|a, b| {
+            position[&a.via]
+                .cmp(&position[&b.via])
+                .then_with(|| b.effort_score.total_cmp(&a.effort_score))
+                .then_with(|| a.concept_id.cmp(&b.concept_id))
+        });
+
+        Ok(Self {
+            target,
+            affected_l1,
+            steps,
+        })
+    }
+
+    pub fn total_effort(&self) -> f64 {
+        self.steps.iter().map(|step| step.effort_score).sum()
+    }
+
+    /// Markdown task list, one unchecked item per step.
+    pub fn to_checklist(&self) -> String {
+        let mut out = format!("## Change plan for L1-{}\n\n", self.target.0);
+        if self.steps.is_empty() {
+            let _ = writeln!(out, "No concept references L1-{}.", self.target.0);
+            return out;
+        }
+        for (index, step) in self.steps.iter().enumerate() {
+            let _ = writeln!(
+                out,
+                "- [ ] {}. Revisit L2-{} via L1-{} ({}) — effort {} ({:.2}; stability {:.2}, {} links)",
+                index + 1,
+                step.concept_id.0,
+                step.via.0,
+                step.cards.join(", "),
+                step.effort.label(),
+                step.effort_score,
+                step.stability_score,
+                step.link_count
+            );
+        }
+        let _ = writeln!(out, "\nTotal effort: {:.2}", self.total_effort());
+        out
+    }
+
+    pub fn artifact(&self) -> GeneratedArtifact {
+        GeneratedArtifact {
+            file_name: format!("change_plan_l1_{}.md", self.target.0),
+            content: self.to_checklist(),
+        }
+    }
+}
+
+fn effort_score(stability_score: f64, link_count: usize) -> f64 {
+    let instability = (1.0 - stability_score).clamp(0.0, 1.0);
+    let links = link_count as f64;
+    let load = links / (links + HALF_LOAD_LINKS);
+    (INSTABILITY_WEIGHT * instability + (1.0 - INSTABILITY_WEIGHT) * load).clamp(0.0, 1.0)
+}
+
+/// Causal hops from `target` to every unit downstream of it.
+fn distances_from(
+    target: L1Id,
+    downstream: &BTreeMap<L1Id, BTreeSet<L1Id>>,
+) -> BTreeMap<L1Id, usize> {
+    let mut distances = BTreeMap::from([(target, 0)]);
+    let mut queue = VecDeque::from([target]);
+    while let Some(id) = queue.pop_front() {
+        let next = distances[&id] + 1;
+        for to in downstream.get(&id).into_iter().flatten() {
+            if !distances.contains_key(to) {
+                distances.insert(*to, next);
+                queue.push_back(*to);
+            }
+        }
+    }
+    distances
+}
+
+/// Kahn's algorithm over the affected units, ready units taken nearest
+/// first. When only cycles remain, the nearest remaining unit goes next.
+fn topological_order(
+    distances: &BTreeMap<L1Id, usize>,
+    downstream: &BTreeMap<L1Id, BTreeSet<L1Id>>,
+) -> Vec<L1Id> {
+    let mut indegree = distances
+        .keys()
+        .map(|id| (*id, 0usize))
+        .collect::<BTreeMap<_, _>>();
+    for (from, targets) in downstream {
+        if distances.contains_key(from) {
+            for to in targets {
+                if let Some(count) = indegree.get_mut(to) {
+                    *count += 1;
+                }
+            }
+        }
+    }
+
+    let mut order = Vec::with_capacity(distances.len());
+    let mut remaining = distances
+        .iter()
+        .map(|(id, distance)| (*distance, *id))
+        .collect::<BTreeSet<_>>();
+    while !remaining.is_empty() {
+        let next = remaining
+            .iter()
+            .find(|(_, id)| indegree[id] == 0)
+            .or_else(|| remaining.first())
+            .copied()
+            .expect("remaining is not empty");
+        remaining.remove(&next);
+        let id = next.1;
+        for to in downstream.get(&id).into_iter().flatten() {
+            if let Some(count) = indegree.get_mut(to) {
+                *count = count.saturating_sub(1);
+            }
+        }
+        order.push(id);
+    }
+    order
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn concept(id: u64, refs: &[u128], a: f32) -> ConceptUnit {
+        ConceptUnit {
+            id: ConceptId(id),
+            l1_refs: refs.iter().map(|r| L1Id(*r)).collect(),
+            integrated_vector: vec![1.0; semantic_dhm::D_SEM],
+            a,
+            s: vec![0.5; semantic_dhm::D_STRUCT],
+            polarity: 1,
+            timestamp: 0,
+            access: semantic_dhm::AccessControl::default(),
+        }
+    }
+
+    /// Links run along sorted refs: L2-11 has 2 -> 3 -> 4, L2-10 has 1 -> 2.
+    fn concepts() -> Vec<ConceptUnit> {
+        vec![
+            concept(10, &[1, 2], 0.2),
+            concept(11, &[2, 3, 4], 1.0),
+            concept(12, &[1], 0.0),
+            concept(13, &[4], 0.5),
+            concept(14, &[5], 0.5),
+        ]
+    }
+
+    #[test]
+    fn steps_follow_causal_order_from_the_changed_unit() {
+        let plan = ChangePlan::build(L1Id(1), &concepts()).expect("plan");
+        assert_eq!(plan.affected_l1, vec![L1Id(1), L1Id(2), L1Id(3), L1Id(4)]);
+        let order = plan
+            .steps
+            .iter()
+            .map(|s| (s.concept_id.0, s.via.0, s.distance))
+            .collect::<Vec<_>>();
+        // L2-10 and L2-12 both start at L1 1; L2-10 has more to revisit.
+        assert_eq!(order, vec![(10, 1, 0), (12, 1, 0), (11, 2, 1), (13, 4, 3)]);
+        assert_eq!(plan.steps[2].cards, vec!["CARD-2", "CARD-3", "CARD-4"]);
+        assert_eq!(plan.steps[2].link_count, 5);
+        assert!(plan.steps[2].effort_score > plan.steps[3].effort_score);
+        assert_eq!(plan.steps[1].effort, EffortLevel::Low);
+
+        // Upstream concepts are untouched by a change further down.
+        let downstream = ChangePlan::build(L1Id(3), &concepts()).expect("plan");
+        let ids = downstream
+            .steps
+            .iter()
+            .map(|s| s.concept_id.0)
+            .collect::<Vec<_>>();
+        assert_eq!(ids, vec![11, 13]);
+    }
+
+    #[test]
+    fn checklist_lists_each_step_and_cycles_still_terminate() {
+        let checklist = ChangePlan::build(L1Id(1), &concepts())
+            .expect("plan")
+            .to_checklist();
+        assert!(checklist.starts_with("## Change plan for L1-1\n"));
+        assert!(checklist.contains("- [ ] 3. Revisit L2-11 via L1-2 (CARD-2, CARD-3, CARD-4)"));
+        assert!(checklist.contains("Total effort:"));
+
+        let lone = ChangePlan::build(L1Id(99), &concepts()).expect("plan");
+        assert!(lone.steps.is_empty());
+        assert!(lone.to_checklist().contains("No concept references L1-99."));
+
+        // Sorted-ref chains never form a cycle, so build one directly.
+        let downstream = BTreeMap::from([
+            (L1Id(7), BTreeSet::from([L1Id(8)])),
+            (L1Id(8), BTreeSet::from([L1Id(7)])),
+        ]);
+        let distances = distances_from(L1Id(8), &downstream);
+        assert_eq!(
+            topological_order(&distances, &downstream),
+            vec![L1Id(8), L1Id(7)]
+        );
+    }
+}
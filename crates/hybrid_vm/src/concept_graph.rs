@@ -0,0 +1,484 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use field_engine::{FieldEngine, NodeCategory, TargetField, resonance_score};
+use memory_space::{DesignNode, DesignState, NodeId, StructuralGraph, Uuid, Value};
+use semantic_dhm::{CausalEdge, ConceptId, ConceptUnitV2, L1Id, RequirementKind};
+use shm::{RuleCategory, RuleId, Shm};
+
+pub const CONCEPT_NODE_KIND: &str = "Concept";
+/// Kind used for causal elements when a concept has no requirement above threshold.
+pub const ELEMENT_NODE_KIND: &str = "Element";
+/// Prefix of the per-edge weight attribute stored on the edge's source node.
+pub const CAUSAL_WEIGHT_PREFIX: &str = "causal_weight:";
+
+const TAG_CONCEPT: u128 = 1;
+const TAG_REQUIREMENT: u128 = 2;
+const TAG_ELEMENT: u128 = 3;
+const PAYLOAD_MASK: u128 = (1u128 << 120) - 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeSource {
+    /// Anchor node for the L2 unit itself.
+    Concept,
+    Requirement(RequirementKind),
+    /// Endpoint of a causal link (an L1 unit inside the concept).
+    Element(L1Id),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NodeOrigin {
+    pub concept: ConceptId,
+    pub source: NodeSource,
+}
+
+/// Output of `ConceptGraphBuilder::build`. `state` is ready for `BeamSearch`;
+/// the remaining fields describe where each node and edge came from.
+#[derive(Clone, Debug)]
+pub struct ConceptGraph {
+    pub state: DesignState,
+    pub origins: BTreeMap<NodeId, NodeOrigin>,
+    pub edge_weights: BTreeMap<(NodeId, NodeId), f64>,
+    /// Causal links that were not added because they would close a cycle.
+    pub dropped_links: Vec<(ConceptId, CausalEdge)>,
+}
+
+impl ConceptGraph {
+    pub fn nodes_for_concept(&self, concept: ConceptId) -> Vec<NodeId> {
+        self.origins
+            .iter()
+            .filter(|(_, origin)| origin.concept == concept)
+            .map(|(id, _)| *id)
+            .collect()
+    }
+}
+
+/// Converts L2 concepts into a `StructuralGraph`.
+///
+/// Each concept yields a `Concept` anchor, one node per derived requirement
+/// (kind = requirement kind) and one node per causal-link endpoint (kind =
+/// the concept's dominant requirement). Every node carries a `concept_id`
+/// attribute so provenance survives search transformations.
+#[derive(Clone, Debug)]
+pub struct ConceptGraphBuilder {
+    min_requirement_strength: f32,
+    state_id: Uuid,
+    profile: String,
+}
+
+impl Default for ConceptGraphBuilder {
+    fn default() -> Self {
+        Self {
+            min_requirement_strength: 0.1,
+            state_id: Uuid::from_u128(1),
+            profile: "concept_graph".to_string(),
+        }
+    }
+}
+
+impl ConceptGraphBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requirements with `|strength|` below this are not materialized as nodes.
+    pub fn with_min_requirement_strength(mut self, strength: f32) -> Self {
+        self.min_requirement_strength = strength.max(0.0);
+        self
+    }
+
+    pub fn with_state_id(mut self, id: Uuid) -> Self {
+        self.state_id = id;
+        self
+    }
+
+    pub fn with_profile(mut self, profile: impl Into<String>) -> Self {
+        self.profile = profile.into();
+        self
+    }
+
+    pub fn build(&self, concepts: &[ConceptUnitV2]) -> ConceptGraph {
+        let mut sorted = concepts.iter().collect::<Vec<_>>();
+        sorted.sort_by_key(|c| c.id);
+
+        let mut graph = StructuralGraph::default();
+        let mut origins = BTreeMap::new();
+        let mut edge_weights = BTreeMap::new();
+        let mut dropped_links = Vec::new();
+
+        for concept in sorted {
+            let anchor = concept_node_id(concept.id);
+            let mut attributes = provenance_attributes(concept.id);
+            attributes.insert(
+                "stability".to_string(),
+                Value::Float(concept.stability_score),
+            );
+            graph = graph.with_node_added(DesignNode::new(anchor, CONCEPT_NODE_KIND, attributes));
+            origins.insert(
+                anchor,
+                NodeOrigin {
+                    concept: concept.id,
+                    source: NodeSource::Concept,
+                },
+            );
+
+            for req in &concept.derived_requirements {
+                if req.strength.abs() < self.min_requirement_strength {
+                    continue;
+                }
+                let id = requirement_node_id(concept.id, req.kind);
+                let mut attributes = provenance_attributes(concept.id);
+                attributes.insert(
+                    "strength".to_string(),
+                    Value::Float(f64::from(req.strength)),
+                );
+                graph = graph
+                    .with_node_added(DesignNode::new(
+                        id,
+                        requirement_kind_name(req.kind),
+                        attributes,
+                    ))
+                    .with_edge_added(anchor, id);
+                origins.insert(
+                    id,
+                    NodeOrigin {
+                        concept: concept.id,
+                        source: NodeSource::Requirement(req.kind),
+                    },
+                );
+            }
+
+            let element_kind = self
+                .dominant_requirement(concept)
+                .map(requirement_kind_name)
+                .unwrap_or(ELEMENT_NODE_KIND);
+            let mut elements = BTreeMap::<L1Id, BTreeMap<String, Value>>::new();
+            for link in &concept.causal_links {
+                for l1 in [link.from, link.to] {
+                    elements.entry(l1).or_insert_with(|| {
+                        let mut attributes = provenance_attributes(concept.id);
+                        attributes.insert("l1_id".to_string(), Value::Text(format!("{:x}", l1.0)));
+                        attributes
+                    });
+                }
+            }
+            let mut links = concept.causal_links.clone();
+            links.sort_by_key(|l| (l.from, l.to));
+            for link in &links {
+                if let Some(attributes) = elements.get_mut(&link.from) {
+                    attributes.insert(
+                        format!("{CAUSAL_WEIGHT_PREFIX}{:x}", link.to.0),
+                        Value::Float(link.weight),
+                    );
+                }
+            }
+            for (l1, attributes) in elements {
+                let id = element_node_id(concept.id, l1);
+                graph = graph
+                    .with_node_added(DesignNode::new(id, element_kind, attributes))
+                    .with_edge_added(anchor, id);
+                origins.insert(
+                    id,
+                    NodeOrigin {
+                        concept: concept.id,
+                        source: NodeSource::Element(l1),
+                    },
+                );
+            }
+
+            let mut seen = BTreeSet::new();
+            for link in links {
+                let edge = (
+                    element_node_id(concept.id, link.from),
+                    element_node_id(concept.id, link.to),
+                );
+                if link.from == link.to || !seen.insert(edge) {
+                    continue;
+                }
+                let next = graph.with_edge_added(edge.0, edge.1);
+                if next.edges().contains(&edge) {
+                    graph = next;
+                    edge_weights.insert(edge, link.weight);
+                } else {
+                    dropped_links.push((concept.id, link));
+                }
+            }
+        }
+
+        ConceptGraph {
+            state: DesignState::new(self.state_id, Arc::new(graph), self.profile.clone()),
+            origins,
+            edge_weights,
+            dropped_links,
+        }
+    }
+
+    fn dominant_requirement(&self, concept: &ConceptUnitV2) -> Option<RequirementKind> {
+        concept
+            .derived_requirements
+            .iter()
+            .filter(|r| r.strength.abs() >= self.min_requirement_strength)
+            .max_by(|l, r| {
+                l.strength
+                    .abs()
+                    .total_cmp(&r.strength.abs())
+                    .then_with(|| r.kind.cmp(&l.kind))
+            })
+            .map(|r| r.kind)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConfidenceLevel {
+    Low,
+    Medium,
+    High,
+}
+
+/// How well one `DerivedRequirement` is reflected in a (searched) state.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RequirementSatisfaction {
+    pub concept: ConceptId,
+    pub kind: RequirementKind,
+    pub strength: f32,
+    /// Seed nodes for this requirement plus nodes generated by supporting rules.
+    pub matched_nodes: Vec<NodeId>,
+    /// Applied rules (from the state's rule history) whose category supports `kind`.
+    pub supporting_rules: Vec<(RuleId, RuleCategory)>,
+    pub field_resonance: f64,
+    pub score: f64,
+    pub satisfied: bool,
+    pub confidence: ConfidenceLevel,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RequirementSatisfactionReport {
+    pub entries: Vec<RequirementSatisfaction>,
+}
+
+impl RequirementSatisfactionReport {
+    pub fn satisfied_count(&self) -> usize {
+        self.entries.iter().filter(|e| e.satisfied).count()
+    }
+
+    pub fn for_concept(&self, concept: ConceptId) -> Vec<&RequirementSatisfaction> {
+        self.entries
+            .iter()
+            .filter(|e| e.concept == concept)
+            .collect()
+    }
+}
+
+pub const REQUIREMENT_SATISFIED_THRESHOLD: f64 = 0.5;
+const RESONANCE_EVIDENCE_THRESHOLD: f64 = 0.3;
+
+/// `annotate_state_with_requirements_using` with the default rule set and a
+/// 64-dimensional field.
+pub fn annotate_state_with_requirements(
+    state: &DesignState,
+    concepts: &[ConceptUnitV2],
+) -> RequirementSatisfactionReport {
+    annotate_state_with_requirements_using(
+        state,
+        concepts,
+        &Shm::with_default_rules(),
+        &FieldEngine::new(64),
+    )
+}
+
+/// Maps nodes of `state` back to the requirements of `concepts`.
+///
+/// Evidence per requirement: surviving seed nodes (via `concept_id`),
+/// applied rules whose category supports the requirement (via the
+/// `history:` profile and `generated_by_*` attributes), and resonance of
+/// the state field with the requirement's field category. Confidence grows
+/// with the number of independent evidence sources.
+pub fn annotate_state_with_requirements_using(
+    state: &DesignState,
+    concepts: &[ConceptUnitV2],
+    shm: &Shm,
+    field: &FieldEngine,
+) -> RequirementSatisfactionReport {
+    let applied = parse_rule_history(&state.profile_snapshot)
+        .into_iter()
+        .filter_map(|id| {
+            shm.rules()
+                .iter()
+                .find(|rule| rule.id == id)
+                .map(|rule| (id, rule.category.clone()))
+        })
+        .collect::<Vec<_>>();
+    let state_field = field.aggregate_state(state);
+
+    let mut entries = Vec::new();
+    let mut sorted = concepts.iter().collect::<Vec<_>>();
+    sorted.sort_by_key(|c| c.id);
+    for concept in sorted {
+        for req in &concept.derived_requirements {
+            let kind_name = requirement_kind_name(req.kind);
+            let supporting_rules = applied
+                .iter()
+                .filter(|(_, category)| supporting_categories(req.kind).contains(category))
+                .cloned()
+                .collect::<Vec<_>>();
+
+            let seed_nodes = state
+                .graph
+                .nodes()
+                .values()
+                .filter(|node| {
+                    node.kind == kind_name
+                        && node.attributes.get("concept_id")
+                            == Some(&Value::Int(concept.id.0 as i64))
+                })
+                .map(|node| node.id)
+                .collect::<Vec<_>>();
+            let generated_nodes = state
+                .graph
+                .nodes()
+                .values()
+                .filter(|node| {
+                    supporting_rules.iter().any(|(id, _)| {
+                        node.attributes
+                            .contains_key(&format!("generated_by_{}", id.as_u128()))
+                    })
+                })
+                .map(|node| node.id)
+                .collect::<Vec<_>>();
+
+            let target = TargetField {
+                data: field
+                    .projector()
+                    .basis_for(requirement_field_category(req.kind)),
+            };
+            let field_resonance = resonance_score(&state_field, &target);
+
+            let seed_evidence = if seed_nodes.is_empty() { 0.0 } else { 1.0 };
+            let rule_evidence = if applied.is_empty() {
+                0.0
+            } else {
+                let ratio = supporting_rules.len() as f64 / applied.len() as f64;
+                (ratio * 2.0).min(1.0)
+            };
+            let score =
+                (0.3 * seed_evidence + 0.4 * rule_evidence + 0.3 * field_resonance).clamp(0.0, 1.0);
+            let sources = usize::from(!seed_nodes.is_empty())
+                + usize::from(!supporting_rules.is_empty())
+                + usize::from(field_resonance >= RESONANCE_EVIDENCE_THRESHOLD);
+            let confidence = match sources {
+                3 => ConfidenceLevel::High,
+                2 => ConfidenceLevel::Medium,
+                _ => ConfidenceLevel::Low,
+            };
+
+            let mut matched_nodes = seed_nodes;
+            matched_nodes.extend(generated_nodes);
+            matched_nodes.sort();
+            matched_nodes.dedup();
+            entries.push(RequirementSatisfaction {
+                concept: concept.id,
+                kind: req.kind,
+                strength: req.strength,
+                matched_nodes,
+                supporting_rules,
+                field_resonance,
+                score,
+                satisfied: score >= REQUIREMENT_SATISFIED_THRESHOLD,
+                confidence,
+            });
+        }
+    }
+    RequirementSatisfactionReport { entries }
+}
+
+/// Rule categories whose transformations are taken to serve a requirement.
+pub fn supporting_categories(kind: RequirementKind) -> &'static [RuleCategory] {
+    match kind {
+        RequirementKind::Performance => &[RuleCategory::Performance],
+        RequirementKind::Memory => &[RuleCategory::Cost, RuleCategory::Refactor],
+        RequirementKind::Security => &[
+            RuleCategory::ConstraintPropagation,
+            RuleCategory::Structural,
+        ],
+        RequirementKind::NoCloud => &[RuleCategory::Cost, RuleCategory::ConstraintPropagation],
+        RequirementKind::Reliability => &[RuleCategory::Reliability],
+    }
+}
+
+pub fn requirement_field_category(kind: RequirementKind) -> NodeCategory {
+    match kind {
+        RequirementKind::Performance => NodeCategory::Performance,
+        RequirementKind::Memory => NodeCategory::Storage,
+        RequirementKind::Security => NodeCategory::Control,
+        RequirementKind::NoCloud => NodeCategory::Network,
+        RequirementKind::Reliability => NodeCategory::Reliability,
+    }
+}
+
+pub fn requirement_kind_name(kind: RequirementKind) -> &'static str {
+    match kind {
+        RequirementKind::Performance => "Performance",
+        RequirementKind::Memory => "Memory",
+        RequirementKind::Security => "Security",
+        RequirementKind::NoCloud => "NoCloud",
+        RequirementKind::Reliability => "Reliability",
+    }
+}
+
+pub fn concept_node_id(concept: ConceptId) -> NodeId {
+    tagged_id(TAG_CONCEPT, u128::from(concept.0))
+}
+
+pub fn requirement_node_id(concept: ConceptId, kind: RequirementKind) -> NodeId {
+    tagged_id(
+        TAG_REQUIREMENT,
+        (u128::from(concept.0) << 8) | requirement_kind_index(kind),
+    )
+}
+
+/// Keyed by the owning concept too: concepts sharing an L1 unit each get
+/// their own element node.
+pub fn element_node_id(concept: ConceptId, l1: L1Id) -> NodeId {
+    // FNV-1a 128 over both ids; L1 ids already use the full 128 bits.
+    const OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+    let payload = concept
+        .0
+        .to_le_bytes()
+        .into_iter()
+        .chain(l1.0.to_le_bytes())
+        .fold(OFFSET, |hash, byte| {
+            (hash ^ u128::from(byte)).wrapping_mul(PRIME)
+        });
+    tagged_id(TAG_ELEMENT, payload)
+}
+
+fn requirement_kind_index(kind: RequirementKind) -> u128 {
+    match kind {
+        RequirementKind::Performance => 0,
+        RequirementKind::Memory => 1,
+        RequirementKind::Security => 2,
+        RequirementKind::NoCloud => 3,
+        RequirementKind::Reliability => 4,
+    }
+}
+
+fn tagged_id(tag: u128, payload: u128) -> NodeId {
+    Uuid::from_u128((tag << 120) | (payload & PAYLOAD_MASK))
+}
+
+/// Reads the `history:<id>,<id>` profile written by `agent_core::apply_atomic`.
+fn parse_rule_history(snapshot: &str) -> Vec<RuleId> {
+    snapshot
+        .strip_prefix("history:")
+        .unwrap_or("")
+        .split(',')
+        .filter_map(|s| s.parse::<u128>().ok().map(Uuid::from_u128))
+        .collect()
+}
+
+fn provenance_attributes(concept: ConceptId) -> BTreeMap<String, Value> {
+    let mut attributes = BTreeMap::new();
+    attributes.insert("concept_id".to_string(), Value::Int(concept.0 as i64));
+    attributes
+}
@@ -0,0 +1,107 @@
+//! Which L1 units a session is currently working with.
+//!
+//! Long `analyze_text` sessions pile up units that no longer describe what
+//! the user is designing. Archiving a unit keeps it stored (cards, stats,
+//! snapshots and provenance still see it) but drops it from the context
+//! L2 rebuilds and draft generation draw on, until it is restored. New
+//! units start active.
+
+use std::collections::BTreeMap;
+
+use semantic_dhm::L1Id;
+use serde::{Deserialize, Serialize};
+
+/// Units that rebuilds and drafts consider.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ContextScope {
+    /// Archived units are left out.
+    #[default]
+    Active,
+    All,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum L1ContextState {
+    Active,
+    Archived {
+        /// Unix time in milliseconds.
+        at_ms: u64,
+    },
+}
+
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ContextWindow {
+    archived: BTreeMap<L1Id, u64>,
+    pub(crate) scope: ContextScope,
+}
+
+impl ContextWindow {
+    pub(crate) fn state(&self, id: L1Id) -> L1ContextState {
+        match self.archived.get(&id) {
+            Some(at_ms) => L1ContextState::Archived { at_ms: *at_ms },
+            None => L1ContextState::Active,
+        }
+    }
+
+    pub(crate) fn in_scope(&self, id: L1Id) -> bool {
+        self.scope == ContextScope::All || !self.archived.contains_key(&id)
+    }
+
+    /// False when `id` was already archived; its archive time is kept.
+    pub(crate) fn archive(&mut self, id: L1Id, at_ms: u64) -> bool {
+        if self.archived.contains_key(&id) {
+            return false;
+        }
+        self.archived.insert(id, at_ms);
+        true
+    }
+
+    /// False when `id` was not archived.
+    pub(crate) fn restore(&mut self, id: L1Id) -> bool {
+        self.archived.remove(&id).is_some()
+    }
+
+    /// Drops the state of a removed unit.
+    pub(crate) fn forget(&mut self, id: L1Id) {
+        self.archived.remove(&id);
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.archived.clear();
+    }
+
+    pub(crate) fn archived(&self) -> impl Iterator<Item = L1Id> + '_ {
+        self.archived.keys().copied()
+    }
+
+    pub(crate) fn has_archived(&self) -> bool {
+        !self.archived.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn archived_units_leave_the_active_scope_until_restored() {
+        let mut window = ContextWindow::default();
+        assert!(window.archive(L1Id(1), 10));
+        assert!(!window.archive(L1Id(1), 20));
+        assert_eq!(
+            window.state(L1Id(1)),
+            L1ContextState::Archived { at_ms: 10 }
+        );
+        assert!(!window.in_scope(L1Id(1)));
+        assert!(window.in_scope(L1Id(2)));
+
+        window.scope = ContextScope::All;
+        assert!(window.in_scope(L1Id(1)));
+        window.scope = ContextScope::Active;
+
+        assert!(window.restore(L1Id(1)));
+        assert!(!window.restore(L1Id(1)));
+        assert_eq!(window.state(L1Id(1)), L1ContextState::Active);
+        assert!(!window.has_archived());
+    }
+}
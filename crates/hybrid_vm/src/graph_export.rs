@@ -0,0 +1,465 @@
+//! Concept-graph export for inspection in Graphviz or Mermaid.
+//!
+//! L1 units are drawn as nodes grouped into one cluster per L2 concept,
+//! with the concept's causal links between them. Concepts that share L1
+//! units, or whose causal links reach into another concept, are joined by
+//! relation edges. Edge width follows the link weight.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+
+use semantic_dhm::{
+    CausalEdge, ConceptId, ConceptUnit, ConceptUnitV2, L1Id, RequirementRole, SemanticError,
+    SemanticUnitL1,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::GeneratedArtifact;
+
+/// Longest node label, in characters, before it is cut with `…`.
+const LABEL_CHARS: usize = 24;
+/// Weights at or above this render as strong edges.
+const STRONG_WEIGHT: f64 = 0.66;
+/// Weights below this render as weak (dashed) edges.
+const WEAK_WEIGHT: f64 = 0.33;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GraphExportFormat {
+    Dot,
+    Mermaid,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct L1Node {
+    pub id: L1Id,
+    pub role: RequirementRole,
+    pub label: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConceptCluster {
+    pub id: ConceptId,
+    pub stability_score: f64,
+    pub members: Vec<L1Id>,
+    /// Links between two members; self-loops are dropped.
+    pub causal_links: Vec<CausalEdge>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ConceptRelationKind {
+    /// Both concepts reference the same L1 units; undirected.
+    SharedL1,
+    /// A causal link of `from` ends at an L1 unit of `to`.
+    Causal,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConceptRelation {
+    pub from: ConceptId,
+    pub to: ConceptId,
+    pub kind: ConceptRelationKind,
+    /// Jaccard overlap of the L1 refs for `SharedL1`, strongest crossing
+    /// link for `Causal`.
+    pub weight: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ConceptGraphExport {
+    pub l1_nodes: Vec<L1Node>,
+    pub clusters: Vec<ConceptCluster>,
+    pub relations: Vec<ConceptRelation>,
+}
+
+impl ConceptGraphExport {
+    pub fn build(
+        l1_units: &[SemanticUnitL1],
+        concepts: &[ConceptUnit],
+    ) -> Result<Self, SemanticError> {
+        let l1_nodes = l1_units
+            .iter()
+            .map(|unit| L1Node {
+                id: unit.id,
+                role: unit.role,
+                label: truncate_label(&unit.source_text),
+            })
+            .collect::<Vec<_>>();
+
+        let mut clusters = Vec::with_capacity(concepts.len());
+        for concept in concepts {
+            let v2 = ConceptUnitV2::try_from(concept)?;
+            let mut members = concept.l1_refs.clone();
+            members.sort();
+            members.dedup();
+            clusters.push(ConceptCluster {
+                id: concept.id,
+                stability_score: v2.stability_score,
+                members,
+                causal_links: v2
+                    .causal_links
+                    .into_iter()
+                    .filter(|link| link.from != link.to)
+                    .collect(),
+            });
+        }
+        clusters.sort_by_key(|c| c.id);
+
+        let relations = relations(&clusters);
+        Ok(Self {
+            l1_nodes,
+            clusters,
+            relations,
+        })
+    }
+
+    pub fn render(&self, format: GraphExportFormat) -> String {
+        match format {
+            GraphExportFormat::Dot => self.to_dot(),
+            GraphExportFormat::Mermaid => self.to_mermaid(),
+        }
+    }
+
+    pub fn artifact(&self, format: GraphExportFormat) -> GeneratedArtifact {
+        let file_name = match format {
+            GraphExportFormat::Dot => "concept_graph.dot",
+            GraphExportFormat::Mermaid => "concept_graph.mmd",
+        };
+        GeneratedArtifact {
+            file_name: file_name.to_string(),
+            content: self.render(format),
+        }
+    }
+
+    pub fn to_dot(&self) -> String {
+        let labels = self.labels();
+        let mut out = String::from(
+            "digraph concept_graph {\n  compound=true;\n  rankdir=LR;\n  node [shape=box];\n",
+        );
+        for cluster in &self.clusters {
+            let _ = writeln!(out, "  subgraph cluster_l2_{} {{", cluster.id.0);
+            let _ = writeln!(
+                out,
+                "    label=\"L2-{} stability={:.2}\";",
+                cluster.id.0, cluster.stability_score
+            );
+            for member in &cluster.members {
+                let _ = writeln!(
+                    out,
+                    "    c{}_l1_{} [label=\"{}\"];",
+                    cluster.id.0,
+                    member.0,
+                    escape(&labels.node(*member))
+                );
+            }
+            for link in &cluster.causal_links {
+                let style = if link.weight < WEAK_WEIGHT {
+                    ", style=dashed"
+                } else {
+                    ""
+                };
+                let _ = writeln!(
+                    out,
+                    "    c{0}_l1_{1} -> c{0}_l1_{2} [label=\"{3:.2}\", penwidth={4:.2}{5}];",
+                    cluster.id.0,
+                    link.from.0,
+                    link.to.0,
+                    link.weight,
+                    pen_width(link.weight),
+                    style
+                );
+            }
+            out.push_str("  }\n");
+        }
+        for relation in &self.relations {
+            let (Some(from), Some(to)) = (
+                self.anchor(relation.from, relation),
+                self.anchor(relation.to, relation),
+            ) else {
+                continue;
+            };
+            let style = match relation.kind {
+                ConceptRelationKind::SharedL1 => "dir=none, style=dotted",
+                ConceptRelationKind::Causal => "style=bold",
+            };
+            let _ = writeln!(
+                out,
+                "  c{}_l1_{} -> c{}_l1_{} [label=\"{:?} {:.2}\", penwidth={:.2}, {}, ltail=cluster_l2_{}, lhead=cluster_l2_{}];",
+                relation.from.0,
+                from.0,
+                relation.to.0,
+                to.0,
+                relation.kind,
+                relation.weight,
+                pen_width(relation.weight),
+                style,
+                relation.from.0,
+                relation.to.0
+            );
+        }
+        out.push_str("}\n");
+        out
+    }
+
+    pub fn to_mermaid(&self) -> String {
+        let labels = self.labels();
+        let mut out = String::from("graph LR\n");
+        let mut edge_index = 0usize;
+        let mut link_styles = Vec::new();
+        for cluster in &self.clusters {
+            let _ = writeln!(
+                out,
+                "  subgraph L2_{}[\"L2-{} stability={:.2}\"]",
+                cluster.id.0, cluster.id.0, cluster.stability_score
+            );
+            for member in &cluster.members {
+                let _ = writeln!(
+                    out,
+                    "    c{}_l1_{}[\"{}\"]",
+                    cluster.id.0,
+                    member.0,
+                    escape(&labels.node(*member))
+                );
+            }
+            for link in &cluster.causal_links {
+                let _ = writeln!(
+                    out,
+                    "    c{0}_l1_{1} {2}|{3:.2}| c{0}_l1_{4}",
+                    cluster.id.0,
+                    link.from.0,
+                    mermaid_arrow(link.weight),
+                    link.weight,
+                    link.to.0
+                );
+                link_styles.push((edge_index, pen_width(link.weight)));
+                edge_index += 1;
+            }
+            out.push_str("  end\n");
+        }
+        for relation in &self.relations {
+            let arrow = match relation.kind {
+                ConceptRelationKind::SharedL1 => "-.-",
+                ConceptRelationKind::Causal => mermaid_arrow(relation.weight),
+            };
+            let _ = writeln!(
+                out,
+                "  L2_{} {}|{:?} {:.2}| L2_{}",
+                relation.from.0, arrow, relation.kind, relation.weight, relation.to.0
+            );
+            link_styles.push((edge_index, pen_width(relation.weight)));
+            edge_index += 1;
+        }
+        for (index, width) in link_styles {
+            let _ = writeln!(out, "  linkStyle {index} stroke-width:{width:.1}px");
+        }
+        out
+    }
+
+    /// A member of `concept` to attach a relation edge to: a shared L1 for
+    /// `SharedL1`, otherwise the first member.
+    fn anchor(&self, concept: ConceptId, relation: &ConceptRelation) -> Option<L1Id> {
+        let cluster = self.clusters.iter().find(|c| c.id == concept)?;
+        if relation.kind == ConceptRelationKind::SharedL1 {
+            let other = if concept == relation.from {
+                relation.to
+            } else {
+                relation.from
+            };
+            if let Some(other) = self.clusters.iter().find(|c| c.id == other)
+                && let Some(shared) = cluster
+                    .members
+                    .iter()
+                    .find(|m| other.members.binary_search(m).is_ok())
+            {
+                return Some(*shared);
+            }
+        }
+        cluster.members.first().copied()
+    }
+
+    fn labels(&self) -> NodeLabels<'_> {
+        NodeLabels(
+            self.l1_nodes
+                .iter()
+                .map(|node| (node.id, node))
+                .collect::<BTreeMap<_, _>>(),
+        )
+    }
+}
+
+struct NodeLabels<'a>(BTreeMap<L1Id, &'a L1Node>);
+
+impl NodeLabels<'_> {
+    fn node(&self, id: L1Id) -> String {
+        match self.0.get(&id) {
+            Some(node) => format!("L1-{} {:?}: {}", id.0, node.role, node.label),
+            None => format!("L1-{}", id.0),
+        }
+    }
+}
+
+fn relations(clusters: &[ConceptCluster]) -> Vec<ConceptRelation> {
+    let mut owners = BTreeMap::<L1Id, Vec<ConceptId>>::new();
+    for cluster in clusters {
+        for member in &cluster.members {
+            owners.entry(*member).or_default().push(cluster.id);
+        }
+    }
+
+    let mut out = Vec::new();
+    for (i, a) in clusters.iter().enumerate() {
+        let members_a = a.members.iter().collect::<BTreeSet<_>>();
+        for b in &clusters[i + 1..] {
+            let shared = b.members.iter().filter(|m| members_a.contains(m)).count();
+            if shared == 0 {
+                continue;
+            }
+            let union = a.members.len() + b.members.len() - shared;
+            out.push(ConceptRelation {
+                from: a.id,
+                to: b.id,
+                kind: ConceptRelationKind::SharedL1,
+                weight: shared as f64 / union as f64,
+            });
+        }
+    }
+
+    let mut causal = BTreeMap::<(ConceptId, ConceptId), f64>::new();
+    for cluster in clusters {
+        for link in &cluster.causal_links {
+            for target in owners.get(&link.to).into_iter().flatten() {
+                if *target == cluster.id {
+                    continue;
+                }
+                let weight = causal.entry((cluster.id, *target)).or_insert(0.0);
+                *weight = weight.max(link.weight);
+            }
+        }
+    }
+    out.extend(
+        causal
+            .into_iter()
+            .map(|((from, to), weight)| ConceptRelation {
+                from,
+                to,
+                kind: ConceptRelationKind::Causal,
+                weight,
+            }),
+    );
+    out
+}
+
+fn mermaid_arrow(weight: f64) -> &'static str {
+    if weight >= STRONG_WEIGHT {
+        "==>"
+    } else if weight < WEAK_WEIGHT {
+        "-.->"
+    } else {
+        "-->"
+    }
+}
+
+fn pen_width(weight: f64) -> f64 {
+    1.0 + 3.0 * weight.clamp(0.0, 1.0)
+}
+
+fn truncate_label(text: &str) -> String {
+    let trimmed = text.trim();
+    if trimmed.chars().count() <= LABEL_CHARS {
+        return trimmed.to_string();
+    }
+    let mut out = trimmed.chars().take(LABEL_CHARS - 1).collect::<String>();
+    out.push('…');
+    out
+}
+
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('"', "'")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn l1(id: u128, text: &str) -> SemanticUnitL1 {
+        SemanticUnitL1 {
+            id: L1Id(id),
+            role: RequirementRole::Goal,
+            polarity: 1,
+            abstraction: 0.5,
+            vector: vec![1.0; semantic_dhm::D_SEM],
+            source_text: text.to_string(),
+            role_confidence: 1.0,
+            abstraction_confidence: 1.0,
+        }
+    }
+
+    fn concept(id: u64, refs: &[u128]) -> ConceptUnit {
+        ConceptUnit {
+            id: ConceptId(id),
+            l1_refs: refs.iter().map(|r| L1Id(*r)).collect(),
+            integrated_vector: vec![1.0; semantic_dhm::D_SEM],
+            a: 0.5,
+            s: vec![0.5; semantic_dhm::D_STRUCT],
+            polarity: 1,
+            timestamp: 0,
+            access: semantic_dhm::AccessControl::default(),
+        }
+    }
+
+    fn export() -> ConceptGraphExport {
+        ConceptGraphExport::build(
+            &[
+                l1(1, "高速化したい"),
+                l1(2, "クラウド\"禁止\""),
+                l1(3, "メモリ512MB以下"),
+            ],
+            &[
+                concept(10, &[1, 2]),
+                concept(11, &[2, 3]),
+                concept(12, &[1]),
+            ],
+        )
+        .expect("export")
+    }
+
+    #[test]
+    fn relations_come_from_shared_refs_and_crossing_links() {
+        let export = export();
+        assert!(
+            export
+                .clusters
+                .iter()
+                .all(|c| c.causal_links.iter().all(|l| l.from != l.to))
+        );
+        let shared = export
+            .relations
+            .iter()
+            .filter(|r| r.kind == ConceptRelationKind::SharedL1)
+            .map(|r| (r.from.0, r.to.0, r.weight))
+            .collect::<Vec<_>>();
+        assert_eq!(shared, vec![(10, 11, 1.0 / 3.0), (10, 12, 0.5)]);
+        // L2-10 links 1 -> 2, and L1 2 also belongs to L2-11.
+        assert!(export.relations.iter().any(|r| {
+            r.kind == ConceptRelationKind::Causal
+                && r.from == ConceptId(10)
+                && r.to == ConceptId(11)
+        }));
+    }
+
+    #[test]
+    fn renderers_emit_clusters_without_concept_self_loops() {
+        let export = export();
+        let mermaid = export.to_mermaid();
+        assert!(mermaid.contains("subgraph L2_10"));
+        assert!(mermaid.contains("c10_l1_1 ==>|1.00| c10_l1_2"));
+        assert!(mermaid.contains("L2_10 -.-|SharedL1 0.33| L2_11"));
+        assert!(!mermaid.contains("L2_10 -->|L1"));
+        assert!(mermaid.contains("linkStyle 0 stroke-width:4.0px"));
+
+        let dot = export.to_dot();
+        assert!(dot.starts_with("digraph concept_graph {"));
+        assert!(dot.contains("subgraph cluster_l2_11"));
+        assert!(dot.contains("クラウド'禁止'"));
+        assert!(dot.contains("lhead=cluster_l2_11"));
+        assert_eq!(export.artifact(GraphExportFormat::Dot).content, dot);
+    }
+}
@@ -0,0 +1,499 @@
+//! Grounding search across pluggable knowledge sources.
+//!
+//! The built-in source is the VM's knowledge store; anything else, such as
+//! a web or API lookup, is a `GroundingProvider` registered with its own
+//! `ProviderLimits`. A provider with a timeout runs on a worker thread, so
+//! a slow or blocking one cannot stall the VM: past the deadline its
+//! `CancelToken` is cancelled and its eventual answer dropped. Calls over a
+//! provider's rate are skipped rather than queued. Results are cached per
+//! (concept, query) so repeating a search never hits a provider twice.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+use core_types::clock::Stopwatch;
+use knowledge_store::KnowledgeStore;
+use semantic_dhm::ConceptId;
+
+/// How often a thread waiting on a provider looks at the caller's token.
+const CANCEL_POLL: Duration = Duration::from_millis(10);
+
+/// Shared flag a provider should check between steps of a long lookup.
+#[derive(Clone, Debug, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroundingRequest {
+    pub concept_id: ConceptId,
+    /// Trimmed search text.
+    pub query: String,
+    /// Results wanted from each provider.
+    pub limit: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GroundingError {
+    EmptyQuery,
+    Cancelled,
+    TimedOut {
+        provider: String,
+        after: Duration,
+    },
+    RateLimited {
+        provider: String,
+        /// Until the oldest call in the window expires.
+        retry_after: Duration,
+    },
+    Provider {
+        provider: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for GroundingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::EmptyQuery => write!(f, "query is empty"),
+            Self::Cancelled => write!(f, "grounding search cancelled"),
+            Self::TimedOut { provider, after } => {
+                write!(f, "provider {provider} timed out after {after:?}")
+            }
+            Self::RateLimited {
+                provider,
+                retry_after,
+            } => write!(f, "provider {provider} is rate limited for {retry_after:?}"),
+            Self::Provider { provider, message } => write!(f, "provider {provider}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for GroundingError {}
+
+/// A knowledge source. `search` may block; register the provider with a
+/// timeout if it can take long, and check `cancel` when it can stop early.
+pub trait GroundingProvider: Send + Sync {
+    fn name(&self) -> &str;
+
+    fn search(
+        &self,
+        request: &GroundingRequest,
+        cancel: &CancelToken,
+    ) -> Result<Vec<String>, GroundingError>;
+}
+
+/// A closure as a provider, for user-supplied web or API lookups.
+pub struct FnProvider<F> {
+    name: String,
+    search: F,
+}
+
+impl<F> FnProvider<F>
+where
+    F: Fn(&GroundingRequest, &CancelToken) -> Result<Vec<String>, GroundingError> + Send + Sync,
+{
+    pub fn new(name: impl Into<String>, search: F) -> Self {
+        Self {
+            name: name.into(),
+            search,
+        }
+    }
+}
+
+impl<F> GroundingProvider for FnProvider<F>
+where
+    F: Fn(&GroundingRequest, &CancelToken) -> Result<Vec<String>, GroundingError> + Send + Sync,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn search(
+        &self,
+        request: &GroundingRequest,
+        cancel: &CancelToken,
+    ) -> Result<Vec<String>, GroundingError> {
+        (self.search)(request, cancel)
+    }
+}
+
+/// The VM's knowledge store as a provider: the labels closest to the query.
+pub struct KnowledgeStoreProvider<'a> {
+    store: &'a KnowledgeStore,
+}
+
+impl<'a> KnowledgeStoreProvider<'a> {
+    pub const NAME: &'static str = "knowledge_store";
+
+    pub fn new(store: &'a KnowledgeStore) -> Self {
+        Self { store }
+    }
+}
+
+impl GroundingProvider for KnowledgeStoreProvider<'_> {
+    fn name(&self) -> &str {
+        Self::NAME
+    }
+
+    fn search(
+        &self,
+        request: &GroundingRequest,
+        _cancel: &CancelToken,
+    ) -> Result<Vec<String>, GroundingError> {
+        Ok(self
+            .store
+            .top_related_labels(&crate::vector_from_text(&request.query), request.limit)
+            .into_iter()
+            .map(|label| format!("Grounded reference: {label} (query={})", request.query))
+            .collect())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProviderLimits {
+    /// Calls allowed per `window`; `None` is unlimited. Without a system
+    /// clock (wasm32) the window never passes.
+    pub max_calls: Option<usize>,
+    pub window: Duration,
+    /// Runs the provider on a worker thread and gives up after this long.
+    /// `None` calls it inline.
+    pub timeout: Option<Duration>,
+}
+
+impl Default for ProviderLimits {
+    fn default() -> Self {
+        Self {
+            max_calls: None,
+            window: Duration::from_secs(60),
+            timeout: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GroundingHit {
+    pub provider: String,
+    pub text: String,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GroundingOutcome {
+    pub hits: Vec<GroundingHit>,
+    /// Served from the cache; no provider was called.
+    pub cached: bool,
+    /// Providers that were skipped or failed; their hits are missing.
+    pub failures: Vec<GroundingError>,
+}
+
+struct ProviderSlot {
+    provider: Arc<dyn GroundingProvider>,
+    limits: ProviderLimits,
+    /// Start offsets of the calls inside the current window.
+    calls: VecDeque<Duration>,
+}
+
+impl ProviderSlot {
+    /// Records a call at `now` unless the window is full.
+    fn admit(&mut self, now: Duration) -> Result<(), GroundingError> {
+        let Some(max_calls) = self.limits.max_calls else {
+            return Ok(());
+        };
+        while self
+            .calls
+            .front()
+            .is_some_and(|start| now.saturating_sub(*start) >= self.limits.window)
+        {
+            self.calls.pop_front();
+        }
+        if self.calls.len() >= max_calls {
+            let oldest = self.calls.front().copied().unwrap_or(now);
+            return Err(GroundingError::RateLimited {
+                provider: self.provider.name().to_string(),
+                retry_after: (oldest + self.limits.window).saturating_sub(now),
+            });
+        }
+        self.calls.push_back(now);
+        Ok(())
+    }
+
+    fn call(
+        &self,
+        request: &GroundingRequest,
+        cancel: &CancelToken,
+    ) -> Result<Vec<String>, GroundingError> {
+        let Some(timeout) = self.limits.timeout else {
+            return self.provider.search(request, cancel);
+        };
+        let provider = Arc::clone(&self.provider);
+        let worker_cancel = CancelToken::new();
+        let (tx, rx) = mpsc::channel();
+        {
+            let request = request.clone();
+            let worker_cancel = worker_cancel.clone();
+            std::thread::spawn(move || {
+                let _ = tx.send(provider.search(&request, &worker_cancel));
+            });
+        }
+        let watch = Stopwatch::start();
+        loop {
+            if cancel.is_cancelled() {
+                worker_cancel.cancel();
+                return Err(GroundingError::Cancelled);
+            }
+            let left = timeout.saturating_sub(watch.elapsed());
+            if left.is_zero() {
+                worker_cancel.cancel();
+                return Err(GroundingError::TimedOut {
+                    provider: self.provider.name().to_string(),
+                    after: timeout,
+                });
+            }
+            match rx.recv_timeout(left.min(CANCEL_POLL)) {
+                Ok(result) => return result,
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    return Err(GroundingError::Provider {
+                        provider: self.provider.name().to_string(),
+                        message: "provider panicked".to_string(),
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// Registered providers, their call history and the result cache.
+pub struct GroundingSearch {
+    providers: Vec<ProviderSlot>,
+    cache: BTreeMap<(ConceptId, String), Vec<GroundingHit>>,
+    clock: Stopwatch,
+}
+
+impl Default for GroundingSearch {
+    fn default() -> Self {
+        Self {
+            providers: Vec::new(),
+            cache: BTreeMap::new(),
+            clock: Stopwatch::start(),
+        }
+    }
+}
+
+impl GroundingSearch {
+    /// Adds a provider queried after those registered before it. Cached
+    /// results predate it, so the cache is cleared.
+    pub fn register(&mut self, provider: Arc<dyn GroundingProvider>, limits: ProviderLimits) {
+        self.providers.push(ProviderSlot {
+            provider,
+            limits,
+            calls: VecDeque::new(),
+        });
+        self.cache.clear();
+    }
+
+    pub fn provider_names(&self) -> Vec<&str> {
+        self.providers.iter().map(|s| s.provider.name()).collect()
+    }
+
+    pub fn cached_queries(&self) -> usize {
+        self.cache.len()
+    }
+
+    pub fn clear_cache(&mut self) {
+        self.cache.clear();
+    }
+
+    /// Queries `local` first, then every registered provider in order.
+    /// A provider that is rate limited, times out or fails is reported in
+    /// `failures` and the search goes on; only a cancelled search errs.
+    /// Outcomes with a failure are not cached, so a retry can fill them in.
+    pub fn search(
+        &mut self,
+        request: &GroundingRequest,
+        local: Option<&dyn GroundingProvider>,
+        cancel: &CancelToken,
+    ) -> Result<GroundingOutcome, GroundingError> {
+        if request.query.is_empty() {
+            return Err(GroundingError::EmptyQuery);
+        }
+        let key = (request.concept_id, request.query.clone());
+        if let Some(hits) = self.cache.get(&key) {
+            return Ok(GroundingOutcome {
+                hits: hits.clone(),
+                cached: true,
+                failures: Vec::new(),
+            });
+        }
+
+        let mut outcome = GroundingOutcome::default();
+        let mut collect = |provider: &str, result: Result<Vec<String>, GroundingError>| {
+            match result {
+                Ok(texts) => outcome
+                    .hits
+                    .extend(
+                        texts
+                            .into_iter()
+                            .take(request.limit)
+                            .map(|text| GroundingHit {
+                                provider: provider.to_string(),
+                                text,
+                            }),
+                    ),
+                Err(GroundingError::Cancelled) => return Err(GroundingError::Cancelled),
+                Err(error) => outcome.failures.push(error),
+            }
+            Ok(())
+        };
+        if let Some(local) = local {
+            if cancel.is_cancelled() {
+                return Err(GroundingError::Cancelled);
+            }
+            collect(local.name(), local.search(request, cancel))?;
+        }
+        for slot in &mut self.providers {
+            if cancel.is_cancelled() {
+                return Err(GroundingError::Cancelled);
+            }
+            let result = slot
+                .admit(self.clock.elapsed())
+                .and_then(|()| slot.call(request, cancel));
+            collect(slot.provider.name(), result)?;
+        }
+
+        if outcome.failures.is_empty() {
+            self.cache.insert(key, outcome.hits.clone());
+        }
+        Ok(outcome)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+
+    use super::*;
+
+    fn request(query: &str) -> GroundingRequest {
+        GroundingRequest {
+            concept_id: ConceptId(1),
+            query: query.to_string(),
+            limit: 2,
+        }
+    }
+
+    fn counting(name: &'static str, calls: Arc<AtomicUsize>) -> Arc<dyn GroundingProvider> {
+        Arc::new(FnProvider::new(name, move |request, _| {
+            calls.fetch_add(1, Ordering::Relaxed);
+            Ok(vec![
+                format!("{name}: {}", request.query),
+                "extra".to_string(),
+                "dropped".to_string(),
+            ])
+        }))
+    }
+
+    #[test]
+    fn results_are_cached_per_concept_and_query() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut search = GroundingSearch::default();
+        search.register(
+            counting("web", Arc::clone(&calls)),
+            ProviderLimits::default(),
+        );
+        let cancel = CancelToken::new();
+
+        let first = search
+            .search(&request("tls"), None, &cancel)
+            .expect("first");
+        assert!(!first.cached);
+        assert_eq!(first.hits.len(), 2);
+        assert_eq!(first.hits[0].text, "web: tls");
+        let again = search
+            .search(&request("tls"), None, &cancel)
+            .expect("again");
+        assert!(again.cached);
+        assert_eq!(again.hits, first.hits);
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+
+        let other = GroundingRequest {
+            concept_id: ConceptId(2),
+            ..request("tls")
+        };
+        search.search(&other, None, &cancel).expect("other concept");
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+        assert_eq!(search.cached_queries(), 2);
+        assert_eq!(
+            search.search(&request(""), None, &cancel),
+            Err(GroundingError::EmptyQuery)
+        );
+    }
+
+    #[test]
+    fn rate_limits_and_timeouts_skip_the_provider() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let mut search = GroundingSearch::default();
+        search.register(
+            counting("api", Arc::clone(&calls)),
+            ProviderLimits {
+                max_calls: Some(1),
+                window: Duration::from_secs(3600),
+                timeout: None,
+            },
+        );
+        search.register(
+            Arc::new(FnProvider::new(
+                "slow",
+                |_: &GroundingRequest, cancel: &CancelToken| {
+                    while !cancel.is_cancelled() {
+                        std::thread::sleep(Duration::from_millis(1));
+                    }
+                    Ok(vec!["late".to_string()])
+                },
+            )),
+            ProviderLimits {
+                timeout: Some(Duration::from_millis(20)),
+                ..ProviderLimits::default()
+            },
+        );
+        let cancel = CancelToken::new();
+
+        let first = search.search(&request("a"), None, &cancel).expect("first");
+        assert_eq!(first.hits.len(), 2);
+        assert!(matches!(
+            first.failures[..],
+            [GroundingError::TimedOut { ref provider, .. }] if provider == "slow"
+        ));
+        let second = search.search(&request("b"), None, &cancel).expect("second");
+        assert!(second.hits.is_empty());
+        assert!(matches!(
+            second.failures[0],
+            GroundingError::RateLimited { ref provider, .. } if provider == "api"
+        ));
+        assert_eq!(calls.load(Ordering::Relaxed), 1);
+        // Partial outcomes are not cached.
+        assert_eq!(search.cached_queries(), 0);
+
+        cancel.cancel();
+        assert_eq!(
+            search.search(&request("c"), None, &cancel),
+            Err(GroundingError::Cancelled)
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! Per-state `GraphAggregates` cache behind
+//! `StructuralEvaluator::evaluate_child`.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use memory_space::{DesignState, GraphAggregates, GraphDiff, StateId, StructuralGraph};
+
+pub(crate) const DEFAULT_CACHE_CAPACITY: usize = 1024;
+
+/// Diffs touching more nodes and edges than this share of the child's node
+/// count are cheaper to recompute than to replay.
+const MAX_DIFF_SHARE: f64 = 0.25;
+
+type Entry = (Arc<StructuralGraph>, Arc<GraphAggregates>);
+
+#[derive(Debug)]
+struct CacheState {
+    entries: BTreeMap<StateId, Entry>,
+    /// Insertion order, oldest first, for eviction.
+    order: VecDeque<StateId>,
+    capacity: usize,
+}
+
+/// Shared between clones of the evaluator. Entries are keyed by state id but
+/// only served while the state still points at the cached graph.
+#[derive(Clone, Debug)]
+pub(crate) struct AggregateCache {
+    state: Arc<Mutex<CacheState>>,
+}
+
+impl AggregateCache {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            state: Arc::new(Mutex::new(CacheState {
+                entries: BTreeMap::new(),
+                order: VecDeque::new(),
+                capacity,
+            })),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.lock().capacity > 0
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.lock().entries.len()
+    }
+
+    /// Drops every entry; returns how many there were.
+    pub(crate) fn clear(&self) -> usize {
+        let mut cache = self.lock();
+        cache.order.clear();
+        let dropped = cache.entries.len();
+        cache.entries.clear();
+        dropped
+    }
+
+    pub(crate) fn get(&self, state: &DesignState) -> Option<Arc<GraphAggregates>> {
+        self.lock()
+            .entries
+            .get(&state.id)
+            .filter(|(graph, _)| Arc::ptr_eq(graph, &state.graph))
+            .map(|(_, aggregates)| Arc::clone(aggregates))
+    }
+
+    /// Aggregates for `child`, replayed from `parent`'s when the graph diff
+    /// between them is small. Both states end up cached.
+    pub(crate) fn for_child(
+        &self,
+        parent: &DesignState,
+        child: &DesignState,
+    ) -> Arc<GraphAggregates> {
+        if let Some(hit) = self.get(child) {
+            return hit;
+        }
+        let aggregates = if Arc::ptr_eq(&parent.graph, &child.graph) {
+            self.for_state(parent)
+        } else {
+            let diff = GraphDiff::between(&parent.graph, &child.graph);
+            if diff_size(&diff) as f64 > MAX_DIFF_SHARE * child.graph.nodes().len() as f64 {
+                Arc::new(GraphAggregates::from_graph(&child.graph))
+            } else {
+                let mut next = (*self.for_state(parent)).clone();
+                next.apply_diff(&diff, &child.graph);
+                Arc::new(next)
+            }
+        };
+        self.insert(child, Arc::clone(&aggregates));
+        aggregates
+    }
+
+    fn for_state(&self, state: &DesignState) -> Arc<GraphAggregates> {
+        if let Some(hit) = self.get(state) {
+            return hit;
+        }
+        let aggregates = Arc::new(GraphAggregates::from_graph(&state.graph));
+        self.insert(state, Arc::clone(&aggregates));
+        aggregates
+    }
+
+    fn insert(&self, state: &DesignState, aggregates: Arc<GraphAggregates>) {
+        let mut cache = self.lock();
+        if cache.capacity == 0 {
+            return;
+        }
+        let previous = cache
+            .entries
+            .insert(state.id, (Arc::clone(&state.graph), aggregates));
+        if previous.is_none() {
+            cache.order.push_back(state.id);
+        }
+        while cache.entries.len() > cache.capacity {
+            let Some(oldest) = cache.order.pop_front() else {
+                break;
+            };
+            cache.entries.remove(&oldest);
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CacheState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn diff_size(diff: &GraphDiff) -> usize {
+    diff.added_nodes.len()
+        + diff.removed_nodes.len()
+        + diff.changed_nodes.len()
+        + diff.added_edges.len()
+        + diff.removed_edges.len()
+}
@@ -0,0 +1,387 @@
+//! Interpretation of memory interference across a VM session: how often
+//! recall changed an evaluated objective, by depth, and whether the size of
+//! those adjustments is drifting. Thresholds crossing into alerts are kept in
+//! a session log so callers see each alert once, when it was raised.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use core_types::ObjectiveVector;
+use memory_space::MemoryInterferenceTelemetry;
+
+/// Adjustments smaller than this (L2 over the four objectives) are treated
+/// as no adjustment.
+const ADJUSTMENT_EPS: f64 = 1e-12;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct InterferenceAlertConfig {
+    /// Largest tolerated share of adjusted evaluations beyond `deep_depth`.
+    pub max_adjustment_rate: f64,
+    /// Evaluations at depths strictly greater than this count as deep.
+    pub deep_depth: usize,
+    /// Deep evaluations needed before the rate is judged.
+    pub min_evaluations: usize,
+    /// Largest tolerated change of the mean adjustment norm between the
+    /// latest telemetry batch and all earlier ones.
+    pub max_delta_drift: f64,
+}
+
+impl Default for InterferenceAlertConfig {
+    fn default() -> Self {
+        Self {
+            max_adjustment_rate: 0.2,
+            deep_depth: 5,
+            min_evaluations: 20,
+            max_delta_drift: 0.1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct DepthInterference {
+    pub evaluations: u64,
+    pub adjusted: u64,
+    pub sum_adjustment_norm: f64,
+}
+
+impl DepthInterference {
+    pub fn adjustment_rate(&self) -> f64 {
+        if self.evaluations == 0 {
+            0.0
+        } else {
+            self.adjusted as f64 / self.evaluations as f64
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InterferenceAlertKind {
+    AdjustmentRate {
+        deep_depth: usize,
+        rate: f64,
+        threshold: f64,
+        evaluations: u64,
+    },
+    DeltaDrift {
+        drift: f64,
+        threshold: f64,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct InterferenceAlert {
+    pub kind: InterferenceAlertKind,
+    /// Evaluations recorded when the alert was raised.
+    pub raised_after: u64,
+}
+
+impl fmt::Display for InterferenceAlert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            InterferenceAlertKind::AdjustmentRate {
+                deep_depth,
+                rate,
+                threshold,
+                evaluations,
+            } => write!(
+                f,
+                "recall adjustments exceed {:.0}% of evaluations at depth > {deep_depth} ({:.1}% of {evaluations})",
+                threshold * 100.0,
+                rate * 100.0
+            ),
+            InterferenceAlertKind::DeltaDrift { drift, threshold } => write!(
+                f,
+                "mean memory delta drifted by {drift:+.4} (threshold {threshold:.4})"
+            ),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InterferenceReport {
+    pub evaluations: u64,
+    pub adjusted: u64,
+    /// Share of all evaluations whose objective recall changed.
+    pub interference_rate: f64,
+    pub per_depth: BTreeMap<usize, DepthInterference>,
+    /// Sample-weighted means over every telemetry batch taken.
+    pub avg_tau_mem: f64,
+    pub avg_delta_norm: f64,
+    pub memory_hit_rate: f64,
+    /// Latest batch's `avg_delta_norm` minus the mean of earlier batches; 0
+    /// until two batches have been seen.
+    pub delta_drift: f64,
+    /// Alerts whose condition currently holds.
+    pub alerts: Vec<InterferenceAlert>,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct TelemetrySums {
+    samples: usize,
+    tau: f64,
+    delta: f64,
+    hit_rate: f64,
+}
+
+impl TelemetrySums {
+    fn add(&mut self, batch: &MemoryInterferenceTelemetry) {
+        let n = batch.samples as f64;
+        self.samples += batch.samples;
+        self.tau += batch.avg_tau_mem * n;
+        self.delta += batch.avg_delta_norm * n;
+        self.hit_rate += batch.memory_hit_rate * n;
+    }
+
+    fn mean(&self, sum: f64) -> f64 {
+        if self.samples == 0 {
+            0.0
+        } else {
+            sum / self.samples as f64
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct InterferenceMonitor {
+    config: InterferenceAlertConfig,
+    per_depth: BTreeMap<usize, DepthInterference>,
+    telemetry: TelemetrySums,
+    delta_drift: f64,
+    /// Alerts raised so far, oldest first.
+    log: Vec<InterferenceAlert>,
+    rate_alert_active: bool,
+    drift_alert_active: bool,
+}
+
+impl InterferenceMonitor {
+    pub fn new(config: InterferenceAlertConfig) -> Self {
+        Self {
+            config,
+            ..Self::default()
+        }
+    }
+
+    pub fn config(&self) -> InterferenceAlertConfig {
+        self.config
+    }
+
+    pub fn set_config(&mut self, config: InterferenceAlertConfig) {
+        self.config = config;
+        self.check();
+    }
+
+    /// Records one evaluation: the objective before and after recall.
+    pub fn record(&mut self, depth: usize, base: &ObjectiveVector, adjusted: &ObjectiveVector) {
+        let norm = adjustment_norm(base, adjusted);
+        let entry = self.per_depth.entry(depth).or_default();
+        entry.evaluations += 1;
+        if norm > ADJUSTMENT_EPS {
+            entry.adjusted += 1;
+            entry.sum_adjustment_norm += norm;
+        }
+        self.check();
+    }
+
+    /// Folds in a batch drained from the memory space. Empty batches are
+    /// ignored.
+    pub fn observe_telemetry(&mut self, batch: &MemoryInterferenceTelemetry) {
+        if batch.samples == 0 {
+            return;
+        }
+        if self.telemetry.samples > 0 {
+            self.delta_drift = batch.avg_delta_norm - self.telemetry.mean(self.telemetry.delta);
+        }
+        self.telemetry.add(batch);
+        self.check();
+    }
+
+    pub fn report(&self) -> InterferenceReport {
+        let (evaluations, adjusted) = self.totals();
+        InterferenceReport {
+            evaluations,
+            adjusted,
+            interference_rate: if evaluations == 0 {
+                0.0
+            } else {
+                adjusted as f64 / evaluations as f64
+            },
+            per_depth: self.per_depth.clone(),
+            avg_tau_mem: self.telemetry.mean(self.telemetry.tau),
+            avg_delta_norm: self.telemetry.mean(self.telemetry.delta),
+            memory_hit_rate: self.telemetry.mean(self.telemetry.hit_rate),
+            delta_drift: self.delta_drift,
+            alerts: self.active_alerts(),
+        }
+    }
+
+    /// Every alert raised this session, oldest first. An alert is raised
+    /// again only after its condition has cleared in between.
+    pub fn log(&self) -> &[InterferenceAlert] {
+        &self.log
+    }
+
+    pub fn take_log(&mut self) -> Vec<InterferenceAlert> {
+        std::mem::take(&mut self.log)
+    }
+
+    fn totals(&self) -> (u64, u64) {
+        self.per_depth
+            .values()
+            .fold((0, 0), |(e, a), d| (e + d.evaluations, a + d.adjusted))
+    }
+
+    fn deep(&self) -> DepthInterference {
+        self.per_depth
+            .range(self.config.deep_depth.saturating_add(1)..)
+            .fold(DepthInterference::default(), |mut acc, (_, d)| {
+                acc.evaluations += d.evaluations;
+                acc.adjusted += d.adjusted;
+                acc.sum_adjustment_norm += d.sum_adjustment_norm;
+                acc
+            })
+    }
+
+    fn rate_alert(&self) -> Option<InterferenceAlertKind> {
+        let deep = self.deep();
+        let rate = deep.adjustment_rate();
+        (deep.evaluations >= self.config.min_evaluations as u64
+            && rate > self.config.max_adjustment_rate)
+            .then_some(InterferenceAlertKind::AdjustmentRate {
+                deep_depth: self.config.deep_depth,
+                rate,
+                threshold: self.config.max_adjustment_rate,
+                evaluations: deep.evaluations,
+            })
+    }
+
+    fn drift_alert(&self) -> Option<InterferenceAlertKind> {
+        (self.delta_drift.abs() > self.config.max_delta_drift).then_some(
+            InterferenceAlertKind::DeltaDrift {
+                drift: self.delta_drift,
+                threshold: self.config.max_delta_drift,
+            },
+        )
+    }
+
+    fn active_alerts(&self) -> Vec<InterferenceAlert> {
+        let raised_after = self.totals().0;
+        [self.rate_alert(), self.drift_alert()]
+            .into_iter()
+            .flatten()
+            .map(|kind| InterferenceAlert { kind, raised_after })
+            .collect()
+    }
+
+    fn check(&mut self) {
+        let raised_after = self.totals().0;
+        let rate = self.rate_alert();
+        if let Some(kind) = rate
+            && !self.rate_alert_active
+        {
+            self.log.push(InterferenceAlert { kind, raised_after });
+        }
+        self.rate_alert_active = rate.is_some();
+
+        let drift = self.drift_alert();
+        if let Some(kind) = drift
+            && !self.drift_alert_active
+        {
+            self.log.push(InterferenceAlert { kind, raised_after });
+        }
+        self.drift_alert_active = drift.is_some();
+    }
+}
+
+fn adjustment_norm(base: &ObjectiveVector, adjusted: &ObjectiveVector) -> f64 {
+    [
+        adjusted.f_struct - base.f_struct,
+        adjusted.f_field - base.f_field,
+        adjusted.f_risk - base.f_risk,
+        adjusted.f_shape - base.f_shape,
+    ]
+    .iter()
+    .map(|d| d * d)
+    .sum::<f64>()
+    .sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn objective(v: f64) -> ObjectiveVector {
+        ObjectiveVector {
+            f_struct: v,
+            f_field: v,
+            f_risk: v,
+            f_shape: v,
+        }
+    }
+
+    #[test]
+    fn deep_adjustment_rate_raises_one_alert_until_it_clears() {
+        let mut monitor = InterferenceMonitor::new(InterferenceAlertConfig {
+            min_evaluations: 10,
+            ..InterferenceAlertConfig::default()
+        });
+        // Shallow adjustments never count toward the deep rate.
+        for _ in 0..10 {
+            monitor.record(2, &objective(0.5), &objective(0.6));
+        }
+        for i in 0..10 {
+            let after = if i < 3 { 0.6 } else { 0.5 };
+            monitor.record(6, &objective(0.5), &objective(after));
+        }
+        let report = monitor.report();
+        assert_eq!(report.evaluations, 20);
+        assert_eq!(report.adjusted, 13);
+        assert_eq!(report.per_depth[&6].adjusted, 3);
+        assert_eq!(monitor.log().len(), 1);
+        assert_eq!(
+            monitor.log()[0].to_string(),
+            "recall adjustments exceed 20% of evaluations at depth > 5 (30.0% of 10)"
+        );
+
+        // Still above the threshold: no second alert.
+        monitor.record(7, &objective(0.5), &objective(0.7));
+        assert_eq!(monitor.log().len(), 1);
+
+        // Clears, then crosses again.
+        for _ in 0..20 {
+            monitor.record(7, &objective(0.5), &objective(0.5));
+        }
+        assert!(monitor.report().alerts.is_empty());
+        for _ in 0..10 {
+            monitor.record(7, &objective(0.5), &objective(0.9));
+        }
+        assert_eq!(monitor.take_log().len(), 2);
+        assert!(monitor.log().is_empty());
+    }
+
+    #[test]
+    fn telemetry_batches_are_sample_weighted_and_drift_is_flagged() {
+        let mut monitor = InterferenceMonitor::default();
+        let batch = |delta: f64, samples: usize| MemoryInterferenceTelemetry {
+            avg_tau_mem: 1.0,
+            avg_delta_norm: delta,
+            memory_hit_rate: 0.5,
+            samples,
+        };
+        monitor.observe_telemetry(&batch(0.1, 30));
+        monitor.observe_telemetry(&batch(0.0, 0));
+        monitor.observe_telemetry(&batch(0.15, 10));
+        let report = monitor.report();
+        assert!((report.avg_delta_norm - 0.1125).abs() < 1e-12);
+        assert!((report.delta_drift - 0.05).abs() < 1e-12);
+        assert!(report.alerts.is_empty());
+
+        monitor.observe_telemetry(&batch(0.4, 10));
+        let report = monitor.report();
+        assert_eq!(report.alerts.len(), 1);
+        assert!(matches!(
+            report.alerts[0].kind,
+            InterferenceAlertKind::DeltaDrift { .. }
+        ));
+        assert_eq!(monitor.log().len(), 1);
+    }
+}
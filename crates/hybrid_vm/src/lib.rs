@@ -1,8 +1,10 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::io;
 use std::path::Path;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::Arc;
 
+use core_types::clock::{self, Stopwatch};
+use core_types::progress::ProgressSink;
 use core_types::{
     ChangeFrontier, ClassNode, Constraint, DependencyEdge, DependencyGraph, DesignHierarchy,
     DesignIR, DesignIntent, DesignUnit, NumericIR, NumericResult, ObjectiveKind, ObjectiveVector,
@@ -15,37 +17,151 @@ use dhm::Dhm;
 use field_engine::{FieldEngine, TargetField};
 use knowledge_store::KnowledgeStore;
 use language_dhm::{LangId, LanguageDhm, LanguageUnit};
-use memory_space::{DesignState, MemoryInterferenceTelemetry};
-use memory_store::{FileStore, InMemoryStore};
-use recomposer::{DecisionReport, DesignReport, Recomposer, ResonanceReport};
+use memory_space::{
+    DesignNode, DesignState, GraphAggregates, GraphMetrics, MemoryInterferenceTelemetry,
+    StructuralGraph, Value,
+};
+use memory_store::{BackedStore, FileStore, InMemoryStore};
+use recomposer::{DesignReport, Recomposer, ResonanceReport};
 use semantic_dhm::{ConceptUnit, SemanticDhm, SemanticL1Dhm, SemanticUnitL1};
 
+pub mod artifact_regeneration;
+pub mod artifact_validation;
+pub mod attribution;
+pub mod change_plan;
+pub mod concept_graph;
+pub mod context_window;
+pub mod graph_export;
+pub mod grounding;
+mod incremental;
+pub mod interference;
+pub mod memory_usage;
+pub mod metrics;
+pub mod mode_policy;
 mod ops;
+pub mod profiles;
+pub mod provenance;
+pub mod requirement_import;
+pub mod resonance_fit;
+pub mod resource_budget;
+pub mod review_checklist;
 pub mod semantic;
+pub mod semantic_trace;
+pub mod stats;
+pub mod test_scaffold;
+pub mod timeline;
+pub mod workspace;
 
 use serde::{Deserialize, Serialize};
 
+use context_window::ContextWindow;
+use incremental::AggregateCache;
+
+pub use artifact_regeneration::{
+    ArtifactChange, ArtifactManifest, ArtifactRegeneration, ManifestEntry, RegenerationReason,
+};
+pub use artifact_validation::{
+    ArtifactCheck, ArtifactValidationReport, ArtifactValidator, ValidationStatus,
+};
+pub use attribution::{Contribution, ObjectiveAttribution};
+pub use change_plan::{ChangePlan, ChangeStep, EffortLevel};
 pub use chm::Chm;
+pub use concept_graph::{
+    ConceptGraph, ConceptGraphBuilder, ConfidenceLevel, NodeOrigin, NodeSource,
+    RequirementSatisfaction, RequirementSatisfactionReport, annotate_state_with_requirements,
+    annotate_state_with_requirements_using,
+};
+pub use context_window::{ContextScope, L1ContextState};
 pub use core_types::{
     DesignCompiler, LayerKind, NumericEvaluator, NumericLowering, SemanticLowering,
     lower_design_to_numeric,
 };
-pub use design_reasoning::{DesignHypothesis, Explanation, MeaningLayerSnapshotV2, SnapshotDiffV2};
+pub use design_reasoning::{
+    BudgetCheck, DesignHypothesis, Explanation, MeaningLayerSnapshotV2, SnapshotDiffV2,
+    SnapshotFieldChange,
+};
+pub use graph_export::{
+    ConceptCluster, ConceptGraphExport, ConceptRelation, ConceptRelationKind, GraphExportFormat,
+    L1Node,
+};
+pub use grounding::{
+    CancelToken, FnProvider, GroundingError, GroundingHit, GroundingOutcome, GroundingProvider,
+    GroundingRequest, GroundingSearch, KnowledgeStoreProvider, ProviderLimits,
+};
+pub use interference::{
+    InterferenceAlert, InterferenceAlertConfig, InterferenceAlertKind, InterferenceMonitor,
+    InterferenceReport,
+};
 pub use knowledge_store::{FeedbackAction, FeedbackEntry};
-pub use recomposer::{ActionType, DecisionWeights, Recommendation};
+pub use language_dhm::DedupReport;
+pub use memory_usage::{
+    ComponentUsage, MemoryComponent, MemoryLimitExceeded, MemoryLimits, MemoryReport,
+};
+pub use metrics::MetricsRegistry;
+pub use mode_policy::{
+    AdaptiveModeConfig, AdaptiveModePolicy, ModeObservation, ModePolicy, ModeSwitch,
+};
+pub use profiles::{ProfileApplication, ProfileStore, UserProfile};
+pub use provenance::{DocumentIngestReport, INLINE_DOCUMENT_ID, L1Provenance};
+pub use recomposer::{ActionType, DecisionReport, DecisionWeights, Recommendation};
+pub use requirement_import::{
+    ImportedRequirement, RequirementFormat, RequirementImportError, RequirementImportReport,
+    RequirementRecord,
+};
+pub use resonance_fit::{
+    LabeledPair, RecallQuality, WeightFitConfig, WeightFitReport, fit_resonance_weights,
+};
+pub use resource_budget::{Resource, ResourceBudget, ResourceCheck, ResourceUsage};
+pub use review_checklist::{
+    ReviewChecklist, ReviewEntity, ReviewItem, ReviewSection, ReviewSeverity,
+};
 pub use semantic::ranking::{
     ObjectiveCase as SemanticObjectiveCase, RankedCase, rank_frontier_by_human_coherence,
 };
 pub use semantic_dhm::{
-    ConceptId, ConceptUnitV2, DerivedRequirement, DesignProjection, L1Id, L2Config, L2Mode,
-    MeaningLayerSnapshot, RequirementKind, RequirementRole as L1RequirementRole, SemanticError,
-    SemanticUnitL1Framework, SemanticUnitL1Input, SemanticUnitL1V2, SemanticUnitL2Detail,
-    Snapshotable,
+    AccessControl, Actor, CausalEdge, ClusteringComparisonReport, ClusteringStrategyKind,
+    ConceptId, ConceptQuery, ConceptUnitV2, DenialReason, DerivedRequirement, DesignProjection,
+    L1Id, L2Change, L2ChangeReason, L2ChangeSet, L2Config, L2Mode, MeaningLayerSnapshot,
+    PermissionDenied, RecallFilter, RequirementKind, RequirementPriority,
+    RequirementRole as L1RequirementRole, ResonanceWeights, SemanticError, SemanticUnitL1Framework,
+    SemanticUnitL1Input, SemanticUnitL1V2, SemanticUnitL2Detail, Snapshotable,
+};
+pub use semantic_trace::{CorrelationId, SemanticOperation, SemanticTraceEvent};
+pub use shm::{
+    AttributePredicate, DesignRule, EdgePattern, EffectVector, LintSeverity, Precondition,
+    PreconditionError, RuleCategory, RuleConflict, RuleId, RulePack, RuleSetLint, Shm,
+    Transformation,
+};
+pub use stats::{
+    FeedbackTotals, Histogram, L1Stats, L2Stats, LastModified, RoleCounts, StatusCounts,
+    WorkspaceStats,
 };
-pub use shm::{DesignRule, EffectVector, RuleCategory, RuleId, Shm, Transformation};
+pub use test_scaffold::{
+    ConstraintBound, QuantitativeConstraint, RustTestTemplate, quantitative_constraints,
+};
+pub use timeline::{
+    ConceptTimeline, TimelineEvent, TimelineEventKind, TimelinePoint, concept_timeline,
+    concept_timelines,
+};
+pub use workspace::WorkspaceManager;
 
 pub trait Evaluator {
     fn evaluate(&self, state: &DesignState) -> ObjectiveVector;
+
+    /// Evaluates `child`, derived from `parent` by a small edit such as one
+    /// rule application. Implementations may reuse work done for `parent`;
+    /// the default evaluates `child` from scratch.
+    fn evaluate_child(&self, parent: &DesignState, child: &DesignState) -> ObjectiveVector {
+        let _ = parent;
+        self.evaluate(child)
+    }
+
+    /// Named contributions behind `evaluate(state)`; `None` when the
+    /// evaluator cannot break its score down.
+    fn explain(&self, state: &DesignState) -> Option<ObjectiveAttribution> {
+        let _ = state;
+        None
+    }
 }
 
 #[derive(Clone, Debug, Default)]
@@ -244,12 +360,9 @@ pub struct ExecutionContext {
 
 impl ExecutionContext {
     pub fn new(mode: ExecutionMode, depth: usize) -> Self {
-        let nanos = SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .map(|d| d.as_nanos() as u64)
-            .unwrap_or(0);
+        let nanos = clock::unix_time_nanos() as u64;
         Self {
-            request_id: nanos ^ (std::process::id() as u64),
+            request_id: nanos ^ ops::util::process_salt(),
             mode,
             depth,
         }
@@ -262,16 +375,25 @@ pub struct HybridTraceRow {
     pub depth: usize,
     pub mode: ExecutionMode,
     pub objective: ObjectiveVector,
+    /// Set on the evaluation after which the mode policy switched modes.
+    pub mode_switch: Option<ModeSwitch>,
+    /// Position among this VM's trace entries, shared with
+    /// `SemanticTraceEvent::seq`.
+    pub seq: u64,
+    pub correlation_id: Option<CorrelationId>,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct ConceptImpact {
+    #[cfg_attr(feature = "schema", schemars(with = "u64"))]
     pub concept_id: ConceptId,
     pub original_stability: f64,
     pub simulated_stability: f64,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct SimulationReport {
     pub original_objectives: ObjectiveVector,
     pub simulated_objectives: ObjectiveVector,
@@ -279,6 +401,11 @@ pub struct SimulationReport {
     pub total_concepts: usize,
 }
 
+impl core_types::SchemaVersioned for SimulationReport {
+    const KIND: &'static str = "simulation_report";
+    const VERSION: u32 = 1;
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct BlastRadiusScore {
     pub coverage: f64,
@@ -293,6 +420,7 @@ pub enum InfoCategory {
     Boundary,
     Metric,
     Objective,
+    Classification,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -301,6 +429,23 @@ pub struct MissingInfo {
     pub category: InfoCategory,
     pub prompt: String,
     pub importance: f64,
+    /// Where the target L1 unit was read from, when known; the prompt cites
+    /// it as well.
+    pub source: Option<L1Provenance>,
+    /// The L2 concept an `Objective` prompt asks to prioritize.
+    pub concept_id: Option<ConceptId>,
+}
+
+/// Outcome of `HybridVM::record_prioritization` for one conflicting L2
+/// concept.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PrioritizationDecision {
+    pub concept_id: ConceptId,
+    /// Requirement kind to optimize first.
+    pub favored: RequirementKind,
+    /// Tags written to the concept's L1 units: `Must` for the favored kind,
+    /// `Could` for the rest.
+    pub applied: Vec<(L1Id, RequirementPriority)>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -313,19 +458,141 @@ pub struct DesignDraft {
     pub added_units: Vec<SemanticUnitL1V2>,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ArtifactFormat {
     Rust,
     Sql,
     Mermaid,
+    /// `#[test]` scaffolds per derived requirement; see `test_scaffold`.
+    RustTests,
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub struct GeneratedArtifact {
     pub file_name: String,
     pub content: String,
 }
 
+/// L1 text behind each concept, for templates that quote requirements.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ArtifactSources {
+    l1_texts: BTreeMap<ConceptId, Vec<(L1Id, String)>>,
+}
+
+impl ArtifactSources {
+    pub fn insert(&mut self, concept: ConceptId, l1: L1Id, text: impl Into<String>) {
+        self.l1_texts
+            .entry(concept)
+            .or_default()
+            .push((l1, text.into()));
+    }
+
+    /// Source texts of `concept`'s L1 units, in L1 order.
+    pub fn l1_texts(&self, concept: ConceptId) -> &[(L1Id, String)] {
+        self.l1_texts.get(&concept).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// Renders artifacts for one format from the projected L2 concepts.
+/// Closures `Fn(&[ConceptUnitV2]) -> Vec<GeneratedArtifact>` implement this directly.
+pub trait ArtifactTemplate: Send + Sync {
+    fn render(&self, l2_units: &[ConceptUnitV2]) -> Vec<GeneratedArtifact>;
+
+    /// What `HybridVM::generate_artifacts` calls. The default ignores
+    /// `sources` and renders from the concepts alone.
+    fn render_with_sources(
+        &self,
+        l2_units: &[ConceptUnitV2],
+        sources: &ArtifactSources,
+    ) -> Vec<GeneratedArtifact> {
+        let _ = sources;
+        self.render(l2_units)
+    }
+}
+
+impl<F> ArtifactTemplate for F
+where
+    F: Fn(&[ConceptUnitV2]) -> Vec<GeneratedArtifact> + Send + Sync,
+{
+    fn render(&self, l2_units: &[ConceptUnitV2]) -> Vec<GeneratedArtifact> {
+        self(l2_units)
+    }
+}
+
+/// Per-format templates used by `HybridVM::generate_artifacts`.
+/// `Default` reproduces the built-in RFC-012 output.
+pub struct ArtifactTemplateSet {
+    rust: Box<dyn ArtifactTemplate>,
+    sql: Box<dyn ArtifactTemplate>,
+    mermaid: Box<dyn ArtifactTemplate>,
+    rust_tests: Box<dyn ArtifactTemplate>,
+}
+
+impl Default for ArtifactTemplateSet {
+    fn default() -> Self {
+        Self {
+            rust: Box::new(generate_rust_artifacts),
+            sql: Box::new(generate_sql_artifacts),
+            mermaid: Box::new(generate_mermaid_artifacts),
+            rust_tests: Box::new(RustTestTemplate),
+        }
+    }
+}
+
+impl ArtifactTemplateSet {
+    pub fn with_template(
+        mut self,
+        format: ArtifactFormat,
+        template: impl ArtifactTemplate + 'static,
+    ) -> Self {
+        self.set_template(format, template);
+        self
+    }
+
+    pub fn set_template(
+        &mut self,
+        format: ArtifactFormat,
+        template: impl ArtifactTemplate + 'static,
+    ) {
+        let slot = match format {
+            ArtifactFormat::Rust => &mut self.rust,
+            ArtifactFormat::Sql => &mut self.sql,
+            ArtifactFormat::Mermaid => &mut self.mermaid,
+            ArtifactFormat::RustTests => &mut self.rust_tests,
+        };
+        *slot = Box::new(template);
+    }
+
+    pub fn render(
+        &self,
+        format: ArtifactFormat,
+        l2_units: &[ConceptUnitV2],
+    ) -> Vec<GeneratedArtifact> {
+        self.template(format).render(l2_units)
+    }
+
+    pub fn render_with_sources(
+        &self,
+        format: ArtifactFormat,
+        l2_units: &[ConceptUnitV2],
+        sources: &ArtifactSources,
+    ) -> Vec<GeneratedArtifact> {
+        self.template(format).render_with_sources(l2_units, sources)
+    }
+
+    fn template(&self, format: ArtifactFormat) -> &dyn ArtifactTemplate {
+        match format {
+            ArtifactFormat::Rust => self.rust.as_ref(),
+            ArtifactFormat::Sql => self.sql.as_ref(),
+            ArtifactFormat::Mermaid => self.mermaid.as_ref(),
+            ArtifactFormat::RustTests => self.rust_tests.as_ref(),
+        }
+    }
+}
+
+/// L1 units whose role was inferred below this confidence get a clarification prompt.
+pub const ROLE_CONFIDENCE_THRESHOLD: f64 = 0.55;
+
 #[derive(Clone, Debug, PartialEq)]
 struct ParetoPoint {
     idx: usize,
@@ -337,9 +604,9 @@ struct ParetoPoint {
 pub struct HybridVM {
     evaluator: StructuralEvaluator,
     dhm: Dhm,
-    language_dhm: LanguageDhm<FileStore<LangId, LanguageUnit>>,
-    semantic_dhm: SemanticDhm<FileStore<ConceptId, ConceptUnit>>,
-    semantic_l1_dhm: SemanticL1Dhm<FileStore<L1Id, SemanticUnitL1>>,
+    language_dhm: LanguageDhm<BackedStore<LangId, LanguageUnit>>,
+    semantic_dhm: SemanticDhm<BackedStore<ConceptId, ConceptUnit>>,
+    semantic_l1_dhm: SemanticL1Dhm<BackedStore<L1Id, SemanticUnitL1>>,
     meaning_engine: MeaningEngine,
     projection_engine: ProjectionEngine,
     hypothesis_engine: HypothesisEngine,
@@ -348,9 +615,24 @@ pub struct HybridVM {
     recomposer: Recomposer,
     knowledge_store: KnowledgeStore,
     l2_grounding: BTreeMap<ConceptId, Vec<String>>,
+    grounding_search: GroundingSearch,
     l2_refinements: BTreeMap<ConceptId, Vec<String>>,
+    l1_priorities: BTreeMap<L1Id, RequirementPriority>,
+    l1_provenance: BTreeMap<L1Id, L1Provenance>,
+    l1_context: ContextWindow,
+    /// Restricted design cards, keyed by the L1 unit each card renders.
+    card_access: BTreeMap<L1Id, AccessControl>,
+    prioritization_decisions: BTreeMap<ConceptId, PrioritizationDecision>,
     mode: ExecutionMode,
+    mode_policy: Option<Box<dyn ModePolicy>>,
     trace: Vec<HybridTraceRow>,
+    semantic_trace: Vec<SemanticTraceEvent>,
+    trace_seq: u64,
+    correlation_id: Option<CorrelationId>,
+    metrics: Arc<MetricsRegistry>,
+    interference: InterferenceMonitor,
+    artifact_templates: ArtifactTemplateSet,
+    memory_limits: MemoryLimits,
 }
 
 impl HybridVM {
@@ -359,13 +641,50 @@ impl HybridVM {
         dhm: Dhm,
         mode: ExecutionMode,
     ) -> Result<Self, SemanticError> {
-        let language_dhm = Self::language_dhm_file(ops::util::default_language_store_path())
-            .map_err(SemanticError::from)?;
-        let semantic_dhm = Self::semantic_dhm_file(ops::util::default_semantic_store_path())
-            .map_err(SemanticError::from)?;
-        let semantic_l1_dhm = Self::semantic_l1_dhm_file(ops::util::default_l1_store_path())
-            .map_err(SemanticError::from)?;
-        Ok(Self {
+        let language_dhm = LanguageDhm::backed(BackedStore::open_file(
+            ops::util::default_language_store_path(),
+        )?)
+        .map_err(SemanticError::from)?;
+        let semantic_dhm = SemanticDhm::backed(BackedStore::open_file(
+            ops::util::default_semantic_store_path(),
+        )?)
+        .map_err(SemanticError::from)?;
+        let semantic_l1_dhm =
+            SemanticL1Dhm::backed(BackedStore::open_file(ops::util::default_l1_store_path())?)
+                .map_err(SemanticError::from)?;
+        Ok(Self::assemble(
+            evaluator,
+            dhm,
+            language_dhm,
+            semantic_dhm,
+            semantic_l1_dhm,
+            mode,
+        ))
+    }
+
+    /// A VM whose recall and semantic stores never touch the filesystem,
+    /// suitable for wasm32 and short-lived sessions.
+    pub fn in_memory(evaluator: StructuralEvaluator) -> Result<Self, SemanticError> {
+        let dhm = Dhm::in_memory(ops::util::memory_mode_from_env())?;
+        Ok(Self::assemble(
+            evaluator,
+            dhm,
+            LanguageDhm::backed(BackedStore::in_memory())?,
+            SemanticDhm::backed(BackedStore::in_memory())?,
+            SemanticL1Dhm::backed(BackedStore::in_memory())?,
+            ExecutionMode::RecallFirst,
+        ))
+    }
+
+    fn assemble(
+        evaluator: StructuralEvaluator,
+        dhm: Dhm,
+        language_dhm: LanguageDhm<BackedStore<LangId, LanguageUnit>>,
+        semantic_dhm: SemanticDhm<BackedStore<ConceptId, ConceptUnit>>,
+        semantic_l1_dhm: SemanticL1Dhm<BackedStore<L1Id, SemanticUnitL1>>,
+        mode: ExecutionMode,
+    ) -> Self {
+        Self {
             evaluator,
             dhm,
             language_dhm,
@@ -383,10 +702,24 @@ impl HybridVM {
                 ks
             },
             l2_grounding: BTreeMap::new(),
+            grounding_search: GroundingSearch::default(),
             l2_refinements: BTreeMap::new(),
+            l1_priorities: BTreeMap::new(),
+            l1_provenance: BTreeMap::new(),
+            l1_context: ContextWindow::default(),
+            card_access: BTreeMap::new(),
+            prioritization_decisions: BTreeMap::new(),
             mode,
+            mode_policy: None,
             trace: Vec::new(),
-        })
+            semantic_trace: Vec::new(),
+            trace_seq: 0,
+            correlation_id: None,
+            metrics: Arc::new(MetricsRegistry::new()),
+            interference: InterferenceMonitor::default(),
+            artifact_templates: ArtifactTemplateSet::default(),
+            memory_limits: MemoryLimits::default(),
+        }
     }
 
     pub fn with_default_memory(evaluator: StructuralEvaluator) -> Result<Self, SemanticError> {
@@ -404,175 +737,781 @@ impl HybridVM {
         self.mode = mode;
     }
 
+    /// Lets `policy` change the mode after each evaluation; switches are
+    /// recorded on the trace row of the evaluation that triggered them.
+    pub fn set_mode_policy(&mut self, policy: impl ModePolicy + 'static) {
+        self.mode_policy = Some(Box::new(policy));
+    }
+
+    /// Keeps the current mode from now on.
+    pub fn clear_mode_policy(&mut self) {
+        self.mode_policy = None;
+    }
+
     pub fn evaluate(&mut self, state: &DesignState) -> ObjectiveVector {
         let depth = ops::util::infer_depth_from_snapshot(&state.profile_snapshot);
         let ctx = ExecutionContext::new(self.mode, depth);
         self.evaluate_with_context(state, &ctx)
     }
 
+    /// Breakdown of the structural evaluation of `state`, before any memory
+    /// recall adjustment `evaluate` may apply.
+    pub fn explain(&self, state: &DesignState) -> ObjectiveAttribution {
+        self.evaluator.explain_state(state)
+    }
+
     pub fn evaluate_with_context(
         &mut self,
         state: &DesignState,
         ctx: &ExecutionContext,
     ) -> ObjectiveVector {
+        let started = Stopwatch::start();
         let base = self.evaluator.evaluate(state);
         let adjusted = match ctx.mode {
             ExecutionMode::RecallFirst => self.dhm.recall_first(&base),
             ExecutionMode::ComputeFirst => self.dhm.evaluate_with_recall(&base, ctx.depth),
         };
+        self.metrics.record_evaluation(ctx.mode);
+        self.metrics.record_cache(self.dhm.last_recall_hit());
+        self.interference.record(ctx.depth, &base, &adjusted);
+        let latency = started.elapsed();
+        self.metrics.observe_latency("evaluate", latency);
+        let mode_switch = self.mode_policy.as_mut().and_then(|policy| {
+            policy.observe(&ModeObservation {
+                mode: ctx.mode,
+                depth: ctx.depth,
+                recall_hit: adjusted != base,
+                latency,
+            })
+        });
+        if let Some(switch) = &mode_switch {
+            self.mode = switch.to;
+        }
+        let seq = self.next_trace_seq();
         self.trace.push(HybridTraceRow {
             request_id: ctx.request_id,
             depth: ctx.depth,
             mode: ctx.mode,
             objective: adjusted.clone(),
+            mode_switch,
+            seq,
+            correlation_id: self.correlation_id,
         });
         adjusted
     }
 
+    /// Drains the memory space's telemetry since the last call; the batch is
+    /// also folded into `interference_report`.
     pub fn take_memory_telemetry(&mut self) -> MemoryInterferenceTelemetry {
-        self.dhm.telemetry()
+        let telemetry = self.dhm.telemetry();
+        self.interference.observe_telemetry(&telemetry);
+        telemetry
+    }
+
+    /// Interference rates, drift and currently active alerts for this session.
+    pub fn interference_report(&self) -> InterferenceReport {
+        self.interference.report()
+    }
+
+    /// Alerts raised so far this session, oldest first.
+    pub fn interference_alert_log(&self) -> &[InterferenceAlert] {
+        self.interference.log()
+    }
+
+    pub fn take_interference_alerts(&mut self) -> Vec<InterferenceAlert> {
+        self.interference.take_log()
+    }
+
+    pub fn set_interference_alert_config(&mut self, config: InterferenceAlertConfig) {
+        self.interference.set_config(config);
+    }
+
+    pub fn trace(&self) -> &[HybridTraceRow] {
+        &self.trace
     }
 
     pub fn take_trace(&mut self) -> Vec<HybridTraceRow> {
         std::mem::take(&mut self.trace)
     }
 
-    pub fn analyze_text(&mut self, text: &str) -> Result<ConceptUnit, SemanticError> {
-        ops::semantic::analyze_text(
-            &self.meaning_engine,
-            text,
-            &mut self.language_dhm,
-            &mut self.semantic_l1_dhm,
-            &mut self.semantic_dhm,
-        )
+    /// Tags every trace entry made from now on until the id is changed or
+    /// cleared; see `semantic_trace`.
+    pub fn set_correlation_id(&mut self, id: Option<CorrelationId>) {
+        self.correlation_id = id;
     }
 
-    pub fn analyze_incremental(&mut self, text: &str) -> Result<ConceptUnit, SemanticError> {
-        self.analyze_text(text)
+    pub fn correlation_id(&self) -> Option<CorrelationId> {
+        self.correlation_id
     }
 
-    pub fn add_knowledge(&mut self, topic: &str, vector: Vec<f32>) {
-        let prompt = format!("{} に関する標準的な設計パターンを適用しますか？", topic);
-        self.knowledge_store.add_knowledge(topic, &prompt, vector);
+    /// The `seq` the next trace entry will get; everything traced before
+    /// this call has a smaller one.
+    pub fn trace_seq(&self) -> u64 {
+        self.trace_seq
     }
 
-    pub fn record_feedback(&mut self, draft_id: &str, action: FeedbackAction) {
-        self.knowledge_store.record_feedback(draft_id, action);
+    pub fn semantic_trace(&self) -> &[SemanticTraceEvent] {
+        &self.semantic_trace
     }
 
-    pub fn adjust_weights(&mut self) {
-        self.knowledge_store.adjust_weights();
+    pub fn take_semantic_trace(&mut self) -> Vec<SemanticTraceEvent> {
+        std::mem::take(&mut self.semantic_trace)
     }
 
-    pub fn feedback_entries(&self) -> Vec<FeedbackEntry> {
-        self.knowledge_store.feedback_entries().to_vec()
+    fn next_trace_seq(&mut self) -> u64 {
+        let seq = self.trace_seq;
+        self.trace_seq += 1;
+        seq
     }
 
-    pub fn load_feedback_entries(&mut self, entries: Vec<FeedbackEntry>) {
-        self.knowledge_store.load_feedback_entries(entries);
+    fn record_semantic(&mut self, operation: SemanticOperation) {
+        let seq = self.next_trace_seq();
+        self.semantic_trace.push(SemanticTraceEvent {
+            seq,
+            correlation_id: self.correlation_id,
+            operation,
+        });
     }
 
-    pub fn clear_advisor_history(&mut self) {
-        self.knowledge_store.clear_feedback_history();
+    /// Approximate memory held by the stores and caches; see
+    /// `memory_usage`. Measuring encodes every store entry.
+    pub fn memory_report(&self) -> MemoryReport {
+        let store = |component, usage: io::Result<memory_store::StoreUsage>| {
+            let usage = usage.unwrap_or_default();
+            ComponentUsage {
+                component,
+                entries: usage.entries,
+                bytes: usage.encoded_bytes,
+            }
+        };
+        let cache = |component, entries: usize, entry_bytes: usize| ComponentUsage {
+            component,
+            entries,
+            bytes: entries.saturating_mul(entry_bytes),
+        };
+        MemoryReport {
+            components: vec![
+                store(
+                    MemoryComponent::LanguageStore,
+                    self.language_dhm.store_usage(),
+                ),
+                store(
+                    MemoryComponent::SemanticL1Store,
+                    self.semantic_l1_dhm.store_usage(),
+                ),
+                store(
+                    MemoryComponent::SemanticL2Store,
+                    self.semantic_dhm.store_usage(),
+                ),
+                cache(
+                    MemoryComponent::RecallCache,
+                    self.dhm.cached_entries(),
+                    std::mem::size_of::<memory_space::MemoryEntry>() + 4 * 8,
+                ),
+                cache(
+                    MemoryComponent::AggregateCache,
+                    self.evaluator.cached_aggregates(),
+                    memory_usage::AGGREGATE_ENTRY_BYTES,
+                ),
+                cache(
+                    MemoryComponent::Trace,
+                    self.trace.len(),
+                    std::mem::size_of::<HybridTraceRow>(),
+                ),
+            ],
+            limits: self.memory_limits,
+            evicted_entries: 0,
+        }
     }
 
-    pub fn clear_context(&mut self) -> Result<(), SemanticError> {
-        let ids = self
-            .semantic_l1_dhm
-            .all_units()
-            .into_iter()
-            .map(|u| u.id)
-            .collect::<Vec<_>>();
-        for id in ids {
-            let _ = self.semantic_l1_dhm.remove(id);
+    /// Everything a workspace dashboard shows, in one call. Measures the
+    /// stores like `memory_report`.
+    pub fn workspace_stats(&self) -> Result<WorkspaceStats, SemanticError> {
+        let l1_units = self.semantic_l1_dhm.all_units();
+        let l1_v2 = self.all_l1_units_v2()?;
+        let concepts = self.semantic_dhm.all_concepts();
+        let grounded = |id: &ConceptId| self.l2_grounding.get(id).is_some_and(|g| !g.is_empty());
+
+        let mut l1 = L1Stats {
+            total: l1_units.len(),
+            prioritized: self.l1_priorities.len(),
+            ..L1Stats::default()
+        };
+        for unit in &l1_units {
+            let roles = &mut l1.by_role;
+            *match unit.role {
+                L1RequirementRole::Goal => &mut roles.goal,
+                L1RequirementRole::Constraint => &mut roles.constraint,
+                L1RequirementRole::Optimization => &mut roles.optimization,
+                L1RequirementRole::Prohibition => &mut roles.prohibition,
+            } += 1;
+            // Matches `get_design_cards`: a card is grounded through its concept.
+            if concepts
+                .iter()
+                .any(|c| c.l1_refs.contains(&unit.id) && grounded(&c.id))
+            {
+                l1.by_status.grounded += 1;
+            } else {
+                l1.by_status.hypothetical += 1;
+            }
         }
-        self.l2_grounding.clear();
-        self.l2_refinements.clear();
-        self.rebuild_l2_from_l1_v2()?;
-        Ok(())
-    }
 
-    /// 能動的に具体的な仕様候補を提案する
-    pub fn generate_drafts(&self) -> Result<Vec<DesignDraft>, SemanticError> {
-        let l1_units = self.all_l1_units_v2()?;
-        let mut drafts = Vec::new();
+        let mut l2 = L2Stats {
+            total: concepts.len(),
+            ..L2Stats::default()
+        };
+        let mut stability = Vec::with_capacity(concepts.len());
+        for concept in &concepts {
+            if grounded(&concept.id) {
+                l2.by_status.grounded += 1;
+            } else {
+                l2.by_status.hypothetical += 1;
+            }
+            if self
+                .l2_refinements
+                .get(&concept.id)
+                .is_some_and(|r| !r.is_empty())
+            {
+                l2.refined += 1;
+            }
+            if concept.access.is_restricted() {
+                l2.restricted += 1;
+            }
+            stability.push(ConceptUnitV2::try_from(concept.clone())?.stability_score);
+        }
 
-        for l1 in l1_units {
-            let objective = l1.objective.as_deref().unwrap_or("");
-            if objective.is_empty() {
-                continue;
+        let feedback_entries = self.knowledge_store.feedback_entries();
+        let mut feedback = FeedbackTotals::default();
+        for entry in feedback_entries {
+            match entry.action {
+                FeedbackAction::Adopt => feedback.adopted += 1,
+                FeedbackAction::Reject => feedback.rejected += 1,
             }
+        }
 
-            let query_vec = vector_from_text(objective);
-            let related_labels = self.knowledge_store.top_related_labels(&query_vec, 3);
+        Ok(WorkspaceStats {
+            l1,
+            l2,
+            ambiguity: Histogram::from_values(l1_v2.iter().map(|u| u.ambiguity_score)),
+            stability: Histogram::from_values(stability),
+            grounding_coverage: if concepts.is_empty() {
+                0.0
+            } else {
+                l2.by_status.grounded as f64 / concepts.len() as f64
+            },
+            feedback,
+            stores: self
+                .memory_report()
+                .components
+                .into_iter()
+                .filter(|c| !c.component.is_evictable() && c.component != MemoryComponent::Trace)
+                .collect(),
+            last_modified: LastModified {
+                l1_ingested_at_ms: self.l1_provenance.values().map(|p| p.ingested_at).max(),
+                l2_updated_at_secs: concepts.iter().map(|c| c.timestamp).max(),
+                feedback_at_secs: feedback_entries.iter().map(|e| e.timestamp).max(),
+            },
+        })
+    }
 
-            for label in related_labels {
-                if let Some(prompt) = self.knowledge_store.get_prompt_by_label(&label) {
-                    if objective.contains(&label) {
-                        continue;
-                    }
+    pub fn set_memory_limits(&mut self, limits: MemoryLimits) {
+        self.memory_limits = limits;
+    }
 
-                    drafts.push(DesignDraft {
-                        draft_id: format!("DRAFT-{}-{}", l1.id.0, label),
-                        parent_l1: l1.id,
-                        prompt,
-                        stability_impact: 0.15,
-                        context_summary: format!(
-                            "「{}」の具体化案",
-                            l1.objective.as_deref().unwrap_or("未定義")
-                        ),
-                        added_units: Vec::new(),
-                    });
-                }
+    pub fn memory_limits(&self) -> MemoryLimits {
+        self.memory_limits
+    }
+
+    /// Frees the caches `MemoryComponent::is_evictable` names; returns the
+    /// number of entries dropped. Later evaluations refill them.
+    pub fn evict_caches(&mut self) -> usize {
+        self.dhm.trim_cache() + self.evaluator.clear_aggregate_cache()
+    }
+
+    /// Evicts the caches when over the soft limit, then fails if usage is
+    /// still over the hard limit. Analysis runs this before ingesting while
+    /// any limit is set.
+    pub fn enforce_memory_limits(&mut self) -> Result<MemoryReport, HybridVmError> {
+        let mut report = self.memory_report();
+        if report.over_soft_limit() || report.over_hard_limit() {
+            let evicted = self.evict_caches();
+            report = self.memory_report();
+            report.evicted_entries = evicted;
+        }
+        match self.memory_limits.hard_bytes {
+            Some(hard_bytes) if report.over_hard_limit() => {
+                Err(HybridVmError::MemoryLimit(MemoryLimitExceeded {
+                    used_bytes: report.total_bytes(),
+                    hard_bytes,
+                }))
             }
+            _ => Ok(report),
         }
+    }
 
-        drafts.truncate(5);
-        Ok(drafts)
+    /// Shared handle for exporting metrics from outside the VM (e.g. an HTTP handler).
+    pub fn metrics(&self) -> Arc<MetricsRegistry> {
+        Arc::clone(&self.metrics)
     }
 
-    pub fn commit_draft(&mut self, draft_id: &str) -> Result<(), SemanticError> {
-        let drafts = self.generate_drafts()?;
-        let draft = drafts
-            .into_iter()
-            .find(|d| d.draft_id == draft_id)
-            .ok_or_else(|| SemanticError::InvalidInput("draft not found".to_string()))?;
+    /// Refreshes store-size gauges and renders all metrics in Prometheus text format.
+    pub fn gather_metrics(&self) -> String {
+        self.refresh_store_metrics();
+        self.metrics.gather()
+    }
 
-        // ドラフトのプロンプトを新しいL1制約として追加
-        let input = SemanticUnitL1Input {
-            role: L1RequirementRole::Constraint,
-            polarity: 1,
-            abstraction: 0.3,
-            vector: vector_from_text(&draft.prompt),
-            source_text: format!("Adopted draft: {}", draft.prompt),
-        };
-        let _ = self.semantic_l1_dhm.insert(&input);
+    fn refresh_store_metrics(&self) {
+        self.metrics
+            .set_store_size("semantic_l1", self.semantic_l1_dhm.all_units().len());
+        self.metrics
+            .set_store_size("semantic_l2", self.semantic_dhm.all_concepts().len());
+        self.metrics
+            .set_store_size("language", self.language_dhm.all_units().len());
+    }
 
-        self.rebuild_l2_from_l1_v2()?;
-        Ok(())
+    /// Analyzes `text` as part of the `INLINE_DOCUMENT_ID` document.
+    pub fn analyze_text(&mut self, text: &str) -> Result<ConceptUnit, SemanticError> {
+        self.analyze_document(INLINE_DOCUMENT_ID, text)
     }
 
-    pub fn pareto_optimize_drafts(&self, drafts: Vec<DesignDraft>) -> Vec<DesignDraft> {
-        if drafts.len() <= 1 {
-            return drafts;
-        }
+    /// Like `analyze_text`, recording `document_id` and each fragment's
+    /// offsets in `text` as the provenance of the new L1 units.
+    pub fn analyze_document(
+        &mut self,
+        document_id: &str,
+        text: &str,
+    ) -> Result<ConceptUnit, SemanticError> {
+        let started = Stopwatch::start();
+        let result = self.analyze_with_provenance(document_id, text, text, 0);
+        self.metrics
+            .observe_latency("analyze_text", started.elapsed());
+        let (concept, added) = result?;
+        self.record_semantic(SemanticOperation::Analyze {
+            document_id: document_id.to_string(),
+            added,
+            concept: concept.id,
+        });
+        Ok(concept)
+    }
 
-        let points = drafts
+    /// Brings the units of `document_id` in line with a new revision of the
+    /// document. Fragments still present keep their units (offsets are
+    /// refreshed), fragments that disappeared lose theirs, and only new
+    /// fragments are analyzed.
+    pub fn ingest_document(
+        &mut self,
+        document_id: &str,
+        text: &str,
+    ) -> Result<DocumentIngestReport, SemanticError> {
+        let started = Stopwatch::start();
+        let mut previous = self
+            .l1_provenance
             .iter()
-            .enumerate()
-            .map(|(idx, d)| {
-                let ambiguity = if d.added_units.is_empty() {
-                    0.0
-                } else {
-                    d.added_units.iter().map(|u| u.ambiguity_score).sum::<f64>()
-                        / d.added_units.len() as f64
-                };
-                let complexity = d
-                    .added_units
-                    .iter()
-                    .map(|u| (u.constraints.len() + u.scope_in.len() + u.scope_out.len()) as f64)
-                    .sum::<f64>();
-                ParetoPoint {
+            .filter(|(_, p)| p.document_id == document_id)
+            .filter_map(|(id, _)| self.semantic_l1_dhm.get(*id))
+            .map(|unit| (unit.id, unit.source_text))
+            .collect::<Vec<_>>();
+        let mut report = DocumentIngestReport {
+            document_id: document_id.to_string(),
+            ..DocumentIngestReport::default()
+        };
+        let mut fresh = Vec::new();
+        for (fragment, range) in self.meaning_engine.locate_l1_fragments(text) {
+            match previous.iter().position(|(_, source)| *source == fragment) {
+                Some(pos) => {
+                    let (id, _) = previous.remove(pos);
+                    if let Some(provenance) = self.l1_provenance.get_mut(&id) {
+                        *provenance =
+                            L1Provenance::new(document_id, text, range, provenance.ingested_at);
+                    }
+                    report.kept.push(id);
+                }
+                None => fresh.push((fragment, range)),
+            }
+        }
+        for (id, _) in &previous {
+            self.authorize_card(*id)?;
+        }
+        for (id, _) in previous {
+            self.semantic_l1_dhm.remove(id)?;
+            self.l1_priorities.remove(&id);
+            self.l1_provenance.remove(&id);
+            self.l1_context.forget(id);
+            self.card_access.remove(&id);
+            report.removed.push(id);
+        }
+        for (fragment, range) in fresh {
+            let start = range.map_or(0, |r| r.start);
+            let (_, added) = self.analyze_with_provenance(document_id, text, &fragment, start)?;
+            report.added.extend(added);
+        }
+        if !report.removed.is_empty() {
+            ops::semantic::rebuild_l2_from_l1(&self.context_l1_units(), &mut self.semantic_dhm)?;
+        }
+        self.metrics
+            .observe_latency("ingest_document", started.elapsed());
+        self.record_semantic(SemanticOperation::IngestDocument {
+            document_id: document_id.to_string(),
+            added: report.added.clone(),
+            removed: report.removed.clone(),
+        });
+        Ok(report)
+    }
+
+    /// Analyzes `fragment`, found at byte `offset` of `document`, and records
+    /// provenance for the L1 units it creates.
+    fn analyze_with_provenance(
+        &mut self,
+        document_id: &str,
+        document: &str,
+        fragment: &str,
+        offset: usize,
+    ) -> Result<(ConceptUnit, Vec<L1Id>), SemanticError> {
+        if self.memory_limits.is_set() {
+            self.enforce_memory_limits()
+                .map_err(|err| SemanticError::EvaluationError(err.to_string()))?;
+        }
+        let before = self
+            .semantic_l1_dhm
+            .all_units()
+            .into_iter()
+            .map(|u| u.id)
+            .collect::<BTreeSet<_>>();
+        let concept = ops::semantic::analyze_text(
+            &self.meaning_engine,
+            fragment,
+            &mut self.language_dhm,
+            &mut self.semantic_l1_dhm,
+            &mut self.semantic_dhm,
+            |id| self.l1_context.in_scope(id),
+        )?;
+        let added = self
+            .semantic_l1_dhm
+            .all_units()
+            .into_iter()
+            .map(|u| u.id)
+            .filter(|id| !before.contains(id))
+            .collect::<Vec<_>>();
+        let ingested_at = clock::unix_time_millis();
+        // Insertion order follows fragment order, and ids are handed out
+        // increasingly.
+        for (id, (_, range)) in added
+            .iter()
+            .zip(self.meaning_engine.locate_l1_fragments(fragment))
+        {
+            let range = range.map(|r| r.start + offset..r.end + offset);
+            self.l1_provenance.insert(
+                *id,
+                L1Provenance::new(document_id, document, range, ingested_at),
+            );
+        }
+        Ok((concept, added))
+    }
+
+    /// What `import_requirements` would add and change in L1 and L2 for
+    /// `records`; nothing is stored.
+    pub fn plan_requirement_import(
+        &self,
+        document_id: &str,
+        records: &[RequirementRecord],
+    ) -> Result<RequirementImportReport, SemanticError> {
+        let (report, _) = self.requirement_import_plan(document_id, records)?;
+        Ok(report)
+    }
+
+    /// Adds one L1 unit per record, with `document_id` and the record id as
+    /// its provenance and the record priority as its priority tag, then
+    /// rebuilds L2. Records whose id is already imported from `document_id`
+    /// are skipped, so re-importing an updated export only adds new records.
+    pub fn import_requirements(
+        &mut self,
+        document_id: &str,
+        records: &[RequirementRecord],
+    ) -> Result<RequirementImportReport, SemanticError> {
+        let started = Stopwatch::start();
+        if self.memory_limits.is_set() {
+            self.enforce_memory_limits()
+                .map_err(|err| SemanticError::EvaluationError(err.to_string()))?;
+        }
+        let (mut report, inputs) = self.requirement_import_plan(document_id, records)?;
+        report.dry_run = false;
+        if inputs.is_empty() {
+            return Ok(report);
+        }
+        let ids = self
+            .semantic_l1_dhm
+            .insert_batch(&inputs)
+            .map_err(SemanticError::from)?;
+        let ingested_at = clock::unix_time_millis();
+        for (id, planned) in ids.into_iter().zip(&mut report.imported) {
+            planned.l1_id = id;
+            self.l1_provenance.insert(
+                id,
+                L1Provenance::for_record(document_id, &planned.record_id, ingested_at),
+            );
+            if let Some(priority) = planned.priority {
+                self.l1_priorities.insert(id, priority);
+            }
+        }
+        report.l2_changes =
+            ops::semantic::rebuild_l2_from_l1(&self.context_l1_units(), &mut self.semantic_dhm)?;
+        self.metrics
+            .observe_latency("import_requirements", started.elapsed());
+        self.record_semantic(SemanticOperation::ImportRequirements {
+            document_id: document_id.to_string(),
+            added: report.imported.iter().map(|r| r.l1_id).collect(),
+            changes: report.l2_changes.clone(),
+        });
+        Ok(report)
+    }
+
+    fn requirement_import_plan(
+        &self,
+        document_id: &str,
+        records: &[RequirementRecord],
+    ) -> Result<(RequirementImportReport, Vec<SemanticUnitL1Input>), SemanticError> {
+        let mut known = self
+            .l1_provenance
+            .values()
+            .filter(|p| p.document_id == document_id)
+            .filter_map(|p| p.record_id.clone())
+            .collect::<BTreeSet<_>>();
+        let mut report = RequirementImportReport {
+            document_id: document_id.to_string(),
+            dry_run: true,
+            ..RequirementImportReport::default()
+        };
+        let mut inputs = Vec::new();
+        let mut planned = Vec::new();
+        for record in records {
+            if !known.insert(record.id.clone()) {
+                report.skipped.push(record.id.clone());
+                continue;
+            }
+            let (inferred, inferred_confidence) = self
+                .meaning_engine
+                .infer_requirement_role_with_confidence(&record.text);
+            // A type given by the source is taken as certain.
+            let (role, role_confidence) = record
+                .role
+                .map_or((inferred, inferred_confidence), |r| (r, 1.0));
+            let (abstraction, abstraction_confidence) = self
+                .meaning_engine
+                .infer_abstraction_with_confidence(&record.text);
+            inputs.push(SemanticUnitL1Input {
+                role,
+                polarity: self.meaning_engine.infer_polarity(role),
+                abstraction,
+                vector: self.meaning_engine.embedding_from_text(&record.text),
+                source_text: record.text.clone(),
+                role_confidence,
+                abstraction_confidence,
+            });
+            planned.push(record);
+        }
+        if inputs.is_empty() {
+            return Ok((report, inputs));
+        }
+        let units = self.semantic_l1_dhm.preview_insert_batch(&inputs);
+        report.imported = units
+            .iter()
+            .zip(planned)
+            .map(|(unit, record)| ImportedRequirement {
+                record_id: record.id.clone(),
+                l1_id: unit.id,
+                role: unit.role,
+                priority: record.priority,
+                text: record.text.clone(),
+            })
+            .collect();
+        let mut l1 = self.context_l1_units();
+        l1.extend(units);
+        report.l2_changes = self
+            .semantic_dhm
+            .preview_rebuild(&l1, semantic_dhm::DEFAULT_L2_CONFIG)?;
+        Ok((report, inputs))
+    }
+
+    pub fn l1_provenance(&self, id: L1Id) -> Option<&L1Provenance> {
+        self.l1_provenance.get(&id)
+    }
+
+    /// L1 units read from `document_id`, in id order.
+    pub fn document_units(&self, document_id: &str) -> Vec<L1Id> {
+        self.l1_provenance
+            .iter()
+            .filter(|(_, p)| p.document_id == document_id)
+            .map(|(id, _)| *id)
+            .collect()
+    }
+
+    pub fn export_l1_provenance(&self) -> Vec<(u128, L1Provenance)> {
+        self.l1_provenance
+            .iter()
+            .map(|(id, p)| (id.0, p.clone()))
+            .collect()
+    }
+
+    pub fn load_l1_provenance(&mut self, data: Vec<(u128, L1Provenance)>) {
+        self.l1_provenance = data
+            .into_iter()
+            .map(|(k, v)| (L1Id(k), v))
+            .collect::<BTreeMap<_, _>>();
+    }
+
+    pub fn analyze_incremental(&mut self, text: &str) -> Result<ConceptUnit, SemanticError> {
+        self.analyze_text(text)
+    }
+
+    /// 既存の LanguageUnit から重複文を統合する（保守用、冪等）
+    pub fn deduplicate_language_units(&mut self) -> Result<DedupReport, SemanticError> {
+        let report = self
+            .language_dhm
+            .deduplicate_existing()
+            .map_err(SemanticError::from)?;
+        self.refresh_store_metrics();
+        Ok(report)
+    }
+
+    pub fn add_knowledge(&mut self, topic: &str, vector: Vec<f32>) {
+        let prompt = format!("{} に関する標準的な設計パターンを適用しますか？", topic);
+        self.knowledge_store.add_knowledge(topic, &prompt, vector);
+    }
+
+    pub fn record_feedback(&mut self, draft_id: &str, action: FeedbackAction) {
+        self.knowledge_store.record_feedback(draft_id, action);
+    }
+
+    pub fn adjust_weights(&mut self) {
+        self.knowledge_store.adjust_weights();
+    }
+
+    pub fn feedback_entries(&self) -> Vec<FeedbackEntry> {
+        self.knowledge_store.feedback_entries().to_vec()
+    }
+
+    pub fn load_feedback_entries(&mut self, entries: Vec<FeedbackEntry>) {
+        self.knowledge_store.load_feedback_entries(entries);
+    }
+
+    pub fn clear_advisor_history(&mut self) {
+        self.knowledge_store.clear_feedback_history();
+    }
+
+    /// Fails without clearing anything if a concept or card is protected
+    /// from the actor.
+    pub fn clear_context(&mut self) -> Result<(), SemanticError> {
+        let ids = self
+            .semantic_l1_dhm
+            .all_units()
+            .into_iter()
+            .map(|u| u.id)
+            .collect::<Vec<_>>();
+        for id in &ids {
+            self.authorize_card(*id)?;
+        }
+        for concept in self.semantic_dhm.all_concepts() {
+            self.semantic_dhm.authorize(concept.id)?;
+        }
+        for id in ids {
+            let _ = self.semantic_l1_dhm.remove(id);
+        }
+        self.l2_grounding.clear();
+        self.grounding_search.clear_cache();
+        self.l2_refinements.clear();
+        self.l1_priorities.clear();
+        self.l1_provenance.clear();
+        self.l1_context.clear();
+        self.card_access.clear();
+        self.prioritization_decisions.clear();
+        self.rebuild_l2_from_l1_v2()?;
+        Ok(())
+    }
+
+    /// 能動的に具体的な仕様候補を提案する
+    pub fn generate_drafts(&self) -> Result<Vec<DesignDraft>, SemanticError> {
+        let l1_units = self.context_l1_units_v2()?;
+        let mut drafts = Vec::new();
+
+        for l1 in l1_units {
+            let objective = l1.objective.as_deref().unwrap_or("");
+            if objective.is_empty() {
+                continue;
+            }
+
+            let query_vec = vector_from_text(objective);
+            let related_labels = self.knowledge_store.top_related_labels(&query_vec, 3);
+
+            for label in related_labels {
+                if let Some(prompt) = self.knowledge_store.get_prompt_by_label(&label) {
+                    if objective.contains(&label) {
+                        continue;
+                    }
+
+                    drafts.push(DesignDraft {
+                        draft_id: format!("DRAFT-{}-{}", l1.id.0, label),
+                        parent_l1: l1.id,
+                        prompt,
+                        stability_impact: 0.15,
+                        context_summary: format!(
+                            "「{}」の具体化案",
+                            l1.objective.as_deref().unwrap_or("未定義")
+                        ),
+                        added_units: Vec::new(),
+                    });
+                }
+            }
+        }
+
+        drafts.truncate(5);
+        Ok(drafts)
+    }
+
+    /// Adopts the draft as an L1 constraint and returns what the resulting
+    /// L2 rebuild changed.
+    pub fn commit_draft(&mut self, draft_id: &str) -> Result<L2ChangeSet, SemanticError> {
+        let drafts = self.generate_drafts()?;
+        let draft = drafts
+            .into_iter()
+            .find(|d| d.draft_id == draft_id)
+            .ok_or_else(|| SemanticError::InvalidInput("draft not found".to_string()))?;
+
+        // ドラフトのプロンプトを新しいL1制約として追加
+        let input = SemanticUnitL1Input {
+            role: L1RequirementRole::Constraint,
+            polarity: 1,
+            abstraction: 0.3,
+            vector: vector_from_text(&draft.prompt),
+            source_text: format!("Adopted draft: {}", draft.prompt),
+            role_confidence: 1.0,
+            abstraction_confidence: 1.0,
+        };
+        let _ = self.semantic_l1_dhm.insert(&input);
+
+        Ok(self.rebuild_l2_from_l1_v2()?.changes)
+    }
+
+    pub fn pareto_optimize_drafts(&self, drafts: Vec<DesignDraft>) -> Vec<DesignDraft> {
+        if drafts.len() <= 1 {
+            return drafts;
+        }
+
+        let points = drafts
+            .iter()
+            .enumerate()
+            .map(|(idx, d)| {
+                let ambiguity = if d.added_units.is_empty() {
+                    0.0
+                } else {
+                    d.added_units.iter().map(|u| u.ambiguity_score).sum::<f64>()
+                        / d.added_units.len() as f64
+                };
+                let complexity = d
+                    .added_units
+                    .iter()
+                    .map(|u| (u.constraints.len() + u.scope_in.len() + u.scope_out.len()) as f64)
+                    .sum::<f64>();
+                ParetoPoint {
                     idx,
                     stability_gain: d.stability_impact,
                     ambiguity_cost: ambiguity,
@@ -605,12 +1544,141 @@ impl HybridVM {
         format: ArtifactFormat,
     ) -> Result<Vec<GeneratedArtifact>, SemanticError> {
         let l2_units = self.project_phase_a_v2()?;
-        let artifacts = match format {
-            ArtifactFormat::Rust => generate_rust_artifacts(&l2_units),
-            ArtifactFormat::Sql => generate_sql_artifacts(&l2_units),
-            ArtifactFormat::Mermaid => generate_mermaid_artifacts(&l2_units),
+        Ok(self
+            .artifact_templates
+            .render_with_sources(format, &l2_units, &self.artifact_sources()))
+    }
+
+    /// `generate_artifacts`, rewriting only the artifacts whose source
+    /// concepts changed since `previous_snapshot`, when `previous_artifacts`
+    /// were generated; the others keep their previous content. Only the
+    /// changed concepts are rendered unless an aggregate artifact such as
+    /// the SQL schema needs the whole set. See `artifact_regeneration`.
+    pub fn regenerate_artifacts(
+        &self,
+        format: ArtifactFormat,
+        previous_snapshot: &MeaningLayerSnapshotV2,
+        previous_artifacts: &[GeneratedArtifact],
+    ) -> Result<ArtifactRegeneration, SemanticError> {
+        let current = self.snapshot_v2()?;
+        let diff = self.compare_snapshots_v2(previous_snapshot, &current);
+        let stale = artifact_regeneration::stale_concepts(
+            previous_artifacts,
+            previous_snapshot,
+            &current,
+            &diff,
+        );
+        let fresh = match stale {
+            Some(stale) if stale.is_empty() => previous_artifacts.to_vec(),
+            stale => {
+                let l2_units = self.project_phase_a_v2()?;
+                let sources = self.artifact_sources();
+                let partial = stale.and_then(|stale| {
+                    let order = l2_units.iter().map(|u| u.id).collect::<Vec<_>>();
+                    let changed = l2_units
+                        .iter()
+                        .filter(|u| stale.contains(&u.id))
+                        .cloned()
+                        .collect::<Vec<_>>();
+                    let rendered = self
+                        .artifact_templates
+                        .render_with_sources(format, &changed, &sources);
+                    artifact_regeneration::splice_partial_render(
+                        rendered,
+                        previous_artifacts,
+                        &stale,
+                        &order,
+                    )
+                });
+                partial.unwrap_or_else(|| {
+                    self.artifact_templates
+                        .render_with_sources(format, &l2_units, &sources)
+                })
+            }
         };
-        Ok(artifacts)
+        Ok(ArtifactRegeneration::merge(
+            fresh,
+            previous_artifacts,
+            previous_snapshot,
+            current,
+            &diff,
+        ))
+    }
+
+    fn artifact_sources(&self) -> ArtifactSources {
+        let mut sources = ArtifactSources::default();
+        for concept in self.semantic_dhm.all_concepts() {
+            for id in concept.l1_refs {
+                if let Some(unit) = self.semantic_l1_dhm.get(id) {
+                    sources.insert(concept.id, id, unit.source_text);
+                }
+            }
+        }
+        sources
+    }
+
+    /// Checks `artifacts` against the requirements of the current concepts
+    /// with the default `ArtifactValidator`.
+    pub fn validate_artifacts(
+        &self,
+        artifacts: &[GeneratedArtifact],
+    ) -> Result<ArtifactValidationReport, SemanticError> {
+        let l2_units = self.project_phase_a_v2()?;
+        Ok(ArtifactValidator::default().validate(artifacts, &l2_units))
+    }
+
+    /// `generate_artifacts` followed by `validate_artifacts`.
+    pub fn generate_validated_artifacts(
+        &self,
+        format: ArtifactFormat,
+    ) -> Result<(Vec<GeneratedArtifact>, ArtifactValidationReport), SemanticError> {
+        let l2_units = self.project_phase_a_v2()?;
+        let artifacts = self.artifact_templates.render_with_sources(
+            format,
+            &l2_units,
+            &self.artifact_sources(),
+        );
+        let report = ArtifactValidator::default().validate(&artifacts, &l2_units);
+        Ok((artifacts, report))
+    }
+
+    /// L1 units clustered by L2 concept, with causal links and the
+    /// relations between concepts, ready to render as DOT or Mermaid.
+    pub fn export_concept_graph(&self) -> Result<ConceptGraphExport, SemanticError> {
+        ConceptGraphExport::build(
+            &self.semantic_l1_dhm.all_units(),
+            &self.semantic_dhm.all_concepts(),
+        )
+    }
+
+    /// Concepts and cards to revisit for a change to `target`, in causal
+    /// order with effort estimates.
+    pub fn plan_l1_change(&self, target: L1Id) -> Result<ChangePlan, SemanticError> {
+        ChangePlan::build(target, &self.semantic_dhm.all_concepts())
+    }
+
+    /// Review checklist for `hypothesis` under the change simulated in
+    /// `change`, with the open questions of `extract_missing_information`.
+    pub fn generate_review_checklist(
+        &self,
+        hypothesis: &DesignHypothesis,
+        change: &SimulationReport,
+    ) -> Result<ReviewChecklist, SemanticError> {
+        let blast = self.evaluate_blast_radius(change);
+        let missing = self.extract_missing_information()?;
+        Ok(ReviewChecklist::build(hypothesis, change, &blast, &missing))
+    }
+
+    pub fn set_artifact_template(
+        &mut self,
+        format: ArtifactFormat,
+        template: impl ArtifactTemplate + 'static,
+    ) {
+        self.artifact_templates.set_template(format, template);
+    }
+
+    pub fn set_artifact_templates(&mut self, templates: ArtifactTemplateSet) {
+        self.artifact_templates = templates;
     }
 
     #[deprecated(
@@ -644,44 +1712,256 @@ impl HybridVM {
             .collect()
     }
 
-    pub fn remove_l1(&mut self, id: L1Id) -> Result<(), HybridVmError> {
-        self.semantic_l1_dhm.remove(id).map_err(HybridVmError::Io)
+    /// Units in the current `ContextScope`: what rebuilds and drafts use.
+    pub fn context_l1_units_v2(&self) -> Result<Vec<SemanticUnitL1V2>, SemanticError> {
+        self.context_l1_units()
+            .into_iter()
+            .map(SemanticUnitL1V2::try_from)
+            .collect()
     }
 
-    #[deprecated(
-        since = "1.0.0",
-        note = "Will be removed in PhaseC. Use rebuild_l2_from_l1_v2"
-    )]
-    pub fn rebuild_l2_from_l1(&mut self) -> Result<(), SemanticError> {
-        ops::semantic::rebuild_l2_from_l1(&self.semantic_l1_dhm, &mut self.semantic_dhm)
+    fn context_l1_units(&self) -> Vec<SemanticUnitL1> {
+        let mut units = self.semantic_l1_dhm.all_units();
+        if self.l1_context.has_archived() {
+            units.retain(|u| self.l1_context.in_scope(u.id));
+        }
+        units
     }
 
-    pub fn rebuild_l2_from_l1_v2(&mut self) -> Result<Vec<ConceptUnitV2>, SemanticError> {
-        ops::semantic::rebuild_l2_from_l1(&self.semantic_l1_dhm, &mut self.semantic_dhm)?;
-        self.semantic_dhm
-            .all_concepts()
-            .into_iter()
-            .map(ConceptUnitV2::try_from)
-            .collect()
+    /// Whether rebuilds and drafts skip archived units (the default) or
+    /// use every unit. Takes effect at the next rebuild.
+    pub fn set_context_scope(&mut self, scope: ContextScope) {
+        self.l1_context.scope = scope;
     }
 
-    pub fn rebuild_l2_from_l1_with_config(
-        &mut self,
-        config: L2Config,
-    ) -> Result<(), SemanticError> {
-        ops::semantic::rebuild_l2_from_l1_with_config(
-            &self.semantic_l1_dhm,
-            &mut self.semantic_dhm,
+    pub fn context_scope(&self) -> ContextScope {
+        self.l1_context.scope
+    }
+
+    /// `None` for an unknown unit.
+    pub fn l1_context_state(&self, id: L1Id) -> Option<L1ContextState> {
+        self.semantic_l1_dhm
+            .get(id)
+            .map(|_| self.l1_context.state(id))
+    }
+
+    pub fn archived_l1_ids(&self) -> Vec<L1Id> {
+        self.l1_context.archived().collect()
+    }
+
+    /// Moves `ids` out of the active context and returns the ones that
+    /// were active. L2 is not rebuilt; call a rebuild to drop them from
+    /// the concepts.
+    pub fn archive_l1(&mut self, ids: &[L1Id]) -> Result<Vec<L1Id>, HybridVmError> {
+        if let Some(id) = ids
+            .iter()
+            .find(|id| self.semantic_l1_dhm.get(**id).is_none())
+        {
+            return Err(HybridVmError::L1NotFound(*id));
+        }
+        let now = clock::unix_time_millis();
+        Ok(ids
+            .iter()
+            .copied()
+            .filter(|id| self.l1_context.archive(*id, now))
+            .collect())
+    }
+
+    /// Archives every active unit ingested before `cutoff_ms` (Unix time in
+    /// milliseconds) and returns them. Units without provenance have no
+    /// ingestion time and stay active.
+    pub fn archive_older_than(&mut self, cutoff_ms: u64) -> Vec<L1Id> {
+        let now = clock::unix_time_millis();
+        let stale = self
+            .l1_provenance
+            .iter()
+            .filter(|(id, p)| p.ingested_at < cutoff_ms && self.semantic_l1_dhm.get(**id).is_some())
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        stale
+            .into_iter()
+            .filter(|id| self.l1_context.archive(*id, now))
+            .collect()
+    }
+
+    /// Returns `ids` to the active context and reports the ones that were
+    /// archived; like `archive_l1`, L2 waits for the next rebuild.
+    pub fn restore_l1(&mut self, ids: &[L1Id]) -> Vec<L1Id> {
+        ids.iter()
+            .copied()
+            .filter(|id| self.l1_context.restore(*id))
+            .collect()
+    }
+
+    pub fn remove_l1(&mut self, id: L1Id) -> Result<(), HybridVmError> {
+        self.authorize_card(id)?;
+        self.semantic_l1_dhm.remove(id).map_err(HybridVmError::Io)?;
+        self.l1_priorities.remove(&id);
+        self.l1_provenance.remove(&id);
+        self.l1_context.forget(id);
+        self.card_access.remove(&id);
+        Ok(())
+    }
+
+    /// Identity later mutations of concepts and cards are checked against.
+    pub fn set_actor(&mut self, actor: Actor) {
+        self.semantic_dhm.set_actor(actor);
+    }
+
+    pub fn actor(&self) -> &Actor {
+        self.semantic_dhm.actor()
+    }
+
+    pub fn set_concept_access(
+        &mut self,
+        id: ConceptId,
+        access: AccessControl,
+    ) -> Result<(), SemanticError> {
+        self.semantic_dhm.set_access(id, access)
+    }
+
+    /// Replaces the ownership and lock of the card rendering `id`; the
+    /// current metadata must allow the actor to change it.
+    pub fn set_card_access(
+        &mut self,
+        id: L1Id,
+        access: AccessControl,
+    ) -> Result<(), HybridVmError> {
+        if self.semantic_l1_dhm.get(id).is_none() {
+            return Err(HybridVmError::L1NotFound(id));
+        }
+        self.authorize_card(id)?;
+        if access.is_restricted() {
+            self.card_access.insert(id, access);
+        } else {
+            self.card_access.remove(&id);
+        }
+        Ok(())
+    }
+
+    pub fn card_access(&self, id: L1Id) -> AccessControl {
+        self.card_access.get(&id).cloned().unwrap_or_default()
+    }
+
+    pub fn export_card_access(&self) -> Vec<(u128, AccessControl)> {
+        self.card_access
+            .iter()
+            .map(|(k, v)| (k.0, v.clone()))
+            .collect()
+    }
+
+    pub fn load_card_access(&mut self, data: Vec<(u128, AccessControl)>) {
+        self.card_access = data
+            .into_iter()
+            .filter(|(_, access)| access.is_restricted())
+            .map(|(k, v)| (L1Id(k), v))
+            .collect::<BTreeMap<_, _>>();
+    }
+
+    fn authorize_card(&self, id: L1Id) -> Result<(), PermissionDenied> {
+        let Some(access) = self.card_access.get(&id) else {
+            return Ok(());
+        };
+        match access.denial(self.actor()) {
+            Some(reason) => Err(PermissionDenied {
+                target: card_id(id),
+                actor: self.actor().name.clone(),
+                reason,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    #[deprecated(
+        since = "1.0.0",
+        note = "Will be removed in PhaseC. Use rebuild_l2_from_l1_v2"
+    )]
+    pub fn rebuild_l2_from_l1(&mut self) -> Result<L2ChangeSet, SemanticError> {
+        let result =
+            ops::semantic::rebuild_l2_from_l1(&self.context_l1_units(), &mut self.semantic_dhm);
+        self.traced_rebuild(result)
+    }
+
+    pub fn rebuild_l2_from_l1_v2(&mut self) -> Result<L2RebuildV2, SemanticError> {
+        let started = Stopwatch::start();
+        let changes =
+            ops::semantic::rebuild_l2_from_l1(&self.context_l1_units(), &mut self.semantic_dhm)?;
+        self.metrics
+            .observe_latency("rebuild_l2", started.elapsed());
+        self.record_semantic(SemanticOperation::RebuildL2 {
+            changes: changes.clone(),
+        });
+        let concepts = self
+            .semantic_dhm
+            .all_concepts()
+            .into_iter()
+            .map(ConceptUnitV2::try_from)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(L2RebuildV2 { concepts, changes })
+    }
+
+    pub fn rebuild_l2_from_l1_with_config(
+        &mut self,
+        config: L2Config,
+    ) -> Result<L2ChangeSet, SemanticError> {
+        let started = Stopwatch::start();
+        let result = ops::semantic::rebuild_l2_from_l1_with_config(
+            &self.context_l1_units(),
+            &mut self.semantic_dhm,
             config,
-        )
+        );
+        self.metrics
+            .observe_latency("rebuild_l2", started.elapsed());
+        self.traced_rebuild(result)
     }
 
-    pub fn rebuild_l2_from_l1_with_mode(&mut self, mode: L2Mode) -> Result<(), SemanticError> {
-        ops::semantic::rebuild_l2_from_l1_with_mode(
-            &self.semantic_l1_dhm,
+    /// `rebuild_l2_from_l1_with_config`, reporting its phases to `progress`.
+    pub fn rebuild_l2_from_l1_with_progress(
+        &mut self,
+        config: L2Config,
+        progress: &dyn ProgressSink,
+    ) -> Result<L2ChangeSet, SemanticError> {
+        let started = Stopwatch::start();
+        let result = ops::semantic::rebuild_l2_from_l1_with_progress(
+            &self.context_l1_units(),
+            &mut self.semantic_dhm,
+            config,
+            progress,
+        );
+        self.metrics
+            .observe_latency("rebuild_l2", started.elapsed());
+        self.traced_rebuild(result)
+    }
+
+    pub fn rebuild_l2_from_l1_with_mode(
+        &mut self,
+        mode: L2Mode,
+    ) -> Result<L2ChangeSet, SemanticError> {
+        let started = Stopwatch::start();
+        let result = ops::semantic::rebuild_l2_from_l1_with_mode(
+            &self.context_l1_units(),
             &mut self.semantic_dhm,
             mode,
-        )
+        );
+        self.metrics
+            .observe_latency("rebuild_l2", started.elapsed());
+        self.traced_rebuild(result)
+    }
+
+    fn traced_rebuild(
+        &mut self,
+        result: Result<L2ChangeSet, SemanticError>,
+    ) -> Result<L2ChangeSet, SemanticError> {
+        if let Ok(changes) = &result {
+            self.record_semantic(SemanticOperation::RebuildL2 {
+                changes: changes.clone(),
+            });
+        }
+        result
+    }
+
+    pub fn compare_l2_strategies(&self, configs: &[L2Config]) -> ClusteringComparisonReport {
+        semantic_dhm::compare_clustering_strategies(&self.semantic_l1_dhm.all_units(), configs)
     }
 
     #[deprecated(since = "1.0.0", note = "Will be removed in PhaseC. Use snapshot_v2")]
@@ -694,11 +1974,15 @@ impl HybridVM {
     }
 
     pub fn snapshot_v2(&self) -> Result<MeaningLayerSnapshotV2, SemanticError> {
-        ops::semantic::snapshot_v2(
+        let mut snapshot = ops::semantic::snapshot_v2(
             &self.snapshot_engine,
             &self.semantic_l1_dhm,
             &self.semantic_dhm,
-        )
+        )?;
+        for digest in &mut snapshot.l2_units {
+            digest.grounding_count = self.l2_grounding.get(&digest.id).map_or(0, Vec::len);
+        }
+        Ok(snapshot)
     }
 
     #[deprecated(
@@ -843,6 +2127,28 @@ impl HybridVM {
         }
     }
 
+    fn missing_info_for(
+        &self,
+        id: L1Id,
+        category: InfoCategory,
+        prompt: String,
+        importance: f64,
+    ) -> MissingInfo {
+        let source = self.l1_provenance.get(&id).cloned();
+        let prompt = match &source {
+            Some(source) => format!("{prompt}（出典: {source}）"),
+            None => prompt,
+        };
+        MissingInfo {
+            target_id: Some(id),
+            category,
+            prompt,
+            importance,
+            source,
+            concept_id: None,
+        }
+    }
+
     pub fn extract_missing_information(&self) -> Result<Vec<MissingInfo>, SemanticError> {
         let l1_units = self.all_l1_units_v2()?;
         let l2_units = self.project_phase_a_v2()?;
@@ -864,16 +2170,34 @@ impl HybridVM {
                     )
                 };
 
-                out.push(MissingInfo {
-                    target_id: Some(l1.id),
-                    category: InfoCategory::Constraint,
+                out.push(self.missing_info_for(
+                    l1.id,
+                    InfoCategory::Constraint,
                     prompt,
-                    importance: 0.8 + l1.ambiguity_score * 0.2,
-                });
+                    0.8 + l1.ambiguity_score * 0.2,
+                ));
+            }
+
+            // 役割推定の確信度が低い場合、制約か目標かをユーザーに確認する
+            if l1.role_confidence < ROLE_CONFIDENCE_THRESHOLD {
+                let statement = l1
+                    .objective
+                    .as_deref()
+                    .or_else(|| l1.constraints.first().map(String::as_str))
+                    .unwrap_or("この項目");
+                out.push(self.missing_info_for(
+                    l1.id,
+                    InfoCategory::Classification,
+                    format!("「{statement}」この文は制約ですか、目標ですか？"),
+                    0.75 + (1.0 - l1.role_confidence) * 0.2,
+                ));
             }
         }
 
         for l2 in &l2_units {
+            if self.prioritization_decisions.contains_key(&l2.id) {
+                continue;
+            }
             let has_pos = l2.derived_requirements.iter().any(|r| r.strength > 0.0);
             let has_neg = l2.derived_requirements.iter().any(|r| r.strength < 0.0);
             if has_pos && has_neg {
@@ -885,6 +2209,8 @@ impl HybridVM {
                         l2.id.0
                     ),
                     importance: 0.85,
+                    source: None,
+                    concept_id: Some(l2.id),
                 });
             }
         }
@@ -895,7 +2221,7 @@ impl HybridVM {
 
     /// RFC-010: 能動的に具体的な仕様候補を提案する
     pub fn generate_proactive_drafts(&self) -> Result<Vec<DesignDraft>, SemanticError> {
-        let l1_units = self.all_l1_units_v2()?;
+        let l1_units = self.context_l1_units_v2()?;
         let mut drafts = Vec::new();
 
         for l1 in l1_units {
@@ -937,24 +2263,76 @@ impl HybridVM {
         &self,
         projection: &DesignProjection,
     ) -> Result<DesignHypothesis, SemanticError> {
-        self.hypothesis_engine.evaluate_hypothesis(projection)
+        self.hypothesis_engine
+            .evaluate_hypothesis_prioritized(projection, &self.requirement_priorities())
+    }
+
+    /// `evaluate_hypothesis`, with the memory and power limits stated in the
+    /// L1 requirements checked against what `state`'s nodes declare: a
+    /// memory limit the design exceeds violates the Memory requirement even
+    /// when the projection satisfies it, and one it meets clears it.
+    pub fn evaluate_hypothesis_for_state(
+        &self,
+        projection: &DesignProjection,
+        state: &DesignState,
+    ) -> Result<DesignHypothesis, SemanticError> {
+        self.evaluate_hypothesis_with_checks(projection, state, &[])
+    }
+
+    /// `evaluate_hypothesis_for_state` plus `extra` checks measured by
+    /// models outside the VM, e.g. estimated latency against a limit.
+    pub fn evaluate_hypothesis_with_checks(
+        &self,
+        projection: &DesignProjection,
+        state: &DesignState,
+        extra: &[BudgetCheck],
+    ) -> Result<DesignHypothesis, SemanticError> {
+        let mut checks = self
+            .resource_budget()
+            .check(state)
+            .iter()
+            .map(ResourceCheck::to_budget_check)
+            .collect::<Vec<_>>();
+        checks.extend_from_slice(extra);
+        self.hypothesis_engine.evaluate_hypothesis_budgeted(
+            projection,
+            &self.requirement_priorities(),
+            &checks,
+        )
+    }
+
+    /// Memory and power upper bounds stated in the L1 requirements
+    /// ("メモリ512MB以下"); the tightest bound per resource wins.
+    pub fn resource_budget(&self) -> ResourceBudget {
+        let constraints = self
+            .semantic_l1_dhm
+            .all_units()
+            .iter()
+            .flat_map(|unit| quantitative_constraints(unit.id, &unit.source_text))
+            .collect::<Vec<_>>();
+        ResourceBudget::from_quantitative(&constraints)
     }
 
     pub fn evaluate_design(&mut self, text: &str) -> Result<DesignHypothesis, SemanticError> {
+        let priorities = self.requirement_priorities();
         ops::semantic::evaluate_design(
             text,
+            &priorities,
             &self.meaning_engine,
             &self.projection_engine,
             &self.hypothesis_engine,
             &mut self.language_dhm,
             &mut self.semantic_l1_dhm,
             &mut self.semantic_dhm,
+            |id| self.l1_context.in_scope(id),
         )
     }
 
     pub fn explain_design_v2(&mut self, text: &str) -> Result<Explanation, SemanticError> {
+        let priorities = self.requirement_priorities();
         ops::semantic::explain_design(
             text,
+            &priorities,
             &self.meaning_engine,
             &self.projection_engine,
             &self.hypothesis_engine,
@@ -962,6 +2340,7 @@ impl HybridVM {
             &mut self.language_dhm,
             &mut self.semantic_l1_dhm,
             &mut self.semantic_dhm,
+            |id| self.l1_context.in_scope(id),
         )
     }
 
@@ -982,7 +2361,11 @@ impl HybridVM {
         left: ConceptId,
         right: ConceptId,
     ) -> Result<ResonanceReport, HybridVmError> {
-        ops::recomposer::compare(&self.semantic_dhm, left, right)
+        let started = Stopwatch::start();
+        let result = ops::recomposer::compare(&self.semantic_dhm, left, right);
+        self.metrics.record_recall();
+        self.metrics.observe_latency("recall", started.elapsed());
+        result
     }
 
     pub fn explain_multiple(
@@ -997,7 +2380,54 @@ impl HybridVM {
         query_id: ConceptId,
         top_k: usize,
     ) -> Result<recomposer::RecommendationReport, HybridVmError> {
-        ops::recomposer::recommend(&self.semantic_dhm, &self.recomposer, query_id, top_k)
+        let started = Stopwatch::start();
+        let result =
+            ops::recomposer::recommend(&self.semantic_dhm, &self.recomposer, query_id, top_k);
+        self.metrics.record_recall();
+        self.metrics.observe_latency("recall", started.elapsed());
+        result
+    }
+
+    /// Top `top_k` stored concepts by resonance with `query`, among those
+    /// `filter` accepts.
+    pub fn recall_concepts(
+        &self,
+        query: &ConceptQuery,
+        top_k: usize,
+        filter: &RecallFilter,
+    ) -> Vec<(ConceptId, f32)> {
+        let started = Stopwatch::start();
+        let result = self.semantic_dhm.recall_filtered(query, top_k, filter);
+        self.metrics.record_recall();
+        self.metrics.observe_latency("recall", started.elapsed());
+        result
+    }
+
+    pub fn resonance_weights(&self) -> ResonanceWeights {
+        self.semantic_dhm.weights()
+    }
+
+    pub fn set_resonance_weights(&mut self, weights: ResonanceWeights) {
+        self.semantic_dhm.set_weights(weights);
+    }
+
+    /// Fits resonance weights to labeled pairs of stored concepts and
+    /// switches to them; see `resonance_fit`.
+    pub fn fit_resonance_weights(
+        &mut self,
+        train: &[LabeledPair],
+        held_out: &[LabeledPair],
+        config: WeightFitConfig,
+    ) -> Result<WeightFitReport, HybridVmError> {
+        let report = resonance_fit::fit_resonance_weights(
+            &self.semantic_dhm.all_concepts(),
+            self.semantic_dhm.weights(),
+            train,
+            held_out,
+            config,
+        )?;
+        self.semantic_dhm.set_weights(report.after);
+        Ok(report)
     }
 
     pub fn design_report(
@@ -1081,31 +2511,20 @@ impl HybridVM {
         let base = base_dir.as_ref();
         std::fs::create_dir_all(base)?;
         let dhm = Dhm::open(base.join("dhm.bin"), ops::util::memory_mode_from_env())?;
-        let language_dhm = Self::language_dhm_file(base.join("language_dhm.bin"))?;
-        let semantic_dhm = Self::semantic_dhm_file(base.join("semantic_dhm.bin"))?;
-        let semantic_l1_dhm = Self::semantic_l1_dhm_file(base.join("semantic_l1_dhm.bin"))?;
-        Ok(Self {
-            evaluator: StructuralEvaluator::default(),
+        let language_dhm =
+            LanguageDhm::backed(BackedStore::open_file(base.join("language_dhm.bin"))?)?;
+        let semantic_dhm =
+            SemanticDhm::backed(BackedStore::open_file(base.join("semantic_dhm.bin"))?)?;
+        let semantic_l1_dhm =
+            SemanticL1Dhm::backed(BackedStore::open_file(base.join("semantic_l1_dhm.bin"))?)?;
+        Ok(Self::assemble(
+            StructuralEvaluator::default(),
             dhm,
             language_dhm,
             semantic_dhm,
             semantic_l1_dhm,
-            meaning_engine: MeaningEngine,
-            projection_engine: ProjectionEngine,
-            hypothesis_engine: HypothesisEngine,
-            language_engine: LanguageEngine::new(),
-            snapshot_engine: SnapshotEngine,
-            recomposer: Recomposer,
-            knowledge_store: {
-                let mut ks = KnowledgeStore::new();
-                ks.preload_defaults();
-                ks
-            },
-            l2_grounding: BTreeMap::new(),
-            l2_refinements: BTreeMap::new(),
-            mode: ExecutionMode::RecallFirst,
-            trace: Vec::new(),
-        })
+            ExecutionMode::RecallFirst,
+        ))
     }
 
     pub fn create_l1_framework(
@@ -1122,6 +2541,8 @@ impl HybridVM {
             abstraction: 0.7,
             vector: vector_from_text(normalized),
             source_text: normalized.to_string(),
+            role_confidence: 1.0,
+            abstraction_confidence: 1.0,
         };
         let id = self.semantic_l1_dhm.insert(&insert);
         let l1 = self.semantic_l1_dhm.get(id).ok_or_else(|| {
@@ -1161,6 +2582,7 @@ impl HybridVM {
         if !exists {
             return Err(SemanticError::MissingField("l2_id"));
         }
+        self.semantic_dhm.authorize(l2_id)?;
         self.l2_grounding
             .entry(l2_id)
             .or_default()
@@ -1206,26 +2628,60 @@ impl HybridVM {
         l2_id: ConceptId,
         query: &str,
     ) -> Result<Vec<String>, SemanticError> {
+        let outcome = self.run_grounding_search_with(l2_id, query, &CancelToken::new())?;
+        Ok(outcome.hits.into_iter().map(|hit| hit.text).collect())
+    }
+
+    /// Grounds `l2_id` from the knowledge store and every registered
+    /// provider. New hits are attached to the concept; a cached outcome was
+    /// attached by the search that produced it.
+    pub fn run_grounding_search_with(
+        &mut self,
+        l2_id: ConceptId,
+        query: &str,
+        cancel: &CancelToken,
+    ) -> Result<GroundingOutcome, SemanticError> {
         if query.trim().is_empty() {
             return Err(SemanticError::InvalidInput("query is empty".to_string()));
         }
-        let related = self
-            .knowledge_store
-            .top_related_labels(&vector_from_text(query), 3);
-        let mut out = Vec::new();
-        for label in related {
-            let line = format!("Grounded reference: {label} (query={})", query.trim());
-            self.update_l2_with_grounding(l2_id, &line)?;
-            out.push(line);
+        if self.semantic_dhm.get(l2_id).is_none() {
+            return Err(SemanticError::MissingField("l2_id"));
         }
-        Ok(out)
+        let request = GroundingRequest {
+            concept_id: l2_id,
+            query: query.trim().to_string(),
+            limit: 3,
+        };
+        let local = KnowledgeStoreProvider::new(&self.knowledge_store);
+        let outcome = self
+            .grounding_search
+            .search(&request, Some(&local), cancel)
+            .map_err(|error| SemanticError::InvalidInput(error.to_string()))?;
+        if !outcome.cached {
+            for hit in &outcome.hits {
+                self.update_l2_with_grounding(l2_id, &hit.text)?;
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// Adds a source consulted by `run_grounding_search` after the
+    /// knowledge store and any provider registered earlier.
+    pub fn register_grounding_provider(
+        &mut self,
+        provider: impl GroundingProvider + 'static,
+        limits: ProviderLimits,
+    ) {
+        self.grounding_search.register(Arc::new(provider), limits);
     }
 
+    /// Adds `detail_text` as an L1 constraint and returns what the resulting
+    /// L2 rebuild changed.
     pub fn refine_l2_detail(
         &mut self,
         l2_id: ConceptId,
         detail_text: &str,
-    ) -> Result<(), SemanticError> {
+    ) -> Result<L2ChangeSet, SemanticError> {
         let text = detail_text.trim();
         if text.is_empty() {
             return Err(SemanticError::InvalidInput(
@@ -1236,6 +2692,7 @@ impl HybridVM {
             .semantic_dhm
             .get(l2_id)
             .ok_or(SemanticError::MissingField("card_id"))?;
+        self.semantic_dhm.authorize(l2_id)?;
         let parent = concept
             .l1_refs
             .first()
@@ -1247,6 +2704,8 @@ impl HybridVM {
             abstraction: 0.35,
             vector: vector_from_text(text),
             source_text: format!("L2-{} refinement: {}", l2_id.0, text),
+            role_confidence: 1.0,
+            abstraction_confidence: 1.0,
         };
         let _ = parent;
         let _ = self.semantic_l1_dhm.insert(&input);
@@ -1254,8 +2713,7 @@ impl HybridVM {
             .entry(l2_id)
             .or_default()
             .push(text.to_string());
-        self.rebuild_l2_from_l1_v2()?;
-        Ok(())
+        Ok(self.rebuild_l2_from_l1_v2()?.changes)
     }
 
     pub fn export_l2_grounding(&self) -> Vec<(u64, Vec<String>)> {
@@ -1286,6 +2744,86 @@ impl HybridVM {
             .collect::<BTreeMap<_, _>>();
     }
 
+    /// Tags an L1 unit with a MoSCoW priority for hypothesis evaluation.
+    pub fn set_l1_priority(
+        &mut self,
+        id: L1Id,
+        priority: RequirementPriority,
+    ) -> Result<(), HybridVmError> {
+        if self.semantic_l1_dhm.get(id).is_none() {
+            return Err(HybridVmError::L1NotFound(id));
+        }
+        self.authorize_card(id)?;
+        self.l1_priorities.insert(id, priority);
+        Ok(())
+    }
+
+    pub fn clear_l1_priority(&mut self, id: L1Id) -> Option<RequirementPriority> {
+        self.l1_priorities.remove(&id)
+    }
+
+    pub fn l1_priority(&self, id: L1Id) -> Option<RequirementPriority> {
+        self.l1_priorities.get(&id).copied()
+    }
+
+    /// Priority of each requirement kind: the most important tag among the
+    /// L1 units projecting onto it.
+    pub fn requirement_priorities(&self) -> BTreeMap<RequirementKind, RequirementPriority> {
+        let mut out = BTreeMap::<RequirementKind, RequirementPriority>::new();
+        for (id, priority) in &self.l1_priorities {
+            let Some(unit) = self.semantic_l1_dhm.get(*id) else {
+                continue;
+            };
+            out.entry(semantic_dhm::infer_requirement_kind(&unit))
+                .and_modify(|current| *current = (*current).min(*priority))
+                .or_insert(*priority);
+        }
+        out
+    }
+
+    /// Resolves the Objective conflict `extract_missing_information` reports
+    /// for `concept_id` by favoring one requirement kind, and stops the
+    /// conflict from being reported again.
+    pub fn record_prioritization(
+        &mut self,
+        concept_id: ConceptId,
+        favored: RequirementKind,
+    ) -> Result<PrioritizationDecision, HybridVmError> {
+        let concept = self
+            .semantic_dhm
+            .get(concept_id)
+            .ok_or(HybridVmError::ConceptNotFound(concept_id))?;
+        self.semantic_dhm.authorize(concept_id)?;
+        for id in &concept.l1_refs {
+            self.authorize_card(*id)?;
+        }
+        let mut applied = Vec::new();
+        for id in concept.l1_refs {
+            let Some(unit) = self.semantic_l1_dhm.get(id) else {
+                continue;
+            };
+            let priority = if semantic_dhm::infer_requirement_kind(&unit) == favored {
+                RequirementPriority::Must
+            } else {
+                RequirementPriority::Could
+            };
+            self.l1_priorities.insert(id, priority);
+            applied.push((id, priority));
+        }
+        let decision = PrioritizationDecision {
+            concept_id,
+            favored,
+            applied,
+        };
+        self.prioritization_decisions
+            .insert(concept_id, decision.clone());
+        Ok(decision)
+    }
+
+    pub fn prioritization_decisions(&self) -> Vec<PrioritizationDecision> {
+        self.prioritization_decisions.values().cloned().collect()
+    }
+
     /// RFC-013: 内部構造をユーザー向けの「デザインカード」形式へ変換する
     pub fn get_design_cards(&mut self) -> Result<Vec<DesignCard>, SemanticError> {
         let l1_units = self.all_l1_units_v2()?;
@@ -1296,11 +2834,12 @@ impl HybridVM {
             let detail = self.derive_l2_detail(l1.id).ok(); // 詳細がない場合は None
 
             let mut card = DesignCard {
-                id: format!("CARD-{}", l1.id.0),
+                id: card_id(l1.id),
                 title: framework.title.clone(),
                 overview: framework.objective.clone(),
                 details: Vec::new(),
                 status: CardStatus::Hypothetical,
+                access: self.card_access(l1.id),
             };
 
             if let Some(d) = detail {
@@ -1325,7 +2864,16 @@ impl HybridVM {
     }
 }
 
+/// `rebuild_l2_from_l1_v2`'s result: every concept after the rebuild and
+/// what changed since the previous one.
+#[derive(Clone, Debug, PartialEq)]
+pub struct L2RebuildV2 {
+    pub concepts: Vec<ConceptUnitV2>,
+    pub changes: L2ChangeSet,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub enum CardStatus {
     Hypothetical,
     Grounded,
@@ -1333,12 +2881,24 @@ pub enum CardStatus {
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DesignCard {
     pub id: String,
     pub title: String,
     pub overview: String,
     pub details: Vec<String>,
     pub status: CardStatus,
+    #[serde(default)]
+    pub access: AccessControl,
+}
+
+fn card_id(id: L1Id) -> String {
+    format!("CARD-{}", id.0)
+}
+
+impl core_types::SchemaVersioned for DesignCard {
+    const KIND: &'static str = "design_card";
+    const VERSION: u32 = 1;
 }
 
 fn vector_from_text(text: &str) -> Vec<f32> {
@@ -1365,7 +2925,7 @@ fn generate_rust_artifacts(l2_units: &[ConceptUnitV2]) -> Vec<GeneratedArtifact>
             content.push_str(&format!(
                 "// source_concept: L2-{}, trace_hash: {:016x}\n\n",
                 concept.id.0,
-                trace_hash_for_concept(concept)
+                artifact_trace_hash(concept)
             ));
             content.push_str("#[derive(Debug, Clone)]\n");
             content.push_str(&format!(
@@ -1429,7 +2989,7 @@ fn generate_sql_artifacts(l2_units: &[ConceptUnitV2]) -> Vec<GeneratedArtifact>
             "INSERT INTO l2_concepts (id, stability_score, trace_hash) VALUES ({}, {:.6}, '{:016x}');\n",
             concept.id.0,
             concept.stability_score,
-            trace_hash_for_concept(concept)
+            artifact_trace_hash(concept)
         ));
         for req in &concept.derived_requirements {
             content.push_str(&format!(
@@ -1474,16 +3034,23 @@ fn generate_mermaid_artifacts(l2_units: &[ConceptUnitV2]) -> Vec<GeneratedArtifa
     }]
 }
 
-fn trace_hash_for_concept(concept: &ConceptUnitV2) -> u64 {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
-
-    let mut hasher = DefaultHasher::new();
-    concept.id.0.hash(&mut hasher);
-    concept.stability_score.to_bits().hash(&mut hasher);
-    concept.derived_requirements.len().hash(&mut hasher);
-    concept.causal_links.len().hash(&mut hasher);
-    hasher.finish()
+/// Stable hash linking a generated artifact back to its source concept.
+/// FNV-1a 64 over little-endian fields, so the value is identical across
+/// runs, platforms and toolchains.
+pub fn artifact_trace_hash(concept: &ConceptUnitV2) -> u64 {
+    const OFFSET: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+    [
+        concept.id.0,
+        concept.stability_score.to_bits(),
+        concept.derived_requirements.len() as u64,
+        concept.causal_links.len() as u64,
+    ]
+    .into_iter()
+    .flat_map(u64::to_le_bytes)
+    .fold(OFFSET, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+    })
 }
 
 fn dominates(a: &ParetoPoint, b: &ParetoPoint) -> bool {
@@ -1536,8 +3103,11 @@ fn objective_from_units(
 pub enum HybridVmError {
     Io(io::Error),
     ConceptNotFound(ConceptId),
+    L1NotFound(L1Id),
     InvalidInput(&'static str),
     Decision(recomposer::DecisionError),
+    MemoryLimit(MemoryLimitExceeded),
+    PermissionDenied(PermissionDenied),
 }
 
 impl std::fmt::Display for HybridVmError {
@@ -1545,8 +3115,11 @@ impl std::fmt::Display for HybridVmError {
         match self {
             Self::Io(err) => write!(f, "{err}"),
             Self::ConceptNotFound(_) => write!(f, "Concept not found"),
+            Self::L1NotFound(id) => write!(f, "L1 unit {} not found", id.0),
             Self::InvalidInput(msg) => write!(f, "{msg}"),
             Self::Decision(err) => write!(f, "{err}"),
+            Self::MemoryLimit(err) => write!(f, "{err}"),
+            Self::PermissionDenied(err) => write!(f, "permission denied: {err}"),
         }
     }
 }
@@ -1559,18 +3132,76 @@ impl From<io::Error> for HybridVmError {
     }
 }
 
+impl From<PermissionDenied> for HybridVmError {
+    fn from(value: PermissionDenied) -> Self {
+        Self::PermissionDenied(value)
+    }
+}
+
+/// Attribute key prefix marking a node-level design constraint.
+///
+/// `constraint:<name>` with a `Bool` records whether the constraint holds;
+/// `constraint:max:<key>` / `constraint:min:<key>` with a number bound the
+/// node's numeric `<key>` attribute. Any other value counts as satisfied.
+pub const CONSTRAINT_ATTRIBUTE_PREFIX: &str = "constraint:";
+
+/// Share of `f_struct` given to attribute terms; the remainder
+/// (`1 - sum`) stays on the topology-only complexity score. Defaults to
+/// `NONE`, so attribute scoring is opt-in.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AttributeWeights {
+    /// Mean attributes per node, saturating at `RICH_NODE_ATTRIBUTES`.
+    pub richness: f64,
+    /// Satisfied / declared constraint attributes. A graph that declares no
+    /// constraints drops this term, so its weight stays on topology instead
+    /// of rewarding or penalizing the absence.
+    pub constraints: f64,
+    /// Distinct node kinds / node count.
+    pub kind_diversity: f64,
+}
+
+impl AttributeWeights {
+    /// Topology only; the default.
+    pub const NONE: Self = Self {
+        richness: 0.0,
+        constraints: 0.0,
+        kind_diversity: 0.0,
+    };
+
+    /// 30% of `f_struct` on attributes, weighted towards constraints.
+    pub const BALANCED: Self = Self {
+        richness: 0.10,
+        constraints: 0.15,
+        kind_diversity: 0.05,
+    };
+
+    fn total(&self) -> f64 {
+        self.richness.max(0.0) + self.constraints.max(0.0) + self.kind_diversity.max(0.0)
+    }
+}
+
+impl Default for AttributeWeights {
+    fn default() -> Self {
+        Self::NONE
+    }
+}
+
+const RICH_NODE_ATTRIBUTES: usize = 8;
+
 #[derive(Clone, Debug)]
 pub struct StructuralEvaluator {
     pub max_nodes: usize,
     pub max_edges: usize,
+    pub attribute_weights: AttributeWeights,
+    /// Share of `f_risk` given to `1 - GraphMetrics::hub_risk`; 0 keeps the
+    /// degree-only heuristic and skips the betweenness computation.
+    pub hub_risk_weight: f64,
+    aggregates: AggregateCache,
 }
 
 impl Default for StructuralEvaluator {
     fn default() -> Self {
-        Self {
-            max_nodes: 1000,
-            max_edges: 5000,
-        }
+        Self::new(1000, 5000)
     }
 }
 
@@ -1579,57 +3210,311 @@ impl StructuralEvaluator {
         Self {
             max_nodes,
             max_edges,
+            attribute_weights: AttributeWeights::default(),
+            hub_risk_weight: 0.0,
+            aggregates: AggregateCache::new(incremental::DEFAULT_CACHE_CAPACITY),
         }
     }
-}
 
-impl Evaluator for StructuralEvaluator {
-    fn evaluate(&self, state: &DesignState) -> ObjectiveVector {
-        let graph = &state.graph;
-        let nodes = graph.nodes().len();
-        let edges = graph.edges().len();
+    /// Bounds how many states keep aggregates for `evaluate_child`, oldest
+    /// evicted first; 0 disables incremental evaluation. Starts a new cache
+    /// instead of sharing the one this evaluator was cloned with.
+    pub fn with_aggregate_cache_capacity(mut self, capacity: usize) -> Self {
+        self.aggregates = AggregateCache::new(capacity);
+        self
+    }
 
-        let node_ratio = ratio(nodes, self.max_nodes);
-        let max_possible_edges = nodes.saturating_mul(nodes.saturating_sub(1)) / 2;
-        let edge_density = if max_possible_edges == 0 {
-            0.0
-        } else {
-            ratio(edges, max_possible_edges)
-        };
+    /// States whose aggregates are currently cached.
+    pub fn cached_aggregates(&self) -> usize {
+        self.aggregates.len()
+    }
 
-        let dag_penalty = if graph.is_dag() { 0.0 } else { 1.0 };
-        let normalized_complexity =
-            clamp01(0.45 * node_ratio + 0.45 * edge_density + 0.10 * dag_penalty);
-        let degree_mass_entropy = graph.normalized_degree_mass_entropy();
-        let degree_entropy = graph.normalized_degree_entropy();
-        let field_base = if let Some(category_entropy) = graph.normalized_category_entropy() {
-            0.75 * category_entropy + 0.25 * degree_mass_entropy
-        } else {
-            0.65 * degree_mass_entropy + 0.35 * degree_entropy
-        };
-        let f_field = clamp01(field_base.sqrt());
+    /// Empties the aggregate cache, which clones of this evaluator share.
+    /// Returns how many states were dropped.
+    pub fn clear_aggregate_cache(&self) -> usize {
+        self.aggregates.clear()
+    }
 
-        let risk_raw = 0.25 * graph.normalized_degree_variance()
-            + 0.20 * graph.normalized_max_degree()
-            + 0.15 * graph.normalized_degree_gini()
-            + 0.20 * edge_density
-            + 0.20 * field_base;
-        let f_risk = sigmoid(6.0 * (clamp01(risk_raw) - 0.5));
-        let f_shape = if nodes < 3 {
-            0.0
+    /// Weights above a combined 1.0 are scaled down proportionally.
+    pub fn with_attribute_weights(mut self, weights: AttributeWeights) -> Self {
+        self.attribute_weights = weights;
+        self
+    }
+
+    pub fn with_hub_risk_weight(mut self, weight: f64) -> Self {
+        self.hub_risk_weight = clamp01(weight);
+        self
+    }
+
+    /// `(name, effective weight, value)` per attribute term, with
+    /// `constraint_satisfaction` weighted 0 when the graph declares none.
+    fn attribute_terms(&self, graph: &StructuralGraph) -> [(&'static str, f64, f64); 3] {
+        let w = self.attribute_weights;
+        let constraints = constraint_satisfaction(graph);
+        [
+            (
+                "attribute_richness",
+                w.richness.max(0.0),
+                attribute_richness(graph),
+            ),
+            (
+                "constraint_satisfaction",
+                constraints.map_or(0.0, |_| w.constraints.max(0.0)),
+                constraints.unwrap_or(0.0),
+            ),
+            (
+                "kind_diversity",
+                w.kind_diversity.max(0.0),
+                kind_diversity(graph),
+            ),
+        ]
+    }
+
+    fn attribute_score(&self, graph: &StructuralGraph) -> (f64, f64) {
+        if self.attribute_weights.total() <= f64::EPSILON || graph.nodes().is_empty() {
+            return (0.0, 0.0);
+        }
+        let terms = self.attribute_terms(graph);
+        let total = terms.iter().map(|(_, weight, _)| weight).sum::<f64>();
+        if total <= f64::EPSILON {
+            return (0.0, 0.0);
+        }
+        let score = terms
+            .iter()
+            .map(|(_, weight, value)| weight * value)
+            .sum::<f64>();
+        (total.min(1.0), score / total)
+    }
+}
+
+/// Degree, category, clustering, and acyclicity terms of a graph, read
+/// either from the graph itself or from cached `GraphAggregates`.
+struct GraphTerms {
+    is_dag: bool,
+    degree_mass_entropy: f64,
+    degree_entropy: f64,
+    category_entropy: Option<f64>,
+    degree_variance: f64,
+    max_degree: f64,
+    degree_gini: f64,
+    clustering: f64,
+}
+
+impl GraphTerms {
+    fn from_graph(graph: &StructuralGraph) -> Self {
+        Self {
+            is_dag: graph.is_dag(),
+            degree_mass_entropy: graph.normalized_degree_mass_entropy(),
+            degree_entropy: graph.normalized_degree_entropy(),
+            category_entropy: graph.normalized_category_entropy(),
+            degree_variance: graph.normalized_degree_variance(),
+            max_degree: graph.normalized_max_degree(),
+            degree_gini: graph.normalized_degree_gini(),
+            clustering: graph.average_clustering_coefficient(),
+        }
+    }
+
+    fn from_aggregates(aggregates: &GraphAggregates) -> Self {
+        Self {
+            is_dag: aggregates.is_dag(),
+            degree_mass_entropy: aggregates.normalized_degree_mass_entropy(),
+            degree_entropy: aggregates.normalized_degree_entropy(),
+            category_entropy: aggregates.normalized_category_entropy(),
+            degree_variance: aggregates.normalized_degree_variance(),
+            max_degree: aggregates.normalized_max_degree(),
+            degree_gini: aggregates.normalized_degree_gini(),
+            clustering: aggregates.average_clustering_coefficient(),
+        }
+    }
+}
+
+impl StructuralEvaluator {
+    fn score(&self, graph: &StructuralGraph, terms: &GraphTerms) -> ObjectiveVector {
+        let nodes = graph.nodes().len();
+        let edges = graph.edges().len();
+
+        let node_ratio = ratio(nodes, self.max_nodes);
+        let max_possible_edges = nodes.saturating_mul(nodes.saturating_sub(1)) / 2;
+        let edge_density = if max_possible_edges == 0 {
+            0.0
+        } else {
+            ratio(edges, max_possible_edges)
+        };
+
+        let dag_penalty = if terms.is_dag { 0.0 } else { 1.0 };
+        let normalized_complexity =
+            clamp01(0.45 * node_ratio + 0.45 * edge_density + 0.10 * dag_penalty);
+        let field_base = if let Some(category_entropy) = terms.category_entropy {
+            0.75 * category_entropy + 0.25 * terms.degree_mass_entropy
+        } else {
+            0.65 * terms.degree_mass_entropy + 0.35 * terms.degree_entropy
+        };
+        let f_field = clamp01(field_base.sqrt());
+
+        let risk_raw = 0.25 * terms.degree_variance
+            + 0.20 * terms.max_degree
+            + 0.15 * terms.degree_gini
+            + 0.20 * edge_density
+            + 0.20 * field_base;
+        let mut f_risk = sigmoid(6.0 * (clamp01(risk_raw) - 0.5));
+        if self.hub_risk_weight > 0.0 {
+            let w = clamp01(self.hub_risk_weight);
+            let hub_risk = GraphMetrics::compute(graph).hub_risk();
+            f_risk = (1.0 - w) * f_risk + w * (1.0 - hub_risk);
+        }
+        let f_shape = if nodes < 3 {
+            0.0
         } else {
-            let clustering = graph.average_clustering_coefficient();
-            clamp01(clustering)
+            clamp01(terms.clustering)
         };
 
+        let (attribute_share, attribute_score) = self.attribute_score(graph);
+        let f_struct = (1.0 - attribute_share) * (1.0 - normalized_complexity)
+            + attribute_share * attribute_score;
+
         ObjectiveVector {
-            f_struct: 1.0 - normalized_complexity,
+            f_struct,
             f_field,
             f_risk,
             f_shape,
         }
         .clamped()
     }
+
+    /// Decomposes `evaluate(state)` term by term; see `attribution`.
+    pub fn explain_state(&self, state: &DesignState) -> ObjectiveAttribution {
+        let graph = &state.graph;
+        let terms = GraphTerms::from_graph(graph);
+        let nodes = graph.nodes().len();
+        let edges = graph.edges().len();
+        let node_ratio = ratio(nodes, self.max_nodes);
+        let max_possible_edges = nodes.saturating_mul(nodes.saturating_sub(1)) / 2;
+        let edge_density = if max_possible_edges == 0 {
+            0.0
+        } else {
+            ratio(edges, max_possible_edges)
+        };
+        let dag_penalty = if terms.is_dag { 0.0 } else { 1.0 };
+        let mut attribution = ObjectiveAttribution::default();
+
+        let (attribute_share, _) = self.attribute_score(graph);
+        let keep = 1.0 - attribute_share;
+        let complexity = [
+            ("node_ratio", 0.45 * node_ratio),
+            ("edge_density", 0.45 * edge_density),
+            ("dag_penalty", 0.10 * dag_penalty),
+        ];
+        attribution
+            .f_struct
+            .push(Contribution::new("baseline", keep));
+        attribution
+            .f_struct
+            .extend(spread(&complexity, clamp01, -keep));
+        if attribute_share > 0.0 {
+            let terms = self.attribute_terms(graph);
+            let total = terms.iter().map(|(_, weight, _)| weight).sum::<f64>();
+            for (name, weight, value) in terms {
+                attribution.f_struct.push(Contribution::new(
+                    name,
+                    attribute_share * weight * value / total,
+                ));
+            }
+        }
+
+        let field_terms = match terms.category_entropy {
+            Some(category_entropy) => [
+                ("category_entropy", 0.75 * category_entropy),
+                ("degree_mass_entropy", 0.25 * terms.degree_mass_entropy),
+            ],
+            None => [
+                ("degree_mass_entropy", 0.65 * terms.degree_mass_entropy),
+                ("degree_entropy", 0.35 * terms.degree_entropy),
+            ],
+        };
+        let field_base = field_terms.iter().map(|(_, v)| v).sum::<f64>();
+        attribution
+            .f_field
+            .extend(spread(&field_terms, |base| clamp01(base.sqrt()), 1.0));
+
+        let risk_terms = [
+            ("degree_variance", 0.25 * terms.degree_variance),
+            ("max_degree", 0.20 * terms.max_degree),
+            ("degree_gini", 0.15 * terms.degree_gini),
+            ("edge_density", 0.20 * edge_density),
+            ("field_entropy", 0.20 * field_base),
+        ];
+        let floor = sigmoid(-3.0);
+        let hub_weight = clamp01(self.hub_risk_weight);
+        let structural_share = if self.hub_risk_weight > 0.0 {
+            1.0 - hub_weight
+        } else {
+            1.0
+        };
+        attribution
+            .f_risk
+            .push(Contribution::new("baseline", structural_share * floor));
+        attribution.f_risk.extend(spread(
+            &risk_terms,
+            |raw| sigmoid(6.0 * (clamp01(raw) - 0.5)) - floor,
+            structural_share,
+        ));
+        if self.hub_risk_weight > 0.0 {
+            let hub_risk = GraphMetrics::compute(graph).hub_risk();
+            attribution
+                .f_risk
+                .push(Contribution::new("hub_risk", hub_weight * (1.0 - hub_risk)));
+        }
+
+        attribution.f_shape.push(if nodes < 3 {
+            Contribution::new("too_few_nodes", 0.0)
+        } else {
+            Contribution::new("clustering", clamp01(terms.clustering))
+        });
+
+        attribution.reconciled(&self.score(graph, &terms), "clamp")
+    }
+}
+
+/// Splits `scale * transform(sum of terms)` over the terms in proportion to
+/// their size.
+fn spread(
+    terms: &[(&'static str, f64)],
+    transform: impl Fn(f64) -> f64,
+    scale: f64,
+) -> Vec<Contribution> {
+    let sum = terms.iter().map(|(_, v)| v).sum::<f64>();
+    let total = scale * transform(sum);
+    terms
+        .iter()
+        .map(|(name, value)| {
+            let share = if sum > 0.0 { value / sum } else { 0.0 };
+            Contribution::new(*name, total * share)
+        })
+        .collect()
+}
+
+impl Evaluator for StructuralEvaluator {
+    /// Uses cached aggregates when `evaluate_child` already produced them
+    /// for this state, so a state scores the same whichever way it is asked.
+    fn evaluate(&self, state: &DesignState) -> ObjectiveVector {
+        let terms = match self.aggregates.get(state) {
+            Some(aggregates) => GraphTerms::from_aggregates(&aggregates),
+            None => GraphTerms::from_graph(&state.graph),
+        };
+        self.score(&state.graph, &terms)
+    }
+
+    fn evaluate_child(&self, parent: &DesignState, child: &DesignState) -> ObjectiveVector {
+        if !self.aggregates.is_enabled() {
+            return self.evaluate(child);
+        }
+        let aggregates = self.aggregates.for_child(parent, child);
+        self.score(&child.graph, &GraphTerms::from_aggregates(&aggregates))
+    }
+
+    fn explain(&self, state: &DesignState) -> Option<ObjectiveAttribution> {
+        Some(self.explain_state(state))
+    }
 }
 
 pub struct FieldAwareEvaluator<'a> {
@@ -1644,6 +3529,73 @@ impl Evaluator for FieldAwareEvaluator<'_> {
         let _ = self.target_field;
         self.structural.evaluate(state)
     }
+
+    fn evaluate_child(&self, parent: &DesignState, child: &DesignState) -> ObjectiveVector {
+        self.structural.evaluate_child(parent, child)
+    }
+
+    fn explain(&self, state: &DesignState) -> Option<ObjectiveAttribution> {
+        self.structural.explain(state)
+    }
+}
+
+fn attribute_richness(graph: &StructuralGraph) -> f64 {
+    let filled = graph
+        .nodes()
+        .values()
+        .map(|node| node.attributes.len().min(RICH_NODE_ATTRIBUTES))
+        .sum::<usize>();
+    ratio(filled, graph.nodes().len() * RICH_NODE_ATTRIBUTES)
+}
+
+/// Satisfied / declared constraint attributes; `None` when none are declared.
+fn constraint_satisfaction(graph: &StructuralGraph) -> Option<f64> {
+    let mut declared = 0usize;
+    let mut satisfied = 0usize;
+    for node in graph.nodes().values() {
+        for (key, value) in &node.attributes {
+            let Some(name) = key.strip_prefix(CONSTRAINT_ATTRIBUTE_PREFIX) else {
+                continue;
+            };
+            declared += 1;
+            if constraint_holds(node, name, value) {
+                satisfied += 1;
+            }
+        }
+    }
+    (declared > 0).then(|| satisfied as f64 / declared as f64)
+}
+
+fn constraint_holds(node: &DesignNode, name: &str, value: &Value) -> bool {
+    let bound = |key: &str| {
+        let limit = numeric_value(value)?;
+        let actual = node.attributes.get(key).and_then(numeric_value)?;
+        Some((actual, limit))
+    };
+    if let Some(key) = name.strip_prefix("max:") {
+        return bound(key).is_some_and(|(actual, limit)| actual <= limit);
+    }
+    if let Some(key) = name.strip_prefix("min:") {
+        return bound(key).is_some_and(|(actual, limit)| actual >= limit);
+    }
+    !matches!(value, Value::Bool(false))
+}
+
+fn numeric_value(value: &Value) -> Option<f64> {
+    match value {
+        Value::Int(v) => Some(*v as f64),
+        Value::Float(v) => Some(*v),
+        Value::Bool(_) | Value::Text(_) => None,
+    }
+}
+
+fn kind_diversity(graph: &StructuralGraph) -> f64 {
+    let kinds = graph
+        .nodes()
+        .values()
+        .map(|node| node.kind.as_str())
+        .collect::<BTreeSet<_>>();
+    ratio(kinds.len(), graph.nodes().len())
 }
 
 fn ratio(count: usize, max: usize) -> f64 {
@@ -1672,16 +3624,20 @@ pub mod experimental {
 #[allow(deprecated)]
 mod tests {
     use design_reasoning::MeaningEngine;
-    use std::collections::BTreeMap;
+    use std::collections::{BTreeMap, BTreeSet};
     use std::sync::Arc;
     use std::time::{SystemTime, UNIX_EPOCH};
 
-    use memory_space::{DesignNode, StructuralGraph, Uuid};
+    use memory_space::{DesignNode, GraphMetrics, StructuralGraph, Uuid, Value};
     use semantic_dhm::RequirementRole;
 
     use crate::{
-        Evaluator, ExecutionContext, ExecutionMode, Explanation, HybridVM, MeaningLayerSnapshotV2,
-        StructuralEvaluator,
+        ArtifactFormat, AttributeWeights, CausalEdge, ConceptGraphBuilder, ConceptId,
+        ConceptUnitV2, ConfidenceLevel, ContextScope, DerivedRequirement, Evaluator,
+        ExecutionContext, ExecutionMode, Explanation, FeedbackAction, FeedbackTotals,
+        GeneratedArtifact, HybridVM, HybridVmError, L1ContextState, L1Id, MeaningLayerSnapshotV2,
+        MemoryComponent, MemoryLimits, NodeSource, RequirementKind, StructuralEvaluator,
+        WorkspaceStats, annotate_state_with_requirements, artifact_trace_hash,
     };
 
     fn state_with_graph(nodes: usize, edges: &[(u128, u128)]) -> memory_space::DesignState {
@@ -1714,6 +3670,60 @@ mod tests {
         assert!(trace.len() >= 2);
     }
 
+    #[test]
+    fn mode_policy_switches_are_applied_and_traced() {
+        struct AlwaysCompute;
+        impl crate::ModePolicy for AlwaysCompute {
+            fn observe(
+                &mut self,
+                observation: &crate::ModeObservation,
+            ) -> Option<crate::ModeSwitch> {
+                (observation.mode == ExecutionMode::RecallFirst).then(|| crate::ModeSwitch {
+                    from: observation.mode,
+                    to: ExecutionMode::ComputeFirst,
+                    reason: "test".to_string(),
+                })
+            }
+        }
+
+        let mut vm = HybridVM::in_memory(StructuralEvaluator::default()).expect("vm");
+        vm.set_mode(ExecutionMode::RecallFirst);
+        vm.set_mode_policy(AlwaysCompute);
+        let s = state_with_graph(3, &[(1, 2)]);
+        vm.evaluate(&s);
+        assert_eq!(vm.mode(), ExecutionMode::ComputeFirst);
+        vm.evaluate(&s);
+
+        let trace = vm.take_trace();
+        assert_eq!(trace[0].mode, ExecutionMode::RecallFirst);
+        let switch = trace[0].mode_switch.as_ref().expect("switch recorded");
+        assert_eq!(switch.to, ExecutionMode::ComputeFirst);
+        assert_eq!(trace[1].mode, ExecutionMode::ComputeFirst);
+        assert!(trace[1].mode_switch.is_none());
+
+        vm.clear_mode_policy();
+        vm.set_mode(ExecutionMode::RecallFirst);
+        vm.evaluate(&s);
+        assert_eq!(vm.mode(), ExecutionMode::RecallFirst);
+    }
+
+    #[test]
+    fn evaluations_feed_the_interference_report() {
+        let mut vm = HybridVM::in_memory(StructuralEvaluator::default()).expect("vm");
+        let s = state_with_graph(3, &[(1, 2)]);
+        for depth in [1, 6, 6] {
+            let ctx = ExecutionContext::new(ExecutionMode::ComputeFirst, depth);
+            vm.evaluate_with_context(&s, &ctx);
+        }
+        let _ = vm.take_memory_telemetry();
+
+        let report = vm.interference_report();
+        assert_eq!(report.evaluations, 3);
+        assert_eq!(report.per_depth[&6].evaluations, 2);
+        assert!((0.0..=1.0).contains(&report.interference_rate));
+        assert!(vm.interference_alert_log().len() <= 2);
+    }
+
     #[test]
     fn structural_score_calculation_correctness() {
         let evaluator = StructuralEvaluator::new(10, 20);
@@ -1737,6 +3747,171 @@ mod tests {
         assert!(simple_obj.f_struct > complex_obj.f_struct);
     }
 
+    #[test]
+    fn attribute_terms_reward_satisfied_constraints_and_kind_diversity() {
+        assert_eq!(AttributeWeights::default(), AttributeWeights::NONE);
+        let evaluator =
+            StructuralEvaluator::new(10, 20).with_attribute_weights(AttributeWeights::BALANCED);
+        let with_attr = |key: &str, value: Value| {
+            let mut state = state_with_graph(3, &[(1, 2), (2, 3)]);
+            let mut nodes = state.graph.nodes().clone();
+            let node = nodes.get_mut(&Uuid::from_u128(1)).expect("node 1");
+            node.attributes
+                .insert("latency_ms".to_string(), Value::Int(40));
+            node.attributes.insert(key.to_string(), value);
+            state.graph = Arc::new(StructuralGraph::new(nodes, state.graph.edges().clone()));
+            state
+        };
+
+        let within = evaluator.evaluate(&with_attr("constraint:max:latency_ms", Value::Int(50)));
+        let beyond = evaluator.evaluate(&with_attr("constraint:max:latency_ms", Value::Int(30)));
+        let violated = evaluator.evaluate(&with_attr("constraint:tls", Value::Bool(false)));
+        assert!(within.f_struct > beyond.f_struct);
+        assert_eq!(beyond.f_struct, violated.f_struct);
+
+        let mut uniform = state_with_graph(3, &[(1, 2), (2, 3)]);
+        let mut nodes = uniform.graph.nodes().clone();
+        for node in nodes.values_mut() {
+            node.kind = "Service".to_string();
+        }
+        uniform.graph = Arc::new(StructuralGraph::new(nodes, uniform.graph.edges().clone()));
+        let distinct = state_with_graph(3, &[(1, 2), (2, 3)]);
+        assert!(evaluator.evaluate(&distinct).f_struct > evaluator.evaluate(&uniform).f_struct);
+
+        let topology_only = evaluator.with_attribute_weights(AttributeWeights::NONE);
+        assert_eq!(
+            topology_only.evaluate(&distinct).f_struct,
+            topology_only.evaluate(&uniform).f_struct
+        );
+
+        // No declared constraints: the term drops out instead of scoring 0.5.
+        let constraints_only =
+            StructuralEvaluator::new(10, 20).with_attribute_weights(AttributeWeights {
+                constraints: 0.15,
+                ..AttributeWeights::NONE
+            });
+        assert_eq!(
+            constraints_only.evaluate(&distinct).f_struct,
+            topology_only.evaluate(&distinct).f_struct
+        );
+    }
+
+    #[test]
+    fn evaluate_child_matches_full_evaluation() {
+        let evaluator = StructuralEvaluator::default();
+        let full = StructuralEvaluator::default().with_aggregate_cache_capacity(0);
+        let edges = (2..=12u128)
+            .flat_map(|i| [(i / 2, i), (i.saturating_sub(3).max(1), i)])
+            .filter(|(from, to)| from != to)
+            .collect::<Vec<_>>();
+        let mut parent = state_with_graph(12, &edges);
+        for (id, (from, to)) in [(13u128, (1u128, 13u128)), (14, (7, 14)), (15, (13, 15))] {
+            let graph = parent
+                .graph
+                .with_node_added(DesignNode::new(
+                    Uuid::from_u128(id),
+                    "Cache",
+                    BTreeMap::new(),
+                ))
+                .with_edge_added(Uuid::from_u128(from), Uuid::from_u128(to))
+                .with_edge_removed(Uuid::from_u128(2), Uuid::from_u128(4));
+            let child = memory_space::DesignState::new(
+                Uuid::from_u128(100 + id),
+                Arc::new(graph),
+                "history:1,2",
+            );
+            let incremental = evaluator.evaluate_child(&parent, &child);
+            let expected = full.evaluate(&child);
+            for (a, b) in [
+                (incremental.f_struct, expected.f_struct),
+                (incremental.f_field, expected.f_field),
+                (incremental.f_risk, expected.f_risk),
+                (incremental.f_shape, expected.f_shape),
+            ] {
+                assert!((a - b).abs() < 1e-9, "{a} != {b}");
+            }
+            assert_eq!(evaluator.evaluate(&child), incremental);
+            parent = child;
+        }
+        assert_eq!(evaluator.cached_aggregates(), 4);
+        assert_eq!(
+            full.evaluate_child(&parent, &parent),
+            full.evaluate(&parent)
+        );
+        assert_eq!(full.cached_aggregates(), 0);
+    }
+
+    #[test]
+    fn hub_risk_weight_penalizes_single_points_of_failure() {
+        let evaluator = StructuralEvaluator::default();
+        let chain = state_with_graph(4, &[(1, 2), (2, 3), (3, 4)]);
+        let diamond = state_with_graph(4, &[(1, 2), (1, 3), (2, 4), (3, 4)]);
+
+        let hub_only = evaluator.clone().with_hub_risk_weight(1.0);
+        let chain_risk = hub_only.evaluate(&chain).f_risk;
+        assert!(
+            (chain_risk - (1.0 - GraphMetrics::compute(&chain.graph).hub_risk())).abs() < 1e-12
+        );
+        assert!(hub_only.evaluate(&diamond).f_risk > chain_risk);
+
+        let unweighted = evaluator.with_hub_risk_weight(0.0);
+        assert_eq!(
+            unweighted.evaluate(&chain),
+            StructuralEvaluator::default().evaluate(&chain)
+        );
+    }
+
+    #[test]
+    fn explained_contributions_sum_to_the_evaluated_objective() {
+        let dense = state_with_graph(4, &[(1, 2), (1, 3), (1, 4), (2, 3), (2, 4), (3, 4)]);
+        let mut constrained = state_with_graph(3, &[(1, 2), (2, 3)]);
+        let mut nodes = constrained.graph.nodes().clone();
+        nodes
+            .get_mut(&Uuid::from_u128(1))
+            .expect("node 1")
+            .attributes
+            .insert("constraint:tls".to_string(), Value::Bool(true));
+        constrained.graph = Arc::new(StructuralGraph::new(
+            nodes,
+            constrained.graph.edges().clone(),
+        ));
+        let evaluators = [
+            StructuralEvaluator::new(10, 20).with_attribute_weights(AttributeWeights::BALANCED),
+            StructuralEvaluator::new(10, 20).with_hub_risk_weight(0.4),
+            StructuralEvaluator::default(),
+        ];
+        for evaluator in &evaluators {
+            for state in [&state_with_graph(2, &[(1, 2)]), &dense, &constrained] {
+                let objective = evaluator.evaluate(state);
+                let attribution = evaluator.explain(state).expect("structural explains");
+                let totals = attribution.totals();
+                for (a, b) in [
+                    (totals.f_struct, objective.f_struct),
+                    (totals.f_field, objective.f_field),
+                    (totals.f_risk, objective.f_risk),
+                    (totals.f_shape, objective.f_shape),
+                ] {
+                    assert!((a - b).abs() < 1e-9, "{a} != {b}");
+                }
+            }
+        }
+
+        let attribution = evaluators[0].explain_state(&dense);
+        let term = |name: &str| {
+            attribution
+                .f_struct
+                .iter()
+                .find(|c| c.name == name)
+                .map(|c| c.value)
+        };
+        assert_eq!(term("dag_penalty"), Some(0.0));
+        assert!(term("edge_density") < term("node_ratio"));
+        let rendered = attribution.to_string();
+        assert_eq!(rendered.lines().count(), 4);
+        assert!(rendered.starts_with("f_struct "));
+        assert!(rendered.contains("edge_density -"));
+    }
+
     #[test]
     fn analyze_text_creates_l1_and_l2_link() {
         let mut vm = HybridVM::with_default_memory(StructuralEvaluator::default()).expect("vm");
@@ -1751,148 +3926,728 @@ mod tests {
     }
 
     #[test]
-    fn snapshot_matches_after_rebuild() {
+    fn snapshot_matches_after_rebuild() {
+        let store_dir = std::env::temp_dir().join(format!(
+            "hybrid_vm_snapshot_test_{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("clock")
+                .as_nanos()
+        ));
+        let mut vm = HybridVM::for_cli_storage(&store_dir).expect("vm");
+        let _ = vm.analyze_text("security 강화");
+        let before = vm.snapshot().expect("snapshot");
+        vm.rebuild_l2_from_l1().expect("rebuild");
+        let after = vm.snapshot().expect("snapshot");
+        assert_eq!(before, after);
+    }
+
+    #[test]
+    fn abstraction_v2_monotonic_examples() {
+        let engine = MeaningEngine;
+        let mem = engine.infer_abstraction("メモリは512MB以下");
+        let fast_api = engine.infer_abstraction("高速なAPI");
+        let high_perf = engine.infer_abstraction("高性能にしたい");
+        let maybe_fast = engine.infer_abstraction("できるだけ速く");
+
+        assert!(mem < fast_api);
+        assert!(high_perf > mem);
+        assert!(maybe_fast > 0.7);
+    }
+
+    #[test]
+    fn polarity_depends_on_role_only() {
+        let engine = MeaningEngine;
+        assert_eq!(engine.infer_polarity(RequirementRole::Goal), 1);
+        assert_eq!(engine.infer_polarity(RequirementRole::Optimization), 1);
+        assert_eq!(engine.infer_polarity(RequirementRole::Constraint), -1);
+        assert_eq!(engine.infer_polarity(RequirementRole::Prohibition), -1);
+    }
+
+    fn hypothesis_from_text(text: &str) -> crate::DesignHypothesis {
+        let store_dir = std::env::temp_dir().join(format!(
+            "hybrid_vm_hypothesis_test_{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("clock")
+                .as_nanos()
+        ));
+        let mut vm = HybridVM::for_cli_storage(&store_dir).expect("vm");
+        let _ = vm.analyze_text(text).expect("analyze");
+        let projection = vm.project_phase_a();
+        vm.evaluate_hypothesis(&projection)
+            .expect("hypothesis evaluation should succeed")
+    }
+
+    #[test]
+    fn hypothesis_score_direction_examples() {
+        let perf = hypothesis_from_text("高速なAPI");
+        let memory = hypothesis_from_text("メモリ512MB以下");
+        assert!(perf.total_score > 0.0);
+        assert!(memory.total_score < 0.0);
+    }
+
+    #[test]
+    fn hypothesis_constraint_violation_baseline() {
+        let memory = hypothesis_from_text("メモリ512MB以下");
+        assert!(!memory.constraint_violation);
+    }
+
+    #[test]
+    fn memory_limit_is_checked_against_node_annotations() {
+        let store_dir = std::env::temp_dir().join(format!(
+            "hybrid_vm_budget_test_{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("clock")
+                .as_nanos()
+        ));
+        let mut vm = HybridVM::for_cli_storage(&store_dir).expect("vm");
+        let _ = vm.analyze_text("メモリ512MB以下").expect("analyze");
+        assert_eq!(
+            vm.resource_budget().limit(crate::Resource::Memory),
+            Some(512.0 * 1024.0)
+        );
+        let projection = vm.project_phase_a();
+        let annotated = |kb: &[i64]| {
+            let mut graph = StructuralGraph::default();
+            for (i, kb) in kb.iter().enumerate() {
+                graph = graph.with_node_added(DesignNode::new(
+                    Uuid::from_u128(i as u128 + 1),
+                    format!("N{i}"),
+                    BTreeMap::from([("memory_kb".to_string(), Value::Int(*kb))]),
+                ));
+            }
+            memory_space::DesignState::new(Uuid::from_u128(99), Arc::new(graph), "history:")
+        };
+
+        let within = vm
+            .evaluate_hypothesis_for_state(&projection, &annotated(&[262_144, 131_072]))
+            .expect("within budget");
+        assert!(!within.constraint_violation);
+        assert_eq!(within.exceeded_budgets().count(), 0);
+
+        let over = vm
+            .evaluate_hypothesis_for_state(&projection, &annotated(&[262_144, 393_216]))
+            .expect("over budget");
+        assert!(over.constraint_violation);
+        assert_eq!(over.budget_checks[0].used, 655_360.0);
+        assert!(
+            over.violated_requirements()
+                .any(|r| r.kind == RequirementKind::Memory)
+        );
+    }
+
+    #[test]
+    fn hypothesis_normalized_score_examples() {
+        let perf = hypothesis_from_text("高速なAPI");
+        let memory = hypothesis_from_text("メモリ512MB以下");
+        let mixed = hypothesis_from_text("メモリ512MB以下。高速なAPI");
+
+        assert!(perf.normalized_score > 0.0);
+        assert!(memory.normalized_score < 0.0);
+        assert!(mixed.normalized_score > 0.0);
+    }
+
+    #[test]
+    fn snapshot_v2_compare_ignores_timestamp() {
+        let store_dir = std::env::temp_dir().join(format!(
+            "hybrid_vm_snapshot_v2_{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("clock")
+                .as_nanos()
+        ));
+        let vm = HybridVM::for_cli_storage(&store_dir).expect("vm");
+        let mut a = vm.snapshot_v2().expect("snapshot_v2 a");
+        let b = vm.snapshot_v2().expect("snapshot_v2 b");
+        a.timestamp_ms = a.timestamp_ms.saturating_add(1_000);
+        let diff = vm.compare_snapshots_v2(&a, &b);
+        assert!(!diff.l1_changed);
+        assert!(!diff.l2_changed);
+    }
+
+    #[test]
+    fn l1_and_l2_v2_api_available() {
+        let mut vm = HybridVM::with_default_memory(StructuralEvaluator::default()).expect("vm");
+        let concept = vm
+            .analyze_text("高性能かつクラウド依存禁止")
+            .expect("analyze");
+        let first_l1 = concept.l1_refs[0];
+        let l1_v2 = vm.get_l1_unit_v2(first_l1).expect("get l1 v2");
+        assert!(l1_v2.is_some());
+        let projected_v2 = vm.project_phase_a_v2().expect("project v2");
+        assert!(!projected_v2.is_empty());
+        let rebuilt_v2 = vm.rebuild_l2_from_l1_v2().expect("rebuild v2");
+        assert!(!rebuilt_v2.concepts.is_empty());
+        assert!(rebuilt_v2.changes.is_empty());
+    }
+
+    #[test]
+    fn regeneration_keeps_artifacts_of_unchanged_concepts() {
+        let store_dir = std::env::temp_dir().join(format!(
+            "hybrid_vm_regen_{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("clock")
+                .as_nanos()
+        ));
+        let mut vm = HybridVM::for_cli_storage(&store_dir).expect("vm");
+        let _ = vm.analyze_text("高速なAPI").expect("analyze");
+        let snapshot = vm.snapshot_v2().expect("snapshot");
+        let mut previous = vm.generate_artifacts(ArtifactFormat::Rust).expect("render");
+        previous[0].content.push_str("// reviewed\n");
+
+        let same = vm
+            .regenerate_artifacts(ArtifactFormat::Rust, &snapshot, &previous)
+            .expect("regenerate");
+        assert_eq!(same.artifacts, previous);
+        assert_eq!(same.manifest.changed().count(), 0);
+
+        let _ = vm.analyze_text("クラウド依存は禁止").expect("analyze");
+        let next = vm
+            .regenerate_artifacts(ArtifactFormat::Rust, &same.snapshot, &same.artifacts)
+            .expect("regenerate");
+        assert!(
+            next.manifest
+                .entries
+                .iter()
+                .any(|e| e.change == crate::ArtifactChange::Added)
+        );
+        // The hand edit survives: the first concept did not change.
+        assert!(next.artifacts.contains(&previous[0]));
+        for (entry, artifact) in next.manifest.entries.iter().zip(&next.artifacts) {
+            assert_eq!(entry.file_name, artifact.file_name);
+            if entry.change == crate::ArtifactChange::Unchanged {
+                assert!(previous.contains(artifact));
+            } else {
+                assert!(!entry.reasons.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn regeneration_renders_only_the_changed_concepts() {
+        let store_dir = std::env::temp_dir().join(format!(
+            "hybrid_vm_partial_regen_{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("clock")
+                .as_nanos()
+        ));
+        let mut vm = HybridVM::for_cli_storage(&store_dir).expect("vm");
+        let rendered = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let seen = Arc::clone(&rendered);
+        vm.set_artifact_template(ArtifactFormat::Rust, move |units: &[ConceptUnitV2]| {
+            seen.lock()
+                .expect("lock")
+                .push(units.iter().map(|u| u.id).collect::<Vec<_>>());
+            super::generate_rust_artifacts(units)
+        });
+        let _ = vm.analyze_text("高速なAPI").expect("analyze");
+        let snapshot = vm.snapshot_v2().expect("snapshot");
+        let rust = vm.generate_artifacts(ArtifactFormat::Rust).expect("render");
+        let sql = vm.generate_artifacts(ArtifactFormat::Sql).expect("render");
+        let unchanged = vm
+            .regenerate_artifacts(ArtifactFormat::Rust, &snapshot, &rust)
+            .expect("regenerate");
+        assert_eq!(unchanged.artifacts, rust);
+        assert_eq!(
+            rendered.lock().expect("lock").len(),
+            1,
+            "nothing re-rendered"
+        );
+
+        let _ = vm.analyze_text("クラウド依存は禁止").expect("analyze");
+        let next = vm
+            .regenerate_artifacts(ArtifactFormat::Rust, &snapshot, &rust)
+            .expect("regenerate");
+        let partial = rendered
+            .lock()
+            .expect("lock")
+            .last()
+            .cloned()
+            .expect("render");
+        let all = vm
+            .project_phase_a_v2()
+            .expect("project")
+            .iter()
+            .map(|u| u.id)
+            .collect::<Vec<_>>();
+        assert!(partial.len() < all.len(), "{partial:?} of {all:?}");
+        assert!(!partial.is_empty());
+        assert_eq!(
+            next.artifacts,
+            vm.generate_artifacts(ArtifactFormat::Rust).expect("render")
+        );
+
+        // The schema cites every concept, so it is rendered in full.
+        let schema = vm
+            .regenerate_artifacts(ArtifactFormat::Sql, &snapshot, &sql)
+            .expect("regenerate");
+        assert_eq!(
+            schema.artifacts,
+            vm.generate_artifacts(ArtifactFormat::Sql).expect("render")
+        );
+    }
+
+    #[test]
+    fn archived_units_drop_out_of_rebuilds_until_restored() {
+        let store_dir = std::env::temp_dir().join(format!(
+            "hybrid_vm_context_{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("clock")
+                .as_nanos()
+        ));
+        let mut vm = HybridVM::for_cli_storage(&store_dir).expect("vm");
+        let _ = vm.analyze_text("高速なAPI").expect("analyze");
+        let old = vm
+            .semantic_l1_dhm
+            .all_units()
+            .iter()
+            .map(|u| u.id)
+            .collect::<Vec<_>>();
+        let aged = vm
+            .export_l1_provenance()
+            .into_iter()
+            .map(|(id, mut p)| {
+                p.ingested_at = 1_000;
+                (id, p)
+            })
+            .collect();
+        vm.load_l1_provenance(aged);
+        let _ = vm.analyze_text("クラウド依存は禁止").expect("analyze");
+        let referenced = |vm: &HybridVM| {
+            vm.snapshot_v2()
+                .expect("snapshot")
+                .l2_units
+                .iter()
+                .flat_map(|u| u.l1_refs.clone())
+                .collect::<BTreeSet<_>>()
+        };
+
+        assert_eq!(vm.archive_older_than(2_000), old);
+        assert!(vm.archive_older_than(2_000).is_empty());
+        assert!(matches!(
+            vm.l1_context_state(old[0]),
+            Some(L1ContextState::Archived { .. })
+        ));
+        vm.rebuild_l2_from_l1_v2().expect("rebuild");
+        assert!(old.iter().all(|id| !referenced(&vm).contains(id)));
+        assert!(
+            vm.context_l1_units_v2()
+                .expect("context")
+                .iter()
+                .all(|u| !old.contains(&u.id))
+        );
+        assert!(
+            vm.generate_drafts()
+                .expect("drafts")
+                .iter()
+                .all(|d| !old.contains(&d.parent_l1))
+        );
+        // Analysis keeps the archive out of its rebuild too.
+        let _ = vm.analyze_text("レイテンシを下げる").expect("analyze");
+        assert!(old.iter().all(|id| !referenced(&vm).contains(id)));
+
+        vm.set_context_scope(ContextScope::All);
+        vm.rebuild_l2_from_l1_v2().expect("rebuild");
+        assert!(old.iter().all(|id| referenced(&vm).contains(id)));
+        vm.set_context_scope(ContextScope::Active);
+
+        assert_eq!(vm.restore_l1(&old), old);
+        assert!(vm.archived_l1_ids().is_empty());
+        vm.rebuild_l2_from_l1_v2().expect("rebuild");
+        assert!(old.iter().all(|id| referenced(&vm).contains(id)));
+        assert!(matches!(
+            vm.archive_l1(&[L1Id(u128::MAX)]),
+            Err(HybridVmError::L1NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn deterministic_outputs_across_100_runs() {
+        let input = "高速なAPI。クラウド依存は禁止。メモリ512MB以下";
+        let mut first_snapshot: Option<MeaningLayerSnapshotV2> = None;
+        let mut first_explain: Option<Explanation> = None;
+        for n in 0..100 {
+            let store_dir = std::env::temp_dir().join(format!(
+                "hybrid_vm_det_{}_{}",
+                n,
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .expect("clock")
+                    .as_nanos()
+            ));
+            let mut vm = HybridVM::for_cli_storage(&store_dir).expect("vm");
+            let _ = vm.analyze_text(input).expect("analyze");
+            let snapshot = vm.snapshot_v2().expect("snapshot_v2");
+            let explain = vm.explain_design_v2(input).expect("explain v2");
+            if let Some(expected) = &first_snapshot {
+                assert_eq!(expected.l1_hash, snapshot.l1_hash);
+                assert_eq!(expected.l2_hash, snapshot.l2_hash);
+                assert_eq!(expected.version, snapshot.version);
+            } else {
+                first_snapshot = Some(snapshot);
+            }
+            if let Some(expected) = &first_explain {
+                assert_eq!(expected, &explain);
+            } else {
+                first_explain = Some(explain);
+            }
+        }
+    }
+
+    #[test]
+    fn repeated_analyze_text_does_not_duplicate_language_units() {
         let store_dir = std::env::temp_dir().join(format!(
-            "hybrid_vm_snapshot_test_{}",
+            "hybrid_vm_language_dedup_{}",
             SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .expect("clock")
                 .as_nanos()
         ));
         let mut vm = HybridVM::for_cli_storage(&store_dir).expect("vm");
-        let _ = vm.analyze_text("security 강화");
-        let before = vm.snapshot().expect("snapshot");
-        vm.rebuild_l2_from_l1().expect("rebuild");
-        let after = vm.snapshot().expect("snapshot");
-        assert_eq!(before, after);
+        vm.analyze_text("高速化を重視する").expect("analyze");
+        vm.analyze_text("高速化を重視する。").expect("analyze");
+        vm.analyze_text("安全性を確保する").expect("analyze");
+        assert!(
+            vm.gather_metrics()
+                .contains("hybrid_vm_store_size{store=\"language\"} 2")
+        );
+        let report = vm.deduplicate_language_units().expect("dedup");
+        assert!(report.merged.is_empty());
+        assert_eq!(report.remaining, 2);
     }
 
     #[test]
-    fn abstraction_v2_monotonic_examples() {
-        let engine = MeaningEngine;
-        let mem = engine.infer_abstraction("メモリは512MB以下");
-        let fast_api = engine.infer_abstraction("高速なAPI");
-        let high_perf = engine.infer_abstraction("高性能にしたい");
-        let maybe_fast = engine.infer_abstraction("できるだけ速く");
-
-        assert!(mem < fast_api);
-        assert!(high_perf > mem);
-        assert!(maybe_fast > 0.7);
+    fn artifact_trace_hash_is_pinned() {
+        let concept = ConceptUnitV2 {
+            id: ConceptId(7),
+            derived_requirements: Vec::new(),
+            causal_links: Vec::new(),
+            stability_score: 0.5,
+        };
+        assert_eq!(artifact_trace_hash(&concept), 0x2149b08123d3404b);
     }
 
     #[test]
-    fn polarity_depends_on_role_only() {
-        let engine = MeaningEngine;
-        assert_eq!(engine.infer_polarity(RequirementRole::Goal), 1);
-        assert_eq!(engine.infer_polarity(RequirementRole::Optimization), 1);
-        assert_eq!(engine.infer_polarity(RequirementRole::Constraint), -1);
-        assert_eq!(engine.infer_polarity(RequirementRole::Prohibition), -1);
-    }
-
-    fn hypothesis_from_text(text: &str) -> crate::DesignHypothesis {
+    fn custom_artifact_template_overrides_default() {
         let store_dir = std::env::temp_dir().join(format!(
-            "hybrid_vm_hypothesis_test_{}",
+            "hybrid_vm_artifact_template_{}",
             SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .expect("clock")
                 .as_nanos()
         ));
         let mut vm = HybridVM::for_cli_storage(&store_dir).expect("vm");
-        let _ = vm.analyze_text(text).expect("analyze");
-        let projection = vm.project_phase_a();
-        vm.evaluate_hypothesis(&projection)
-            .expect("hypothesis evaluation should succeed")
-    }
+        vm.analyze_text("高速化を重視する").expect("analyze");
+
+        let default_rust = vm.generate_artifacts(ArtifactFormat::Rust).expect("rust");
+        assert!(!default_rust.is_empty());
+        assert!(default_rust[0].content.contains("pub trait Concept"));
+
+        vm.set_artifact_template(
+            ArtifactFormat::Rust,
+            |l2: &[ConceptUnitV2]| -> Vec<GeneratedArtifact> {
+                l2.iter()
+                    .map(|c| GeneratedArtifact {
+                        file_name: format!("custom_{}.rs", c.id.0),
+                        content: format!(
+                            "// {:016x} requirements={}\n",
+                            artifact_trace_hash(c),
+                            c.derived_requirements.len()
+                        ),
+                    })
+                    .collect()
+            },
+        );
+        let custom = vm.generate_artifacts(ArtifactFormat::Rust).expect("rust");
+        assert_eq!(custom.len(), default_rust.len());
+        assert!(custom[0].file_name.starts_with("custom_"));
+        assert!(!custom[0].content.contains("pub trait"));
 
-    #[test]
-    fn hypothesis_score_direction_examples() {
-        let perf = hypothesis_from_text("高速なAPI");
-        let memory = hypothesis_from_text("メモリ512MB以下");
-        assert!(perf.total_score > 0.0);
-        assert!(memory.total_score < 0.0);
+        let sql = vm.generate_artifacts(ArtifactFormat::Sql).expect("sql");
+        assert_eq!(sql[0].file_name, "schema.sql");
     }
 
     #[test]
-    fn hypothesis_constraint_violation_baseline() {
-        let memory = hypothesis_from_text("メモリ512MB以下");
-        assert!(!memory.constraint_violation);
+    fn rust_test_scaffolds_carry_l1_constraint_values_and_validate() {
+        let mut vm = HybridVM::in_memory(StructuralEvaluator::default()).expect("vm");
+        vm.analyze_text("レスポンス200ms以下").expect("analyze");
+
+        let (artifacts, report) = vm
+            .generate_validated_artifacts(ArtifactFormat::RustTests)
+            .expect("tests");
+        assert!(!artifacts.is_empty());
+        let scaffold = artifacts
+            .iter()
+            .find(|a| a.content.contains("_performance()"))
+            .expect("performance test");
+        assert!(scaffold.file_name.ends_with("_tests.rs"));
+        assert!(scaffold.content.contains("// L1-"));
+        assert!(scaffold.content.contains("assert!(measured_1 <= 200.0"));
+        assert!(
+            report
+                .structural_checks()
+                .all(|c| c.status == crate::ValidationStatus::Pass)
+        );
     }
 
     #[test]
-    fn hypothesis_normalized_score_examples() {
-        let perf = hypothesis_from_text("高速なAPI");
-        let memory = hypothesis_from_text("メモリ512MB以下");
-        let mixed = hypothesis_from_text("メモリ512MB以下。高速なAPI");
+    fn gather_renders_prometheus_exposition() {
+        let store_dir = std::env::temp_dir().join(format!(
+            "hybrid_vm_metrics_{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("clock")
+                .as_nanos()
+        ));
+        let mut vm = HybridVM::for_cli_storage(&store_dir).expect("vm");
+        let handle = vm.metrics();
+        vm.analyze_text("高速化を重視").expect("analyze");
+        let s = state_with_graph(3, &[(1, 2)]);
+        let ctx = ExecutionContext::new(ExecutionMode::ComputeFirst, 1);
+        vm.evaluate_with_context(&s, &ctx);
+        vm.evaluate_with_context(&s, &ExecutionContext::new(ExecutionMode::RecallFirst, 1));
+
+        let text = vm.gather_metrics();
+        assert!(text.contains("# TYPE hybrid_vm_evaluations_total counter"));
+        assert!(text.contains("hybrid_vm_evaluations_total{mode=\"compute_first\"} 1"));
+        assert!(text.contains("hybrid_vm_evaluations_total{mode=\"recall_first\"} 1"));
+        assert!(text.contains("hybrid_vm_store_size{store=\"semantic_l1\"} 1"));
+        assert!(text.contains("hybrid_vm_op_latency_seconds_count{op=\"analyze_text\"} 1"));
+        assert!(
+            text.contains("hybrid_vm_op_latency_seconds_bucket{op=\"evaluate\",le=\"+Inf\"} 2")
+        );
 
-        assert!(perf.normalized_score > 0.0);
-        assert!(memory.normalized_score < 0.0);
-        assert!(mixed.normalized_score > 0.0);
+        let snap = handle.snapshot();
+        // The first lookup finds an empty memory; the second recalls the
+        // objective the first evaluation stored.
+        assert_eq!((snap.cache_hits, snap.cache_misses), (1, 1));
+        assert_eq!(handle.gather(), text);
     }
 
     #[test]
-    fn snapshot_v2_compare_ignores_timestamp() {
+    fn low_role_confidence_yields_classification_prompt() {
         let store_dir = std::env::temp_dir().join(format!(
-            "hybrid_vm_snapshot_v2_{}",
+            "hybrid_vm_role_confidence_{}",
             SystemTime::now()
                 .duration_since(UNIX_EPOCH)
                 .expect("clock")
                 .as_nanos()
         ));
-        let vm = HybridVM::for_cli_storage(&store_dir).expect("vm");
-        let mut a = vm.snapshot_v2().expect("snapshot_v2 a");
-        let b = vm.snapshot_v2().expect("snapshot_v2 b");
-        a.timestamp_ms = a.timestamp_ms.saturating_add(1_000);
-        let diff = vm.compare_snapshots_v2(&a, &b);
-        assert!(!diff.l1_changed);
-        assert!(!diff.l2_changed);
+        let mut vm = HybridVM::for_cli_storage(&store_dir).expect("vm");
+        let concept = vm.analyze_text("レスポンス200ms").expect("analyze");
+        let l1 = vm
+            .get_l1_unit_v2(concept.l1_refs[0])
+            .expect("l1 v2")
+            .expect("l1 exists");
+        assert!(l1.role_confidence < crate::ROLE_CONFIDENCE_THRESHOLD);
+
+        let missing = vm.extract_missing_information().expect("missing info");
+        assert!(missing.iter().any(|m| {
+            m.category == crate::InfoCategory::Classification
+                && m.target_id == Some(l1.id)
+                && m.prompt.contains("制約ですか、目標ですか")
+        }));
     }
 
     #[test]
-    fn l1_and_l2_v2_api_available() {
-        let mut vm = HybridVM::with_default_memory(StructuralEvaluator::default()).expect("vm");
+    fn prioritization_resolves_objective_conflict_and_weights_evaluation() {
+        let store_dir = std::env::temp_dir().join(format!(
+            "hybrid_vm_prioritization_{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("clock")
+                .as_nanos()
+        ));
+        let mut vm = HybridVM::for_cli_storage(&store_dir).expect("vm");
         let concept = vm
-            .analyze_text("高性能かつクラウド依存禁止")
+            .analyze_text("クラウド禁止でオンプレミス運用、ただし高速")
             .expect("analyze");
-        let first_l1 = concept.l1_refs[0];
-        let l1_v2 = vm.get_l1_unit_v2(first_l1).expect("get l1 v2");
-        assert!(l1_v2.is_some());
-        let projected_v2 = vm.project_phase_a_v2().expect("project v2");
-        assert!(!projected_v2.is_empty());
-        let rebuilt_v2 = vm.rebuild_l2_from_l1_v2().expect("rebuild v2");
-        assert!(!rebuilt_v2.is_empty());
+        let l1_id = concept.l1_refs[0];
+        let kind = semantic_dhm::infer_requirement_kind(
+            &vm.semantic_l1_dhm.get(l1_id).expect("l1 exists"),
+        );
+
+        let decision = vm
+            .record_prioritization(concept.id, kind)
+            .expect("prioritize");
+        assert_eq!(decision.applied.len(), concept.l1_refs.len());
+        assert_eq!(
+            vm.l1_priority(l1_id),
+            Some(crate::RequirementPriority::Must)
+        );
+        assert_eq!(
+            vm.requirement_priorities().get(&kind),
+            Some(&crate::RequirementPriority::Must)
+        );
+        let missing = vm.extract_missing_information().expect("missing info");
+        assert!(
+            !missing
+                .iter()
+                .any(|m| m.prompt.starts_with(&format!("L2-{} ", concept.id.0)))
+        );
+        assert_eq!(vm.prioritization_decisions(), vec![decision]);
+
+        vm.remove_l1(l1_id).expect("remove");
+        assert_eq!(vm.l1_priority(l1_id), None);
+        assert!(matches!(
+            vm.set_l1_priority(l1_id, crate::RequirementPriority::Should),
+            Err(crate::HybridVmError::L1NotFound(_))
+        ));
     }
 
     #[test]
-    fn deterministic_outputs_across_100_runs() {
-        let input = "高速なAPI。クラウド依存は禁止。メモリ512MB以下";
-        let mut first_snapshot: Option<MeaningLayerSnapshotV2> = None;
-        let mut first_explain: Option<Explanation> = None;
-        for n in 0..100 {
-            let store_dir = std::env::temp_dir().join(format!(
-                "hybrid_vm_det_{}_{}",
-                n,
-                SystemTime::now()
-                    .duration_since(UNIX_EPOCH)
-                    .expect("clock")
-                    .as_nanos()
-            ));
-            let mut vm = HybridVM::for_cli_storage(&store_dir).expect("vm");
-            let _ = vm.analyze_text(input).expect("analyze");
-            let snapshot = vm.snapshot_v2().expect("snapshot_v2");
-            let explain = vm.explain_design_v2(input).expect("explain v2");
-            if let Some(expected) = &first_snapshot {
-                assert_eq!(expected.l1_hash, snapshot.l1_hash);
-                assert_eq!(expected.l2_hash, snapshot.l2_hash);
-                assert_eq!(expected.version, snapshot.version);
-            } else {
-                first_snapshot = Some(snapshot);
-            }
-            if let Some(expected) = &first_explain {
-                assert_eq!(expected, &explain);
-            } else {
-                first_explain = Some(explain);
+    fn card_and_concept_access_is_enforced_until_overridden() {
+        let mut vm = HybridVM::in_memory(StructuralEvaluator::default()).expect("vm");
+        let concept = vm.analyze_text("応答時間を短縮する").expect("analyze");
+        let l1_id = concept.l1_refs[0];
+        let kind = semantic_dhm::infer_requirement_kind(
+            &vm.semantic_l1_dhm.get(l1_id).expect("l1 exists"),
+        );
+
+        vm.set_actor(crate::Actor::named("alice"));
+        vm.set_card_access(l1_id, crate::AccessControl::owned_by("alice"))
+            .expect("claim card");
+        vm.set_concept_access(concept.id, crate::AccessControl::default().locked())
+            .expect("lock concept");
+        let cards = vm.get_design_cards().expect("cards");
+        assert_eq!(cards[0].access.owner.as_deref(), Some("alice"));
+
+        vm.set_actor(crate::Actor::named("bob"));
+        match vm.set_l1_priority(l1_id, crate::RequirementPriority::Must) {
+            Err(crate::HybridVmError::PermissionDenied(denied)) => {
+                assert_eq!(denied.target, format!("CARD-{}", l1_id.0));
+                assert_eq!(
+                    denied.reason,
+                    crate::DenialReason::OwnedBy("alice".to_string())
+                );
             }
+            other => panic!("expected PermissionDenied, got {other:?}"),
         }
+        assert!(matches!(
+            vm.remove_l1(l1_id),
+            Err(crate::HybridVmError::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            vm.clear_context(),
+            Err(crate::SemanticError::PermissionDenied(_))
+        ));
+
+        vm.set_actor(crate::Actor::named("alice"));
+        vm.set_l1_priority(l1_id, crate::RequirementPriority::Must)
+            .expect("owner edits card");
+        assert!(matches!(
+            vm.update_l2_with_grounding(concept.id, "SLA 99.9%"),
+            Err(crate::SemanticError::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            vm.record_prioritization(concept.id, kind),
+            Err(crate::HybridVmError::PermissionDenied(_))
+        ));
+
+        vm.set_actor(crate::Actor::named("bob").with_override());
+        vm.remove_l1(l1_id).expect("override");
+        assert_eq!(vm.card_access(l1_id), crate::AccessControl::default());
+    }
+
+    #[test]
+    fn review_checklist_links_conflicts_and_changed_concepts() {
+        let store_dir = std::env::temp_dir().join(format!(
+            "hybrid_vm_review_checklist_{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("clock")
+                .as_nanos()
+        ));
+        let mut vm = HybridVM::for_cli_storage(&store_dir).expect("vm");
+        let text = "クラウド禁止でオンプレミス運用、ただし高速";
+        let hypothesis = vm.evaluate_design(text).expect("hypothesis");
+        assert!(hypothesis.constraint_violation);
+        let l1_id = vm.all_l1_units_v2().expect("l1")[0].id;
+        let change = vm.simulate_removal(l1_id).expect("simulate");
+        assert!(!change.affected_concepts.is_empty());
+
+        let checklist = vm
+            .generate_review_checklist(&hypothesis, &change)
+            .expect("checklist");
+        let linked = |section: crate::ReviewSection| {
+            checklist
+                .section(section)
+                .flat_map(|item| item.entities.clone())
+                .collect::<Vec<_>>()
+        };
+        let changed = change
+            .affected_concepts
+            .iter()
+            .map(|impact| crate::ReviewEntity::Concept(impact.concept_id))
+            .collect::<Vec<_>>();
+        assert_eq!(linked(crate::ReviewSection::RiskyConcept), changed);
+        assert!(
+            linked(crate::ReviewSection::Conflict)
+                .contains(&crate::ReviewEntity::Requirement(RequirementKind::NoCloud))
+        );
+        assert_eq!(
+            checklist.blast_radius,
+            vm.evaluate_blast_radius(&change).total_score
+        );
+        assert!(
+            checklist
+                .artifact()
+                .content
+                .contains("### Unresolved conflicts")
+        );
+    }
+
+    #[test]
+    fn ingest_document_records_provenance_and_reingests_only_changes() {
+        let store_dir = std::env::temp_dir().join(format!(
+            "hybrid_vm_provenance_{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("clock")
+                .as_nanos()
+        ));
+        let mut vm = HybridVM::for_cli_storage(&store_dir).expect("vm");
+        let v1 = "クラウドは使わない。レスポンス200ms";
+        let report = vm.ingest_document("spec.md", v1).expect("ingest v1");
+        assert_eq!(report.added.len(), 2);
+        let latency = vm.l1_provenance(report.added[1]).expect("provenance");
+        assert_eq!(latency.document_id, "spec.md");
+        assert_eq!(latency.char_range, Some(10..20));
+        assert_eq!(
+            &v1[latency.byte_range.clone().expect("bytes")],
+            "レスポンス200ms"
+        );
+
+        let missing = vm.extract_missing_information().expect("missing info");
+        let prompt = missing
+            .iter()
+            .find(|m| m.target_id == Some(report.added[1]))
+            .expect("classification prompt");
+        assert_eq!(prompt.source.as_ref(), Some(latency));
+        assert!(prompt.prompt.contains("出典: spec.md:10-20"));
+
+        let v2 = "メモリ512MB以下。レスポンス200ms";
+        let edit = vm.ingest_document("spec.md", v2).expect("ingest v2");
+        assert_eq!(edit.kept, vec![report.added[1]]);
+        assert_eq!(edit.removed, vec![report.added[0]]);
+        assert_eq!(edit.added.len(), 1);
+        assert_eq!(
+            vm.l1_provenance(report.added[1])
+                .expect("kept provenance")
+                .char_range,
+            Some(11..21)
+        );
+        assert_eq!(vm.document_units("spec.md").len(), 2);
+        assert!(
+            vm.ingest_document("spec.md", v2)
+                .expect("ingest again")
+                .is_unchanged()
+        );
     }
 
     #[test]
@@ -1917,6 +4672,89 @@ mod tests {
         assert!(!detail.methods.is_empty());
     }
 
+    #[test]
+    fn refinements_and_drafts_report_what_the_rebuild_changed() {
+        let mut vm = HybridVM::in_memory(StructuralEvaluator::default()).expect("vm");
+        let concept = vm.analyze_text("応答時間を短縮する").expect("analyze");
+        assert!(
+            vm.rebuild_l2_from_l1_v2()
+                .expect("rebuild")
+                .changes
+                .is_empty()
+        );
+
+        let changes = vm
+            .refine_l2_detail(concept.id, "p99 レイテンシ 200ms 以下")
+            .expect("refine");
+        assert!(!changes.is_empty());
+        let current = vm
+            .semantic_dhm
+            .all_concepts()
+            .into_iter()
+            .map(|c| c.id)
+            .collect::<std::collections::BTreeSet<_>>();
+        for change in changes.created.iter().chain(&changes.updated) {
+            assert!(current.contains(&change.id));
+        }
+        for change in &changes.deleted {
+            assert!(!current.contains(&change.id));
+        }
+        assert!(changes.created.iter().any(|c| matches!(
+            &c.reason,
+            crate::L2ChangeReason::L1Added(added) if !added.is_empty()
+        )));
+
+        if let Some(draft) = vm.generate_drafts().expect("drafts").first() {
+            let changes = vm.commit_draft(&draft.draft_id).expect("commit");
+            assert!(!changes.is_empty());
+        }
+    }
+
+    #[test]
+    fn grounding_search_consults_providers_once_per_query() {
+        let mut vm = HybridVM::in_memory(StructuralEvaluator::default()).expect("vm");
+        let concept = vm.analyze_text("クラウドは使わない").expect("analyze");
+        vm.register_grounding_provider(
+            crate::FnProvider::new("docs", |request: &crate::GroundingRequest, _: &_| {
+                Ok(vec![format!("docs: {}", request.query)])
+            }),
+            crate::ProviderLimits::default(),
+        );
+
+        let outcome = vm
+            .run_grounding_search_with(concept.id, " on-prem ", &crate::CancelToken::new())
+            .expect("search");
+        assert!(!outcome.cached);
+        assert_eq!(
+            outcome.hits.last().map(|hit| hit.provider.as_str()),
+            Some("docs")
+        );
+        let lines = vm
+            .run_grounding_search(concept.id, "on-prem")
+            .expect("cached search");
+        assert_eq!(lines.last().map(String::as_str), Some("docs: on-prem"));
+        let grounding = vm.export_l2_grounding();
+        assert_eq!(grounding[0].1.len(), outcome.hits.len());
+        assert!(vm.run_grounding_search(concept.id, "  ").is_err());
+    }
+
+    #[test]
+    fn snapshot_history_yields_grounding_timeline() {
+        let mut vm = HybridVM::in_memory(StructuralEvaluator::default()).expect("vm");
+        let concept = vm.analyze_text("クラウドは使わない").expect("analyze");
+        let before = vm.snapshot_v2().expect("before");
+        vm.update_l2_with_grounding(concept.id, "on-prem deployment guide")
+            .expect("ground");
+        let after = vm.snapshot_v2().expect("after");
+
+        let timeline = crate::concept_timeline(&[before, after], concept.id).expect("timeline");
+        assert_eq!(timeline.points[1].grounding_count, 1);
+        assert_eq!(
+            timeline.events.last().map(|e| &e.kind),
+            Some(&crate::TimelineEventKind::Grounded { added: 1 })
+        );
+    }
+
     #[test]
     fn rfc014_grounding_update_is_reflected_in_detail() {
         let store_dir = std::env::temp_dir().join(format!(
@@ -1941,4 +4779,323 @@ mod tests {
                 .any(|g| g.contains("OWASP ASVS controls"))
         );
     }
+
+    #[test]
+    fn in_memory_vm_analyzes_without_persisting() {
+        let mut vm = HybridVM::in_memory(StructuralEvaluator::default()).expect("vm");
+        let concept = vm.analyze_text("応答時間を短縮する").expect("analyze");
+        assert!(!concept.l1_refs.is_empty());
+        assert_eq!(vm.project_phase_a_v2().expect("project").len(), 1);
+
+        let fresh = HybridVM::in_memory(StructuralEvaluator::default()).expect("fresh vm");
+        assert!(fresh.all_l1_units_v2().expect("l1").is_empty());
+    }
+
+    #[test]
+    fn memory_limits_evict_caches_then_refuse_to_grow() {
+        let evaluator = StructuralEvaluator::default();
+        let parent = state_with_graph(4, &[(1, 2), (2, 3)]);
+        let child = memory_space::DesignState::new(
+            Uuid::from_u128(50),
+            Arc::new(
+                parent
+                    .graph
+                    .with_edge_added(Uuid::from_u128(3), Uuid::from_u128(4)),
+            ),
+            "history:1",
+        );
+        evaluator.evaluate_child(&parent, &child);
+        let mut vm = HybridVM::in_memory(evaluator.clone()).expect("vm");
+        vm.analyze_text("応答時間を短縮する").expect("analyze");
+
+        let report = vm.memory_report();
+        let l1 = report
+            .component(MemoryComponent::SemanticL1Store)
+            .expect("l1 store");
+        assert!(l1.entries > 0 && l1.bytes > 0);
+        let aggregates = report
+            .component(MemoryComponent::AggregateCache)
+            .expect("aggregates");
+        assert_eq!(aggregates.entries, 2);
+        assert!(report.total_bytes() > report.evictable_bytes());
+
+        vm.set_memory_limits(MemoryLimits {
+            soft_bytes: Some(1),
+            hard_bytes: None,
+        });
+        let evicted = vm.enforce_memory_limits().expect("soft limit only evicts");
+        assert!(evicted.evicted_entries >= 2);
+        assert_eq!(evaluator.cached_aggregates(), 0);
+
+        let hard = evicted.total_bytes() - 1;
+        vm.set_memory_limits(MemoryLimits {
+            soft_bytes: None,
+            hard_bytes: Some(hard),
+        });
+        match vm.enforce_memory_limits() {
+            Err(HybridVmError::MemoryLimit(err)) => {
+                assert_eq!(err.hard_bytes, hard);
+                assert!(err.used_bytes > hard);
+            }
+            other => panic!("expected a memory limit error, got {other:?}"),
+        }
+        let units = vm.all_l1_units_v2().expect("l1").len();
+        assert!(vm.analyze_text("クラウドは使わない").is_err());
+        assert_eq!(vm.all_l1_units_v2().expect("l1").len(), units);
+
+        vm.set_memory_limits(MemoryLimits::default());
+        assert!(vm.analyze_text("クラウドは使わない").is_ok());
+    }
+
+    #[test]
+    fn workspace_stats_aggregates_layers_feedback_and_stores() {
+        let mut vm = HybridVM::in_memory(StructuralEvaluator::default()).expect("vm");
+        let empty = vm.workspace_stats().expect("stats");
+        assert_eq!(empty.l1.total, 0);
+        assert_eq!(empty.grounding_coverage, 0.0);
+        assert_eq!(empty.last_modified.l2_updated_at_secs, None);
+
+        vm.analyze_text("応答時間を短縮する").expect("analyze");
+        vm.analyze_text("クラウドは使わない").expect("analyze");
+        vm.record_feedback("draft-a", FeedbackAction::Adopt);
+        vm.record_feedback("draft-b", FeedbackAction::Reject);
+        vm.record_feedback("draft-c", FeedbackAction::Adopt);
+
+        let stats = vm.workspace_stats().expect("stats");
+        let l1_units = vm.all_l1_units_v2().expect("l1").len();
+        assert_eq!(stats.l1.total, l1_units);
+        let roles = stats.l1.by_role;
+        assert_eq!(
+            roles.goal + roles.constraint + roles.optimization + roles.prohibition,
+            l1_units
+        );
+        assert_eq!(
+            stats.l1.by_status.hypothetical + stats.l1.by_status.grounded,
+            l1_units
+        );
+        assert_eq!(stats.ambiguity.samples(), l1_units);
+        assert_eq!(stats.l2.total, vm.semantic_dhm.all_concepts().len());
+        assert_eq!(stats.stability.samples(), stats.l2.total);
+        assert_eq!(
+            stats.feedback,
+            FeedbackTotals {
+                adopted: 2,
+                rejected: 1
+            }
+        );
+        assert!(stats.last_modified.feedback_at_secs.is_some());
+        assert!(stats.last_modified.l2_updated_at_secs.is_some());
+        let components: Vec<_> = stats.stores.iter().map(|c| c.component).collect();
+        assert_eq!(
+            components,
+            [
+                MemoryComponent::LanguageStore,
+                MemoryComponent::SemanticL1Store,
+                MemoryComponent::SemanticL2Store
+            ]
+        );
+
+        let json = serde_json::to_string(&stats).expect("serialize");
+        let back: WorkspaceStats = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(back, stats);
+    }
+
+    #[test]
+    fn requirement_import_dry_run_matches_the_commit_and_skips_known_records() {
+        let mut vm = HybridVM::in_memory(StructuralEvaluator::default()).expect("vm");
+        vm.analyze_text("応答時間を短縮する").expect("analyze");
+        let records = crate::RequirementFormat::Csv
+            .parse(
+                "id,text,type,priority\n\
+                 R1,Keep latency under 200ms,constraint,must\n\
+                 R2,Avoid vendor lock-in,,could\n",
+            )
+            .expect("csv");
+        let units = vm.all_l1_units_v2().expect("l1").len();
+        let concepts = vm.semantic_dhm.all_concepts();
+
+        let plan = vm
+            .plan_requirement_import("backlog.csv", &records)
+            .expect("plan");
+        assert!(plan.dry_run);
+        assert_eq!(plan.imported.len(), 2);
+        assert!(!plan.l2_changes.is_empty());
+        assert_eq!(vm.all_l1_units_v2().expect("l1").len(), units);
+        assert_eq!(vm.semantic_dhm.all_concepts(), concepts);
+
+        let report = vm
+            .import_requirements("backlog.csv", &records)
+            .expect("import");
+        assert!(!report.dry_run);
+        assert_eq!(report.imported, plan.imported);
+        assert_eq!(report.l2_changes, plan.l2_changes);
+        let r1 = &report.imported[0];
+        assert_eq!(r1.role, RequirementRole::Constraint);
+        assert_eq!(report.imported[1].role, RequirementRole::Prohibition);
+        assert_eq!(
+            vm.l1_priority(r1.l1_id),
+            Some(crate::RequirementPriority::Must)
+        );
+        let provenance = vm.l1_provenance(r1.l1_id).expect("provenance");
+        assert_eq!(provenance.record_id.as_deref(), Some("R1"));
+        assert_eq!(provenance.to_string(), "backlog.csv#R1");
+
+        let again = vm
+            .import_requirements("backlog.csv", &records)
+            .expect("reimport");
+        assert!(again.imported.is_empty());
+        assert_eq!(again.skipped, ["R1", "R2"]);
+        assert_eq!(vm.all_l1_units_v2().expect("l1").len(), units + 2);
+    }
+
+    #[test]
+    fn semantic_events_and_evaluations_share_seq_and_correlation_id() {
+        let mut vm = HybridVM::in_memory(StructuralEvaluator::default()).expect("vm");
+        vm.analyze_text("高速化を重視する")
+            .expect("untagged analysis");
+        let run = crate::CorrelationId(42);
+        vm.set_correlation_id(Some(run));
+        vm.analyze_text("セキュリティを確保する").expect("analysis");
+        vm.rebuild_l2_from_l1_with_mode(crate::L2Mode::Stable)
+            .expect("rebuild");
+        vm.evaluate(&state_with_graph(3, &[(1, 2)]));
+        vm.set_correlation_id(None);
+
+        let events = vm.take_semantic_trace();
+        let trace = vm.take_trace();
+        assert_eq!(events.iter().map(|e| e.seq).collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(events[0].correlation_id, None);
+        assert!(events[1..].iter().all(|e| e.correlation_id == Some(run)));
+        assert_eq!(events[2].operation.name(), "rebuild_l2");
+        assert_eq!((trace[0].seq, trace[0].correlation_id), (3, Some(run)));
+        assert_eq!(vm.trace_seq(), 4);
+    }
+
+    fn concept_with_links(id: u64, links: &[(u128, u128, f64)]) -> ConceptUnitV2 {
+        ConceptUnitV2 {
+            id: ConceptId(id),
+            derived_requirements: vec![
+                DerivedRequirement {
+                    kind: RequirementKind::Performance,
+                    strength: 0.8,
+                },
+                DerivedRequirement {
+                    kind: RequirementKind::Memory,
+                    strength: 0.05,
+                },
+            ],
+            causal_links: links
+                .iter()
+                .map(|(from, to, weight)| CausalEdge {
+                    from: L1Id(*from),
+                    to: L1Id(*to),
+                    weight: *weight,
+                })
+                .collect(),
+            stability_score: 0.9,
+        }
+    }
+
+    #[test]
+    fn concept_graph_builder_maps_requirements_and_causal_links() {
+        let concepts = vec![
+            concept_with_links(2, &[(10, 11, 0.5), (11, 12, 0.25)]),
+            concept_with_links(1, &[]),
+        ];
+        let built = ConceptGraphBuilder::new().build(&concepts);
+        let graph = &built.state.graph;
+
+        // 2 anchors + 2 Performance nodes (Memory is below threshold) + 3 elements.
+        assert_eq!(graph.nodes().len(), 7);
+        assert_eq!(built.edge_weights.len(), 2);
+        assert!(built.dropped_links.is_empty());
+        assert_eq!(built.nodes_for_concept(ConceptId(2)).len(), 5);
+        for (id, origin) in &built.origins {
+            let node = graph.nodes().get(id).expect("node exists");
+            assert_eq!(
+                node.attributes.get("concept_id"),
+                Some(&memory_space::Value::Int(origin.concept.0 as i64))
+            );
+            if let NodeSource::Element(_) = origin.source {
+                assert_eq!(node.kind, "Performance");
+            }
+        }
+        let (&(from, _), weight) = built.edge_weights.iter().next().expect("edge");
+        assert!(
+            graph.nodes()[&from]
+                .attributes
+                .keys()
+                .any(|k| k.starts_with("causal_weight:"))
+        );
+        assert!(*weight > 0.0);
+        assert!(graph.is_dag());
+    }
+
+    #[test]
+    fn concept_graph_builder_drops_cycle_closing_links() {
+        let concepts = vec![concept_with_links(
+            1,
+            &[(1, 2, 1.0), (2, 3, 1.0), (3, 1, 1.0)],
+        )];
+        let built = ConceptGraphBuilder::new().build(&concepts);
+        assert_eq!(built.edge_weights.len(), 2);
+        assert_eq!(built.dropped_links.len(), 1);
+        assert_eq!(built.dropped_links[0].1.from, L1Id(3));
+        assert!(built.state.graph.is_dag());
+    }
+
+    #[test]
+    fn concept_graph_builder_keeps_shared_l1_elements_per_concept() {
+        let concepts = vec![
+            concept_with_links(1, &[(10, 11, 0.5)]),
+            concept_with_links(2, &[(10, 12, 0.5)]),
+        ];
+        let built = ConceptGraphBuilder::new().build(&concepts);
+        assert_eq!(built.nodes_for_concept(ConceptId(1)).len(), 4);
+        assert_eq!(built.nodes_for_concept(ConceptId(2)).len(), 4);
+        assert_ne!(
+            crate::concept_graph::element_node_id(ConceptId(1), L1Id(10)),
+            crate::concept_graph::element_node_id(ConceptId(2), L1Id(10))
+        );
+        assert_eq!(built.edge_weights.len(), 2);
+    }
+
+    #[test]
+    fn annotate_state_matches_seed_and_rule_generated_nodes_to_requirements() {
+        let concepts = vec![concept_with_links(3, &[(20, 21, 1.0)])];
+        let seed = ConceptGraphBuilder::new().build(&concepts).state;
+        let mut attrs = BTreeMap::new();
+        attrs.insert(
+            "generated_by_1004".to_string(),
+            memory_space::Value::Bool(true),
+        );
+        let generated = Uuid::from_u128(0xABCD);
+        let graph = seed
+            .graph
+            .with_node_added(DesignNode::new(generated, "GeneratedNode", attrs));
+        let state =
+            memory_space::DesignState::new(Uuid::from_u128(9), Arc::new(graph), "history:1004");
+
+        let report = annotate_state_with_requirements(&state, &concepts);
+        assert_eq!(report.entries.len(), 2);
+        let perf = report
+            .entries
+            .iter()
+            .find(|e| e.kind == RequirementKind::Performance)
+            .expect("performance entry");
+        assert!(perf.matched_nodes.contains(&generated));
+        assert_eq!(perf.supporting_rules.len(), 1);
+        assert!(perf.satisfied);
+        assert!(perf.confidence >= ConfidenceLevel::Medium);
+
+        let memory = report
+            .entries
+            .iter()
+            .find(|e| e.kind == RequirementKind::Memory)
+            .expect("memory entry");
+        assert!(memory.matched_nodes.is_empty());
+        assert!(!memory.satisfied);
+        assert_eq!(memory.confidence, ConfidenceLevel::Low);
+        assert_eq!(report.satisfied_count(), 1);
+    }
 }
@@ -0,0 +1,160 @@
+//! Approximate memory accounting for a VM and the limits enforced on it.
+//!
+//! Stores are measured as entries times encoded size, which tracks what a
+//! workspace holds rather than allocator overhead; caches are counted with a
+//! flat per-entry estimate. Crossing the soft limit evicts the caches, which
+//! can be rebuilt; crossing the hard limit, after eviction, is reported as a
+//! `MemoryLimitExceeded` instead of letting the process run out of memory.
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+/// Estimated bytes per state in the evaluator's aggregate cache; aggregates
+/// grow with the graph, so this stands for a mid-sized design.
+pub(crate) const AGGREGATE_ENTRY_BYTES: usize = 2048;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum MemoryComponent {
+    LanguageStore,
+    SemanticL1Store,
+    SemanticL2Store,
+    /// Recall entries `Dhm` keeps in memory.
+    RecallCache,
+    /// Per-state graph aggregates of the structural evaluator.
+    AggregateCache,
+    /// Evaluation rows not yet taken with `take_trace`.
+    Trace,
+}
+
+impl MemoryComponent {
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::LanguageStore => "language_store",
+            Self::SemanticL1Store => "semantic_l1_store",
+            Self::SemanticL2Store => "semantic_l2_store",
+            Self::RecallCache => "recall_cache",
+            Self::AggregateCache => "aggregate_cache",
+            Self::Trace => "trace",
+        }
+    }
+
+    /// Whether `HybridVM::evict_caches` can free it.
+    pub fn is_evictable(self) -> bool {
+        matches!(self, Self::RecallCache | Self::AggregateCache)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ComponentUsage {
+    pub component: MemoryComponent,
+    pub entries: usize,
+    pub bytes: usize,
+}
+
+/// Both limits are in bytes of `MemoryReport::total_bytes`; `None` turns a
+/// limit off. With neither set, nothing is measured on ingest.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryLimits {
+    pub soft_bytes: Option<usize>,
+    pub hard_bytes: Option<usize>,
+}
+
+impl MemoryLimits {
+    pub fn is_set(&self) -> bool {
+        self.soft_bytes.is_some() || self.hard_bytes.is_some()
+    }
+
+    pub fn over_soft(&self, bytes: usize) -> bool {
+        self.soft_bytes.is_some_and(|limit| bytes > limit)
+    }
+
+    pub fn over_hard(&self, bytes: usize) -> bool {
+        self.hard_bytes.is_some_and(|limit| bytes > limit)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryReport {
+    pub components: Vec<ComponentUsage>,
+    pub limits: MemoryLimits,
+    /// Entries freed by the eviction that preceded this report, if any.
+    pub evicted_entries: usize,
+}
+
+impl MemoryReport {
+    pub fn total_bytes(&self) -> usize {
+        self.components.iter().map(|c| c.bytes).sum()
+    }
+
+    pub fn evictable_bytes(&self) -> usize {
+        self.components
+            .iter()
+            .filter(|c| c.component.is_evictable())
+            .map(|c| c.bytes)
+            .sum()
+    }
+
+    pub fn component(&self, component: MemoryComponent) -> Option<&ComponentUsage> {
+        self.components.iter().find(|c| c.component == component)
+    }
+
+    pub fn over_soft_limit(&self) -> bool {
+        self.limits.over_soft(self.total_bytes())
+    }
+
+    pub fn over_hard_limit(&self) -> bool {
+        self.limits.over_hard(self.total_bytes())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryLimitExceeded {
+    pub used_bytes: usize,
+    pub hard_bytes: usize,
+}
+
+impl fmt::Display for MemoryLimitExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "memory use of {} bytes exceeds the hard limit of {} bytes",
+            self.used_bytes, self.hard_bytes
+        )
+    }
+}
+
+impl std::error::Error for MemoryLimitExceeded {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_totals_and_limits() {
+        let report = MemoryReport {
+            components: vec![
+                ComponentUsage {
+                    component: MemoryComponent::SemanticL1Store,
+                    entries: 2,
+                    bytes: 300,
+                },
+                ComponentUsage {
+                    component: MemoryComponent::AggregateCache,
+                    entries: 1,
+                    bytes: AGGREGATE_ENTRY_BYTES,
+                },
+            ],
+            limits: MemoryLimits {
+                soft_bytes: Some(1000),
+                hard_bytes: Some(5000),
+            },
+            evicted_entries: 0,
+        };
+        assert_eq!(report.total_bytes(), 300 + AGGREGATE_ENTRY_BYTES);
+        assert_eq!(report.evictable_bytes(), AGGREGATE_ENTRY_BYTES);
+        assert!(report.over_soft_limit() && !report.over_hard_limit());
+        assert!(!MemoryLimits::default().is_set());
+        assert!(!MemoryLimits::default().over_hard(usize::MAX));
+    }
+}
@@ -0,0 +1,209 @@
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::ExecutionMode;
+
+/// Upper bounds (seconds) of the latency histogram buckets. `+Inf` is implicit.
+pub const LATENCY_BUCKETS_SECONDS: [f64; 10] =
+    [0.0005, 0.001, 0.005, 0.01, 0.025, 0.05, 0.1, 0.5, 1.0, 5.0];
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LatencyHistogram {
+    /// Non-cumulative counts per bucket; the final slot is the `+Inf` overflow.
+    pub buckets: [u64; LATENCY_BUCKETS_SECONDS.len() + 1],
+    pub sum_seconds: f64,
+    pub count: u64,
+}
+
+impl LatencyHistogram {
+    pub fn observe(&mut self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        let slot = LATENCY_BUCKETS_SECONDS
+            .iter()
+            .position(|le| secs <= *le)
+            .unwrap_or(LATENCY_BUCKETS_SECONDS.len());
+        self.buckets[slot] = self.buckets[slot].saturating_add(1);
+        self.sum_seconds += secs;
+        self.count = self.count.saturating_add(1);
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MetricsSnapshot {
+    pub evaluations_recall_first: u64,
+    pub evaluations_compute_first: u64,
+    pub recalls: u64,
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub store_sizes: BTreeMap<&'static str, u64>,
+    pub latencies: BTreeMap<&'static str, LatencyHistogram>,
+}
+
+/// Process-local metric registry shared by a `HybridVM` and whoever exposes it.
+/// Rendering is plain text so an HTTP layer can serve `gather()` as-is.
+#[derive(Debug, Default)]
+pub struct MetricsRegistry {
+    state: Mutex<MetricsSnapshot>,
+}
+
+impl MetricsRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record_evaluation(&self, mode: ExecutionMode) {
+        self.update(|s| match mode {
+            ExecutionMode::RecallFirst => s.evaluations_recall_first += 1,
+            ExecutionMode::ComputeFirst => s.evaluations_compute_first += 1,
+        });
+    }
+
+    pub fn record_recall(&self) {
+        self.update(|s| s.recalls += 1);
+    }
+
+    /// One recall lookup; a hit means memory held an objective similar to the
+    /// one being evaluated.
+    pub fn record_cache(&self, hit: bool) {
+        self.update(|s| {
+            if hit {
+                s.cache_hits += 1;
+            } else {
+                s.cache_misses += 1;
+            }
+        });
+    }
+
+    pub fn set_store_size(&self, store: &'static str, size: usize) {
+        self.update(|s| {
+            s.store_sizes.insert(store, size as u64);
+        });
+    }
+
+    pub fn observe_latency(&self, op: &'static str, elapsed: Duration) {
+        self.update(|s| s.latencies.entry(op).or_default().observe(elapsed));
+    }
+
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        self.state
+            .lock()
+            .map(|s| s.clone())
+            .unwrap_or_else(|poisoned| poisoned.into_inner().clone())
+    }
+
+    pub fn reset(&self) {
+        self.update(|s| *s = MetricsSnapshot::default());
+    }
+
+    /// Renders all metrics in the Prometheus text exposition format (0.0.4).
+    pub fn gather(&self) -> String {
+        render_prometheus(&self.snapshot())
+    }
+
+    fn update(&self, f: impl FnOnce(&mut MetricsSnapshot)) {
+        let mut guard = self
+            .state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        f(&mut guard);
+    }
+}
+
+pub fn render_prometheus(snapshot: &MetricsSnapshot) -> String {
+    let mut out = String::new();
+
+    write_header(
+        &mut out,
+        "hybrid_vm_evaluations_total",
+        "Objective evaluations by execution mode.",
+        "counter",
+    );
+    let _ = writeln!(
+        out,
+        "hybrid_vm_evaluations_total{{mode=\"recall_first\"}} {}",
+        snapshot.evaluations_recall_first
+    );
+    let _ = writeln!(
+        out,
+        "hybrid_vm_evaluations_total{{mode=\"compute_first\"}} {}",
+        snapshot.evaluations_compute_first
+    );
+
+    write_header(
+        &mut out,
+        "hybrid_vm_recalls_total",
+        "Semantic recall operations (compare/recommend).",
+        "counter",
+    );
+    let _ = writeln!(out, "hybrid_vm_recalls_total {}", snapshot.recalls);
+
+    write_header(
+        &mut out,
+        "hybrid_vm_cache_hits_total",
+        "Memory recall lookups that found a similar remembered objective.",
+        "counter",
+    );
+    let _ = writeln!(out, "hybrid_vm_cache_hits_total {}", snapshot.cache_hits);
+    write_header(
+        &mut out,
+        "hybrid_vm_cache_misses_total",
+        "Memory recall lookups that found no similar remembered objective.",
+        "counter",
+    );
+    let _ = writeln!(
+        out,
+        "hybrid_vm_cache_misses_total {}",
+        snapshot.cache_misses
+    );
+
+    write_header(
+        &mut out,
+        "hybrid_vm_store_size",
+        "Number of entries per semantic store.",
+        "gauge",
+    );
+    for (store, size) in &snapshot.store_sizes {
+        let _ = writeln!(out, "hybrid_vm_store_size{{store=\"{store}\"}} {size}");
+    }
+
+    write_header(
+        &mut out,
+        "hybrid_vm_op_latency_seconds",
+        "Latency of HybridVM operations.",
+        "histogram",
+    );
+    for (op, hist) in &snapshot.latencies {
+        let mut cumulative = 0u64;
+        for (le, count) in LATENCY_BUCKETS_SECONDS.iter().zip(hist.buckets.iter()) {
+            cumulative += count;
+            let _ = writeln!(
+                out,
+                "hybrid_vm_op_latency_seconds_bucket{{op=\"{op}\",le=\"{le}\"}} {cumulative}"
+            );
+        }
+        let _ = writeln!(
+            out,
+            "hybrid_vm_op_latency_seconds_bucket{{op=\"{op}\",le=\"+Inf\"}} {}",
+            hist.count
+        );
+        let _ = writeln!(
+            out,
+            "hybrid_vm_op_latency_seconds_sum{{op=\"{op}\"}} {}",
+            hist.sum_seconds
+        );
+        let _ = writeln!(
+            out,
+            "hybrid_vm_op_latency_seconds_count{{op=\"{op}\"}} {}",
+            hist.count
+        );
+    }
+
+    out
+}
+
+fn write_header(out: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+}
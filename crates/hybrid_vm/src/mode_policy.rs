@@ -0,0 +1,232 @@
+//! Policies that move a `HybridVM` between `RecallFirst` and `ComputeFirst`
+//! as evaluations come in.
+//!
+//! `RecallFirst` only reads the recall memory; `ComputeFirst` also writes
+//! every evaluated objective back, which warms the memory at the cost of a
+//! store write per evaluation.
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Duration;
+
+use crate::ExecutionMode;
+
+/// What the VM saw for one evaluation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ModeObservation {
+    pub mode: ExecutionMode,
+    pub depth: usize,
+    /// Recall changed the computed objective.
+    pub recall_hit: bool,
+    pub latency: Duration,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModeSwitch {
+    pub from: ExecutionMode,
+    pub to: ExecutionMode,
+    pub reason: String,
+}
+
+impl fmt::Display for ModeSwitch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} -> {:?}: {}", self.from, self.to, self.reason)
+    }
+}
+
+/// Decides the mode for the next evaluation. Returning `None` keeps the
+/// current mode.
+pub trait ModePolicy: Send {
+    fn observe(&mut self, observation: &ModeObservation) -> Option<ModeSwitch>;
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AdaptiveModeConfig {
+    /// Evaluations judged together; the window restarts after a switch, so
+    /// this is also the minimum dwell time in a mode.
+    pub window: usize,
+    /// Below this recall hit rate `RecallFirst` falls back to `ComputeFirst`.
+    pub min_hit_rate: f64,
+    /// `ComputeFirst` returns to `RecallFirst` once the hit rate reaches this.
+    /// Keeping it above `min_hit_rate` stops the policy from flapping.
+    pub recover_hit_rate: f64,
+    /// Mean `ComputeFirst` latency above which a hit rate of at least
+    /// `min_hit_rate` is enough to return to `RecallFirst`.
+    pub max_compute_latency: Duration,
+}
+
+impl Default for AdaptiveModeConfig {
+    fn default() -> Self {
+        Self {
+            window: 32,
+            min_hit_rate: 0.2,
+            recover_hit_rate: 0.5,
+            max_compute_latency: Duration::from_millis(5),
+        }
+    }
+}
+
+/// Switches on windowed recall hit rate and latency, with a hysteresis band
+/// between `min_hit_rate` and `recover_hit_rate`.
+#[derive(Clone, Debug, Default)]
+pub struct AdaptiveModePolicy {
+    config: AdaptiveModeConfig,
+    recent: VecDeque<ModeObservation>,
+}
+
+impl AdaptiveModePolicy {
+    pub fn new(config: AdaptiveModeConfig) -> Self {
+        Self {
+            config,
+            recent: VecDeque::new(),
+        }
+    }
+
+    pub fn config(&self) -> AdaptiveModeConfig {
+        self.config
+    }
+
+    fn hit_rate(&self) -> f64 {
+        let hits = self.recent.iter().filter(|o| o.recall_hit).count();
+        hits as f64 / self.recent.len().max(1) as f64
+    }
+
+    fn mean_latency(&self) -> Duration {
+        let total = self.recent.iter().map(|o| o.latency).sum::<Duration>();
+        total / self.recent.len().max(1) as u32
+    }
+}
+
+impl ModePolicy for AdaptiveModePolicy {
+    fn observe(&mut self, observation: &ModeObservation) -> Option<ModeSwitch> {
+        // Observations from another mode (e.g. an explicit context) say
+        // nothing about the current one.
+        if self
+            .recent
+            .back()
+            .is_some_and(|last| last.mode != observation.mode)
+        {
+            self.recent.clear();
+        }
+        self.recent.push_back(*observation);
+        let window = self.config.window.max(1);
+        while self.recent.len() > window {
+            self.recent.pop_front();
+        }
+        if self.recent.len() < window {
+            return None;
+        }
+
+        let hit_rate = self.hit_rate();
+        let switch = match observation.mode {
+            ExecutionMode::RecallFirst if hit_rate < self.config.min_hit_rate => Some(ModeSwitch {
+                from: ExecutionMode::RecallFirst,
+                to: ExecutionMode::ComputeFirst,
+                reason: format!(
+                    "recall hit rate {hit_rate:.2} below {:.2}",
+                    self.config.min_hit_rate
+                ),
+            }),
+            ExecutionMode::ComputeFirst if hit_rate >= self.config.recover_hit_rate => {
+                Some(ModeSwitch {
+                    from: ExecutionMode::ComputeFirst,
+                    to: ExecutionMode::RecallFirst,
+                    reason: format!(
+                        "recall hit rate {hit_rate:.2} reached {:.2}",
+                        self.config.recover_hit_rate
+                    ),
+                })
+            }
+            ExecutionMode::ComputeFirst
+                if hit_rate >= self.config.min_hit_rate
+                    && self.mean_latency() > self.config.max_compute_latency =>
+            {
+                Some(ModeSwitch {
+                    from: ExecutionMode::ComputeFirst,
+                    to: ExecutionMode::RecallFirst,
+                    reason: format!(
+                        "mean latency {:?} above {:?} with recall hit rate {hit_rate:.2}",
+                        self.mean_latency(),
+                        self.config.max_compute_latency
+                    ),
+                })
+            }
+            _ => None,
+        };
+        if switch.is_some() {
+            self.recent.clear();
+        }
+        switch
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn observation(mode: ExecutionMode, recall_hit: bool, latency_ms: u64) -> ModeObservation {
+        ModeObservation {
+            mode,
+            depth: 1,
+            recall_hit,
+            latency: Duration::from_millis(latency_ms),
+        }
+    }
+
+    fn policy() -> AdaptiveModePolicy {
+        AdaptiveModePolicy::new(AdaptiveModeConfig {
+            window: 4,
+            ..AdaptiveModeConfig::default()
+        })
+    }
+
+    #[test]
+    fn low_recall_hit_rate_falls_back_to_compute_first() {
+        let mut policy = policy();
+        for _ in 0..3 {
+            assert_eq!(
+                policy.observe(&observation(ExecutionMode::RecallFirst, false, 0)),
+                None
+            );
+        }
+        let switch = policy
+            .observe(&observation(ExecutionMode::RecallFirst, false, 0))
+            .expect("switch");
+        assert_eq!(switch.to, ExecutionMode::ComputeFirst);
+        assert!(
+            switch
+                .to_string()
+                .starts_with("RecallFirst -> ComputeFirst")
+        );
+    }
+
+    #[test]
+    fn hit_rates_inside_the_hysteresis_band_keep_the_mode() {
+        // 1 of 4 hits: above min_hit_rate, below recover_hit_rate.
+        for mode in [ExecutionMode::RecallFirst, ExecutionMode::ComputeFirst] {
+            let mut policy = policy();
+            for hit in [true, false, false, false, true, false, false, false] {
+                assert_eq!(policy.observe(&observation(mode, hit, 0)), None);
+            }
+        }
+    }
+
+    #[test]
+    fn warm_or_slow_compute_first_returns_to_recall_first() {
+        let mut warm = policy();
+        let switches = [true, true, false, false]
+            .into_iter()
+            .filter_map(|hit| warm.observe(&observation(ExecutionMode::ComputeFirst, hit, 0)))
+            .collect::<Vec<_>>();
+        assert_eq!(switches.len(), 1);
+        assert_eq!(switches[0].to, ExecutionMode::RecallFirst);
+
+        let mut slow = policy();
+        let switches = [true, false, false, false]
+            .into_iter()
+            .filter_map(|hit| slow.observe(&observation(ExecutionMode::ComputeFirst, hit, 20)))
+            .collect::<Vec<_>>();
+        assert_eq!(switches.len(), 1);
+        assert!(switches[0].reason.contains("latency"));
+    }
+}
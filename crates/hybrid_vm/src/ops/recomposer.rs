@@ -4,13 +4,13 @@ use recomposer::{
 };
 use semantic_dhm::{ConceptId, ConceptQuery, ConceptUnit, ResonanceWeights, SemanticDhm};
 
-use memory_store::FileStore;
+use memory_store::BackedStore;
 
 use crate::HybridVmError;
 use crate::ops::util::{dedup_ids, dot_norm};
 
 pub(crate) fn compare(
-    semantic_dhm: &SemanticDhm<FileStore<ConceptId, ConceptUnit>>,
+    semantic_dhm: &SemanticDhm<BackedStore<ConceptId, ConceptUnit>>,
     left: ConceptId,
     right: ConceptId,
 ) -> Result<ResonanceReport, HybridVmError> {
@@ -42,7 +42,7 @@ pub(crate) fn compare(
 }
 
 pub(crate) fn explain_multiple(
-    semantic_dhm: &SemanticDhm<FileStore<ConceptId, ConceptUnit>>,
+    semantic_dhm: &SemanticDhm<BackedStore<ConceptId, ConceptUnit>>,
     recomposer: &Recomposer,
     concept_ids: &[ConceptId],
 ) -> Result<recomposer::MultiExplanation, HybridVmError> {
@@ -67,7 +67,7 @@ pub(crate) fn explain_multiple(
 }
 
 pub(crate) fn recommend(
-    semantic_dhm: &SemanticDhm<FileStore<ConceptId, ConceptUnit>>,
+    semantic_dhm: &SemanticDhm<BackedStore<ConceptId, ConceptUnit>>,
     recomposer: &Recomposer,
     query_id: ConceptId,
     top_k: usize,
@@ -95,7 +95,7 @@ pub(crate) fn recommend(
 }
 
 pub(crate) fn design_report(
-    semantic_dhm: &SemanticDhm<FileStore<ConceptId, ConceptUnit>>,
+    semantic_dhm: &SemanticDhm<BackedStore<ConceptId, ConceptUnit>>,
     recomposer: &Recomposer,
     concept_ids: &[ConceptId],
     top_k: usize,
@@ -119,7 +119,7 @@ pub(crate) fn design_report(
 }
 
 pub(crate) fn decide(
-    semantic_dhm: &SemanticDhm<FileStore<ConceptId, ConceptUnit>>,
+    semantic_dhm: &SemanticDhm<BackedStore<ConceptId, ConceptUnit>>,
     recomposer: &Recomposer,
     ids: &[ConceptId],
     weights: DecisionWeights,
@@ -144,7 +144,7 @@ pub(crate) fn decide(
 
 #[allow(dead_code)]
 pub(crate) fn weights(
-    semantic_dhm: &SemanticDhm<FileStore<ConceptId, ConceptUnit>>,
+    semantic_dhm: &SemanticDhm<BackedStore<ConceptId, ConceptUnit>>,
 ) -> ResonanceWeights {
     semantic_dhm.weights()
 }
@@ -1,57 +1,74 @@
+use std::collections::BTreeMap;
+
+use core_types::progress::ProgressSink;
 use design_reasoning::{
     DesignHypothesis, Explanation, HypothesisEngine, LanguageEngine, MeaningEngine,
     MeaningLayerSnapshotV2, ProjectionEngine, SnapshotDiffV2, SnapshotEngine,
 };
 use language_dhm::{LangId, LanguageDhm, LanguageUnit};
-use memory_store::FileStore;
+use memory_store::BackedStore;
 use semantic_dhm::{
-    ConceptId, ConceptUnit, L1Id, L2Config, L2Mode, MeaningLayerSnapshot, SemanticDhm,
-    SemanticError, SemanticL1Dhm, SemanticUnitL1,
+    ConceptId, ConceptUnit, L1Id, L2ChangeSet, L2Config, L2Mode, MeaningLayerSnapshot,
+    RequirementKind, RequirementPriority, SemanticDhm, SemanticError, SemanticL1Dhm,
+    SemanticUnitL1,
 };
 
 pub(crate) fn analyze_text(
     meaning_engine: &MeaningEngine,
     text: &str,
-    language_dhm: &mut LanguageDhm<FileStore<LangId, LanguageUnit>>,
-    semantic_l1_dhm: &mut SemanticL1Dhm<FileStore<L1Id, SemanticUnitL1>>,
-    semantic_dhm: &mut SemanticDhm<FileStore<ConceptId, ConceptUnit>>,
+    language_dhm: &mut LanguageDhm<BackedStore<LangId, LanguageUnit>>,
+    semantic_l1_dhm: &mut SemanticL1Dhm<BackedStore<L1Id, SemanticUnitL1>>,
+    semantic_dhm: &mut SemanticDhm<BackedStore<ConceptId, ConceptUnit>>,
+    in_context: impl Fn(L1Id) -> bool,
 ) -> Result<ConceptUnit, SemanticError> {
-    meaning_engine.analyze_text(text, language_dhm, semantic_l1_dhm, semantic_dhm)
+    meaning_engine.analyze_text_in_context(
+        text,
+        language_dhm,
+        semantic_l1_dhm,
+        semantic_dhm,
+        in_context,
+    )
 }
 
 pub(crate) fn rebuild_l2_from_l1(
-    semantic_l1_dhm: &SemanticL1Dhm<FileStore<L1Id, SemanticUnitL1>>,
-    semantic_dhm: &mut SemanticDhm<FileStore<ConceptId, ConceptUnit>>,
-) -> Result<(), SemanticError> {
-    let l1 = semantic_l1_dhm.all_units();
-    semantic_dhm.rebuild_l2_from_l1(&l1)
+    l1: &[SemanticUnitL1],
+    semantic_dhm: &mut SemanticDhm<BackedStore<ConceptId, ConceptUnit>>,
+) -> Result<L2ChangeSet, SemanticError> {
+    semantic_dhm.rebuild_l2_from_l1(l1)
 }
 
 pub(crate) fn rebuild_l2_from_l1_with_config(
-    semantic_l1_dhm: &SemanticL1Dhm<FileStore<L1Id, SemanticUnitL1>>,
-    semantic_dhm: &mut SemanticDhm<FileStore<ConceptId, ConceptUnit>>,
+    l1: &[SemanticUnitL1],
+    semantic_dhm: &mut SemanticDhm<BackedStore<ConceptId, ConceptUnit>>,
     config: L2Config,
-) -> Result<(), SemanticError> {
-    let l1 = semantic_l1_dhm.all_units();
-    semantic_dhm.rebuild_l2_from_l1_with_config(&l1, config)
+) -> Result<L2ChangeSet, SemanticError> {
+    semantic_dhm.rebuild_l2_from_l1_with_config(l1, config)
+}
+
+pub(crate) fn rebuild_l2_from_l1_with_progress(
+    l1: &[SemanticUnitL1],
+    semantic_dhm: &mut SemanticDhm<BackedStore<ConceptId, ConceptUnit>>,
+    config: L2Config,
+    progress: &dyn ProgressSink,
+) -> Result<L2ChangeSet, SemanticError> {
+    semantic_dhm.rebuild_l2_from_l1_with_progress(l1, config, progress)
 }
 
 pub(crate) fn rebuild_l2_from_l1_with_mode(
-    semantic_l1_dhm: &SemanticL1Dhm<FileStore<L1Id, SemanticUnitL1>>,
-    semantic_dhm: &mut SemanticDhm<FileStore<ConceptId, ConceptUnit>>,
+    l1: &[SemanticUnitL1],
+    semantic_dhm: &mut SemanticDhm<BackedStore<ConceptId, ConceptUnit>>,
     mode: L2Mode,
-) -> Result<(), SemanticError> {
-    let l1 = semantic_l1_dhm.all_units();
-    semantic_dhm.rebuild_l2_from_l1_with_mode(&l1, mode)
+) -> Result<L2ChangeSet, SemanticError> {
+    semantic_dhm.rebuild_l2_from_l1_with_mode(l1, mode)
 }
 
 pub(crate) fn snapshot(
     snapshot_engine: &SnapshotEngine,
-    semantic_l1_dhm: &SemanticL1Dhm<FileStore<L1Id, SemanticUnitL1>>,
-    semantic_dhm: &SemanticDhm<FileStore<ConceptId, ConceptUnit>>,
+    semantic_l1_dhm: &SemanticL1Dhm<BackedStore<L1Id, SemanticUnitL1>>,
+    semantic_dhm: &SemanticDhm<BackedStore<ConceptId, ConceptUnit>>,
 ) -> Result<MeaningLayerSnapshot, SemanticError> {
     snapshot_engine.snapshot(
-        semantic_dhm.l2_config().algorithm_version,
+        semantic_dhm.l2_config(),
         semantic_l1_dhm.all_units(),
         semantic_dhm.all_concepts(),
     )
@@ -59,8 +76,8 @@ pub(crate) fn snapshot(
 
 pub(crate) fn snapshot_v2(
     snapshot_engine: &SnapshotEngine,
-    semantic_l1_dhm: &SemanticL1Dhm<FileStore<L1Id, SemanticUnitL1>>,
-    semantic_dhm: &SemanticDhm<FileStore<ConceptId, ConceptUnit>>,
+    semantic_l1_dhm: &SemanticL1Dhm<BackedStore<L1Id, SemanticUnitL1>>,
+    semantic_dhm: &SemanticDhm<BackedStore<ConceptId, ConceptUnit>>,
 ) -> Result<MeaningLayerSnapshotV2, SemanticError> {
     snapshot_engine.make_snapshot_v2(&semantic_l1_dhm.all_units(), &semantic_dhm.all_concepts())
 }
@@ -75,20 +92,23 @@ pub(crate) fn compare_snapshots_v2(
 
 pub(crate) fn project_phase_a(
     projection_engine: &ProjectionEngine,
-    semantic_l1_dhm: &SemanticL1Dhm<FileStore<L1Id, SemanticUnitL1>>,
-    semantic_dhm: &SemanticDhm<FileStore<ConceptId, ConceptUnit>>,
+    semantic_l1_dhm: &SemanticL1Dhm<BackedStore<L1Id, SemanticUnitL1>>,
+    semantic_dhm: &SemanticDhm<BackedStore<ConceptId, ConceptUnit>>,
 ) -> semantic_dhm::DesignProjection {
     projection_engine.project_phase_a(&semantic_dhm.all_concepts(), &semantic_l1_dhm.all_units())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub(crate) fn evaluate_design(
     text: &str,
+    priorities: &BTreeMap<RequirementKind, RequirementPriority>,
     meaning_engine: &MeaningEngine,
     projection_engine: &ProjectionEngine,
     hypothesis_engine: &HypothesisEngine,
-    language_dhm: &mut LanguageDhm<FileStore<LangId, LanguageUnit>>,
-    semantic_l1_dhm: &mut SemanticL1Dhm<FileStore<L1Id, SemanticUnitL1>>,
-    semantic_dhm: &mut SemanticDhm<FileStore<ConceptId, ConceptUnit>>,
+    language_dhm: &mut LanguageDhm<BackedStore<LangId, LanguageUnit>>,
+    semantic_l1_dhm: &mut SemanticL1Dhm<BackedStore<L1Id, SemanticUnitL1>>,
+    semantic_dhm: &mut SemanticDhm<BackedStore<ConceptId, ConceptUnit>>,
+    in_context: impl Fn(L1Id) -> bool,
 ) -> Result<DesignHypothesis, SemanticError> {
     let _ = analyze_text(
         meaning_engine,
@@ -96,21 +116,24 @@ pub(crate) fn evaluate_design(
         language_dhm,
         semantic_l1_dhm,
         semantic_dhm,
+        in_context,
     )?;
     let projection = project_phase_a(projection_engine, semantic_l1_dhm, semantic_dhm);
-    hypothesis_engine.evaluate_hypothesis(&projection)
+    hypothesis_engine.evaluate_hypothesis_prioritized(&projection, priorities)
 }
 
 #[allow(clippy::too_many_arguments)]
 pub(crate) fn explain_design(
     text: &str,
+    priorities: &BTreeMap<RequirementKind, RequirementPriority>,
     meaning_engine: &MeaningEngine,
     projection_engine: &ProjectionEngine,
     hypothesis_engine: &HypothesisEngine,
     language_engine: &LanguageEngine,
-    language_dhm: &mut LanguageDhm<FileStore<LangId, LanguageUnit>>,
-    semantic_l1_dhm: &mut SemanticL1Dhm<FileStore<L1Id, SemanticUnitL1>>,
-    semantic_dhm: &mut SemanticDhm<FileStore<ConceptId, ConceptUnit>>,
+    language_dhm: &mut LanguageDhm<BackedStore<LangId, LanguageUnit>>,
+    semantic_l1_dhm: &mut SemanticL1Dhm<BackedStore<L1Id, SemanticUnitL1>>,
+    semantic_dhm: &mut SemanticDhm<BackedStore<ConceptId, ConceptUnit>>,
+    in_context: impl Fn(L1Id) -> bool,
 ) -> Result<Explanation, SemanticError> {
     let _ = analyze_text(
         meaning_engine,
@@ -118,9 +141,10 @@ pub(crate) fn explain_design(
         language_dhm,
         semantic_l1_dhm,
         semantic_dhm,
+        in_context,
     )?;
     let projection = project_phase_a(projection_engine, semantic_l1_dhm, semantic_dhm);
-    let hypothesis = hypothesis_engine.evaluate_hypothesis(&projection)?;
+    let hypothesis = hypothesis_engine.evaluate_hypothesis_prioritized(&projection, priorities)?;
     let state = language_engine.build_state(&projection, &semantic_l1_dhm.all_units(), &hypothesis);
     Ok(language_engine.explain_state(&state))
 }
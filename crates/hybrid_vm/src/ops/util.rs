@@ -1,7 +1,7 @@
 use std::collections::HashSet;
 use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
 
+use core_types::clock;
 use semantic_dhm::ConceptId;
 
 use memory_space::InterferenceMode;
@@ -14,13 +14,19 @@ pub(crate) fn infer_depth_from_snapshot(snapshot: &str) -> usize {
 }
 
 pub(crate) fn default_store_path() -> PathBuf {
-    let id = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .map(|d| d.as_nanos())
-        .unwrap_or(0);
+    let id = clock::unix_time_nanos();
     std::env::temp_dir().join(format!("hybrid_vm_store_{}_{}.bin", std::process::id(), id))
 }
 
+/// Mixed into request ids; `std::process::id` panics on wasm32-unknown-unknown.
+pub(crate) fn process_salt() -> u64 {
+    if cfg!(all(target_arch = "wasm32", target_os = "unknown")) {
+        0
+    } else {
+        std::process::id() as u64
+    }
+}
+
 pub(crate) fn default_language_store_path() -> PathBuf {
     std::env::temp_dir().join("hybrid_vm_language_dhm.bin")
 }
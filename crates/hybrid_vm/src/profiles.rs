@@ -0,0 +1,168 @@
+//! Named preference profiles kept with a workspace.
+//!
+//! A profile pairs the objective preference used to rank designs with the
+//! `DecisionWeights` used to judge them, so each user can keep their own
+//! under a name and pick one per run. Saving under an existing name bumps
+//! its revision; `apply` returns the profile together with a
+//! `ProfileApplication` naming the exact revision, for the run manifest.
+
+use std::collections::BTreeMap;
+
+use core_types::ProfileVector;
+use core_types::clock;
+use serde::{Deserialize, Serialize};
+
+use crate::{DecisionWeights, HybridVmError};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UserProfile {
+    /// Normalized to sum to one.
+    pub preference: ProfileVector,
+    /// Normalized to sum to one.
+    pub decision_weights: DecisionWeights,
+    /// 1 on first save, then bumped by every save under the same name.
+    pub revision: u64,
+    pub updated_at_ms: u64,
+}
+
+/// Which profile revision a run used, and when it was applied.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ProfileApplication {
+    pub name: String,
+    pub revision: u64,
+    pub applied_at_ms: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ProfileStore {
+    default: Option<String>,
+    profiles: BTreeMap<String, UserProfile>,
+}
+
+impl ProfileStore {
+    /// Profile names, sorted.
+    pub fn names(&self) -> Vec<&str> {
+        self.profiles.keys().map(String::as_str).collect()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&UserProfile> {
+        self.profiles.get(name)
+    }
+
+    /// Creates or replaces `name`. Both weight sets are normalized; the
+    /// decision weights must not all be zero or negative.
+    pub fn save(
+        &mut self,
+        name: &str,
+        preference: ProfileVector,
+        decision_weights: DecisionWeights,
+    ) -> Result<&UserProfile, HybridVmError> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(HybridVmError::InvalidInput("profile name is empty"));
+        }
+        let decision_weights = decision_weights
+            .normalized()
+            .map_err(|_| HybridVmError::InvalidInput("decision weights sum to zero"))?;
+        let revision = self.profiles.get(name).map_or(0, |p| p.revision) + 1;
+        let profile = UserProfile {
+            preference: preference.normalized(),
+            decision_weights,
+            revision,
+            updated_at_ms: clock::unix_time_millis(),
+        };
+        self.profiles.insert(name.to_string(), profile);
+        Ok(&self.profiles[name])
+    }
+
+    /// Removes `name`, and unsets it as the default if it was.
+    pub fn remove(&mut self, name: &str) -> Option<UserProfile> {
+        if self.default.as_deref() == Some(name) {
+            self.default = None;
+        }
+        self.profiles.remove(name)
+    }
+
+    pub fn set_default(&mut self, name: &str) -> Result<(), HybridVmError> {
+        if !self.profiles.contains_key(name) {
+            return Err(HybridVmError::InvalidInput("unknown profile"));
+        }
+        self.default = Some(name.to_string());
+        Ok(())
+    }
+
+    pub fn clear_default(&mut self) {
+        self.default = None;
+    }
+
+    pub fn default_name(&self) -> Option<&str> {
+        self.default.as_deref()
+    }
+
+    /// `name`, or the default profile when `name` is `None`. `None` when
+    /// that profile does not exist.
+    pub fn apply(&self, name: Option<&str>) -> Option<(UserProfile, ProfileApplication)> {
+        let name = name.or(self.default.as_deref())?;
+        let profile = self.profiles.get(name)?;
+        Some((
+            profile.clone(),
+            ProfileApplication {
+                name: name.to_string(),
+                revision: profile.revision,
+                applied_at_ms: clock::unix_time_millis(),
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preference(struct_weight: f64) -> ProfileVector {
+        ProfileVector {
+            struct_weight,
+            field_weight: 1.0,
+            risk_weight: 1.0,
+            cost_weight: 1.0,
+        }
+    }
+
+    #[test]
+    fn saves_bump_revisions_and_apply_falls_back_to_the_default() {
+        let mut store = ProfileStore::default();
+        assert!(store.apply(None).is_none());
+        store
+            .save("alice", preference(1.0), DecisionWeights::default())
+            .expect("save");
+        let resaved = store
+            .save(" alice ", preference(5.0), DecisionWeights::default())
+            .expect("resave");
+        assert_eq!(resaved.revision, 2);
+        assert!((resaved.preference.struct_weight - 0.625).abs() < 1e-12);
+        store
+            .save("bob", preference(1.0), DecisionWeights::default())
+            .expect("save");
+        assert_eq!(store.names(), vec!["alice", "bob"]);
+
+        assert!(store.set_default("carol").is_err());
+        store.set_default("alice").expect("default");
+        let (profile, application) = store.apply(None).expect("default profile");
+        assert_eq!(application.name, "alice");
+        assert_eq!(application.revision, 2);
+        assert_eq!(&profile, store.get("alice").expect("alice"));
+        assert_eq!(store.apply(Some("bob")).expect("bob").1.revision, 1);
+
+        store.remove("alice");
+        assert_eq!(store.default_name(), None);
+        let zero = DecisionWeights {
+            coherence: 0.0,
+            stability: 0.0,
+            conflict: 0.0,
+            tradeoff: 0.0,
+        };
+        assert!(store.save("zero", preference(1.0), zero).is_err());
+        assert!(store.save("  ", preference(1.0), zero).is_err());
+    }
+}
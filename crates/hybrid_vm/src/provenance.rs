@@ -0,0 +1,91 @@
+//! Where each L1 unit came from: the source document and the span of the
+//! sentence fragment it was extracted from.
+
+use std::fmt;
+use std::ops::Range;
+
+use semantic_dhm::L1Id;
+use serde::{Deserialize, Serialize};
+
+/// Document id recorded for text passed to `HybridVM::analyze_text`.
+pub const INLINE_DOCUMENT_ID: &str = "inline";
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L1Provenance {
+    pub document_id: String,
+    /// Byte range of the fragment in the document; `None` when the fragment
+    /// could not be matched back to the text.
+    pub byte_range: Option<Range<usize>>,
+    /// Same span in characters, for display.
+    pub char_range: Option<Range<usize>>,
+    /// Unix time in milliseconds of the ingestion that created the unit.
+    pub ingested_at: u64,
+    /// Id of the record the unit was imported from, for structured
+    /// requirement imports.
+    #[serde(default)]
+    pub record_id: Option<String>,
+}
+
+impl L1Provenance {
+    pub(crate) fn new(
+        document_id: &str,
+        text: &str,
+        byte_range: Option<Range<usize>>,
+        ingested_at: u64,
+    ) -> Self {
+        let char_range = byte_range.clone().map(|range| char_span(text, range));
+        Self {
+            document_id: document_id.to_string(),
+            byte_range,
+            char_range,
+            ingested_at,
+            record_id: None,
+        }
+    }
+
+    pub(crate) fn for_record(document_id: &str, record_id: &str, ingested_at: u64) -> Self {
+        Self {
+            document_id: document_id.to_string(),
+            byte_range: None,
+            char_range: None,
+            ingested_at,
+            record_id: Some(record_id.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for L1Provenance {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.document_id)?;
+        if let Some(record_id) = &self.record_id {
+            write!(f, "#{record_id}")?;
+        }
+        match &self.char_range {
+            Some(range) => write!(f, ":{}-{}", range.start, range.end),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Result of `HybridVM::ingest_document`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DocumentIngestReport {
+    pub document_id: String,
+    /// Units whose fragment is still in the document; only their offsets
+    /// were refreshed.
+    pub kept: Vec<L1Id>,
+    pub added: Vec<L1Id>,
+    /// Units whose fragment is gone from the document, now removed.
+    pub removed: Vec<L1Id>,
+}
+
+impl DocumentIngestReport {
+    pub fn is_unchanged(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+fn char_span(text: &str, range: Range<usize>) -> Range<usize> {
+    let start = text[..range.start].chars().count();
+    start..start + text[range].chars().count()
+}
@@ -0,0 +1,752 @@
+//! Requirements kept outside the workspace: spreadsheet exports (CSV), ReqIF
+//! exchange files and user-story backlogs (JSON).
+//!
+//! Each format is read into `RequirementRecord`s, one per requirement.
+//! `HybridVM::import_requirements` turns every record into a single L1 unit
+//! (records are not split into fragments the way free text is). The unit
+//! keeps the record id in its provenance and the record priority as its
+//! priority tag.
+//!
+//! Column, attribute and field names are matched case-insensitively:
+//! - `id` (`identifier`, `key`, `ReqIF.ForeignID`)
+//! - `text` (`description`, `requirement`, `ReqIF.Text`)
+//! - `type` (`kind`, `role`, `category`)
+//! - `priority`
+//!
+//! A type that is not one of the four L1 roles or a known alias leaves the
+//! role to text inference. A priority that is not a MoSCoW value or
+//! high/medium/low is an error: a priority silently dropped would change
+//! how the requirement is weighed.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use semantic_dhm::{L1Id, L2ChangeSet, RequirementPriority, RequirementRole};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RequirementFormat {
+    /// Header row, then one requirement per row; RFC 4180 quoting.
+    Csv,
+    /// `SPEC-OBJECT`s with string, XHTML or enumeration attribute values.
+    ReqIf,
+    /// An array of stories, or an object holding one under `stories`. A
+    /// story without `text` is assembled from `as_a`, `i_want` and `so_that`.
+    UserStoriesJson,
+}
+
+impl RequirementFormat {
+    /// Format for a file extension: `csv`, `reqif` or `json`.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "csv" => Some(Self::Csv),
+            "reqif" | "xml" => Some(Self::ReqIf),
+            "json" => Some(Self::UserStoriesJson),
+            _ => None,
+        }
+    }
+
+    pub fn parse(self, input: &str) -> Result<Vec<RequirementRecord>, RequirementImportError> {
+        let records = match self {
+            Self::Csv => parse_csv(input)?,
+            Self::ReqIf => parse_reqif(input)?,
+            Self::UserStoriesJson => parse_user_stories(input)?,
+        };
+        let mut seen = BTreeMap::new();
+        for (index, record) in records.iter().enumerate() {
+            if let Some(first) = seen.insert(record.id.as_str(), index) {
+                return Err(RequirementImportError::new(
+                    index + 1,
+                    format!("duplicate id {} (first in record {})", record.id, first + 1),
+                ));
+            }
+        }
+        Ok(records)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RequirementRecord {
+    pub id: String,
+    pub text: String,
+    /// `None` when the record has no type or one that names no role.
+    pub role: Option<RequirementRole>,
+    pub priority: Option<RequirementPriority>,
+}
+
+impl RequirementRecord {
+    fn from_fields(
+        index: usize,
+        id: Option<String>,
+        text: Option<String>,
+        kind: Option<String>,
+        priority: Option<String>,
+    ) -> Result<Self, RequirementImportError> {
+        let text = text
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .ok_or_else(|| RequirementImportError::new(index, "missing requirement text"))?;
+        let id = id
+            .map(|i| i.trim().to_string())
+            .filter(|i| !i.is_empty())
+            .ok_or_else(|| RequirementImportError::new(index, "missing requirement id"))?;
+        let priority = match priority.as_deref().map(str::trim) {
+            None | Some("") => None,
+            Some(value) => Some(parse_priority(value).ok_or_else(|| {
+                RequirementImportError::new(index, format!("unknown priority {value:?}"))
+            })?),
+        };
+        Ok(Self {
+            id,
+            text,
+            role: kind.as_deref().and_then(parse_role),
+            priority,
+        })
+    }
+}
+
+/// A record that could not be read. `record` counts from 1: the data row of
+/// a CSV file, the `SPEC-OBJECT` of a ReqIF file, the story of a backlog;
+/// 0 when the input as a whole is malformed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequirementImportError {
+    pub record: usize,
+    pub message: String,
+}
+
+impl RequirementImportError {
+    fn new(record: usize, message: impl Into<String>) -> Self {
+        Self {
+            record,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for RequirementImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.record == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "record {}: {}", self.record, self.message)
+        }
+    }
+}
+
+impl std::error::Error for RequirementImportError {}
+
+/// One record as an L1 unit, added or, in a dry run, to be added.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ImportedRequirement {
+    pub record_id: String,
+    pub l1_id: L1Id,
+    pub role: RequirementRole,
+    pub priority: Option<RequirementPriority>,
+    pub text: String,
+}
+
+/// Result of `HybridVM::import_requirements`.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RequirementImportReport {
+    pub document_id: String,
+    /// Nothing was stored; ids and L2 changes are what a real import would
+    /// produce if nothing else changed in between.
+    pub dry_run: bool,
+    pub imported: Vec<ImportedRequirement>,
+    /// Records whose id an earlier import of the document already added.
+    pub skipped: Vec<String>,
+    pub l2_changes: L2ChangeSet,
+}
+
+fn parse_role(kind: &str) -> Option<RequirementRole> {
+    match normalize_name(kind).as_str() {
+        "goal" | "functional" | "feature" | "story" | "userstory" => Some(RequirementRole::Goal),
+        "constraint" | "nonfunctional" | "nfr" => Some(RequirementRole::Constraint),
+        "optimization" | "optimisation" | "quality" => Some(RequirementRole::Optimization),
+        "prohibition" | "forbidden" => Some(RequirementRole::Prohibition),
+        _ => None,
+    }
+}
+
+fn parse_priority(value: &str) -> Option<RequirementPriority> {
+    match normalize_name(value).as_str() {
+        "must" | "musthave" | "high" | "critical" => Some(RequirementPriority::Must),
+        "should" | "shouldhave" | "medium" => Some(RequirementPriority::Should),
+        "could" | "couldhave" | "low" => Some(RequirementPriority::Could),
+        "wont" | "wonthave" => Some(RequirementPriority::Wont),
+        _ => None,
+    }
+}
+
+/// Lowercase alphanumerics only, so `Non-Functional`, `won't` and
+/// `ReqIF.Text` compare as `nonfunctional`, `wont` and `reqiftext`.
+fn normalize_name(name: &str) -> String {
+    name.chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Field {
+    Id,
+    Text,
+    Kind,
+    Priority,
+}
+
+fn field_for(name: &str) -> Option<Field> {
+    let name = normalize_name(name);
+    let name = name.strip_prefix("reqif").unwrap_or(&name);
+    match name {
+        "id" | "identifier" | "key" | "foreignid" => Some(Field::Id),
+        "text" | "description" | "requirement" => Some(Field::Text),
+        "type" | "kind" | "role" | "category" => Some(Field::Kind),
+        "priority" => Some(Field::Priority),
+        _ => None,
+    }
+}
+
+#[derive(Default)]
+struct Fields {
+    id: Option<String>,
+    text: Option<String>,
+    kind: Option<String>,
+    priority: Option<String>,
+}
+
+impl Fields {
+    fn set(&mut self, field: Field, value: String) {
+        let slot = match field {
+            Field::Id => &mut self.id,
+            Field::Text => &mut self.text,
+            Field::Kind => &mut self.kind,
+            Field::Priority => &mut self.priority,
+        };
+        slot.get_or_insert(value);
+    }
+
+    fn into_record(self, index: usize) -> Result<RequirementRecord, RequirementImportError> {
+        RequirementRecord::from_fields(index, self.id, self.text, self.kind, self.priority)
+    }
+}
+
+fn parse_csv(input: &str) -> Result<Vec<RequirementRecord>, RequirementImportError> {
+    let mut rows = csv_rows(input.strip_prefix('\u{feff}').unwrap_or(input))?.into_iter();
+    let header = rows
+        .next()
+        .ok_or_else(|| RequirementImportError::new(0, "empty CSV"))?;
+    let columns = header.iter().map(|h| field_for(h)).collect::<Vec<_>>();
+    for (required, name) in [(Field::Id, "id"), (Field::Text, "text")] {
+        if !columns.contains(&Some(required)) {
+            return Err(RequirementImportError::new(
+                0,
+                format!("CSV header has no {name} column"),
+            ));
+        }
+    }
+    rows.filter(|row| row.iter().any(|cell| !cell.trim().is_empty()))
+        .enumerate()
+        .map(|(index, row)| {
+            let mut fields = Fields::default();
+            for (column, cell) in columns.iter().zip(row) {
+                if let Some(field) = column {
+                    fields.set(*field, cell);
+                }
+            }
+            fields.into_record(index + 1)
+        })
+        .collect()
+}
+
+fn csv_rows(input: &str) -> Result<Vec<Vec<String>>, RequirementImportError> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut cell = String::new();
+    let mut quoted = false;
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                chars.next();
+                cell.push('"');
+            }
+            '"' if quoted => quoted = false,
+            '"' if cell.is_empty() => quoted = true,
+            ',' if !quoted => row.push(std::mem::take(&mut cell)),
+            '\r' if !quoted && chars.peek() == Some(&'\n') => {}
+            '\n' if !quoted => {
+                row.push(std::mem::take(&mut cell));
+                rows.push(std::mem::take(&mut row));
+            }
+            _ => cell.push(c),
+        }
+    }
+    if quoted {
+        return Err(RequirementImportError::new(
+            0,
+            "unterminated quoted CSV cell",
+        ));
+    }
+    if !cell.is_empty() || !row.is_empty() {
+        row.push(cell);
+        rows.push(row);
+    }
+    Ok(rows)
+}
+
+fn parse_user_stories(input: &str) -> Result<Vec<RequirementRecord>, RequirementImportError> {
+    let value: Value = serde_json::from_str(input)
+        .map_err(|err| RequirementImportError::new(0, err.to_string()))?;
+    let stories = match &value {
+        Value::Array(stories) => stories,
+        Value::Object(map) => match map.get("stories") {
+            Some(Value::Array(stories)) => stories,
+            _ => {
+                return Err(RequirementImportError::new(
+                    0,
+                    "expected a \"stories\" array",
+                ));
+            }
+        },
+        _ => {
+            return Err(RequirementImportError::new(
+                0,
+                "expected an array of stories",
+            ));
+        }
+    };
+    stories
+        .iter()
+        .enumerate()
+        .map(|(index, story)| {
+            let Value::Object(map) = story else {
+                return Err(RequirementImportError::new(index + 1, "not an object"));
+            };
+            let mut fields = Fields::default();
+            let mut narrative = BTreeMap::new();
+            for (key, value) in map {
+                let value = match value {
+                    Value::String(s) => s.clone(),
+                    Value::Number(n) => n.to_string(),
+                    _ => continue,
+                };
+                match field_for(key) {
+                    Some(field) => fields.set(field, value),
+                    None => {
+                        narrative.insert(normalize_name(key), value);
+                    }
+                }
+            }
+            if fields.text.is_none() {
+                fields.text = story_text(&narrative);
+                fields.kind.get_or_insert_with(|| "story".to_string());
+            }
+            fields.into_record(index + 1)
+        })
+        .collect()
+}
+
+/// "As a <as_a>, I want <i_want> so that <so_that>", or `None` without
+/// `i_want`.
+fn story_text(narrative: &BTreeMap<String, String>) -> Option<String> {
+    let want = narrative.get("iwant")?;
+    let mut text = match narrative.get("asa") {
+        Some(role) => format!("As a {role}, I want {want}"),
+        None => format!("I want {want}"),
+    };
+    if let Some(benefit) = narrative.get("sothat") {
+        text.push_str(" so that ");
+        text.push_str(benefit);
+    }
+    Some(text)
+}
+
+fn parse_reqif(input: &str) -> Result<Vec<RequirementRecord>, RequirementImportError> {
+    let elements = xml_elements(input)?;
+    // Attribute definitions and enumeration values are referenced by
+    // identifier; both are declared before or after the objects using them.
+    let mut names = BTreeMap::new();
+    for element in &elements {
+        if let Xml::Open { name, attrs, .. } = element
+            && (name.starts_with("ATTRIBUTE-DEFINITION-") || name == "ENUM-VALUE")
+            && let (Some(id), Some(long_name)) =
+                (attr(attrs, "IDENTIFIER"), attr(attrs, "LONG-NAME"))
+        {
+            names.insert(id.to_string(), long_name.to_string());
+        }
+    }
+
+    let mut records = Vec::new();
+    let mut iter = elements.iter();
+    while let Some(element) = iter.next() {
+        let Xml::Open { name, attrs, .. } = element else {
+            continue;
+        };
+        if name != "SPEC-OBJECT" {
+            continue;
+        }
+        let mut fields = Fields::default();
+        let mut current: Option<AttributeValue> = None;
+        let mut open: Option<&str> = None;
+        for inner in iter.by_ref() {
+            match inner {
+                Xml::Close(name) if name == "SPEC-OBJECT" => break,
+                Xml::Open { name, attrs, .. } if name.starts_with("ATTRIBUTE-VALUE-") => {
+                    current = Some(AttributeValue {
+                        value: attr(attrs, "THE-VALUE").map(str::to_string),
+                        ..AttributeValue::default()
+                    });
+                }
+                Xml::Close(name) if name.starts_with("ATTRIBUTE-VALUE-") => {
+                    if let Some((field, value)) =
+                        current.take().and_then(|value| value.resolve(&names))
+                    {
+                        fields.set(field, value);
+                    }
+                }
+                Xml::Open { name, .. } => {
+                    if name == "THE-VALUE"
+                        && let Some(value) = current.as_mut()
+                    {
+                        value.xhtml = Some(String::new());
+                    }
+                    open = Some(name);
+                }
+                Xml::Close(name) => {
+                    if name == "THE-VALUE"
+                        && let Some(value) = current.as_mut()
+                    {
+                        value.finish_xhtml();
+                    }
+                    open = None;
+                }
+                Xml::Text(text) => {
+                    let Some(value) = current.as_mut() else {
+                        continue;
+                    };
+                    if let Some(buffer) = value.xhtml.as_mut() {
+                        buffer.push(' ');
+                        buffer.push_str(text);
+                    } else if open.is_some_and(|o| o.starts_with("ATTRIBUTE-DEFINITION-")) {
+                        value.definition = Some(text.clone());
+                    } else if open == Some("ENUM-VALUE-REF") {
+                        value.enum_refs.push(text.clone());
+                    }
+                }
+            }
+        }
+        if fields.id.is_none() {
+            fields.id = attr(attrs, "IDENTIFIER").map(str::to_string);
+        }
+        records.push(fields.into_record(records.len() + 1)?);
+    }
+    Ok(records)
+}
+
+/// One `ATTRIBUTE-VALUE-*` of a `SPEC-OBJECT` while it is being read.
+#[derive(Default)]
+struct AttributeValue {
+    definition: Option<String>,
+    /// `THE-VALUE` attribute of string, integer and other simple values.
+    value: Option<String>,
+    /// Text of an XHTML `THE-VALUE` element, while inside it.
+    xhtml: Option<String>,
+    enum_refs: Vec<String>,
+}
+
+impl AttributeValue {
+    fn finish_xhtml(&mut self) {
+        if let Some(text) = self.xhtml.take() {
+            self.value
+                .get_or_insert(text.split_whitespace().collect::<Vec<_>>().join(" "));
+        }
+    }
+
+    /// The field this value fills, by the long name of its definition, and
+    /// the value; enumeration values become their long names.
+    fn resolve(self, names: &BTreeMap<String, String>) -> Option<(Field, String)> {
+        let field = field_for(names.get(self.definition.as_ref()?)?)?;
+        let value = self.value.or_else(|| {
+            let values = self
+                .enum_refs
+                .iter()
+                .filter_map(|r| names.get(r).cloned())
+                .collect::<Vec<_>>();
+            (!values.is_empty()).then(|| values.join(" "))
+        })?;
+        Some((field, value))
+    }
+}
+
+enum Xml {
+    /// `<NAME/>` is followed by its `Close`.
+    Open {
+        name: String,
+        attrs: Vec<(String, String)>,
+    },
+    Close(String),
+    /// Non-blank character data, entities decoded.
+    Text(String),
+}
+
+fn attr<'a>(attrs: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    attrs
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// Flat element stream of `input`; enough XML for ReqIF exports: no DTDs,
+/// namespace prefixes are dropped from element names.
+fn xml_elements(input: &str) -> Result<Vec<Xml>, RequirementImportError> {
+    let malformed = || RequirementImportError::new(0, "malformed ReqIF XML");
+    let mut out = Vec::new();
+    let mut rest = input;
+    while let Some(start) = rest.find('<') {
+        let text = rest[..start].trim();
+        if !text.is_empty() {
+            out.push(Xml::Text(decode_entities(text)));
+        }
+        rest = &rest[start..];
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = &after[after.find("-->").ok_or_else(malformed)? + 3..];
+            continue;
+        }
+        if let Some(after) = rest.strip_prefix("<![CDATA[") {
+            let end = after.find("]]>").ok_or_else(malformed)?;
+            out.push(Xml::Text(after[..end].to_string()));
+            rest = &after[end + 3..];
+            continue;
+        }
+        let end = tag_end(rest).ok_or_else(malformed)?;
+        let tag = &rest[1..end];
+        rest = &rest[end + 1..];
+        if tag.starts_with('?') || tag.starts_with('!') {
+            continue;
+        }
+        if let Some(name) = tag.strip_prefix('/') {
+            out.push(Xml::Close(local_name(name.trim()).to_string()));
+            continue;
+        }
+        let empty = tag.ends_with('/');
+        let tag = tag.trim_end_matches('/');
+        let (name, mut attrs_src) = tag.split_once(char::is_whitespace).unwrap_or((tag, ""));
+        let mut attrs = Vec::new();
+        while let Some(eq) = attrs_src.find('=') {
+            let key = attrs_src[..eq].trim();
+            let value_src = attrs_src[eq + 1..].trim_start();
+            let quote = value_src.chars().next().ok_or_else(malformed)?;
+            if quote != '"' && quote != '\'' {
+                return Err(malformed());
+            }
+            let close = value_src[1..].find(quote).ok_or_else(malformed)? + 1;
+            attrs.push((
+                local_name(key).to_string(),
+                decode_entities(&value_src[1..close]),
+            ));
+            attrs_src = &value_src[close + 1..];
+        }
+        out.push(Xml::Open {
+            name: local_name(name).to_string(),
+            attrs,
+        });
+        if empty {
+            out.push(Xml::Close(local_name(name).to_string()));
+        }
+    }
+    Ok(out)
+}
+
+/// Index of the `>` closing the tag `tag` starts with, skipping quoted
+/// attribute values.
+fn tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+    for (i, c) in tag.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), _) if q == c => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn local_name(name: &str) -> &str {
+    name.rsplit_once(':').map_or(name, |(_, local)| local)
+}
+
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        rest = &rest[amp..];
+        let Some(semi) = rest.find(';') else { break };
+        let entity = &rest[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            _ => entity
+                .strip_prefix("#x")
+                .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                .or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                .and_then(char::from_u32),
+        };
+        match decoded {
+            Some(c) => {
+                out.push(c);
+                rest = &rest[semi + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_reads_quoted_cells_aliases_and_priorities() {
+        let csv = "ID,Description,Type,Priority\r\n\
+                   REQ-1,\"Respond within 200ms, even at peak\",Non-Functional,High\r\n\
+                   REQ-2,\"Say \"\"hello\"\"\nto users\",feature,\r\n\
+                   \r\n";
+        let records = RequirementFormat::Csv.parse(csv).expect("csv");
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].id, "REQ-1");
+        assert_eq!(records[0].text, "Respond within 200ms, even at peak");
+        assert_eq!(records[0].role, Some(RequirementRole::Constraint));
+        assert_eq!(records[0].priority, Some(RequirementPriority::Must));
+        assert_eq!(records[1].text, "Say \"hello\"\nto users");
+        assert_eq!(records[1].role, Some(RequirementRole::Goal));
+        assert_eq!(records[1].priority, None);
+    }
+
+    #[test]
+    fn csv_rejects_unknown_priority_duplicates_and_missing_columns() {
+        let err = RequirementFormat::Csv
+            .parse("id,text,priority\nR1,Fast,urgent\n")
+            .expect_err("priority");
+        assert_eq!(err.record, 1);
+        assert!(err.message.contains("urgent"));
+        let err = RequirementFormat::Csv
+            .parse("id,text\nR1,Fast\nR1,Safe\n")
+            .expect_err("duplicate");
+        assert_eq!(err.record, 2);
+        assert!(
+            RequirementFormat::Csv
+                .parse("name,text\nR1,Fast\n")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn reqif_resolves_definitions_xhtml_and_enumerations() {
+        let reqif = r#"<?xml version="1.0" encoding="UTF-8"?>
+<REQ-IF xmlns="http://www.omg.org/spec/ReqIF/20110401/reqif.xsd" xmlns:xhtml="http://www.w3.org/1999/xhtml">
+  <CORE-CONTENT><REQ-IF-CONTENT>
+    <DATATYPES>
+      <DATATYPE-DEFINITION-ENUMERATION IDENTIFIER="dt-prio" LONG-NAME="Priorities">
+        <SPECIFIED-VALUES>
+          <ENUM-VALUE IDENTIFIER="ev-high" LONG-NAME="High"/>
+          <ENUM-VALUE IDENTIFIER="ev-low" LONG-NAME="Low"/>
+        </SPECIFIED-VALUES>
+      </DATATYPE-DEFINITION-ENUMERATION>
+    </DATATYPES>
+    <SPEC-TYPES>
+      <SPEC-OBJECT-TYPE IDENTIFIER="t-req" LONG-NAME="Requirement">
+        <SPEC-ATTRIBUTES>
+          <ATTRIBUTE-DEFINITION-STRING IDENTIFIER="ad-id" LONG-NAME="ReqIF.ForeignID"/>
+          <ATTRIBUTE-DEFINITION-XHTML IDENTIFIER="ad-text" LONG-NAME="ReqIF.Text"/>
+          <ATTRIBUTE-DEFINITION-STRING IDENTIFIER="ad-type" LONG-NAME="Type"/>
+          <ATTRIBUTE-DEFINITION-ENUMERATION IDENTIFIER="ad-prio" LONG-NAME="Priority"/>
+        </SPEC-ATTRIBUTES>
+      </SPEC-OBJECT-TYPE>
+    </SPEC-TYPES>
+    <SPEC-OBJECTS>
+      <SPEC-OBJECT IDENTIFIER="so-1" LAST-CHANGE="2026-01-01T00:00:00Z">
+        <VALUES>
+          <ATTRIBUTE-VALUE-STRING THE-VALUE="SYS-10">
+            <DEFINITION><ATTRIBUTE-DEFINITION-STRING-REF>ad-id</ATTRIBUTE-DEFINITION-STRING-REF></DEFINITION>
+          </ATTRIBUTE-VALUE-STRING>
+          <ATTRIBUTE-VALUE-XHTML>
+            <DEFINITION><ATTRIBUTE-DEFINITION-XHTML-REF>ad-text</ATTRIBUTE-DEFINITION-XHTML-REF></DEFINITION>
+            <THE-VALUE><xhtml:div>Avoid <xhtml:b>cloud</xhtml:b> &amp; vendor lock-in</xhtml:div></THE-VALUE>
+          </ATTRIBUTE-VALUE-XHTML>
+          <ATTRIBUTE-VALUE-STRING THE-VALUE="prohibition">
+            <DEFINITION><ATTRIBUTE-DEFINITION-STRING-REF>ad-type</ATTRIBUTE-DEFINITION-STRING-REF></DEFINITION>
+          </ATTRIBUTE-VALUE-STRING>
+          <ATTRIBUTE-VALUE-ENUMERATION>
+            <DEFINITION><ATTRIBUTE-DEFINITION-ENUMERATION-REF>ad-prio</ATTRIBUTE-DEFINITION-ENUMERATION-REF></DEFINITION>
+            <VALUES><ENUM-VALUE-REF>ev-high</ENUM-VALUE-REF></VALUES>
+          </ATTRIBUTE-VALUE-ENUMERATION>
+        </VALUES>
+        <TYPE><SPEC-OBJECT-TYPE-REF>t-req</SPEC-OBJECT-TYPE-REF></TYPE>
+      </SPEC-OBJECT>
+      <SPEC-OBJECT IDENTIFIER="so-2">
+        <VALUES>
+          <ATTRIBUTE-VALUE-XHTML>
+            <DEFINITION><ATTRIBUTE-DEFINITION-XHTML-REF>ad-text</ATTRIBUTE-DEFINITION-XHTML-REF></DEFINITION>
+            <THE-VALUE><xhtml:p>Minimize energy use</xhtml:p></THE-VALUE>
+          </ATTRIBUTE-VALUE-XHTML>
+        </VALUES>
+      </SPEC-OBJECT>
+    </SPEC-OBJECTS>
+  </REQ-IF-CONTENT></CORE-CONTENT>
+</REQ-IF>"#;
+        let records = RequirementFormat::ReqIf.parse(reqif).expect("reqif");
+        assert_eq!(
+            records,
+            vec![
+                RequirementRecord {
+                    id: "SYS-10".to_string(),
+                    text: "Avoid cloud & vendor lock-in".to_string(),
+                    role: Some(RequirementRole::Prohibition),
+                    priority: Some(RequirementPriority::Must),
+                },
+                RequirementRecord {
+                    id: "so-2".to_string(),
+                    text: "Minimize energy use".to_string(),
+                    role: None,
+                    priority: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn user_stories_assemble_text_from_narrative_fields() {
+        let json = r#"{"stories": [
+            {"id": "US-1", "as_a": "operator", "i_want": "one-click rollback",
+             "so_that": "outages stay short", "priority": "Must Have"},
+            {"key": 7, "text": "Export reports as CSV", "type": "quality"}
+        ]}"#;
+        let records = RequirementFormat::UserStoriesJson
+            .parse(json)
+            .expect("stories");
+        assert_eq!(
+            records[0].text,
+            "As a operator, I want one-click rollback so that outages stay short"
+        );
+        assert_eq!(records[0].role, Some(RequirementRole::Goal));
+        assert_eq!(records[0].priority, Some(RequirementPriority::Must));
+        assert_eq!(records[1].id, "7");
+        assert_eq!(records[1].role, Some(RequirementRole::Optimization));
+        let err = RequirementFormat::UserStoriesJson
+            .parse(r#"[{"id": "US-2"}]"#)
+            .expect_err("no text");
+        assert_eq!(err.record, 1);
+    }
+}
@@ -0,0 +1,341 @@
+//! Fitting resonance weights to labeled concept pairs.
+//!
+//! Resonance weighs vector similarity by `gamma1`, structure similarity by
+//! `gamma2` and the abstraction gap, as a penalty, by `gamma3`. Given pairs
+//! a user marked as "should match" or "should not match",
+//! `fit_resonance_weights` walks a grid over the weight simplex and keeps
+//! the weights that best rank matching pairs above non-matching ones. A
+//! held-out set, scored before and after, shows whether the new weights
+//! also recall better on pairs the fit never saw.
+
+use std::collections::BTreeMap;
+
+use semantic_dhm::{ConceptId, ConceptQuery, ConceptUnit, ResonanceWeights, resonance};
+use serde::{Deserialize, Serialize};
+
+use crate::HybridVmError;
+
+/// Gain in training pair accuracy below which the starting weights are kept.
+const MIN_GAIN: f64 = 1e-9;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabeledPair {
+    pub left: ConceptId,
+    pub right: ConceptId,
+    /// `true` for "should match".
+    pub matches: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct WeightFitConfig {
+    /// Spacing of the grid over the simplex; must lie in (0, 1].
+    pub grid_step: f32,
+    /// Cut-off of `RecallQuality::recall_at_k`.
+    pub recall_k: usize,
+}
+
+impl Default for WeightFitConfig {
+    fn default() -> Self {
+        Self {
+            grid_step: 0.05,
+            recall_k: 3,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct RecallQuality {
+    /// Share of (matching, non-matching) pair combinations where the matching
+    /// pair resonates more; ties count half. 0.5 without both kinds.
+    pub pair_accuracy: f64,
+    /// Share of matching pairs whose `right` is among the `recall_k` concepts
+    /// resonating most with `left`. 0 without matching pairs.
+    pub recall_at_k: f64,
+    pub pairs: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WeightFitReport {
+    pub before: ResonanceWeights,
+    pub after: ResonanceWeights,
+    pub train_before: RecallQuality,
+    pub train_after: RecallQuality,
+    pub held_out_before: RecallQuality,
+    pub held_out_after: RecallQuality,
+    /// Grid points scored.
+    pub candidates: usize,
+}
+
+impl WeightFitReport {
+    pub fn held_out_gain(&self) -> f64 {
+        self.held_out_after.pair_accuracy - self.held_out_before.pair_accuracy
+    }
+}
+
+/// Fits weights on `train`, starting from `initial`, and scores `held_out`
+/// with both. The starting weights are kept unless a grid point does
+/// strictly better on the training pairs.
+pub fn fit_resonance_weights(
+    concepts: &[ConceptUnit],
+    initial: ResonanceWeights,
+    train: &[LabeledPair],
+    held_out: &[LabeledPair],
+    config: WeightFitConfig,
+) -> Result<WeightFitReport, HybridVmError> {
+    if !(config.grid_step > 0.0 && config.grid_step <= 1.0) {
+        return Err(HybridVmError::InvalidInput("grid_step must be in (0, 1]"));
+    }
+    let by_id = concepts
+        .iter()
+        .map(|c| (c.id, c))
+        .collect::<BTreeMap<_, _>>();
+    let train_terms = pair_terms(&by_id, train)?;
+    let held_out_terms = pair_terms(&by_id, held_out)?;
+
+    let before = initial.normalized();
+    let mut after = before;
+    let mut best = pair_accuracy(&train_terms, before);
+    let steps = (1.0 / config.grid_step).round().max(1.0) as usize;
+    let mut candidates = 0;
+    for i in 0..=steps {
+        for j in 0..=steps - i {
+            let gamma1 = i as f32 / steps as f32;
+            let gamma2 = j as f32 / steps as f32;
+            let candidate = ResonanceWeights {
+                gamma1,
+                gamma2,
+                gamma3: (1.0 - gamma1 - gamma2).max(0.0),
+            };
+            candidates += 1;
+            let accuracy = pair_accuracy(&train_terms, candidate);
+            if accuracy > best + MIN_GAIN {
+                best = accuracy;
+                after = candidate;
+            }
+        }
+    }
+
+    let quality = |pairs: &[LabeledPair], terms: &[Terms], weights| RecallQuality {
+        pair_accuracy: pair_accuracy(terms, weights),
+        recall_at_k: recall_at_k(concepts, &by_id, pairs, weights, config.recall_k),
+        pairs: pairs.len(),
+    };
+    Ok(WeightFitReport {
+        before,
+        after,
+        train_before: quality(train, &train_terms, before),
+        train_after: quality(train, &train_terms, after),
+        held_out_before: quality(held_out, &held_out_terms, before),
+        held_out_after: quality(held_out, &held_out_terms, after),
+        candidates,
+    })
+}
+
+/// The three resonance terms of a pair, so grid points need no vector math.
+#[derive(Clone, Copy, Debug)]
+struct Terms {
+    vector: f32,
+    structure: f32,
+    /// Negated abstraction gap.
+    abstraction: f32,
+    matches: bool,
+}
+
+impl Terms {
+    fn score(&self, w: ResonanceWeights) -> f32 {
+        w.gamma1 * self.vector + w.gamma2 * self.structure + w.gamma3 * self.abstraction
+    }
+}
+
+fn pair_terms(
+    by_id: &BTreeMap<ConceptId, &ConceptUnit>,
+    pairs: &[LabeledPair],
+) -> Result<Vec<Terms>, HybridVmError> {
+    pairs
+        .iter()
+        .map(|pair| {
+            let left = by_id
+                .get(&pair.left)
+                .ok_or(HybridVmError::ConceptNotFound(pair.left))?;
+            let right = by_id
+                .get(&pair.right)
+                .ok_or(HybridVmError::ConceptNotFound(pair.right))?;
+            let query = query_of(left);
+            let term = |gamma1, gamma2, gamma3| {
+                resonance(
+                    &query,
+                    right,
+                    ResonanceWeights {
+                        gamma1,
+                        gamma2,
+                        gamma3,
+                    },
+                )
+            };
+            Ok(Terms {
+                vector: term(1.0, 0.0, 0.0),
+                structure: term(0.0, 1.0, 0.0),
+                abstraction: term(0.0, 0.0, 1.0),
+                matches: pair.matches,
+            })
+        })
+        .collect()
+}
+
+fn pair_accuracy(terms: &[Terms], weights: ResonanceWeights) -> f64 {
+    let scores = |matches: bool| {
+        terms
+            .iter()
+            .filter(|t| t.matches == matches)
+            .map(|t| t.score(weights))
+            .collect::<Vec<_>>()
+    };
+    let (positive, negative) = (scores(true), scores(false));
+    if positive.is_empty() || negative.is_empty() {
+        return 0.5;
+    }
+    let mut wins = 0.0;
+    for p in &positive {
+        for n in &negative {
+            wins += match p.total_cmp(n) {
+                std::cmp::Ordering::Greater => 1.0,
+                std::cmp::Ordering::Equal => 0.5,
+                std::cmp::Ordering::Less => 0.0,
+            };
+        }
+    }
+    wins / (positive.len() * negative.len()) as f64
+}
+
+fn recall_at_k(
+    concepts: &[ConceptUnit],
+    by_id: &BTreeMap<ConceptId, &ConceptUnit>,
+    pairs: &[LabeledPair],
+    weights: ResonanceWeights,
+    k: usize,
+) -> f64 {
+    let positives = pairs.iter().filter(|p| p.matches).collect::<Vec<_>>();
+    if positives.is_empty() {
+        return 0.0;
+    }
+    let hits = positives
+        .iter()
+        .filter(|pair| {
+            let query = query_of(by_id[&pair.left]);
+            let mut ranked = concepts
+                .iter()
+                .filter(|c| c.id != pair.left)
+                .map(|c| (c.id, resonance(&query, c, weights)))
+                .collect::<Vec<_>>();
+            ranked.sort_by(|a, b| b.1.total_cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+            ranked.iter().take(k).any(|(id, _)| *id == pair.right)
+        })
+        .count();
+    hits as f64 / positives.len() as f64
+}
+
+fn query_of(c: &ConceptUnit) -> ConceptQuery {
+    ConceptQuery {
+        v: c.integrated_vector.clone(),
+        a: c.a,
+        s: c.s.clone(),
+        polarity: c.polarity,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Vectors along `v_axis`, structures along `s_axis`.
+    fn concept(id: u64, v_axis: usize, s_axis: usize) -> ConceptUnit {
+        let mut v = vec![0.0; semantic_dhm::D_SEM];
+        v[v_axis] = 1.0;
+        let mut s = vec![0.0; semantic_dhm::D_STRUCT];
+        s[s_axis] = 1.0;
+        ConceptUnit {
+            id: ConceptId(id),
+            l1_refs: Vec::new(),
+            integrated_vector: v,
+            a: 0.5,
+            s,
+            polarity: 1,
+            timestamp: 0,
+            access: semantic_dhm::AccessControl::default(),
+        }
+    }
+
+    fn pair(left: u64, right: u64, matches: bool) -> LabeledPair {
+        LabeledPair {
+            left: ConceptId(left),
+            right: ConceptId(right),
+            matches,
+        }
+    }
+
+    #[test]
+    fn fit_moves_weight_to_the_term_that_separates_the_labels() {
+        // Matching pairs share structure but not vectors; the rest the reverse.
+        let concepts = vec![
+            concept(1, 0, 0),
+            concept(2, 1, 0),
+            concept(3, 0, 1),
+            concept(4, 2, 2),
+            concept(5, 3, 2),
+            concept(6, 2, 3),
+        ];
+        let train = [pair(1, 2, true), pair(1, 3, false)];
+        let held_out = [pair(4, 5, true), pair(4, 6, false)];
+        let report = fit_resonance_weights(
+            &concepts,
+            ResonanceWeights::default(),
+            &train,
+            &held_out,
+            WeightFitConfig {
+                recall_k: 1,
+                ..WeightFitConfig::default()
+            },
+        )
+        .expect("fit");
+
+        assert_eq!(report.candidates, 231);
+        assert_eq!(report.train_before.pair_accuracy, 0.0);
+        assert_eq!(report.train_after.pair_accuracy, 1.0);
+        assert!(report.after.gamma2 > report.after.gamma1);
+        assert_eq!(report.held_out_before.recall_at_k, 0.0);
+        assert_eq!(report.held_out_after.recall_at_k, 1.0);
+        assert_eq!(report.held_out_gain(), 1.0);
+    }
+
+    #[test]
+    fn fit_keeps_weights_it_cannot_improve_and_rejects_bad_input() {
+        let concepts = vec![concept(1, 0, 0), concept(2, 0, 0)];
+        let initial = ResonanceWeights::default();
+        let report = fit_resonance_weights(
+            &concepts,
+            initial,
+            &[pair(1, 2, true)],
+            &[],
+            WeightFitConfig::default(),
+        )
+        .expect("fit");
+        assert_eq!(report.after, initial.normalized());
+        assert_eq!(report.held_out_after.pairs, 0);
+
+        assert!(matches!(
+            fit_resonance_weights(
+                &concepts,
+                initial,
+                &[pair(1, 9, true)],
+                &[],
+                WeightFitConfig::default()
+            ),
+            Err(HybridVmError::ConceptNotFound(ConceptId(9)))
+        ));
+        let zero_step = WeightFitConfig {
+            grid_step: 0.0,
+            ..WeightFitConfig::default()
+        };
+        assert!(fit_resonance_weights(&concepts, initial, &[], &[], zero_step).is_err());
+    }
+}
@@ -0,0 +1,306 @@
+//! Memory and power budgets for embedded designs.
+//!
+//! Nodes declare what they consume through numeric `memory_kb` and
+//! `power_mw` attributes; a design's usage is the sum over its nodes. Limits
+//! come from requirement text ("メモリ512MB以下", "at most 2W") or from
+//! `Constraint::ResourceLimit`, converted to the attribute units.
+
+use std::collections::BTreeMap;
+
+use core_types::Constraint;
+use design_reasoning::BudgetCheck;
+use memory_space::{DesignState, Value};
+use semantic_dhm::{L1Id, RequirementKind};
+use serde::{Deserialize, Serialize};
+
+use crate::test_scaffold::{ConstraintBound, QuantitativeConstraint, quantitative_constraints};
+
+/// Limit units: spelling, resource, and the factor to the attribute unit.
+const LIMIT_UNITS: [(&str, Resource, f64); 7] = [
+    ("KB", Resource::Memory, 1.0),
+    ("MB", Resource::Memory, 1024.0),
+    ("GB", Resource::Memory, 1024.0 * 1024.0),
+    ("TB", Resource::Memory, 1024.0 * 1024.0 * 1024.0),
+    ("mW", Resource::Power, 1.0),
+    ("W", Resource::Power, 1000.0),
+    ("kW", Resource::Power, 1_000_000.0),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum Resource {
+    Memory,
+    Power,
+}
+
+impl Resource {
+    pub const ALL: [Resource; 2] = [Resource::Memory, Resource::Power];
+
+    /// Node attribute holding the node's consumption.
+    pub fn attribute(self) -> &'static str {
+        match self {
+            Self::Memory => "memory_kb",
+            Self::Power => "power_mw",
+        }
+    }
+
+    pub fn unit(self) -> &'static str {
+        match self {
+            Self::Memory => "KB",
+            Self::Power => "mW",
+        }
+    }
+
+    /// Requirement kind a limit on this resource stands for.
+    pub fn requirement_kind(self) -> Option<RequirementKind> {
+        match self {
+            Self::Memory => Some(RequirementKind::Memory),
+            Self::Power => None,
+        }
+    }
+
+    /// Matches `Constraint::ResourceLimit::resource` names.
+    fn from_name(name: &str) -> Option<Self> {
+        let lower = name.trim().to_lowercase();
+        if ["memory", "mem", "ram", "メモリ"]
+            .iter()
+            .any(|n| lower.starts_with(n))
+        {
+            Some(Self::Memory)
+        } else if ["power", "電力", "消費電力"]
+            .iter()
+            .any(|n| lower.starts_with(n))
+        {
+            Some(Self::Power)
+        } else {
+            None
+        }
+    }
+}
+
+/// `value` in `unit` converted to the attribute unit of its resource.
+fn limit_value(value: f64, unit: &str) -> Option<(Resource, f64)> {
+    LIMIT_UNITS
+        .iter()
+        .find(|(spelling, _, _)| *spelling == unit)
+        .map(|(_, resource, factor)| (*resource, value * factor))
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    pub totals: BTreeMap<Resource, f64>,
+    /// Nodes without a numeric annotation for the resource; they count as 0.
+    pub unannotated: BTreeMap<Resource, usize>,
+}
+
+impl ResourceUsage {
+    pub fn of(state: &DesignState) -> Self {
+        let mut usage = Self::default();
+        for resource in Resource::ALL {
+            let mut total = 0.0;
+            let mut missing = 0;
+            for node in state.graph.nodes().values() {
+                match node.attributes.get(resource.attribute()) {
+                    Some(Value::Int(v)) => total += (*v).max(0) as f64,
+                    Some(Value::Float(v)) if v.is_finite() => total += v.max(0.0),
+                    _ => missing += 1,
+                }
+            }
+            usage.totals.insert(resource, total);
+            usage.unannotated.insert(resource, missing);
+        }
+        usage
+    }
+
+    pub fn total(&self, resource: Resource) -> f64 {
+        self.totals.get(&resource).copied().unwrap_or(0.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ResourceCheck {
+    pub resource: Resource,
+    pub used: f64,
+    pub limit: f64,
+    pub unannotated_nodes: usize,
+}
+
+impl ResourceCheck {
+    pub fn is_exceeded(&self) -> bool {
+        self.used > self.limit
+    }
+
+    /// `used / limit`; infinite for a used resource with a zero limit.
+    pub fn utilization(&self) -> f64 {
+        self.to_budget_check().utilization()
+    }
+
+    pub fn to_budget_check(&self) -> BudgetCheck {
+        BudgetCheck {
+            kind: self.resource.requirement_kind(),
+            resource: self.resource.attribute().to_string(),
+            used: self.used,
+            limit: self.limit,
+        }
+    }
+}
+
+/// Upper limits per resource, in attribute units (KB, mW).
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct ResourceBudget {
+    pub limits: BTreeMap<Resource, f64>,
+}
+
+impl ResourceBudget {
+    /// Sets `limit`, or keeps the current one when it is tighter.
+    pub fn with_limit(mut self, resource: Resource, limit: f64) -> Self {
+        self.tighten(resource, limit);
+        self
+    }
+
+    fn tighten(&mut self, resource: Resource, limit: f64) {
+        let entry = self.limits.entry(resource).or_insert(limit);
+        *entry = entry.min(limit);
+    }
+
+    pub fn limit(&self, resource: Resource) -> Option<f64> {
+        self.limits.get(&resource).copied()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.limits.is_empty()
+    }
+
+    /// Upper bounds ("以下", "at most") in a memory or power unit; other
+    /// constraints are ignored. The tightest bound per resource wins.
+    pub fn from_quantitative(constraints: &[QuantitativeConstraint]) -> Self {
+        let mut budget = Self::default();
+        for constraint in constraints {
+            if constraint.bound != ConstraintBound::AtMost {
+                continue;
+            }
+            if let Some((resource, limit)) = limit_value(constraint.value, &constraint.unit) {
+                budget.tighten(resource, limit);
+            }
+        }
+        budget
+    }
+
+    /// Limits from requirement sentences; see `from_quantitative`.
+    pub fn from_requirement_text(text: &str) -> Self {
+        Self::from_quantitative(&quantitative_constraints(L1Id(0), text))
+    }
+
+    /// `Constraint::ResourceLimit`s naming memory or power whose limit reads
+    /// as a number and unit ("512MB"). The limit is an upper bound even
+    /// without a bound word.
+    pub fn from_constraints(constraints: &[Constraint]) -> Self {
+        let mut budget = Self::default();
+        for constraint in constraints {
+            let Constraint::ResourceLimit(spec) = constraint else {
+                continue;
+            };
+            let Some(named) = Resource::from_name(&spec.resource) else {
+                continue;
+            };
+            let parsed = quantitative_constraints(L1Id(0), &spec.limit)
+                .into_iter()
+                .find_map(|c| limit_value(c.value, &c.unit))
+                .filter(|(resource, _)| *resource == named);
+            if let Some((resource, limit)) = parsed {
+                budget.tighten(resource, limit);
+            }
+        }
+        budget
+    }
+
+    /// One check per limited resource.
+    pub fn check(&self, state: &DesignState) -> Vec<ResourceCheck> {
+        let usage = ResourceUsage::of(state);
+        self.limits
+            .iter()
+            .map(|(resource, limit)| ResourceCheck {
+                resource: *resource,
+                used: usage.total(*resource),
+                limit: *limit,
+                unannotated_nodes: usage.unannotated.get(resource).copied().unwrap_or(0),
+            })
+            .collect()
+    }
+
+    /// `1 - utilization` of the most utilized resource, in `[0, 1]`: 1 for
+    /// an idle design or no limits, 0 at or past a limit.
+    pub fn score(&self, state: &DesignState) -> f64 {
+        self.check(state)
+            .iter()
+            .map(|c| (1.0 - c.utilization()).clamp(0.0, 1.0))
+            .fold(1.0, f64::min)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use core_types::ResourceSpec;
+    use memory_space::{DesignNode, StructuralGraph, Uuid};
+
+    use super::*;
+
+    fn state(nodes: &[(&str, Value)]) -> DesignState {
+        let mut graph = StructuralGraph::default();
+        for (i, (key, value)) in nodes.iter().enumerate() {
+            graph = graph.with_node_added(DesignNode::new(
+                Uuid::from_u128(i as u128 + 1),
+                "Service",
+                BTreeMap::from([(key.to_string(), value.clone())]),
+            ));
+        }
+        DesignState::new(Uuid::from_u128(99), Arc::new(graph), "history:")
+    }
+
+    #[test]
+    fn limits_are_read_in_attribute_units_and_the_tightest_wins() {
+        let budget = ResourceBudget::from_requirement_text(
+            "メモリ512MB以下、消費電力は最大2W。ピーク時は1GB以下",
+        );
+        assert_eq!(budget.limit(Resource::Memory), Some(512.0 * 1024.0));
+        assert_eq!(budget.limit(Resource::Power), Some(2000.0));
+        // A target without a bound word is not a limit.
+        assert!(ResourceBudget::from_requirement_text("メモリ512MB").is_empty());
+
+        let from_ir = ResourceBudget::from_constraints(&[
+            Constraint::ResourceLimit(ResourceSpec {
+                resource: "memory".to_string(),
+                limit: "256 KB".to_string(),
+            }),
+            Constraint::ResourceLimit(ResourceSpec {
+                resource: "power".to_string(),
+                limit: "256 KB".to_string(),
+            }),
+            Constraint::Invariant("no-cycles".to_string()),
+        ]);
+        assert_eq!(from_ir.limits, BTreeMap::from([(Resource::Memory, 256.0)]));
+    }
+
+    #[test]
+    fn usage_sums_annotations_and_scores_the_tightest_resource() {
+        let design = state(&[
+            ("memory_kb", Value::Int(300)),
+            ("memory_kb", Value::Float(100.0)),
+            ("power_mw", Value::Int(900)),
+        ]);
+        let budget = ResourceBudget::default()
+            .with_limit(Resource::Memory, 500.0)
+            .with_limit(Resource::Power, 1000.0);
+        let checks = budget.check(&design);
+        assert_eq!(checks[0].used, 400.0);
+        assert_eq!(checks[0].unannotated_nodes, 1);
+        assert!(!checks.iter().any(ResourceCheck::is_exceeded));
+        assert!((budget.score(&design) - 0.1).abs() < 1e-9);
+
+        let tight = budget.with_limit(Resource::Memory, 200.0);
+        assert!(tight.check(&design)[0].is_exceeded());
+        assert_eq!(tight.score(&design), 0.0);
+        assert_eq!(ResourceBudget::default().score(&design), 1.0);
+    }
+}
@@ -0,0 +1,401 @@
+//! Review checklist for a design hypothesis.
+//!
+//! Three sources feed the checklist. Open questions come from
+//! `MissingInfo`. Concepts whose stability a simulated change moves are
+//! the risky ones to double-check, graded by how far they move and how
+//! wide the blast radius is. Unresolved conflicts are the requirement
+//! conflicts still waiting for a prioritization, plus any violation the
+//! hypothesis itself reports.
+
+use std::fmt::Write as _;
+
+use design_reasoning::DesignHypothesis;
+use semantic_dhm::{ConceptId, L1Id, RequirementKind};
+use serde::{Deserialize, Serialize};
+
+use crate::{BlastRadiusScore, GeneratedArtifact, InfoCategory, MissingInfo, SimulationReport};
+
+/// Blast radius total from which every risky concept is at least `Medium`.
+const MEDIUM_BLAST: f64 = 0.3;
+/// Blast radius total from which every risky concept is `High`.
+const HIGH_BLAST: f64 = 0.6;
+/// Stability shift that makes a concept `Medium` risk on its own.
+const MEDIUM_SHIFT: f64 = 0.1;
+/// Stability below which a concept ends up too fragile to pass unchecked.
+const FRAGILE_STABILITY: f64 = 0.3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ReviewSeverity {
+    Low,
+    Medium,
+    High,
+}
+
+impl ReviewSeverity {
+    /// Grades a `MissingInfo::importance`.
+    fn from_importance(importance: f64) -> Self {
+        if importance >= 0.85 {
+            Self::High
+        } else if importance >= 0.7 {
+            Self::Medium
+        } else {
+            Self::Low
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Self::Low => "low",
+            Self::Medium => "medium",
+            Self::High => "high",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ReviewSection {
+    Question,
+    RiskyConcept,
+    Conflict,
+}
+
+impl ReviewSection {
+    const ALL: [Self; 3] = [Self::Question, Self::RiskyConcept, Self::Conflict];
+
+    fn heading(self) -> &'static str {
+        match self {
+            Self::Question => "Questions to ask",
+            Self::RiskyConcept => "Risky concepts to double-check",
+            Self::Conflict => "Unresolved conflicts",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ReviewEntity {
+    L1(L1Id),
+    Concept(ConceptId),
+    Requirement(RequirementKind),
+}
+
+impl ReviewEntity {
+    fn label(self) -> String {
+        match self {
+            Self::L1(id) => format!("L1-{}", id.0),
+            Self::Concept(id) => format!("L2-{}", id.0),
+            Self::Requirement(kind) => format!("{kind:?}"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReviewItem {
+    pub section: ReviewSection,
+    pub severity: ReviewSeverity,
+    pub text: String,
+    pub entities: Vec<ReviewEntity>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ReviewChecklist {
+    pub hypothesis_score: f64,
+    pub blast_radius: f64,
+    /// Grouped by section in `ReviewSection` order, most severe first
+    /// within a section.
+    pub items: Vec<ReviewItem>,
+}
+
+impl ReviewChecklist {
+    pub fn build(
+        hypothesis: &DesignHypothesis,
+        change: &SimulationReport,
+        blast: &BlastRadiusScore,
+        missing: &[MissingInfo],
+    ) -> Self {
+        let mut items = Vec::new();
+        for info in missing {
+            let (section, entities) = match info.category {
+                InfoCategory::Objective => (
+                    ReviewSection::Conflict,
+                    info.concept_id
+                        .map(ReviewEntity::Concept)
+                        .into_iter()
+                        .collect(),
+                ),
+                _ => (
+                    ReviewSection::Question,
+                    info.target_id.map(ReviewEntity::L1).into_iter().collect(),
+                ),
+            };
+            items.push(ReviewItem {
+                section,
+                severity: ReviewSeverity::from_importance(info.importance),
+                text: info.prompt.clone(),
+                entities,
+            });
+        }
+
+        for impact in &change.affected_concepts {
+            let shift = impact.simulated_stability - impact.original_stability;
+            let severity = if blast.total_score >= HIGH_BLAST
+                || impact.simulated_stability < FRAGILE_STABILITY
+            {
+                ReviewSeverity::High
+            } else if blast.total_score >= MEDIUM_BLAST || shift.abs() >= MEDIUM_SHIFT {
+                ReviewSeverity::Medium
+            } else {
+                ReviewSeverity::Low
+            };
+            items.push(ReviewItem {
+                section: ReviewSection::RiskyConcept,
+                severity,
+                text: format!(
+                    "Double-check L2-{}: stability {:.2} -> {:.2}",
+                    impact.concept_id.0, impact.original_stability, impact.simulated_stability
+                ),
+                entities: vec![ReviewEntity::Concept(impact.concept_id)],
+            });
+        }
+
+        if hypothesis.must_violation || hypothesis.constraint_violation {
+            let mut entities = hypothesis
+                .violated_requirements()
+                .map(|d| ReviewEntity::Requirement(d.kind))
+                .collect::<Vec<_>>();
+            entities.sort();
+            entities.dedup();
+            let (severity, text) = if hypothesis.must_violation {
+                (
+                    ReviewSeverity::High,
+                    "A Must requirement is violated; the hypothesis scores -1 until it is resolved",
+                )
+            } else {
+                (
+                    ReviewSeverity::Medium,
+                    "A hard constraint is pulled against by the hypothesis",
+                )
+            };
+            items.push(ReviewItem {
+                section: ReviewSection::Conflict,
+                severity,
+                text: text.to_string(),
+                entities,
+            });
+        }
+
+        // Stable, so items of equal rank keep their source order.
+        items.sort_by(|a, b| a.section.cmp(&b.section).then(b.severity.cmp(&a.severity)));
+        Self {
+            hypothesis_score: hypothesis.normalized_score,
+            blast_radius: blast.total_score,
+            items,
+        }
+    }
+
+    pub fn section(&self, section: ReviewSection) -> impl Iterator<Item = &ReviewItem> {
+        self.items
+            .iter()
+            .filter(move |item| item.section == section)
+    }
+
+    pub fn highest_severity(&self) -> Option<ReviewSeverity> {
+        self.items.iter().map(|item| item.severity).max()
+    }
+
+    /// Markdown with one unchecked item per entry, under a heading per
+    /// section.
+    pub fn to_markdown(&self) -> String {
+        let mut out = String::from("## Design review checklist\n\n");
+        let _ = writeln!(
+            out,
+            "Hypothesis score {:.3}, blast radius {:.2}.",
+            self.hypothesis_score, self.blast_radius
+        );
+        for section in ReviewSection::ALL {
+            let _ = writeln!(out, "\n### {}\n", section.heading());
+            let mut empty = true;
+            for item in self.section(section) {
+                empty = false;
+                let _ = write!(out, "- [ ] [{}] {}", item.severity.label(), item.text);
+                if !item.entities.is_empty() {
+                    let labels = item.entities.iter().map(|e| e.label()).collect::<Vec<_>>();
+                    let _ = write!(out, " ({})", labels.join(", "));
+                }
+                out.push('\n');
+            }
+            if empty {
+                out.push_str("None.\n");
+            }
+        }
+        out
+    }
+
+    pub fn artifact(&self) -> GeneratedArtifact {
+        GeneratedArtifact {
+            file_name: "review_checklist.md".to_string(),
+            content: self.to_markdown(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core_types::ObjectiveVector;
+    use semantic_dhm::DerivedRequirement;
+
+    use super::*;
+    use crate::ConceptImpact;
+
+    fn hypothesis(must_violation: bool) -> DesignHypothesis {
+        DesignHypothesis {
+            requirements: vec![
+                DerivedRequirement {
+                    kind: RequirementKind::Performance,
+                    strength: -0.4,
+                },
+                DerivedRequirement {
+                    kind: RequirementKind::Memory,
+                    strength: 0.6,
+                },
+                DerivedRequirement {
+                    kind: RequirementKind::Security,
+                    strength: 0.5,
+                },
+            ],
+            total_score: 0.1,
+            normalized_score: if must_violation { -1.0 } else { 0.1 },
+            constraint_violation: true,
+            must_violation,
+            budget_checks: vec![],
+        }
+    }
+
+    fn change(impacts: &[(u64, f64, f64)]) -> SimulationReport {
+        let objectives = ObjectiveVector {
+            f_struct: 0.5,
+            f_field: 0.5,
+            f_risk: 0.5,
+            f_shape: 0.5,
+        };
+        SimulationReport {
+            original_objectives: objectives.clone(),
+            simulated_objectives: objectives,
+            affected_concepts: impacts
+                .iter()
+                .map(|(id, before, after)| ConceptImpact {
+                    concept_id: ConceptId(*id),
+                    original_stability: *before,
+                    simulated_stability: *after,
+                })
+                .collect(),
+            total_concepts: 4,
+        }
+    }
+
+    fn blast(total_score: f64) -> BlastRadiusScore {
+        BlastRadiusScore {
+            coverage: total_score,
+            intensity: total_score,
+            structural_risk: 0.0,
+            total_score,
+        }
+    }
+
+    fn missing() -> Vec<MissingInfo> {
+        vec![
+            MissingInfo {
+                target_id: Some(L1Id(3)),
+                category: InfoCategory::Classification,
+                prompt: "constraint or goal?".to_string(),
+                importance: 0.76,
+                source: None,
+                concept_id: None,
+            },
+            MissingInfo {
+                target_id: None,
+                category: InfoCategory::Objective,
+                prompt: "prioritize L2-7".to_string(),
+                importance: 0.85,
+                source: None,
+                concept_id: Some(ConceptId(7)),
+            },
+        ]
+    }
+
+    #[test]
+    fn items_are_grouped_graded_and_linked() {
+        let checklist = ReviewChecklist::build(
+            &hypothesis(true),
+            &change(&[(1, 0.8, 0.75), (2, 0.5, 0.2), (3, 0.6, 0.45)]),
+            &blast(0.2),
+            &missing(),
+        );
+        let summary = checklist
+            .items
+            .iter()
+            .map(|i| (i.section, i.severity, i.entities.clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    ReviewSection::Question,
+                    ReviewSeverity::Medium,
+                    vec![ReviewEntity::L1(L1Id(3))]
+                ),
+                (
+                    ReviewSection::RiskyConcept,
+                    ReviewSeverity::High,
+                    vec![ReviewEntity::Concept(ConceptId(2))]
+                ),
+                (
+                    ReviewSection::RiskyConcept,
+                    ReviewSeverity::Medium,
+                    vec![ReviewEntity::Concept(ConceptId(3))]
+                ),
+                (
+                    ReviewSection::RiskyConcept,
+                    ReviewSeverity::Low,
+                    vec![ReviewEntity::Concept(ConceptId(1))]
+                ),
+                (
+                    ReviewSection::Conflict,
+                    ReviewSeverity::High,
+                    vec![ReviewEntity::Concept(ConceptId(7))]
+                ),
+                (
+                    ReviewSection::Conflict,
+                    ReviewSeverity::High,
+                    vec![
+                        ReviewEntity::Requirement(RequirementKind::Performance),
+                        ReviewEntity::Requirement(RequirementKind::Memory),
+                    ]
+                ),
+            ]
+        );
+        assert_eq!(checklist.highest_severity(), Some(ReviewSeverity::High));
+
+        // A wide blast radius makes every moved concept high risk.
+        let wide = ReviewChecklist::build(
+            &hypothesis(false),
+            &change(&[(1, 0.8, 0.75)]),
+            &blast(0.7),
+            &[],
+        );
+        assert_eq!(wide.section(ReviewSection::RiskyConcept).count(), 1);
+        assert!(wide.items.iter().all(|i| i.severity != ReviewSeverity::Low));
+    }
+
+    #[test]
+    fn markdown_lists_every_section() {
+        let checklist =
+            ReviewChecklist::build(&hypothesis(false), &change(&[]), &blast(0.0), &missing());
+        let markdown = checklist.artifact().content;
+        assert!(markdown.starts_with("## Design review checklist\n"));
+        assert!(markdown.contains("- [ ] [medium] constraint or goal? (L1-3)"));
+        assert!(markdown.contains("### Risky concepts to double-check\n\nNone.\n"));
+        assert!(markdown.contains("- [ ] [high] prioritize L2-7 (L2-7)"));
+        assert!(markdown.contains("- [ ] [medium] A hard constraint is pulled against"));
+        assert!(markdown.contains("(Performance, Memory)"));
+    }
+}
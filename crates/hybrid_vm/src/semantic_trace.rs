@@ -0,0 +1,133 @@
+//! Trace of the semantic operations a `HybridVM` performed.
+//!
+//! Evaluations are traced as `HybridTraceRow`s; analyses, ingests, imports
+//! and L2 rebuilds are traced here. Both draw their `seq` from one counter,
+//! so the two traces interleave exactly, and both carry the correlation id
+//! that was set when the entry was made. That id is how a caller (a design
+//! pipeline run, say) finds its own entries and joins them with traces the
+//! VM never sees, such as the search's.
+
+use std::fmt;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use core_types::clock;
+use semantic_dhm::{ConceptId, L1Id, L2ChangeSet};
+use serde::{Deserialize, Serialize};
+
+/// Id of the run trace entries belong to; displayed as 16 hex digits.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub struct CorrelationId(pub u64);
+
+impl CorrelationId {
+    /// A fresh id: a per-process base taken from the clock, plus a counter,
+    /// so ids generated in one process never repeat.
+    pub fn generate() -> Self {
+        static BASE: OnceLock<u64> = OnceLock::new();
+        static COUNTER: AtomicU64 = AtomicU64::new(0);
+        let base = *BASE.get_or_init(|| {
+            clock::unix_time_nanos() as u64 ^ crate::ops::util::process_salt().rotate_left(32)
+        });
+        Self(base.wrapping_add(COUNTER.fetch_add(1, Ordering::Relaxed)))
+    }
+}
+
+impl fmt::Display for CorrelationId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum SemanticOperation {
+    /// `analyze_text` / `analyze_document`.
+    Analyze {
+        document_id: String,
+        added: Vec<L1Id>,
+        concept: ConceptId,
+    },
+    IngestDocument {
+        document_id: String,
+        added: Vec<L1Id>,
+        removed: Vec<L1Id>,
+    },
+    ImportRequirements {
+        document_id: String,
+        added: Vec<L1Id>,
+        changes: L2ChangeSet,
+    },
+    RebuildL2 {
+        changes: L2ChangeSet,
+    },
+}
+
+impl SemanticOperation {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Analyze { .. } => "analyze",
+            Self::IngestDocument { .. } => "ingest_document",
+            Self::ImportRequirements { .. } => "import_requirements",
+            Self::RebuildL2 { .. } => "rebuild_l2",
+        }
+    }
+}
+
+impl fmt::Display for SemanticOperation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ids = |ids: &[L1Id]| {
+            ids.iter()
+                .map(|id| format!("L1-{}", id.0))
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+        let changes = |c: &L2ChangeSet| {
+            format!(
+                "{} created, {} updated, {} deleted",
+                c.created.len(),
+                c.updated.len(),
+                c.deleted.len()
+            )
+        };
+        match self {
+            Self::Analyze {
+                document_id,
+                added,
+                concept,
+            } => write!(
+                f,
+                "analyze {document_id}: +[{}] -> L2-{}",
+                ids(added),
+                concept.0
+            ),
+            Self::IngestDocument {
+                document_id,
+                added,
+                removed,
+            } => write!(
+                f,
+                "ingest {document_id}: +[{}] -[{}]",
+                ids(added),
+                ids(removed)
+            ),
+            Self::ImportRequirements {
+                document_id,
+                added,
+                changes: c,
+            } => write!(
+                f,
+                "import {document_id}: +[{}]; L2 {}",
+                ids(added),
+                changes(c)
+            ),
+            Self::RebuildL2 { changes: c } => write!(f, "rebuild L2: {}", changes(c)),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SemanticTraceEvent {
+    /// Shared with `HybridTraceRow::seq`.
+    pub seq: u64,
+    pub correlation_id: Option<CorrelationId>,
+    pub operation: SemanticOperation,
+}
@@ -0,0 +1,122 @@
+//! Workspace-level statistics for dashboards.
+//!
+//! `HybridVM::workspace_stats` gathers in one pass what a dashboard would
+//! otherwise assemble from a dozen queries: how many L1 units and concepts
+//! there are by role and status, how ambiguity and stability are spread,
+//! how much of L2 is grounded, feedback totals, store sizes, and when each
+//! layer last changed.
+
+use serde::{Deserialize, Serialize};
+
+use crate::memory_usage::ComponentUsage;
+
+/// Equal-width bins over `[0, 1]` in every `Histogram`.
+pub const HISTOGRAM_BINS: usize = 10;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RoleCounts {
+    pub goal: usize,
+    pub constraint: usize,
+    pub optimization: usize,
+    pub prohibition: usize,
+}
+
+/// Counts by `CardStatus`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusCounts {
+    pub hypothetical: usize,
+    pub grounded: usize,
+    pub confirmed: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L1Stats {
+    pub total: usize,
+    pub by_role: RoleCounts,
+    /// Status of each unit's design card.
+    pub by_status: StatusCounts,
+    /// Units tagged with a `RequirementPriority`.
+    pub prioritized: usize,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L2Stats {
+    pub total: usize,
+    /// Grounded once grounding data is attached.
+    pub by_status: StatusCounts,
+    /// Concepts with at least one refinement.
+    pub refined: usize,
+    /// Concepts with an owner or a lock.
+    pub restricted: usize,
+}
+
+/// Values clamped into `[0, 1]` and counted in `HISTOGRAM_BINS` bins; the
+/// last bin includes 1.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Histogram {
+    pub bins: Vec<usize>,
+    /// 0 without samples.
+    pub mean: f64,
+}
+
+impl Histogram {
+    pub fn from_values(values: impl IntoIterator<Item = f64>) -> Self {
+        let mut bins = vec![0; HISTOGRAM_BINS];
+        let (mut sum, mut n) = (0.0, 0usize);
+        for value in values.into_iter().filter(|v| v.is_finite()) {
+            let value = value.clamp(0.0, 1.0);
+            let bin = ((value * HISTOGRAM_BINS as f64) as usize).min(HISTOGRAM_BINS - 1);
+            bins[bin] += 1;
+            sum += value;
+            n += 1;
+        }
+        Self {
+            bins,
+            mean: if n == 0 { 0.0 } else { sum / n as f64 },
+        }
+    }
+
+    pub fn samples(&self) -> usize {
+        self.bins.iter().sum()
+    }
+}
+
+impl Default for Histogram {
+    fn default() -> Self {
+        Self::from_values([])
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FeedbackTotals {
+    pub adopted: usize,
+    pub rejected: usize,
+}
+
+/// Latest change per layer, each in the unit its source records; `None`
+/// when the layer holds nothing timestamped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LastModified {
+    /// Newest `L1Provenance::ingested_at`.
+    pub l1_ingested_at_ms: Option<u64>,
+    /// Newest `ConceptUnit::timestamp`.
+    pub l2_updated_at_secs: Option<u64>,
+    /// Newest knowledge-store feedback.
+    pub feedback_at_secs: Option<u64>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct WorkspaceStats {
+    pub l1: L1Stats,
+    pub l2: L2Stats,
+    /// Of L1 `ambiguity_score`.
+    pub ambiguity: Histogram,
+    /// Of L2 `stability_score`.
+    pub stability: Histogram,
+    /// Share of concepts with grounding data; 0 without concepts.
+    pub grounding_coverage: f64,
+    pub feedback: FeedbackTotals,
+    /// The store components of `HybridVM::memory_report`.
+    pub stores: Vec<ComponentUsage>,
+    pub last_modified: LastModified,
+}
@@ -0,0 +1,395 @@
+//! `ArtifactFormat::RustTests`: `#[test]` scaffolds that trace back to the
+//! requirements they check.
+//!
+//! Each concept gets one test per requirement kind it carries, named
+//! `concept_<id>_<kind>`. Numbers with a unit in the concept's L1 text
+//! ("200ms以下", "at least 99.9%") become quantitative constraints, and the
+//! test for the matching kind asserts them against a measurement left as
+//! NaN, so the test fails until someone fills it in. Tests without a
+//! quantitative constraint hold a `todo!` naming what to assert.
+
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+
+use semantic_dhm::{ConceptUnitV2, L1Id, RequirementKind};
+use serde::{Deserialize, Serialize};
+
+use crate::{ArtifactSources, ArtifactTemplate, GeneratedArtifact, artifact_trace_hash};
+
+/// Units read after a number, matched case-insensitively, longest first:
+/// spelling, canonical unit, and the requirement kind the unit measures.
+const UNITS: [(&str, &str, Option<RequirementKind>); 16] = [
+    ("ミリ秒", "ms", Some(RequirementKind::Performance)),
+    ("msec", "ms", Some(RequirementKind::Performance)),
+    ("sec", "s", Some(RequirementKind::Performance)),
+    ("rps", "rps", Some(RequirementKind::Performance)),
+    ("qps", "qps", Some(RequirementKind::Performance)),
+    ("tps", "tps", Some(RequirementKind::Performance)),
+    ("ms", "ms", Some(RequirementKind::Performance)),
+    ("kb", "KB", Some(RequirementKind::Memory)),
+    ("mb", "MB", Some(RequirementKind::Memory)),
+    ("gb", "GB", Some(RequirementKind::Memory)),
+    ("tb", "TB", Some(RequirementKind::Memory)),
+    ("秒", "s", Some(RequirementKind::Performance)),
+    ("s", "s", Some(RequirementKind::Performance)),
+    ("%", "%", Some(RequirementKind::Reliability)),
+    ("％", "%", Some(RequirementKind::Reliability)),
+    ("件", "items", None),
+];
+/// Requirements weaker than this, either way, get no test of their own.
+const MIN_REQUIREMENT_STRENGTH: f32 = 0.1;
+/// Bounds written after the value, as Japanese does.
+const SUFFIX_BOUNDS: [(&str, ConstraintBound); 4] = [
+    ("以下", ConstraintBound::AtMost),
+    ("未満", ConstraintBound::AtMost),
+    ("以内", ConstraintBound::AtMost),
+    ("以上", ConstraintBound::AtLeast),
+];
+/// Bounds written before the value; the one closest to it wins.
+const PREFIX_BOUNDS: [(&str, ConstraintBound); 14] = [
+    ("at most", ConstraintBound::AtMost),
+    ("less than", ConstraintBound::AtMost),
+    ("under", ConstraintBound::AtMost),
+    ("below", ConstraintBound::AtMost),
+    ("within", ConstraintBound::AtMost),
+    ("max", ConstraintBound::AtMost),
+    ("最大", ConstraintBound::AtMost),
+    ("上限", ConstraintBound::AtMost),
+    ("at least", ConstraintBound::AtLeast),
+    ("more than", ConstraintBound::AtLeast),
+    ("over", ConstraintBound::AtLeast),
+    ("min", ConstraintBound::AtLeast),
+    ("最小", ConstraintBound::AtLeast),
+    ("下限", ConstraintBound::AtLeast),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ConstraintBound {
+    AtMost,
+    AtLeast,
+    /// No bound word: the value itself is the target.
+    Exactly,
+}
+
+impl ConstraintBound {
+    fn operator(self) -> &'static str {
+        match self {
+            Self::AtMost => "<=",
+            Self::AtLeast => ">=",
+            Self::Exactly => "==",
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct QuantitativeConstraint {
+    pub source: L1Id,
+    pub bound: ConstraintBound,
+    pub value: f64,
+    /// Canonical unit, the word after the number when it is not a known
+    /// unit, or empty.
+    pub unit: String,
+    /// Kind the unit measures; `None` for counts and unknown units.
+    pub kind: Option<RequirementKind>,
+}
+
+/// Every number in `text` with the unit and bound around it.
+pub fn quantitative_constraints(source: L1Id, text: &str) -> Vec<QuantitativeConstraint> {
+    let mut out = Vec::new();
+    let mut rest = text;
+    let mut offset = 0;
+    while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+        let number_len = rest[start..]
+            .find(|c: char| !(c.is_ascii_digit() || c == '.' || c == ','))
+            .unwrap_or(rest.len() - start);
+        let number = rest[start..start + number_len].trim_end_matches(['.', ',']);
+        let after = &rest[start + number.len()..];
+        let consumed = start + number.len();
+        let Ok(value) = number.replace(',', "").parse::<f64>() else {
+            offset += consumed;
+            rest = after;
+            continue;
+        };
+        let (unit, kind, unit_len) = read_unit(after);
+        let tail = after[unit_len..].trim_start();
+        let bound = SUFFIX_BOUNDS
+            .iter()
+            .find(|(word, _)| tail.starts_with(word))
+            .map(|(_, bound)| *bound)
+            .or_else(|| prefix_bound(clause_before(text, offset, offset + start)))
+            .unwrap_or(ConstraintBound::Exactly);
+        out.push(QuantitativeConstraint {
+            source,
+            bound,
+            value,
+            unit,
+            kind,
+        });
+        offset += consumed + unit_len;
+        rest = &after[unit_len..];
+    }
+    out
+}
+
+/// The unit at the start of `text`, after at most one space, and the bytes
+/// it takes up.
+fn read_unit(text: &str) -> (String, Option<RequirementKind>, usize) {
+    let trimmed = text.strip_prefix(' ').unwrap_or(text);
+    let skipped = text.len() - trimmed.len();
+    let lower = trimmed.to_lowercase();
+    for (spelling, unit, kind) in UNITS {
+        if let Some(next) = lower.strip_prefix(spelling)
+            && !next.starts_with(|c: char| c.is_ascii_alphabetic())
+        {
+            return (unit.to_string(), kind, skipped + spelling.len());
+        }
+    }
+    let word_len = trimmed
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(trimmed.len());
+    if word_len == 0 {
+        return (String::new(), None, 0);
+    }
+    (trimmed[..word_len].to_string(), None, skipped + word_len)
+}
+
+/// `text[..end]` from the start of its clause, never reaching back past
+/// `floor`, where the previous constraint ended.
+fn clause_before(text: &str, floor: usize, end: usize) -> &str {
+    let before = &text[floor..end];
+    let start = before.rfind([',', ';', '、', '。', '，']).map_or(0, |at| {
+        at + before[at..].chars().next().map_or(1, char::len_utf8)
+    });
+    &before[start..]
+}
+
+fn prefix_bound(before: &str) -> Option<ConstraintBound> {
+    let lower = before.to_lowercase();
+    PREFIX_BOUNDS
+        .iter()
+        .filter_map(|(word, bound)| lower.rfind(word).map(|at| (at + word.len(), *bound)))
+        .max_by_key(|(end, _)| *end)
+        .map(|(_, bound)| bound)
+}
+
+/// Built-in `ArtifactFormat::RustTests` template: one
+/// `concept_<id>_tests.rs` per concept with at least one requirement of
+/// strength 0.1 or more, either way, or a quantitative constraint.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RustTestTemplate;
+
+impl ArtifactTemplate for RustTestTemplate {
+    fn render(&self, l2_units: &[ConceptUnitV2]) -> Vec<GeneratedArtifact> {
+        self.render_with_sources(l2_units, &ArtifactSources::default())
+    }
+
+    fn render_with_sources(
+        &self,
+        l2_units: &[ConceptUnitV2],
+        sources: &ArtifactSources,
+    ) -> Vec<GeneratedArtifact> {
+        l2_units
+            .iter()
+            .filter_map(|concept| render_concept(concept, sources))
+            .collect()
+    }
+}
+
+fn render_concept(concept: &ConceptUnitV2, sources: &ArtifactSources) -> Option<GeneratedArtifact> {
+    let texts = sources.l1_texts(concept.id);
+    let constraints = texts
+        .iter()
+        .flat_map(|(id, text)| quantitative_constraints(*id, text))
+        .collect::<Vec<_>>();
+    let requirements = concept
+        .derived_requirements
+        .iter()
+        .filter(|r| r.strength.abs() >= MIN_REQUIREMENT_STRENGTH)
+        .collect::<Vec<_>>();
+    let mut kinds = Vec::new();
+    for kind in requirements
+        .iter()
+        .map(|r| r.kind)
+        .chain(constraints.iter().filter_map(|c| c.kind))
+    {
+        if !kinds.contains(&kind) {
+            kinds.push(kind);
+        }
+    }
+    if kinds.is_empty() {
+        return None;
+    }
+
+    let id = concept.id.0;
+    let mut content = String::new();
+    content.push_str("// Auto-generated by RFC-012 Artifact Transformer\n");
+    let _ = writeln!(
+        content,
+        "// source_concept: L2-{id}, trace_hash: {:016x}",
+        artifact_trace_hash(concept)
+    );
+    content.push_str("// Test scaffolds: replace each TODO with a check of the implementation.\n");
+    for (l1, text) in texts {
+        let _ = writeln!(content, "// L1-{}: {}", l1.0, one_line(text));
+    }
+    let _ = writeln!(content, "\n#[cfg(test)]\nmod concept_{id}_requirements {{");
+    let mut names = BTreeSet::new();
+    for kind in kinds {
+        let name = format!("concept_{id}_{}", kind_name(kind));
+        if !names.insert(name.clone()) {
+            continue;
+        }
+        content.push('\n');
+        match requirements.iter().find(|r| r.kind == kind) {
+            Some(req) => {
+                let _ = writeln!(
+                    content,
+                    "    // requirement: {kind:?} (strength={:.2})",
+                    req.strength
+                );
+            }
+            None => {
+                let _ = writeln!(
+                    content,
+                    "    // requirement: {kind:?} (from L1 constraints)"
+                );
+            }
+        }
+        let _ = writeln!(content, "    #[test]\n    fn {name}() {{");
+        let matching = constraints
+            .iter()
+            .filter(|c| c.kind == Some(kind))
+            .collect::<Vec<_>>();
+        if matching.is_empty() {
+            let _ = writeln!(
+                content,
+                "        todo!(\"assert that concept {id} meets its {kind:?} requirement\");"
+            );
+        }
+        for (i, constraint) in matching.into_iter().enumerate() {
+            let var = format!("measured_{}", i + 1);
+            let op = constraint.bound.operator();
+            let expected = format!("{:?}", constraint.value);
+            let _ = writeln!(
+                content,
+                "        // constraint: {op} {expected} {} (L1-{})",
+                constraint.unit, constraint.source.0
+            );
+            let _ = writeln!(
+                content,
+                "        let {var}: f64 = f64::NAN; // TODO: measure in {}",
+                constraint.unit
+            );
+            let _ = writeln!(
+                content,
+                "        assert!({var} {op} {expected}, \"L1-{}: expected {op} {expected} {}, measured {{}}\", {var});",
+                constraint.source.0, constraint.unit
+            );
+        }
+        content.push_str("    }\n");
+    }
+    content.push_str("}\n");
+    Some(GeneratedArtifact {
+        file_name: format!("concept_{id}_tests.rs"),
+        content,
+    })
+}
+
+fn kind_name(kind: RequirementKind) -> &'static str {
+    match kind {
+        RequirementKind::Performance => "performance",
+        RequirementKind::Memory => "memory",
+        RequirementKind::Security => "security",
+        RequirementKind::NoCloud => "no_cloud",
+        RequirementKind::Reliability => "reliability",
+    }
+}
+
+fn one_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use semantic_dhm::{ConceptId, DerivedRequirement};
+
+    use super::*;
+
+    #[test]
+    fn constraints_read_units_and_bounds_on_either_side() {
+        let found = quantitative_constraints(
+            L1Id(4),
+            "レスポンス200ms以下, at least 99.9% uptime, memory max 1,024 MB; 3 replicas under 2 s",
+        );
+        let summary = found
+            .iter()
+            .map(|c| (c.value, c.unit.as_str(), c.bound, c.kind))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary,
+            [
+                (
+                    200.0,
+                    "ms",
+                    ConstraintBound::AtMost,
+                    Some(RequirementKind::Performance)
+                ),
+                (
+                    99.9,
+                    "%",
+                    ConstraintBound::AtLeast,
+                    Some(RequirementKind::Reliability)
+                ),
+                (
+                    1024.0,
+                    "MB",
+                    ConstraintBound::AtMost,
+                    Some(RequirementKind::Memory)
+                ),
+                (3.0, "replicas", ConstraintBound::Exactly, None),
+                (
+                    2.0,
+                    "s",
+                    ConstraintBound::AtMost,
+                    Some(RequirementKind::Performance)
+                ),
+            ]
+        );
+        assert!(found.iter().all(|c| c.source == L1Id(4)));
+        assert!(quantitative_constraints(L1Id(1), "クラウドは使わない").is_empty());
+    }
+
+    #[test]
+    fn scaffolds_name_tests_after_concept_and_kind() {
+        let concept = ConceptUnitV2 {
+            id: ConceptId(7),
+            derived_requirements: vec![
+                DerivedRequirement {
+                    kind: RequirementKind::Performance,
+                    strength: 0.8,
+                },
+                DerivedRequirement {
+                    kind: RequirementKind::NoCloud,
+                    strength: 0.5,
+                },
+            ],
+            causal_links: Vec::new(),
+            stability_score: 0.9,
+        };
+        let mut sources = ArtifactSources::default();
+        sources.insert(concept.id, L1Id(3), "応答時間は200ms以内");
+        let artifacts =
+            RustTestTemplate.render_with_sources(std::slice::from_ref(&concept), &sources);
+        assert_eq!(artifacts.len(), 1);
+        let content = &artifacts[0].content;
+        assert_eq!(artifacts[0].file_name, "concept_7_tests.rs");
+        assert!(content.contains("source_concept: L2-7"));
+        assert!(content.contains("fn concept_7_performance()"));
+        assert!(content.contains("assert!(measured_1 <= 200.0"));
+        assert!(content.contains("fn concept_7_no_cloud()"));
+        assert!(content.contains("todo!(\"assert that concept 7 meets its NoCloud requirement\")"));
+
+        let plain = RustTestTemplate.render(&[concept]);
+        assert!(!plain[0].content.contains("measured_1"));
+    }
+}
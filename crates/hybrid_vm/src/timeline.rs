@@ -0,0 +1,344 @@
+//! Per-concept timelines over a history of `snapshot_v2` snapshots.
+//!
+//! Each concept gets one point per snapshot, carrying its stability,
+//! abstraction, L1 composition and grounding count, plus the events that
+//! happened between consecutive snapshots. A concept that is absent from a
+//! snapshot gets an empty point, so a plot can break its line there.
+//!
+//! Concepts are rebuilt from L1 units, so one can vanish into others. When
+//! that happens its L1 refs are followed: refs that end up in a single
+//! concept mark a merge, refs spread over several mark a split, and a new
+//! concept built from refs others held before records where it came from.
+//! Snapshots without per-unit digests carry no concept data and are skipped.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use design_reasoning::{L2UnitDigest, MeaningLayerSnapshotV2};
+use semantic_dhm::{ConceptId, L1Id, stability_from_abstraction};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TimelinePoint {
+    /// Position in the history given to `concept_timelines`.
+    pub snapshot: usize,
+    pub timestamp_ms: u64,
+    /// `None` where the concept is absent.
+    pub stability: Option<f64>,
+    pub abstraction: Option<f32>,
+    pub l1_refs: Vec<L1Id>,
+    pub grounding_count: usize,
+}
+
+impl TimelinePoint {
+    pub fn is_present(&self) -> bool {
+        self.stability.is_some()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum TimelineEventKind {
+    /// First appearance, from L1 units no concept held before.
+    Created,
+    /// Appearance from L1 units previously held by these concepts.
+    Derived {
+        from: Vec<ConceptId>,
+    },
+    RefsAdded(Vec<L1Id>),
+    RefsRemoved(Vec<L1Id>),
+    Grounded {
+        added: usize,
+    },
+    /// Gone, and so are its L1 units.
+    Removed,
+    /// Gone; its L1 units now all belong to `target`.
+    MergedInto {
+        target: ConceptId,
+    },
+    /// Gone; its L1 units are spread over `targets`.
+    SplitInto {
+        targets: Vec<ConceptId>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct TimelineEvent {
+    /// The snapshot where the change is first seen.
+    pub snapshot: usize,
+    pub timestamp_ms: u64,
+    pub kind: TimelineEventKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ConceptTimeline {
+    pub concept_id: ConceptId,
+    /// One per usable snapshot, oldest first.
+    pub points: Vec<TimelinePoint>,
+    pub events: Vec<TimelineEvent>,
+}
+
+impl ConceptTimeline {
+    /// `(timestamp_ms, stability)` for the snapshots holding the concept.
+    pub fn stability_series(&self) -> Vec<(u64, f64)> {
+        self.points
+            .iter()
+            .filter_map(|p| p.stability.map(|s| (p.timestamp_ms, s)))
+            .collect()
+    }
+}
+
+/// Timelines of every concept seen in `history`, by concept id. The history
+/// is taken in the order given.
+pub fn concept_timelines(history: &[MeaningLayerSnapshotV2]) -> Vec<ConceptTimeline> {
+    let snapshots = history
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| !s.l2_units.is_empty() || !s.l1_units.is_empty())
+        .map(|(index, s)| {
+            let units = s
+                .l2_units
+                .iter()
+                .map(|d| (d.id, d))
+                .collect::<BTreeMap<_, _>>();
+            (index, s.timestamp_ms, units)
+        })
+        .collect::<Vec<_>>();
+    let ids = snapshots
+        .iter()
+        .flat_map(|(_, _, units)| units.keys().copied())
+        .collect::<BTreeSet<_>>();
+    let mut timelines = ids
+        .iter()
+        .map(|id| {
+            (
+                *id,
+                ConceptTimeline {
+                    concept_id: *id,
+                    points: Vec::with_capacity(snapshots.len()),
+                    events: Vec::new(),
+                },
+            )
+        })
+        .collect::<BTreeMap<_, _>>();
+
+    let empty = BTreeMap::new();
+    for (position, (index, timestamp_ms, units)) in snapshots.iter().enumerate() {
+        let previous = position.checked_sub(1).map_or(&empty, |p| &snapshots[p].2);
+        let event = |kind| TimelineEvent {
+            snapshot: *index,
+            timestamp_ms: *timestamp_ms,
+            kind,
+        };
+        let owners_before = ref_owners(previous);
+        let owners_now = ref_owners(units);
+        for (id, timeline) in &mut timelines {
+            let now = units.get(id);
+            timeline
+                .points
+                .push(point(*index, *timestamp_ms, now.copied()));
+            match (previous.get(id), now) {
+                (None, Some(now)) => {
+                    let from = owners(&now.l1_refs, &owners_before, *id);
+                    timeline.events.push(event(if from.is_empty() {
+                        TimelineEventKind::Created
+                    } else {
+                        TimelineEventKind::Derived { from }
+                    }));
+                }
+                (Some(before), Some(now)) => {
+                    let (added, removed) = ref_changes(&before.l1_refs, &now.l1_refs);
+                    if !added.is_empty() {
+                        timeline
+                            .events
+                            .push(event(TimelineEventKind::RefsAdded(added)));
+                    }
+                    if !removed.is_empty() {
+                        timeline
+                            .events
+                            .push(event(TimelineEventKind::RefsRemoved(removed)));
+                    }
+                    if now.grounding_count > before.grounding_count {
+                        timeline.events.push(event(TimelineEventKind::Grounded {
+                            added: now.grounding_count - before.grounding_count,
+                        }));
+                    }
+                }
+                (Some(before), None) => {
+                    let targets = owners(&before.l1_refs, &owners_now, *id);
+                    timeline.events.push(event(match targets.len() {
+                        0 => TimelineEventKind::Removed,
+                        1 => TimelineEventKind::MergedInto { target: targets[0] },
+                        _ => TimelineEventKind::SplitInto { targets },
+                    }));
+                }
+                (None, None) => {}
+            }
+        }
+    }
+    timelines.into_values().collect()
+}
+
+/// The timeline of `concept_id`, or `None` if no snapshot holds it.
+pub fn concept_timeline(
+    history: &[MeaningLayerSnapshotV2],
+    concept_id: ConceptId,
+) -> Option<ConceptTimeline> {
+    concept_timelines(history)
+        .into_iter()
+        .find(|t| t.concept_id == concept_id)
+}
+
+fn point(snapshot: usize, timestamp_ms: u64, digest: Option<&L2UnitDigest>) -> TimelinePoint {
+    TimelinePoint {
+        snapshot,
+        timestamp_ms,
+        stability: digest.map(|d| stability_from_abstraction(d.abstraction())),
+        abstraction: digest.map(L2UnitDigest::abstraction),
+        l1_refs: digest.map(|d| d.l1_refs.clone()).unwrap_or_default(),
+        grounding_count: digest.map_or(0, |d| d.grounding_count),
+    }
+}
+
+fn ref_owners(units: &BTreeMap<ConceptId, &L2UnitDigest>) -> BTreeMap<L1Id, BTreeSet<ConceptId>> {
+    let mut owners = BTreeMap::<L1Id, BTreeSet<ConceptId>>::new();
+    for (id, digest) in units {
+        for l1 in &digest.l1_refs {
+            owners.entry(*l1).or_default().insert(*id);
+        }
+    }
+    owners
+}
+
+/// Concepts other than `except` holding any of `refs`, sorted.
+fn owners(
+    refs: &[L1Id],
+    owners: &BTreeMap<L1Id, BTreeSet<ConceptId>>,
+    except: ConceptId,
+) -> Vec<ConceptId> {
+    refs.iter()
+        .filter_map(|l1| owners.get(l1))
+        .flatten()
+        .copied()
+        .filter(|id| *id != except)
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn ref_changes(before: &[L1Id], after: &[L1Id]) -> (Vec<L1Id>, Vec<L1Id>) {
+    let before = before.iter().copied().collect::<BTreeSet<_>>();
+    let after = after.iter().copied().collect::<BTreeSet<_>>();
+    (
+        after.difference(&before).copied().collect(),
+        before.difference(&after).copied().collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn digest(id: u64, refs: &[u128], abstraction: f32, grounding_count: usize) -> L2UnitDigest {
+        L2UnitDigest {
+            id: ConceptId(id),
+            l1_refs: refs.iter().map(|r| L1Id(*r)).collect(),
+            polarity: 1,
+            abstraction_micros: (abstraction * 1e6).round() as i32,
+            vector_hash: 0,
+            grounding_count,
+        }
+    }
+
+    fn snapshot(timestamp_ms: u64, l2_units: Vec<L2UnitDigest>) -> MeaningLayerSnapshotV2 {
+        MeaningLayerSnapshotV2 {
+            l1_hash: 0,
+            l2_hash: 0,
+            timestamp_ms,
+            version: 2,
+            l1_units: Vec::new(),
+            l2_units,
+        }
+    }
+
+    fn kinds(timeline: &ConceptTimeline) -> Vec<(usize, TimelineEventKind)> {
+        timeline
+            .events
+            .iter()
+            .map(|e| (e.snapshot, e.kind.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn timelines_track_composition_grounding_merges_and_splits() {
+        let history = vec![
+            snapshot(
+                10,
+                vec![digest(1, &[1, 2], 0.5, 0), digest(2, &[3], 0.2, 0)],
+            ),
+            // L2-1 gains L1 4 and a grounding entry; L2-2 is absorbed by L2-1.
+            snapshot(20, vec![digest(1, &[1, 2, 3, 4], 0.4, 1)]),
+            // Digest-less snapshots are skipped.
+            snapshot(25, Vec::new()),
+            // L2-1 splits into L2-5 and L2-6.
+            snapshot(
+                30,
+                vec![digest(5, &[1, 2], 0.3, 0), digest(6, &[3, 4], 0.6, 0)],
+            ),
+            snapshot(40, vec![digest(5, &[1, 2], 0.3, 0)]),
+        ];
+        let timelines = concept_timelines(&history);
+        assert_eq!(
+            timelines.iter().map(|t| t.concept_id.0).collect::<Vec<_>>(),
+            vec![1, 2, 5, 6]
+        );
+
+        let first = &timelines[0];
+        assert_eq!(
+            first.points.iter().map(|p| p.snapshot).collect::<Vec<_>>(),
+            vec![0, 1, 3, 4]
+        );
+        let series = first.stability_series();
+        assert_eq!(series.len(), 2);
+        assert!((series[0].1 - 0.85).abs() < 1e-6 && (series[1].1 - 0.88).abs() < 1e-6);
+        assert!(!first.points[2].is_present());
+        assert_eq!(
+            kinds(first),
+            vec![
+                (0, TimelineEventKind::Created),
+                (1, TimelineEventKind::RefsAdded(vec![L1Id(3), L1Id(4)])),
+                (1, TimelineEventKind::Grounded { added: 1 }),
+                (
+                    3,
+                    TimelineEventKind::SplitInto {
+                        targets: vec![ConceptId(5), ConceptId(6)]
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            kinds(&timelines[1]),
+            vec![
+                (0, TimelineEventKind::Created),
+                (
+                    1,
+                    TimelineEventKind::MergedInto {
+                        target: ConceptId(1)
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            kinds(&timelines[2]),
+            vec![(
+                3,
+                TimelineEventKind::Derived {
+                    from: vec![ConceptId(1)]
+                }
+            )]
+        );
+        assert_eq!(
+            kinds(&timelines[3]).last(),
+            Some(&(4, TimelineEventKind::Removed))
+        );
+        assert!(concept_timeline(&history, ConceptId(9)).is_none());
+    }
+}
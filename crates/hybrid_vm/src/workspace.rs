@@ -0,0 +1,369 @@
+//! Several named workspaces under one root directory, one `HybridVM` each.
+//!
+//! A workspace is a subdirectory of the root holding the files written by
+//! `HybridVM::for_cli_storage`, plus `knowledge_feedback.json` with that
+//! workspace's knowledge-store feedback and, once set or fitted,
+//! `resonance_weights.json`. Users' preference profiles go to
+//! `profiles.json` once saved. Nothing is shared between workspaces: recall
+//! memory, semantic layers, resonance weights, profiles and
+//! feedback-adjusted knowledge weights all stay in their own directory.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use knowledge_store::FeedbackEntry;
+
+use crate::{HybridVM, ProfileStore, ResonanceWeights};
+
+/// Knowledge-store feedback of a workspace, relative to its directory.
+pub const KNOWLEDGE_FEEDBACK_FILE: &str = "knowledge_feedback.json";
+/// Resonance weights of a workspace, relative to its directory. Absent
+/// until weights differ from the defaults.
+pub const RESONANCE_WEIGHTS_FILE: &str = "resonance_weights.json";
+/// Preference profiles of a workspace, relative to its directory.
+pub const PROFILES_FILE: &str = "profiles.json";
+
+pub struct WorkspaceManager {
+    root: PathBuf,
+    open: BTreeMap<String, HybridVM>,
+}
+
+impl WorkspaceManager {
+    pub fn new(root: impl AsRef<Path>) -> io::Result<Self> {
+        let root = root.as_ref().to_path_buf();
+        std::fs::create_dir_all(&root)?;
+        Ok(Self {
+            root,
+            open: BTreeMap::new(),
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn workspace_dir(&self, name: &str) -> PathBuf {
+        self.root.join(name)
+    }
+
+    /// Workspace names found under the root, sorted. Directories whose names
+    /// are not valid workspace names are skipped.
+    pub fn list(&self) -> io::Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in std::fs::read_dir(&self.root)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            if let Some(name) = entry.file_name().to_str()
+                && is_valid_workspace_name(name)
+            {
+                names.push(name.to_string());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    pub fn exists(&self, name: &str) -> bool {
+        is_valid_workspace_name(name) && self.workspace_dir(name).is_dir()
+    }
+
+    pub fn is_open(&self, name: &str) -> bool {
+        self.open.contains_key(name)
+    }
+
+    /// Names of the workspaces with a live VM, sorted.
+    pub fn open_workspaces(&self) -> Vec<String> {
+        self.open.keys().cloned().collect()
+    }
+
+    /// The workspace's VM, opening (and creating) it on first use.
+    pub fn open(&mut self, name: &str) -> io::Result<&mut HybridVM> {
+        validate_workspace_name(name)?;
+        if !self.open.contains_key(name) {
+            let dir = self.workspace_dir(name);
+            let mut vm = HybridVM::for_cli_storage(&dir)?;
+            vm.load_feedback_entries(read_feedback(&dir)?);
+            if let Some(weights) = read_weights(&dir)? {
+                vm.set_resonance_weights(weights);
+            }
+            self.open.insert(name.to_string(), vm);
+        }
+        Ok(self.open.get_mut(name).expect("workspace opened above"))
+    }
+
+    /// The workspace's VM if it is already open.
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut HybridVM> {
+        self.open.get_mut(name)
+    }
+
+    /// Writes the workspace's knowledge-store feedback and resonance
+    /// weights. The semantic layers are written through by their stores and
+    /// need no save.
+    pub fn save(&self, name: &str) -> io::Result<()> {
+        let vm = self.open.get(name).ok_or_else(|| not_open(name))?;
+        let dir = self.workspace_dir(name);
+        write_feedback(&dir, &vm.feedback_entries())?;
+        let weights = vm.resonance_weights();
+        if weights != ResonanceWeights::default().normalized()
+            || dir.join(RESONANCE_WEIGHTS_FILE).exists()
+        {
+            write_weights(&dir, weights)?;
+        }
+        Ok(())
+    }
+
+    /// The workspace's preference profiles; empty if none were saved. The
+    /// workspace need not be open.
+    pub fn load_profiles(&self, name: &str) -> io::Result<ProfileStore> {
+        validate_workspace_name(name)?;
+        match std::fs::read(self.workspace_dir(name).join(PROFILES_FILE)) {
+            Ok(bytes) => serde_json::from_slice(&bytes)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(ProfileStore::default()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Writes `profiles` as the workspace's profiles, creating the
+    /// workspace directory if needed.
+    pub fn save_profiles(&self, name: &str, profiles: &ProfileStore) -> io::Result<()> {
+        validate_workspace_name(name)?;
+        let dir = self.workspace_dir(name);
+        std::fs::create_dir_all(&dir)?;
+        let json = serde_json::to_vec_pretty(profiles)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        std::fs::write(dir.join(PROFILES_FILE), json)
+    }
+
+    /// Saves and drops the workspace's VM. Returns `false` if it was not
+    /// open.
+    pub fn close(&mut self, name: &str) -> io::Result<bool> {
+        if !self.open.contains_key(name) {
+            return Ok(false);
+        }
+        self.save(name)?;
+        self.open.remove(name);
+        Ok(true)
+    }
+
+    /// Copies `source` into a new workspace `branch` and opens it. The two
+    /// evolve independently afterwards.
+    pub fn branch(&mut self, source: &str, branch: &str) -> io::Result<&mut HybridVM> {
+        validate_workspace_name(source)?;
+        validate_workspace_name(branch)?;
+        if !self.exists(source) {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("unknown workspace: {source}"),
+            ));
+        }
+        let target = self.workspace_dir(branch);
+        if target.exists() {
+            return Err(io::Error::new(
+                io::ErrorKind::AlreadyExists,
+                format!("workspace already exists: {branch}"),
+            ));
+        }
+        if self.is_open(source) {
+            self.save(source)?;
+        }
+        std::fs::create_dir_all(&target)?;
+        for entry in std::fs::read_dir(self.workspace_dir(source))? {
+            let entry = entry?;
+            if entry.file_type()?.is_file() {
+                std::fs::copy(entry.path(), target.join(entry.file_name()))?;
+            }
+        }
+        self.open(branch)
+    }
+}
+
+/// Workspace names become directory names, so only `[A-Za-z0-9_-]` is
+/// allowed.
+pub fn is_valid_workspace_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 64
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn validate_workspace_name(name: &str) -> io::Result<()> {
+    if is_valid_workspace_name(name) {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid workspace name {name:?}: use 1-64 characters of [A-Za-z0-9_-]"),
+        ))
+    }
+}
+
+fn not_open(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("workspace not open: {name}"),
+    )
+}
+
+fn read_feedback(dir: &Path) -> io::Result<Vec<FeedbackEntry>> {
+    match std::fs::read(dir.join(KNOWLEDGE_FEEDBACK_FILE)) {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(err) => Err(err),
+    }
+}
+
+fn write_feedback(dir: &Path, entries: &[FeedbackEntry]) -> io::Result<()> {
+    let json = serde_json::to_vec_pretty(entries)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    std::fs::write(dir.join(KNOWLEDGE_FEEDBACK_FILE), json)
+}
+
+fn read_weights(dir: &Path) -> io::Result<Option<ResonanceWeights>> {
+    match std::fs::read(dir.join(RESONANCE_WEIGHTS_FILE)) {
+        Ok(bytes) => serde_json::from_slice(&bytes)
+            .map(Some)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+fn write_weights(dir: &Path, weights: ResonanceWeights) -> io::Result<()> {
+    let json = serde_json::to_vec_pretty(&weights)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    std::fs::write(dir.join(RESONANCE_WEIGHTS_FILE), json)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use super::*;
+    use crate::FeedbackAction;
+
+    fn root() -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "hybrid_vm_workspaces_{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("clock")
+                .as_nanos()
+        ))
+    }
+
+    #[test]
+    fn workspaces_are_listed_opened_lazily_and_isolated() {
+        let root = root();
+        let mut manager = WorkspaceManager::new(&root).expect("manager");
+        assert!(manager.list().expect("list").is_empty());
+
+        manager
+            .open("alpha")
+            .expect("alpha")
+            .analyze_text("メモリ512MB以下")
+            .expect("analyze");
+        manager
+            .open("alpha")
+            .expect("alpha")
+            .record_feedback("draft-1", FeedbackAction::Adopt);
+        manager.open("beta").expect("beta");
+        std::fs::create_dir_all(root.join("not a workspace")).expect("stray dir");
+        assert_eq!(manager.list().expect("list"), vec!["alpha", "beta"]);
+        assert!(manager.open("../escape").is_err());
+
+        let beta = manager.get_mut("beta").expect("beta open");
+        assert!(beta.all_l1_units_v2().expect("l1").is_empty());
+        assert!(beta.feedback_entries().is_empty());
+
+        assert!(manager.close("alpha").expect("close"));
+        assert!(!manager.is_open("alpha"));
+        let alpha = manager.open("alpha").expect("reopen");
+        assert_eq!(alpha.all_l1_units_v2().expect("l1").len(), 1);
+        assert_eq!(alpha.feedback_entries().len(), 1);
+    }
+
+    #[test]
+    fn branch_copies_state_and_then_diverges() {
+        let mut manager = WorkspaceManager::new(root()).expect("manager");
+        manager
+            .open("main")
+            .expect("main")
+            .analyze_text("クラウドは使わない")
+            .expect("analyze");
+
+        let branch = manager.branch("main", "experiment").expect("branch");
+        assert_eq!(branch.all_l1_units_v2().expect("l1").len(), 1);
+        branch.analyze_text("レスポンス200ms").expect("analyze");
+
+        let main = manager.open("main").expect("main");
+        assert_eq!(main.all_l1_units_v2().expect("l1").len(), 1);
+        assert!(manager.branch("main", "experiment").is_err());
+        assert!(manager.branch("missing", "other").is_err());
+    }
+
+    #[test]
+    fn profiles_persist_per_workspace_with_their_default() {
+        let manager = WorkspaceManager::new(root()).expect("manager");
+        assert_eq!(
+            manager.load_profiles("team").expect("empty"),
+            ProfileStore::default()
+        );
+        let mut profiles = ProfileStore::default();
+        profiles
+            .save(
+                "alice",
+                core_types::ProfileVector {
+                    struct_weight: 2.0,
+                    field_weight: 1.0,
+                    risk_weight: 1.0,
+                    cost_weight: 0.0,
+                },
+                crate::DecisionWeights::default(),
+            )
+            .expect("save");
+        profiles.set_default("alice").expect("default");
+        manager.save_profiles("team", &profiles).expect("write");
+
+        let loaded = manager.load_profiles("team").expect("read");
+        assert_eq!(loaded, profiles);
+        assert_eq!(loaded.apply(None).expect("default").1.name, "alice");
+        assert!(
+            manager
+                .load_profiles("other")
+                .expect("other")
+                .names()
+                .is_empty()
+        );
+        assert!(manager.save_profiles("../escape", &profiles).is_err());
+    }
+
+    #[test]
+    fn resonance_weights_persist_per_workspace() {
+        let root = root();
+        let mut manager = WorkspaceManager::new(&root).expect("manager");
+        let tuned = ResonanceWeights {
+            gamma1: 0.2,
+            gamma2: 0.6,
+            gamma3: 0.2,
+        };
+        manager
+            .open("tuned")
+            .expect("tuned")
+            .set_resonance_weights(tuned);
+        manager.open("plain").expect("plain");
+        assert!(manager.close("tuned").expect("close"));
+        assert!(manager.close("plain").expect("close"));
+        assert!(!root.join("plain").join(RESONANCE_WEIGHTS_FILE).exists());
+
+        let reopened = manager.open("tuned").expect("reopen").resonance_weights();
+        assert!((reopened.gamma2 - 0.6).abs() < 1e-6);
+        let plain = manager.open("plain").expect("reopen").resonance_weights();
+        assert_eq!(plain, ResonanceWeights::default().normalized());
+    }
+}
@@ -1,12 +1,33 @@
 use std::cmp::Ordering;
+use std::collections::HashMap;
 use std::io;
 use std::path::Path;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
 use std::time::{SystemTime, UNIX_EPOCH};
 
-use memory_store::{Codec, FileStore, InMemoryStore, Store};
+use memory_store::{BackedStore, Codec, FileStore, InMemoryStore, Store, StoreUsage};
 
 pub const EMBEDDING_DIM: usize = 384;
 
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DedupConfig {
+    pub enabled: bool,
+    /// Embeddings at or above this resonance are treated as the same sentence.
+    pub similarity_threshold: f32,
+}
+
+pub const DEFAULT_DEDUP_CONFIG: DedupConfig = DedupConfig {
+    enabled: true,
+    similarity_threshold: 0.995,
+};
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DedupReport {
+    /// `(kept, merged)` pairs; the merged unit was removed from the store.
+    pub merged: Vec<(LangId, LangId)>,
+    pub remaining: usize,
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct LangId(u64);
 
@@ -92,6 +113,10 @@ where
 {
     store: S,
     next_id: u64,
+    dedup: DedupConfig,
+    /// Canonical text of each stored unit mapped to the oldest id holding it.
+    /// Empty canonical forms are never indexed.
+    canonical_index: HashMap<String, LangId>,
 }
 
 impl<S> LanguageDhm<S>
@@ -99,16 +124,32 @@ where
     S: Store<LangId, LanguageUnit>,
 {
     pub(crate) fn new(store: S) -> io::Result<Self> {
-        let next_id = store
-            .entries()?
-            .into_iter()
-            .map(|(id, _)| id.0)
-            .max()
-            .map(|v| v.saturating_add(1))
+        let mut entries = store.entries()?;
+        entries.sort_by_key(|(id, _)| *id);
+        let next_id = entries
+            .last()
+            .map(|(id, _)| id.0.saturating_add(1))
             .unwrap_or(1);
-        Ok(Self { store, next_id })
+        let canonical_index = build_canonical_index(&entries);
+        Ok(Self {
+            store,
+            next_id,
+            dedup: DEFAULT_DEDUP_CONFIG,
+            canonical_index,
+        })
     }
 
+    pub fn dedup_config(&self) -> DedupConfig {
+        self.dedup
+    }
+
+    pub fn set_dedup_config(&mut self, config: DedupConfig) {
+        self.dedup = config;
+    }
+
+    /// Stores `text`, or refreshes an existing duplicate and returns its id.
+    /// Canonical matches come from the index; only unmatched texts fall back to
+    /// a resonance pass over stored embeddings.
     pub fn insert(&mut self, text: &str, embedding: Vec<f32>) -> io::Result<LangId> {
         if embedding.len() != EMBEDDING_DIM {
             return Err(io::Error::new(
@@ -116,19 +157,90 @@ where
                 "embedding length must be EMBEDDING_DIM",
             ));
         }
+        let embedding = normalize_l2(&embedding);
+        let canonical = canonicalize_text(text);
+        if self.dedup.enabled {
+            let existing = match self.canonical_index.get(&canonical) {
+                Some(id) => self.store.get(id)?.map(|unit| (*id, unit)),
+                None => self.store.entries()?.into_iter().find(|(_, unit)| {
+                    resonance(&embedding, &unit.embedding) >= self.dedup.similarity_threshold
+                }),
+            };
+            if let Some((id, mut unit)) = existing {
+                unit.timestamp = now_ts();
+                self.store.put(id, unit)?;
+                return Ok(id);
+            }
+        }
+
         let id = LangId(self.next_id);
         self.next_id = self.next_id.saturating_add(1);
 
         let unit = LanguageUnit {
             id,
-            embedding: normalize_l2(&embedding),
+            embedding,
             raw_text: text.to_string(),
             timestamp: now_ts(),
         };
         self.store.put(id, unit)?;
+        if !canonical.is_empty() {
+            self.canonical_index.entry(canonical).or_insert(id);
+        }
         Ok(id)
     }
 
+    /// Merges duplicates already in the store, keeping the oldest id of each
+    /// group. Running it twice reports no merges the second time.
+    pub fn deduplicate_existing(&mut self) -> io::Result<DedupReport> {
+        let mut entries = self.store.entries()?;
+        entries.sort_by_key(|(id, _)| *id);
+
+        let mut kept: Vec<(LangId, LanguageUnit)> = Vec::with_capacity(entries.len());
+        let mut by_canonical: HashMap<String, usize> = HashMap::new();
+        let mut merged = Vec::new();
+        for (id, unit) in entries {
+            let canonical = canonicalize_text(&unit.raw_text);
+            let target = by_canonical.get(&canonical).copied().or_else(|| {
+                kept.iter().position(|(_, k)| {
+                    resonance(&unit.embedding, &k.embedding) >= self.dedup.similarity_threshold
+                })
+            });
+            match target {
+                Some(slot) => {
+                    let (kept_id, k) = &mut kept[slot];
+                    k.timestamp = k.timestamp.max(unit.timestamp);
+                    merged.push((*kept_id, id));
+                }
+                None => {
+                    if !canonical.is_empty() {
+                        by_canonical.insert(canonical, kept.len());
+                    }
+                    kept.push((id, unit));
+                }
+            }
+        }
+
+        let remaining = kept.len();
+        if !merged.is_empty() {
+            self.canonical_index = build_canonical_index(&kept);
+            self.store.replace_all(kept)?;
+        }
+        Ok(DedupReport { merged, remaining })
+    }
+
+    pub fn store_usage(&self) -> io::Result<StoreUsage> {
+        self.store.usage()
+    }
+
+    pub fn all_units(&self) -> Vec<LanguageUnit> {
+        self.store
+            .entries()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(_, unit)| unit)
+            .collect()
+    }
+
     pub fn recall(&self, query_embedding: &[f32], top_k: usize) -> Vec<(LangId, f32)> {
         if top_k == 0 || query_embedding.len() != EMBEDDING_DIM {
             return Vec::new();
@@ -166,6 +278,12 @@ impl LanguageDhm<FileStore<LangId, LanguageUnit>> {
     }
 }
 
+impl LanguageDhm<BackedStore<LangId, LanguageUnit>> {
+    pub fn backed(store: BackedStore<LangId, LanguageUnit>) -> io::Result<Self> {
+        Self::new(store)
+    }
+}
+
 pub fn resonance(a: &[f32], b: &[f32]) -> f32 {
     let n = a.len().min(b.len());
     let mut dot = 0.0f32;
@@ -184,6 +302,52 @@ pub fn interfere(a: &[f32], b: &[f32]) -> Vec<f32> {
     out
 }
 
+/// Lowercases, folds full-width ASCII, drops punctuation and collapses whitespace.
+pub fn canonicalize_text(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '\u{FF01}'..='\u{FF5E}' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            _ => c,
+        })
+        .filter(|c| !is_punctuation(*c))
+        .flat_map(char::to_lowercase)
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is_punctuation(c: char) -> bool {
+    c.is_ascii_punctuation()
+        || matches!(
+            c,
+            '、' | '。'
+                | '・'
+                | '「'
+                | '」'
+                | '『'
+                | '』'
+                | '（'
+                | '）'
+                | '【'
+                | '】'
+                | '〜'
+                | '…'
+        )
+}
+
+/// Expects `entries` sorted by id so the oldest unit wins each canonical form.
+fn build_canonical_index(entries: &[(LangId, LanguageUnit)]) -> HashMap<String, LangId> {
+    let mut index = HashMap::with_capacity(entries.len());
+    for (id, unit) in entries {
+        let canonical = canonicalize_text(&unit.raw_text);
+        if !canonical.is_empty() {
+            index.entry(canonical).or_insert(*id);
+        }
+    }
+    index
+}
+
 fn normalize_l2(v: &[f32]) -> Vec<f32> {
     let norm = v.iter().map(|x| x * x).sum::<f32>().sqrt();
     if norm <= f32::EPSILON {
@@ -192,6 +356,8 @@ fn normalize_l2(v: &[f32]) -> Vec<f32> {
     v.iter().map(|x| x / norm).collect()
 }
 
+// Falls back to 0 where the OS clock is unavailable (wasm32-unknown-unknown).
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
 fn now_ts() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -199,6 +365,11 @@ fn now_ts() -> u64 {
         .unwrap_or(0)
 }
 
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn now_ts() -> u64 {
+    0
+}
+
 fn read_u32(raw: &[u8], idx: &mut usize) -> io::Result<u32> {
     if idx.saturating_add(4) > raw.len() {
         return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "u32"));
@@ -233,7 +404,10 @@ fn read_f32(raw: &[u8], idx: &mut usize) -> io::Result<f32> {
 mod tests {
     use std::time::{SystemTime, UNIX_EPOCH};
 
-    use super::{EMBEDDING_DIM, LanguageDhm, interfere, resonance};
+    use super::{
+        DEFAULT_DEDUP_CONFIG, DedupConfig, EMBEDDING_DIM, LanguageDhm, canonicalize_text,
+        interfere, resonance,
+    };
 
     fn vec_with(value: f32) -> Vec<f32> {
         vec![value; EMBEDDING_DIM]
@@ -296,6 +470,74 @@ mod tests {
         let _ = std::fs::remove_file(path);
     }
 
+    #[test]
+    fn canonicalization_ignores_spacing_and_punctuation() {
+        assert_eq!(
+            canonicalize_text("  応答を 200ms 以内に。 "),
+            canonicalize_text("応答を　２００ＭＳ　以内に")
+        );
+        assert_eq!(canonicalize_text("Fast,  secure!"), "fast secure");
+    }
+
+    #[test]
+    fn insert_merges_near_identical_sentences() {
+        let mut dhm = LanguageDhm::in_memory().expect("in-memory");
+        let mut a = vec![0.0; EMBEDDING_DIM];
+        a[0] = 1.0;
+        let mut b = vec![0.0; EMBEDDING_DIM];
+        b[5] = 1.0;
+        let first = dhm.insert("高速化を重視する。", a).expect("insert");
+        let second = dhm
+            .insert(" 高速化を重視する！", b.clone())
+            .expect("insert");
+        assert_eq!(first, second);
+
+        let mut near_b = b.clone();
+        near_b[6] = 0.01;
+        let third = dhm.insert("別の文", b).expect("insert");
+        let fourth = dhm.insert("さらに別の文", near_b).expect("insert");
+        assert_ne!(first, third);
+        assert_eq!(third, fourth);
+        assert_eq!(dhm.all_units().len(), 2);
+    }
+
+    #[test]
+    fn insert_keeps_texts_without_canonical_form_apart() {
+        let mut dhm = LanguageDhm::in_memory().expect("in-memory");
+        let mut a = vec![0.0; EMBEDDING_DIM];
+        a[0] = 1.0;
+        let mut b = vec![0.0; EMBEDDING_DIM];
+        b[1] = 1.0;
+        let first = dhm.insert("。。", a).expect("insert");
+        let second = dhm.insert("!?", b).expect("insert");
+        assert_ne!(first, second);
+        assert_eq!(dhm.all_units().len(), 2);
+    }
+
+    #[test]
+    fn deduplicate_existing_is_idempotent() {
+        let mut dhm = LanguageDhm::in_memory().expect("in-memory");
+        dhm.set_dedup_config(DedupConfig {
+            enabled: false,
+            ..DEFAULT_DEDUP_CONFIG
+        });
+        let first = dhm.insert("Latency  first", vec_with(1.0)).expect("insert");
+        let dup = dhm.insert("latency first.", vec_with(1.0)).expect("insert");
+        let mut other = vec![0.0; EMBEDDING_DIM];
+        other[2] = 1.0;
+        let _ = dhm.insert("unrelated", other).expect("insert");
+        assert_eq!(dhm.all_units().len(), 3);
+
+        let report = dhm.deduplicate_existing().expect("dedup");
+        assert_eq!(report.merged, vec![(first, dup)]);
+        assert_eq!(report.remaining, 2);
+        assert!(dhm.get(dup).is_none());
+
+        let again = dhm.deduplicate_existing().expect("dedup");
+        assert!(again.merged.is_empty());
+        assert_eq!(again.remaining, 2);
+    }
+
     #[test]
     fn interfere_hadamard_test() {
         let a = vec![1.0, 2.0, 3.0];
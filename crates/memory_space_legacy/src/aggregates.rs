@@ -0,0 +1,419 @@
+//! Degree, category, and clustering aggregates of a `StructuralGraph` that
+//! can be carried from a parent graph to a child through a `GraphDiff`.
+//!
+//! The normalized metrics mirror the `StructuralGraph::normalized_*` family
+//! but read running sums and histograms instead of rebuilding the undirected
+//! neighbourhood, so they agree with the full computation up to
+//! floating-point rounding.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::diff::GraphDiff;
+use crate::graph::{StructuralGraph, max_degree_variance_for_simple_graph};
+use crate::node::DesignNode;
+use crate::types::{NodeId, Value};
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GraphAggregates {
+    adjacency: BTreeMap<NodeId, BTreeSet<NodeId>>,
+    /// Undirected degree -> number of nodes with that degree.
+    degree_histogram: BTreeMap<usize, usize>,
+    degree_sum: usize,
+    degree_square_sum: usize,
+    /// `sum(d * ln d)`, the only term of the degree-mass entropy that
+    /// depends on individual degrees.
+    degree_xlogx_sum: f64,
+    category_counts: BTreeMap<String, usize>,
+    /// Local clustering coefficient per node with degree >= 2.
+    clustering: BTreeMap<NodeId, f64>,
+    is_dag: bool,
+}
+
+impl GraphAggregates {
+    pub fn from_graph(graph: &StructuralGraph) -> Self {
+        let mut aggregates = Self {
+            is_dag: graph.is_dag(),
+            ..Self::default()
+        };
+        for node in graph.nodes().values() {
+            aggregates.insert_node(node);
+        }
+        for (from, to) in graph.edges() {
+            aggregates.link(*from, *to);
+        }
+        let ids = aggregates.adjacency.keys().copied().collect::<Vec<_>>();
+        for id in ids {
+            aggregates.refresh_clustering(id);
+        }
+        aggregates
+    }
+
+    /// Advances these aggregates from the graph `diff` was taken against to
+    /// `after`.
+    pub fn apply_diff(&mut self, diff: &GraphDiff, after: &StructuralGraph) {
+        let mut touched = BTreeSet::new();
+        for (from, to) in &diff.removed_edges {
+            // The reverse edge keeps the undirected link alive.
+            if !after.edges().contains(&(*to, *from)) {
+                self.unlink(*from, *to);
+                touched.extend([*from, *to]);
+            }
+        }
+        for node in &diff.removed_nodes {
+            self.remove_node(node);
+        }
+        for (before, next) in &diff.changed_nodes {
+            self.adjust_category(before, false);
+            self.adjust_category(next, true);
+        }
+        for node in &diff.added_nodes {
+            self.insert_node(node);
+        }
+        for (from, to) in &diff.added_edges {
+            if self.link(*from, *to) {
+                touched.extend([*from, *to]);
+            }
+        }
+        if !diff.added_edges.is_empty() || !self.is_dag {
+            self.is_dag = after.is_dag();
+        }
+
+        // A changed link only moves triangles through its endpoints and
+        // their common neighbours, all of which neighbour an endpoint.
+        let mut refresh = BTreeSet::new();
+        for id in &touched {
+            if let Some(neighbors) = self.adjacency.get(id) {
+                refresh.insert(*id);
+                refresh.extend(neighbors.iter().copied());
+            }
+        }
+        for id in refresh {
+            self.refresh_clustering(id);
+        }
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.adjacency.len()
+    }
+
+    pub fn is_dag(&self) -> bool {
+        self.is_dag
+    }
+
+    pub fn degree_histogram(&self) -> &BTreeMap<usize, usize> {
+        &self.degree_histogram
+    }
+
+    pub fn category_counts(&self) -> &BTreeMap<String, usize> {
+        &self.category_counts
+    }
+
+    pub fn normalized_category_entropy(&self) -> Option<f64> {
+        if self.category_counts.is_empty() {
+            return None;
+        }
+        let total = self.category_counts.values().sum::<usize>() as f64;
+        let k = self.category_counts.len() as f64;
+        if k <= 1.0 {
+            return Some(0.0);
+        }
+        Some((entropy(self.category_counts.values().copied(), total) / k.ln()).clamp(0.0, 1.0))
+    }
+
+    pub fn normalized_degree_entropy(&self) -> f64 {
+        let n = self.node_count();
+        let m = self.degree_histogram.len() as f64;
+        if n < 2 || m <= 1.0 {
+            return 0.0;
+        }
+        (entropy(self.degree_histogram.values().copied(), n as f64) / m.ln()).clamp(0.0, 1.0)
+    }
+
+    pub fn normalized_degree_mass_entropy(&self) -> f64 {
+        let n = self.node_count();
+        if n < 2 || self.degree_sum == 0 {
+            return 0.0;
+        }
+        let total = self.degree_sum as f64;
+        let entropy = total.ln() - self.degree_xlogx_sum / total;
+        (entropy / (n as f64).ln()).clamp(0.0, 1.0)
+    }
+
+    pub fn normalized_degree_gini(&self) -> f64 {
+        let n = self.node_count();
+        if n < 2 || self.degree_sum == 0 {
+            return 0.0;
+        }
+        let n_f = n as f64;
+        let mut weighted_sum = 0.0;
+        let mut first_rank = 1.0;
+        for (degree, count) in &self.degree_histogram {
+            let count = *count as f64;
+            let rank_sum = count * first_rank + count * (count - 1.0) / 2.0;
+            weighted_sum += (2.0 * rank_sum - count * (n_f + 1.0)) * *degree as f64;
+            first_rank += count;
+        }
+        (weighted_sum / (n_f * self.degree_sum as f64)).clamp(0.0, 1.0)
+    }
+
+    pub fn normalized_max_degree(&self) -> f64 {
+        let n = self.node_count();
+        if n < 2 {
+            return 0.0;
+        }
+        let max_degree = self
+            .degree_histogram
+            .keys()
+            .next_back()
+            .copied()
+            .unwrap_or(0);
+        (max_degree as f64 / (n - 1) as f64).clamp(0.0, 1.0)
+    }
+
+    pub fn normalized_degree_variance(&self) -> f64 {
+        let n = self.node_count();
+        if n < 3 {
+            return 0.0;
+        }
+        let n_f = n as f64;
+        let mean = self.degree_sum as f64 / n_f;
+        let var = self.degree_square_sum as f64 / n_f - mean * mean;
+        let max_var = max_degree_variance_for_simple_graph(n);
+        if max_var <= 1e-12 {
+            return 0.0;
+        }
+        (var / max_var).clamp(0.0, 1.0)
+    }
+
+    pub fn average_clustering_coefficient(&self) -> f64 {
+        let n = self.node_count();
+        if n < 3 {
+            return 0.0;
+        }
+        (self.clustering.values().sum::<f64>() / n as f64).clamp(0.0, 1.0)
+    }
+
+    fn insert_node(&mut self, node: &DesignNode) {
+        if self.adjacency.insert(node.id, BTreeSet::new()).is_none() {
+            self.count_degree(0, true);
+            self.adjust_category(node, true);
+        }
+    }
+
+    fn remove_node(&mut self, node: &DesignNode) {
+        let Some(neighbors) = self.adjacency.get(&node.id).cloned() else {
+            return;
+        };
+        for neighbor in neighbors {
+            self.unlink(node.id, neighbor);
+        }
+        self.adjacency.remove(&node.id);
+        self.count_degree(0, false);
+        self.clustering.remove(&node.id);
+        self.adjust_category(node, false);
+    }
+
+    /// Adds the undirected link `a - b`; false when it already existed or an
+    /// endpoint is unknown.
+    fn link(&mut self, a: NodeId, b: NodeId) -> bool {
+        if a == b || !self.adjacency.contains_key(&a) || !self.adjacency.contains_key(&b) {
+            return false;
+        }
+        if !self.adjacency.entry(a).or_default().insert(b) {
+            return false;
+        }
+        self.adjacency.entry(b).or_default().insert(a);
+        self.shift_degree(a, 1);
+        self.shift_degree(b, 1);
+        true
+    }
+
+    fn unlink(&mut self, a: NodeId, b: NodeId) {
+        let removed = self.adjacency.get_mut(&a).is_some_and(|set| set.remove(&b));
+        if !removed {
+            return;
+        }
+        if let Some(set) = self.adjacency.get_mut(&b) {
+            set.remove(&a);
+        }
+        self.shift_degree(a, -1);
+        self.shift_degree(b, -1);
+    }
+
+    /// Moves `id` from its previous degree to its current one.
+    fn shift_degree(&mut self, id: NodeId, delta: isize) {
+        let current = self.adjacency.get(&id).map_or(0, BTreeSet::len);
+        let previous = current.saturating_add_signed(-delta);
+        self.count_degree(previous, false);
+        self.count_degree(current, true);
+    }
+
+    fn count_degree(&mut self, degree: usize, add: bool) {
+        let d = degree as f64;
+        let xlogx = if degree > 0 { d * d.ln() } else { 0.0 };
+        if add {
+            *self.degree_histogram.entry(degree).or_insert(0) += 1;
+            self.degree_sum += degree;
+            self.degree_square_sum += degree * degree;
+            self.degree_xlogx_sum += xlogx;
+        } else {
+            if let Some(count) = self.degree_histogram.get_mut(&degree) {
+                *count -= 1;
+                if *count == 0 {
+                    self.degree_histogram.remove(&degree);
+                }
+            }
+            self.degree_sum -= degree;
+            self.degree_square_sum -= degree * degree;
+            self.degree_xlogx_sum -= xlogx;
+        }
+    }
+
+    fn adjust_category(&mut self, node: &DesignNode, add: bool) {
+        let Some(Value::Text(category)) = node.attributes.get("category") else {
+            return;
+        };
+        let trimmed = category.trim();
+        if trimmed.is_empty() {
+            return;
+        }
+        if add {
+            *self.category_counts.entry(trimmed.to_string()).or_insert(0) += 1;
+        } else if let Some(count) = self.category_counts.get_mut(trimmed) {
+            *count -= 1;
+            if *count == 0 {
+                self.category_counts.remove(trimmed);
+            }
+        }
+    }
+
+    fn refresh_clustering(&mut self, id: NodeId) {
+        let Some(adj) = self.adjacency.get(&id) else {
+            return;
+        };
+        let k = adj.len();
+        if k < 2 {
+            self.clustering.remove(&id);
+            return;
+        }
+        let adj_vec = adj.iter().copied().collect::<Vec<_>>();
+        let mut links = 0usize;
+        for (i, a) in adj_vec.iter().enumerate() {
+            let Some(a_adj) = self.adjacency.get(a) else {
+                continue;
+            };
+            links += adj_vec[i + 1..]
+                .iter()
+                .filter(|b| a_adj.contains(b))
+                .count();
+        }
+        let c = (2.0 * links as f64) / (k * (k - 1)) as f64;
+        self.clustering.insert(id, c);
+    }
+}
+
+fn entropy(counts: impl Iterator<Item = usize>, total: f64) -> f64 {
+    let mut entropy = 0.0;
+    for count in counts {
+        let p = count as f64 / total;
+        if p > 0.0 {
+            entropy -= p * p.ln();
+        }
+    }
+    entropy
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use proptest::prelude::*;
+
+    use crate::{DesignNode, GraphAggregates, GraphDiff, StructuralGraph, Uuid, Value};
+
+    fn node(id: u128, category: &str) -> DesignNode {
+        let attributes =
+            BTreeMap::from([("category".to_string(), Value::Text(category.to_string()))]);
+        DesignNode::new(Uuid::from_u128(id), "node", attributes)
+    }
+
+    fn assert_matches_graph(aggregates: &GraphAggregates, graph: &StructuralGraph) {
+        let pairs = [
+            (
+                aggregates.normalized_degree_entropy(),
+                graph.normalized_degree_entropy(),
+            ),
+            (
+                aggregates.normalized_degree_mass_entropy(),
+                graph.normalized_degree_mass_entropy(),
+            ),
+            (
+                aggregates.normalized_degree_gini(),
+                graph.normalized_degree_gini(),
+            ),
+            (
+                aggregates.normalized_max_degree(),
+                graph.normalized_max_degree(),
+            ),
+            (
+                aggregates.normalized_degree_variance(),
+                graph.normalized_degree_variance(),
+            ),
+            (
+                aggregates.average_clustering_coefficient(),
+                graph.average_clustering_coefficient(),
+            ),
+            (
+                aggregates.normalized_category_entropy().unwrap_or(-1.0),
+                graph.normalized_category_entropy().unwrap_or(-1.0),
+            ),
+        ];
+        for (incremental, full) in pairs {
+            assert!((incremental - full).abs() < 1e-9, "{incremental} != {full}");
+        }
+        assert_eq!(aggregates.category_counts(), &graph.category_counts());
+        assert_eq!(aggregates.is_dag(), graph.is_dag());
+    }
+
+    #[test]
+    fn from_graph_matches_full_metrics() {
+        let graph = StructuralGraph::default()
+            .with_node_added(node(1, "api"))
+            .with_node_added(node(2, "db"))
+            .with_node_added(node(3, "db"))
+            .with_node_added(node(4, "cache"))
+            .with_edge_added(Uuid::from_u128(1), Uuid::from_u128(2))
+            .with_edge_added(Uuid::from_u128(1), Uuid::from_u128(3))
+            .with_edge_added(Uuid::from_u128(2), Uuid::from_u128(3))
+            .with_edge_added(Uuid::from_u128(3), Uuid::from_u128(4));
+        let aggregates = GraphAggregates::from_graph(&graph);
+        assert_eq!(
+            aggregates.degree_histogram(),
+            &BTreeMap::from([(1, 1), (2, 2), (3, 1)])
+        );
+        assert_matches_graph(&aggregates, &graph);
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(128))]
+
+        #[test]
+        fn diffs_keep_aggregates_in_sync(
+            ops in proptest::collection::vec((0u8..5u8, 0u128..8u128, 0u128..8u128), 0..40usize),
+        ) {
+            let mut graph = StructuralGraph::default();
+            let mut aggregates = GraphAggregates::from_graph(&graph);
+            for (op, a, b) in ops {
+                let next = match op {
+                    0 => graph.with_node_added(node(a, ["api", "db", "cache"][(b % 3) as usize])),
+                    1 => graph.with_node_removed(Uuid::from_u128(a)),
+                    2 | 3 => graph.with_edge_added(Uuid::from_u128(a), Uuid::from_u128(b)),
+                    _ => graph.with_edge_removed(Uuid::from_u128(a), Uuid::from_u128(b)),
+                };
+                aggregates.apply_diff(&GraphDiff::between(&graph, &next), &next);
+                graph = next;
+                assert_matches_graph(&aggregates, &graph);
+            }
+        }
+    }
+}
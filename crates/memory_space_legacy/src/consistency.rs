@@ -0,0 +1,324 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::graph::StructuralGraph;
+use crate::node::DesignNode;
+use crate::state::DesignState;
+use crate::types::{NodeId, Value};
+
+/// How nodes of an inferred graph are paired with reference nodes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum NodeMatching {
+    /// Same `NodeId` on both sides.
+    #[default]
+    ById,
+    /// Same text value under this attribute key, e.g. `"name"`, for graphs
+    /// whose ids were minted independently. Nodes without the attribute stay
+    /// unmatched, and only the first node (in id order) per value is paired.
+    ByAttribute(String),
+}
+
+/// One disagreement between a reference graph and an inferred graph.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ConsistencyMismatch {
+    /// Reference node with no inferred counterpart.
+    MissingNode(DesignNode),
+    /// Inferred node with no reference counterpart.
+    ExtraNode(DesignNode),
+    KindMismatch {
+        reference: NodeId,
+        inferred: NodeId,
+        expected: String,
+        found: String,
+    },
+    /// `None` means the attribute is absent on that side.
+    AttributeMismatch {
+        reference: NodeId,
+        inferred: NodeId,
+        key: String,
+        expected: Option<Value>,
+        found: Option<Value>,
+    },
+    /// Reference edge, in reference ids, not present in the inferred graph.
+    MissingEdge(NodeId, NodeId),
+    /// Inferred edge, in inferred ids, not present in the reference graph.
+    ExtraEdge(NodeId, NodeId),
+}
+
+/// Node, edge and attribute consistency of an inferred graph measured
+/// against a reference graph. Ratios over an empty denominator are `1.0`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ConsistencyReport {
+    pub node_recall: f64,
+    pub node_precision: f64,
+    pub edge_recall: f64,
+    pub edge_precision: f64,
+    /// Share of attribute keys, over all matched node pairs, whose values
+    /// agree on both sides.
+    pub attribute_agreement: f64,
+    /// `(reference, inferred)` pairs in reference id order.
+    pub matched_nodes: Vec<(NodeId, NodeId)>,
+    pub mismatches: Vec<ConsistencyMismatch>,
+}
+
+impl ConsistencyReport {
+    pub fn between(reference: &StructuralGraph, inferred: &StructuralGraph) -> Self {
+        Self::between_with(reference, inferred, &NodeMatching::ById)
+    }
+
+    pub fn between_states(reference: &DesignState, inferred: &DesignState) -> Self {
+        Self::between(&reference.graph, &inferred.graph)
+    }
+
+    pub fn between_with(
+        reference: &StructuralGraph,
+        inferred: &StructuralGraph,
+        matching: &NodeMatching,
+    ) -> Self {
+        let pairs = match_nodes(reference, inferred, matching);
+        let to_reference: BTreeMap<NodeId, NodeId> = pairs.iter().map(|(r, i)| (*i, *r)).collect();
+        let mut mismatches = Vec::new();
+
+        for (id, node) in reference.nodes() {
+            if !pairs.contains_key(id) {
+                mismatches.push(ConsistencyMismatch::MissingNode(node.clone()));
+            }
+        }
+        for (id, node) in inferred.nodes() {
+            if !to_reference.contains_key(id) {
+                mismatches.push(ConsistencyMismatch::ExtraNode(node.clone()));
+            }
+        }
+
+        let mut agreeing_keys = 0usize;
+        let mut total_keys = 0usize;
+        for (ref_id, inf_id) in &pairs {
+            let expected = &reference.nodes()[ref_id];
+            let found = &inferred.nodes()[inf_id];
+            if expected.kind != found.kind {
+                mismatches.push(ConsistencyMismatch::KindMismatch {
+                    reference: *ref_id,
+                    inferred: *inf_id,
+                    expected: expected.kind.clone(),
+                    found: found.kind.clone(),
+                });
+            }
+            let keys: BTreeSet<&String> = expected
+                .attributes
+                .keys()
+                .chain(found.attributes.keys())
+                .collect();
+            for key in keys {
+                total_keys += 1;
+                let left = expected.attributes.get(key);
+                let right = found.attributes.get(key);
+                if left == right {
+                    agreeing_keys += 1;
+                } else {
+                    mismatches.push(ConsistencyMismatch::AttributeMismatch {
+                        reference: *ref_id,
+                        inferred: *inf_id,
+                        key: key.clone(),
+                        expected: left.cloned(),
+                        found: right.cloned(),
+                    });
+                }
+            }
+        }
+
+        // Inferred edges whose endpoints both matched, translated to reference ids.
+        let translated: BTreeSet<(NodeId, NodeId)> = inferred
+            .edges()
+            .iter()
+            .filter_map(|(from, to)| Some((*to_reference.get(from)?, *to_reference.get(to)?)))
+            .collect();
+        let recalled_edges = reference.edges().intersection(&translated).count();
+        for (from, to) in reference.edges() {
+            if !translated.contains(&(*from, *to)) {
+                mismatches.push(ConsistencyMismatch::MissingEdge(*from, *to));
+            }
+        }
+        let mut precise_edges = 0usize;
+        for (from, to) in inferred.edges() {
+            let hit = match (to_reference.get(from), to_reference.get(to)) {
+                (Some(f), Some(t)) => reference.edges().contains(&(*f, *t)),
+                _ => false,
+            };
+            if hit {
+                precise_edges += 1;
+            } else {
+                mismatches.push(ConsistencyMismatch::ExtraEdge(*from, *to));
+            }
+        }
+
+        Self {
+            node_recall: ratio(pairs.len(), reference.nodes().len()),
+            node_precision: ratio(pairs.len(), inferred.nodes().len()),
+            edge_recall: ratio(recalled_edges, reference.edges().len()),
+            edge_precision: ratio(precise_edges, inferred.edges().len()),
+            attribute_agreement: ratio(agreeing_keys, total_keys),
+            matched_nodes: pairs.into_iter().collect(),
+            mismatches,
+        }
+    }
+
+    /// Mean of node recall, edge recall and attribute agreement, in the
+    /// spirit of a round-trip consistency rate.
+    pub fn consistency_rate(&self) -> f64 {
+        ((self.node_recall + self.edge_recall + self.attribute_agreement) / 3.0).clamp(0.0, 1.0)
+    }
+
+    pub fn is_consistent(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Reference id -> inferred id.
+fn match_nodes(
+    reference: &StructuralGraph,
+    inferred: &StructuralGraph,
+    matching: &NodeMatching,
+) -> BTreeMap<NodeId, NodeId> {
+    match matching {
+        NodeMatching::ById => reference
+            .nodes()
+            .keys()
+            .filter(|id| inferred.nodes().contains_key(id))
+            .map(|id| (*id, *id))
+            .collect(),
+        NodeMatching::ByAttribute(key) => {
+            let mut by_value: BTreeMap<&str, NodeId> = BTreeMap::new();
+            for (id, node) in inferred.nodes() {
+                if let Some(Value::Text(value)) = node.attributes.get(key) {
+                    by_value.entry(value.as_str()).or_insert(*id);
+                }
+            }
+            let mut pairs = BTreeMap::new();
+            for (id, node) in reference.nodes() {
+                if let Some(Value::Text(value)) = node.attributes.get(key)
+                    && let Some(inferred_id) = by_value.remove(value.as_str())
+                {
+                    pairs.insert(*id, inferred_id);
+                }
+            }
+            pairs
+        }
+    }
+}
+
+fn ratio(hits: usize, total: usize) -> f64 {
+    if total == 0 {
+        1.0
+    } else {
+        hits as f64 / total as f64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{
+        ConsistencyMismatch, ConsistencyReport, DesignNode, NodeMatching, StructuralGraph, Uuid,
+        Value,
+    };
+
+    fn named(id: u128, kind: &str, name: &str) -> DesignNode {
+        DesignNode::new(
+            Uuid::from_u128(id),
+            kind,
+            BTreeMap::from([("name".to_string(), Value::Text(name.to_string()))]),
+        )
+    }
+
+    fn reference() -> StructuralGraph {
+        StructuralGraph::default()
+            .with_node_added(named(1, "Interface", "api"))
+            .with_node_added(named(2, "Service", "orders"))
+            .with_node_added(named(3, "Storage", "db"))
+            .with_edge_added(Uuid::from_u128(1), Uuid::from_u128(2))
+            .with_edge_added(Uuid::from_u128(2), Uuid::from_u128(3))
+    }
+
+    #[test]
+    fn identical_graphs_are_fully_consistent() {
+        let report = ConsistencyReport::between(&reference(), &reference());
+
+        assert!(report.is_consistent());
+        assert_eq!(report.node_recall, 1.0);
+        assert_eq!(report.edge_precision, 1.0);
+        assert_eq!(report.attribute_agreement, 1.0);
+        assert_eq!(report.consistency_rate(), 1.0);
+        assert_eq!(report.matched_nodes.len(), 3);
+    }
+
+    #[test]
+    fn attribute_matching_reports_recall_precision_and_mismatches() {
+        // Independently minted ids; "db" missing, "cache" extra, orders retyped.
+        let mut orders = named(20, "Module", "orders");
+        orders
+            .attributes
+            .insert("replicas".to_string(), Value::Int(2));
+        let inferred = StructuralGraph::default()
+            .with_node_added(named(10, "Interface", "api"))
+            .with_node_added(orders)
+            .with_node_added(named(40, "Storage", "cache"))
+            .with_edge_added(Uuid::from_u128(10), Uuid::from_u128(20))
+            .with_edge_added(Uuid::from_u128(20), Uuid::from_u128(40));
+
+        let report = ConsistencyReport::between_with(
+            &reference(),
+            &inferred,
+            &NodeMatching::ByAttribute("name".to_string()),
+        );
+
+        assert_eq!(
+            report.matched_nodes,
+            vec![
+                (Uuid::from_u128(1), Uuid::from_u128(10)),
+                (Uuid::from_u128(2), Uuid::from_u128(20)),
+            ]
+        );
+        assert!((report.node_recall - 2.0 / 3.0).abs() < 1e-12);
+        assert!((report.node_precision - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(report.edge_recall, 0.5);
+        assert_eq!(report.edge_precision, 0.5);
+        // api: name agrees; orders: name agrees, replicas only inferred.
+        assert!((report.attribute_agreement - 2.0 / 3.0).abs() < 1e-12);
+        assert_eq!(
+            report.mismatches,
+            vec![
+                ConsistencyMismatch::MissingNode(named(3, "Storage", "db")),
+                ConsistencyMismatch::ExtraNode(named(40, "Storage", "cache")),
+                ConsistencyMismatch::KindMismatch {
+                    reference: Uuid::from_u128(2),
+                    inferred: Uuid::from_u128(20),
+                    expected: "Service".to_string(),
+                    found: "Module".to_string(),
+                },
+                ConsistencyMismatch::AttributeMismatch {
+                    reference: Uuid::from_u128(2),
+                    inferred: Uuid::from_u128(20),
+                    key: "replicas".to_string(),
+                    expected: None,
+                    found: Some(Value::Int(2)),
+                },
+                ConsistencyMismatch::MissingEdge(Uuid::from_u128(2), Uuid::from_u128(3)),
+                ConsistencyMismatch::ExtraEdge(Uuid::from_u128(20), Uuid::from_u128(40)),
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_inferred_graph_has_zero_recall_and_vacuous_precision() {
+        let report = ConsistencyReport::between(&reference(), &StructuralGraph::default());
+
+        assert_eq!(report.node_recall, 0.0);
+        assert_eq!(report.node_precision, 1.0);
+        assert_eq!(report.edge_recall, 0.0);
+        assert_eq!(report.edge_precision, 1.0);
+        assert_eq!(report.mismatches.len(), 5);
+    }
+}
@@ -0,0 +1,829 @@
+//! Import of architecture diagrams written as Mermaid flowcharts or
+//! Graphviz DOT.
+//!
+//! Only the part of each language that describes boxes and arrows is read.
+//! A node's kind comes from its label: `Service: Orders` gives kind
+//! `Service` with a `label` attribute of `Orders`, any other label is the
+//! kind itself, and an unlabelled node is of the kind of its identifier.
+//! Style hints become attributes: DOT node attributes other than `label`,
+//! and for Mermaid the bracket shape, `style` properties and classes.
+//! Edges keep their direction; edge labels and styles are dropped.
+//!
+//! Node ids are handed out as 1, 2, 3, … in order of first appearance, so
+//! the same diagram always yields the same graph. The graph must satisfy
+//! `StructuralGraph::validate`, which rules out cycles and self loops.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use crate::graph::{GraphViolation, StructuralGraph};
+use crate::node::DesignNode;
+use crate::state::DesignState;
+use crate::types::{NodeId, StateId, Uuid, Value};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiagramFormat {
+    Mermaid,
+    Dot,
+}
+
+impl DiagramFormat {
+    /// Looks at the first statement: `graph`/`flowchart` followed by a
+    /// direction is Mermaid, `[strict] graph|digraph … {` is DOT.
+    pub fn detect(source: &str) -> Option<Self> {
+        let first = source.lines().map(str::trim).find(|line| {
+            !line.is_empty()
+                && !line.starts_with("%%")
+                && !line.starts_with("//")
+                && !line.starts_with('#')
+        })?;
+        let mut words = first.split_whitespace();
+        let mut head = words.next()?;
+        if head == "strict" {
+            head = words.next()?;
+        }
+        match head {
+            "flowchart" => Some(Self::Mermaid),
+            "digraph" => Some(Self::Dot),
+            "graph" if first.contains('{') => Some(Self::Dot),
+            "graph" => Some(Self::Mermaid),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum DiagramError {
+    UnknownFormat,
+    /// `line` is 1-based.
+    Syntax {
+        line: usize,
+        message: String,
+    },
+    InvalidGraph(Vec<GraphViolation>),
+}
+
+impl Display for DiagramError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownFormat => write!(f, "input is neither a Mermaid flowchart nor DOT"),
+            Self::Syntax { line, message } => write!(f, "line {line}: {message}"),
+            Self::InvalidGraph(violations) => {
+                write!(f, "diagram is not a valid design graph: ")?;
+                for (i, violation) in violations.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{violation}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for DiagramError {}
+
+#[derive(Clone, Debug)]
+pub struct DiagramImport {
+    pub graph: StructuralGraph,
+    /// Diagram identifier of every node.
+    pub ids: BTreeMap<String, NodeId>,
+}
+
+impl DiagramImport {
+    /// The diagram as an initial search state.
+    pub fn into_state(self, id: StateId) -> DesignState {
+        DesignState::new(id, Arc::new(self.graph), "diagram")
+    }
+}
+
+/// Detects the format and parses accordingly.
+pub fn parse_diagram(source: &str) -> Result<DiagramImport, DiagramError> {
+    match DiagramFormat::detect(source).ok_or(DiagramError::UnknownFormat)? {
+        DiagramFormat::Mermaid => parse_mermaid(source),
+        DiagramFormat::Dot => parse_dot(source),
+    }
+}
+
+/// Nodes and edges collected while parsing, keyed by diagram identifier.
+#[derive(Default)]
+struct Builder {
+    order: Vec<String>,
+    labels: BTreeMap<String, String>,
+    attributes: BTreeMap<String, BTreeMap<String, Value>>,
+    edges: Vec<(String, String)>,
+}
+
+impl Builder {
+    fn node(&mut self, id: &str) {
+        if !self.attributes.contains_key(id) {
+            self.order.push(id.to_string());
+            self.attributes.insert(id.to_string(), BTreeMap::new());
+        }
+    }
+
+    fn label(&mut self, id: &str, label: &str) {
+        self.node(id);
+        self.labels.insert(id.to_string(), label.to_string());
+    }
+
+    fn attribute(&mut self, id: &str, key: &str, value: &str) {
+        self.node(id);
+        self.attributes
+            .get_mut(id)
+            .expect("node registered above")
+            .insert(key.to_string(), parse_value(value));
+    }
+
+    fn edge(&mut self, from: &str, to: &str) {
+        self.node(from);
+        self.node(to);
+        self.edges.push((from.to_string(), to.to_string()));
+    }
+
+    fn finish(mut self) -> Result<DiagramImport, DiagramError> {
+        let ids = self
+            .order
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (name.clone(), Uuid::from_u128(index as u128 + 1)))
+            .collect::<BTreeMap<_, _>>();
+        let mut nodes = BTreeMap::new();
+        for name in &self.order {
+            let mut attributes = self.attributes.remove(name).unwrap_or_default();
+            let label = self.labels.get(name).map(|l| collapse_whitespace(l));
+            let kind = match label.as_deref().map(|l| l.split_once(':')) {
+                Some(Some((kind, rest))) if !kind.trim().is_empty() => {
+                    if !rest.trim().is_empty() {
+                        attributes.insert("label".to_string(), Value::Text(rest.trim().into()));
+                    }
+                    kind.trim().to_string()
+                }
+                Some(_) if label.as_deref().is_some_and(|l| !l.is_empty()) => {
+                    label.clone().unwrap_or_default()
+                }
+                _ => name.clone(),
+            };
+            let id = ids[name];
+            nodes.insert(id, DesignNode::new(id, kind, attributes));
+        }
+        let edges = self
+            .edges
+            .iter()
+            .map(|(from, to)| (ids[from], ids[to]))
+            .collect::<BTreeSet<_>>();
+        let graph = StructuralGraph::try_new(nodes, edges).map_err(DiagramError::InvalidGraph)?;
+        Ok(DiagramImport { graph, ids })
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn parse_value(raw: &str) -> Value {
+    let raw = raw.trim();
+    if let Ok(v) = raw.parse::<i64>() {
+        Value::Int(v)
+    } else if let Ok(v) = raw.parse::<f64>()
+        && v.is_finite()
+    {
+        Value::Float(v)
+    } else if let Ok(v) = raw.parse::<bool>() {
+        Value::Bool(v)
+    } else {
+        Value::Text(raw.to_string())
+    }
+}
+
+fn unquote(text: &str) -> &str {
+    let text = text.trim();
+    text.strip_prefix('"')
+        .and_then(|t| t.strip_suffix('"'))
+        .unwrap_or(text)
+}
+
+// ---------------------------------------------------------------- Mermaid
+
+/// Bracket pairs around a Mermaid node label, longest opener first, with
+/// the `shape` attribute each one gives.
+const MERMAID_SHAPES: [(&str, &str, &str); 9] = [
+    ("((", "))", "circle"),
+    ("([", "])", "stadium"),
+    ("[(", ")]", "cylinder"),
+    ("[[", "]]", "subroutine"),
+    ("{{", "}}", "hexagon"),
+    ("[", "]", "box"),
+    ("(", ")", "round"),
+    ("{", "}", "diamond"),
+    (">", "]", "flag"),
+];
+
+/// Line-oriented subset: node declarations with any bracket shape,
+/// `A --> B --> C` chains with `&` groups and `|text|` or `-- text -->`
+/// labels, `style`, `class` and `:::class`. `subgraph`/`end` are read
+/// through; `classDef`, `linkStyle`, `click` and `direction` are skipped.
+pub fn parse_mermaid(source: &str) -> Result<DiagramImport, DiagramError> {
+    let mut builder = Builder::default();
+    let mut seen_header = false;
+    for (index, raw) in source.lines().enumerate() {
+        let line = index + 1;
+        let text = raw.split("%%").next().unwrap_or("").trim();
+        if text.is_empty() {
+            continue;
+        }
+        let keyword = text.split_whitespace().next().unwrap_or("");
+        if !seen_header {
+            seen_header = true;
+            if keyword == "flowchart" || keyword == "graph" {
+                continue;
+            }
+        }
+        match keyword {
+            "subgraph" | "end" | "classDef" | "linkStyle" | "click" | "direction" => {}
+            "style" => mermaid_style(&mut builder, text, line)?,
+            "class" => mermaid_class(&mut builder, text, line)?,
+            _ => {
+                for statement in text.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+                    MermaidStatement::new(statement, line).parse(&mut builder)?;
+                }
+            }
+        }
+    }
+    builder.finish()
+}
+
+fn syntax(line: usize, message: impl Into<String>) -> DiagramError {
+    DiagramError::Syntax {
+        line,
+        message: message.into(),
+    }
+}
+
+fn mermaid_style(builder: &mut Builder, text: &str, line: usize) -> Result<(), DiagramError> {
+    let mut parts = text.splitn(3, char::is_whitespace).skip(1);
+    let id = parts
+        .next()
+        .filter(|id| is_mermaid_id(id))
+        .ok_or_else(|| syntax(line, "style needs a node id"))?;
+    for property in parts.next().unwrap_or("").split(',') {
+        if let Some((key, value)) = property.split_once(':') {
+            builder.attribute(id, key.trim(), value);
+        }
+    }
+    Ok(())
+}
+
+fn mermaid_class(builder: &mut Builder, text: &str, line: usize) -> Result<(), DiagramError> {
+    let parts = text.split_whitespace().collect::<Vec<_>>();
+    let [_, ids, class] = parts[..] else {
+        return Err(syntax(line, "expected `class <ids> <class>`"));
+    };
+    for id in ids.split(',') {
+        builder.attribute(id, "class", class);
+    }
+    Ok(())
+}
+
+fn is_mermaid_id(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(is_mermaid_id_char)
+}
+
+fn is_mermaid_id_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+fn is_arrow_char(c: char) -> bool {
+    matches!(c, '-' | '=' | '.' | '<' | '>')
+}
+
+struct MermaidStatement<'a> {
+    rest: &'a str,
+    line: usize,
+}
+
+impl<'a> MermaidStatement<'a> {
+    fn new(text: &'a str, line: usize) -> Self {
+        Self { rest: text, line }
+    }
+
+    fn parse(mut self, builder: &mut Builder) -> Result<(), DiagramError> {
+        let mut previous = self.group(builder)?;
+        while !self.rest.trim().is_empty() {
+            self.arrow()?;
+            let next = self.group(builder)?;
+            for from in &previous {
+                for to in &next {
+                    builder.edge(from, to);
+                }
+            }
+            previous = next;
+        }
+        Ok(())
+    }
+
+    /// `A & B[label] & C`.
+    fn group(&mut self, builder: &mut Builder) -> Result<Vec<String>, DiagramError> {
+        let mut ids = vec![self.node(builder)?];
+        while let Some(rest) = self.rest.trim_start().strip_prefix('&') {
+            self.rest = rest;
+            ids.push(self.node(builder)?);
+        }
+        Ok(ids)
+    }
+
+    fn node(&mut self, builder: &mut Builder) -> Result<String, DiagramError> {
+        let text = self.rest.trim_start();
+        let end = text
+            .find(|c: char| !is_mermaid_id_char(c))
+            .unwrap_or(text.len());
+        if end == 0 {
+            return Err(syntax(self.line, format!("expected a node id at `{text}`")));
+        }
+        let id = &text[..end];
+        builder.node(id);
+        let mut rest = &text[end..];
+        if let Some((open, close, shape)) = MERMAID_SHAPES
+            .iter()
+            .find(|(open, _, _)| rest.starts_with(open))
+        {
+            let body = &rest[open.len()..];
+            let close_at = body
+                .find(close)
+                .ok_or_else(|| syntax(self.line, format!("unclosed `{open}` after `{id}`")))?;
+            builder.label(id, unquote(&body[..close_at]));
+            builder.attribute(id, "shape", shape);
+            rest = &body[close_at + close.len()..];
+        }
+        if let Some(after) = rest.strip_prefix(":::") {
+            let end = after
+                .find(|c: char| !is_mermaid_id_char(c) && c != '-')
+                .unwrap_or(after.len());
+            builder.attribute(id, "class", &after[..end]);
+            rest = &after[end..];
+        }
+        self.rest = rest;
+        Ok(id.to_string())
+    }
+
+    /// `-->`, `---`, `==>`, `-.->` and friends, with an optional `|text|`
+    /// after or `-- text -->` around the label.
+    fn arrow(&mut self) -> Result<(), DiagramError> {
+        let text = self.rest.trim_start();
+        let end = text.find(|c| !is_arrow_char(c)).unwrap_or(text.len());
+        if end < 2 {
+            return Err(syntax(self.line, format!("expected an arrow at `{text}`")));
+        }
+        let arrow = &text[..end];
+        let mut rest = &text[end..];
+        if matches!(arrow, "--" | "==" | "-.") {
+            let closing = ["-->", "---", "==>", "===", ".->", "-.-"]
+                .iter()
+                .filter_map(|closing| rest.find(closing))
+                .min()
+                .ok_or_else(|| syntax(self.line, "unterminated edge label"))?;
+            let after = &rest[closing..];
+            let close_end = after.find(|c| !is_arrow_char(c)).unwrap_or(after.len());
+            rest = &after[close_end..];
+        }
+        let trimmed = rest.trim_start();
+        if let Some(label) = trimmed.strip_prefix('|') {
+            let close = label
+                .find('|')
+                .ok_or_else(|| syntax(self.line, "unclosed `|` edge label"))?;
+            rest = &label[close + 1..];
+        }
+        self.rest = rest;
+        Ok(())
+    }
+}
+
+// -------------------------------------------------------------------- DOT
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Id(String),
+    Symbol(char),
+    EdgeOp,
+}
+
+fn tokenize_dot(source: &str) -> Result<Vec<(Token, usize)>, DiagramError> {
+    let chars = source.chars().collect::<Vec<_>>();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let next = chars.get(i + 1).copied();
+        match c {
+            '\n' => {
+                line += 1;
+                i += 1;
+            }
+            c if c.is_whitespace() => i += 1,
+            '#' => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if next == Some('/') => {
+                while i < chars.len() && chars[i] != '\n' {
+                    i += 1;
+                }
+            }
+            '/' if next == Some('*') => {
+                i += 2;
+                while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                    if chars[i] == '\n' {
+                        line += 1;
+                    }
+                    i += 1;
+                }
+                i += 2;
+            }
+            '-' if matches!(next, Some('>') | Some('-')) => {
+                tokens.push((Token::EdgeOp, line));
+                i += 2;
+            }
+            '{' | '}' | '[' | ']' | ';' | ',' | '=' | ':' => {
+                tokens.push((Token::Symbol(c), line));
+                i += 1;
+            }
+            '"' => {
+                let start_line = line;
+                let mut text = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(syntax(start_line, "unterminated string")),
+                        Some('"') => break,
+                        Some('\\') if chars.get(i + 1) == Some(&'"') => {
+                            text.push('"');
+                            i += 1;
+                        }
+                        Some(ch) => {
+                            if *ch == '\n' {
+                                line += 1;
+                            }
+                            text.push(*ch);
+                        }
+                    }
+                    i += 1;
+                }
+                tokens.push((Token::Id(text), start_line));
+                i += 1;
+            }
+            c if c.is_alphanumeric() || c == '_' || c == '.' || c == '-' => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_alphanumeric() || matches!(chars[i], '_' | '.' | '-'))
+                    && !(chars[i] == '-' && matches!(chars.get(i + 1), Some('>') | Some('-')))
+                {
+                    i += 1;
+                }
+                tokens.push((Token::Id(chars[start..i].iter().collect()), line));
+            }
+            other => return Err(syntax(line, format!("unexpected `{other}`"))),
+        }
+    }
+    Ok(tokens)
+}
+
+/// `[strict] graph|digraph [name] { … }` with node statements, edge chains
+/// (`a -> b -> c`, or `--`), `node [...]` defaults and nested subgraphs.
+/// Ports, edges to subgraphs and HTML labels are not supported.
+pub fn parse_dot(source: &str) -> Result<DiagramImport, DiagramError> {
+    let tokens = tokenize_dot(source)?;
+    let mut parser = DotParser {
+        tokens: &tokens,
+        pos: 0,
+        builder: Builder::default(),
+    };
+    parser.header()?;
+    parser.statements(&BTreeMap::new())?;
+    if let Some((token, line)) = parser.tokens.get(parser.pos) {
+        return Err(syntax(
+            *line,
+            format!("unexpected {token:?} after the graph"),
+        ));
+    }
+    parser.builder.finish()
+}
+
+struct DotParser<'a> {
+    tokens: &'a [(Token, usize)],
+    pos: usize,
+    builder: Builder,
+}
+
+impl DotParser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn line(&self) -> usize {
+        self.tokens
+            .get(self.pos)
+            .or(self.tokens.last())
+            .map_or(1, |(_, line)| *line)
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        if self.peek() == Some(&Token::Symbol(symbol)) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), DiagramError> {
+        if self.eat(symbol) {
+            Ok(())
+        } else {
+            Err(syntax(self.line(), format!("expected `{symbol}`")))
+        }
+    }
+
+    fn id(&mut self) -> Option<String> {
+        match self.peek() {
+            Some(Token::Id(id)) => {
+                let id = id.clone();
+                self.pos += 1;
+                Some(id)
+            }
+            _ => None,
+        }
+    }
+
+    fn header(&mut self) -> Result<(), DiagramError> {
+        let mut keyword = self.id();
+        if keyword.as_deref() == Some("strict") {
+            keyword = self.id();
+        }
+        if !matches!(keyword.as_deref(), Some("graph" | "digraph")) {
+            return Err(syntax(self.line(), "expected `graph` or `digraph`"));
+        }
+        if matches!(self.peek(), Some(Token::Id(_))) {
+            self.pos += 1;
+        }
+        self.expect('{')
+    }
+
+    /// Statements up to and including the closing `}`.
+    fn statements(&mut self, defaults: &BTreeMap<String, String>) -> Result<(), DiagramError> {
+        let mut defaults = defaults.clone();
+        loop {
+            if self.eat('}') {
+                return Ok(());
+            }
+            if self.eat(';') {
+                continue;
+            }
+            let line = self.line();
+            let Some(head) = self.id() else {
+                if self.eat('{') {
+                    self.statements(&defaults)?;
+                    continue;
+                }
+                return Err(syntax(line, "expected a statement or `}`"));
+            };
+            match head.as_str() {
+                "node" => defaults.extend(self.attributes()?),
+                "edge" | "graph" => {
+                    self.attributes()?;
+                }
+                "subgraph" => {
+                    if matches!(self.peek(), Some(Token::Id(_))) {
+                        self.pos += 1;
+                    }
+                    self.expect('{')?;
+                    self.statements(&defaults)?;
+                }
+                _ if self.eat('=') => {
+                    self.id()
+                        .ok_or_else(|| syntax(line, "expected a value after `=`"))?;
+                }
+                _ => self.node_or_edge(head, &defaults)?,
+            }
+        }
+    }
+
+    fn node_or_edge(
+        &mut self,
+        head: String,
+        defaults: &BTreeMap<String, String>,
+    ) -> Result<(), DiagramError> {
+        if self.peek() == Some(&Token::Symbol(':')) {
+            return Err(syntax(self.line(), "node ports are not supported"));
+        }
+        let mut chain = vec![head];
+        while self.peek() == Some(&Token::EdgeOp) {
+            self.pos += 1;
+            let line = self.line();
+            chain.push(
+                self.id()
+                    .ok_or_else(|| syntax(line, "edges must end at a node id"))?,
+            );
+        }
+        let attributes = self.attributes()?;
+        for id in &chain {
+            if !self.builder.attributes.contains_key(id) {
+                self.apply(id, defaults);
+            }
+        }
+        if let [id] = &chain[..] {
+            self.apply(id, &attributes);
+        } else {
+            for pair in chain.windows(2) {
+                self.builder.edge(&pair[0], &pair[1]);
+            }
+        }
+        Ok(())
+    }
+
+    fn apply(&mut self, id: &str, attributes: &BTreeMap<String, String>) {
+        self.builder.node(id);
+        for (key, value) in attributes {
+            if key == "label" {
+                self.builder.label(id, value);
+            } else {
+                self.builder.attribute(id, key, value);
+            }
+        }
+    }
+
+    /// Zero or more `[k=v, k=v; …]` lists.
+    fn attributes(&mut self) -> Result<BTreeMap<String, String>, DiagramError> {
+        let mut out = BTreeMap::new();
+        while self.eat('[') {
+            loop {
+                if self.eat(']') {
+                    break;
+                }
+                if self.eat(',') || self.eat(';') {
+                    continue;
+                }
+                let line = self.line();
+                let key = self
+                    .id()
+                    .ok_or_else(|| syntax(line, "expected an attribute name"))?;
+                self.expect('=')?;
+                let value = self
+                    .id()
+                    .ok_or_else(|| syntax(line, format!("expected a value for `{key}`")))?;
+                out.insert(key, value);
+            }
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kind_of(import: &DiagramImport, id: &str) -> String {
+        import.graph.nodes()[&import.ids[id]].kind.clone()
+    }
+
+    fn attribute(import: &DiagramImport, id: &str, key: &str) -> Option<Value> {
+        import.graph.nodes()[&import.ids[id]]
+            .attributes
+            .get(key)
+            .cloned()
+    }
+
+    fn has_edge(import: &DiagramImport, from: &str, to: &str) -> bool {
+        import
+            .graph
+            .edges()
+            .contains(&(import.ids[from], import.ids[to]))
+    }
+
+    #[test]
+    fn mermaid_flowchart_becomes_a_graph() {
+        let source = r#"
+            flowchart LR
+              %% entry point
+              client((User)) --> gw[Gateway: edge]
+              gw -->|REST| orders["Service: Orders"] & payments(Service: Payments)
+              orders -- writes --> db[(Database)]:::storage
+              payments -.-> db
+              style gw fill:#f9f,stroke-width:2
+              class orders,payments core
+        "#;
+        let import = parse_diagram(source).expect("mermaid");
+        assert_eq!(import.graph.nodes().len(), 5);
+        assert_eq!(import.ids["client"], Uuid::from_u128(1));
+        assert_eq!(kind_of(&import, "client"), "User");
+        assert_eq!(kind_of(&import, "gw"), "Gateway");
+        assert_eq!(kind_of(&import, "orders"), "Service");
+        assert_eq!(
+            attribute(&import, "orders", "label"),
+            Some(Value::Text("Orders".into()))
+        );
+        assert_eq!(
+            attribute(&import, "db", "shape"),
+            Some(Value::Text("cylinder".into()))
+        );
+        assert_eq!(
+            attribute(&import, "db", "class"),
+            Some(Value::Text("storage".into()))
+        );
+        assert_eq!(
+            attribute(&import, "gw", "stroke-width"),
+            Some(Value::Int(2))
+        );
+        assert_eq!(
+            attribute(&import, "payments", "class"),
+            Some(Value::Text("core".into()))
+        );
+        for (from, to) in [
+            ("client", "gw"),
+            ("gw", "orders"),
+            ("gw", "payments"),
+            ("orders", "db"),
+            ("payments", "db"),
+        ] {
+            assert!(has_edge(&import, from, to), "{from} -> {to}");
+        }
+        assert_eq!(import.graph.edges().len(), 5);
+        assert!(import.graph.validate().is_ok());
+    }
+
+    #[test]
+    fn dot_digraph_becomes_a_graph() {
+        let source = r#"
+            // generated
+            digraph arch {
+              rankdir=LR;
+              node [shape=box];
+              api [label="Service: API", replicas=3];
+              cache [label=Cache, shape=ellipse, ttl=1.5];
+              subgraph cluster_data {
+                db [label="Database", managed=true];
+              }
+              api -> cache -> db [color=red];
+              api -> db;
+              worker;
+            }
+        "#;
+        let import = parse_diagram(source).expect("dot");
+        assert_eq!(import.graph.nodes().len(), 4);
+        assert_eq!(kind_of(&import, "api"), "Service");
+        assert_eq!(kind_of(&import, "worker"), "worker");
+        assert_eq!(attribute(&import, "api", "replicas"), Some(Value::Int(3)));
+        assert_eq!(
+            attribute(&import, "api", "shape"),
+            Some(Value::Text("box".into()))
+        );
+        assert_eq!(
+            attribute(&import, "cache", "shape"),
+            Some(Value::Text("ellipse".into()))
+        );
+        assert_eq!(attribute(&import, "cache", "ttl"), Some(Value::Float(1.5)));
+        assert_eq!(attribute(&import, "db", "managed"), Some(Value::Bool(true)));
+        assert!(has_edge(&import, "api", "cache"));
+        assert!(has_edge(&import, "cache", "db"));
+        assert!(has_edge(&import, "api", "db"));
+        assert_eq!(import.graph.edges().len(), 3);
+
+        let state = import.into_state(Uuid::from_u128(9));
+        assert_eq!(state.profile_snapshot, "diagram");
+    }
+
+    #[test]
+    fn unsupported_or_invalid_diagrams_are_rejected() {
+        assert_eq!(
+            parse_diagram("sequenceDiagram\n A->>B: hi").unwrap_err(),
+            DiagramError::UnknownFormat
+        );
+        assert!(matches!(
+            parse_mermaid("graph TD\n a --> b\n b --> a"),
+            Err(DiagramError::InvalidGraph(_))
+        ));
+        assert!(matches!(
+            parse_mermaid("graph TD\n a[open --> b"),
+            Err(DiagramError::Syntax { line: 2, .. })
+        ));
+        assert!(matches!(
+            parse_dot("digraph { a -> }"),
+            Err(DiagramError::Syntax { line: 1, .. })
+        ));
+        assert!(matches!(
+            parse_dot("digraph {\n a:p1 -> b\n}"),
+            Err(DiagramError::Syntax { line: 2, .. })
+        ));
+        assert_eq!(
+            DiagramFormat::detect("strict graph g { a -- b }"),
+            Some(DiagramFormat::Dot)
+        );
+        assert_eq!(
+            DiagramFormat::detect("graph TD\n a --- b"),
+            Some(DiagramFormat::Mermaid)
+        );
+    }
+}
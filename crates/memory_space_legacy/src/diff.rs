@@ -0,0 +1,90 @@
+use crate::graph::StructuralGraph;
+use crate::node::DesignNode;
+use crate::types::NodeId;
+
+/// Structural difference between two graphs, `before -> after`.
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GraphDiff {
+    pub added_nodes: Vec<DesignNode>,
+    pub removed_nodes: Vec<DesignNode>,
+    /// Same id on both sides but a different kind or attributes; `(before, after)`.
+    pub changed_nodes: Vec<(DesignNode, DesignNode)>,
+    pub added_edges: Vec<(NodeId, NodeId)>,
+    pub removed_edges: Vec<(NodeId, NodeId)>,
+}
+
+impl GraphDiff {
+    pub fn between(before: &StructuralGraph, after: &StructuralGraph) -> Self {
+        let mut diff = Self::default();
+        for (id, node) in before.nodes() {
+            match after.nodes().get(id) {
+                None => diff.removed_nodes.push(node.clone()),
+                Some(next) if next != node => diff.changed_nodes.push((node.clone(), next.clone())),
+                Some(_) => {}
+            }
+        }
+        diff.added_nodes = after
+            .nodes()
+            .iter()
+            .filter(|(id, _)| !before.nodes().contains_key(id))
+            .map(|(_, node)| node.clone())
+            .collect();
+        diff.added_edges = after.edges().difference(before.edges()).copied().collect();
+        diff.removed_edges = before.edges().difference(after.edges()).copied().collect();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.changed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use crate::{DesignNode, GraphDiff, StructuralGraph, Uuid, Value};
+
+    fn node(id: u128, kind: &str) -> DesignNode {
+        DesignNode::new(Uuid::from_u128(id), kind, BTreeMap::new())
+    }
+
+    #[test]
+    fn diff_reports_nodes_edges_and_attribute_changes() {
+        let before = StructuralGraph::default()
+            .with_node_added(node(1, "A"))
+            .with_node_added(node(2, "B"))
+            .with_edge_added(Uuid::from_u128(1), Uuid::from_u128(2));
+        let mut changed = node(1, "A");
+        changed
+            .attributes
+            .insert("replicas".to_string(), Value::Int(2));
+        let after = StructuralGraph::new(
+            BTreeMap::from([
+                (changed.id, changed.clone()),
+                (Uuid::from_u128(3), node(3, "C")),
+            ]),
+            BTreeSet::from([(Uuid::from_u128(1), Uuid::from_u128(3))]),
+        );
+
+        let diff = GraphDiff::between(&before, &after);
+
+        assert_eq!(diff.added_nodes, vec![node(3, "C")]);
+        assert_eq!(diff.removed_nodes, vec![node(2, "B")]);
+        assert_eq!(diff.changed_nodes, vec![(node(1, "A"), changed)]);
+        assert_eq!(
+            diff.added_edges,
+            vec![(Uuid::from_u128(1), Uuid::from_u128(3))]
+        );
+        assert_eq!(
+            diff.removed_edges,
+            vec![(Uuid::from_u128(1), Uuid::from_u128(2))]
+        );
+        assert!(GraphDiff::between(&after, &after).is_empty());
+    }
+}
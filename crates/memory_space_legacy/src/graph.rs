@@ -4,11 +4,117 @@ use crate::node::DesignNode;
 use crate::types::{NodeId, Value};
 
 #[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(into = "GraphRepr", try_from = "GraphRepr")
+)]
 pub struct StructuralGraph {
     nodes: BTreeMap<NodeId, DesignNode>,
     edges: BTreeSet<(NodeId, NodeId)>,
 }
 
+/// Wire form of `StructuralGraph`; decoding re-checks the DAG invariants
+/// instead of panicking like `StructuralGraph::new`.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct GraphRepr {
+    nodes: Vec<DesignNode>,
+    edges: Vec<(NodeId, NodeId)>,
+}
+
+#[cfg(feature = "serde")]
+impl From<StructuralGraph> for GraphRepr {
+    fn from(graph: StructuralGraph) -> Self {
+        Self {
+            nodes: graph.nodes.into_values().collect(),
+            edges: graph.edges.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<GraphRepr> for StructuralGraph {
+    type Error = String;
+
+    fn try_from(repr: GraphRepr) -> Result<Self, Self::Error> {
+        let mut nodes = BTreeMap::new();
+        for node in repr.nodes {
+            let id = node.id;
+            if nodes.insert(id, node).is_some() {
+                return Err(format!("duplicate node id {id:?}"));
+            }
+        }
+        let graph = Self {
+            nodes,
+            edges: repr.edges.into_iter().collect(),
+        };
+        if !graph.all_edges_have_valid_endpoints() {
+            return Err("edge references a missing node".to_string());
+        }
+        if !graph.no_self_loops() {
+            return Err("graph contains a self loop".to_string());
+        }
+        if !graph.is_dag() {
+            return Err("graph contains a cycle".to_string());
+        }
+        Ok(graph)
+    }
+}
+
+/// Upper bound on attributes per node accepted by `StructuralGraph::validate`.
+pub const MAX_NODE_ATTRIBUTES: usize = 256;
+/// Upper bound on one attribute's key + text payload, in bytes.
+pub const MAX_ATTRIBUTE_BYTES: usize = 4096;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GraphViolation {
+    DanglingEdge {
+        from: NodeId,
+        to: NodeId,
+    },
+    SelfLoop(NodeId),
+    /// The node stored under `key` reports a different `id`, so two entries
+    /// could claim the same identity.
+    NodeIdMismatch {
+        key: NodeId,
+        node_id: NodeId,
+    },
+    Cycle,
+    TooManyAttributes {
+        node: NodeId,
+        count: usize,
+    },
+    AttributeTooLarge {
+        node: NodeId,
+        key: String,
+        bytes: usize,
+    },
+}
+
+impl std::fmt::Display for GraphViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::DanglingEdge { from, to } => {
+                write!(f, "edge {from:?} -> {to:?} references a missing node")
+            }
+            Self::SelfLoop(id) => write!(f, "self loop on {id:?}"),
+            Self::NodeIdMismatch { key, node_id } => {
+                write!(f, "node stored under {key:?} has id {node_id:?}")
+            }
+            Self::Cycle => write!(f, "graph contains a cycle"),
+            Self::TooManyAttributes { node, count } => write!(
+                f,
+                "node {node:?} has {count} attributes (limit {MAX_NODE_ATTRIBUTES})"
+            ),
+            Self::AttributeTooLarge { node, key, bytes } => write!(
+                f,
+                "attribute {key:?} on {node:?} is {bytes} bytes (limit {MAX_ATTRIBUTE_BYTES})"
+            ),
+        }
+    }
+}
+
 impl StructuralGraph {
     pub fn new(nodes: BTreeMap<NodeId, DesignNode>, edges: BTreeSet<(NodeId, NodeId)>) -> Self {
         let graph = Self { nodes, edges };
@@ -18,6 +124,17 @@ impl StructuralGraph {
         graph
     }
 
+    /// Non-panicking counterpart of `new` for untrusted input; reports every
+    /// `validate` violation instead of asserting.
+    pub fn try_new(
+        nodes: BTreeMap<NodeId, DesignNode>,
+        edges: BTreeSet<(NodeId, NodeId)>,
+    ) -> Result<Self, Vec<GraphViolation>> {
+        let graph = Self { nodes, edges };
+        graph.validate()?;
+        Ok(graph)
+    }
+
     pub fn nodes(&self) -> &BTreeMap<NodeId, DesignNode> {
         &self.nodes
     }
@@ -330,6 +447,66 @@ impl StructuralGraph {
         (var / max_var).clamp(0.0, 1.0)
     }
 
+    /// Checks every structural invariant and reports all violations found,
+    /// unlike `is_dag` which only answers yes/no.
+    pub fn validate(&self) -> Result<(), Vec<GraphViolation>> {
+        let mut violations = Vec::new();
+        for (key, node) in &self.nodes {
+            if node.id != *key {
+                violations.push(GraphViolation::NodeIdMismatch {
+                    key: *key,
+                    node_id: node.id,
+                });
+            }
+            if node.attributes.len() > MAX_NODE_ATTRIBUTES {
+                violations.push(GraphViolation::TooManyAttributes {
+                    node: *key,
+                    count: node.attributes.len(),
+                });
+            }
+            for (attr, value) in &node.attributes {
+                let bytes = attr.len()
+                    + match value {
+                        Value::Text(text) => text.len(),
+                        _ => 8,
+                    };
+                if bytes > MAX_ATTRIBUTE_BYTES {
+                    violations.push(GraphViolation::AttributeTooLarge {
+                        node: *key,
+                        key: attr.clone(),
+                        bytes,
+                    });
+                }
+            }
+        }
+        for (from, to) in &self.edges {
+            if from == to {
+                violations.push(GraphViolation::SelfLoop(*from));
+            } else if !self.nodes.contains_key(from) || !self.nodes.contains_key(to) {
+                violations.push(GraphViolation::DanglingEdge {
+                    from: *from,
+                    to: *to,
+                });
+            }
+        }
+        // is_dag already fails on dangling edges / self loops; only report a
+        // cycle when those are absent so each defect is listed once.
+        if violations.iter().all(|v| {
+            !matches!(
+                v,
+                GraphViolation::DanglingEdge { .. } | GraphViolation::SelfLoop(_)
+            )
+        }) && !self.is_dag()
+        {
+            violations.push(GraphViolation::Cycle);
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
     fn all_edges_have_valid_endpoints(&self) -> bool {
         self.edges
             .iter()
@@ -359,7 +536,7 @@ impl StructuralGraph {
     }
 }
 
-fn max_degree_variance_for_simple_graph(n: usize) -> f64 {
+pub(crate) fn max_degree_variance_for_simple_graph(n: usize) -> f64 {
     if n < 3 {
         return 0.0;
     }
@@ -542,6 +719,48 @@ mod tests {
         assert!((0.0..=1.0).contains(&v));
     }
 
+    #[test]
+    fn validate_reports_each_violation() {
+        let a = sample_node(1, "A");
+        let b = sample_node(2, "B");
+        let mut oversized = sample_node(3, "C");
+        oversized.attributes.insert(
+            "blob".to_string(),
+            crate::types::Value::Text("x".repeat(super::MAX_ATTRIBUTE_BYTES)),
+        );
+        let mut nodes = BTreeMap::new();
+        nodes.insert(a.id, a.clone());
+        nodes.insert(b.id, DesignNode::with_id(a.id, "Impostor", BTreeMap::new()));
+        nodes.insert(oversized.id, oversized.clone());
+        let ghost = Uuid::from_u128(99);
+        let graph = StructuralGraph {
+            nodes,
+            edges: [(a.id, ghost), (b.id, b.id)].into_iter().collect(),
+        };
+
+        let violations = graph.validate().expect_err("invalid graph");
+        assert!(violations.contains(&super::GraphViolation::DanglingEdge {
+            from: a.id,
+            to: ghost
+        }));
+        assert!(violations.contains(&super::GraphViolation::SelfLoop(b.id)));
+        assert!(violations.contains(&super::GraphViolation::NodeIdMismatch {
+            key: b.id,
+            node_id: a.id
+        }));
+        assert!(violations.iter().any(|v| matches!(
+            v,
+            super::GraphViolation::AttributeTooLarge { node, .. } if *node == oversized.id
+        )));
+        assert!(!violations.contains(&super::GraphViolation::Cycle));
+
+        let healthy = StructuralGraph::default()
+            .with_node_added(a.clone())
+            .with_node_added(b)
+            .with_edge_added(a.id, Uuid::from_u128(2));
+        assert_eq!(healthy.validate(), Ok(()));
+    }
+
     #[test]
     fn degree_gini_in_range() {
         let a = sample_node(1, "A");
@@ -598,5 +817,22 @@ mod proptest_props {
 
             prop_assert!(result.is_dag(), "with_edge_added must always return a DAG");
         }
+
+        /// 公開 API のみで構築したグラフは任意の操作列の後も validate() を通過する
+        #[test]
+        fn public_ops_preserve_all_invariants(
+            ops in proptest::collection::vec((0u8..4u8, 0u128..8u128, 0u128..8u128), 0..40usize),
+        ) {
+            let mut g = StructuralGraph::default();
+            for (op, a, b) in ops {
+                g = match op {
+                    0 => g.with_node_added(node(a)),
+                    1 => g.with_node_removed(Uuid::from_u128(a)),
+                    2 => g.with_edge_added(Uuid::from_u128(a), Uuid::from_u128(b)),
+                    _ => g.with_edge_removed(Uuid::from_u128(a), Uuid::from_u128(b)),
+                };
+                prop_assert_eq!(g.validate(), Ok(()));
+            }
+        }
     }
 }
@@ -0,0 +1,373 @@
+//! Centrality and connectivity metrics over a `StructuralGraph`.
+//!
+//! Betweenness and strongly connected components follow edge direction;
+//! articulation points treat edges as undirected, since a node whose removal
+//! disconnects the graph is critical whichever way dependencies point.
+
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
+
+use crate::graph::StructuralGraph;
+use crate::types::NodeId;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GraphMetrics {
+    /// Brandes betweenness over directed shortest paths, scaled into
+    /// `[0, 1]` by `(n - 1)(n - 2)`.
+    pub betweenness: BTreeMap<NodeId, f64>,
+    pub articulation_points: BTreeSet<NodeId>,
+    /// Each component sorted by id; components ordered by their first id.
+    /// Graphs built through `StructuralGraph`'s edge API are DAGs, so every
+    /// component is a single node unless the graph was assembled otherwise.
+    pub strongly_connected_components: Vec<Vec<NodeId>>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AnnotationKind {
+    SinglePointOfFailure,
+    Bottleneck { betweenness: f64 },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeAnnotation {
+    pub node: NodeId,
+    pub kind: AnnotationKind,
+    pub message: String,
+}
+
+impl GraphMetrics {
+    pub fn compute(graph: &StructuralGraph) -> Self {
+        let index = Indexed::new(graph);
+        Self {
+            betweenness: index.betweenness(),
+            articulation_points: index.articulation_points(),
+            strongly_connected_components: index.strongly_connected_components(),
+        }
+    }
+
+    pub fn max_betweenness(&self) -> f64 {
+        self.betweenness.values().copied().fold(0.0, f64::max)
+    }
+
+    /// Nodes at or above `threshold` betweenness, most central first.
+    pub fn bottlenecks(&self, threshold: f64) -> Vec<(NodeId, f64)> {
+        let mut out = self
+            .betweenness
+            .iter()
+            .filter(|(_, b)| **b >= threshold && **b > 0.0)
+            .map(|(id, b)| (*id, *b))
+            .collect::<Vec<_>>();
+        out.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        out
+    }
+
+    /// Components with more than one node, i.e. dependency cycles.
+    pub fn cycles(&self) -> impl Iterator<Item = &Vec<NodeId>> {
+        self.strongly_connected_components
+            .iter()
+            .filter(|c| c.len() > 1)
+    }
+
+    /// How much the design hangs on a few nodes, in `[0, 1]`: the mean of
+    /// the highest betweenness and the share of nodes that are articulation
+    /// points.
+    pub fn hub_risk(&self) -> f64 {
+        if self.betweenness.is_empty() {
+            return 0.0;
+        }
+        let articulation_share =
+            self.articulation_points.len() as f64 / self.betweenness.len() as f64;
+        (0.5 * self.max_betweenness() + 0.5 * articulation_share).clamp(0.0, 1.0)
+    }
+
+    /// Human-readable findings, ordered by node id; single points of
+    /// failure before bottlenecks.
+    pub fn annotate(
+        &self,
+        graph: &StructuralGraph,
+        bottleneck_threshold: f64,
+    ) -> Vec<NodeAnnotation> {
+        let label = |id: &NodeId| match graph.nodes().get(id) {
+            Some(node) => format!("{} {}", node.kind, id.as_u128()),
+            None => id.as_u128().to_string(),
+        };
+        let mut out = Vec::new();
+        for id in &self.articulation_points {
+            out.push(NodeAnnotation {
+                node: *id,
+                kind: AnnotationKind::SinglePointOfFailure,
+                message: format!(
+                    "{} is a single point of failure: removing it disconnects the design",
+                    label(id)
+                ),
+            });
+        }
+        for (id, betweenness) in self.bottlenecks(bottleneck_threshold) {
+            out.push(NodeAnnotation {
+                node: id,
+                kind: AnnotationKind::Bottleneck { betweenness },
+                message: format!(
+                    "{} is a bottleneck: {:.0}% of dependency paths pass through it",
+                    label(&id),
+                    betweenness * 100.0
+                ),
+            });
+        }
+        out.sort_by_key(|a| a.node);
+        out
+    }
+}
+
+/// Dense indices over the graph's nodes, in id order.
+struct Indexed {
+    ids: Vec<NodeId>,
+    out: Vec<Vec<usize>>,
+    undirected: Vec<Vec<usize>>,
+}
+
+impl Indexed {
+    fn new(graph: &StructuralGraph) -> Self {
+        let ids = graph.nodes().keys().copied().collect::<Vec<_>>();
+        let pos = ids
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (*id, i))
+            .collect::<BTreeMap<_, _>>();
+        let mut out = vec![Vec::new(); ids.len()];
+        let mut undirected = vec![BTreeSet::new(); ids.len()];
+        for (from, to) in graph.edges() {
+            let (Some(&f), Some(&t)) = (pos.get(from), pos.get(to)) else {
+                continue;
+            };
+            out[f].push(t);
+            undirected[f].insert(t);
+            undirected[t].insert(f);
+        }
+        Self {
+            ids,
+            out,
+            undirected: undirected
+                .into_iter()
+                .map(|s| s.into_iter().collect())
+                .collect(),
+        }
+    }
+
+    fn betweenness(&self) -> BTreeMap<NodeId, f64> {
+        let n = self.ids.len();
+        let mut centrality = vec![0.0; n];
+        for s in 0..n {
+            let mut stack = Vec::with_capacity(n);
+            let mut preds = vec![Vec::new(); n];
+            let mut sigma = vec![0.0; n];
+            let mut dist = vec![usize::MAX; n];
+            sigma[s] = 1.0;
+            dist[s] = 0;
+            let mut queue = VecDeque::from([s]);
+            while let Some(v) = queue.pop_front() {
+                stack.push(v);
+                for &w in &self.out[v] {
+                    if dist[w] == usize::MAX {
+                        dist[w] = dist[v] + 1;
+                        queue.push_back(w);
+                    }
+                    if dist[w] == dist[v] + 1 {
+                        sigma[w] += sigma[v];
+                        preds[w].push(v);
+                    }
+                }
+            }
+            let mut delta = vec![0.0; n];
+            while let Some(w) = stack.pop() {
+                for &v in &preds[w] {
+                    delta[v] += sigma[v] / sigma[w] * (1.0 + delta[w]);
+                }
+                if w != s {
+                    centrality[w] += delta[w];
+                }
+            }
+        }
+        let scale = if n > 2 {
+            1.0 / ((n - 1) * (n - 2)) as f64
+        } else {
+            0.0
+        };
+        self.ids
+            .iter()
+            .zip(centrality)
+            .map(|(id, c)| (*id, c * scale))
+            .collect()
+    }
+
+    fn articulation_points(&self) -> BTreeSet<NodeId> {
+        let n = self.ids.len();
+        let mut disc = vec![usize::MAX; n];
+        let mut low = vec![0; n];
+        let mut points = BTreeSet::new();
+        let mut time = 0;
+        for root in 0..n {
+            if disc[root] != usize::MAX {
+                continue;
+            }
+            disc[root] = time;
+            low[root] = time;
+            time += 1;
+            let mut root_children = 0;
+            // (node, parent, next neighbor position)
+            let mut stack = vec![(root, usize::MAX, 0usize)];
+            while let Some(&mut (v, parent, ref mut next)) = stack.last_mut() {
+                if let Some(&w) = self.undirected[v].get(*next) {
+                    *next += 1;
+                    if disc[w] == usize::MAX {
+                        disc[w] = time;
+                        low[w] = time;
+                        time += 1;
+                        if v == root {
+                            root_children += 1;
+                        }
+                        stack.push((w, v, 0));
+                    } else if w != parent {
+                        low[v] = low[v].min(disc[w]);
+                    }
+                    continue;
+                }
+                stack.pop();
+                if parent != usize::MAX {
+                    low[parent] = low[parent].min(low[v]);
+                    if parent != root && low[v] >= disc[parent] {
+                        points.insert(self.ids[parent]);
+                    }
+                }
+            }
+            if root_children > 1 {
+                points.insert(self.ids[root]);
+            }
+        }
+        points
+    }
+
+    /// Tarjan's algorithm, iterative.
+    fn strongly_connected_components(&self) -> Vec<Vec<NodeId>> {
+        let n = self.ids.len();
+        let mut index = vec![usize::MAX; n];
+        let mut low = vec![0; n];
+        let mut on_stack = vec![false; n];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        let mut counter = 0;
+        for root in 0..n {
+            if index[root] != usize::MAX {
+                continue;
+            }
+            let mut work = vec![(root, 0usize)];
+            index[root] = counter;
+            low[root] = counter;
+            counter += 1;
+            stack.push(root);
+            on_stack[root] = true;
+            while let Some(&mut (v, ref mut next)) = work.last_mut() {
+                if let Some(&w) = self.out[v].get(*next) {
+                    *next += 1;
+                    if index[w] == usize::MAX {
+                        index[w] = counter;
+                        low[w] = counter;
+                        counter += 1;
+                        stack.push(w);
+                        on_stack[w] = true;
+                        work.push((w, 0));
+                    } else if on_stack[w] {
+                        low[v] = low[v].min(index[w]);
+                    }
+                    continue;
+                }
+                work.pop();
+                if let Some(&(parent, _)) = work.last() {
+                    low[parent] = low[parent].min(low[v]);
+                }
+                if low[v] == index[v] {
+                    let mut component = Vec::new();
+                    while let Some(w) = stack.pop() {
+                        on_stack[w] = false;
+                        component.push(self.ids[w]);
+                        if w == v {
+                            break;
+                        }
+                    }
+                    component.sort();
+                    components.push(component);
+                }
+            }
+        }
+        components.sort();
+        components
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use crate::{AnnotationKind, DesignNode, GraphMetrics, StructuralGraph, Uuid};
+
+    fn id(n: u128) -> Uuid {
+        Uuid::from_u128(n)
+    }
+
+    fn graph(nodes: &[u128], edges: &[(u128, u128)]) -> StructuralGraph {
+        StructuralGraph::new(
+            nodes
+                .iter()
+                .map(|n| (id(*n), DesignNode::new(id(*n), "Service", BTreeMap::new())))
+                .collect(),
+            edges.iter().map(|(a, b)| (id(*a), id(*b))).collect(),
+        )
+    }
+
+    #[test]
+    fn chain_middle_is_the_bottleneck_and_cut_vertex() {
+        // 1 -> 2 -> 3, plus 2 -> 4
+        let g = graph(&[1, 2, 3, 4], &[(1, 2), (2, 3), (2, 4)]);
+        let metrics = GraphMetrics::compute(&g);
+
+        // Paths through 2: 1->3 and 1->4, out of (n-1)(n-2) = 6 ordered pairs.
+        assert!((metrics.betweenness[&id(2)] - 2.0 / 6.0).abs() < 1e-12);
+        assert_eq!(metrics.betweenness[&id(1)], 0.0);
+        assert_eq!(metrics.articulation_points, BTreeSet::from([id(2)]));
+        assert_eq!(metrics.bottlenecks(0.1), vec![(id(2), 2.0 / 6.0)]);
+        assert_eq!(metrics.cycles().count(), 0);
+        assert_eq!(metrics.strongly_connected_components.len(), 4);
+
+        let notes = metrics.annotate(&g, 0.1);
+        assert_eq!(notes.len(), 2);
+        assert!(notes.iter().all(|n| n.node == id(2)));
+        assert!(notes[0].message.contains("single point of failure"));
+    }
+
+    #[test]
+    fn redundant_paths_remove_cut_vertices() {
+        // Diamond 1 -> {2, 3} -> 4, then 4 -> 5.
+        let g = graph(&[1, 2, 3, 4, 5], &[(1, 2), (1, 3), (2, 4), (3, 4), (4, 5)]);
+        let metrics = GraphMetrics::compute(&g);
+        assert_eq!(metrics.articulation_points, BTreeSet::from([id(4)]));
+        assert_eq!(metrics.cycles().count(), 0);
+        assert_eq!(metrics.strongly_connected_components.len(), 5);
+        assert_eq!(metrics.bottlenecks(0.0)[0].0, id(4));
+        assert!(
+            metrics
+                .annotate(&g, 1.0)
+                .iter()
+                .all(|n| n.kind == AnnotationKind::SinglePointOfFailure)
+        );
+
+        let ring = graph(&[1, 2, 3], &[(1, 2), (2, 3), (1, 3)]);
+        let metrics = GraphMetrics::compute(&ring);
+        assert!(metrics.articulation_points.is_empty());
+        assert_eq!(metrics.hub_risk(), 0.0);
+        assert_eq!(
+            GraphMetrics::compute(&StructuralGraph::default()).hub_risk(),
+            0.0
+        );
+    }
+}
@@ -1,6 +1,7 @@
 use std::fs::{File, OpenOptions};
 use std::io::{self, Read, Seek, SeekFrom, Write};
 use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 use std::thread;
 use std::time::Duration;
 
@@ -18,10 +19,17 @@ pub struct MemoryEntry {
 
 #[derive(Debug)]
 pub struct HolographicVectorStore {
-    path: PathBuf,
+    backing: Backing,
     dimension: u32,
 }
 
+#[derive(Debug)]
+enum Backing {
+    File(PathBuf),
+    /// No filesystem access at all; used for wasm32 and throwaway sessions.
+    Memory(Mutex<Vec<MemoryEntry>>),
+}
+
 impl HolographicVectorStore {
     pub fn open(path: impl AsRef<Path>, dimension: u32) -> io::Result<Self> {
         let path = path.as_ref().to_path_buf();
@@ -38,11 +46,25 @@ impl HolographicVectorStore {
         } else {
             Self::validate_header(&mut file)?;
         }
-        Ok(Self { path, dimension })
+        Ok(Self {
+            backing: Backing::File(path),
+            dimension,
+        })
     }
 
-    pub fn path(&self) -> &Path {
-        &self.path
+    pub fn in_memory(dimension: u32) -> Self {
+        Self {
+            backing: Backing::Memory(Mutex::new(Vec::new())),
+            dimension,
+        }
+    }
+
+    /// `None` for in-memory stores.
+    pub fn path(&self) -> Option<&Path> {
+        match &self.backing {
+            Backing::File(path) => Some(path),
+            Backing::Memory(_) => None,
+        }
     }
 
     pub fn dimension(&self) -> u32 {
@@ -56,8 +78,15 @@ impl HolographicVectorStore {
                 "vector dimension mismatch",
             ));
         }
-        let _lock = FileLockGuard::acquire(&self.path)?;
-        let mut file = self.open_rw()?;
+        let path = match &self.backing {
+            Backing::File(path) => path,
+            Backing::Memory(entries) => {
+                lock_entries(entries)?.push(entry.clone());
+                return Ok(());
+            }
+        };
+        let _lock = FileLockGuard::acquire(path)?;
+        let mut file = Self::open_rw(path)?;
         let count = Self::read_count(&mut file)?;
         file.seek(SeekFrom::End(0))?;
         file.write_all(&entry.id.to_le_bytes())?;
@@ -72,8 +101,12 @@ impl HolographicVectorStore {
     }
 
     pub fn entries(&self) -> io::Result<Vec<MemoryEntry>> {
-        let _lock = FileLockGuard::acquire(&self.path)?;
-        let mut file = self.open_rw()?;
+        let path = match &self.backing {
+            Backing::File(path) => path,
+            Backing::Memory(entries) => return Ok(lock_entries(entries)?.clone()),
+        };
+        let _lock = FileLockGuard::acquire(path)?;
+        let mut file = Self::open_rw(path)?;
         Self::validate_header(&mut file)?;
         let count = Self::read_count(&mut file)? as usize;
         file.seek(SeekFrom::Start(HEADER_SIZE))?;
@@ -98,14 +131,18 @@ impl HolographicVectorStore {
     }
 
     pub fn entry_count(&self) -> io::Result<u64> {
-        let _lock = FileLockGuard::acquire(&self.path)?;
-        let mut file = self.open_rw()?;
+        let path = match &self.backing {
+            Backing::File(path) => path,
+            Backing::Memory(entries) => return Ok(lock_entries(entries)?.len() as u64),
+        };
+        let _lock = FileLockGuard::acquire(path)?;
+        let mut file = Self::open_rw(path)?;
         let count = Self::read_count(&mut file)?;
         Ok(count)
     }
 
-    fn open_rw(&self) -> io::Result<File> {
-        OpenOptions::new().read(true).write(true).open(&self.path)
+    fn open_rw(path: &Path) -> io::Result<File> {
+        OpenOptions::new().read(true).write(true).open(path)
     }
 
     fn validate_header(file: &mut File) -> io::Result<()> {
@@ -148,6 +185,14 @@ impl HolographicVectorStore {
     }
 }
 
+fn lock_entries(
+    entries: &Mutex<Vec<MemoryEntry>>,
+) -> io::Result<std::sync::MutexGuard<'_, Vec<MemoryEntry>>> {
+    entries
+        .lock()
+        .map_err(|_| io::Error::other("in-memory vector store poisoned"))
+}
+
 fn read_u32(file: &mut File) -> io::Result<u32> {
     let mut buf = [0u8; 4];
     file.read_exact(&mut buf)?;
@@ -228,4 +273,36 @@ mod tests {
         assert_eq!(items[0].vector.len(), 4);
         let _ = std::fs::remove_file(path);
     }
+
+    #[test]
+    fn in_memory_store_rejects_mismatched_dimension_and_keeps_order() {
+        let store = HolographicVectorStore::in_memory(2);
+        assert!(store.path().is_none());
+        let entry = |id| MemoryEntry {
+            id,
+            depth: 0,
+            timestamp: id,
+            vector: vec![0.5, 0.5],
+        };
+        store.append(&entry(1)).expect("append");
+        store.append(&entry(2)).expect("append");
+        assert!(
+            store
+                .append(&MemoryEntry {
+                    vector: vec![1.0],
+                    ..entry(3)
+                })
+                .is_err()
+        );
+        assert_eq!(store.entry_count().expect("count"), 2);
+        assert_eq!(
+            store
+                .entries()
+                .expect("entries")
+                .iter()
+                .map(|e| e.id)
+                .collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+    }
 }
@@ -0,0 +1,311 @@
+//! Stable interchange format for `DesignState`.
+//!
+//! A `DesignStateDocument` is the state plus where it came from, wrapped in
+//! a `core_types::Versioned` envelope. The JSON form is meant to be written
+//! by hand or by a GUI and fed straight into a search as its initial state,
+//! so import is strict: unknown fields, duplicate node ids, oversized
+//! attributes and broken DAG invariants are all rejected with a specific
+//! error rather than silently repaired. With the `binary` feature the same
+//! envelope can also be written as compact postcard bytes.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+
+use core_types::{SchemaMismatch, SchemaVersioned, Versioned};
+use serde::{Deserialize, Serialize};
+
+use crate::graph::{GraphViolation, StructuralGraph};
+use crate::node::DesignNode;
+use crate::state::DesignState;
+use crate::types::{NodeId, StateId, Value};
+
+/// Where an exported state came from.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StateProvenance {
+    /// Free-form origin tag such as `"gui"`, `"hand"` or `"beam_search"`.
+    #[serde(default)]
+    pub source: String,
+    #[serde(default)]
+    pub parent: Option<StateId>,
+    /// Seconds since the Unix epoch.
+    #[serde(default)]
+    pub created_at: Option<u64>,
+    #[serde(default)]
+    pub note: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct DesignStateDocument {
+    pub state: DesignState,
+    pub provenance: StateProvenance,
+}
+
+#[derive(Debug)]
+pub enum ImportError {
+    /// Malformed input, including unknown fields and badly formed ids.
+    Syntax(String),
+    Schema(SchemaMismatch),
+    DuplicateNode(NodeId),
+    InvalidGraph(Vec<GraphViolation>),
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Syntax(message) => write!(f, "malformed design state: {message}"),
+            Self::Schema(mismatch) => write!(f, "{mismatch}"),
+            Self::DuplicateNode(id) => write!(f, "node {id:?} is declared more than once"),
+            Self::InvalidGraph(violations) => {
+                write!(f, "invalid design graph: ")?;
+                for (i, violation) in violations.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{violation}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+impl From<SchemaMismatch> for ImportError {
+    fn from(mismatch: SchemaMismatch) -> Self {
+        Self::Schema(mismatch)
+    }
+}
+
+impl SchemaVersioned for DesignStateDocument {
+    const KIND: &'static str = "design_state_document";
+    const VERSION: u32 = 1;
+}
+
+/// Wire shape. Kept separate from `DesignState` so the format stays fixed
+/// when in-memory types grow, and so decoding can report structured errors.
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct DocumentRepr {
+    id: StateId,
+    #[serde(default)]
+    profile_snapshot: String,
+    #[serde(default)]
+    provenance: StateProvenance,
+    nodes: Vec<NodeRepr>,
+    #[serde(default)]
+    edges: Vec<(NodeId, NodeId)>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NodeRepr {
+    id: NodeId,
+    kind: String,
+    #[serde(default)]
+    attributes: BTreeMap<String, Value>,
+}
+
+impl SchemaVersioned for DocumentRepr {
+    const KIND: &'static str = DesignStateDocument::KIND;
+    const VERSION: u32 = DesignStateDocument::VERSION;
+}
+
+impl DesignStateDocument {
+    pub fn new(state: DesignState) -> Self {
+        Self {
+            state,
+            provenance: StateProvenance::default(),
+        }
+    }
+
+    pub fn with_provenance(mut self, provenance: StateProvenance) -> Self {
+        self.provenance = provenance;
+        self
+    }
+
+    pub fn into_state(self) -> DesignState {
+        self.state
+    }
+
+    /// Pretty-printed, deterministic JSON (nodes and edges in id order).
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(&Versioned::new(self.to_repr()))
+            .expect("design state document is always serializable")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, ImportError> {
+        let envelope: Versioned<DocumentRepr> =
+            serde_json::from_str(json).map_err(|e| ImportError::Syntax(e.to_string()))?;
+        Self::from_repr(envelope.into_checked()?)
+    }
+
+    #[cfg(feature = "binary")]
+    pub fn to_binary(&self) -> Vec<u8> {
+        postcard::to_allocvec(&Versioned::new(self.to_repr()))
+            .expect("design state document is always serializable")
+    }
+
+    #[cfg(feature = "binary")]
+    pub fn from_binary(bytes: &[u8]) -> Result<Self, ImportError> {
+        let envelope: Versioned<DocumentRepr> =
+            postcard::from_bytes(bytes).map_err(|e| ImportError::Syntax(e.to_string()))?;
+        Self::from_repr(envelope.into_checked()?)
+    }
+
+    fn to_repr(&self) -> DocumentRepr {
+        let graph = &self.state.graph;
+        DocumentRepr {
+            id: self.state.id,
+            profile_snapshot: self.state.profile_snapshot.clone(),
+            provenance: self.provenance.clone(),
+            nodes: graph
+                .nodes()
+                .values()
+                .map(|node| NodeRepr {
+                    id: node.id,
+                    kind: node.kind.clone(),
+                    attributes: node.attributes.clone(),
+                })
+                .collect(),
+            edges: graph.edges().iter().copied().collect(),
+        }
+    }
+
+    fn from_repr(repr: DocumentRepr) -> Result<Self, ImportError> {
+        let mut nodes = BTreeMap::new();
+        for node in repr.nodes {
+            let id = node.id;
+            if nodes
+                .insert(id, DesignNode::new(id, node.kind, node.attributes))
+                .is_some()
+            {
+                return Err(ImportError::DuplicateNode(id));
+            }
+        }
+        let edges: BTreeSet<(NodeId, NodeId)> = repr.edges.into_iter().collect();
+        let graph = StructuralGraph::try_new(nodes, edges).map_err(ImportError::InvalidGraph)?;
+        Ok(Self {
+            state: DesignState::new(repr.id, Arc::new(graph), repr.profile_snapshot),
+            provenance: repr.provenance,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use crate::{
+        DesignNode, DesignState, DesignStateDocument, GraphViolation, ImportError, StateProvenance,
+        StructuralGraph, Uuid, Value,
+    };
+
+    fn document() -> DesignStateDocument {
+        let attrs = BTreeMap::from([
+            ("label".to_string(), Value::Text("api".to_string())),
+            ("replicas".to_string(), Value::Int(2)),
+        ]);
+        let graph = StructuralGraph::default()
+            .with_node_added(DesignNode::new(Uuid::from_u128(1), "Interface", attrs))
+            .with_node_added(DesignNode::new(
+                Uuid::from_u128(2),
+                "Storage",
+                BTreeMap::new(),
+            ))
+            .with_edge_added(Uuid::from_u128(1), Uuid::from_u128(2));
+        DesignStateDocument::new(DesignState::new(
+            Uuid::from_u128(7),
+            Arc::new(graph),
+            "history:1",
+        ))
+        .with_provenance(StateProvenance {
+            source: "gui".to_string(),
+            parent: Some(Uuid::from_u128(6)),
+            created_at: Some(1_700_000_000),
+            note: None,
+        })
+    }
+
+    #[test]
+    fn json_round_trip_preserves_graph_attributes_and_provenance() {
+        let original = document();
+        let json = original.to_json();
+        assert!(json.contains("\"kind\": \"design_state_document\""));
+
+        let decoded = DesignStateDocument::from_json(&json).expect("import");
+        assert_eq!(decoded.state.id, original.state.id);
+        assert_eq!(decoded.state.graph, original.state.graph);
+        assert_eq!(decoded.state.profile_snapshot, "history:1");
+        assert_eq!(decoded.provenance, original.provenance);
+        assert_eq!(decoded.to_json(), json);
+    }
+
+    #[test]
+    fn hand_authored_json_with_defaults_is_accepted() {
+        let json = r#"{"kind":"design_state_document","version":1,"data":{
+            "id":"0a",
+            "nodes":[{"id":"01","kind":"Service"},
+                     {"id":"02","kind":"Storage","attributes":{"engine":{"Text":"sqlite"}}}],
+            "edges":[["01","02"]]}}"#;
+
+        let document = DesignStateDocument::from_json(json).expect("import");
+        assert_eq!(document.state.id, Uuid::from_u128(10));
+        assert_eq!(document.state.graph.nodes().len(), 2);
+        assert_eq!(document.provenance, StateProvenance::default());
+    }
+
+    #[test]
+    fn import_rejects_unknown_fields_duplicates_cycles_and_wrong_schema() {
+        let wrap =
+            |data: &str| format!(r#"{{"kind":"design_state_document","version":1,"data":{data}}}"#);
+
+        let unknown = wrap(r#"{"id":"01","nodes":[],"colour":"red"}"#);
+        assert!(matches!(
+            DesignStateDocument::from_json(&unknown),
+            Err(ImportError::Syntax(message)) if message.contains("colour")
+        ));
+
+        let duplicate =
+            wrap(r#"{"id":"01","nodes":[{"id":"02","kind":"A"},{"id":"02","kind":"B"}]}"#);
+        assert!(matches!(
+            DesignStateDocument::from_json(&duplicate),
+            Err(ImportError::DuplicateNode(id)) if id == Uuid::from_u128(2)
+        ));
+
+        let cyclic = wrap(
+            r#"{"id":"01","nodes":[{"id":"02","kind":"A"},{"id":"03","kind":"B"}],
+                "edges":[["02","03"],["03","02"]]}"#,
+        );
+        assert!(matches!(
+            DesignStateDocument::from_json(&cyclic),
+            Err(ImportError::InvalidGraph(violations)) if violations == vec![GraphViolation::Cycle]
+        ));
+
+        let stale = document()
+            .to_json()
+            .replace("\"version\": 1", "\"version\": 99");
+        assert!(matches!(
+            DesignStateDocument::from_json(&stale),
+            Err(ImportError::Schema(_))
+        ));
+    }
+
+    #[cfg(feature = "binary")]
+    #[test]
+    fn binary_round_trip_matches_json() {
+        let original = document();
+        let bytes = original.to_binary();
+        let decoded = DesignStateDocument::from_binary(&bytes).expect("import");
+
+        assert_eq!(decoded.to_json(), original.to_json());
+        assert!(bytes.len() < original.to_json().len());
+        assert!(matches!(
+            DesignStateDocument::from_binary(&bytes[..bytes.len() / 2]),
+            Err(ImportError::Syntax(_))
+        ));
+    }
+}
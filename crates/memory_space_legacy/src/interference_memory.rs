@@ -42,6 +42,7 @@ pub struct MemorySpace {
     stats_sum_delta: f64,
     stats_sum_hit_rate: f64,
     stats_count: usize,
+    last_lookup_hit: bool,
 }
 
 impl MemorySpace {
@@ -66,6 +67,7 @@ impl MemorySpace {
             stats_sum_delta: 0.0,
             stats_sum_hit_rate: 0.0,
             stats_count: 0,
+            last_lookup_hit: false,
         })
     }
 
@@ -79,9 +81,16 @@ impl MemorySpace {
         self.stats_sum_delta += step.delta_norm;
         self.stats_sum_hit_rate += step.hit_rate;
         self.stats_count = self.stats_count.saturating_add(1);
+        self.last_lookup_hit = step.hit_rate > 0.0;
         adjusted
     }
 
+    /// Whether the last `apply_interference` found a remembered objective
+    /// similar to its input. Always false while interference is disabled.
+    pub fn last_lookup_hit(&self) -> bool {
+        self.last_lookup_hit
+    }
+
     pub fn take_telemetry(&mut self) -> MemoryInterferenceTelemetry {
         if self.stats_count == 0 {
             return MemoryInterferenceTelemetry::default();
@@ -233,6 +242,19 @@ impl MemorySpace {
         }
     }
 
+    /// Entries held in memory for recall; the store keeps them all anyway.
+    pub fn cached_entries(&self) -> usize {
+        self.entries_cache.len()
+    }
+
+    /// Drops cached entries older than the recall window, which recall never
+    /// reads. Returns how many were dropped.
+    pub fn trim_cache(&mut self) -> usize {
+        let excess = self.entries_cache.len().saturating_sub(self.window);
+        self.entries_cache.drain(..excess);
+        excess
+    }
+
     fn recent_entries(&self) -> Vec<&MemoryEntry> {
         let len = self.entries_cache.len();
         if len == 0 {
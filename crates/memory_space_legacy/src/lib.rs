@@ -1,16 +1,37 @@
+pub mod aggregates;
+pub mod consistency;
+pub mod diagram;
+pub mod diff;
 pub mod exploration;
 pub mod graph;
+pub mod graph_metrics;
 pub mod holographic_store;
+#[cfg(feature = "serde")]
+pub mod interchange;
 pub mod interference_memory;
 pub mod node;
+pub mod schema;
 pub mod state;
 pub mod types;
 
+pub use aggregates::GraphAggregates;
+pub use consistency::{ConsistencyMismatch, ConsistencyReport, NodeMatching};
+pub use diagram::{
+    DiagramError, DiagramFormat, DiagramImport, parse_diagram, parse_dot, parse_mermaid,
+};
+pub use diff::GraphDiff;
 pub use exploration::ExplorationMemory;
-pub use graph::StructuralGraph;
+pub use graph::{GraphViolation, MAX_ATTRIBUTE_BYTES, MAX_NODE_ATTRIBUTES, StructuralGraph};
+pub use graph_metrics::{AnnotationKind, GraphMetrics, NodeAnnotation};
 pub use holographic_store::{HolographicVectorStore, MemoryEntry};
+#[cfg(feature = "serde")]
+pub use interchange::{DesignStateDocument, ImportError, StateProvenance};
 pub use interference_memory::{InterferenceMode, MemoryInterferenceTelemetry, MemorySpace};
 pub use node::DesignNode;
+pub use schema::{
+    AttributeSpec, AttributeType, EdgeKind, EdgeRule, GraphSchema, NodeType, SchemaViolation,
+    TypedGraph,
+};
 pub use state::DesignState;
 pub use types::{NodeId, StateId, Uuid, Value};
 
@@ -3,6 +3,7 @@ use std::collections::BTreeMap;
 use crate::types::{NodeId, Value};
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DesignNode {
     pub id: NodeId,
     pub kind: String,
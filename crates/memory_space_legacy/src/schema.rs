@@ -0,0 +1,582 @@
+//! Optional typed layer over `StructuralGraph`: a registry of node types with
+//! declared attributes, and typed edges carrying a payload. `TypedGraph`
+//! checks every mutation against its schema so generators can read typed
+//! components instead of sniffing kind strings.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::graph::{GraphViolation, StructuralGraph};
+use crate::node::DesignNode;
+use crate::types::{NodeId, Value};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AttributeType {
+    Int,
+    Float,
+    /// `Int` or `Float`.
+    Number,
+    Bool,
+    Text,
+}
+
+impl AttributeType {
+    pub fn accepts(&self, value: &Value) -> bool {
+        matches!(
+            (self, value),
+            (Self::Int, Value::Int(_))
+                | (Self::Float, Value::Float(_))
+                | (Self::Number, Value::Int(_) | Value::Float(_))
+                | (Self::Bool, Value::Bool(_))
+                | (Self::Text, Value::Text(_))
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AttributeSpec {
+    pub ty: AttributeType,
+    pub required: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NodeType {
+    pub kind: String,
+    pub attributes: BTreeMap<String, AttributeSpec>,
+    /// Accept attributes that are not declared in `attributes`.
+    pub open: bool,
+}
+
+impl NodeType {
+    pub fn new(kind: impl Into<String>) -> Self {
+        Self {
+            kind: kind.into(),
+            attributes: BTreeMap::new(),
+            open: false,
+        }
+    }
+
+    pub fn required(mut self, key: impl Into<String>, ty: AttributeType) -> Self {
+        self.attributes
+            .insert(key.into(), AttributeSpec { ty, required: true });
+        self
+    }
+
+    pub fn optional(mut self, key: impl Into<String>, ty: AttributeType) -> Self {
+        self.attributes.insert(
+            key.into(),
+            AttributeSpec {
+                ty,
+                required: false,
+            },
+        );
+        self
+    }
+
+    pub fn open(mut self) -> Self {
+        self.open = true;
+        self
+    }
+}
+
+/// Typed edge. Untyped graphs are read as all `Depends`.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EdgeKind {
+    #[default]
+    Depends,
+    Calls {
+        synchronous: bool,
+    },
+    DataFlow {
+        /// Message or record type carried along the edge.
+        payload: String,
+    },
+    Contains,
+}
+
+impl EdgeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Depends => "depends",
+            Self::Calls { .. } => "calls",
+            Self::DataFlow { .. } => "data_flow",
+            Self::Contains => "contains",
+        }
+    }
+}
+
+/// Permits edges named `edge` (see `EdgeKind::name`) from `from_kind` to
+/// `to_kind`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EdgeRule {
+    pub from_kind: String,
+    pub to_kind: String,
+    pub edge: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SchemaViolation {
+    UnknownKind {
+        node: NodeId,
+        kind: String,
+    },
+    MissingAttribute {
+        node: NodeId,
+        key: String,
+    },
+    UndeclaredAttribute {
+        node: NodeId,
+        key: String,
+    },
+    AttributeType {
+        node: NodeId,
+        key: String,
+        expected: AttributeType,
+    },
+    EdgeNotAllowed {
+        from: NodeId,
+        to: NodeId,
+        edge: &'static str,
+    },
+    UnknownNode(NodeId),
+    DuplicateNode(NodeId),
+    /// The underlying graph refused the mutation.
+    Structure(GraphViolation),
+}
+
+impl std::fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownKind { node, kind } => {
+                write!(f, "node {node:?} has unregistered kind {kind:?}")
+            }
+            Self::MissingAttribute { node, key } => {
+                write!(f, "node {node:?} is missing required attribute {key:?}")
+            }
+            Self::UndeclaredAttribute { node, key } => {
+                write!(f, "node {node:?} has undeclared attribute {key:?}")
+            }
+            Self::AttributeType {
+                node,
+                key,
+                expected,
+            } => write!(f, "attribute {key:?} on {node:?} must be {expected:?}"),
+            Self::EdgeNotAllowed { from, to, edge } => {
+                write!(f, "{edge} edge {from:?} -> {to:?} is not allowed")
+            }
+            Self::UnknownNode(id) => write!(f, "node {id:?} does not exist"),
+            Self::DuplicateNode(id) => write!(f, "node {id:?} already exists"),
+            Self::Structure(violation) => write!(f, "{violation}"),
+        }
+    }
+}
+
+impl std::error::Error for SchemaViolation {}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GraphSchema {
+    pub node_types: BTreeMap<String, NodeType>,
+    /// Empty means any edge between registered kinds is allowed.
+    pub edge_rules: Vec<EdgeRule>,
+    /// Accept nodes whose kind has no registered `NodeType`, unchecked.
+    pub allow_unknown_kinds: bool,
+}
+
+impl GraphSchema {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn register(mut self, node_type: NodeType) -> Self {
+        self.node_types.insert(node_type.kind.clone(), node_type);
+        self
+    }
+
+    pub fn allow_edge(
+        mut self,
+        from_kind: impl Into<String>,
+        to_kind: impl Into<String>,
+        edge: &EdgeKind,
+    ) -> Self {
+        self.edge_rules.push(EdgeRule {
+            from_kind: from_kind.into(),
+            to_kind: to_kind.into(),
+            edge: edge.name().to_string(),
+        });
+        self
+    }
+
+    pub fn allow_unknown_kinds(mut self) -> Self {
+        self.allow_unknown_kinds = true;
+        self
+    }
+
+    pub fn node_type(&self, kind: &str) -> Option<&NodeType> {
+        self.node_types.get(kind)
+    }
+
+    pub fn check_node(&self, node: &DesignNode) -> Result<(), Vec<SchemaViolation>> {
+        let Some(node_type) = self.node_types.get(&node.kind) else {
+            return if self.allow_unknown_kinds {
+                Ok(())
+            } else {
+                Err(vec![SchemaViolation::UnknownKind {
+                    node: node.id,
+                    kind: node.kind.clone(),
+                }])
+            };
+        };
+
+        let mut violations = Vec::new();
+        for (key, spec) in &node_type.attributes {
+            match node.attributes.get(key) {
+                None if spec.required => violations.push(SchemaViolation::MissingAttribute {
+                    node: node.id,
+                    key: key.clone(),
+                }),
+                Some(value) if !spec.ty.accepts(value) => {
+                    violations.push(SchemaViolation::AttributeType {
+                        node: node.id,
+                        key: key.clone(),
+                        expected: spec.ty,
+                    })
+                }
+                _ => {}
+            }
+        }
+        if !node_type.open {
+            for key in node.attributes.keys() {
+                if !node_type.attributes.contains_key(key) {
+                    violations.push(SchemaViolation::UndeclaredAttribute {
+                        node: node.id,
+                        key: key.clone(),
+                    });
+                }
+            }
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    pub fn check_edge(
+        &self,
+        from: &DesignNode,
+        to: &DesignNode,
+        edge: &EdgeKind,
+    ) -> Result<(), SchemaViolation> {
+        let allowed = self.edge_rules.is_empty()
+            || self.edge_rules.iter().any(|rule| {
+                rule.from_kind == from.kind && rule.to_kind == to.kind && rule.edge == edge.name()
+            });
+        if allowed {
+            Ok(())
+        } else {
+            Err(SchemaViolation::EdgeNotAllowed {
+                from: from.id,
+                to: to.id,
+                edge: edge.name(),
+            })
+        }
+    }
+}
+
+/// A `StructuralGraph` whose nodes and edges are checked against a schema on
+/// every mutation. Mutators return a new graph, like `StructuralGraph`'s.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TypedGraph {
+    graph: StructuralGraph,
+    edge_kinds: BTreeMap<(NodeId, NodeId), EdgeKind>,
+    schema: Arc<GraphSchema>,
+}
+
+impl TypedGraph {
+    pub fn new(schema: Arc<GraphSchema>) -> Self {
+        Self {
+            graph: StructuralGraph::default(),
+            edge_kinds: BTreeMap::new(),
+            schema,
+        }
+    }
+
+    /// Adopts an untyped graph, reading every edge as `EdgeKind::Depends`.
+    pub fn from_graph(
+        schema: Arc<GraphSchema>,
+        graph: StructuralGraph,
+    ) -> Result<Self, Vec<SchemaViolation>> {
+        let edge_kinds = graph
+            .edges()
+            .iter()
+            .map(|edge| (*edge, EdgeKind::Depends))
+            .collect();
+        let typed = Self {
+            graph,
+            edge_kinds,
+            schema,
+        };
+        typed.validate().map(|()| typed)
+    }
+
+    pub fn graph(&self) -> &StructuralGraph {
+        &self.graph
+    }
+
+    pub fn into_graph(self) -> StructuralGraph {
+        self.graph
+    }
+
+    pub fn schema(&self) -> &GraphSchema {
+        &self.schema
+    }
+
+    pub fn edge_kind(&self, from: NodeId, to: NodeId) -> Option<&EdgeKind> {
+        self.edge_kinds.get(&(from, to))
+    }
+
+    pub fn typed_edges(&self) -> impl Iterator<Item = (NodeId, NodeId, &EdgeKind)> {
+        self.edge_kinds
+            .iter()
+            .map(|((from, to), kind)| (*from, *to, kind))
+    }
+
+    pub fn node_type(&self, id: NodeId) -> Option<&NodeType> {
+        let node = self.graph.nodes().get(&id)?;
+        self.schema.node_type(&node.kind)
+    }
+
+    /// Rejects duplicate ids as well as schema violations.
+    pub fn try_add_node(&self, node: DesignNode) -> Result<Self, Vec<SchemaViolation>> {
+        if self.graph.nodes().contains_key(&node.id) {
+            return Err(vec![SchemaViolation::DuplicateNode(node.id)]);
+        }
+        self.schema.check_node(&node)?;
+        Ok(Self {
+            graph: self.graph.with_node_added(node),
+            ..self.clone()
+        })
+    }
+
+    pub fn try_add_edge(
+        &self,
+        from: NodeId,
+        to: NodeId,
+        kind: EdgeKind,
+    ) -> Result<Self, SchemaViolation> {
+        if from == to {
+            return Err(SchemaViolation::Structure(GraphViolation::SelfLoop(from)));
+        }
+        let (Some(from_node), Some(to_node)) =
+            (self.graph.nodes().get(&from), self.graph.nodes().get(&to))
+        else {
+            return Err(SchemaViolation::Structure(GraphViolation::DanglingEdge {
+                from,
+                to,
+            }));
+        };
+        self.schema.check_edge(from_node, to_node, &kind)?;
+
+        let graph = self.graph.with_edge_added(from, to);
+        if !graph.edges().contains(&(from, to)) {
+            return Err(SchemaViolation::Structure(GraphViolation::Cycle));
+        }
+        let mut edge_kinds = self.edge_kinds.clone();
+        edge_kinds.insert((from, to), kind);
+        Ok(Self {
+            graph,
+            edge_kinds,
+            schema: Arc::clone(&self.schema),
+        })
+    }
+
+    pub fn try_set_attribute(
+        &self,
+        id: NodeId,
+        key: impl Into<String>,
+        value: Value,
+    ) -> Result<Self, Vec<SchemaViolation>> {
+        let Some(node) = self.graph.nodes().get(&id) else {
+            return Err(vec![SchemaViolation::UnknownNode(id)]);
+        };
+        let mut node = node.clone();
+        node.attributes.insert(key.into(), value);
+        self.schema.check_node(&node)?;
+
+        let mut nodes = self.graph.nodes().clone();
+        nodes.insert(id, node);
+        Ok(Self {
+            graph: StructuralGraph::new(nodes, self.graph.edges().clone()),
+            ..self.clone()
+        })
+    }
+
+    pub fn with_node_removed(&self, id: NodeId) -> Self {
+        let mut edge_kinds = self.edge_kinds.clone();
+        edge_kinds.retain(|(from, to), _| *from != id && *to != id);
+        Self {
+            graph: self.graph.with_node_removed(id),
+            edge_kinds,
+            schema: Arc::clone(&self.schema),
+        }
+    }
+
+    pub fn with_edge_removed(&self, from: NodeId, to: NodeId) -> Self {
+        let mut edge_kinds = self.edge_kinds.clone();
+        edge_kinds.remove(&(from, to));
+        Self {
+            graph: self.graph.with_edge_removed(from, to),
+            edge_kinds,
+            schema: Arc::clone(&self.schema),
+        }
+    }
+
+    /// Full re-check: structural invariants, every node, and every edge.
+    pub fn validate(&self) -> Result<(), Vec<SchemaViolation>> {
+        let mut violations = match self.graph.validate() {
+            Ok(()) => Vec::new(),
+            Err(structural) => structural
+                .into_iter()
+                .map(SchemaViolation::Structure)
+                .collect(),
+        };
+        for node in self.graph.nodes().values() {
+            if let Err(found) = self.schema.check_node(node) {
+                violations.extend(found);
+            }
+        }
+        for (from, to) in self.graph.edges() {
+            let (Some(from_node), Some(to_node)) =
+                (self.graph.nodes().get(from), self.graph.nodes().get(to))
+            else {
+                continue;
+            };
+            let kind = self
+                .edge_kinds
+                .get(&(*from, *to))
+                .cloned()
+                .unwrap_or_default();
+            if let Err(violation) = self.schema.check_edge(from_node, to_node, &kind) {
+                violations.push(violation);
+            }
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::types::Uuid;
+
+    fn schema() -> Arc<GraphSchema> {
+        let calls = EdgeKind::Calls { synchronous: true };
+        let flow = EdgeKind::DataFlow {
+            payload: String::new(),
+        };
+        Arc::new(
+            GraphSchema::new()
+                .register(
+                    NodeType::new("Service")
+                        .required("port", AttributeType::Int)
+                        .optional("replicas", AttributeType::Int),
+                )
+                .register(NodeType::new("Database").required("engine", AttributeType::Text))
+                .allow_edge("Service", "Service", &calls)
+                .allow_edge("Service", "Database", &flow),
+        )
+    }
+
+    fn node(id: u128, kind: &str, attrs: &[(&str, Value)]) -> DesignNode {
+        let attributes = attrs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.clone()))
+            .collect::<BTreeMap<_, _>>();
+        DesignNode::new(Uuid::from_u128(id), kind, attributes)
+    }
+
+    #[test]
+    fn mutations_are_checked_against_the_schema() {
+        let api = node(1, "Service", &[("port", Value::Int(80))]);
+        let db = node(2, "Database", &[("engine", Value::Text("pg".into()))]);
+        let graph = TypedGraph::new(schema())
+            .try_add_node(api)
+            .and_then(|g| g.try_add_node(db))
+            .expect("valid nodes");
+
+        let bad_port = node(3, "Service", &[("port", Value::Text("80".into()))]);
+        assert_eq!(
+            graph.try_add_node(bad_port).err(),
+            Some(vec![SchemaViolation::AttributeType {
+                node: Uuid::from_u128(3),
+                key: "port".to_string(),
+                expected: AttributeType::Int,
+            }])
+        );
+        let untyped = node(4, "Queue", &[]);
+        assert!(matches!(
+            graph.try_add_node(untyped).err().as_deref(),
+            Some([SchemaViolation::UnknownKind { .. }])
+        ));
+
+        let flow = EdgeKind::DataFlow {
+            payload: "Order".to_string(),
+        };
+        let wired = graph
+            .try_add_edge(Uuid::from_u128(1), Uuid::from_u128(2), flow.clone())
+            .expect("service -> database data flow is allowed");
+        assert_eq!(
+            wired.edge_kind(Uuid::from_u128(1), Uuid::from_u128(2)),
+            Some(&flow)
+        );
+        assert!(matches!(
+            graph.try_add_edge(Uuid::from_u128(2), Uuid::from_u128(1), EdgeKind::Contains),
+            Err(SchemaViolation::EdgeNotAllowed {
+                edge: "contains",
+                ..
+            })
+        ));
+        assert!(
+            wired
+                .try_set_attribute(Uuid::from_u128(1), "debug", Value::Bool(true))
+                .is_err()
+        );
+        assert!(wired.validate().is_ok());
+    }
+
+    #[test]
+    fn adopting_an_untyped_graph_reports_every_violation() {
+        let graph = StructuralGraph::default()
+            .with_node_added(node(1, "Service", &[]))
+            .with_node_added(node(2, "Database", &[("engine", Value::Int(1))]))
+            .with_edge_added(Uuid::from_u128(1), Uuid::from_u128(2));
+
+        let violations = TypedGraph::from_graph(schema(), graph.clone()).expect_err("invalid");
+        assert_eq!(violations.len(), 3);
+        assert!(violations.contains(&SchemaViolation::EdgeNotAllowed {
+            from: Uuid::from_u128(1),
+            to: Uuid::from_u128(2),
+            edge: "depends",
+        }));
+
+        let permissive = Arc::new(GraphSchema::new().allow_unknown_kinds());
+        let typed = TypedGraph::from_graph(permissive, graph).expect("no types registered");
+        assert_eq!(
+            typed.typed_edges().map(|(_, _, k)| k).collect::<Vec<_>>(),
+            vec![&EdgeKind::Depends]
+        );
+    }
+}
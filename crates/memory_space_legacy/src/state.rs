@@ -4,12 +4,19 @@ use crate::graph::StructuralGraph;
 use crate::types::StateId;
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DesignState {
     pub id: StateId,
     pub graph: Arc<StructuralGraph>,
     pub profile_snapshot: String,
 }
 
+#[cfg(feature = "serde")]
+impl core_types::SchemaVersioned for DesignState {
+    const KIND: &'static str = "design_state";
+    const VERSION: u32 = 1;
+}
+
 impl DesignState {
     pub fn new(
         id: StateId,
@@ -49,4 +56,45 @@ mod tests {
 
         assert!(Arc::ptr_eq(&state.graph, &cloned.graph));
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn design_state_round_trips_through_versioned_json() {
+        use std::collections::BTreeMap;
+
+        use core_types::Versioned;
+
+        use crate::{DesignNode, Value};
+
+        let mut attrs = BTreeMap::new();
+        attrs.insert("weight".to_string(), Value::Float(0.5));
+        attrs.insert("label".to_string(), Value::Text("api".to_string()));
+        let graph = StructuralGraph::default()
+            .with_node_added(DesignNode::new(Uuid::from_u128(1), "Interface", attrs))
+            .with_node_added(DesignNode::new(
+                Uuid::from_u128(2),
+                "Storage",
+                BTreeMap::new(),
+            ))
+            .with_edge_added(Uuid::from_u128(1), Uuid::from_u128(2));
+        let state = DesignState::new(Uuid::from_u128(7), Arc::new(graph), "history:1001");
+
+        let json = serde_json::to_string(&Versioned::new(state.clone())).expect("serialize");
+        assert!(json.contains("\"id\":\"00000000000000000000000000000007\""));
+        let decoded: Versioned<DesignState> = serde_json::from_str(&json).expect("parse");
+        let decoded = decoded.into_checked().expect("schema");
+        assert_eq!(decoded.id, state.id);
+        assert_eq!(decoded.graph, state.graph);
+        assert_eq!(decoded.profile_snapshot, state.profile_snapshot);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn cyclic_graph_is_rejected_on_decode() {
+        let json = r#"{"nodes":[
+            {"id":"01","kind":"A","attributes":{}},
+            {"id":"02","kind":"B","attributes":{}}],
+            "edges":[["01","02"],["02","01"]]}"#;
+        assert!(serde_json::from_str::<StructuralGraph>(json).is_err());
+    }
 }
@@ -6,8 +6,59 @@ use std::path::{Path, PathBuf};
 use std::sync::RwLock;
 
 pub trait Codec: Sized {
+    /// Layout version `encode` writes, recorded in the `FileStore` header.
+    /// Bump it whenever the layout changes and register the upgrade from
+    /// the previous version in `migrations`.
+    const FORMAT_VERSION: u32 = 1;
+
     fn encode(&self) -> Vec<u8>;
     fn decode(bytes: &[u8]) -> io::Result<Self>;
+
+    /// Upgrades from every older `FORMAT_VERSION` to the current one.
+    fn migrations() -> Migrations {
+        Migrations::new()
+    }
+}
+
+/// Rewrites one record from version `n` to `n + 1`.
+pub type MigrationStep = fn(&[u8]) -> io::Result<Vec<u8>>;
+
+/// Per-type chain of `MigrationStep`s. Files written before the header
+/// existed are version 0; unless a step from 0 is registered they are taken
+/// to already hold version 1 records.
+#[derive(Clone, Debug, Default)]
+pub struct Migrations {
+    steps: BTreeMap<u32, MigrationStep>,
+}
+
+impl Migrations {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers the upgrade from `from` to `from + 1`.
+    pub fn step(mut self, from: u32, step: MigrationStep) -> Self {
+        self.steps.insert(from, step);
+        self
+    }
+
+    /// Runs every step from `from` up to `to`.
+    pub fn upgrade(&self, from: u32, to: u32, bytes: &[u8]) -> io::Result<Vec<u8>> {
+        let mut out = bytes.to_vec();
+        for version in from..to {
+            out = match self.steps.get(&version) {
+                Some(step) => step(&out)?,
+                None if version == 0 => out,
+                None => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("no migration from format version {version}"),
+                    ));
+                }
+            };
+        }
+        Ok(out)
+    }
 }
 
 impl Codec for String {
@@ -31,6 +82,23 @@ impl Codec for Vec<u8> {
     }
 }
 
+/// Approximate footprint of a store's contents.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StoreUsage {
+    pub entries: usize,
+    /// Encoded key and value bytes, without any framing.
+    pub encoded_bytes: usize,
+}
+
+impl StoreUsage {
+    fn of<'a, K: Codec + 'a, V: Codec + 'a>(entries: impl Iterator<Item = (&'a K, &'a V)>) -> Self {
+        entries.fold(Self::default(), |usage, (k, v)| Self {
+            entries: usage.entries + 1,
+            encoded_bytes: usage.encoded_bytes + k.encode().len() + v.encode().len(),
+        })
+    }
+}
+
 pub trait Store<K, V>: Send + Sync
 where
     K: Clone + Ord + Codec,
@@ -40,6 +108,27 @@ where
     fn get(&self, key: &K) -> io::Result<Option<V>>;
     fn entries(&self) -> io::Result<Vec<(K, V)>>;
     fn replace_all(&self, entries: Vec<(K, V)>) -> io::Result<()>;
+
+    /// Drops `removes`, then writes `puts`, as one write. Missing keys are
+    /// ignored.
+    fn apply_batch(&self, puts: Vec<(K, V)>, removes: &[K]) -> io::Result<()> {
+        let mut map = self.entries()?.into_iter().collect::<BTreeMap<_, _>>();
+        apply_to_map(&mut map, puts, removes);
+        self.replace_all(map.into_iter().collect())
+    }
+
+    /// Encodes every entry, so it costs as much as a full write.
+    fn usage(&self) -> io::Result<StoreUsage> {
+        let entries = self.entries()?;
+        Ok(StoreUsage::of(entries.iter().map(|(k, v)| (k, v))))
+    }
+}
+
+fn apply_to_map<K: Ord, V>(map: &mut BTreeMap<K, V>, puts: Vec<(K, V)>, removes: &[K]) {
+    for key in removes {
+        map.remove(key);
+    }
+    map.extend(puts);
 }
 
 #[derive(Debug, Default)]
@@ -104,6 +193,38 @@ where
         }
         Ok(())
     }
+
+    fn apply_batch(&self, puts: Vec<(K, V)>, removes: &[K]) -> io::Result<()> {
+        let mut guard = self
+            .inner
+            .write()
+            .map_err(|_| io::Error::other("in-memory store poisoned"))?;
+        apply_to_map(&mut guard, puts, removes);
+        Ok(())
+    }
+
+    fn usage(&self) -> io::Result<StoreUsage> {
+        let guard = self
+            .inner
+            .read()
+            .map_err(|_| io::Error::other("in-memory store poisoned"))?;
+        Ok(StoreUsage::of(guard.iter()))
+    }
+}
+
+/// Leading bytes of a `FileStore` file, followed by the key and value
+/// format versions. Files without it predate versioning.
+pub const STORE_MAGIC: [u8; 4] = *b"DBMS";
+
+/// What `FileStore::open` did to bring an older file up to date.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StoreMigration {
+    /// Key and value format versions found on disk; 0 for a headerless file.
+    pub from: (u32, u32),
+    pub to: (u32, u32),
+    pub records: usize,
+    /// Untouched copy of the file as it was before the upgrade.
+    pub backup: PathBuf,
 }
 
 #[derive(Debug)]
@@ -113,6 +234,7 @@ where
     V: Clone + Codec,
 {
     path: PathBuf,
+    migration: Option<StoreMigration>,
     _marker: PhantomData<(K, V)>,
 }
 
@@ -121,66 +243,91 @@ where
     K: Clone + Ord + Codec,
     V: Clone + Codec,
 {
+    /// Opens or creates the store at `path`. A file written with older
+    /// format versions is upgraded through `Codec::migrations` and rewritten,
+    /// after copying the original to `<path>.v<key>-<value>.bak`.
     pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
         let path = path.as_ref().to_path_buf();
-        if !path.exists() {
-            let mut file = OpenOptions::new()
-                .create(true)
-                .write(true)
-                .truncate(true)
-                .open(&path)?;
-            file.write_all(&0u64.to_le_bytes())?;
-        }
-        Ok(Self {
+        let mut store = Self {
             path,
+            migration: None,
             _marker: PhantomData,
-        })
+        };
+        if !store.path.exists() {
+            store.write_map(&BTreeMap::new())?;
+            return Ok(store);
+        }
+        let raw = std::fs::read(&store.path)?;
+        let (from, body) = split_header(&raw);
+        let to = (K::FORMAT_VERSION, V::FORMAT_VERSION);
+        if from == to {
+            return Ok(store);
+        }
+        if from.0 > to.0 || from.1 > to.1 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "store format {}-{} is newer than supported {}-{}",
+                    from.0, from.1, to.0, to.1
+                ),
+            ));
+        }
+        // Upgrade everything in memory first so a failed step leaves the
+        // file as it was.
+        let (key_steps, value_steps) = (K::migrations(), V::migrations());
+        let mut map = BTreeMap::new();
+        for (key, value) in read_records(body)? {
+            let key = K::decode(&key_steps.upgrade(from.0, to.0, key)?)?;
+            let value = V::decode(&value_steps.upgrade(from.1, to.1, value)?)?;
+            map.insert(key, value);
+        }
+        let backup = backup_path(&store.path, from);
+        std::fs::copy(&store.path, &backup)?;
+        store.write_map(&map)?;
+        store.migration = Some(StoreMigration {
+            from,
+            to,
+            records: map.len(),
+            backup,
+        });
+        Ok(store)
     }
 
     pub fn path(&self) -> &Path {
         &self.path
     }
 
+    /// Set when `open` upgraded the file.
+    pub fn migration(&self) -> Option<&StoreMigration> {
+        self.migration.as_ref()
+    }
+
     fn read_map(&self) -> io::Result<BTreeMap<K, V>> {
         let mut file = OpenOptions::new().read(true).open(&self.path)?;
         let mut raw = Vec::new();
         file.read_to_end(&mut raw)?;
-        if raw.len() < 8 {
-            return Ok(BTreeMap::new());
+        let (version, body) = split_header(&raw);
+        if version != (K::FORMAT_VERSION, V::FORMAT_VERSION) && body.len() >= 8 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "store format {}-{} changed after open",
+                    version.0, version.1
+                ),
+            ));
         }
-        let mut idx = 0usize;
-        let count = read_u64(&raw, &mut idx)? as usize;
         let mut out = BTreeMap::new();
-        for _ in 0..count {
-            let k_len = read_u32(&raw, &mut idx)? as usize;
-            let key_end = idx.saturating_add(k_len);
-            if key_end > raw.len() {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "corrupt key length",
-                ));
-            }
-            let key = K::decode(&raw[idx..key_end])?;
-            idx = key_end;
-
-            let v_len = read_u32(&raw, &mut idx)? as usize;
-            let value_end = idx.saturating_add(v_len);
-            if value_end > raw.len() {
-                return Err(io::Error::new(
-                    io::ErrorKind::InvalidData,
-                    "corrupt value length",
-                ));
-            }
-            let value = V::decode(&raw[idx..value_end])?;
-            idx = value_end;
-
-            out.insert(key, value);
+        for (key, value) in read_records(body)? {
+            out.insert(K::decode(key)?, V::decode(value)?);
         }
         Ok(out)
     }
 
     fn write_map(&self, map: &BTreeMap<K, V>) -> io::Result<()> {
         let mut encoded = Vec::new();
+        encoded.extend_from_slice(&STORE_MAGIC);
+        encoded.extend_from_slice(&K::FORMAT_VERSION.to_le_bytes());
+        encoded.extend_from_slice(&V::FORMAT_VERSION.to_le_bytes());
         encoded.extend_from_slice(&(map.len() as u64).to_le_bytes());
         for (k, v) in map {
             let kb = k.encode();
@@ -202,6 +349,57 @@ where
     }
 }
 
+/// Key and value format versions and the record section of a store file.
+fn split_header(raw: &[u8]) -> ((u32, u32), &[u8]) {
+    if raw.len() < 12 || raw[..4] != STORE_MAGIC {
+        return ((0, 0), raw);
+    }
+    let mut idx = 4usize;
+    let key = read_u32(raw, &mut idx).unwrap_or(0);
+    let value = read_u32(raw, &mut idx).unwrap_or(0);
+    ((key, value), &raw[idx..])
+}
+
+/// Raw key and value bytes of every record.
+fn read_records(body: &[u8]) -> io::Result<Vec<(&[u8], &[u8])>> {
+    if body.len() < 8 {
+        return Ok(Vec::new());
+    }
+    let mut idx = 0usize;
+    let count = read_u64(body, &mut idx)? as usize;
+    let mut out = Vec::new();
+    for _ in 0..count {
+        let k_len = read_u32(body, &mut idx)? as usize;
+        let key_end = idx.saturating_add(k_len);
+        if key_end > body.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "corrupt key length",
+            ));
+        }
+        let key = &body[idx..key_end];
+        idx = key_end;
+
+        let v_len = read_u32(body, &mut idx)? as usize;
+        let value_end = idx.saturating_add(v_len);
+        if value_end > body.len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "corrupt value length",
+            ));
+        }
+        out.push((key, &body[idx..value_end]));
+        idx = value_end;
+    }
+    Ok(out)
+}
+
+fn backup_path(path: &Path, (key, value): (u32, u32)) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(format!(".v{key}-{value}.bak"));
+    PathBuf::from(name)
+}
+
 impl<K, V> Store<K, V> for FileStore<K, V>
 where
     K: Clone + Ord + Codec + Send + Sync + 'static,
@@ -227,6 +425,100 @@ where
         let map = entries.into_iter().collect::<BTreeMap<_, _>>();
         self.write_map(&map)
     }
+
+    fn apply_batch(&self, puts: Vec<(K, V)>, removes: &[K]) -> io::Result<()> {
+        let mut map = self.read_map()?;
+        apply_to_map(&mut map, puts, removes);
+        self.write_map(&map)
+    }
+}
+
+/// Either backing chosen at runtime, so callers that hold a concrete store
+/// type can still run without a filesystem (e.g. on wasm32).
+#[derive(Debug)]
+pub enum BackedStore<K, V>
+where
+    K: Clone + Ord + Codec,
+    V: Clone + Codec,
+{
+    Memory(InMemoryStore<K, V>),
+    File(FileStore<K, V>),
+}
+
+impl<K, V> BackedStore<K, V>
+where
+    K: Clone + Ord + Codec,
+    V: Clone + Codec,
+{
+    pub fn in_memory() -> Self {
+        Self::Memory(InMemoryStore::new())
+    }
+
+    pub fn open_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        FileStore::open(path).map(Self::File)
+    }
+
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::Memory(_) => None,
+            Self::File(store) => Some(store.path()),
+        }
+    }
+
+    pub fn migration(&self) -> Option<&StoreMigration> {
+        match self {
+            Self::Memory(_) => None,
+            Self::File(store) => store.migration(),
+        }
+    }
+}
+
+impl<K, V> Store<K, V> for BackedStore<K, V>
+where
+    K: Clone + Ord + Codec + Send + Sync + 'static,
+    V: Clone + Codec + Send + Sync + 'static,
+{
+    fn put(&self, key: K, value: V) -> io::Result<()> {
+        match self {
+            Self::Memory(store) => store.put(key, value),
+            Self::File(store) => store.put(key, value),
+        }
+    }
+
+    fn get(&self, key: &K) -> io::Result<Option<V>> {
+        match self {
+            Self::Memory(store) => store.get(key),
+            Self::File(store) => store.get(key),
+        }
+    }
+
+    fn entries(&self) -> io::Result<Vec<(K, V)>> {
+        match self {
+            Self::Memory(store) => store.entries(),
+            Self::File(store) => store.entries(),
+        }
+    }
+
+    fn replace_all(&self, entries: Vec<(K, V)>) -> io::Result<()> {
+        match self {
+            Self::Memory(store) => store.replace_all(entries),
+            Self::File(store) => store.replace_all(entries),
+        }
+    }
+
+    fn apply_batch(&self, puts: Vec<(K, V)>, removes: &[K]) -> io::Result<()> {
+        match self {
+            Self::Memory(store) => store.apply_batch(puts, removes),
+            Self::File(store) => store.apply_batch(puts, removes),
+        }
+    }
+
+    fn usage(&self) -> io::Result<StoreUsage> {
+        match self {
+            Self::Memory(store) => store.usage(),
+            Self::File(store) => store.usage(),
+        }
+    }
 }
 
 fn read_u32(raw: &[u8], idx: &mut usize) -> io::Result<u32> {
@@ -253,7 +545,42 @@ fn read_u64(raw: &[u8], idx: &mut usize) -> io::Result<u64> {
 mod tests {
     use std::time::{SystemTime, UNIX_EPOCH};
 
-    use super::{FileStore, InMemoryStore, Store};
+    use std::io;
+
+    use super::{BackedStore, Codec, FileStore, InMemoryStore, Migrations, STORE_MAGIC, Store};
+
+    fn temp_path(label: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!(
+            "memory_store_{label}_{}.bin",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("clock")
+                .as_nanos()
+        ))
+    }
+
+    /// Version 2 appends a `!` that version 1 records lack.
+    #[derive(Clone, Debug, PartialEq)]
+    struct Shout(String);
+
+    impl Codec for Shout {
+        const FORMAT_VERSION: u32 = 2;
+
+        fn encode(&self) -> Vec<u8> {
+            format!("{}!", self.0).into_bytes()
+        }
+
+        fn decode(bytes: &[u8]) -> io::Result<Self> {
+            let text = String::decode(bytes)?;
+            text.strip_suffix('!')
+                .map(|t| Self(t.to_string()))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "missing !"))
+        }
+
+        fn migrations() -> Migrations {
+            Migrations::new().step(1, |bytes| Ok([bytes, b"!"].concat()))
+        }
+    }
 
     #[test]
     fn in_memory_store_roundtrip() {
@@ -287,4 +614,101 @@ mod tests {
         }
         let _ = std::fs::remove_file(path);
     }
+
+    #[test]
+    fn apply_batch_removes_then_puts_in_one_write() {
+        let path = std::env::temp_dir().join(format!(
+            "memory_store_batch_{}.bin",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("clock")
+                .as_nanos()
+        ));
+        let file = FileStore::<String, String>::open(&path).expect("open");
+        let memory = InMemoryStore::<String, String>::new();
+        for store in [&file as &dyn Store<String, String>, &memory] {
+            store.put("a".to_string(), "1".to_string()).expect("put");
+            store.put("b".to_string(), "2".to_string()).expect("put");
+            store
+                .apply_batch(
+                    vec![
+                        ("b".to_string(), "3".to_string()),
+                        ("c".to_string(), "4".to_string()),
+                    ],
+                    &["a".to_string(), "b".to_string(), "z".to_string()],
+                )
+                .expect("batch");
+            assert_eq!(
+                store.entries().expect("entries"),
+                vec![
+                    ("b".to_string(), "3".to_string()),
+                    ("c".to_string(), "4".to_string()),
+                ]
+            );
+        }
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn backed_store_in_memory_never_touches_disk() {
+        let store = BackedStore::<String, String>::in_memory();
+        assert!(store.path().is_none());
+        store.put("k".to_string(), "v".to_string()).expect("put");
+        store
+            .replace_all(vec![("x".to_string(), "y".to_string())])
+            .expect("replace");
+        assert_eq!(
+            store.entries().expect("entries"),
+            vec![("x".to_string(), "y".to_string())]
+        );
+    }
+
+    #[test]
+    fn headerless_file_is_upgraded_with_a_backup() {
+        let path = temp_path("legacy");
+        let mut legacy = 1u64.to_le_bytes().to_vec();
+        for part in [&b"k"[..], &b"hello"[..]] {
+            legacy.extend_from_slice(&(part.len() as u32).to_le_bytes());
+            legacy.extend_from_slice(part);
+        }
+        std::fs::write(&path, &legacy).expect("write legacy");
+
+        let store = FileStore::<String, String>::open(&path).expect("open");
+        let migration = store.migration().expect("migrated").clone();
+        assert_eq!((migration.from, migration.to), ((0, 0), (1, 1)));
+        assert_eq!(migration.records, 1);
+        assert_eq!(std::fs::read(&migration.backup).expect("backup"), legacy);
+        assert_eq!(&std::fs::read(&path).expect("rewritten")[..4], &STORE_MAGIC);
+        assert_eq!(
+            store.get(&"k".to_string()).expect("get").as_deref(),
+            Some("hello")
+        );
+
+        let reopened = FileStore::<String, String>::open(&path).expect("reopen");
+        assert!(reopened.migration().is_none());
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_file(migration.backup);
+    }
+
+    #[test]
+    fn registered_steps_upgrade_records_and_newer_files_are_refused() {
+        let path = temp_path("steps");
+        {
+            let store = FileStore::<String, String>::open(&path).expect("open v1");
+            store.put("k".to_string(), "hi".to_string()).expect("put");
+        }
+        let store = FileStore::<String, Shout>::open(&path).expect("open v2");
+        let migration = store.migration().expect("migrated").clone();
+        assert_eq!((migration.from, migration.to), ((1, 1), (1, 2)));
+        assert_eq!(
+            store.get(&"k".to_string()).expect("get"),
+            Some(Shout("hi".to_string()))
+        );
+
+        let err = FileStore::<String, String>::open(&path).expect_err("newer");
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(Migrations::new().upgrade(1, 2, b"x").is_err());
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_file(migration.backup);
+    }
 }
@@ -4,7 +4,8 @@ use crate::Recomposer;
 use crate::consistency::compute_consistency;
 use crate::explain::round2;
 
-#[derive(Clone, Copy, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DecisionWeights {
     pub coherence: f32,
     pub stability: f32,
@@ -23,7 +24,8 @@ impl Default for DecisionWeights {
     }
 }
 
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 pub struct DecisionReport {
     pub decision_score: f32,
     pub interpretation: String,
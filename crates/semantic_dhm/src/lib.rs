@@ -1,12 +1,14 @@
 use std::cmp::Ordering;
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 use std::io;
 use std::path::Path;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
 use std::time::{SystemTime, UNIX_EPOCH};
 
 use concept_engine::{Canonicalizer, ConceptId as CanonicalConceptId, ConceptRegistry};
+use core_types::progress::{NoopProgress, ProgressSink, ProgressTracker};
 use meaning_extractor::{MeaningStructure, NodeId, RelationType, RoleType};
-use memory_store::{Codec, FileStore, InMemoryStore, Store};
+use memory_store::{BackedStore, Codec, FileStore, InMemoryStore, Migrations, Store, StoreUsage};
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug)]
@@ -16,6 +18,7 @@ pub enum SemanticError {
     InconsistentState(&'static str),
     EvaluationError(String),
     SnapshotError(String),
+    PermissionDenied(PermissionDenied),
 }
 
 impl std::fmt::Display for SemanticError {
@@ -26,6 +29,7 @@ impl std::fmt::Display for SemanticError {
             Self::InconsistentState(msg) => write!(f, "inconsistent state: {msg}"),
             Self::EvaluationError(msg) => write!(f, "evaluation error: {msg}"),
             Self::SnapshotError(msg) => write!(f, "snapshot error: {msg}"),
+            Self::PermissionDenied(err) => write!(f, "permission denied: {err}"),
         }
     }
 }
@@ -34,10 +38,19 @@ impl std::error::Error for SemanticError {}
 
 impl From<io::Error> for SemanticError {
     fn from(value: io::Error) -> Self {
+        if let Some(denied) = permission_denied(&value) {
+            return SemanticError::PermissionDenied(denied.clone());
+        }
         SemanticError::EvaluationError(value.to_string())
     }
 }
 
+impl From<PermissionDenied> for SemanticError {
+    fn from(value: PermissionDenied) -> Self {
+        SemanticError::PermissionDenied(value)
+    }
+}
+
 pub const D_SEM: usize = 384;
 pub const D_STRUCT: usize = 384;
 pub const SIM_PRECISION: f64 = 1000.0;
@@ -81,22 +94,168 @@ impl SemanticEngine {
 pub struct L2Config {
     pub similarity_threshold: f64,
     pub algorithm_version: u32,
+    pub strategy: ClusteringStrategyKind,
 }
 
 pub const DEFAULT_L2_CONFIG: L2Config = L2Config {
     similarity_threshold: 0.995,
     algorithm_version: 1,
+    strategy: ClusteringStrategyKind::ConnectedComponents,
 };
 
+/// L1→L2 grouping algorithm. `ConnectedComponents` is the Stable behaviour;
+/// the others are intended to be selected through `L2Mode::Experimental`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ClusteringStrategyKind {
+    ConnectedComponents,
+    Agglomerative,
+    KMeans { max_k: usize },
+}
+
+pub trait ClusteringStrategy {
+    fn name(&self) -> &'static str;
+    /// Groups must be sorted internally and as a whole so that L2 ids stay deterministic.
+    fn group(&self, l1_units: &[SemanticUnitL1]) -> Vec<Vec<L1Id>>;
+}
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum L2Mode {
     Stable,
     Experimental(L2Config),
 }
 
+/// When `SemanticDhm::rebuild_l2_from_l1*` actually rebuilds. `Deferred`
+/// only remembers the request, so a batch of L1 edits that each ask for a
+/// rebuild costs one `flush_rebuild`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum L2RebuildMode {
+    #[default]
+    Immediate,
+    Deferred,
+}
+
+/// Why a rebuild created, updated or deleted a concept.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub enum L2ChangeReason {
+    /// Groups L1 units no previous concept referenced.
+    L1Added(Vec<L1Id>),
+    /// Referenced L1 units that are no longer present.
+    L1Removed(Vec<L1Id>),
+    /// The same L1 units, grouped differently.
+    Regrouped,
+    /// Same L1 units whose vectors, abstraction or polarity moved.
+    ContentChanged,
+    /// The L2 algorithm version changed, and with it every concept id.
+    AlgorithmChanged,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L2Change {
+    pub id: ConceptId,
+    pub reason: L2ChangeReason,
+}
+
+/// What one rebuild did to L2, for consumers that update incrementally.
+/// Each list is sorted by id.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L2ChangeSet {
+    pub created: Vec<L2Change>,
+    pub updated: Vec<L2Change>,
+    pub deleted: Vec<L2Change>,
+}
+
+impl L2ChangeSet {
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.updated.is_empty() && self.deleted.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.created.len() + self.updated.len() + self.deleted.len()
+    }
+
+    /// Every id that consumers have to re-render or drop.
+    pub fn touched(&self) -> BTreeSet<ConceptId> {
+        self.created
+            .iter()
+            .chain(&self.updated)
+            .chain(&self.deleted)
+            .map(|change| change.id)
+            .collect()
+    }
+}
+
+/// The changes that turn `before` into `after`. `algorithm_changed` marks
+/// ids that only moved because the L2 algorithm version did.
+pub fn diff_l2(
+    before: &[ConceptUnit],
+    after: &[ConceptUnit],
+    algorithm_changed: bool,
+) -> L2ChangeSet {
+    let before_by_id = before.iter().map(|c| (c.id, c)).collect::<BTreeMap<_, _>>();
+    let after_by_id = after.iter().map(|c| (c.id, c)).collect::<BTreeMap<_, _>>();
+    let before_refs = before
+        .iter()
+        .flat_map(|c| c.l1_refs.iter().copied())
+        .collect::<BTreeSet<_>>();
+    let after_refs = after
+        .iter()
+        .flat_map(|c| c.l1_refs.iter().copied())
+        .collect::<BTreeSet<_>>();
+    let algorithm_changed = algorithm_changed && !before.is_empty();
+
+    let mut changes = L2ChangeSet::default();
+    for (id, concept) in &after_by_id {
+        match before_by_id.get(id) {
+            Some(previous) if !same_content(previous, concept) => changes.updated.push(L2Change {
+                id: *id,
+                reason: L2ChangeReason::ContentChanged,
+            }),
+            Some(_) => {}
+            None => {
+                let added = concept
+                    .l1_refs
+                    .iter()
+                    .copied()
+                    .filter(|r| !before_refs.contains(r))
+                    .collect::<Vec<_>>();
+                let reason = if algorithm_changed {
+                    L2ChangeReason::AlgorithmChanged
+                } else if added.is_empty() {
+                    L2ChangeReason::Regrouped
+                } else {
+                    L2ChangeReason::L1Added(added)
+                };
+                changes.created.push(L2Change { id: *id, reason });
+            }
+        }
+    }
+    for (id, concept) in &before_by_id {
+        if after_by_id.contains_key(id) {
+            continue;
+        }
+        let removed = concept
+            .l1_refs
+            .iter()
+            .copied()
+            .filter(|r| !after_refs.contains(r))
+            .collect::<Vec<_>>();
+        let reason = if algorithm_changed {
+            L2ChangeReason::AlgorithmChanged
+        } else if removed.is_empty() {
+            L2ChangeReason::Regrouped
+        } else {
+            L2ChangeReason::L1Removed(removed)
+        };
+        changes.deleted.push(L2Change { id: *id, reason });
+    }
+    changes
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct MeaningLayerSnapshot {
     pub algorithm_version: u32,
+    /// Grouping strategy that produced `l2`.
+    pub strategy: ClusteringStrategyKind,
     pub l1: Vec<L1Snapshot>,
     pub l2: Vec<L2Snapshot>,
 }
@@ -121,6 +280,7 @@ pub struct L2Snapshot {
 pub struct SnapshotDiff {
     pub identical: bool,
     pub algorithm_version_changed: bool,
+    pub strategy_changed: bool,
     pub l1_changed: bool,
     pub l2_changed: bool,
 }
@@ -169,6 +329,28 @@ impl Codec for L1Id {
     }
 }
 
+/// MoSCoW priority of a requirement, most important first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum RequirementPriority {
+    Must,
+    Should,
+    Could,
+    Wont,
+}
+
+impl RequirementPriority {
+    /// Multiplier on requirement strength during hypothesis evaluation.
+    /// Untagged requirements weigh as `Should`.
+    pub fn weight(self) -> f64 {
+        match self {
+            Self::Must => 2.0,
+            Self::Should => 1.0,
+            Self::Could => 0.5,
+            Self::Wont => 0.0,
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
 pub enum RequirementRole {
     Goal,
@@ -185,6 +367,8 @@ pub struct SemanticUnitL1 {
     pub abstraction: f32,
     pub vector: Vec<f32>,
     pub source_text: String,
+    pub role_confidence: f32,
+    pub abstraction_confidence: f32,
 }
 
 pub type UnitId = L1Id;
@@ -197,6 +381,10 @@ pub struct SemanticUnitL1V2 {
     pub scope_out: Vec<String>,
     pub constraints: Vec<String>,
     pub ambiguity_score: f64,
+    #[serde(default = "full_confidence")]
+    pub role_confidence: f64,
+    #[serde(default = "full_confidence")]
+    pub abstraction_confidence: f64,
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -225,6 +413,8 @@ pub struct SemanticUnitL1Input {
     pub abstraction: f32,
     pub vector: Vec<f32>,
     pub source_text: String,
+    pub role_confidence: f32,
+    pub abstraction_confidence: f32,
 }
 
 impl Codec for SemanticUnitL1 {
@@ -241,37 +431,60 @@ impl Codec for SemanticUnitL1 {
         let src = self.source_text.as_bytes();
         out.extend_from_slice(&(src.len() as u32).to_le_bytes());
         out.extend_from_slice(src);
+        out.extend_from_slice(&self.role_confidence.to_le_bytes());
+        out.extend_from_slice(&self.abstraction_confidence.to_le_bytes());
         out
     }
 
     fn decode(bytes: &[u8]) -> io::Result<Self> {
-        let mut idx = 0usize;
-        let id = read_u128(bytes, &mut idx)?;
-        let role = role_from_u8(read_u8(bytes, &mut idx)?)?;
-        let polarity = normalize_polarity_i8(read_u8(bytes, &mut idx)? as i8);
-        let abstraction = read_f32(bytes, &mut idx)?.clamp(0.0, 1.0);
-        let v_len = read_u32(bytes, &mut idx)? as usize;
-        let mut vector = Vec::with_capacity(v_len);
-        for _ in 0..v_len {
-            vector.push(read_f32(bytes, &mut idx)?);
-        }
-        let src_len = read_u32(bytes, &mut idx)? as usize;
-        if idx.saturating_add(src_len) > bytes.len() {
-            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "source_text"));
-        }
-        let source_text = String::from_utf8(bytes[idx..idx + src_len].to_vec())
-            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "source_text"))?;
-        Ok(Self {
-            id: L1Id(id),
-            role,
-            polarity,
-            abstraction,
-            vector: normalize_with_dim(&vector, D_SEM),
-            source_text,
-        })
+        decode_l1(bytes, false)
+    }
+
+    fn migrations() -> Migrations {
+        Migrations::new().step(0, |bytes| decode_l1(bytes, true).map(|unit| unit.encode()))
     }
 }
 
+/// `legacy` accepts units persisted before confidence scoring, which carry
+/// no confidence trailer; they are treated as certain.
+fn decode_l1(bytes: &[u8], legacy: bool) -> io::Result<SemanticUnitL1> {
+    let mut idx = 0usize;
+    let id = read_u128(bytes, &mut idx)?;
+    let role = role_from_u8(read_u8(bytes, &mut idx)?)?;
+    let polarity = normalize_polarity_i8(read_u8(bytes, &mut idx)? as i8);
+    let abstraction = read_f32(bytes, &mut idx)?.clamp(0.0, 1.0);
+    let v_len = read_u32(bytes, &mut idx)? as usize;
+    let mut vector = Vec::with_capacity(v_len);
+    for _ in 0..v_len {
+        vector.push(read_f32(bytes, &mut idx)?);
+    }
+    let src_len = read_u32(bytes, &mut idx)? as usize;
+    if idx.saturating_add(src_len) > bytes.len() {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "source_text"));
+    }
+    let source_text = String::from_utf8(bytes[idx..idx + src_len].to_vec())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "source_text"))?;
+    idx += src_len;
+    let (role_confidence, abstraction_confidence) = if legacy && idx == bytes.len() {
+        (1.0, 1.0)
+    } else {
+        (
+            read_f32(bytes, &mut idx)?.clamp(0.0, 1.0),
+            read_f32(bytes, &mut idx)?.clamp(0.0, 1.0),
+        )
+    };
+    Ok(SemanticUnitL1 {
+        id: L1Id(id),
+        role,
+        polarity,
+        abstraction,
+        vector: normalize_with_dim(&vector, D_SEM),
+        source_text,
+        role_confidence,
+        abstraction_confidence,
+    })
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct ConceptId(pub u64);
@@ -304,6 +517,122 @@ pub struct ConceptUnit {
     pub s: Vec<f32>,
     pub polarity: i8,
     pub timestamp: u64,
+    pub access: AccessControl,
+}
+
+/// Ownership and lock on a shared concept or card. The default restricts
+/// nothing.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct AccessControl {
+    /// Only this actor may change the entry; `None` leaves it open.
+    #[serde(default)]
+    pub owner: Option<String>,
+    /// Read-only for everyone, the owner included.
+    #[serde(default)]
+    pub locked: bool,
+}
+
+impl AccessControl {
+    pub fn owned_by(owner: impl Into<String>) -> Self {
+        Self {
+            owner: Some(owner.into()),
+            locked: false,
+        }
+    }
+
+    pub fn locked(mut self) -> Self {
+        self.locked = true;
+        self
+    }
+
+    pub fn is_restricted(&self) -> bool {
+        self.locked || self.owner.is_some()
+    }
+
+    /// Why `actor` may not change an entry carrying this metadata, if it
+    /// may not.
+    pub fn denial(&self, actor: &Actor) -> Option<DenialReason> {
+        if actor.override_access {
+            return None;
+        }
+        if self.locked {
+            return Some(DenialReason::Locked);
+        }
+        match &self.owner {
+            Some(owner) if actor.name.as_deref() != Some(owner.as_str()) => {
+                Some(DenialReason::OwnedBy(owner.clone()))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Identity mutations run as. The default is anonymous and can only change
+/// unrestricted entries.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Actor {
+    pub name: Option<String>,
+    /// Ignores locks and ownership, for administrators and migrations.
+    pub override_access: bool,
+}
+
+impl Actor {
+    pub fn named(name: impl Into<String>) -> Self {
+        Self {
+            name: Some(name.into()),
+            override_access: false,
+        }
+    }
+
+    pub fn with_override(mut self) -> Self {
+        self.override_access = true;
+        self
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DenialReason {
+    Locked,
+    OwnedBy(String),
+}
+
+/// A mutation rejected by `AccessControl`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PermissionDenied {
+    /// The concept or card id.
+    pub target: String,
+    pub actor: Option<String>,
+    pub reason: DenialReason,
+}
+
+impl std::fmt::Display for PermissionDenied {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let actor = self.actor.as_deref().unwrap_or("anonymous");
+        match &self.reason {
+            DenialReason::Locked => {
+                write!(f, "{} is locked; {actor} may not change it", self.target)
+            }
+            DenialReason::OwnedBy(owner) => write!(
+                f,
+                "{} is owned by {owner}; {actor} may not change it",
+                self.target
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PermissionDenied {}
+
+impl From<PermissionDenied> for io::Error {
+    fn from(value: PermissionDenied) -> Self {
+        io::Error::new(io::ErrorKind::PermissionDenied, value)
+    }
+}
+
+/// The `PermissionDenied` carried by an error from a batch store API.
+pub fn permission_denied(err: &io::Error) -> Option<&PermissionDenied> {
+    err.get_ref()?.downcast_ref()
 }
 
 #[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
@@ -402,73 +731,122 @@ pub fn migrate_l2_v2_to_detail(
 }
 
 impl Codec for ConceptUnit {
+    /// 2 appends the access trailer.
+    const FORMAT_VERSION: u32 = 2;
+
     fn encode(&self) -> Vec<u8> {
-        let mut out = Vec::new();
-        out.extend_from_slice(&self.id.0.to_le_bytes());
-        out.extend_from_slice(&(self.integrated_vector.len() as u32).to_le_bytes());
-        for x in &self.integrated_vector {
-            out.extend_from_slice(&x.to_le_bytes());
-        }
-        out.extend_from_slice(&self.a.to_le_bytes());
-        out.extend_from_slice(&(self.s.len() as u32).to_le_bytes());
-        for x in &self.s {
-            out.extend_from_slice(&x.to_le_bytes());
-        }
-        out.push(self.polarity as u8);
-        out.extend_from_slice(&self.timestamp.to_le_bytes());
-        out.extend_from_slice(&(self.l1_refs.len() as u32).to_le_bytes());
-        for id in &self.l1_refs {
-            out.extend_from_slice(&id.0.to_le_bytes());
-        }
+        let mut out = encode_concept_v1(self);
+        out.push(self.access.locked as u8);
+        let owner = self.access.owner.as_deref().unwrap_or_default().as_bytes();
+        out.extend_from_slice(&(owner.len() as u32).to_le_bytes());
+        out.extend_from_slice(owner);
         out
     }
 
     fn decode(bytes: &[u8]) -> io::Result<Self> {
-        let mut idx = 0usize;
-        let id = read_u64(bytes, &mut idx)?;
+        decode_concept(bytes, false)
+    }
 
-        let v_len = read_u32(bytes, &mut idx)? as usize;
-        let mut v = Vec::with_capacity(v_len);
-        for _ in 0..v_len {
-            v.push(read_f32(bytes, &mut idx)?);
-        }
+    fn migrations() -> Migrations {
+        Migrations::new()
+            .step(0, |bytes| {
+                decode_concept(bytes, true).map(|concept| encode_concept_v1(&concept))
+            })
+            .step(1, |bytes| {
+                let mut out = bytes.to_vec();
+                out.extend_from_slice(&[0, 0, 0, 0, 0]);
+                Ok(out)
+            })
+    }
+}
 
-        let a = read_f32(bytes, &mut idx)?;
+/// The layout before access metadata was persisted.
+fn encode_concept_v1(concept: &ConceptUnit) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&concept.id.0.to_le_bytes());
+    out.extend_from_slice(&(concept.integrated_vector.len() as u32).to_le_bytes());
+    for x in &concept.integrated_vector {
+        out.extend_from_slice(&x.to_le_bytes());
+    }
+    out.extend_from_slice(&concept.a.to_le_bytes());
+    out.extend_from_slice(&(concept.s.len() as u32).to_le_bytes());
+    for x in &concept.s {
+        out.extend_from_slice(&x.to_le_bytes());
+    }
+    out.push(concept.polarity as u8);
+    out.extend_from_slice(&concept.timestamp.to_le_bytes());
+    out.extend_from_slice(&(concept.l1_refs.len() as u32).to_le_bytes());
+    for id in &concept.l1_refs {
+        out.extend_from_slice(&id.0.to_le_bytes());
+    }
+    out
+}
 
-        let s_len = read_u32(bytes, &mut idx)? as usize;
-        let mut s = Vec::with_capacity(s_len);
-        for _ in 0..s_len {
-            s.push(read_f32(bytes, &mut idx)?);
-        }
+/// `legacy` accepts the layouts written before polarity and then `l1_refs`
+/// were persisted; the missing fields default to neutral and empty. Legacy
+/// bytes never carry the access trailer.
+fn decode_concept(bytes: &[u8], legacy: bool) -> io::Result<ConceptUnit> {
+    let mut idx = 0usize;
+    let id = read_u64(bytes, &mut idx)?;
 
-        let (polarity, timestamp) = if idx.saturating_add(8) == bytes.len() {
-            (0, read_u64(bytes, &mut idx)?)
-        } else {
-            let p = read_u8(bytes, &mut idx)? as i8;
-            (normalize_polarity_i8(p), read_u64(bytes, &mut idx)?)
-        };
+    let v_len = read_u32(bytes, &mut idx)? as usize;
+    let mut v = Vec::with_capacity(v_len);
+    for _ in 0..v_len {
+        v.push(read_f32(bytes, &mut idx)?);
+    }
 
-        let l1_refs = if idx < bytes.len() {
-            let refs_len = read_u32(bytes, &mut idx)? as usize;
-            let mut refs = Vec::with_capacity(refs_len);
-            for _ in 0..refs_len {
-                refs.push(L1Id(read_u128(bytes, &mut idx)?));
-            }
-            refs
-        } else {
-            Vec::new()
-        };
+    let a = read_f32(bytes, &mut idx)?;
 
-        Ok(Self {
-            id: ConceptId(id),
-            l1_refs,
-            integrated_vector: v,
-            a,
-            s,
-            polarity,
-            timestamp,
-        })
+    let s_len = read_u32(bytes, &mut idx)? as usize;
+    let mut s = Vec::with_capacity(s_len);
+    for _ in 0..s_len {
+        s.push(read_f32(bytes, &mut idx)?);
     }
+
+    let (polarity, timestamp) = if legacy && idx.saturating_add(8) == bytes.len() {
+        (0, read_u64(bytes, &mut idx)?)
+    } else {
+        let p = read_u8(bytes, &mut idx)? as i8;
+        (normalize_polarity_i8(p), read_u64(bytes, &mut idx)?)
+    };
+
+    let l1_refs = if legacy && idx == bytes.len() {
+        Vec::new()
+    } else {
+        let refs_len = read_u32(bytes, &mut idx)? as usize;
+        let mut refs = Vec::with_capacity(refs_len);
+        for _ in 0..refs_len {
+            refs.push(L1Id(read_u128(bytes, &mut idx)?));
+        }
+        refs
+    };
+
+    let access = if legacy {
+        AccessControl::default()
+    } else {
+        let locked = read_u8(bytes, &mut idx)? != 0;
+        let owner_len = read_u32(bytes, &mut idx)? as usize;
+        if idx.saturating_add(owner_len) > bytes.len() {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "owner"));
+        }
+        let owner = String::from_utf8(bytes[idx..idx + owner_len].to_vec())
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "owner"))?;
+        AccessControl {
+            owner: (!owner.is_empty()).then_some(owner),
+            locked,
+        }
+    };
+
+    Ok(ConceptUnit {
+        id: ConceptId(id),
+        l1_refs,
+        integrated_vector: v,
+        a,
+        s,
+        polarity,
+        timestamp,
+        access,
+    })
 }
 
 impl TryFrom<&SemanticUnitL1> for SemanticUnitL1V2 {
@@ -507,6 +885,8 @@ impl TryFrom<&SemanticUnitL1> for SemanticUnitL1V2 {
             scope_out: canonicalize_string_vec(scope_out),
             constraints: canonicalize_string_vec(constraints),
             ambiguity_score: f64::from(value.abstraction).clamp(0.0, 1.0),
+            role_confidence: f64::from(value.role_confidence).clamp(0.0, 1.0),
+            abstraction_confidence: f64::from(value.abstraction_confidence).clamp(0.0, 1.0),
         })
     }
 }
@@ -561,7 +941,7 @@ impl TryFrom<&ConceptUnit> for ConceptUnitV2 {
         });
         derived_requirements.sort_by(|l, r| l.kind.cmp(&r.kind));
 
-        let stability_score = (1.0 - f64::from(value.a).abs() * 0.3).clamp(0.0, 1.0);
+        let stability_score = concept_stability(value);
         Ok(Self {
             id: value.id,
             derived_requirements,
@@ -579,7 +959,7 @@ impl TryFrom<ConceptUnit> for ConceptUnitV2 {
     }
 }
 
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
 pub struct ResonanceWeights {
     pub gamma1: f32,
     pub gamma2: f32,
@@ -607,6 +987,64 @@ impl ResonanceWeights {
     }
 }
 
+/// Stability of a concept as reported by `ConceptUnitV2`: concepts closer
+/// to the abstract end move more when their L1 units change.
+pub fn concept_stability(c: &ConceptUnit) -> f64 {
+    stability_from_abstraction(c.a)
+}
+
+/// `concept_stability` of a concept with abstraction `a`.
+pub fn stability_from_abstraction(a: f32) -> f64 {
+    (1.0 - f64::from(a).abs() * 0.3).clamp(0.0, 1.0)
+}
+
+/// Metadata predicates `SemanticDhm::recall_filtered` checks before scoring.
+/// Unset fields accept every concept.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RecallFilter {
+    pub polarity: Option<i8>,
+    /// Lowest accepted `concept_stability`.
+    pub min_stability: Option<f64>,
+    /// L1 units a concept must all reference.
+    pub must_include: Vec<L1Id>,
+    /// Inclusive bounds on the concept timestamp.
+    pub since: Option<u64>,
+    pub until: Option<u64>,
+}
+
+impl RecallFilter {
+    pub fn with_polarity(mut self, polarity: i8) -> Self {
+        self.polarity = Some(polarity);
+        self
+    }
+
+    pub fn with_min_stability(mut self, min_stability: f64) -> Self {
+        self.min_stability = Some(min_stability);
+        self
+    }
+
+    pub fn including(mut self, l1: L1Id) -> Self {
+        self.must_include.push(l1);
+        self
+    }
+
+    pub fn between(mut self, since: u64, until: u64) -> Self {
+        self.since = Some(since);
+        self.until = Some(until);
+        self
+    }
+
+    pub fn matches(&self, c: &ConceptUnit) -> bool {
+        self.polarity.is_none_or(|p| c.polarity == p)
+            && self
+                .min_stability
+                .is_none_or(|min| concept_stability(c) >= min)
+            && self.must_include.iter().all(|id| c.l1_refs.contains(id))
+            && self.since.is_none_or(|since| c.timestamp >= since)
+            && self.until.is_none_or(|until| c.timestamp <= until)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ConceptQuery {
     pub v: Vec<f32>,
@@ -634,6 +1072,10 @@ where
     next_id: u64,
     weights: ResonanceWeights,
     l2_config: L2Config,
+    rebuild_mode: L2RebuildMode,
+    /// Config of the latest rebuild requested while deferred.
+    pending_rebuild: Option<L2Config>,
+    actor: Actor,
 }
 
 pub struct SemanticL1Dhm<S>
@@ -642,6 +1084,8 @@ where
 {
     store: S,
     next_id: u128,
+    lint: L1LintConfig,
+    last_findings: Vec<L1LintFinding>,
 }
 
 impl<S> SemanticDhm<S>
@@ -661,9 +1105,56 @@ where
             next_id,
             weights: weights.normalized(),
             l2_config: DEFAULT_L2_CONFIG,
+            rebuild_mode: L2RebuildMode::Immediate,
+            pending_rebuild: None,
+            actor: Actor::default(),
         })
     }
 
+    /// Identity later mutations are checked against.
+    pub fn set_actor(&mut self, actor: Actor) {
+        self.actor = actor;
+    }
+
+    pub fn actor(&self) -> &Actor {
+        &self.actor
+    }
+
+    /// Whether the actor may change `id`; unknown ids pass.
+    pub fn authorize(&self, id: ConceptId) -> Result<(), PermissionDenied> {
+        match self.get(id) {
+            Some(concept) => self.check_access(&concept),
+            None => Ok(()),
+        }
+    }
+
+    fn check_access(&self, concept: &ConceptUnit) -> Result<(), PermissionDenied> {
+        match concept.access.denial(&self.actor) {
+            Some(reason) => Err(PermissionDenied {
+                target: format!("concept {}", concept.id.0),
+                actor: self.actor.name.clone(),
+                reason,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Replaces the ownership and lock of `id`; the current metadata must
+    /// allow the actor to change it.
+    pub fn set_access(
+        &mut self,
+        id: ConceptId,
+        access: AccessControl,
+    ) -> Result<(), SemanticError> {
+        let current = self
+            .store
+            .get(&id)?
+            .ok_or_else(|| SemanticError::InvalidInput(format!("unknown concept {}", id.0)))?;
+        self.check_access(&current)?;
+        self.store.put(id, ConceptUnit { access, ..current })?;
+        Ok(())
+    }
+
     pub fn project(&self, m: &MeaningStructure) -> ConceptQuery {
         phi(m)
     }
@@ -686,16 +1177,86 @@ where
             s: q.s,
             polarity: q.polarity,
             timestamp: now_ts(),
+            access: AccessControl::default(),
         };
 
         let _ = self.store.put(id, unit);
         id
     }
 
+    /// `insert_query` for each query, written to the store at once.
+    pub fn insert_query_batch(&mut self, queries: &[ConceptQuery]) -> io::Result<Vec<ConceptId>> {
+        let timestamp = now_ts();
+        let units = queries
+            .iter()
+            .zip(self.next_id..)
+            .map(|(query, id)| {
+                let q = query.clone().normalized();
+                let unit = ConceptUnit {
+                    id: ConceptId(id),
+                    l1_refs: Vec::new(),
+                    integrated_vector: q.v,
+                    a: q.a,
+                    s: q.s,
+                    polarity: q.polarity,
+                    timestamp,
+                    access: AccessControl::default(),
+                };
+                (unit.id, unit)
+            })
+            .collect::<Vec<_>>();
+        let ids = units.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        self.store.apply_batch(units, &[])?;
+        self.next_id = self.next_id.saturating_add(ids.len() as u64);
+        Ok(ids)
+    }
+
+    /// Replaces the vectors of existing concepts, keeping their ids, L1
+    /// references and access. Fails without writing anything if an id is
+    /// unknown or protected from the actor; see `permission_denied`.
+    pub fn update_query_batch(&mut self, updates: &[(ConceptId, ConceptQuery)]) -> io::Result<()> {
+        let timestamp = now_ts();
+        let mut units = Vec::with_capacity(updates.len());
+        for (id, query) in updates {
+            let current = self.store.get(id)?.ok_or_else(|| {
+                io::Error::new(io::ErrorKind::NotFound, format!("unknown concept {}", id.0))
+            })?;
+            self.check_access(&current)?;
+            let q = query.clone().normalized();
+            units.push((
+                *id,
+                ConceptUnit {
+                    integrated_vector: q.v,
+                    a: q.a,
+                    s: q.s,
+                    polarity: q.polarity,
+                    timestamp,
+                    ..current
+                },
+            ));
+        }
+        self.store.apply_batch(units, &[])
+    }
+
+    /// Drops `ids` in one write; unknown ids are ignored. Fails without
+    /// writing anything if one of them is protected from the actor.
+    pub fn remove_batch(&mut self, ids: &[ConceptId]) -> io::Result<()> {
+        for id in ids {
+            if let Some(current) = self.store.get(id)? {
+                self.check_access(&current)?;
+            }
+        }
+        self.store.apply_batch(Vec::new(), ids)
+    }
+
     pub fn get(&self, id: ConceptId) -> Option<ConceptUnit> {
         self.store.get(&id).unwrap_or(None)
     }
 
+    pub fn store_usage(&self) -> io::Result<StoreUsage> {
+        self.store.usage()
+    }
+
     pub fn all_concepts(&self) -> Vec<ConceptUnit> {
         let mut entries = self.store.entries().unwrap_or_default();
         entries.sort_by(|(lid, _), (rid, _)| lid.cmp(rid));
@@ -703,6 +1264,17 @@ where
     }
 
     pub fn recall(&self, query: &ConceptQuery, top_k: usize) -> Vec<(ConceptId, f32)> {
+        self.recall_filtered(query, top_k, &RecallFilter::default())
+    }
+
+    /// `recall` over the concepts `filter` accepts; the others are never
+    /// scored.
+    pub fn recall_filtered(
+        &self,
+        query: &ConceptQuery,
+        top_k: usize,
+        filter: &RecallFilter,
+    ) -> Vec<(ConceptId, f32)> {
         if top_k == 0 {
             return Vec::new();
         }
@@ -712,6 +1284,7 @@ where
             .entries()
             .unwrap_or_default()
             .into_iter()
+            .filter(|(_, c)| filter.matches(c))
             .map(|(id, c)| {
                 let score = resonance(&q, &c, self.weights);
                 (id, score)
@@ -727,19 +1300,63 @@ where
         self.weights
     }
 
+    pub fn set_weights(&mut self, weights: ResonanceWeights) {
+        self.weights = weights.normalized();
+    }
+
     pub fn l2_config(&self) -> L2Config {
         self.l2_config
     }
 
+    /// Switching back to `Immediate` leaves a pending rebuild pending;
+    /// `flush_rebuild` it.
+    pub fn set_rebuild_mode(&mut self, mode: L2RebuildMode) {
+        self.rebuild_mode = mode;
+    }
+
+    pub fn rebuild_mode(&self) -> L2RebuildMode {
+        self.rebuild_mode
+    }
+
+    /// True when a deferred rebuild has not been flushed; L2 is stale then.
+    pub fn has_pending_rebuild(&self) -> bool {
+        self.pending_rebuild.is_some()
+    }
+
+    /// Runs the pending rebuild, if any, over the current `l1_units`, with
+    /// the config of the latest deferred request. Returns its changes, or
+    /// `None` when nothing was pending.
+    pub fn flush_rebuild(
+        &mut self,
+        l1_units: &[SemanticUnitL1],
+    ) -> Result<Option<L2ChangeSet>, SemanticError> {
+        let Some(config) = self.pending_rebuild else {
+            return Ok(None);
+        };
+        let changes = self.rebuild_now(l1_units, config, &NoopProgress)?;
+        self.pending_rebuild = None;
+        Ok(Some(changes))
+    }
+
+    /// Leaves an existing concept with the same id untouched when the actor
+    /// may not change it.
     pub fn insert_from_l1_units(&mut self, l1_units: &[SemanticUnitL1]) -> ConceptId {
-        let unit = build_l2_unit_from_l1(l1_units, self.l2_config);
+        let mut unit = build_l2_unit_from_l1(l1_units, self.l2_config);
         let id = unit.id;
+        match self.store.get(&id) {
+            Ok(Some(current)) if self.check_access(&current).is_err() => return id,
+            Ok(Some(current)) => unit.access = current.access,
+            _ => {}
+        }
         let _ = self.store.put(id, unit);
         self.next_id = self.next_id.max(id.0.saturating_add(1));
         id
     }
 
-    pub fn rebuild_l2_from_l1(&mut self, l1_units: &[SemanticUnitL1]) -> Result<(), SemanticError> {
+    pub fn rebuild_l2_from_l1(
+        &mut self,
+        l1_units: &[SemanticUnitL1],
+    ) -> Result<L2ChangeSet, SemanticError> {
         self.rebuild_l2_from_l1_with_config(l1_units, DEFAULT_L2_CONFIG)
     }
 
@@ -747,15 +1364,64 @@ where
         &mut self,
         l1_units: &[SemanticUnitL1],
         config: L2Config,
-    ) -> Result<(), SemanticError> {
-        let rebuilt = build_l2_cache_with_config(l1_units, config);
+    ) -> Result<L2ChangeSet, SemanticError> {
+        self.rebuild_l2_from_l1_with_progress(l1_units, config, &NoopProgress)
+    }
+
+    /// `rebuild_l2_from_l1_with_config`, reporting the clustering, unit
+    /// building and store phases to `progress`. A deferred rebuild reports
+    /// no progress and no changes until it is flushed.
+    pub fn rebuild_l2_from_l1_with_progress(
+        &mut self,
+        l1_units: &[SemanticUnitL1],
+        config: L2Config,
+        progress: &dyn ProgressSink,
+    ) -> Result<L2ChangeSet, SemanticError> {
+        if self.rebuild_mode == L2RebuildMode::Deferred {
+            self.pending_rebuild = Some(config);
+            return Ok(L2ChangeSet::default());
+        }
+        self.rebuild_now(l1_units, config, progress)
+    }
+
+    /// What an immediate `rebuild_l2_from_l1_with_config` would change,
+    /// refused where that rebuild would be. Nothing is stored.
+    pub fn preview_rebuild(
+        &self,
+        l1_units: &[SemanticUnitL1],
+        config: L2Config,
+    ) -> Result<L2ChangeSet, SemanticError> {
+        let mut rebuilt = build_l2_cache_with_progress(l1_units, config, &NoopProgress);
+        self.carry_access(&mut rebuilt)?;
+        Ok(diff_l2(
+            &self.all_concepts(),
+            &rebuilt,
+            self.l2_config.algorithm_version != config.algorithm_version,
+        ))
+    }
+
+    fn rebuild_now(
+        &mut self,
+        l1_units: &[SemanticUnitL1],
+        config: L2Config,
+        progress: &dyn ProgressSink,
+    ) -> Result<L2ChangeSet, SemanticError> {
+        let mut rebuilt = build_l2_cache_with_progress(l1_units, config, progress);
+        self.carry_access(&mut rebuilt)?;
+        let changes = diff_l2(
+            &self.all_concepts(),
+            &rebuilt,
+            self.l2_config.algorithm_version != config.algorithm_version,
+        );
         let entries = rebuilt
             .into_iter()
             .map(|unit| (unit.id, unit))
             .collect::<Vec<_>>();
+        let store = ProgressTracker::start(progress, "rebuild_l2.store", entries.len() as u64);
         self.store
             .replace_all(entries)
             .map_err(|e| SemanticError::EvaluationError(e.to_string()))?;
+        store.finish();
         self.next_id = self
             .store
             .entries()
@@ -766,6 +1432,31 @@ where
             .map(|v| v.saturating_add(1))
             .unwrap_or(1);
         self.l2_config = config;
+        Ok(changes)
+    }
+
+    /// Copies access metadata onto the rebuilt concepts with the same id and
+    /// refuses the rebuild if it would drop or change a concept the actor
+    /// may not change.
+    fn carry_access(&self, rebuilt: &mut [ConceptUnit]) -> Result<(), SemanticError> {
+        let mut by_id = rebuilt
+            .iter_mut()
+            .map(|unit| (unit.id, unit))
+            .collect::<BTreeMap<_, _>>();
+        let current = self.store.entries()?;
+        for (id, concept) in current {
+            if !concept.access.is_restricted() {
+                continue;
+            }
+            match by_id.get_mut(&id) {
+                Some(unit) if same_content(unit, &concept) => unit.access = concept.access,
+                Some(unit) => {
+                    self.check_access(&concept)?;
+                    unit.access = concept.access;
+                }
+                None => self.check_access(&concept)?,
+            }
+        }
         Ok(())
     }
 
@@ -773,7 +1464,7 @@ where
         &mut self,
         l1_units: &[SemanticUnitL1],
         mode: L2Mode,
-    ) -> Result<(), SemanticError> {
+    ) -> Result<L2ChangeSet, SemanticError> {
         match mode {
             L2Mode::Stable => self.rebuild_l2_from_l1_with_config(l1_units, DEFAULT_L2_CONFIG),
             L2Mode::Experimental(config) => self.rebuild_l2_from_l1_with_config(l1_units, config),
@@ -793,6 +1484,12 @@ impl SemanticDhm<FileStore<ConceptId, ConceptUnit>> {
     }
 }
 
+impl SemanticDhm<BackedStore<ConceptId, ConceptUnit>> {
+    pub fn backed(store: BackedStore<ConceptId, ConceptUnit>) -> io::Result<Self> {
+        Self::new(store, ResonanceWeights::default())
+    }
+}
+
 impl<S> SemanticL1Dhm<S>
 where
     S: Store<L1Id, SemanticUnitL1>,
@@ -805,20 +1502,139 @@ where
             .max()
             .map(|v| v.saturating_add(1))
             .unwrap_or(1);
-        Ok(Self { store, next_id })
+        Ok(Self {
+            store,
+            next_id,
+            lint: L1LintConfig::default(),
+            last_findings: Vec::new(),
+        })
+    }
+
+    pub fn with_lint_config(mut self, config: L1LintConfig) -> Self {
+        self.lint = config;
+        self
     }
 
+    pub fn lint_config(&self) -> &L1LintConfig {
+        &self.lint
+    }
+
+    /// Stores the unit whatever its lint findings; they are kept in
+    /// `last_lint_findings`. Use `try_insert` to honour
+    /// `L1LintConfig::block_on_error`.
     pub fn insert(&mut self, input: &SemanticUnitL1Input) -> L1Id {
-        let id = L1Id(self.next_id);
+        let unit = normalize_l1_input(L1Id(self.next_id), input);
+        self.last_findings = lint_l1_unit(&unit, &self.lint);
+        self.store_unit(unit)
+    }
+
+    /// `insert`, refused when blocking is enabled and a finding is at
+    /// `LintLevel::Error`; nothing is stored and no id is used up then.
+    pub fn try_insert(&mut self, input: &SemanticUnitL1Input) -> Result<L1Id, L1LintRejection> {
+        let unit = normalize_l1_input(L1Id(self.next_id), input);
+        let findings = lint_l1_unit(&unit, &self.lint);
+        if self.lint.block_on_error
+            && findings
+                .iter()
+                .any(|finding| finding.level == LintLevel::Error)
+        {
+            self.last_findings.clear();
+            let findings = findings
+                .into_iter()
+                .map(|finding| L1LintFinding {
+                    unit: None,
+                    ..finding
+                })
+                .collect();
+            return Err(L1LintRejection { findings });
+        }
+        self.last_findings = findings;
+        Ok(self.store_unit(unit))
+    }
+
+    /// `insert` for each input, written to the store at once. Lint findings
+    /// for all of them land in `last_lint_findings`; nothing is blocked.
+    pub fn insert_batch(&mut self, inputs: &[SemanticUnitL1Input]) -> io::Result<Vec<L1Id>> {
+        let units = inputs
+            .iter()
+            .zip(self.next_id..)
+            .map(|(input, id)| normalize_l1_input(L1Id(id), input))
+            .collect::<Vec<_>>();
+        let ids = units.iter().map(|unit| unit.id).collect::<Vec<_>>();
+        let findings = units
+            .iter()
+            .flat_map(|unit| lint_l1_unit(unit, &self.lint))
+            .collect();
+        self.store
+            .apply_batch(units.into_iter().map(|unit| (unit.id, unit)).collect(), &[])?;
+        self.next_id = self.next_id.saturating_add(ids.len() as u128);
+        self.last_findings = findings;
+        Ok(ids)
+    }
+
+    /// Units `insert_batch` would store for `inputs`, with the ids they
+    /// would get. Nothing is stored.
+    pub fn preview_insert_batch(&self, inputs: &[SemanticUnitL1Input]) -> Vec<SemanticUnitL1> {
+        inputs
+            .iter()
+            .zip(self.next_id..)
+            .map(|(input, id)| normalize_l1_input(L1Id(id), input))
+            .collect()
+    }
+
+    /// Replaces existing units in one write, linted like `insert_batch`.
+    /// Fails without writing anything if an id is unknown.
+    pub fn update_batch(&mut self, updates: &[(L1Id, SemanticUnitL1Input)]) -> io::Result<()> {
+        let mut units = Vec::with_capacity(updates.len());
+        for (id, input) in updates {
+            if self.store.get(id)?.is_none() {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!("unknown l1 unit {}", id.0),
+                ));
+            }
+            units.push(normalize_l1_input(*id, input));
+        }
+        let findings = units
+            .iter()
+            .flat_map(|unit| lint_l1_unit(unit, &self.lint))
+            .collect();
+        self.store
+            .apply_batch(units.into_iter().map(|unit| (unit.id, unit)).collect(), &[])?;
+        self.last_findings = findings;
+        Ok(())
+    }
+
+    /// `remove` for each id, in one write.
+    pub fn remove_batch(&mut self, ids: &[L1Id]) -> io::Result<()> {
+        self.store.apply_batch(Vec::new(), ids)?;
+        self.next_id = self
+            .store
+            .entries()?
+            .into_iter()
+            .map(|(key, _)| key.0)
+            .max()
+            .map(|v| v.saturating_add(1))
+            .unwrap_or(1);
+        Ok(())
+    }
+
+    /// Findings for the units stored by the last insert or update.
+    pub fn last_lint_findings(&self) -> &[L1LintFinding] {
+        &self.last_findings
+    }
+
+    /// Lints every stored unit, in id order.
+    pub fn lint_l1_units(&self) -> Vec<L1LintFinding> {
+        self.all_units()
+            .iter()
+            .flat_map(|unit| lint_l1_unit(unit, &self.lint))
+            .collect()
+    }
+
+    fn store_unit(&mut self, unit: SemanticUnitL1) -> L1Id {
+        let id = unit.id;
         self.next_id = self.next_id.saturating_add(1);
-        let unit = SemanticUnitL1 {
-            id,
-            role: input.role,
-            polarity: normalize_polarity_i8(input.polarity),
-            abstraction: input.abstraction.clamp(0.0, 1.0),
-            vector: normalize_with_dim(&input.vector, D_SEM),
-            source_text: input.source_text.clone(),
-        };
         let _ = self.store.put(id, unit);
         id
     }
@@ -827,6 +1643,10 @@ where
         self.store.get(&id).unwrap_or(None)
     }
 
+    pub fn store_usage(&self) -> io::Result<StoreUsage> {
+        self.store.usage()
+    }
+
     pub fn all_units(&self) -> Vec<SemanticUnitL1> {
         let mut entries = self.store.entries().unwrap_or_default();
         entries.sort_by(|(l, _), (r, _)| l.cmp(r));
@@ -865,6 +1685,222 @@ impl SemanticL1Dhm<FileStore<L1Id, SemanticUnitL1>> {
     }
 }
 
+impl SemanticL1Dhm<BackedStore<L1Id, SemanticUnitL1>> {
+    pub fn backed(store: BackedStore<L1Id, SemanticUnitL1>) -> io::Result<Self> {
+        Self::new(store)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum LintLevel {
+    Allow,
+    Warn,
+    Error,
+}
+
+/// Checks run on L1 units; each names a way an input quietly weakens the
+/// L2 concepts built from it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum L1LintRule {
+    /// No source text, so the unit has no objective, scope or constraint.
+    EmptyScope,
+    /// Abstraction (ambiguity) at 1.0: nothing concrete to design against.
+    FullAmbiguity,
+    /// A constraint or prohibition with positive polarity pulls L2 towards
+    /// what it should rule out.
+    PositiveConstraint,
+    /// A zero vector resonates with nothing and clusters arbitrarily.
+    ZeroVector,
+}
+
+impl L1LintRule {
+    pub const ALL: [Self; 4] = [
+        Self::EmptyScope,
+        Self::FullAmbiguity,
+        Self::PositiveConstraint,
+        Self::ZeroVector,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::EmptyScope => "empty_scope",
+            Self::FullAmbiguity => "full_ambiguity",
+            Self::PositiveConstraint => "positive_constraint",
+            Self::ZeroVector => "zero_vector",
+        }
+    }
+
+    pub fn default_level(self) -> LintLevel {
+        match self {
+            Self::EmptyScope => LintLevel::Error,
+            Self::FullAmbiguity | Self::PositiveConstraint | Self::ZeroVector => LintLevel::Warn,
+        }
+    }
+}
+
+/// Per-rule levels; rules not overridden use `L1LintRule::default_level`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct L1LintConfig {
+    pub levels: BTreeMap<L1LintRule, LintLevel>,
+    /// Makes `SemanticL1Dhm::try_insert` refuse units with an Error finding.
+    pub block_on_error: bool,
+}
+
+impl L1LintConfig {
+    pub fn with_level(mut self, rule: L1LintRule, level: LintLevel) -> Self {
+        self.levels.insert(rule, level);
+        self
+    }
+
+    pub fn blocking(mut self) -> Self {
+        self.block_on_error = true;
+        self
+    }
+
+    pub fn level(&self, rule: L1LintRule) -> LintLevel {
+        self.levels
+            .get(&rule)
+            .copied()
+            .unwrap_or_else(|| rule.default_level())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub enum L1LintFix {
+    AddSourceText,
+    SetAbstraction(f32),
+    SetPolarity(i8),
+    ChangeRole(RequirementRole),
+    ProvideVector,
+}
+
+impl std::fmt::Display for L1LintFix {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AddSourceText => write!(f, "state what the requirement covers in source_text"),
+            Self::SetAbstraction(value) => {
+                write!(f, "lower abstraction to {value} or split the requirement")
+            }
+            Self::SetPolarity(value) => write!(f, "set polarity to {value}"),
+            Self::ChangeRole(role) => write!(f, "change the role to {role:?}"),
+            Self::ProvideVector => write!(f, "supply a non-zero embedding vector"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct L1LintFinding {
+    /// `None` for a unit that was refused before it got an id.
+    pub unit: Option<L1Id>,
+    pub rule: L1LintRule,
+    pub level: LintLevel,
+    pub message: String,
+    /// Alternatives, preferred first.
+    pub fixes: Vec<L1LintFix>,
+}
+
+/// Returned by `SemanticL1Dhm::try_insert` for a blocked unit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct L1LintRejection {
+    pub findings: Vec<L1LintFinding>,
+}
+
+impl std::fmt::Display for L1LintRejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rules = self
+            .findings
+            .iter()
+            .filter(|finding| finding.level == LintLevel::Error)
+            .map(|finding| finding.rule.name())
+            .collect::<Vec<_>>();
+        write!(f, "l1 unit rejected by lint: {}", rules.join(", "))
+    }
+}
+
+impl std::error::Error for L1LintRejection {}
+
+impl From<L1LintRejection> for SemanticError {
+    fn from(value: L1LintRejection) -> Self {
+        SemanticError::InvalidInput(value.to_string())
+    }
+}
+
+/// Findings for `unit` at the levels in `config`, in `L1LintRule::ALL`
+/// order; allowed rules are skipped.
+pub fn lint_l1_unit(unit: &SemanticUnitL1, config: &L1LintConfig) -> Vec<L1LintFinding> {
+    let mut findings = Vec::new();
+    for rule in L1LintRule::ALL {
+        let level = config.level(rule);
+        if level == LintLevel::Allow {
+            continue;
+        }
+        let violation =
+            match rule {
+                L1LintRule::EmptyScope => canonicalize_text_field(&unit.source_text)
+                    .is_empty()
+                    .then(|| {
+                        (
+                            "source text is empty".to_string(),
+                            vec![L1LintFix::AddSourceText],
+                        )
+                    }),
+                L1LintRule::FullAmbiguity => (unit.abstraction >= 1.0).then(|| {
+                    (
+                        format!("abstraction is {}", unit.abstraction),
+                        vec![L1LintFix::SetAbstraction(0.8)],
+                    )
+                }),
+                L1LintRule::PositiveConstraint => (matches!(
+                    unit.role,
+                    RequirementRole::Constraint | RequirementRole::Prohibition
+                ) && unit.polarity > 0)
+                    .then(|| {
+                        (
+                            format!("{:?} has positive polarity", unit.role),
+                            vec![
+                                L1LintFix::SetPolarity(-1),
+                                L1LintFix::ChangeRole(RequirementRole::Goal),
+                            ],
+                        )
+                    }),
+                L1LintRule::ZeroVector => {
+                    unit.vector
+                        .iter()
+                        .all(|v| v.abs() <= f32::EPSILON)
+                        .then(|| {
+                            (
+                                "vector is all zeros".to_string(),
+                                vec![L1LintFix::ProvideVector],
+                            )
+                        })
+                }
+            };
+        if let Some((message, fixes)) = violation {
+            findings.push(L1LintFinding {
+                unit: Some(unit.id),
+                rule,
+                level,
+                message,
+                fixes,
+            });
+        }
+    }
+    findings
+}
+
+fn normalize_l1_input(id: L1Id, input: &SemanticUnitL1Input) -> SemanticUnitL1 {
+    SemanticUnitL1 {
+        id,
+        role: input.role,
+        polarity: normalize_polarity_i8(input.polarity),
+        abstraction: input.abstraction.clamp(0.0, 1.0),
+        vector: normalize_with_dim(&input.vector, D_SEM),
+        source_text: input.source_text.clone(),
+        role_confidence: input.role_confidence.clamp(0.0, 1.0),
+        abstraction_confidence: input.abstraction_confidence.clamp(0.0, 1.0),
+    }
+}
+
 pub fn phi(m: &MeaningStructure) -> ConceptQuery {
     let mut node_map: BTreeMap<NodeId, Vec<f32>> = BTreeMap::new();
 
@@ -952,37 +1988,234 @@ pub fn deterministic_grouping_with_config(
     l1_units: &[SemanticUnitL1],
     config: L2Config,
 ) -> Vec<Vec<L1Id>> {
-    if l1_units.is_empty() {
-        return Vec::new();
+    clustering_strategy(config).group(l1_units)
+}
+
+pub fn clustering_strategy(config: L2Config) -> Box<dyn ClusteringStrategy> {
+    match config.strategy {
+        ClusteringStrategyKind::ConnectedComponents => Box::new(ConnectedComponentsClustering {
+            similarity_threshold: config.similarity_threshold,
+        }),
+        ClusteringStrategyKind::Agglomerative => Box::new(AgglomerativeClustering {
+            similarity_threshold: config.similarity_threshold,
+            max_units: AGGLOMERATIVE_MAX_UNITS,
+        }),
+        ClusteringStrategyKind::KMeans { max_k } => Box::new(KMeansClustering {
+            max_k,
+            max_iterations: KMEANS_MAX_ITERATIONS,
+        }),
     }
+}
 
-    let normalized = normalized_l1(l1_units.to_vec());
-    let n = normalized.len();
-    let mut uf = UnionFind::new(n);
+/// Single-linkage grouping: every pair above the threshold is joined.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ConnectedComponentsClustering {
+    pub similarity_threshold: f64,
+}
 
-    for i in 0..n {
-        for j in (i + 1)..n {
-            let sim = cosine_similarity(&normalized[i].vector, &normalized[j].vector);
-            let qsim = quantize_similarity(sim);
-            let qth = quantize_similarity(config.similarity_threshold);
-            if qsim >= qth {
-                uf.union(i, j);
+impl ClusteringStrategy for ConnectedComponentsClustering {
+    fn name(&self) -> &'static str {
+        "connected_components"
+    }
+
+    fn group(&self, l1_units: &[SemanticUnitL1]) -> Vec<Vec<L1Id>> {
+        if l1_units.is_empty() {
+            return Vec::new();
+        }
+
+        let normalized = normalized_l1(l1_units.to_vec());
+        let n = normalized.len();
+        let sims = similarity_matrix(&normalized);
+        let qth = quantize_similarity(self.similarity_threshold);
+        let mut uf = UnionFind::new(n);
+
+        for (i, row) in sims.iter().enumerate() {
+            for (j, sim) in row.iter().enumerate().skip(i + 1) {
+                if *sim >= qth {
+                    uf.union(i, j);
+                }
             }
         }
+
+        let assignment = (0..n).map(|idx| uf.find(idx)).collect::<Vec<_>>();
+        groups_from_assignment(&normalized, &assignment)
     }
+}
 
-    let mut groups = BTreeMap::<usize, Vec<L1Id>>::new();
-    for (idx, unit) in normalized.iter().enumerate() {
-        let root = uf.find(idx);
-        groups.entry(root).or_default().push(unit.id);
+/// Unit count above which `AgglomerativeClustering` falls back to
+/// connected components; the merge loop is O(n³).
+pub const AGGLOMERATIVE_MAX_UNITS: usize = 256;
+
+/// Average-linkage agglomeration: the most similar pair of clusters is merged
+/// while their mean pairwise similarity stays above the threshold. Inputs
+/// larger than `max_units` are grouped by `ConnectedComponentsClustering` at
+/// the same threshold instead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AgglomerativeClustering {
+    pub similarity_threshold: f64,
+    pub max_units: usize,
+}
+
+impl ClusteringStrategy for AgglomerativeClustering {
+    fn name(&self) -> &'static str {
+        "agglomerative"
     }
 
-    let mut out = groups.into_values().collect::<Vec<_>>();
-    for group in &mut out {
-        group.sort();
+    fn group(&self, l1_units: &[SemanticUnitL1]) -> Vec<Vec<L1Id>> {
+        if l1_units.is_empty() {
+            return Vec::new();
+        }
+        if l1_units.len() > self.max_units {
+            return ConnectedComponentsClustering {
+                similarity_threshold: self.similarity_threshold,
+            }
+            .group(l1_units);
+        }
+
+        let normalized = normalized_l1(l1_units.to_vec());
+        let sims = similarity_matrix(&normalized);
+        let qth = quantize_similarity(self.similarity_threshold);
+        let mut clusters = (0..normalized.len()).map(|i| vec![i]).collect::<Vec<_>>();
+
+        loop {
+            let mut best: Option<(i64, usize, usize)> = None;
+            for a in 0..clusters.len() {
+                for b in (a + 1)..clusters.len() {
+                    let linkage = average_linkage(&sims, &clusters[a], &clusters[b]);
+                    if linkage < qth {
+                        continue;
+                    }
+                    if best.is_none_or(|(q, _, _)| linkage > q) {
+                        best = Some((linkage, a, b));
+                    }
+                }
+            }
+            let Some((_, a, b)) = best else {
+                break;
+            };
+            let merged = clusters.remove(b);
+            clusters[a].extend(merged);
+        }
+
+        let mut assignment = vec![0usize; normalized.len()];
+        for (cluster_idx, members) in clusters.iter().enumerate() {
+            for &member in members {
+                assignment[member] = cluster_idx;
+            }
+        }
+        groups_from_assignment(&normalized, &assignment)
+    }
+}
+
+const KMEANS_MAX_ITERATIONS: usize = 32;
+
+/// k-means over normalized L1 vectors. k is chosen in `2..=max_k` by the best
+/// mean silhouette; when no k yields a positive silhouette everything stays in
+/// one concept.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KMeansClustering {
+    pub max_k: usize,
+    pub max_iterations: usize,
+}
+
+impl ClusteringStrategy for KMeansClustering {
+    fn name(&self) -> &'static str {
+        "kmeans_silhouette"
+    }
+
+    fn group(&self, l1_units: &[SemanticUnitL1]) -> Vec<Vec<L1Id>> {
+        if l1_units.is_empty() {
+            return Vec::new();
+        }
+
+        let normalized = normalized_l1(l1_units.to_vec());
+        let n = normalized.len();
+        let vectors = normalized
+            .iter()
+            .map(|u| normalize_with_dim(&u.vector, D_SEM))
+            .collect::<Vec<_>>();
+        let sims = similarity_matrix(&normalized);
+
+        let mut best_assignment = vec![0usize; n];
+        let mut best_score = 0i64;
+        for k in 2..=self.max_k.min(n.saturating_sub(1)) {
+            let assignment = kmeans_assign(&vectors, k, self.max_iterations);
+            let score = quantize_similarity(mean_silhouette(&sims, &assignment));
+            if score > best_score {
+                best_score = score;
+                best_assignment = assignment;
+            }
+        }
+        groups_from_assignment(&normalized, &best_assignment)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClusteringReportEntry {
+    pub strategy: &'static str,
+    pub concept_count: usize,
+    pub singleton_count: usize,
+    pub largest_concept: usize,
+    /// Mean member-to-centroid similarity per concept, in output order.
+    pub stability: Vec<f64>,
+    pub stability_min: f64,
+    pub stability_mean: f64,
+    pub stability_max: f64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ClusteringComparisonReport {
+    pub unit_count: usize,
+    pub entries: Vec<ClusteringReportEntry>,
+}
+
+pub fn compare_clustering_strategies(
+    l1_units: &[SemanticUnitL1],
+    configs: &[L2Config],
+) -> ClusteringComparisonReport {
+    let normalized = normalized_l1(l1_units.to_vec());
+    let by_id = normalized
+        .iter()
+        .map(|u| (u.id, normalize_with_dim(&u.vector, D_SEM)))
+        .collect::<BTreeMap<_, _>>();
+    let entries = configs
+        .iter()
+        .map(|config| {
+            let strategy = clustering_strategy(*config);
+            let groups = strategy.group(&normalized);
+            let stability = groups
+                .iter()
+                .map(|group| {
+                    let members = group
+                        .iter()
+                        .filter_map(|id| by_id.get(id).cloned())
+                        .collect::<Vec<_>>();
+                    cluster_cohesion(&members)
+                })
+                .collect::<Vec<_>>();
+            let stability_min = stability.iter().copied().reduce(f64::min).unwrap_or(0.0);
+            let stability_max = stability.iter().copied().reduce(f64::max).unwrap_or(0.0);
+            let stability_mean = if stability.is_empty() {
+                0.0
+            } else {
+                stability.iter().sum::<f64>() / stability.len() as f64
+            };
+            ClusteringReportEntry {
+                strategy: strategy.name(),
+                concept_count: groups.len(),
+                singleton_count: groups.iter().filter(|g| g.len() == 1).count(),
+                largest_concept: groups.iter().map(Vec::len).max().unwrap_or(0),
+                stability,
+                stability_min,
+                stability_mean,
+                stability_max,
+            }
+        })
+        .collect();
+    ClusteringComparisonReport {
+        unit_count: normalized.len(),
+        entries,
     }
-    out.sort();
-    out
 }
 
 pub fn build_l2_cache(l1_units: &[SemanticUnitL1]) -> Vec<ConceptUnit> {
@@ -992,13 +2225,27 @@ pub fn build_l2_cache(l1_units: &[SemanticUnitL1]) -> Vec<ConceptUnit> {
 pub fn build_l2_cache_with_config(
     l1_units: &[SemanticUnitL1],
     config: L2Config,
+) -> Vec<ConceptUnit> {
+    build_l2_cache_with_progress(l1_units, config, &NoopProgress)
+}
+
+/// `build_l2_cache_with_config`, reporting the `rebuild_l2.cluster` phase
+/// (a single step, as strategies group all units at once) and the
+/// `rebuild_l2.build` phase, one item per concept.
+pub fn build_l2_cache_with_progress(
+    l1_units: &[SemanticUnitL1],
+    config: L2Config,
+    progress: &dyn ProgressSink,
 ) -> Vec<ConceptUnit> {
     let normalized = normalized_l1(l1_units.to_vec());
     let by_id = normalized
         .iter()
         .map(|u| (u.id, u.clone()))
         .collect::<BTreeMap<_, _>>();
+    let cluster = ProgressTracker::start(progress, "rebuild_l2.cluster", 1);
     let groups = deterministic_grouping_with_config(&normalized, config);
+    cluster.finish();
+    let mut build = ProgressTracker::start(progress, "rebuild_l2.build", groups.len() as u64);
     let mut out = Vec::with_capacity(groups.len());
     for refs in groups {
         let members = refs
@@ -1006,7 +2253,9 @@ pub fn build_l2_cache_with_config(
             .filter_map(|id| by_id.get(id).cloned())
             .collect::<Vec<_>>();
         out.push(build_l2_unit_from_l1(&members, config));
+        build.advance(1);
     }
+    build.finish();
     out.sort_by(|l, r| l.id.cmp(&r.id));
     out
 }
@@ -1014,6 +2263,7 @@ pub fn build_l2_cache_with_config(
 #[derive(Clone, Debug)]
 pub struct MeaningLayerState {
     pub algorithm_version: u32,
+    pub strategy: ClusteringStrategyKind,
     pub l1_units: Vec<SemanticUnitL1>,
     pub l2_units: Vec<ConceptUnit>,
 }
@@ -1050,6 +2300,7 @@ impl Snapshotable for MeaningLayerState {
 
         MeaningLayerSnapshot {
             algorithm_version: self.algorithm_version,
+            strategy: self.strategy,
             l1,
             l2,
         }
@@ -1068,6 +2319,7 @@ pub fn compare_snapshots(
     Ok(SnapshotDiff {
         identical: a == b,
         algorithm_version_changed: a.algorithm_version != b.algorithm_version,
+        strategy_changed: a.strategy != b.strategy,
         l1_changed: a.l1 != b.l1,
         l2_changed: a.l2 != b.l2,
     })
@@ -1135,6 +2387,15 @@ pub fn generate_l2_id(l1_refs: &[L1Id], algorithm_version: u32) -> ConceptId {
     ConceptId(hash.max(1))
 }
 
+/// Equal apart from timestamp and access.
+fn same_content(a: &ConceptUnit, b: &ConceptUnit) -> bool {
+    a.l1_refs == b.l1_refs
+        && a.integrated_vector == b.integrated_vector
+        && a.a == b.a
+        && a.s == b.s
+        && a.polarity == b.polarity
+}
+
 fn build_l2_unit_from_l1(l1_units: &[SemanticUnitL1], config: L2Config) -> ConceptUnit {
     let mut refs = l1_units.iter().map(|u| u.id).collect::<Vec<_>>();
     refs.sort();
@@ -1147,6 +2408,7 @@ fn build_l2_unit_from_l1(l1_units: &[SemanticUnitL1], config: L2Config) -> Conce
         s: query.s,
         polarity: query.polarity,
         timestamp: 0,
+        access: AccessControl::default(),
     }
 }
 
@@ -1275,6 +2537,154 @@ fn quantize_similarity(similarity: f64) -> i64 {
     (similarity.clamp(-1.0, 1.0) * SIM_PRECISION).round() as i64
 }
 
+fn similarity_matrix(units: &[SemanticUnitL1]) -> Vec<Vec<i64>> {
+    let n = units.len();
+    let mut out = vec![vec![quantize_similarity(1.0); n]; n];
+    for i in 0..n {
+        for j in (i + 1)..n {
+            let q = quantize_similarity(cosine_similarity(&units[i].vector, &units[j].vector));
+            out[i][j] = q;
+            out[j][i] = q;
+        }
+    }
+    out
+}
+
+fn average_linkage(sims: &[Vec<i64>], a: &[usize], b: &[usize]) -> i64 {
+    let mut total = 0i64;
+    for &i in a {
+        for &j in b {
+            total += sims[i][j];
+        }
+    }
+    total / (a.len() * b.len()) as i64
+}
+
+fn groups_from_assignment(units: &[SemanticUnitL1], assignment: &[usize]) -> Vec<Vec<L1Id>> {
+    let mut groups = BTreeMap::<usize, Vec<L1Id>>::new();
+    for (unit, cluster) in units.iter().zip(assignment) {
+        groups.entry(*cluster).or_default().push(unit.id);
+    }
+
+    let mut out = groups.into_values().collect::<Vec<_>>();
+    for group in &mut out {
+        group.sort();
+    }
+    out.sort();
+    out
+}
+
+fn kmeans_assign(vectors: &[Vec<f32>], k: usize, max_iterations: usize) -> Vec<usize> {
+    // 決定性のため、先頭ベクトルから最遠点を順に選んで初期中心とする
+    let mut centroids = vec![vectors[0].clone()];
+    while centroids.len() < k {
+        let mut far_idx = 0usize;
+        let mut far_sim = f32::INFINITY;
+        for (idx, v) in vectors.iter().enumerate() {
+            let nearest = centroids
+                .iter()
+                .map(|c| dot(v, c))
+                .fold(f32::NEG_INFINITY, f32::max);
+            if nearest < far_sim {
+                far_sim = nearest;
+                far_idx = idx;
+            }
+        }
+        centroids.push(vectors[far_idx].clone());
+    }
+
+    let mut assignment = vec![0usize; vectors.len()];
+    for _ in 0..max_iterations.max(1) {
+        let next = vectors
+            .iter()
+            .map(|v| {
+                let mut best = 0usize;
+                let mut best_sim = f32::NEG_INFINITY;
+                for (c_idx, c) in centroids.iter().enumerate() {
+                    let sim = dot(v, c);
+                    if sim > best_sim {
+                        best_sim = sim;
+                        best = c_idx;
+                    }
+                }
+                best
+            })
+            .collect::<Vec<_>>();
+        let converged = next == assignment;
+        assignment = next;
+        for (c_idx, centroid) in centroids.iter_mut().enumerate() {
+            let mut acc = vec![0.0f32; D_SEM];
+            for (v, _) in vectors
+                .iter()
+                .zip(&assignment)
+                .filter(|(_, a)| **a == c_idx)
+            {
+                add_scaled(&mut acc, v, 1.0);
+            }
+            if acc.iter().any(|x| *x != 0.0) {
+                *centroid = normalize_with_dim(&acc, D_SEM);
+            }
+        }
+        if converged {
+            break;
+        }
+    }
+    assignment
+}
+
+fn mean_silhouette(sims: &[Vec<i64>], assignment: &[usize]) -> f64 {
+    let n = assignment.len();
+    if n < 2 {
+        return 0.0;
+    }
+    let mut total = 0.0;
+    for i in 0..n {
+        let mut per_cluster = BTreeMap::<usize, (f64, usize)>::new();
+        for j in 0..n {
+            if i == j {
+                continue;
+            }
+            let distance = 1.0 - sims[i][j] as f64 / SIM_PRECISION;
+            let entry = per_cluster.entry(assignment[j]).or_insert((0.0, 0));
+            entry.0 += distance;
+            entry.1 += 1;
+        }
+        let Some((own_sum, own_count)) = per_cluster.remove(&assignment[i]) else {
+            // singleton cluster contributes 0 by definition
+            continue;
+        };
+        let a = own_sum / own_count as f64;
+        let b = per_cluster
+            .values()
+            .map(|(sum, count)| sum / *count as f64)
+            .fold(f64::INFINITY, f64::min);
+        if !b.is_finite() {
+            continue;
+        }
+        let denom = a.max(b);
+        if denom > 0.0 {
+            total += (b - a) / denom;
+        }
+    }
+    total / n as f64
+}
+
+fn cluster_cohesion(members: &[Vec<f32>]) -> f64 {
+    if members.is_empty() {
+        return 0.0;
+    }
+    let mut acc = vec![0.0f32; D_SEM];
+    for v in members {
+        add_scaled(&mut acc, v, 1.0);
+    }
+    let centroid = normalize_with_dim(&acc, D_SEM);
+    let sum = members
+        .iter()
+        .map(|v| dot(v, &centroid) as f64)
+        .sum::<f64>();
+    (sum / members.len() as f64).clamp(-1.0, 1.0)
+}
+
 #[derive(Debug)]
 struct UnionFind {
     parent: Vec<usize>,
@@ -1321,6 +2731,8 @@ fn normalize_with_dim(v: &[f32], dim: usize) -> Vec<f32> {
     out
 }
 
+// SystemTime::now panics on wasm32-unknown-unknown; units carry a zero timestamp there.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
 fn now_ts() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -1328,6 +2740,11 @@ fn now_ts() -> u64 {
         .unwrap_or(0)
 }
 
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn now_ts() -> u64 {
+    0
+}
+
 fn read_u32(raw: &[u8], idx: &mut usize) -> io::Result<u32> {
     if idx.saturating_add(4) > raw.len() {
         return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "u32"));
@@ -1422,7 +2839,8 @@ fn role_projection_weight(role: RequirementRole) -> f32 {
     }
 }
 
-fn infer_requirement_kind(l1: &SemanticUnitL1) -> RequirementKind {
+/// Requirement kind an L1 unit contributes to in `project_phase_a`.
+pub fn infer_requirement_kind(l1: &SemanticUnitL1) -> RequirementKind {
     let text = l1.source_text.to_ascii_lowercase();
     if text.contains("cloud") || l1.source_text.contains("クラウド") {
         RequirementKind::NoCloud
@@ -1437,6 +2855,10 @@ fn infer_requirement_kind(l1: &SemanticUnitL1) -> RequirementKind {
     }
 }
 
+fn full_confidence() -> f64 {
+    1.0
+}
+
 fn canonicalize_text_field(text: &str) -> String {
     text.to_lowercase()
         .split_whitespace()
@@ -1577,6 +2999,72 @@ mod tests {
         assert_eq!(out[0].0, id1);
     }
 
+    #[test]
+    fn recall_filtered_scores_only_matching_concepts() {
+        let dhm = SemanticDhm::in_memory().expect("mem");
+        let unit = |id: u64, polarity, a, refs: &[u128], timestamp| ConceptUnit {
+            id: ConceptId(id),
+            l1_refs: refs.iter().map(|r| L1Id(*r)).collect(),
+            integrated_vector: vec![1.0; D_SEM],
+            a,
+            s: vec![1.0; D_STRUCT],
+            polarity,
+            timestamp,
+            access: AccessControl::default(),
+        };
+        let units = vec![
+            unit(1, 1, 0.5, &[1, 2], 10),
+            unit(2, -1, 0.5, &[1], 20),
+            unit(3, 1, 1.0, &[2], 30),
+            unit(4, 1, 0.4, &[1, 2], 40),
+        ];
+        dhm.store
+            .apply_batch(units.into_iter().map(|u| (u.id, u)).collect(), &[])
+            .expect("seed");
+        let query = ConceptQuery {
+            v: vec![1.0; D_SEM],
+            a: 0.5,
+            s: vec![1.0; D_STRUCT],
+            polarity: 1,
+        };
+        let ids = |filter: RecallFilter| {
+            let mut ids = dhm
+                .recall_filtered(&query, 10, &filter)
+                .into_iter()
+                .map(|(id, _)| id.0)
+                .collect::<Vec<_>>();
+            ids.sort();
+            ids
+        };
+
+        assert_eq!(ids(RecallFilter::default()), vec![1, 2, 3, 4]);
+        assert_eq!(ids(RecallFilter::default().with_polarity(1)), vec![1, 3, 4]);
+        // Stability is 1 - 0.3 * a: 0.85, 0.85, 0.7, 0.88.
+        assert_eq!(
+            ids(RecallFilter::default().with_min_stability(0.8)),
+            vec![1, 2, 4]
+        );
+        assert_eq!(
+            ids(RecallFilter::default()
+                .including(L1Id(1))
+                .including(L1Id(2))),
+            vec![1, 4]
+        );
+        assert_eq!(ids(RecallFilter::default().between(20, 30)), vec![2, 3]);
+        assert_eq!(
+            ids(RecallFilter::default()
+                .with_polarity(1)
+                .including(L1Id(2))
+                .between(0, 35)),
+            vec![1, 3]
+        );
+        assert!(ids(RecallFilter::default().with_polarity(0)).is_empty());
+        assert_eq!(
+            dhm.recall(&query, 2),
+            dhm.recall_filtered(&query, 2, &RecallFilter::default())
+        );
+    }
+
     #[test]
     fn fusion_abstract_and_repulse_work() {
         let mut dhm = SemanticDhm::in_memory().expect("mem");
@@ -1621,6 +3109,47 @@ mod tests {
         let _ = std::fs::remove_file(path);
     }
 
+    #[test]
+    fn headerless_concept_store_is_migrated_on_open() {
+        let path = std::env::temp_dir().join(format!(
+            "semantic_dhm_legacy_{}.bin",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .expect("clock")
+                .as_nanos()
+        ));
+        let concept = ConceptUnit {
+            id: ConceptId(3),
+            l1_refs: Vec::new(),
+            integrated_vector: vec![0.5; 4],
+            a: 0.2,
+            s: vec![0.1; 2],
+            polarity: 0,
+            timestamp: 9,
+            access: AccessControl::default(),
+        };
+        // Written before polarity and l1_refs were persisted.
+        let encoded = encode_concept_v1(&concept);
+        let oldest = [
+            &encoded[..encoded.len() - 13],
+            &encoded[encoded.len() - 12..encoded.len() - 4],
+        ]
+        .concat();
+        assert!(ConceptUnit::decode(&oldest).is_err());
+        let mut legacy = 1u64.to_le_bytes().to_vec();
+        for part in [concept.id.encode(), oldest] {
+            legacy.extend_from_slice(&(part.len() as u32).to_le_bytes());
+            legacy.extend_from_slice(&part);
+        }
+        std::fs::write(&path, &legacy).expect("write legacy");
+
+        let store = FileStore::<ConceptId, ConceptUnit>::open(&path).expect("open");
+        let backup = store.migration().expect("migrated").backup.clone();
+        assert_eq!(store.get(&concept.id).expect("get"), Some(concept));
+        let _ = std::fs::remove_file(path);
+        let _ = std::fs::remove_file(backup);
+    }
+
     #[test]
     fn stability_condition() {
         assert!(is_stable(0.5001, 0.50015, 0.001));
@@ -1636,6 +3165,8 @@ mod tests {
             abstraction: 0.8,
             vector: vec![1.0; D_SEM],
             source_text: "高速化したい".to_string(),
+            role_confidence: 1.0,
+            abstraction_confidence: 1.0,
         });
         let unit = l1.get(l1_id).expect("unit");
         assert_eq!(unit.role, RequirementRole::Goal);
@@ -1649,6 +3180,33 @@ mod tests {
         assert_eq!(concept.l1_refs[0], l1_id);
     }
 
+    #[test]
+    fn l1_codec_defaults_confidence_for_legacy_bytes() {
+        let unit = SemanticUnitL1 {
+            id: L1Id(5),
+            role: RequirementRole::Constraint,
+            polarity: -1,
+            abstraction: 0.3,
+            vector: vec![1.0; D_SEM],
+            source_text: "must".to_string(),
+            role_confidence: 0.4,
+            abstraction_confidence: 0.7,
+        };
+        let encoded = unit.encode();
+        let decoded = SemanticUnitL1::decode(&encoded).expect("decode");
+        assert!((decoded.role_confidence - 0.4).abs() < 1e-6);
+        assert!((decoded.abstraction_confidence - 0.7).abs() < 1e-6);
+
+        let trailerless = &encoded[..encoded.len() - 8];
+        assert!(SemanticUnitL1::decode(trailerless).is_err());
+        let upgraded = SemanticUnitL1::migrations()
+            .upgrade(0, SemanticUnitL1::FORMAT_VERSION, trailerless)
+            .expect("migrate");
+        let legacy = SemanticUnitL1::decode(&upgraded).expect("legacy");
+        assert_eq!(legacy.role_confidence, 1.0);
+        assert_eq!(legacy.abstraction_confidence, 1.0);
+    }
+
     #[test]
     fn l2_order_invariance() {
         let units = vec![
@@ -1659,6 +3217,8 @@ mod tests {
                 abstraction: 0.7,
                 vector: vec![1.0; D_SEM],
                 source_text: "goal".to_string(),
+                role_confidence: 1.0,
+                abstraction_confidence: 1.0,
             },
             SemanticUnitL1 {
                 id: L1Id(20),
@@ -1667,6 +3227,8 @@ mod tests {
                 abstraction: 0.2,
                 vector: vec![0.2; D_SEM],
                 source_text: "constraint".to_string(),
+                role_confidence: 1.0,
+                abstraction_confidence: 1.0,
             },
             SemanticUnitL1 {
                 id: L1Id(30),
@@ -1675,6 +3237,8 @@ mod tests {
                 abstraction: 0.5,
                 vector: vec![0.95; D_SEM],
                 source_text: "optimization".to_string(),
+                role_confidence: 1.0,
+                abstraction_confidence: 1.0,
             },
         ];
 
@@ -1697,6 +3261,8 @@ mod tests {
             abstraction: 0.8,
             vector: vec![1.0; D_SEM],
             source_text: "高速化".to_string(),
+            role_confidence: 1.0,
+            abstraction_confidence: 1.0,
         });
         let u2 = l1.insert(&SemanticUnitL1Input {
             role: RequirementRole::Prohibition,
@@ -1704,6 +3270,8 @@ mod tests {
             abstraction: 0.4,
             vector: vec![-1.0; D_SEM],
             source_text: "禁止".to_string(),
+            role_confidence: 1.0,
+            abstraction_confidence: 1.0,
         });
 
         let l1_units = vec![l1.get(u1).expect("u1"), l1.get(u2).expect("u2")];
@@ -1725,6 +3293,8 @@ mod tests {
             abstraction: 0.8,
             vector: vec![1.0; D_SEM],
             source_text: "keep".to_string(),
+            role_confidence: 1.0,
+            abstraction_confidence: 1.0,
         });
         let removed = l1.insert(&SemanticUnitL1Input {
             role: RequirementRole::Constraint,
@@ -1732,6 +3302,8 @@ mod tests {
             abstraction: 0.2,
             vector: vec![-1.0; D_SEM],
             source_text: "remove".to_string(),
+            role_confidence: 1.0,
+            abstraction_confidence: 1.0,
         });
 
         dhm.rebuild_l2_from_l1(&l1.all_units()).expect("rebuild");
@@ -1749,6 +3321,294 @@ mod tests {
         );
     }
 
+    #[test]
+    fn l1_lint_reports_findings_and_blocks_errors_on_request() {
+        let input = |role, polarity, abstraction, text: &str| SemanticUnitL1Input {
+            role,
+            polarity,
+            abstraction,
+            vector: vec![1.0; D_SEM],
+            source_text: text.to_string(),
+            role_confidence: 1.0,
+            abstraction_confidence: 1.0,
+        };
+        let mut l1 = SemanticL1Dhm::in_memory().expect("l1");
+        let clean = l1.insert(&input(RequirementRole::Goal, 1, 0.4, "fast checkout"));
+        assert!(l1.last_lint_findings().is_empty());
+
+        let vague = l1.insert(&input(RequirementRole::Constraint, 1, 1.0, "  "));
+        let rules = l1
+            .last_lint_findings()
+            .iter()
+            .map(|f| (f.rule, f.level))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            rules,
+            vec![
+                (L1LintRule::EmptyScope, LintLevel::Error),
+                (L1LintRule::FullAmbiguity, LintLevel::Warn),
+                (L1LintRule::PositiveConstraint, LintLevel::Warn),
+            ]
+        );
+        assert_eq!(
+            l1.last_lint_findings()[2].fixes[0],
+            L1LintFix::SetPolarity(-1)
+        );
+        let on_demand = l1.lint_l1_units();
+        assert_eq!(on_demand.len(), 3);
+        assert!(on_demand.iter().all(|f| f.unit == Some(vague)));
+
+        // Blocking refuses the unit without using up an id.
+        let mut blocking = SemanticL1Dhm::in_memory().expect("l1").with_lint_config(
+            L1LintConfig::default()
+                .with_level(L1LintRule::PositiveConstraint, LintLevel::Error)
+                .with_level(L1LintRule::EmptyScope, LintLevel::Allow)
+                .blocking(),
+        );
+        let rejected = blocking
+            .try_insert(&input(RequirementRole::Prohibition, 1, 0.2, "no cloud"))
+            .expect_err("positive prohibition blocked");
+        assert_eq!(rejected.findings[0].rule, L1LintRule::PositiveConstraint);
+        assert_eq!(rejected.findings[0].unit, None);
+        assert!(blocking.all_units().is_empty());
+        let empty = blocking
+            .try_insert(&input(RequirementRole::Goal, 1, 0.2, ""))
+            .expect("empty scope allowed");
+        assert_eq!(empty, L1Id(1));
+        assert_eq!(clean, L1Id(1));
+    }
+
+    #[test]
+    fn batches_write_once_and_deferred_rebuild_runs_on_flush() {
+        let input = |polarity, vector: f32, text: &str| SemanticUnitL1Input {
+            role: RequirementRole::Goal,
+            polarity,
+            abstraction: 0.5,
+            vector: vec![vector; D_SEM],
+            source_text: text.to_string(),
+            role_confidence: 1.0,
+            abstraction_confidence: 1.0,
+        };
+        let mut l1 = SemanticL1Dhm::in_memory().expect("l1");
+        let ids = l1
+            .insert_batch(&[input(1, 1.0, "a"), input(1, 0.5, "b"), input(-1, -1.0, "c")])
+            .expect("insert batch");
+        assert_eq!(ids, vec![L1Id(1), L1Id(2), L1Id(3)]);
+        l1.update_batch(&[(L1Id(2), input(-1, -0.5, "b2"))])
+            .expect("update batch");
+        assert_eq!(l1.get(L1Id(2)).expect("updated").source_text, "b2");
+        let missing = l1
+            .update_batch(&[
+                (L1Id(1), input(1, 1.0, "a2")),
+                (L1Id(9), input(1, 1.0, "z")),
+            ])
+            .expect_err("unknown id");
+        assert_eq!(missing.kind(), io::ErrorKind::NotFound);
+        assert_eq!(l1.get(L1Id(1)).expect("untouched").source_text, "a");
+        l1.remove_batch(&[L1Id(3)]).expect("remove batch");
+        assert_eq!(l1.insert(&input(1, 1.0, "d")), L1Id(3));
+
+        let mut immediate = SemanticDhm::in_memory().expect("dhm");
+        let (progress, updates) = core_types::progress::ChannelProgress::new();
+        immediate
+            .rebuild_l2_from_l1_with_progress(&l1.all_units(), DEFAULT_L2_CONFIG, &progress)
+            .expect("rebuild");
+        let updates = updates.try_iter().collect::<Vec<_>>();
+        let mut phases = updates.iter().map(|u| u.phase.as_str()).collect::<Vec<_>>();
+        phases.dedup();
+        assert_eq!(
+            phases,
+            vec!["rebuild_l2.cluster", "rebuild_l2.build", "rebuild_l2.store"]
+        );
+        let built = updates
+            .iter()
+            .rfind(|u| u.phase == "rebuild_l2.build")
+            .expect("build phase");
+        assert!(built.is_done());
+        assert_eq!(built.total as usize, immediate.all_concepts().len());
+
+        let mut dhm = SemanticDhm::in_memory().expect("dhm");
+        dhm.set_rebuild_mode(L2RebuildMode::Deferred);
+        for _ in 0..3 {
+            dhm.rebuild_l2_from_l1(&l1.all_units()).expect("deferred");
+        }
+        assert!(dhm.has_pending_rebuild() && dhm.all_concepts().is_empty());
+        assert!(dhm.flush_rebuild(&l1.all_units()).expect("flush").is_some());
+        assert!(
+            dhm.flush_rebuild(&l1.all_units())
+                .expect("nothing pending")
+                .is_none()
+        );
+        assert_eq!(dhm.all_concepts(), immediate.all_concepts());
+
+        let query = |v: f32| ConceptQuery {
+            v: vec![v; D_SEM],
+            a: 0.5,
+            s: vec![0.0; D_STRUCT],
+            polarity: 1,
+        };
+        let before = dhm.all_concepts().len();
+        let added = dhm
+            .insert_query_batch(&[query(1.0), query(-1.0)])
+            .expect("query batch");
+        assert_eq!(dhm.all_concepts().len(), before + 2);
+        dhm.update_query_batch(&[(added[0], query(0.25))])
+            .expect("update queries");
+        let updated = dhm.get(added[0]).expect("updated concept");
+        assert!(updated.l1_refs.is_empty() && updated.integrated_vector[0] > 0.0);
+        dhm.remove_batch(&added).expect("remove concepts");
+        assert_eq!(dhm.all_concepts().len(), before);
+    }
+
+    #[test]
+    fn rebuilds_report_created_updated_and_deleted_concepts() {
+        let unit = |id: u64, refs: &[u128], v: f32| ConceptUnit {
+            id: ConceptId(id),
+            l1_refs: refs.iter().map(|r| L1Id(*r)).collect(),
+            integrated_vector: vec![v; 4],
+            a: 0.5,
+            s: vec![0.0; 2],
+            polarity: 1,
+            timestamp: id,
+            access: AccessControl::default(),
+        };
+        let before = vec![
+            unit(1, &[1, 2], 1.0),
+            unit(2, &[3], 1.0),
+            unit(3, &[4], 1.0),
+        ];
+        let after = vec![
+            unit(1, &[1, 2], 0.5),
+            unit(4, &[3, 5], 1.0),
+            unit(5, &[4], 1.0),
+        ];
+        let changes = diff_l2(&before, &after, false);
+        assert_eq!(
+            changes.updated,
+            vec![L2Change {
+                id: ConceptId(1),
+                reason: L2ChangeReason::ContentChanged,
+            }]
+        );
+        assert_eq!(
+            changes.created,
+            vec![
+                L2Change {
+                    id: ConceptId(4),
+                    reason: L2ChangeReason::L1Added(vec![L1Id(5)]),
+                },
+                L2Change {
+                    id: ConceptId(5),
+                    reason: L2ChangeReason::Regrouped,
+                },
+            ]
+        );
+        assert_eq!(changes.deleted.len(), 2);
+        assert!(
+            changes
+                .deleted
+                .iter()
+                .all(|c| c.reason == L2ChangeReason::Regrouped)
+        );
+        assert_eq!(changes.touched().len(), 5);
+        assert!(diff_l2(&before, &before, false).is_empty());
+        assert!(
+            diff_l2(&before, &after, true)
+                .created
+                .iter()
+                .all(|c| c.reason == L2ChangeReason::AlgorithmChanged)
+        );
+
+        let mut l1 = SemanticL1Dhm::in_memory().expect("l1");
+        let l1_id = l1.insert(&SemanticUnitL1Input {
+            role: RequirementRole::Goal,
+            polarity: 1,
+            abstraction: 0.8,
+            vector: vec![1.0; D_SEM],
+            source_text: "高速化したい".to_string(),
+            role_confidence: 1.0,
+            abstraction_confidence: 1.0,
+        });
+        let mut dhm = SemanticDhm::in_memory().expect("dhm");
+        let first = dhm.rebuild_l2_from_l1(&l1.all_units()).expect("rebuild");
+        assert_eq!(first.created.len(), 1);
+        assert_eq!(
+            first.created[0].reason,
+            L2ChangeReason::L1Added(vec![l1_id])
+        );
+        assert!(
+            dhm.rebuild_l2_from_l1(&l1.all_units())
+                .expect("unchanged")
+                .is_empty()
+        );
+        dhm.set_rebuild_mode(L2RebuildMode::Deferred);
+        assert!(dhm.rebuild_l2_from_l1(&[]).expect("deferred").is_empty());
+        let flushed = dhm.flush_rebuild(&[]).expect("flush").expect("pending");
+        assert_eq!(
+            flushed.deleted,
+            vec![L2Change {
+                id: first.created[0].id,
+                reason: L2ChangeReason::L1Removed(vec![l1_id]),
+            }]
+        );
+    }
+
+    #[test]
+    fn restricted_concepts_reject_changes_from_other_actors() {
+        let mut l1 = SemanticL1Dhm::in_memory().expect("l1");
+        l1.insert(&SemanticUnitL1Input {
+            role: RequirementRole::Goal,
+            polarity: 1,
+            abstraction: 0.8,
+            vector: vec![1.0; D_SEM],
+            source_text: "高速化したい".to_string(),
+            role_confidence: 1.0,
+            abstraction_confidence: 1.0,
+        });
+        let mut dhm = SemanticDhm::in_memory().expect("dhm");
+        dhm.rebuild_l2_from_l1(&l1.all_units()).expect("rebuild");
+        let id = dhm.all_concepts()[0].id;
+
+        dhm.set_actor(Actor::named("alice"));
+        dhm.set_access(id, AccessControl::owned_by("alice"))
+            .expect("claim");
+        dhm.rebuild_l2_from_l1(&l1.all_units())
+            .expect("unchanged rebuild");
+        let owned = dhm.get(id).expect("concept");
+        assert_eq!(owned.access.owner.as_deref(), Some("alice"));
+        assert_eq!(ConceptUnit::decode(&owned.encode()).expect("decode"), owned);
+
+        dhm.set_actor(Actor::named("bob"));
+        let err = dhm.remove_batch(&[id]).expect_err("owned by alice");
+        assert_eq!(
+            permission_denied(&err).map(|d| &d.reason),
+            Some(&DenialReason::OwnedBy("alice".to_string()))
+        );
+        assert!(matches!(
+            dhm.rebuild_l2_from_l1(&[]),
+            Err(SemanticError::PermissionDenied(_))
+        ));
+        assert!(dhm.get(id).is_some());
+
+        dhm.set_actor(Actor::named("alice"));
+        dhm.set_access(id, AccessControl::owned_by("alice").locked())
+            .expect("lock");
+        let query = ConceptQuery {
+            v: vec![0.5; D_SEM],
+            a: 0.5,
+            s: vec![0.0; D_STRUCT],
+            polarity: 1,
+        };
+        let err = dhm
+            .update_query_batch(&[(id, query)])
+            .expect_err("locked for the owner too");
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+        dhm.set_actor(Actor::named("bob").with_override());
+        dhm.rebuild_l2_from_l1(&[]).expect("override");
+        assert!(dhm.all_concepts().is_empty());
+    }
+
     #[test]
     fn l2_id_depends_on_algorithm_version() {
         let refs = vec![L1Id(1), L1Id(2), L1Id(3)];
@@ -1767,6 +3627,8 @@ mod tests {
                 abstraction: 0.6,
                 vector: vec![1.0; D_SEM],
                 source_text: "a".to_string(),
+                role_confidence: 1.0,
+                abstraction_confidence: 1.0,
             },
             SemanticUnitL1 {
                 id: L1Id(200),
@@ -1775,6 +3637,8 @@ mod tests {
                 abstraction: 0.6,
                 vector: vec![0.99; D_SEM],
                 source_text: "b".to_string(),
+                role_confidence: 1.0,
+                abstraction_confidence: 1.0,
             },
         ];
         let stable = build_l2_cache_with_config(&units, DEFAULT_L2_CONFIG);
@@ -1783,11 +3647,164 @@ mod tests {
             L2Config {
                 similarity_threshold: DEFAULT_L2_CONFIG.similarity_threshold,
                 algorithm_version: DEFAULT_L2_CONFIG.algorithm_version + 1,
+                strategy: DEFAULT_L2_CONFIG.strategy,
             },
         );
         assert_ne!(stable, experimental);
     }
 
+    fn axis_unit(id: u128, axis: usize, noise: f32) -> SemanticUnitL1 {
+        let mut vector = vec![0.0; D_SEM];
+        vector[axis] = 1.0;
+        vector[axis + 1] = noise;
+        SemanticUnitL1 {
+            id: L1Id(id),
+            role: RequirementRole::Goal,
+            polarity: 1,
+            abstraction: 0.5,
+            vector,
+            source_text: format!("u{id}"),
+            role_confidence: 1.0,
+            abstraction_confidence: 1.0,
+        }
+    }
+
+    fn two_topic_units() -> Vec<SemanticUnitL1> {
+        vec![
+            axis_unit(1, 0, 0.0),
+            axis_unit(2, 0, 0.25),
+            axis_unit(3, 0, 0.5),
+            axis_unit(4, 10, 0.0),
+            axis_unit(5, 10, 0.25),
+            axis_unit(6, 10, 0.5),
+        ]
+    }
+
+    fn config_with(strategy: ClusteringStrategyKind, threshold: f64) -> L2Config {
+        L2Config {
+            similarity_threshold: threshold,
+            strategy,
+            ..DEFAULT_L2_CONFIG
+        }
+    }
+
+    #[test]
+    fn kmeans_selects_k_by_silhouette() {
+        let groups = deterministic_grouping_with_config(
+            &two_topic_units(),
+            config_with(ClusteringStrategyKind::KMeans { max_k: 4 }, 0.0),
+        );
+        assert_eq!(
+            groups,
+            vec![
+                vec![L1Id(1), L1Id(2), L1Id(3)],
+                vec![L1Id(4), L1Id(5), L1Id(6)]
+            ]
+        );
+    }
+
+    #[test]
+    fn agglomerative_uses_average_linkage() {
+        let units = vec![
+            axis_unit(1, 0, 0.0),
+            axis_unit(2, 0, 0.5),
+            axis_unit(3, 0, 1.5),
+        ];
+        // 1-2 と 2-3 は閾値を超えるが、平均連結では 3 を取り込めない
+        let threshold = 0.85;
+        let single = deterministic_grouping_with_config(
+            &units,
+            config_with(ClusteringStrategyKind::ConnectedComponents, threshold),
+        );
+        let average = deterministic_grouping_with_config(
+            &units,
+            config_with(ClusteringStrategyKind::Agglomerative, threshold),
+        );
+        assert_eq!(single, vec![vec![L1Id(1), L1Id(2), L1Id(3)]]);
+        assert_eq!(average, vec![vec![L1Id(1), L1Id(2)], vec![L1Id(3)]]);
+
+        let capped = AgglomerativeClustering {
+            similarity_threshold: threshold,
+            max_units: 2,
+        }
+        .group(&units);
+        assert_eq!(capped, single);
+    }
+
+    #[test]
+    fn snapshots_differ_by_clustering_strategy() {
+        let units = two_topic_units();
+        let snapshot = |config: L2Config| {
+            MeaningLayerState {
+                algorithm_version: config.algorithm_version,
+                strategy: config.strategy,
+                l1_units: units.clone(),
+                l2_units: build_l2_cache_with_config(&units, config),
+            }
+            .snapshot()
+        };
+        let stable = snapshot(DEFAULT_L2_CONFIG);
+        let kmeans = snapshot(config_with(
+            ClusteringStrategyKind::KMeans { max_k: 4 },
+            0.0,
+        ));
+        let diff = compare_snapshots(&stable, &kmeans).expect("compare");
+        assert!(!diff.identical);
+        assert!(diff.strategy_changed);
+        assert!(!diff.algorithm_version_changed);
+    }
+
+    #[test]
+    fn experimental_mode_applies_strategy() {
+        let mut dhm = SemanticDhm::in_memory().expect("dhm");
+        let units = two_topic_units();
+        dhm.rebuild_l2_from_l1_with_mode(
+            &units,
+            L2Mode::Experimental(config_with(
+                ClusteringStrategyKind::KMeans { max_k: 3 },
+                0.0,
+            )),
+        )
+        .expect("rebuild");
+        assert_eq!(dhm.all_concepts().len(), 2);
+        dhm.rebuild_l2_from_l1_with_mode(&units, L2Mode::Stable)
+            .expect("rebuild");
+        assert_eq!(dhm.all_concepts().len(), 6);
+    }
+
+    #[test]
+    fn comparison_report_counts_concepts_and_stability() {
+        let units = two_topic_units();
+        let report = compare_clustering_strategies(
+            &units,
+            &[
+                DEFAULT_L2_CONFIG,
+                config_with(ClusteringStrategyKind::Agglomerative, 0.9),
+                config_with(ClusteringStrategyKind::KMeans { max_k: 4 }, 0.0),
+            ],
+        );
+        assert_eq!(report.unit_count, 6);
+        let counts = report
+            .entries
+            .iter()
+            .map(|e| (e.strategy, e.concept_count, e.singleton_count))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            counts,
+            vec![
+                ("connected_components", 6, 6),
+                ("agglomerative", 2, 0),
+                ("kmeans_silhouette", 2, 0),
+            ]
+        );
+        for entry in &report.entries {
+            assert_eq!(entry.stability.len(), entry.concept_count);
+            assert!(entry.stability_min <= entry.stability_mean);
+            assert!(entry.stability_mean <= entry.stability_max);
+        }
+        assert!((report.entries[0].stability_mean - 1.0).abs() < 1e-6);
+    }
+
     #[test]
     fn snapshot_is_deterministic_and_order_invariant() {
         let l1_a = SemanticUnitL1 {
@@ -1797,6 +3814,8 @@ mod tests {
             abstraction: 0.7,
             vector: vec![1.0; D_SEM],
             source_text: "performance".to_string(),
+            role_confidence: 1.0,
+            abstraction_confidence: 1.0,
         };
         let l1_b = SemanticUnitL1 {
             id: L1Id(2),
@@ -1805,17 +3824,21 @@ mod tests {
             abstraction: 0.6,
             vector: vec![0.5; D_SEM],
             source_text: "no cloud".to_string(),
+            role_confidence: 1.0,
+            abstraction_confidence: 1.0,
         };
 
         let l2 = build_l2_cache(&[l1_a.clone(), l1_b.clone()]);
         let s1 = MeaningLayerState {
             algorithm_version: DEFAULT_L2_CONFIG.algorithm_version,
+            strategy: DEFAULT_L2_CONFIG.strategy,
             l1_units: vec![l1_a.clone(), l1_b.clone()],
             l2_units: l2.clone(),
         }
         .snapshot();
         let s2 = MeaningLayerState {
             algorithm_version: DEFAULT_L2_CONFIG.algorithm_version,
+            strategy: DEFAULT_L2_CONFIG.strategy,
             l1_units: vec![l1_b, l1_a],
             l2_units: l2,
         }
@@ -1835,6 +3858,8 @@ mod tests {
                 abstraction: 0.9,
                 vector: vec![1.0; D_SEM],
                 source_text: "security hardening".to_string(),
+                role_confidence: 1.0,
+                abstraction_confidence: 1.0,
             },
             SemanticUnitL1 {
                 id: L1Id(12),
@@ -1843,6 +3868,8 @@ mod tests {
                 abstraction: 0.8,
                 vector: vec![0.8; D_SEM],
                 source_text: "no cloud dependency".to_string(),
+                role_confidence: 1.0,
+                abstraction_confidence: 1.0,
             },
         ];
         let l2 = build_l2_cache(&l1);
@@ -1862,6 +3889,8 @@ mod tests {
             scope_out: vec!["batch".to_string()],
             constraints: vec![],
             ambiguity_score: 0.2,
+            role_confidence: 1.0,
+            abstraction_confidence: 1.0,
         };
         let migrated = migrate_l1_v2_to_framework(&[l1]);
         assert_eq!(migrated.len(), 1);
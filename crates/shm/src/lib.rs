@@ -1,10 +1,23 @@
 use memory_space::{DesignState, Uuid};
 
+pub mod lint;
+#[cfg(feature = "serde")]
+pub mod pack;
+pub mod precondition;
 pub mod store;
 
+pub use lint::{
+    CategoryConflict, LintSeverity, PreconditionOverlap, RuleConflict, RuleSetLint,
+    STRONG_NEGATIVE_STRENGTH,
+};
+#[cfg(feature = "serde")]
+pub use pack::RulePack;
+pub use precondition::{AttributePredicate, EdgePattern, Precondition, PreconditionError};
+
 pub type RuleId = Uuid;
 
-#[derive(Clone, Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RuleCategory {
     Structural,
     Performance,
@@ -14,9 +27,8 @@ pub enum RuleCategory {
     ConstraintPropagation,
 }
 
-pub type Precondition = fn(&DesignState) -> bool;
-
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Transformation {
     AddNode,
     RemoveNode,
@@ -26,6 +38,7 @@ pub enum Transformation {
 }
 
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct EffectVector {
     pub delta_struct: f64,
     pub delta_field: f64,
@@ -34,6 +47,7 @@ pub struct EffectVector {
 }
 
 #[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DesignRule {
     pub id: RuleId,
     pub category: RuleCategory,
@@ -60,13 +74,24 @@ impl Shm {
     pub fn applicable_rules(&self, state: &DesignState) -> Vec<&DesignRule> {
         self.rules
             .iter()
-            .filter(|rule| (rule.precondition)(state))
+            .filter(|rule| rule.precondition.evaluate(state))
             .collect()
     }
 
     pub fn rules(&self) -> &[DesignRule] {
         &self.rules
     }
+
+    /// Returns false if no rule has `id`.
+    pub fn set_priority(&mut self, id: RuleId, priority: f64) -> bool {
+        match self.rules.iter_mut().find(|rule| rule.id == id) {
+            Some(rule) => {
+                rule.priority = priority;
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 fn default_rules() -> Vec<DesignRule> {
@@ -75,7 +100,7 @@ fn default_rules() -> Vec<DesignRule> {
             1001,
             RuleCategory::Refactor,
             0.95,
-            precondition_multi_node,
+            precondition_multi_node(),
             Transformation::ModifyAttribute,
             effect(0.8, 0.0, -0.2, 0.1),
         ), // Single Responsibility
@@ -83,7 +108,7 @@ fn default_rules() -> Vec<DesignRule> {
             1002,
             RuleCategory::Structural,
             0.90,
-            precondition_has_edges,
+            precondition_has_edges(),
             Transformation::RewireDependency,
             effect(0.7, 0.0, -0.3, 0.0),
         ), // Reduce Coupling
@@ -91,7 +116,7 @@ fn default_rules() -> Vec<DesignRule> {
             1003,
             RuleCategory::Structural,
             0.86,
-            precondition_depth_at_least_two,
+            precondition_depth_at_least_two(),
             Transformation::AddNode,
             effect(0.6, 0.0, -0.1, 0.1),
         ), // Introduce Layer
@@ -99,7 +124,7 @@ fn default_rules() -> Vec<DesignRule> {
             1004,
             RuleCategory::Performance,
             0.84,
-            precondition_multi_node,
+            precondition_multi_node(),
             Transformation::AddConstraint,
             effect(0.4, 0.5, 0.0, 0.2),
         ), // Introduce Caching (abstract)
@@ -107,7 +132,7 @@ fn default_rules() -> Vec<DesignRule> {
             1005,
             RuleCategory::Reliability,
             0.82,
-            precondition_has_leaf_node,
+            precondition_has_leaf_node(),
             Transformation::AddNode,
             effect(0.3, 0.0, -0.6, 0.3),
         ), // Add Redundancy
@@ -115,7 +140,7 @@ fn default_rules() -> Vec<DesignRule> {
             1006,
             RuleCategory::Refactor,
             0.88,
-            precondition_large_node,
+            precondition_large_node(),
             Transformation::AddNode,
             effect(0.5, 0.0, -0.2, 0.1),
         ), // Split Node
@@ -123,7 +148,7 @@ fn default_rules() -> Vec<DesignRule> {
             1007,
             RuleCategory::Refactor,
             0.70,
-            precondition_multi_node,
+            precondition_multi_node(),
             Transformation::RemoveNode,
             effect(0.4, 0.0, 0.1, -0.4),
         ), // Merge Node
@@ -131,7 +156,7 @@ fn default_rules() -> Vec<DesignRule> {
             1008,
             RuleCategory::ConstraintPropagation,
             0.78,
-            precondition_depth_over_three,
+            precondition_depth_over_three(),
             Transformation::AddConstraint,
             effect(0.6, 0.0, -0.2, 0.0),
         ), // Limit Depth
@@ -139,7 +164,7 @@ fn default_rules() -> Vec<DesignRule> {
             1009,
             RuleCategory::Structural,
             0.92,
-            precondition_high_edge_density,
+            precondition_high_edge_density(),
             Transformation::RewireDependency,
             effect(0.7, 0.0, -0.4, 0.0),
         ), // Remove Cycle (preventive in DAG model)
@@ -147,7 +172,7 @@ fn default_rules() -> Vec<DesignRule> {
             1010,
             RuleCategory::ConstraintPropagation,
             0.80,
-            precondition_multi_node,
+            precondition_multi_node(),
             Transformation::AddConstraint,
             effect(0.5, 0.0, -0.2, 0.0),
         ), // Add Constraint
@@ -155,7 +180,7 @@ fn default_rules() -> Vec<DesignRule> {
             1011,
             RuleCategory::Refactor,
             0.85,
-            precondition_high_edge_density,
+            precondition_high_edge_density(),
             Transformation::ModifyAttribute,
             effect(0.7, 0.0, -0.2, 0.0),
         ), // Reduce Complexity
@@ -163,7 +188,7 @@ fn default_rules() -> Vec<DesignRule> {
             1012,
             RuleCategory::Structural,
             0.83,
-            precondition_multi_node,
+            precondition_multi_node(),
             Transformation::AddNode,
             effect(0.5, 0.0, -0.2, 0.1),
         ), // Introduce Interface
@@ -171,7 +196,7 @@ fn default_rules() -> Vec<DesignRule> {
             1013,
             RuleCategory::Reliability,
             0.79,
-            precondition_has_edges,
+            precondition_has_edges(),
             Transformation::AddConstraint,
             effect(0.2, 0.0, -0.5, 0.1),
         ), // Introduce Timeout
@@ -179,7 +204,7 @@ fn default_rules() -> Vec<DesignRule> {
             1014,
             RuleCategory::Reliability,
             0.87,
-            precondition_has_leaf_node,
+            precondition_has_leaf_node(),
             Transformation::AddConstraint,
             effect(0.3, 0.0, -0.6, 0.2),
         ), // Fail Safe
@@ -187,7 +212,7 @@ fn default_rules() -> Vec<DesignRule> {
             1015,
             RuleCategory::Refactor,
             0.81,
-            precondition_multi_node,
+            precondition_multi_node(),
             Transformation::ModifyAttribute,
             effect(0.6, 0.0, -0.3, 0.0),
         ), // Partition Responsibility
@@ -195,7 +220,7 @@ fn default_rules() -> Vec<DesignRule> {
             1016,
             RuleCategory::Structural,
             0.77,
-            precondition_has_edges,
+            precondition_has_edges(),
             Transformation::RewireDependency,
             effect(0.6, 0.0, -0.2, 0.0),
         ), // Abstract Dependency
@@ -203,7 +228,7 @@ fn default_rules() -> Vec<DesignRule> {
             1017,
             RuleCategory::Cost,
             0.76,
-            precondition_resource_heavy,
+            precondition_resource_heavy(),
             Transformation::AddConstraint,
             effect(0.1, 0.0, 0.0, -0.7),
         ), // Resource Cap
@@ -211,7 +236,7 @@ fn default_rules() -> Vec<DesignRule> {
             1018,
             RuleCategory::Structural,
             0.75,
-            precondition_high_fanout,
+            precondition_high_fanout(),
             Transformation::RewireDependency,
             effect(0.6, 0.0, -0.2, -0.1),
         ), // Minimize Dependency Fanout
@@ -219,7 +244,7 @@ fn default_rules() -> Vec<DesignRule> {
             1019,
             RuleCategory::Refactor,
             0.74,
-            precondition_multi_node,
+            precondition_multi_node(),
             Transformation::RemoveNode,
             effect(0.5, 0.0, -0.1, -0.2),
         ), // Consolidate Nodes
@@ -227,7 +252,7 @@ fn default_rules() -> Vec<DesignRule> {
             1020,
             RuleCategory::Refactor,
             0.89,
-            precondition_large_node,
+            precondition_large_node(),
             Transformation::ModifyAttribute,
             effect(0.8, 0.0, -0.2, -0.1),
         ), // Simplify Structure
@@ -261,69 +286,40 @@ fn effect(delta_struct: f64, delta_field: f64, delta_risk: f64, delta_cost: f64)
     }
 }
 
-fn node_count(state: &DesignState) -> usize {
-    state.graph.nodes().len()
-}
-
-fn edge_count(state: &DesignState) -> usize {
-    state.graph.edges().len()
+fn precondition_multi_node() -> Precondition {
+    Precondition::NodeCount { min: 2, max: None }
 }
 
-fn precondition_multi_node(state: &DesignState) -> bool {
-    node_count(state) >= 2
+fn precondition_has_edges() -> Precondition {
+    Precondition::EdgeCount { min: 1, max: None }
 }
 
-fn precondition_has_edges(state: &DesignState) -> bool {
-    edge_count(state) > 0
-}
-
-fn precondition_has_leaf_node(state: &DesignState) -> bool {
-    let mut outgoing = std::collections::BTreeMap::new();
-    for (from, _) in state.graph.edges() {
-        *outgoing.entry(*from).or_insert(0usize) += 1;
-    }
-
-    state
-        .graph
-        .nodes()
-        .keys()
-        .any(|id| !outgoing.contains_key(id))
+fn precondition_has_leaf_node() -> Precondition {
+    Precondition::HasEdgePattern(EdgePattern::Leaf)
 }
 
-fn precondition_large_node(state: &DesignState) -> bool {
-    state
-        .graph
-        .nodes()
-        .values()
-        .any(|node| node.attributes.len() >= 3)
+fn precondition_large_node() -> Precondition {
+    Precondition::AnyNode(AttributePredicate::AttributeCountAtLeast(3))
 }
 
-fn precondition_depth_at_least_two(state: &DesignState) -> bool {
-    edge_count(state) >= 2
+fn precondition_depth_at_least_two() -> Precondition {
+    Precondition::EdgeCount { min: 2, max: None }
 }
 
-fn precondition_depth_over_three(state: &DesignState) -> bool {
-    edge_count(state) >= 4
+fn precondition_depth_over_three() -> Precondition {
+    Precondition::EdgeCount { min: 4, max: None }
 }
 
-fn precondition_high_edge_density(state: &DesignState) -> bool {
-    let n = node_count(state);
-    if n <= 1 {
-        return false;
-    }
-    edge_count(state) > n
+fn precondition_high_edge_density() -> Precondition {
+    Precondition::NodeCount { min: 2, max: None }.and(Precondition::EdgesPerNodeAbove(1.0))
 }
 
-fn precondition_resource_heavy(state: &DesignState) -> bool {
-    node_count(state) >= 4
+fn precondition_resource_heavy() -> Precondition {
+    Precondition::NodeCount { min: 4, max: None }
 }
 
-fn precondition_high_fanout(state: &DesignState) -> bool {
-    let mut outgoing = std::collections::BTreeMap::new();
-    for (from, _) in state.graph.edges() {
-        *outgoing.entry(*from).or_insert(0usize) += 1;
-    }
-    outgoing.values().any(|count| *count >= 2)
+fn precondition_high_fanout() -> Precondition {
+    Precondition::HasEdgePattern(EdgePattern::FanOutAtLeast(2))
 }
 
 #[cfg(test)]
@@ -333,7 +329,7 @@ mod tests {
 
     use memory_space::{DesignNode, DesignState, StructuralGraph, Uuid, Value};
 
-    use crate::{RuleId, Shm};
+    use crate::{AttributePredicate, EdgePattern, Precondition, RuleId, Shm};
 
     fn state_with_graph(node_specs: &[(u128, usize)], edges: &[(u128, u128)]) -> DesignState {
         let mut graph = StructuralGraph::default();
@@ -386,7 +382,7 @@ mod tests {
         assert!(
             applicable
                 .iter()
-                .all(|rule| (rule.precondition)(&connected))
+                .all(|rule| rule.precondition.evaluate(&connected))
         );
         assert!(shm.rules().len() >= 20);
     }
@@ -409,4 +405,99 @@ mod tests {
 
         assert_eq!(first, second);
     }
+
+    #[test]
+    fn precondition_dsl_combinators_and_history() {
+        let mut state = state_with_graph(&[(1, 3), (2, 0), (3, 0)], &[(1, 2), (1, 3)]);
+        state.profile_snapshot = "history:1001,1004".to_string();
+
+        let guard = Precondition::NodeCount {
+            min: 2,
+            max: Some(3),
+        }
+        .and(Precondition::HasEdgePattern(EdgePattern::FanOutAtLeast(2)))
+        .and(Precondition::HistoryContains(RuleId::from_u128(1004)));
+        assert!(guard.evaluate(&state));
+        assert!(!guard.clone().negate().evaluate(&state));
+        assert!(
+            !Precondition::HistoryContains(RuleId::from_u128(1002))
+                .or(Precondition::AnyNode(AttributePredicate::Present(
+                    "missing".into()
+                )))
+                .evaluate(&state)
+        );
+        assert!(
+            Precondition::AnyNode(AttributePredicate::InRange {
+                key: "a2".into(),
+                min: Some(2.0),
+                max: None,
+            })
+            .evaluate(&state)
+        );
+
+        let custom = Precondition::Custom(|s| s.graph.nodes().len() == 3);
+        assert!(custom.evaluate(&state));
+        assert!(!guard.and(custom).is_declarative());
+        assert!(
+            Shm::with_default_rules()
+                .rules()
+                .iter()
+                .all(|rule| rule.precondition.is_declarative())
+        );
+    }
+
+    #[test]
+    fn precondition_validate_rejects_unsatisfiable_guards() {
+        use crate::PreconditionError;
+
+        assert!(
+            Shm::with_default_rules()
+                .rules()
+                .iter()
+                .all(|rule| rule.precondition.validate().is_ok())
+        );
+        assert_eq!(
+            Precondition::NodeCount {
+                min: 4,
+                max: Some(2)
+            }
+            .validate(),
+            Err(PreconditionError::EmptyRange)
+        );
+        assert_eq!(
+            Precondition::Always
+                .and(Precondition::EdgesPerNodeAbove(f64::NAN))
+                .validate(),
+            Err(PreconditionError::NonFinite)
+        );
+        assert_eq!(
+            Precondition::Or(Vec::new()).negate().validate(),
+            Err(PreconditionError::EmptyCombinator)
+        );
+        assert_eq!(
+            Precondition::Custom(|_| true).validate(),
+            Err(PreconditionError::NotDeclarative)
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn default_rules_round_trip_through_json() {
+        let shm = Shm::with_default_rules();
+        let json = serde_json::to_string(shm.rules()).expect("serialize");
+        let decoded: Vec<crate::DesignRule> = serde_json::from_str(&json).expect("deserialize");
+        let restored = Shm::new(decoded);
+
+        let state = state_with_graph(&[(1, 3), (2, 0), (3, 0)], &[(1, 2), (1, 3)]);
+        let ids = |shm: &Shm| -> Vec<RuleId> {
+            shm.applicable_rules(&state)
+                .iter()
+                .map(|rule| rule.id)
+                .collect()
+        };
+        assert_eq!(ids(&shm), ids(&restored));
+
+        let custom = Precondition::Custom(|_| true);
+        assert!(serde_json::to_string(&custom).is_err());
+    }
 }
@@ -0,0 +1,476 @@
+//! Static conflict analysis of a rule set.
+//!
+//! Two rules conflict when their transformations undo each other and their
+//! preconditions can hold on the same state, so the beam keeps toggling
+//! between them. CHM edges with negative strength, learned from earlier
+//! runs, are treated as independent evidence of the same kind of fight.
+
+use std::cmp::Reverse;
+use std::collections::BTreeMap;
+
+use chm::Chm;
+
+use crate::precondition::{EdgePattern, Precondition};
+use crate::{DesignRule, RuleCategory, RuleId, Shm, Transformation};
+
+/// CHM strength at or below which a negative edge alone warrants a warning.
+pub const STRONG_NEGATIVE_STRENGTH: f64 = -0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LintSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// How often two preconditions hold together, judged from node/edge count
+/// bounds. `Possible` is the conservative answer for guards the analysis
+/// cannot bound (attribute predicates, history, `Not`, `Custom`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PreconditionOverlap {
+    /// Never on the same state.
+    Disjoint,
+    Possible,
+    /// Whenever the narrower guard holds, so does the other.
+    Subsumed,
+    /// Same guard: both rules fire on every state either fires on.
+    Identical,
+}
+
+impl PreconditionOverlap {
+    pub fn always_cofires(self) -> bool {
+        matches!(self, Self::Subsumed | Self::Identical)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuleConflict {
+    pub rules: (RuleId, RuleId),
+    pub categories: (RuleCategory, RuleCategory),
+    pub transformations: (Transformation, Transformation),
+    /// Whether the transformations undo each other.
+    pub opposing: bool,
+    pub overlap: PreconditionOverlap,
+    /// Most negative CHM strength between the two rules, either direction.
+    pub chm_strength: Option<f64>,
+    pub severity: LintSeverity,
+    pub message: String,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CategoryConflict {
+    /// Ordered so `(a, b)` and `(b, a)` share one entry.
+    pub categories: (RuleCategory, RuleCategory),
+    pub conflicts: usize,
+    pub worst: LintSeverity,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RuleSetLint {
+    /// Most severe first, then in rule-set order.
+    pub conflicts: Vec<RuleConflict>,
+    pub category_pairs: Vec<CategoryConflict>,
+}
+
+impl RuleSetLint {
+    pub fn analyze(rules: &[DesignRule], chm: &Chm) -> Self {
+        let mut conflicts = Vec::new();
+        for (i, left) in rules.iter().enumerate() {
+            for right in &rules[i + 1..] {
+                if let Some(conflict) = pair_conflict(left, right, chm) {
+                    conflicts.push(conflict);
+                }
+            }
+        }
+        conflicts.sort_by_key(|c| Reverse(c.severity));
+
+        let mut by_category: BTreeMap<(RuleCategory, RuleCategory), (usize, LintSeverity)> =
+            BTreeMap::new();
+        for conflict in &conflicts {
+            let (a, b) = conflict.categories.clone();
+            let key = if a <= b { (a, b) } else { (b, a) };
+            let entry = by_category.entry(key).or_insert((0, LintSeverity::Info));
+            entry.0 += 1;
+            entry.1 = entry.1.max(conflict.severity);
+        }
+        let mut category_pairs = by_category
+            .into_iter()
+            .map(|(categories, (conflicts, worst))| CategoryConflict {
+                categories,
+                conflicts,
+                worst,
+            })
+            .collect::<Vec<_>>();
+        category_pairs.sort_by_key(|p| Reverse((p.worst, p.conflicts)));
+
+        Self {
+            conflicts,
+            category_pairs,
+        }
+    }
+
+    pub fn max_severity(&self) -> Option<LintSeverity> {
+        self.conflicts.iter().map(|c| c.severity).max()
+    }
+
+    pub fn at_least(&self, severity: LintSeverity) -> impl Iterator<Item = &RuleConflict> {
+        self.conflicts
+            .iter()
+            .filter(move |c| c.severity >= severity)
+    }
+
+    pub fn is_clean(&self) -> bool {
+        self.conflicts.is_empty()
+    }
+}
+
+impl Shm {
+    pub fn lint(&self, chm: &Chm) -> RuleSetLint {
+        RuleSetLint::analyze(self.rules(), chm)
+    }
+}
+
+fn pair_conflict(left: &DesignRule, right: &DesignRule, chm: &Chm) -> Option<RuleConflict> {
+    let overlap = overlap(&left.precondition, &right.precondition);
+    if overlap == PreconditionOverlap::Disjoint {
+        return None;
+    }
+    let opposing = opposing(&left.transformation, &right.transformation);
+    let chm_strength = [
+        chm.strength(left.id, right.id),
+        chm.strength(right.id, left.id),
+    ]
+    .into_iter()
+    .flatten()
+    .filter(|s| *s < 0.0)
+    .min_by(f64::total_cmp);
+
+    let severity = match (opposing, overlap.always_cofires(), chm_strength) {
+        (true, true, Some(_)) => LintSeverity::Error,
+        (true, true, None) | (true, false, Some(_)) => LintSeverity::Warning,
+        (true, false, None) => LintSeverity::Info,
+        (false, cofires, Some(s)) if cofires && s <= STRONG_NEGATIVE_STRENGTH => {
+            LintSeverity::Warning
+        }
+        (false, _, Some(_)) => LintSeverity::Info,
+        (false, _, None) => return None,
+    };
+
+    let mut message = format!(
+        "{:?} ({:?}) and {:?} ({:?})",
+        left.transformation, left.category, right.transformation, right.category
+    );
+    if opposing {
+        message.push_str(" undo each other");
+    }
+    message.push_str(match overlap {
+        PreconditionOverlap::Identical => " under the same precondition",
+        PreconditionOverlap::Subsumed => " whenever the narrower precondition holds",
+        _ => " when both preconditions hold",
+    });
+    if let Some(strength) = chm_strength {
+        message.push_str(&format!("; CHM records a negative link ({strength:.2})"));
+    }
+
+    Some(RuleConflict {
+        rules: (left.id, right.id),
+        categories: (left.category.clone(), right.category.clone()),
+        transformations: (left.transformation.clone(), right.transformation.clone()),
+        opposing,
+        overlap,
+        chm_strength,
+        severity,
+        message,
+    })
+}
+
+/// Pairs where one transformation removes what the other builds on.
+fn opposing(a: &Transformation, b: &Transformation) -> bool {
+    use Transformation::*;
+    matches!(
+        (a, b),
+        (AddNode, RemoveNode)
+            | (RemoveNode, AddNode)
+            | (AddConstraint, RemoveNode)
+            | (RemoveNode, AddConstraint)
+    )
+}
+
+fn overlap(a: &Precondition, b: &Precondition) -> PreconditionOverlap {
+    if same(a, b) {
+        return PreconditionOverlap::Identical;
+    }
+    let (ba, bb) = (Bounds::of(a), Bounds::of(b));
+    if ba.intersect(&bb).is_empty() {
+        return PreconditionOverlap::Disjoint;
+    }
+    let implies =
+        |narrow: &Bounds, wide: &Bounds, wide_exact: bool| wide_exact && narrow.within(wide);
+    if implies(&ba, &bb, is_count_only(b)) || implies(&bb, &ba, is_count_only(a)) {
+        PreconditionOverlap::Subsumed
+    } else {
+        PreconditionOverlap::Possible
+    }
+}
+
+/// Structural equality; `Custom` guards are never considered equal.
+fn same(a: &Precondition, b: &Precondition) -> bool {
+    use Precondition::*;
+    match (a, b) {
+        (Always, Always) => true,
+        (NodeCount { min: a, max: x }, NodeCount { min: b, max: y })
+        | (EdgeCount { min: a, max: x }, EdgeCount { min: b, max: y }) => a == b && x == y,
+        (EdgesPerNodeAbove(a), EdgesPerNodeAbove(b)) => a == b,
+        (AnyNode(a), AnyNode(b)) => a == b,
+        (HasEdgePattern(a), HasEdgePattern(b)) => a == b,
+        (HistoryContains(a), HistoryContains(b)) => a == b,
+        (And(a), And(b)) | (Or(a), Or(b)) => {
+            a.len() == b.len() && a.iter().zip(b).all(|(x, y)| same(x, y))
+        }
+        (Not(a), Not(b)) => same(a, b),
+        _ => false,
+    }
+}
+
+/// True when `Bounds::of` describes the guard exactly rather than
+/// over-approximating it.
+fn is_count_only(precondition: &Precondition) -> bool {
+    match precondition {
+        Precondition::Always | Precondition::NodeCount { .. } | Precondition::EdgeCount { .. } => {
+            true
+        }
+        Precondition::And(all) => all.iter().all(is_count_only),
+        _ => false,
+    }
+}
+
+/// Inclusive node/edge count ranges a guard can hold in; `None` is unbounded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Bounds {
+    nodes: (usize, Option<usize>),
+    edges: (usize, Option<usize>),
+}
+
+impl Bounds {
+    const ANY: Self = Self {
+        nodes: (0, None),
+        edges: (0, None),
+    };
+
+    fn of(precondition: &Precondition) -> Self {
+        let at_least = |nodes: usize, edges: usize| Self {
+            nodes: (nodes, None),
+            edges: (edges, None),
+        };
+        match precondition {
+            Precondition::NodeCount { min, max } => Self {
+                nodes: (*min, *max),
+                ..Self::ANY
+            },
+            Precondition::EdgeCount { min, max } => Self {
+                edges: (*min, *max),
+                ..Self::ANY
+            },
+            Precondition::EdgesPerNodeAbove(ratio) if *ratio >= 0.0 => at_least(2, 1),
+            Precondition::AnyNode(_) => at_least(1, 0),
+            Precondition::HasEdgePattern(pattern) => match pattern {
+                EdgePattern::Leaf => at_least(1, 0),
+                EdgePattern::FanOutAtLeast(n) | EdgePattern::FanInAtLeast(n) => at_least(n + 1, *n),
+                EdgePattern::Between { .. } => at_least(2, 1),
+            },
+            Precondition::And(all) => all
+                .iter()
+                .fold(Self::ANY, |acc, p| acc.intersect(&Self::of(p))),
+            Precondition::Or(any) => any
+                .iter()
+                .map(Self::of)
+                .reduce(|acc, b| acc.hull(&b))
+                .unwrap_or(Self::ANY),
+            _ => Self::ANY,
+        }
+        .normalized()
+    }
+
+    /// A graph with edges has at least two nodes, since self loops are
+    /// rejected.
+    fn normalized(mut self) -> Self {
+        if self.edges.0 > 0 {
+            self.nodes.0 = self.nodes.0.max(2);
+        }
+        if self.nodes.1.is_some_and(|max| max < 2) {
+            self.edges.1 = Some(0);
+        }
+        self
+    }
+
+    fn intersect(&self, other: &Self) -> Self {
+        Self {
+            nodes: range_intersect(self.nodes, other.nodes),
+            edges: range_intersect(self.edges, other.edges),
+        }
+        .normalized()
+    }
+
+    fn hull(&self, other: &Self) -> Self {
+        let hull = |(a, x): (usize, Option<usize>), (b, y): (usize, Option<usize>)| {
+            (a.min(b), x.zip(y).map(|(x, y)| x.max(y)))
+        };
+        Self {
+            nodes: hull(self.nodes, other.nodes),
+            edges: hull(self.edges, other.edges),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        let empty = |(min, max): (usize, Option<usize>)| max.is_some_and(|max| max < min);
+        empty(self.nodes) || empty(self.edges)
+    }
+
+    fn within(&self, other: &Self) -> bool {
+        let inside = |(a, x): (usize, Option<usize>), (b, y): (usize, Option<usize>)| {
+            a >= b
+                && match (x, y) {
+                    (_, None) => true,
+                    (Some(x), Some(y)) => x <= y,
+                    (None, Some(_)) => false,
+                }
+        };
+        inside(self.nodes, other.nodes) && inside(self.edges, other.edges)
+    }
+}
+
+fn range_intersect(
+    (a, x): (usize, Option<usize>),
+    (b, y): (usize, Option<usize>),
+) -> (usize, Option<usize>) {
+    let max = match (x, y) {
+        (Some(x), Some(y)) => Some(x.min(y)),
+        (x, y) => x.or(y),
+    };
+    (a.max(b), max)
+}
+
+#[cfg(test)]
+mod tests {
+    use chm::Chm;
+
+    use super::{LintSeverity, PreconditionOverlap, RuleSetLint, overlap};
+    use crate::{
+        AttributePredicate, DesignRule, EdgePattern, EffectVector, Precondition, RuleCategory,
+        RuleId, Shm, Transformation,
+    };
+
+    fn rule(
+        id: u128,
+        category: RuleCategory,
+        precondition: Precondition,
+        transformation: Transformation,
+    ) -> DesignRule {
+        DesignRule {
+            id: RuleId::from_u128(id),
+            category,
+            priority: 0.5,
+            precondition,
+            transformation,
+            expected_effect: EffectVector {
+                delta_struct: 0.0,
+                delta_field: 0.0,
+                delta_risk: 0.0,
+                delta_cost: 0.0,
+            },
+        }
+    }
+
+    #[test]
+    fn overlap_uses_count_bounds_and_structure() {
+        let multi = Precondition::NodeCount { min: 2, max: None };
+        let tiny = Precondition::NodeCount {
+            min: 0,
+            max: Some(1),
+        };
+        let fan_out = Precondition::HasEdgePattern(EdgePattern::FanOutAtLeast(2));
+        let attr = Precondition::AnyNode(AttributePredicate::Present("cache".into()));
+
+        assert_eq!(overlap(&multi, &multi), PreconditionOverlap::Identical);
+        assert_eq!(overlap(&multi, &tiny), PreconditionOverlap::Disjoint);
+        // Fan-out needs three nodes, so it implies the exact multi-node guard.
+        assert_eq!(overlap(&fan_out, &multi), PreconditionOverlap::Subsumed);
+        assert_eq!(overlap(&fan_out, &tiny), PreconditionOverlap::Disjoint);
+        assert_eq!(overlap(&attr, &multi), PreconditionOverlap::Possible);
+    }
+
+    #[test]
+    fn opposing_rules_with_negative_chm_edge_are_errors() {
+        let multi = Precondition::NodeCount { min: 2, max: None };
+        let rules = vec![
+            rule(
+                1,
+                RuleCategory::ConstraintPropagation,
+                multi.clone(),
+                Transformation::AddConstraint,
+            ),
+            rule(
+                2,
+                RuleCategory::Refactor,
+                multi.clone(),
+                Transformation::RemoveNode,
+            ),
+            rule(
+                3,
+                RuleCategory::Performance,
+                Precondition::NodeCount {
+                    min: 0,
+                    max: Some(1),
+                },
+                Transformation::AddNode,
+            ),
+            rule(
+                4,
+                RuleCategory::Cost,
+                multi,
+                Transformation::ModifyAttribute,
+            ),
+        ];
+        let mut chm = Chm::default();
+        chm.insert_edge(RuleId::from_u128(2), RuleId::from_u128(1), -0.8);
+        chm.insert_edge(RuleId::from_u128(4), RuleId::from_u128(1), -0.2);
+
+        let lint = RuleSetLint::analyze(&rules, &chm);
+
+        // 2/3 oppose but never co-fire, so only two pairs are reported.
+        assert_eq!(lint.conflicts.len(), 2);
+        let worst = &lint.conflicts[0];
+        assert_eq!(worst.rules, (RuleId::from_u128(1), RuleId::from_u128(2)));
+        assert_eq!(worst.severity, LintSeverity::Error);
+        assert_eq!(worst.chm_strength, Some(-0.8));
+        assert!(worst.message.contains("undo each other"));
+        assert_eq!(lint.conflicts[1].severity, LintSeverity::Info);
+        assert!(!lint.conflicts[1].opposing);
+        assert_eq!(lint.max_severity(), Some(LintSeverity::Error));
+        assert_eq!(lint.at_least(LintSeverity::Warning).count(), 1);
+
+        assert_eq!(
+            lint.category_pairs[0].categories,
+            (RuleCategory::Refactor, RuleCategory::ConstraintPropagation)
+        );
+        assert_eq!(lint.category_pairs[0].worst, LintSeverity::Error);
+    }
+
+    #[test]
+    fn default_rules_report_merge_node_against_additive_rules() {
+        let lint = Shm::with_default_rules().lint(&Chm::default());
+
+        assert_eq!(lint.max_severity(), Some(LintSeverity::Warning));
+        let merge = RuleId::from_u128(1007);
+        let caching = RuleId::from_u128(1004);
+        assert!(lint.at_least(LintSeverity::Warning).any(|c| {
+            (c.rules == (caching, merge) || c.rules == (merge, caching))
+                && c.overlap == PreconditionOverlap::Identical
+        }));
+    }
+}
@@ -0,0 +1,83 @@
+//! On-disk rule packs: a named rule set in a `Versioned` JSON envelope.
+
+use std::io;
+use std::path::Path;
+
+use core_types::{SchemaVersioned, Versioned};
+
+use crate::{DesignRule, Shm};
+
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct RulePack {
+    pub name: String,
+    pub rules: Vec<DesignRule>,
+}
+
+impl SchemaVersioned for RulePack {
+    const KIND: &'static str = "rule_pack";
+    const VERSION: u32 = 1;
+}
+
+impl RulePack {
+    pub fn new(name: impl Into<String>, rules: Vec<DesignRule>) -> Self {
+        Self {
+            name: name.into(),
+            rules,
+        }
+    }
+
+    /// Fails with `InvalidData` on malformed JSON, a kind/version mismatch,
+    /// or a rule whose precondition is `Custom`.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let raw = std::fs::read_to_string(path)?;
+        let envelope: Versioned<Self> = serde_json::from_str(&raw).map_err(invalid_data)?;
+        envelope.into_checked().map_err(invalid_data)
+    }
+
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json =
+            serde_json::to_string_pretty(&Versioned::new(self.clone())).map_err(invalid_data)?;
+        std::fs::write(path, json)
+    }
+}
+
+impl From<RulePack> for Shm {
+    fn from(pack: RulePack) -> Self {
+        Shm::new(pack.rules)
+    }
+}
+
+fn invalid_data(err: impl std::error::Error + Send + Sync + 'static) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RulePack;
+    use crate::{Precondition, Shm};
+
+    #[test]
+    fn rule_pack_save_load_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("shm_pack_{}", std::process::id()));
+        let path = dir.join("default.json");
+        let pack = RulePack::new("default", Shm::with_default_rules().rules().to_vec());
+        pack.save(&path).expect("save");
+
+        let loaded = RulePack::load(&path).expect("load");
+        assert_eq!(loaded.name, "default");
+        assert_eq!(loaded.rules.len(), pack.rules.len());
+        assert_eq!(
+            loaded.rules[0].expected_effect,
+            pack.rules[0].expected_effect
+        );
+
+        let mut custom = pack.clone();
+        custom.rules[0].precondition = Precondition::Custom(|_| true);
+        assert!(custom.save(&path).is_err());
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::BTreeMap;
+
+use memory_space::{DesignNode, DesignState, Value};
+
+use crate::RuleId;
+
+/// Declarative rule guard. Everything except `Custom` is plain data, so rule
+/// sets can be serialized and authored outside of Rust.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Precondition {
+    Always,
+    /// Inclusive bounds; `max: None` is unbounded.
+    NodeCount {
+        min: usize,
+        max: Option<usize>,
+    },
+    EdgeCount {
+        min: usize,
+        max: Option<usize>,
+    },
+    /// `edges / nodes > ratio`; false on an empty graph.
+    EdgesPerNodeAbove(f64),
+    /// Holds if at least one node satisfies the predicate.
+    AnyNode(AttributePredicate),
+    /// Holds if the graph contains at least one match of the pattern.
+    HasEdgePattern(EdgePattern),
+    /// Checks the `history:<id>,<id>` profile written by rule application.
+    HistoryContains(RuleId),
+    And(Vec<Precondition>),
+    Or(Vec<Precondition>),
+    Not(Box<Precondition>),
+    /// Escape hatch for guards the DSL cannot express. Not serializable.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    Custom(fn(&DesignState) -> bool),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AttributePredicate {
+    Present(String),
+    Equals(String, Value),
+    /// Numeric attribute (`Int` or `Float`) inside the inclusive range.
+    InRange {
+        key: String,
+        min: Option<f64>,
+        max: Option<f64>,
+    },
+    AttributeCountAtLeast(usize),
+    KindIs(String),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum EdgePattern {
+    /// A node without outgoing edges.
+    Leaf,
+    FanOutAtLeast(usize),
+    FanInAtLeast(usize),
+    /// An edge whose endpoints have the given kinds; `None` matches any kind.
+    Between {
+        from_kind: Option<String>,
+        to_kind: Option<String>,
+    },
+}
+
+impl Precondition {
+    pub fn evaluate(&self, state: &DesignState) -> bool {
+        let graph = &state.graph;
+        match self {
+            Self::Always => true,
+            Self::NodeCount { min, max } => within(graph.nodes().len(), *min, *max),
+            Self::EdgeCount { min, max } => within(graph.edges().len(), *min, *max),
+            Self::EdgesPerNodeAbove(ratio) => {
+                let nodes = graph.nodes().len();
+                nodes > 0 && graph.edges().len() as f64 / nodes as f64 > *ratio
+            }
+            Self::AnyNode(predicate) => graph.nodes().values().any(|node| predicate.matches(node)),
+            Self::HasEdgePattern(pattern) => pattern.matches(state),
+            Self::HistoryContains(rule_id) => {
+                rule_history(&state.profile_snapshot).any(|id| id == rule_id.as_u128())
+            }
+            Self::And(all) => all.iter().all(|p| p.evaluate(state)),
+            Self::Or(any) => any.iter().any(|p| p.evaluate(state)),
+            Self::Not(inner) => !inner.evaluate(state),
+            Self::Custom(f) => f(state),
+        }
+    }
+
+    /// True when the tree contains no `Custom` leaf.
+    pub fn is_declarative(&self) -> bool {
+        match self {
+            Self::Custom(_) => false,
+            Self::And(all) | Self::Or(all) => all.iter().all(Self::is_declarative),
+            Self::Not(inner) => inner.is_declarative(),
+            _ => true,
+        }
+    }
+
+    pub fn and(self, other: Precondition) -> Self {
+        match self {
+            Self::And(mut all) => {
+                all.push(other);
+                Self::And(all)
+            }
+            first => Self::And(vec![first, other]),
+        }
+    }
+
+    pub fn or(self, other: Precondition) -> Self {
+        match self {
+            Self::Or(mut any) => {
+                any.push(other);
+                Self::Or(any)
+            }
+            first => Self::Or(vec![first, other]),
+        }
+    }
+
+    pub fn negate(self) -> Self {
+        Self::Not(Box::new(self))
+    }
+
+    /// Checks that the guard is declarative and can be satisfied at all:
+    /// bounds in order, finite thresholds, no empty `And`/`Or`. Meant for
+    /// rules authored outside of Rust before they reach a search.
+    pub fn validate(&self) -> Result<(), PreconditionError> {
+        match self {
+            Self::Always | Self::HistoryContains(_) | Self::HasEdgePattern(_) => Ok(()),
+            Self::NodeCount { min, max } | Self::EdgeCount { min, max } => match max {
+                Some(max) if max < min => Err(PreconditionError::EmptyRange),
+                _ => Ok(()),
+            },
+            Self::EdgesPerNodeAbove(ratio) if !ratio.is_finite() => {
+                Err(PreconditionError::NonFinite)
+            }
+            Self::EdgesPerNodeAbove(_) => Ok(()),
+            Self::AnyNode(AttributePredicate::InRange { min, max, .. }) => {
+                if min.is_some_and(|v| !v.is_finite()) || max.is_some_and(|v| !v.is_finite()) {
+                    return Err(PreconditionError::NonFinite);
+                }
+                match (min, max) {
+                    (Some(lo), Some(hi)) if hi < lo => Err(PreconditionError::EmptyRange),
+                    _ => Ok(()),
+                }
+            }
+            Self::AnyNode(_) => Ok(()),
+            Self::And(all) | Self::Or(all) if all.is_empty() => {
+                Err(PreconditionError::EmptyCombinator)
+            }
+            Self::And(all) | Self::Or(all) => all.iter().try_for_each(Self::validate),
+            Self::Not(inner) => inner.validate(),
+            Self::Custom(_) => Err(PreconditionError::NotDeclarative),
+        }
+    }
+}
+
+/// Why `Precondition::validate` rejected a guard.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PreconditionError {
+    /// Contains a `Custom` leaf.
+    NotDeclarative,
+    /// A `max` below its `min`.
+    EmptyRange,
+    /// A NaN or infinite threshold.
+    NonFinite,
+    /// An `And` or `Or` without operands.
+    EmptyCombinator,
+}
+
+impl std::fmt::Display for PreconditionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            Self::NotDeclarative => "precondition contains a custom guard",
+            Self::EmptyRange => "precondition range has max below min",
+            Self::NonFinite => "precondition threshold is not finite",
+            Self::EmptyCombinator => "precondition combinator has no operands",
+        };
+        f.write_str(message)
+    }
+}
+
+impl std::error::Error for PreconditionError {}
+
+impl From<fn(&DesignState) -> bool> for Precondition {
+    fn from(f: fn(&DesignState) -> bool) -> Self {
+        Self::Custom(f)
+    }
+}
+
+impl AttributePredicate {
+    pub fn matches(&self, node: &DesignNode) -> bool {
+        match self {
+            Self::Present(key) => node.attributes.contains_key(key),
+            Self::Equals(key, value) => node.attributes.get(key) == Some(value),
+            Self::InRange { key, min, max } => {
+                let Some(x) = node.attributes.get(key).and_then(numeric) else {
+                    return false;
+                };
+                min.is_none_or(|lo| x >= lo) && max.is_none_or(|hi| x <= hi)
+            }
+            Self::AttributeCountAtLeast(n) => node.attributes.len() >= *n,
+            Self::KindIs(kind) => node.kind == *kind,
+        }
+    }
+}
+
+impl EdgePattern {
+    pub fn matches(&self, state: &DesignState) -> bool {
+        let graph = &state.graph;
+        match self {
+            Self::Leaf => {
+                let outgoing = degree(graph.edges().iter().map(|(from, _)| *from));
+                graph.nodes().keys().any(|id| !outgoing.contains_key(id))
+            }
+            Self::FanOutAtLeast(n) => degree(graph.edges().iter().map(|(from, _)| *from))
+                .values()
+                .any(|count| count >= n),
+            Self::FanInAtLeast(n) => degree(graph.edges().iter().map(|(_, to)| *to))
+                .values()
+                .any(|count| count >= n),
+            Self::Between { from_kind, to_kind } => {
+                let kind_ok = |id, want: &Option<String>| {
+                    want.as_ref().is_none_or(|kind| {
+                        graph
+                            .nodes()
+                            .get(&id)
+                            .is_some_and(|node| node.kind == *kind)
+                    })
+                };
+                graph
+                    .edges()
+                    .iter()
+                    .any(|(from, to)| kind_ok(*from, from_kind) && kind_ok(*to, to_kind))
+            }
+        }
+    }
+}
+
+fn within(value: usize, min: usize, max: Option<usize>) -> bool {
+    value >= min && max.is_none_or(|max| value <= max)
+}
+
+fn numeric(value: &Value) -> Option<f64> {
+    match value {
+        Value::Int(v) => Some(*v as f64),
+        Value::Float(v) => Some(*v),
+        Value::Bool(_) | Value::Text(_) => None,
+    }
+}
+
+fn degree<K: Ord>(endpoints: impl Iterator<Item = K>) -> BTreeMap<K, usize> {
+    let mut counts = BTreeMap::new();
+    for key in endpoints {
+        *counts.entry(key).or_insert(0usize) += 1;
+    }
+    counts
+}
+
+fn rule_history(snapshot: &str) -> impl Iterator<Item = u128> + '_ {
+    snapshot
+        .strip_prefix("history:")
+        .unwrap_or("")
+        .split(',')
+        .filter_map(|s| s.parse::<u128>().ok())
+}